@@ -0,0 +1,384 @@
+//! Echo Bench - Load against an echo server: throughput runs and soak tests.
+//!
+//! # What This Demonstrates
+//!
+//! 1. **Throughput** - Calls per second from many concurrent callers
+//! 2. **Soak testing** - Hours of steady load with sessions and clients churning
+//! 3. **Leak detection** - Task and memory growth, sampled through the admin `/debug/*` routes
+//!
+//! # Architecture
+//!
+//! ```text
+//! echo-bench --address localhost:50051 --callers 64 --calls 200    → calls/s, errors
+//!
+//! echo-bench --address localhost:50051 soak --duration 4h --admin-url http://localhost:9090
+//!     ├── callers: echo / echo_with_session, a new session every --session-every calls,
+//!     │            a new client (own channel pool) every --reconnect-every calls
+//!     ├── every --sample-every: server  GET /debug/runtime, /debug/tasks, /debug/memory
+//!     │                         client  the same reports of this process
+//!     └── at the end: growth since the baseline (first sample after --warmup)
+//!             beyond --max-task-growth / --max-memory-growth-percent → exit with an error
+//! ```
+//!
+//! The baseline is taken once pools, caches and session stores had time
+//! to reach their steady size - keep `--warmup` above the server's
+//! `--session-idle-secs`, so abandoned sessions are being swept by then.
+//! The end value is the lowest of the last few samples: a passing spike
+//! isn't a leak, a rising floor is.
+//!
+//! Server memory is the jemalloc heap with `--features jemalloc`, its
+//! resident set size otherwise (Linux).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use clap::{Parser, Subcommand};
+use hsu_common::{Error, Result};
+use hyper::client::HttpConnector;
+use hyper::{Client, StatusCode, Uri};
+use tracing::{info, warn};
+
+use echo_api::{grpc_echo_service, GatewayOptions};
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, parse_described, BootstrapArgs, ConfigCheck, ConfigDescription,
+    DiagnosticsSample, Runtimes,
+};
+use echo_contract::{format_duration, parse_duration};
+
+/// Samples at the end of a soak whose minimum is compared to the baseline.
+const TAIL_SAMPLES: usize = 3;
+
+/// Reads one figure of a sample.
+type Figure = fn(&DiagnosticsSample) -> Option<u64>;
+
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Load against an echo server: throughput runs and soak tests")]
+struct Args {
+    /// gRPC address of the echo server
+    #[arg(long, default_value = "localhost:50051")]
+    address: String,
+
+    /// Concurrent callers
+    #[arg(long, default_value_t = 64)]
+    callers: usize,
+
+    /// Calls per caller in a throughput run
+    #[arg(long, default_value_t = 200)]
+    calls: usize,
+
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Keep a steady load for hours and fail if tasks or memory keep
+    /// growing (server through --admin-url, and this client)
+    Soak(SoakArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct SoakArgs {
+    /// How long to run (e.g. 4h)
+    #[arg(long, default_value = "4h", value_parser = duration)]
+    duration: Duration,
+
+    /// How often to sample tasks and memory
+    #[arg(long, default_value = "1m", value_parser = duration)]
+    sample_every: Duration,
+
+    /// Load before the baseline sample
+    #[arg(long, default_value = "10m", value_parser = duration)]
+    warmup: Duration,
+
+    /// The server's admin endpoint (--admin-addr); only this client is checked without it
+    #[arg(long, value_name = "URL")]
+    admin_url: Option<String>,
+
+    /// Fail if a process has this many more tasks than at the baseline
+    #[arg(long, value_name = "N", default_value_t = 16)]
+    max_task_growth: u64,
+
+    /// Fail if a process's memory grew by more than this since the baseline
+    #[arg(long, value_name = "PERCENT", default_value_t = 20.0)]
+    max_memory_growth_percent: f64,
+
+    /// Calls per session before a caller starts a new one
+    #[arg(long, value_name = "N", default_value_t = 100)]
+    session_every: u64,
+
+    /// Calls per client before a caller drops it and connects a new one
+    #[arg(long, value_name = "N", default_value_t = 1000)]
+    reconnect_every: u64,
+
+    /// Pause of each caller between calls, in milliseconds
+    #[arg(long, value_name = "MS", default_value_t = 10)]
+    call_interval_ms: u64,
+}
+
+fn duration(value: &str) -> std::result::Result<Duration, String> {
+    parse_duration(value).map_err(|e| e.to_string())
+}
+
+fn main() -> Result<()> {
+    let (args, description) = parse_described::<Args>();
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    let mut check = ConfigCheck::new();
+    check.require(args.callers > 0, "--callers", || "must be positive".to_string());
+    if let Some(Command::Soak(soak)) = &args.command {
+        check.require(!soak.sample_every.is_zero(), "--sample-every", || "must be positive".to_string());
+        check.require(soak.warmup < soak.duration, "--warmup", || {
+            format!("{} leaves no time after it (--duration {})", format_duration(soak.warmup), format_duration(soak.duration))
+        });
+        check.require(soak.session_every > 0, "--session-every", || "must be positive".to_string());
+        check.require(soak.reconnect_every > 0, "--reconnect-every", || "must be positive".to_string());
+        if let Some(url) = &soak.admin_url {
+            check.require_ok("--admin-url", AdminClient::new(url).map(drop));
+        }
+    }
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+
+    bootstrap(&args.bootstrap)?;
+    announce_startup(&args.bootstrap, &description)?;
+
+    match &args.command {
+        Some(Command::Soak(soak)) => run_soak(&args, soak).await,
+        None => run_throughput(&args).await,
+    }
+}
+
+/// Sends `--callers` x `--calls` echoes as fast as they are answered.
+async fn run_throughput(args: &Args) -> Result<()> {
+    let service = grpc_echo_service(&args.address, &GatewayOptions::default());
+    // Fails fast if the server isn't there, and warms up the channel
+    service.echo("warm-up".into()).await?;
+
+    let errors = Arc::new(AtomicU64::new(0));
+    let started = Instant::now();
+    let callers: Vec<_> = (0..args.callers).map(|caller| {
+        let (service, errors, calls) = (service.clone(), errors.clone(), args.calls);
+        tokio::spawn(async move {
+            for call in 0..calls {
+                if service.echo(format!("bench {}-{}", caller, call).into()).await.is_err() {
+                    errors.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        })
+    }).collect();
+    for caller in callers {
+        let _ = caller.await;
+    }
+    let (calls, elapsed) = (args.callers * args.calls, started.elapsed());
+    info!("[EchoBench] ✅ {} call(s) in {:.1?}: {:.0} calls/s, {} error(s)",
+        calls, elapsed, calls as f64 / elapsed.as_secs_f64(), errors.load(Ordering::Relaxed));
+    Ok(())
+}
+
+/// Calls made and failed by the soak callers.
+#[derive(Default)]
+struct Load {
+    calls: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// Runs the soak load, sampling both processes, and checks their growth.
+async fn run_soak(args: &Args, soak: &SoakArgs) -> Result<()> {
+    let admin = soak.admin_url.as_deref().map(AdminClient::new).transpose()?;
+    if admin.is_none() {
+        warn!("[EchoBench] No --admin-url: only this client is checked for leaks");
+    }
+    info!("[EchoBench] Soaking {} for {} with {} caller(s), baseline after {}",
+        args.address, format_duration(soak.duration), args.callers, format_duration(soak.warmup));
+
+    let load = Arc::new(Load::default());
+    let callers: Vec<_> = (0..args.callers)
+        .map(|caller| tokio::spawn(soak_caller(args.address.clone(), caller, Churn::from(soak), load.clone())))
+        .collect();
+
+    let mut client = Vec::new();
+    let mut server = Vec::new();
+    let started = Instant::now();
+    let mut ticker = tokio::time::interval(soak.sample_every);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => {
+                warn!("[EchoBench] Interrupted after {:.0?}", started.elapsed());
+                break;
+            }
+        }
+        let elapsed = started.elapsed();
+        let local = DiagnosticsSample::local();
+        client.push((elapsed, local));
+        let remote = match &admin {
+            Some(admin) => match admin.sample().await {
+                Ok(sample) => {
+                    server.push((elapsed, sample));
+                    Some(sample)
+                }
+                Err(e) => {
+                    warn!("[EchoBench] Can't sample the server: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        info!("[EchoBench] {:>6.0?} calls={} errors={} | client {} | server {}",
+            elapsed, load.calls.load(Ordering::Relaxed), load.errors.load(Ordering::Relaxed),
+            describe(Some(local)), describe(remote));
+        if elapsed >= soak.duration {
+            break;
+        }
+    }
+    for caller in callers {
+        caller.abort();
+    }
+
+    let mut problems = leaks("client", &client, soak);
+    if admin.is_some() {
+        problems.extend(leaks("server", &server, soak));
+    }
+    if !problems.is_empty() {
+        for problem in &problems {
+            warn!("[EchoBench] ❌ {}", problem);
+        }
+        return Err(Error::Validation { message: format!("Soak test found {} leak(s)", problems.len()) });
+    }
+    info!("[EchoBench] ✅ No leaks: {} call(s), {} error(s) over {:.0?}",
+        load.calls.load(Ordering::Relaxed), load.errors.load(Ordering::Relaxed), started.elapsed());
+    Ok(())
+}
+
+/// How the soak callers churn sessions and clients.
+#[derive(Clone, Copy)]
+struct Churn {
+    session_every: u64,
+    reconnect_every: u64,
+    interval: Duration,
+}
+
+impl From<&SoakArgs> for Churn {
+    fn from(soak: &SoakArgs) -> Self {
+        Self {
+            session_every: soak.session_every,
+            reconnect_every: soak.reconnect_every,
+            interval: Duration::from_millis(soak.call_interval_ms),
+        }
+    }
+}
+
+/// Alternates plain and session echoes until aborted, moving to a new
+/// session and a new client (with its own channel pool) as it goes.
+async fn soak_caller(address: String, caller: usize, churn: Churn, load: Arc<Load>) {
+    let mut service = grpc_echo_service(&address, &GatewayOptions::default());
+    for call in 1u64.. {
+        if call % churn.reconnect_every == 0 {
+            // The previous client and its channels are dropped here
+            service = grpc_echo_service(&address, &GatewayOptions::default());
+        }
+        let message: Arc<str> = format!("soak {}-{}", caller, call).into();
+        let result = if call % 2 == 0 {
+            service.echo(message).await.map(drop)
+        } else {
+            let session = format!("soak-{}-{}", caller, call / churn.session_every);
+            service.echo_with_session(session, message).await.map(drop)
+        };
+        load.calls.fetch_add(1, Ordering::Relaxed);
+        if result.is_err() {
+            load.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        tokio::time::sleep(churn.interval).await;
+    }
+}
+
+/// Problems with the growth of `process` between the baseline and the
+/// end of the run.
+fn leaks(process: &str, samples: &[(Duration, DiagnosticsSample)], soak: &SoakArgs) -> Vec<String> {
+    let Some(start) = samples.iter().position(|(elapsed, _)| *elapsed >= soak.warmup) else {
+        warn!("[EchoBench] {}: the run ended before the warm-up, nothing to compare", process);
+        return Vec::new();
+    };
+    let (baseline, after) = (samples[start].1, &samples[start + 1..]);
+    if after.is_empty() {
+        warn!("[EchoBench] {}: no sample after the baseline, nothing to compare", process);
+        return Vec::new();
+    }
+    let tail = &after[after.len().saturating_sub(TAIL_SAMPLES)..];
+    let lowest = |value: Figure| tail.iter().filter_map(|(_, sample)| value(sample)).min();
+
+    let mut problems = Vec::new();
+    let tasks: [(&str, Figure); 2] = [
+        ("alive tasks", |sample| Some(sample.alive_tasks)),
+        ("tracked tasks", |sample| Some(sample.tracked_tasks)),
+    ];
+    for (name, value) in tasks {
+        let (Some(before), Some(end)) = (value(&baseline), lowest(value)) else { continue };
+        if end.saturating_sub(before) > soak.max_task_growth {
+            problems.push(format!("{}: {} grew from {} to {} (limit +{})",
+                process, name, before, end, soak.max_task_growth));
+        }
+    }
+    match (baseline.memory_bytes, lowest(|sample| sample.memory_bytes)) {
+        (Some(before), Some(end)) if before > 0 => {
+            let growth = (end as f64 - before as f64) * 100.0 / before as f64;
+            if growth > soak.max_memory_growth_percent {
+                problems.push(format!("{}: memory grew {:.1}% from {} to {} bytes (limit {}%)",
+                    process, growth, before, end, soak.max_memory_growth_percent));
+            }
+        }
+        _ => warn!("[EchoBench] {}: no memory figures, memory not checked", process),
+    }
+    problems
+}
+
+/// One sample in a log line.
+fn describe(sample: Option<DiagnosticsSample>) -> String {
+    match sample {
+        Some(sample) => format!("tasks={}/{} memory={}", sample.alive_tasks, sample.tracked_tasks,
+            sample.memory_bytes.map_or_else(|| "?".to_string(), |bytes| format!("{}KiB", bytes / 1024))),
+        None => "-".to_string(),
+    }
+}
+
+/// Reads the diagnostics reports of a server's admin endpoint.
+struct AdminClient {
+    client: Client<HttpConnector>,
+    base_url: String,
+}
+
+impl AdminClient {
+    fn new(url: &str) -> Result<Self> {
+        let base_url = url.trim_end_matches('/').to_string();
+        base_url.parse::<Uri>().map_err(|e| Error::Validation {
+            message: format!("Invalid admin URL '{}': {}", url, e),
+        })?;
+        Ok(Self { client: Client::new(), base_url })
+    }
+
+    async fn sample(&self) -> Result<DiagnosticsSample> {
+        let runtime = self.get("/debug/runtime").await?;
+        let tasks = self.get("/debug/tasks").await?;
+        let memory = self.get("/debug/memory").await?;
+        Ok(DiagnosticsSample::parse(&runtime, &tasks, &memory))
+    }
+
+    async fn get(&self, path: &str) -> Result<String> {
+        let uri: Uri = format!("{}{}", self.base_url, path).parse().map_err(|e| Error::Protocol(format!("{}", e)))?;
+        let response = self.client.get(uri).await.map_err(|e| Error::Protocol(format!("GET {}: {}", path, e)))?;
+        if response.status() != StatusCode::OK {
+            return Err(Error::Protocol(format!("GET {}: HTTP {}", path, response.status())));
+        }
+        let body = hyper::body::to_bytes(response.into_body()).await
+            .map_err(|e| Error::Protocol(format!("GET {}: {}", path, e)))?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
@@ -0,0 +1,215 @@
+//! Echo Diff - Replays a message corpus against two targets and reports divergences.
+//!
+//! # What This Demonstrates
+//!
+//! 1. **Cross-protocol consistency** - Direct, gRPC and JSON/HTTP must answer alike
+//! 2. **Implementation parity** - A Go and a Rust server behind the same contract
+//! 3. **Call observation** - Replies, error kinds and server metadata per call
+//!
+//! # Architecture
+//!
+//! ```text
+//! corpus (capture.ndjson from `echo-grpc-srv --record`, or --messages: one echo per line)
+//!     ↓ in order (session counts depend on it)
+//! echo-diff --left direct --right grpc:localhost:50051
+//!     ├── observe_call(left)  ─┐ concurrently
+//!     ├── observe_call(right) ─┘
+//!     └── diff_observations → reply / outcome / error kind / metadata divergences
+//! ```
+//!
+//! Exits with an error if any call diverges, so it can gate CI.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use clap::Parser;
+use hsu_common::{Error, Result};
+use serde_json::json;
+use tracing::{info, warn};
+
+use echo_api::{
+    diff_observations, observe_call, read_capture, CaptureRecord, DiffOptions, Divergence, Observation,
+};
+use echo_api_grpc::generated::echo_service_client::EchoServiceClient;
+use echo_api_grpc::{ChannelPool, EchoGrpcGateway, EchoJsonGateway};
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, parse_described, BootstrapArgs, ConfigCheck, ConfigDescription,
+    Runtimes,
+};
+use echo_contract::{EchoMethod, EchoService};
+use echo_server::{DecoratedEchoService, EchoServiceImpl, ResponseDecoration};
+
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Replays a corpus against two echo targets and reports divergences")]
+struct Args {
+    /// Capture file written by `echo-grpc-srv --record` (or a message per line with --messages)
+    corpus: PathBuf,
+
+    /// Treat the corpus as plain text: each non-empty line is echoed
+    #[arg(long)]
+    messages: bool,
+
+    /// First target: direct (in-process echo service), grpc:HOST:PORT or http:HOST:PORT (JSON transcoding)
+    #[arg(long, default_value = "direct")]
+    left: String,
+
+    /// Second target, same forms as --left
+    #[arg(long, default_value = "grpc:localhost:50051")]
+    right: String,
+
+    /// Don't compare this response metadata key (repeatable; instance-id and request-id never are)
+    #[arg(long, value_name = "KEY")]
+    ignore_metadata: Vec<String>,
+
+    /// Print each divergent call as a JSON line on stdout
+    #[arg(long)]
+    json: bool,
+
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+}
+
+fn main() -> Result<()> {
+    let (args, description) = parse_described::<Args>();
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    let mut check = ConfigCheck::new();
+    check.require_ok("--left", connect(&args.left).map(drop));
+    check.require_ok("--right", connect(&args.right).map(drop));
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+
+    bootstrap(&args.bootstrap)?;
+    announce_startup(&args.bootstrap, &description)?;
+
+    let records = if args.messages { read_messages(&args.corpus)? } else { read_capture(&args.corpus)? };
+    let (left, right) = (connect(&args.left)?, connect(&args.right)?);
+    let options = args.ignore_metadata.iter().fold(DiffOptions::default(), |options, key| {
+        options.with_ignored_metadata(key.as_str())
+    });
+    info!("[EchoDiff] Replaying {} call(s) from {} against {} and {}",
+        records.len(), args.corpus.display(), args.left, args.right);
+
+    let mut report = Report::default();
+    for (index, record) in records.iter().enumerate() {
+        let (left, right) = tokio::join!(observe_call(&*left, record), observe_call(&*right, record));
+        let (Some(left), Some(right)) = (left, right) else {
+            report.skipped += 1;
+            continue;
+        };
+        report.add(&left, &right);
+
+        let divergences = diff_observations(&left, &right, &options);
+        if divergences.is_empty() {
+            continue;
+        }
+        report.divergent += 1;
+        if args.json {
+            println!("{}", divergence_json(index, record, &left, &right, &divergences));
+        }
+        for divergence in &divergences {
+            warn!("[EchoDiff] Call #{} ({}): {}", index + 1, record.method, divergence);
+        }
+    }
+    report.log();
+
+    if report.divergent > 0 {
+        return Err(Error::Validation {
+            message: format!("{} of {} call(s) differ between {} and {}",
+                report.divergent, report.calls, args.left, args.right),
+        });
+    }
+    Ok(())
+}
+
+/// Reads a plain-text corpus: one `echo` call per non-empty line.
+fn read_messages(path: &Path) -> Result<Vec<CaptureRecord>> {
+    let text = std::fs::read_to_string(path).map_err(|e| Error::Validation {
+        message: format!("Corpus {}: {}", path.display(), e),
+    })?;
+    Ok(text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| CaptureRecord {
+            offset: Duration::ZERO,
+            method: EchoMethod::Echo,
+            request: json!({ "message": line }),
+            response: None,
+            error: None,
+            latency: Duration::ZERO,
+        })
+        .collect())
+}
+
+/// Creates a target from its `direct` / `grpc:HOST:PORT` / `http:HOST:PORT` form.
+fn connect(target: &str) -> Result<Arc<dyn EchoService>> {
+    if target == "direct" {
+        // Same metadata as a server (server-version), so only real differences show
+        let service = Arc::new(EchoServiceImpl::new());
+        return Ok(Arc::new(DecoratedEchoService::new(service, ResponseDecoration::instance())));
+    }
+    match target.split_once(':') {
+        Some(("grpc", address)) => {
+            let channel = ChannelPool::default().channel(address)?;
+            Ok(Arc::new(EchoGrpcGateway::from_client(EchoServiceClient::new(channel))))
+        }
+        Some(("http", address)) => Ok(Arc::new(EchoJsonGateway::new(address))),
+        _ => Err(Error::Validation {
+            message: format!("Unknown target '{}' (expected direct, grpc:HOST:PORT or http:HOST:PORT)", target),
+        }),
+    }
+}
+
+/// One line of `--json` output.
+fn divergence_json(
+    index: usize,
+    record: &CaptureRecord,
+    left: &Observation,
+    right: &Observation,
+    divergences: &[Divergence],
+) -> serde_json::Value {
+    let side = |observation: &Observation| json!({
+        "latency_ms": observation.latency.as_secs_f64() * 1000.0,
+        "attempts": observation.attempts,
+        "server": &observation.server_metadata,
+    });
+    json!({
+        "call": index + 1,
+        "method": record.method.as_str(),
+        "request": &record.request,
+        "divergences": divergences.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        "left": side(left),
+        "right": side(right),
+    })
+}
+
+/// Diff outcome.
+#[derive(Default)]
+struct Report {
+    calls: usize,
+    divergent: usize,
+    skipped: usize,
+    latency: (Duration, Duration),
+    attempts: (u64, u64),
+}
+
+impl Report {
+    fn add(&mut self, left: &Observation, right: &Observation) {
+        self.calls += 1;
+        self.latency.0 += left.latency;
+        self.latency.1 += right.latency;
+        self.attempts.0 += u64::from(left.attempts);
+        self.attempts.1 += u64::from(right.attempts);
+    }
+
+    fn log(&self) {
+        let mean = |total: Duration| total.checked_div(self.calls as u32).unwrap_or_default();
+        info!("[EchoDiff] ✅ {} call(s): {} differ, {} skipped; mean latency {:?} vs {:?}, attempts {} vs {}",
+            self.calls, self.divergent, self.skipped, mean(self.latency.0), mean(self.latency.1),
+            self.attempts.0, self.attempts.1);
+    }
+}
@@ -12,7 +12,7 @@
 //! ```
 //! main.rs (this file)
 //!     ↓ calls
-//! echo_server::init() + echo_client::init()
+//! echo_server::init() + echo_client::init() + echo_monitor::init()
 //!     ↓ registers descriptors
 //! Framework Registry
 //!     ↓ framework calls
@@ -38,36 +38,111 @@
 //!
 //! **Rust version:** (this file - similar pattern!)
 
+use clap::Parser;
 use hsu_module_api::{Config, ModuleConfig, run_with_config};
+use tracing::{info_span, Instrument};
 use hsu_common::{ModuleID, Result};
 
 use echo_server::{init_echo_server_module, EchoServerModuleConfig};
 use echo_client::{init_echo_client_module, EchoClientModuleConfig};
+use echo_monitor::{init_echo_monitor_module, EchoMonitorModuleConfig};
+use echo_api::{cancel_on_ctrl_c, validate_module_dependencies};
+use echo_contract::{echo_module_id, ECHO_CLIENT_MODULE_ID, ECHO_MONITOR_MODULE_ID};
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, parse_described, BootstrapArgs, ConfigCheck, ConfigDescription,
+    Describe, Runtimes, Validate,
+};
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    tracing_subscriber::fmt::init();
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Echo direct communication demo")]
+struct Args {
+    /// Echo within this session; the server counts messages per session
+    #[arg(long)]
+    session: Option<String>,
     
-    // Register modules
-    init_echo_server_module(EchoServerModuleConfig::default())?;
-    init_echo_client_module(EchoClientModuleConfig::default())?;
+    /// Print the reply and how it was delivered (protocol, endpoint,
+    /// latency, attempts) as one JSON line on stdout
+    #[arg(long, conflicts_with = "session")]
+    json: bool,
     
-    // Configure and run
+    /// Log the server's echo events (pub/sub demo)
+    #[arg(long)]
+    watch_events: bool,
+    
+    /// Also run the echo-monitor module (aggregates the server's events)
+    #[arg(long)]
+    monitor: bool,
+    
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+}
+
+/// jemalloc as the global allocator, so `/debug/memory` has stats to report.
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+fn main() -> Result<()> {
+    let (args, description) = parse_described::<Args>();
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    // Configure, and report every problem before anything is set up
     let config = Config {
         runtime: Default::default(),
         modules: vec![
             ModuleConfig {
-                id: ModuleID::from("echo"),
+                id: echo_module_id(),
                 enabled: true,
                 servers: vec![],
             },
+            // Before the client, so the monitor sees the client's calls
             ModuleConfig {
-                id: ModuleID::from("echo-client"),
+                id: ModuleID::from(ECHO_MONITOR_MODULE_ID),
+                enabled: args.monitor,
+                servers: vec![],
+            },
+            ModuleConfig {
+                id: ModuleID::from(ECHO_CLIENT_MODULE_ID),
                 enabled: true,
                 servers: vec![],
             },
         ],
     };
+    let mut check = ConfigCheck::new();
+    config.validate(&mut check);
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+
+    bootstrap(&args.bootstrap)?;
+
+    // Register modules; Ctrl-C during their start aborts it
+    let cancellation = cancel_on_ctrl_c();
+    init_echo_server_module(EchoServerModuleConfig {
+        cancellation: cancellation.clone(),
+        ..Default::default()
+    })?;
+    init_echo_client_module(EchoClientModuleConfig {
+        session_id: args.session,
+        watch_events: args.watch_events,
+        json_output: args.json,
+        cancellation: cancellation.clone(),
+        ..Default::default()
+    })?;
+    init_echo_monitor_module(EchoMonitorModuleConfig {
+        cancellation,
+        ..Default::default()
+    })?;
     
-    run_with_config(config).await
+    // Fail fast if an enabled module consumes a service nothing provides
+    validate_module_dependencies(&config, &[])?;
+
+    announce_startup(&args.bootstrap, &description.merge(config.describe()))?;
+    // Root of every span in the process (see echo_contract::spans)
+    run_with_config(config)
+        .instrument(info_span!("bin", name = env!("CARGO_BIN_NAME"), pid = std::process::id()))
+        .await
 }
@@ -0,0 +1,359 @@
+//! `echo-grpc-cli check` - connectivity smoke test.
+//!
+//! # Architecture
+//!
+//! Resolves the echo module the way the client would (configured address,
+//! mDNS or registry), then probes every transport it publishes without
+//! starting any module:
+//!
+//! ```text
+//! resolve "echo"  ──→ [grpc 10.0.0.5:50051, ...]
+//!     ↓ per endpoint
+//! grpc       connect + Echo x3 ──→ reachable, TLS, auth, best RTT
+//! json/http  POST /proto.EchoService/Echo (JSON transcoding)
+//! direct     skipped - only exists inside one process
+//! ```
+//!
+//! The report ends with what this CLI's gateways support per protocol
+//! (see [`gateway_capabilities`]), and warns about published protocols
+//! they can't use - an `http` endpoint is reachable with curl, but no
+//! gateway of this CLI will call it:
+//!
+//! ```text
+//! module: echo
+//! PROTOCOL  WIRED  STREAMING  COMPRESSION  AUTH  ENCRYPTED  NOTE
+//! direct    no     no         -            -     no         module 'echo' isn't in this process
+//! grpc      yes    yes        no           no    no
+//! http      no     no         -            -     no         no HTTP gateway factory
+//! ⚠️  http published at 10.0.0.5:8080, but this CLI can't use it: no HTTP gateway factory
+//! ```
+//!
+//! Exits nonzero if no transport answered an echo, so deployment scripts
+//! can use it as a smoke test:
+//!
+//! ```bash
+//! echo-grpc-cli --direct-address localhost:50051 check && deploy-next-stage
+//! ```
+//!
+//! ## TLS and Auth
+//!
+//! The workspace has no TLS (tonic is built without it) and no auth layer
+//! yet. The report says so instead of guessing: `https://` addresses show
+//! up as unsupported, and auth shows whether the server rejected the
+//! probe (`UNAUTHENTICATED`/`PERMISSION_DENIED`, HTTP 401/403) or
+//! answered it (`accepted`). Any other failure says nothing about auth:
+//! it shows as `unknown`.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+use clap::Args;
+use hsu_common::{Error, Protocol, Result};
+use hsu_module_api::RuntimeConfig;
+use hyper::{Body, Client, Method, Request, StatusCode};
+use serde_json::{json, Value};
+use tonic::Code;
+
+use echo_api::{gateway_capabilities, protocol_name, registry_backend, GatewayOptions};
+use echo_api_grpc::generated::{echo_service_client::EchoServiceClient, EchoRequest};
+use echo_api_grpc::GrpcChannelOptions;
+use echo_contract::{echo_module_id, ECHO_MODULE_ID};
+
+/// Echo calls per transport; the report shows the fastest.
+const PINGS: u32 = 3;
+
+/// Options of the `check` command.
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    /// Give up on a transport after this many milliseconds
+    #[arg(long, default_value_t = 3000)]
+    pub timeout_ms: u64,
+}
+
+/// Where to look for the echo module.
+pub enum Resolve<'a> {
+    /// `--direct-address`.
+    Address(&'a str),
+    /// mDNS browse.
+    Mdns,
+    /// The registry at the runtime config's URL.
+    Registry(&'a RuntimeConfig),
+}
+
+/// Outcome of probing one transport.
+#[derive(Debug)]
+struct Probe {
+    transport: &'static str,
+    endpoint: String,
+    /// `Err` holds why the transport didn't answer an echo.
+    reachable: std::result::Result<(), String>,
+    tls: &'static str,
+    auth: &'static str,
+    rtt: Option<Duration>,
+}
+
+impl Probe {
+    fn new(transport: &'static str, endpoint: &str) -> Self {
+        Self {
+            transport,
+            endpoint: endpoint.to_string(),
+            reachable: Err("not probed".to_string()),
+            tls: tls_state(endpoint),
+            auth: "-",
+            rtt: None,
+        }
+    }
+
+    fn works(&self) -> bool {
+        self.reachable.is_ok()
+    }
+}
+
+impl fmt::Display for Probe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reachable = match &self.reachable {
+            Ok(()) => "yes".to_string(),
+            Err(reason) => format!("no ({})", reason),
+        };
+        let rtt = self.rtt.map(|rtt| format!("{:.1}ms", rtt.as_secs_f64() * 1000.0)).unwrap_or_else(|| "-".to_string());
+        write!(f, "{:<10} {:<24} {:<10} {:<10} {:<8} {}", self.transport, self.endpoint, self.tls, self.auth, rtt, reachable)
+    }
+}
+
+/// Runs the check and prints the report; fails if no transport works.
+pub async fn run(
+    check: &CheckArgs,
+    resolve: Resolve<'_>,
+    channel: &GrpcChannelOptions,
+    gateways: &GatewayOptions,
+) -> Result<()> {
+    let timeout = Duration::from_millis(check.timeout_ms);
+    let (source, apis) = resolve_echo(resolve, timeout).await?;
+    println!("echo module: {} endpoint(s) via {}", apis.len(), source);
+    println!("{:<10} {:<24} {:<10} {:<10} {:<8} REACHABLE", "TRANSPORT", "ENDPOINT", "TLS", "AUTH", "RTT");
+
+    let mut probes = Vec::new();
+    for (protocol, address) in &apis {
+        let endpoint_probes = match protocol {
+            // JSON transcoding shares the gRPC port
+            Protocol::Grpc => vec![probe_grpc(address, channel, timeout).await, probe_json("json", address, timeout).await],
+            Protocol::Http => vec![probe_json("http", address, timeout).await],
+            other => {
+                println!("{:<10} {:<24} skipped (not probed from outside the process)", protocol_name(other), address);
+                continue;
+            }
+        };
+        for probe in &endpoint_probes {
+            println!("{}", probe);
+        }
+        probes.extend(endpoint_probes);
+    }
+    println!("{:<10} {:<24} skipped (only within one process, see echo-direct-cli)", "direct", "in-process");
+
+    let capabilities = gateway_capabilities(echo_module_id(), gateways, None, None);
+    println!("\n{}", capabilities);
+    for (protocol, address) in &apis {
+        if !capabilities.supports(*protocol) {
+            let note = capabilities.get(*protocol).and_then(|entry| entry.note.as_deref()).unwrap_or("not wired");
+            println!("⚠️  {} published at {}, but this CLI can't use it: {}", protocol_name(protocol), address, note);
+        }
+    }
+
+    let working = probes.iter().filter(|probe| probe.works()).count();
+    if working == 0 {
+        return Err(Error::Protocol(format!("No transport reached the echo module ({} probed)", probes.len())));
+    }
+    println!("✅ {} of {} transports work", working, probes.len());
+    Ok(())
+}
+
+/// Finds the echo module's endpoints; returns where they came from too.
+pub(crate) async fn resolve_echo(resolve: Resolve<'_>, timeout: Duration) -> Result<(String, Vec<(Protocol, String)>)> {
+    match resolve {
+        Resolve::Address(address) => Ok(("--direct-address".to_string(), vec![(Protocol::Grpc, address.to_string())])),
+        Resolve::Mdns => {
+            let address = echo_api::mdns::browse(ECHO_MODULE_ID, timeout).await?;
+            Ok(("mDNS".to_string(), vec![(Protocol::Grpc, address)]))
+        }
+        Resolve::Registry(runtime) => {
+            let backend = registry_backend(runtime)?;
+            let apis = tokio::time::timeout(timeout, backend.discover(ECHO_MODULE_ID))
+                .await
+                .map_err(|_| Error::Protocol(format!("Registry {} didn't answer within {:?}", runtime.service_registry.url, timeout)))??;
+            if apis.is_empty() {
+                return Err(Error::Protocol(format!("Module 'echo' isn't registered at {}", runtime.service_registry.url)));
+            }
+            let source = format!("{} registry at {}", backend.name(), runtime.service_registry.url);
+            Ok((source, apis.into_iter().map(|api| (api.protocol, api.address)).collect()))
+        }
+    }
+}
+
+/// Dials `address` and times a few echo calls.
+async fn probe_grpc(address: &str, options: &GrpcChannelOptions, timeout: Duration) -> Probe {
+    let mut probe = Probe::new("grpc", address);
+    if address.starts_with("https://") {
+        probe.reachable = Err("TLS not supported by this build".to_string());
+        return probe;
+    }
+    let endpoint = match options.endpoint(address) {
+        Ok(endpoint) => endpoint.timeout(timeout),
+        Err(e) => {
+            probe.reachable = Err(e.to_string());
+            return probe;
+        }
+    };
+    let channel = match tokio::time::timeout(timeout, endpoint.connect()).await {
+        Ok(Ok(channel)) => channel,
+        Ok(Err(e)) => {
+            probe.reachable = Err(format!("connect failed: {}", e));
+            return probe;
+        }
+        Err(_) => {
+            probe.reachable = Err(format!("connect timed out after {:?}", timeout));
+            return probe;
+        }
+    };
+
+    let mut client = EchoServiceClient::new(channel);
+    for _ in 0..PINGS {
+        let started = Instant::now();
+        let result = client.echo(tonic::Request::new(EchoRequest { message: "check".to_string() })).await;
+        let elapsed = started.elapsed();
+        match result {
+            Ok(_) => {
+                probe.auth = "accepted";
+                probe.reachable = Ok(());
+                probe.rtt = Some(probe.rtt.map_or(elapsed, |best| best.min(elapsed)));
+            }
+            Err(status) => {
+                probe.auth = grpc_auth_state(status.code());
+                probe.reachable = Err(format!("{:?}: {}", status.code(), status.message()));
+                return probe;
+            }
+        }
+    }
+    probe
+}
+
+/// Posts an echo as JSON (the transcoding path browsers use).
+async fn probe_json(transport: &'static str, address: &str, timeout: Duration) -> Probe {
+    let mut probe = Probe::new(transport, address);
+    if address.starts_with("https://") {
+        probe.reachable = Err("TLS not supported by this build".to_string());
+        return probe;
+    }
+    let base = if address.contains("://") { address.to_string() } else { format!("http://{}", address) };
+    let uri = format!("{}/proto.EchoService/Echo", base.trim_end_matches('/'));
+    let client = Client::new();
+
+    for _ in 0..PINGS {
+        let request = match Request::builder()
+            .method(Method::POST)
+            .uri(&uri)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({ "message": "check" }).to_string()))
+        {
+            Ok(request) => request,
+            Err(e) => {
+                probe.reachable = Err(format!("invalid URI {}: {}", uri, e));
+                return probe;
+            }
+        };
+        let started = Instant::now();
+        let response = match tokio::time::timeout(timeout, client.request(request)).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => {
+                probe.reachable = Err(format!("request failed (JSON transcoding off?): {}", e));
+                return probe;
+            }
+            Err(_) => {
+                probe.reachable = Err(format!("timed out after {:?}", timeout));
+                return probe;
+            }
+        };
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap_or_default();
+        let elapsed = started.elapsed();
+        probe.auth = http_auth_state(status);
+        let reply: Option<Value> = serde_json::from_slice(&body).ok();
+        if status != StatusCode::OK || reply.as_ref().and_then(|reply| reply.get("message")).is_none() {
+            probe.reachable = Err(format!("HTTP {}", status));
+            return probe;
+        }
+        probe.reachable = Ok(());
+        probe.rtt = Some(probe.rtt.map_or(elapsed, |best| best.min(elapsed)));
+    }
+    probe
+}
+
+fn tls_state(address: &str) -> &'static str {
+    if address.starts_with("https://") { "required" } else { "plaintext" }
+}
+
+fn grpc_auth_state(code: Code) -> &'static str {
+    match code {
+        Code::Unauthenticated => "rejected",
+        Code::PermissionDenied => "denied",
+        // Failed before or after auth, we can't tell
+        _ => "unknown",
+    }
+}
+
+fn http_auth_state(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::UNAUTHORIZED => "rejected",
+        StatusCode::FORBIDDEN => "denied",
+        status if status.is_success() => "accepted",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_nothing_listening_fails_the_check() {
+        let check = CheckArgs { timeout_ms: 500 };
+        // Nothing listens on port 1
+        let error = run(&check, Resolve::Address("127.0.0.1:1"), &GrpcChannelOptions::default())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("No transport reached"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_https_reported_as_unsupported() {
+        let probe = probe_grpc("https://example.com:443", &GrpcChannelOptions::default(), Duration::from_millis(100)).await;
+        assert_eq!(probe.tls, "required");
+        assert!(probe.reachable.unwrap_err().contains("TLS"));
+    }
+
+    #[tokio::test]
+    async fn test_json_probe_measures_rtt() {
+        // Answers like the JSON transcoding path
+        let make_service = hyper::service::make_service_fn(|_| async {
+            Ok::<_, hyper::Error>(hyper::service::service_fn(|_request| async {
+                Ok::<_, hyper::Error>(hyper::Response::new(Body::from(r#"{"message":"check"}"#)))
+            }))
+        });
+        let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let address = server.local_addr().to_string();
+        tokio::spawn(server);
+
+        let probe = probe_json("json", &address, Duration::from_secs(5)).await;
+        assert!(probe.works(), "{}", probe);
+        assert_eq!(probe.auth, "accepted");
+        assert!(probe.rtt.is_some());
+    }
+
+    #[test]
+    fn test_auth_states() {
+        assert_eq!(grpc_auth_state(Code::Unauthenticated), "rejected");
+        assert_eq!(grpc_auth_state(Code::Unavailable), "unknown");
+        assert_eq!(grpc_auth_state(Code::InvalidArgument), "unknown");
+        assert_eq!(http_auth_state(StatusCode::FORBIDDEN), "denied");
+        assert_eq!(http_auth_state(StatusCode::OK), "accepted");
+        assert_eq!(http_auth_state(StatusCode::SERVICE_UNAVAILABLE), "unknown");
+    }
+}
@@ -0,0 +1,367 @@
+//! `echo-grpc-cli history` / `export` / `import` - a server's echo history.
+//!
+//! Needs a server started with `--history` (or `--history-db`).
+//! `history` prints one page, newest first, and the cursor of the next;
+//! `--all` streams every matching entry instead, and `--stream` prints
+//! each as it arrives (one JSON object per line with `--json`); a slow
+//! terminal slows the server's stream down rather than piling entries up
+//! here. `export` writes the
+//! matching entries to a file, NDJSON or CSV, as the server streams it;
+//! `import` uploads such a file into another server's history (entries
+//! it already has are skipped, a bad file imports nothing):
+//!
+//! ```bash
+//! echo-grpc-cli --direct-address localhost:50051 history --limit 20 --contains hello
+//! echo-grpc-cli --direct-address localhost:50051 history --cursor 42
+//! echo-grpc-cli --direct-address localhost:50051 history --all --since-ms 1700000000000
+//! echo-grpc-cli --direct-address localhost:50051 --json history --stream | jq .message
+//! echo-grpc-cli --direct-address localhost:50051 export history.csv --contains hello
+//! echo-grpc-cli --direct-address localhost:50052 import history.csv
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use bytes::Bytes;
+use clap::Args;
+use futures::{StreamExt, TryStreamExt};
+use hsu_common::{Error, Result};
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use echo_api_grpc::GrpcChannelOptions;
+use echo_contract::{ByteStream, HistoryEntry, HistoryExportFormat, HistoryPage, HistoryQuery, HistoryStream};
+
+use crate::check::Resolve;
+use crate::schedule::with_service;
+
+/// Options of the `history` command.
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    /// Entries per page (0: server default); with --all, the total (0: all)
+    #[arg(long, default_value_t = 0)]
+    pub limit: u32,
+
+    /// Continue after this cursor (printed as next_cursor)
+    #[arg(long)]
+    pub cursor: Option<String>,
+
+    #[command(flatten)]
+    pub filter: HistoryFilterArgs,
+
+    /// Stream every matching entry instead of one page
+    #[arg(long)]
+    pub all: bool,
+
+    /// Like --all, printing each entry as it arrives (NDJSON with --json);
+    /// the stream is read no faster than stdout takes it
+    #[arg(long, conflicts_with_all = ["all", "cursor"])]
+    pub stream: bool,
+
+    /// Give up after this many milliseconds
+    #[arg(long, default_value_t = 3000)]
+    pub timeout_ms: u64,
+}
+
+/// Options of the `export` command.
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// File to write (replaced once the export completes)
+    pub output: PathBuf,
+
+    /// ndjson or csv (default: from the file extension, else ndjson)
+    #[arg(long)]
+    pub format: Option<String>,
+
+    /// Export at most this many entries, newest first (0: all)
+    #[arg(long, default_value_t = 0)]
+    pub limit: u32,
+
+    #[command(flatten)]
+    pub filter: HistoryFilterArgs,
+
+    /// Give up finding the server after this many milliseconds (the
+    /// export itself takes as long as it takes)
+    #[arg(long, default_value_t = 3000)]
+    pub timeout_ms: u64,
+}
+
+/// Options of the `import` command.
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// File written by `export`
+    pub input: PathBuf,
+
+    /// ndjson or csv (default: from the file extension, else ndjson)
+    #[arg(long)]
+    pub format: Option<String>,
+
+    /// Give up finding the server after this many milliseconds (the
+    /// import itself takes as long as it takes)
+    #[arg(long, default_value_t = 3000)]
+    pub timeout_ms: u64,
+}
+
+/// Bytes per uploaded chunk.
+const IMPORT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Entry filters shared by `history` and `export`.
+#[derive(Args, Debug)]
+pub struct HistoryFilterArgs {
+    /// Only entries recorded at or after this Unix time (milliseconds)
+    #[arg(long)]
+    pub since_ms: Option<u64>,
+
+    /// Only entries recorded before this Unix time (milliseconds)
+    #[arg(long)]
+    pub until_ms: Option<u64>,
+
+    /// Only entries whose message contains this text
+    #[arg(long)]
+    pub contains: Option<String>,
+}
+
+impl HistoryFilterArgs {
+    fn query(&self, cursor: Option<String>, limit: u32) -> HistoryQuery {
+        let at = |ms: u64| UNIX_EPOCH + Duration::from_millis(ms);
+        HistoryQuery {
+            cursor,
+            limit,
+            since: self.since_ms.map(at),
+            until: self.until_ms.map(at),
+            contains: self.contains.clone(),
+        }
+    }
+}
+
+/// Prints a page (or, with `--all`, every entry) of the history.
+pub async fn run(args: &HistoryArgs, resolve: Resolve<'_>, channel: &GrpcChannelOptions, json: bool) -> Result<()> {
+    let query = args.filter.query(args.cursor.clone(), args.limit);
+    if args.stream {
+        return with_service(resolve, channel, Duration::from_millis(args.timeout_ms), |service| async move {
+            let entries = service.stream_history(query).await?;
+            print_stream(entries, tokio::io::stdout(), json).await.map(|_| ())
+        }).await;
+    }
+    let page = with_service(resolve, channel, Duration::from_millis(args.timeout_ms), |service| async move {
+        if args.all {
+            let entries = service.stream_history(query).await?.try_collect().await?;
+            Ok(HistoryPage { entries, next_cursor: None })
+        } else {
+            service.get_history(query).await
+        }
+    }).await?;
+
+    if json {
+        let entries: Vec<_> = page.entries.iter().map(entry_json).collect();
+        println!("{}", json!({ "entries": entries, "next_cursor": page.next_cursor }));
+    } else {
+        for entry in &page.entries {
+            println!("{}", format_entry(entry));
+        }
+        if let Some(cursor) = &page.next_cursor {
+            println!("next_cursor: {}", cursor);
+        }
+    }
+    Ok(())
+}
+
+/// Writes each entry of `entries` to `out` as it arrives; returns how
+/// many were written.
+///
+/// The next entry is only polled once the previous line is written and
+/// flushed. A slow `out` (terminal, full pipe) thus leaves the stream
+/// unread, its HTTP/2 window fills up and the server's sends wait: at most
+/// a window's worth of entries is buffered here, however long the history.
+async fn print_stream<W: AsyncWrite + Unpin>(mut entries: HistoryStream, mut out: W, json: bool) -> Result<u64> {
+    let io_error = |e: std::io::Error| Error::Protocol(format!("Failed to write to stdout: {}", e));
+    let mut written = 0;
+    while let Some(entry) = entries.next().await {
+        let entry = entry?;
+        let line = if json { entry_json(&entry).to_string() } else { format_entry(&entry) };
+        out.write_all(format!("{}\n", line).as_bytes()).await.map_err(io_error)?;
+        out.flush().await.map_err(io_error)?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Streams the export into `args.output`; prints the byte count.
+///
+/// Writes `<output>.partial` and renames it at the end, so a failed
+/// export never leaves a truncated file under the real name.
+pub async fn run_export(args: &ExportArgs, resolve: Resolve<'_>, channel: &GrpcChannelOptions, json: bool) -> Result<()> {
+    let format = match &args.format {
+        Some(name) => name.parse()?,
+        None => format_from_extension(&args.output),
+    };
+    let query = args.filter.query(None, args.limit);
+    let partial = PathBuf::from(format!("{}.partial", args.output.display()));
+    let io_error = |e: std::io::Error| Error::Protocol(format!("Failed to write {}: {}", partial.display(), e));
+
+    let written = with_service(resolve, channel, Duration::from_millis(args.timeout_ms), |service| async move {
+        let mut chunks = service.export_history(format, query).await?;
+        let mut file = tokio::fs::File::create(&partial).await.map_err(io_error)?;
+        let mut written = 0;
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk).await.map_err(io_error)?;
+            written += chunk.len() as u64;
+        }
+        file.flush().await.map_err(io_error)?;
+        Ok(written)
+    }).await;
+    let written = match written {
+        Ok(written) => written,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e);
+        }
+    };
+    tokio::fs::rename(&partial, &args.output).await.map_err(io_error)?;
+
+    if json {
+        println!("{}", json!({
+            "path": args.output.display().to_string(),
+            "format": format.as_str(),
+            "bytes": written,
+        }));
+    } else {
+        println!("exported {} bytes of {} to {}", written, format, args.output.display());
+    }
+    Ok(())
+}
+
+/// Uploads `args.input` into the server's history; prints how many
+/// entries were imported and how many it already had.
+pub async fn run_import(args: &ImportArgs, resolve: Resolve<'_>, channel: &GrpcChannelOptions, json: bool) -> Result<()> {
+    let format = match &args.format {
+        Some(name) => name.parse()?,
+        None => format_from_extension(&args.input),
+    };
+    // Before looking for a server: a missing file is the likelier mistake
+    let file = tokio::fs::File::open(&args.input).await
+        .map_err(|e| Error::Protocol(format!("Failed to read {}: {}", args.input.display(), e)))?;
+
+    let report = with_service(resolve, channel, Duration::from_millis(args.timeout_ms), |service| async move {
+        service.import_history(format, read_chunks(file, args.input.clone())).await
+    }).await?;
+
+    if json {
+        println!("{}", json!({
+            "path": args.input.display().to_string(),
+            "format": format.as_str(),
+            "imported": report.imported,
+            "duplicates": report.duplicates,
+        }));
+    } else {
+        println!("imported {} entries from {} ({} already there)", report.imported, args.input.display(), report.duplicates);
+    }
+    Ok(())
+}
+
+/// `file` as a stream of chunks; a read error ends it (and the import).
+fn read_chunks(file: tokio::fs::File, path: PathBuf) -> ByteStream {
+    Box::pin(futures::stream::unfold(Some(file), move |file| {
+        let path = path.clone();
+        async move {
+            let mut file = file?;
+            let mut chunk = vec![0; IMPORT_CHUNK_BYTES];
+            match file.read(&mut chunk).await {
+                Ok(0) => None,
+                Ok(read) => {
+                    chunk.truncate(read);
+                    Some((Ok(Bytes::from(chunk)), Some(file)))
+                }
+                Err(e) => Some((Err(Error::Protocol(format!("Failed to read {}: {}", path.display(), e))), None)),
+            }
+        }
+    }))
+}
+
+fn format_from_extension(path: &Path) -> HistoryExportFormat {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("csv") => HistoryExportFormat::Csv,
+        _ => HistoryExportFormat::Ndjson,
+    }
+}
+
+fn entry_json(entry: &HistoryEntry) -> serde_json::Value {
+    json!({
+        "id": entry.id,
+        "record_id": entry.record_id,
+        "method": entry.method.as_str(),
+        "message": entry.message,
+        "request_bytes": entry.request_bytes,
+        "success": entry.success,
+        "at_unix_ms": unix_ms(entry.at),
+    })
+}
+
+fn format_entry(entry: &HistoryEntry) -> String {
+    let outcome = if entry.success { "ok" } else { "failed" };
+    format!("#{} {} {} {}B {} {:?}",
+        entry.id, unix_ms(entry.at), entry.method.as_str(), entry.request_bytes, outcome, entry.message)
+}
+
+fn unix_ms(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use echo_contract::EchoMethod;
+
+    fn entry(id: u64) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            record_id: format!("r{}", id),
+            method: EchoMethod::Echo,
+            message: format!("message {}", id),
+            request_bytes: 9,
+            success: true,
+            at: UNIX_EPOCH + Duration::from_millis(id),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_is_read_no_faster_than_output() {
+        // Counts the entries taken from the stream
+        let pulled = Arc::new(AtomicU64::new(0));
+        let counter = pulled.clone();
+        let entries: HistoryStream = Box::pin(futures::stream::iter(1..=1000).map(move |id| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(entry(id))
+        }));
+
+        // A "terminal" holding a few lines that nobody reads yet
+        let (out, mut terminal) = tokio::io::duplex(256);
+        let printing = tokio::spawn(print_stream(entries, out, true));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let stalled_at = pulled.load(Ordering::SeqCst);
+        assert!(stalled_at < 10, "pulled {} entries into a full output", stalled_at);
+
+        // Reading it lets the rest through, each entry a JSON line
+        let mut lines = String::new();
+        terminal.read_to_string(&mut lines).await.unwrap();
+        assert_eq!(printing.await.unwrap().unwrap(), 1000);
+        let first: serde_json::Value = serde_json::from_str(lines.lines().next().unwrap()).unwrap();
+        assert_eq!(first["message"], "message 1");
+        assert_eq!(lines.lines().count(), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_stops_printing() {
+        let entries: HistoryStream = Box::pin(futures::stream::iter(vec![
+            Ok(entry(1)),
+            Err(Error::Protocol("stream reset".to_string())),
+            Ok(entry(3)),
+        ]));
+        let mut out = Vec::new();
+        let error = print_stream(entries, &mut out, false).await.unwrap_err();
+        assert!(error.to_string().contains("stream reset"));
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1);
+    }
+}
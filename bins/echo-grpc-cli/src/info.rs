@@ -0,0 +1,73 @@
+//! `echo-grpc-cli info` - which server instance answers?
+//!
+//! Resolves the echo module like `check` does and asks every endpoint
+//! for its `GetInfo`: module, instance ID, version, git hash, uptime and
+//! features. Behind a load balancer, repeated runs show which instances
+//! take turns:
+//!
+//! ```bash
+//! echo-grpc-cli --direct-address localhost:50051 info
+//! echo-grpc-cli --registry-url consul://localhost:8500 --json info
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+use clap::Args;
+use hsu_common::{Error, Protocol, Result};
+use serde_json::json;
+
+use echo_api::{grpc_echo_service, render_info, GatewayOptions};
+use echo_api_grpc::{ChannelPool, GrpcChannelOptions};
+
+use crate::check::{resolve_echo, Resolve};
+
+/// Options of the `info` command.
+#[derive(Args, Debug)]
+pub struct InfoArgs {
+    /// Give up on an endpoint after this many milliseconds
+    #[arg(long, default_value_t = 3000)]
+    pub timeout_ms: u64,
+}
+
+/// Prints the info of every gRPC endpoint; fails if none answered.
+///
+/// With `json`, prints one JSON object per endpoint and line.
+pub async fn run(info: &InfoArgs, resolve: Resolve<'_>, channel: &GrpcChannelOptions, json: bool) -> Result<()> {
+    let timeout = Duration::from_millis(info.timeout_ms);
+    let (_, apis) = resolve_echo(resolve, timeout).await?;
+
+    let mut answered = 0;
+    for (protocol, address) in apis.iter().filter(|(protocol, _)| *protocol == Protocol::Grpc) {
+        let options = GatewayOptions {
+            deadline: Some(timeout),
+            channel_pool: Arc::new(ChannelPool::new(channel.clone())),
+            ..Default::default()
+        };
+        let result = grpc_echo_service(address, &options).get_info().await;
+        options.channel_pool.close();
+        match result {
+            Ok(server) if json => {
+                answered += 1;
+                println!("{}", json!({
+                    "endpoint": address,
+                    "module_id": server.module_id,
+                    "instance_id": server.instance_id,
+                    "version": server.version,
+                    "git_hash": server.git_hash,
+                    "uptime_secs": server.uptime.as_secs(),
+                    "features": server.features,
+                }));
+            }
+            Ok(server) => {
+                answered += 1;
+                println!("endpoint: {} ({:?})\n{}", address, protocol, render_info(&server));
+            }
+            Err(e) => eprintln!("endpoint: {} - no answer: {}", address, e),
+        }
+    }
+
+    if answered == 0 {
+        return Err(Error::Protocol(format!("No gRPC endpoint of the echo module answered ({} found)", apis.len())));
+    }
+    Ok(())
+}
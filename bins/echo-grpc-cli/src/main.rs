@@ -36,44 +36,492 @@
 //! ```
 //!
 //! **Rust version:** (this file - similar pattern!)
+//!
+//! `echo-grpc-cli check` probes the echo module's transports instead of
+//! running the client (see `check.rs`); `echo-grpc-cli info` shows which
+//! server instances answer (see `info.rs`); `schedule` and `cancel`
+//! manage server-side scheduled echoes (see `schedule.rs`); `history`
+//! pages through the server's call history, `export` saves it to a
+//! file and `import` loads such a file into another server (see
+//! `history.rs`).
+
+mod check;
+mod history;
+mod info;
+mod schedule;
 
-use hsu_common::{ModuleID, Result};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use hsu_common::{Error, ModuleID, Protocol, Result};
 use hsu_module_api::{Config, ModuleConfig, RuntimeConfig, ServiceRegistryConfig, run_with_config};
-use clap::Parser;
+use tracing::{info_span, Instrument};
+use clap::{Parser, Subcommand};
 
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, parse_described, spawn_inmem_registry, BootstrapArgs, ConfigCheck,
+    ConfigDescription, Describe, Runtimes, Validate,
+};
+use echo_api::{
+    BatchingConfig, Discovery, GatewayOptions, HedgingPolicy, LatencyBudgets, MirrorConfig, MirrorTarget, PayloadKey, ResponseValidator,
+    WarmStandbyConfig,
+    cancel_on_ctrl_c, framework_config, module_registry_backend,
+    validate_module_dependencies,
+};
+use echo_contract::{echo_module_id, parse_transforms, Priority, Secret, ECHO_CLIENT_MODULE_ID, ECHO_MONITOR_MODULE_ID};
+use echo_api_grpc::{GrpcChannelOptions, SigningKey};
 use echo_client::{init_echo_client_module, EchoClientModuleConfig};
+use echo_monitor::{init_echo_monitor_module, EchoMonitorModuleConfig, SloObjectives};
+
+/// Payload encryption key shared with the server, `ID:HEX`.
+const PAYLOAD_KEY_VARIABLE: &str = "ECHO_PAYLOAD_KEY";
+/// Request signing key shared with the server, `CALLER:HEX`.
+const SIGNING_KEY_VARIABLE: &str = "ECHO_SIGNING_KEY";
+
+/// Where the keys come from (never flags: they'd show in `ps` and the config dump).
+const SECRETS_HELP: &str = "Keys (the variable, or the file named by its *_FILE variant):
+  ECHO_PAYLOAD_KEY[_FILE]  ID:<64 hex digits>: seal messages with this key shared with the server
+  ECHO_SIGNING_KEY[_FILE]  CALLER:HEX: sign every request as CALLER with this secret shared with the server";
 
 /// Command-line arguments
 #[derive(Parser, Debug)]
-#[command(author, version, about = "Echo gRPC Client - NEW ARCHITECTURE")]
+#[command(author, version, about = "Echo gRPC Client - NEW ARCHITECTURE", after_help = SECRETS_HELP)]
 struct Args {
-    /// Service registry URL
-    #[arg(short, long, default_value = "http://localhost:8080")]
+    /// Service registry URL: http://host:port (HSU registry), consul://host:port or etcd://host:port
+    #[arg(short, long, default_value = "http://localhost:8080", global = true)]
     registry_url: String,
+    
+    /// Registry: http (external registry at --registry-url) or inmem
+    /// (in-memory registry hosted at --registry-url by the first process started)
+    #[arg(long, default_value = "http", global = true)]
+    registry: String,
+    
+    /// Stream this file through the echo service (large-payload demo)
+    #[arg(long)]
+    file: Option<PathBuf>,
+    
+    /// Per-call deadline in milliseconds (unbounded if omitted)
+    #[arg(long)]
+    deadline_ms: Option<u64>,
+    
+    /// Priority class of the calls (high, normal, low)
+    #[arg(long, default_value = "normal")]
+    priority: String,
+    
+    /// Transform the echoed message on the server, e.g. `nfkc,upper` or
+    /// `lower:tr,truncate:20`
+    #[arg(long, default_value = "")]
+    transform: String,
+    
+    /// Caller name sent with every call; the server accounts (and may
+    /// limit) echoed bytes per caller
+    #[arg(long)]
+    caller: Option<String>,
+    
+    /// Languages for the server's error messages (`de`, `fr;q=0.8, en;q=0.5`);
+    /// unsupported ones fall back to English
+    #[arg(long, value_name = "LANGUAGES")]
+    accept_language: Option<String>,
+    
+    /// Echo within this session; the server counts messages per session
+    #[arg(long)]
+    session: Option<String>,
+    
+    /// Print the reply and how it was delivered (protocol, endpoint,
+    /// latency, attempts) as one JSON line on stdout; with a command, print
+    /// its result as JSON
+    #[arg(long, conflicts_with = "session", global = true)]
+    json: bool,
+    
+    /// Log the server's echo events (pub/sub demo)
+    #[arg(long)]
+    watch_events: bool,
+    
+    /// Also run the echo-monitor module (aggregates the server's events)
+    #[arg(long)]
+    monitor: bool,
+    
+    /// With --monitor: availability objective in percent; attainment over
+    /// 1m/5m/1h windows is logged on shutdown
+    #[arg(long, default_value_t = 99.9, value_name = "PERCENT")]
+    slo_availability: f64,
+    
+    /// With --monitor: share of calls within their --latency-budget, in percent
+    #[arg(long, default_value_t = 99.0, value_name = "PERCENT")]
+    slo_latency: f64,
+    
+    /// Hedge echo calls slower than this many milliseconds with a second attempt
+    #[arg(long)]
+    hedge_after_ms: Option<u64>,
+    
+    /// Share one request among identical echo calls in flight at once
+    #[arg(long)]
+    coalesce: bool,
+    
+    /// Send gRPC echo calls in EchoBatch requests of up to this many messages
+    #[arg(long)]
+    batch_max: Option<usize>,
+    
+    /// With --batch-max: how long a call waits for its batch to fill
+    #[arg(long, default_value_t = 5)]
+    batch_linger_ms: u64,
+    
+    /// Race gRPC against JSON/HTTP (server --json-transcoding) on the first call, keep the winner
+    #[arg(long)]
+    race_protocols: bool,
+    
+    /// Keep a second transport (http or grpc) connected next to the one in
+    /// use and resend UNAVAILABLE calls over it at once
+    #[arg(long, value_name = "PROTOCOL")]
+    warm_standby: Option<String>,
+    
+    /// With --warm-standby: seconds between probes of the standby
+    #[arg(long, default_value_t = 10)]
+    standby_probe_secs: u64,
+    
+    /// Fail calls whose reply differs from the message (transformed calls excepted)
+    #[arg(long)]
+    expect_echo: bool,
+    
+    /// Fail calls whose reply doesn't match this regular expression
+    #[arg(long, value_name = "REGEX")]
+    expect_match: Option<String>,
+    
+    /// Fail calls whose reply is longer than this many bytes
+    #[arg(long, value_name = "BYTES")]
+    max_reply_bytes: Option<usize>,
+    
+    /// Send a SHA-256 of each message and verify the one on the reply (INTEGRITY_ERROR on mismatch)
+    #[arg(long)]
+    integrity: bool,
+    
+    /// Copy echo calls to the JSON/HTTP adapter at host:port and compare its
+    /// replies with the primary's (echo_mirror_* metrics); callers only see the primary
+    #[arg(long, value_name = "HOST:PORT", conflicts_with = "mirror_grpc")]
+    mirror_http: Option<String>,
+    
+    /// Like --mirror-http, to the gRPC server at host:port
+    #[arg(long, value_name = "HOST:PORT")]
+    mirror_grpc: Option<String>,
+    
+    /// Latency budgets per method, e.g. `echo=50ms,echo_bytes=200ms`: slower
+    /// calls are logged with their call info and counted
+    /// (echo_latency_budget_violations_total), not failed
+    #[arg(long, value_name = "METHOD=DURATION,...", default_value = "")]
+    latency_budget: String,
+    
+    /// Connect straight to this gRPC server (host:port), bypassing the registry channel
+    #[arg(long, global = true)]
+    direct_address: Option<String>,
+    
+    /// How to find the server: registry or mdns (LAN, no registry needed)
+    #[arg(long, default_value = "registry", global = true)]
+    discovery: String,
+    
+    /// HTTP/2 keepalive ping interval in seconds (0 disables pings)
+    #[arg(long, default_value_t = 30)]
+    keepalive_secs: u64,
+    
+    /// Seconds to wait for a keepalive ping acknowledgement
+    #[arg(long, default_value_t = 10)]
+    keepalive_timeout_secs: u64,
+    
+    /// Only ping while calls are in flight (default pings idle connections too)
+    #[arg(long)]
+    no_keepalive_while_idle: bool,
+    
+    /// Drop channels unused for this many seconds (0 keeps them forever)
+    #[arg(long, default_value_t = 300)]
+    idle_timeout_secs: u64,
+    
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
-    tracing_subscriber::fmt::init();
-    
-    init_echo_client_module(EchoClientModuleConfig::default())?;
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Probe each transport of the echo module (reachability, TLS, auth,
+    /// RTT) and exit nonzero if none works
+    Check(check::CheckArgs),
+    /// Show the server instance behind each endpoint (instance ID,
+    /// version, git hash, uptime, features)
+    Info(info::InfoArgs),
+    /// Have the server echo a message later, once or recurring
+    Schedule(schedule::ScheduleArgs),
+    /// Cancel a scheduled echo by job ID
+    Cancel(schedule::CancelArgs),
+    /// Page through the server's echo history, newest first
+    History(history::HistoryArgs),
+    /// Save the server's echo history to a file (NDJSON or CSV)
+    Export(history::ExportArgs),
+    /// Load an exported history file into the server's history
+    Import(history::ImportArgs),
+}
+
+/// jemalloc as the global allocator, so `/debug/memory` has stats to report.
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+fn main() -> Result<()> {
+    let (args, description) = parse_described::<Args>();
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    let secrets = Secrets::load()?;
     
-    let config = Config {
-        runtime: RuntimeConfig {
-            service_registry: ServiceRegistryConfig {
-                url: args.registry_url,
-            },
-            servers: vec![],
+    let runtime = RuntimeConfig {
+        service_registry: ServiceRegistryConfig {
+            url: args.registry_url.clone(),
         },
+        servers: vec![],
+    };
+    let config = Config {
+        runtime,
         modules: vec![
+            // Before the client, so the monitor sees the client's calls
             ModuleConfig {
-                id: ModuleID::from("echo-client"),
+                id: ModuleID::from(ECHO_MONITOR_MODULE_ID),
+                enabled: args.monitor,
+                servers: vec![],
+            },
+            ModuleConfig {
+                id: ModuleID::from(ECHO_CLIENT_MODULE_ID),
                 enabled: true,
                 servers: vec![],
             },
         ],
     };
     
-    run_with_config(config).await
+    // Report every problem at once, before anything is set up
+    let mut check = check_args(&args, &secrets);
+    config.validate(&mut check);
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+    
+    bootstrap(&args.bootstrap)?;
+    let description = description
+        .with_secrets(PAYLOAD_KEY_VARIABLE, secrets.payload_key.as_slice())
+        .with_secrets(SIGNING_KEY_VARIABLE, secrets.signing_key.as_slice());
+    announce_startup(&args.bootstrap, &description.merge(config.describe()))?;
+    
+    // Local development without the external registry process
+    let _registry = match args.registry.as_str() {
+        "http" => None,
+        "inmem" => spawn_inmem_registry(&args.registry_url)?,
+        other => return Err(Error::Validation {
+            message: format!("Unknown registry '{}' (expected http or inmem)", other),
+        }),
+    };
+    
+    let seconds = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+    let grpc_channel = GrpcChannelOptions {
+        keepalive_interval: seconds(args.keepalive_secs),
+        keepalive_timeout: Duration::from_secs(args.keepalive_timeout_secs),
+        keepalive_while_idle: !args.no_keepalive_while_idle,
+        idle_timeout: seconds(args.idle_timeout_secs),
+        ..Default::default()
+    };
+    
+    if let Some(command) = &args.command {
+        let resolve = match (&args.direct_address, args.discovery.as_str()) {
+            (Some(address), _) => check::Resolve::Address(address),
+            (None, "mdns") => check::Resolve::Mdns,
+            (None, "registry") => check::Resolve::Registry(&config.runtime),
+            (None, other) => return Err(Error::Validation {
+                message: format!("Unknown discovery '{}' (expected registry or mdns)", other),
+            }),
+        };
+        return match command {
+            Command::Check(check) => {
+                // What this CLI's gateways could do with the given flags
+                let gateways = GatewayOptions {
+                    protocol_racing: args.race_protocols,
+                    payload_key: secrets.payload_key.as_ref().map(|key| PayloadKey::parse(key.expose())).transpose()?.map(Arc::new),
+                    signing_key: secrets.signing_key.as_ref().map(|key| SigningKey::parse(key.expose())).transpose()?.map(Arc::new),
+                    ..Default::default()
+                };
+                check::run(check, resolve, &grpc_channel, &gateways).await
+            }
+            Command::Info(info) => info::run(info, resolve, &grpc_channel, args.json).await,
+            Command::Schedule(schedule) => schedule::run_schedule(schedule, resolve, &grpc_channel, args.json).await,
+            Command::Cancel(cancel) => schedule::run_cancel(cancel, resolve, &grpc_channel, args.json).await,
+            Command::History(history) => history::run(history, resolve, &grpc_channel, args.json).await,
+            Command::Export(export) => history::run_export(export, resolve, &grpc_channel, args.json).await,
+            Command::Import(import) => history::run_import(import, resolve, &grpc_channel, args.json).await,
+        };
+    }
+    
+    let discovery = match args.discovery.as_str() {
+        // Consul/etcd are asked by the gateways themselves
+        "registry" => match module_registry_backend(&config.runtime)? {
+            Some(backend) => Discovery::Backend(backend),
+            None => Discovery::Registry,
+        },
+        "mdns" => Discovery::mdns(),
+        other => return Err(Error::Validation {
+            message: format!("Unknown discovery '{}' (expected registry or mdns)", other),
+        }),
+    };
+    
+    let slo = slo_objectives(&args);
+    // Ctrl-C while waiting on the registry or the server aborts the start
+    let cancellation = cancel_on_ctrl_c();
+    init_echo_client_module(EchoClientModuleConfig {
+        file: args.file,
+        call_deadline: args.deadline_ms.map(Duration::from_millis),
+        grpc_address: args.direct_address.clone(),
+        grpc_channel,
+        discovery: discovery.clone(),
+        priority: args.priority.parse::<Priority>()?,
+        transforms: parse_transforms(&args.transform)?,
+        caller: args.caller,
+        accept_language: args.accept_language,
+        session_id: args.session,
+        watch_events: args.watch_events,
+        json_output: args.json,
+        hedging: args.hedge_after_ms.map(|ms| HedgingPolicy {
+            delay: Duration::from_millis(ms),
+            ..Default::default()
+        }),
+        coalescing: args.coalesce,
+        batching: args.batch_max.map(|max_messages| BatchingConfig {
+            max_messages,
+            linger: Duration::from_millis(args.batch_linger_ms),
+        }),
+        protocol_racing: args.race_protocols,
+        validators: response_validators(args.expect_echo, args.expect_match.as_deref(), args.max_reply_bytes)?,
+        integrity: args.integrity,
+        payload_key: secrets.payload_key.as_ref().map(|key| PayloadKey::parse(key.expose())).transpose()?.map(Arc::new),
+        signing_key: secrets.signing_key.as_ref().map(|key| SigningKey::parse(key.expose())).transpose()?.map(Arc::new),
+        mirror: args
+            .mirror_http
+            .map(MirrorTarget::Http)
+            .or(args.mirror_grpc.map(MirrorTarget::Grpc))
+            .map(MirrorConfig::new),
+        latency_budgets: args.latency_budget.parse::<LatencyBudgets>()?,
+        warm_standby: warm_standby(&args)?,
+        cancellation: cancellation.clone(),
+        ..Default::default()
+    })?;
+    init_echo_monitor_module(EchoMonitorModuleConfig {
+        grpc_address: args.direct_address,
+        discovery,
+        report_interval: Some(Duration::from_secs(10)),
+        slo,
+        cancellation,
+        ..Default::default()
+    })?;
+    
+    // Fail fast if an enabled module consumes a service nothing provides
+    // ("echo" itself is served by echo-grpc-srv)
+    validate_module_dependencies(&config, &[echo_module_id()])?;
+
+    // Root of every span in the process (see echo_contract::spans);
+    // a Consul/etcd URL stays with the echo modules
+    run_with_config(framework_config(config))
+        .instrument(info_span!("bin", name = env!("CARGO_BIN_NAME"), pid = std::process::id()))
+        .await
+}
+
+/// Keys read from the environment (see [`SECRETS_HELP`]).
+struct Secrets {
+    payload_key: Option<Secret<String>>,
+    signing_key: Option<Secret<String>>,
+}
+
+impl Secrets {
+    fn load() -> Result<Self> {
+        Ok(Self {
+            payload_key: Secret::from_env(PAYLOAD_KEY_VARIABLE)?,
+            signing_key: Secret::from_env(SIGNING_KEY_VARIABLE)?,
+        })
+    }
+}
+
+/// Cross-flag rules clap can't express.
+fn check_args(args: &Args, secrets: &Secrets) -> ConfigCheck {
+    let mut check = ConfigCheck::new();
+    check.require(matches!(args.registry.as_str(), "http" | "inmem"), "--registry", || {
+        format!("unknown registry '{}' (expected http or inmem)", args.registry)
+    });
+    check.require(matches!(args.discovery.as_str(), "registry" | "mdns"), "--discovery", || {
+        format!("unknown discovery '{}' (expected registry or mdns)", args.discovery)
+    });
+    check.require_ok("--priority", args.priority.parse::<Priority>());
+    check.require_ok("--transform", parse_transforms(&args.transform));
+    check.require(args.deadline_ms != Some(0), "--deadline-ms", || "must be positive".to_string());
+    check.require(args.batch_max != Some(0), "--batch-max", || "must batch at least 1 message".to_string());
+    // One objective at a time, so each problem names its own flag
+    let availability = SloObjectives { availability: args.slo_availability, ..Default::default() };
+    check.require_ok("--slo-availability", availability.validate());
+    let latency = SloObjectives { latency: args.slo_latency, ..Default::default() };
+    check.require_ok("--slo-latency", latency.validate());
+    check.require_ok("--latency-budget", args.latency_budget.parse::<LatencyBudgets>());
+    check.require_ok("--warm-standby", warm_standby(args));
+    check.require(args.standby_probe_secs > 0, "--standby-probe-secs", || "must be positive".to_string());
+    check.require_ok(
+        "--expect-match",
+        response_validators(args.expect_echo, args.expect_match.as_deref(), args.max_reply_bytes),
+    );
+    if let Some(key) = &secrets.payload_key {
+        check.require_ok(PAYLOAD_KEY_VARIABLE, PayloadKey::parse(key.expose()));
+    }
+    if let Some(key) = &secrets.signing_key {
+        check.require_ok(SIGNING_KEY_VARIABLE, SigningKey::parse(key.expose()));
+    }
+    if args.keepalive_secs > 0 {
+        check.require(args.keepalive_timeout_secs > 0, "--keepalive-timeout-secs", || {
+            "must be positive while keepalive pings are on".to_string()
+        });
+    }
+    check
+}
+
+/// The monitor's objectives from `--slo-availability` and `--slo-latency`.
+fn slo_objectives(args: &Args) -> SloObjectives {
+    SloObjectives { availability: args.slo_availability, latency: args.slo_latency, ..Default::default() }
+}
+
+/// The standby selected by `--warm-standby` and `--standby-probe-secs`.
+fn warm_standby(args: &Args) -> Result<Option<WarmStandbyConfig>> {
+    let Some(protocol) = &args.warm_standby else {
+        return Ok(None);
+    };
+    let protocol = match protocol.as_str() {
+        "grpc" => Protocol::Grpc,
+        "http" => Protocol::Http,
+        other => return Err(Error::Validation {
+            message: format!("Unknown standby protocol '{}' (expected http or grpc)", other),
+        }),
+    };
+    Ok(Some(WarmStandbyConfig {
+        protocol,
+        probe_interval: Duration::from_secs(args.standby_probe_secs),
+    }))
+}
+
+/// Builds the reply checks selected by `--expect-echo`, `--expect-match`
+/// and `--max-reply-bytes`.
+fn response_validators(
+    expect_echo: bool,
+    expect_match: Option<&str>,
+    max_reply_bytes: Option<usize>,
+) -> Result<Vec<ResponseValidator>> {
+    let mut validators = Vec::new();
+    if expect_echo {
+        validators.push(ResponseValidator::EqualsRequest);
+    }
+    if let Some(pattern) = expect_match {
+        validators.push(ResponseValidator::matches(pattern)?);
+    }
+    if let Some(limit) = max_reply_bytes {
+        validators.push(ResponseValidator::MaxBytes(limit));
+    }
+    Ok(validators)
 }
@@ -0,0 +1,117 @@
+//! `echo-grpc-cli schedule` / `cancel` - server-side scheduled echoes.
+//!
+//! Resolves the echo module like `check` does and asks its first gRPC
+//! endpoint to echo a message later; watch the runs with `--watch-events`
+//! on another client:
+//!
+//! ```bash
+//! echo-grpc-cli --direct-address localhost:50051 schedule "every 1m" "tick"
+//! echo-grpc-cli --direct-address localhost:50051 schedule "0 9 * * 1-5" "good morning"
+//! echo-grpc-cli --direct-address localhost:50051 cancel 6f1c...
+//! ```
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use clap::Args;
+use hsu_common::{Error, Protocol, Result};
+use serde_json::json;
+
+use echo_api::{grpc_echo_service, GatewayOptions};
+use echo_api_grpc::{ChannelPool, GrpcChannelOptions};
+use echo_contract::{EchoSchedule, EchoService};
+
+use crate::check::{resolve_echo, Resolve};
+
+/// Options of the `schedule` command.
+#[derive(Args, Debug)]
+pub struct ScheduleArgs {
+    /// When to echo: "30s", "every 5m" or a cron expression ("*/15 * * * *", UTC)
+    pub schedule: String,
+
+    /// Message to echo
+    pub message: String,
+
+    /// Give up after this many milliseconds
+    #[arg(long, default_value_t = 3000)]
+    pub timeout_ms: u64,
+}
+
+/// Options of the `cancel` command.
+#[derive(Args, Debug)]
+pub struct CancelArgs {
+    /// Job ID printed by `schedule`
+    pub job_id: String,
+
+    /// Give up after this many milliseconds
+    #[arg(long, default_value_t = 3000)]
+    pub timeout_ms: u64,
+}
+
+/// Schedules the echo and prints the job ID and first run.
+pub async fn run_schedule(args: &ScheduleArgs, resolve: Resolve<'_>, channel: &GrpcChannelOptions, json: bool) -> Result<()> {
+    // Reject a malformed schedule before looking up the server
+    let schedule: EchoSchedule = args.schedule.parse()?;
+    let scheduled = with_service(resolve, channel, Duration::from_millis(args.timeout_ms), |service| {
+        let message = args.message.as_str().into();
+        let schedule = schedule.clone();
+        async move { service.schedule_echo(message, schedule).await }
+    }).await?;
+
+    let next_run_unix_ms = scheduled.next_run.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+    if json {
+        println!("{}", json!({
+            "job_id": scheduled.job_id,
+            "schedule": schedule.to_string(),
+            "next_run_unix_ms": next_run_unix_ms,
+        }));
+    } else {
+        let in_secs = scheduled.next_run.duration_since(SystemTime::now()).unwrap_or_default().as_secs();
+        println!("job_id: {}\nschedule: {}\nnext_run: in {}s", scheduled.job_id, schedule, in_secs);
+    }
+    Ok(())
+}
+
+/// Cancels the job; fails if it wasn't pending.
+pub async fn run_cancel(args: &CancelArgs, resolve: Resolve<'_>, channel: &GrpcChannelOptions, json: bool) -> Result<()> {
+    let cancelled = with_service(resolve, channel, Duration::from_millis(args.timeout_ms), |service| {
+        let job_id = args.job_id.clone();
+        async move { service.cancel_scheduled_echo(job_id).await }
+    }).await?;
+
+    if json {
+        println!("{}", json!({ "job_id": args.job_id, "cancelled": cancelled }));
+    }
+    if !cancelled {
+        return Err(Error::Validation {
+            message: format!("No scheduled echo '{}' is pending", args.job_id),
+        });
+    }
+    if !json {
+        println!("cancelled: {}", args.job_id);
+    }
+    Ok(())
+}
+
+/// Runs `call` against the first gRPC endpoint of the echo module.
+///
+/// Jobs live on one server instance, so the call isn't spread over
+/// endpoints like `info`'s.
+pub(crate) async fn with_service<T, F, Fut>(resolve: Resolve<'_>, channel: &GrpcChannelOptions, timeout: Duration, call: F) -> Result<T>
+where
+    F: FnOnce(Arc<dyn EchoService>) -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let (_, apis) = resolve_echo(resolve, timeout).await?;
+    let Some((_, address)) = apis.iter().find(|(protocol, _)| *protocol == Protocol::Grpc) else {
+        return Err(Error::Protocol(format!("The echo module has no gRPC endpoint ({} found)", apis.len())));
+    };
+
+    let options = GatewayOptions {
+        deadline: Some(timeout),
+        channel_pool: Arc::new(ChannelPool::new(channel.clone())),
+        ..Default::default()
+    };
+    let result = call(grpc_echo_service(address, &options)).await;
+    options.channel_pool.close();
+    result
+}
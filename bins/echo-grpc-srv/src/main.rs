@@ -7,6 +7,9 @@
 //! 3. **gRPC Server** - Framework-managed protocol server
 //! 4. **Service Registry** - Automatic API publishing
 //! 5. **Complete Framework Integration** - Runtime manages everything!
+//! 6. **Managed Unit** (`--supervise`) - The server as a child process,
+//!    health-checked over `grpc.health.v1` and restarted on crash; with
+//!    `--pipe` also called over its stdin/stdout instead of TCP
 //!
 //! # Architecture (Updated to NEW PATTERN!)
 //!
@@ -32,53 +35,752 @@
 //! - ✅ Framework creates modules from registry
 //! - ✅ Much less boilerplate!
 
-use clap::Parser;
-use hsu_common::{ModuleID, Protocol, Result};
+use std::ffi::OsString;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use clap::{CommandFactory, Parser, Subcommand};
+use hsu_common::{Error, Protocol, Result};
 use hsu_module_api::{Config, ModuleConfig, RuntimeConfig, ServiceRegistryConfig, ProtocolServerConfig, run_with_config};
+use tracing::{debug, info, info_span, Instrument};
 
-use echo_server::{init_echo_server_module, EchoServerModuleConfig};
+use echo_api::{
+    AdaptiveConcurrencyConfig, AimdConfig, AuditSink, ByteQuotaConfig, ChaosConfig, ControllerKind, GradientConfig,
+    GatewayOptions, JsonTranscoding, ManagedUnit, ManagedUnitConfig, NdjsonAuditSink, PayloadKey, PayloadKeyring,
+    PipeChannel, PriorityLanesConfig, SlowStartConfig,
+    cancel_on_ctrl_c, framework_config, module_registry_backend, new_standalone_echo_service_gateways,
+    validate_module_dependencies,
+};
+use echo_api_grpc::{SignatureVerifier, SigningKey};
+use echo_contract::{echo_module_id, EchoServiceGateways, Secret};
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, load_secrets, parse_described, parse_listen_addresses,
+    spawn_inmem_registry, BootstrapArgs, ConfigCheck, ConfigDescription, Describe, PidFile, Runtimes, Validate,
+};
+use echo_server::{
+    init_echo_server_module, CanaryConfig, EchoServerModuleConfig, EchoServiceConfig, HistoryStore, IdStrategy, InMemoryHistoryStore,
+    ResponseDecoration, RetentionConfig, SchedulerConfig, SelfTestConfig, SessionConfig,
+};
+
+/// Payload encryption keys, `ID:HEX` each.
+const PAYLOAD_KEYS_VARIABLE: &str = "ECHO_PAYLOAD_KEYS";
+/// Request signing keys, `CALLER:HEX` each.
+const SIGNING_KEYS_VARIABLE: &str = "ECHO_SIGNING_KEYS";
+
+/// Where the keys come from (never flags: they'd show in `ps` and the config dump).
+const SECRETS_HELP: &str = "Keys (comma/whitespace separated, or one per line of the *_FILE file):
+  ECHO_PAYLOAD_KEYS[_FILE]  ID:<64 hex digits>, one per client module: open sealed messages, seal replies, refuse plain ones
+  ECHO_SIGNING_KEYS[_FILE]  CALLER:HEX, one per calling module: accept only signed requests, each once";
 
 /// Command-line arguments
 #[derive(Parser, Debug)]
-#[command(author, version, about = "Echo gRPC Server with full HSU framework")]
+#[command(author, version, about = "Echo gRPC Server with full HSU framework", after_help = SECRETS_HELP)]
 struct Args {
     /// Port to listen on (0 = dynamic allocation)
     #[arg(short, long, default_value = "0")]
     port: u16,
     
-    /// Service registry URL
+    /// Address to bind, repeatable (e.g. 127.0.0.1:50051, [::]:0, ::1); default 0.0.0.0:<port>
+    #[arg(long = "listen")]
+    listen: Vec<String>,
+    
+    /// Service registry URL: http://host:port (HSU registry), consul://host:port or etcd://host:port
     #[arg(short, long, default_value = "http://localhost:8080")]
     registry_url: String,
+    
+    /// Host published to Consul/etcd with the bound ports (what clients dial)
+    #[arg(long, default_value = "localhost")]
+    advertise_host: String,
+    
+    /// Registry: http (external registry at --registry-url) or inmem
+    /// (in-memory registry hosted at --registry-url by the first process started)
+    #[arg(long, default_value = "http")]
+    registry: String,
+    
+    /// Admit at most N concurrent calls, queueing the rest in weighted priority lanes
+    #[arg(long, value_name = "N")]
+    priority_lanes: Option<usize>,
+    
+    /// With --priority-lanes: reject calls beyond this many queued (RESOURCE_EXHAUSTED + retry-after)
+    #[arg(long, value_name = "N", requires = "priority_lanes")]
+    max_queued: Option<usize>,
+    
+    /// Reject callers (`x-echo-caller`) echoing more than BYTES per
+    /// --byte-quota-window-secs (RESOURCE_EXHAUSTED + retry-after)
+    #[arg(long, value_name = "BYTES")]
+    byte_quota: Option<u64>,
+    
+    /// Rolling window of --byte-quota and --byte-accounting
+    #[arg(long, default_value_t = 60)]
+    byte_quota_window_secs: u64,
+    
+    /// Count bytes echoed per caller without a limit (admin /quotas and /metrics)
+    #[arg(long, conflicts_with = "byte_quota")]
+    byte_accounting: bool,
+    
+    /// After start, ramp the concurrent calls admitted from --slow-start-from
+    /// to --slow-start-to over SECS (rejected: RESOURCE_EXHAUSTED + retry-after)
+    #[arg(long, value_name = "SECS")]
+    slow_start_secs: Option<u64>,
+    
+    /// With --slow-start-secs: concurrent calls admitted right after start
+    #[arg(long, value_name = "N", default_value_t = 4)]
+    slow_start_from: usize,
+    
+    /// With --slow-start-secs: concurrent calls admitted once ramped up
+    #[arg(long, value_name = "N", default_value_t = 64)]
+    slow_start_to: usize,
+    
+    /// Adapt the concurrency limit to observed latency: aimd or gradient
+    #[arg(long, value_name = "ALGORITHM")]
+    adaptive_limit: Option<String>,
+    
+    /// Expire echo sessions idle for this many seconds
+    #[arg(long, default_value_t = 300)]
+    session_idle_secs: u64,
+    
+    /// Also accept JSON requests (HTTP/1 POST) on every gRPC port
+    #[arg(long)]
+    json_transcoding: bool,
+    
+    /// Accept JSON requests only on this gRPC port, repeatable
+    #[arg(long = "json-transcoding-port", value_name = "PORT", conflicts_with = "json_transcoding")]
+    json_transcoding_ports: Vec<u16>,
+    
+    /// Let browser pages from this origin call the JSON endpoints (`*`: any origin), repeatable
+    #[arg(long = "cors-origin", value_name = "ORIGIN")]
+    cors_origins: Vec<String>,
+    
+    /// Record every call with its response to this NDJSON file (replay with echo-replay)
+    #[arg(long, value_name = "PATH")]
+    record: Option<PathBuf>,
+    
+    /// Advertise the gRPC endpoints over mDNS (clients use --discovery mdns)
+    #[arg(long)]
+    mdns: bool,
+    
+    /// Tag every response with this instance (instance-id, server-version
+    /// metadata) and a request-id, so clients behind a load balancer see
+    /// who served them
+    #[arg(long)]
+    tag_responses: bool,
+    
+    /// Prepend this to every text reply (e.g. "[srv-a] ")
+    #[arg(long)]
+    response_prefix: Option<String>,
+    
+    /// Append this to every text reply
+    #[arg(long)]
+    response_suffix: Option<String>,
+    
+    /// Attach key=value to every response as gRPC metadata, repeatable
+    #[arg(long = "response-metadata", value_name = "KEY=VALUE")]
+    response_metadata: Vec<String>,
+    
+    /// Run a second implementation ("v2") next to the regular one and route
+    /// this share of the echo calls to it (change it with POST
+    /// /maintenance/echo/traffic-split on --admin-addr)
+    #[arg(long, value_name = "PERCENT")]
+    canary_percent: Option<u8>,
+    
+    /// With --canary-percent: prepended to the canary's text replies
+    #[arg(long, default_value = "[v2] ")]
+    canary_prefix: String,
+    
+    /// Echo through the Direct path and every gRPC port before reporting
+    /// Ready; refuse to start if it fails (result on the admin /health)
+    #[arg(long)]
+    self_test: bool,
+    
+    /// Accept scheduled echoes (schedule_echo: "30s", "every 5m", cron)
+    #[arg(long)]
+    scheduler: bool,
+    
+    /// Keep scheduled echoes in this JSON file across restarts (implies --scheduler)
+    #[arg(long, value_name = "PATH")]
+    job_store: Option<PathBuf>,
+    
+    /// Keep a history of echo calls (in memory); events are published
+    /// through its transactional outbox
+    #[arg(long)]
+    history: bool,
+    
+    /// Keep the history in this SQLite database across restarts (implies --history)
+    #[cfg(feature = "sqlite")]
+    #[arg(long, value_name = "PATH")]
+    history_db: Option<PathBuf>,
+    
+    /// Prune history entries older than this many seconds (manual purge:
+    /// POST /maintenance/echo/purge-history on --admin-addr)
+    #[arg(long, value_name = "SECS")]
+    history_max_age_secs: Option<u64>,
+    
+    /// Keep at most this many history entries, pruning the oldest
+    #[arg(long, value_name = "N")]
+    history_max_entries: Option<usize>,
+    
+    /// How request, session, job and history record IDs are generated:
+    /// uuid-v4, uuid-v7 or snowflake:WORKER (each instance its own worker)
+    #[arg(long, default_value = "uuid-v4", value_name = "STRATEGY")]
+    id_strategy: String,
+    
+    /// How long POST /maintenance/echo/enter-maintenance waits for calls in
+    /// flight to finish before answering
+    #[arg(long, default_value = "30", value_name = "SECS")]
+    maintenance_drain_secs: u64,
+    
+    /// Reject signed requests whose timestamp is further than this from our clock
+    #[arg(long, default_value_t = 30, value_name = "SECS")]
+    signature_max_skew_secs: u64,
+    
+    /// Write the PID here and refuse to start if another instance holds it
+    #[arg(long)]
+    pid_file: Option<PathBuf>,
+    
+    /// Run the server as a supervised child process (managed unit),
+    /// restarted when it crashes or fails health checks; needs a fixed
+    /// --port or --listen port (the admin endpoint and PID file stay here)
+    #[arg(long)]
+    supervise: bool,
+    
+    /// With --supervise: seconds between gRPC health checks of the child
+    #[arg(long, value_name = "SECS", default_value_t = 2, requires = "supervise")]
+    health_interval_secs: u64,
+    
+    /// With --supervise: give up and exit with an error after this many restarts
+    #[arg(long, value_name = "N", requires = "supervise")]
+    max_restarts: Option<u32>,
+    
+    /// With --supervise: also call the child over its stdin/stdout
+    /// (length-prefixed frames, no TCP) and probe it that way
+    #[arg(long, requires = "supervise")]
+    pipe: bool,
+    
+    /// Serve echo calls over stdin/stdout (set by a --pipe parent)
+    #[arg(long, hide = true)]
+    serve_stdio: bool,
+    
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve with injected faults, to demo client resilience (change them
+    /// live: POST /maintenance/echo/chaos -d 'delay=20%:500ms,error=5%,drop=30s' or 'off')
+    Chaos(ChaosArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct ChaosArgs {
+    /// Delay this share of the calls by --delay-ms
+    #[arg(long, value_name = "PERCENT", default_value_t = 0)]
+    delay_percent: u8,
+    
+    /// How long a delayed call waits
+    #[arg(long, value_name = "MS", default_value_t = 200)]
+    delay_ms: u64,
+    
+    /// Fail this share of the calls with UNAVAILABLE
+    #[arg(long, value_name = "PERCENT", default_value_t = 0)]
+    error_percent: u8,
+    
+    /// Drop the connections this often: calls in flight and open streams fail with UNAVAILABLE
+    #[arg(long, value_name = "SECS")]
+    drop_every_secs: Option<u64>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
-    tracing_subscriber::fmt::init();
+impl ChaosArgs {
+    fn config(&self) -> ChaosConfig {
+        ChaosConfig {
+            delay_percent: self.delay_percent,
+            delay: Duration::from_millis(self.delay_ms),
+            error_percent: self.error_percent,
+            drop_every: self.drop_every_secs.map(Duration::from_secs),
+        }
+    }
+}
+
+/// jemalloc as the global allocator, so `/debug/memory` has stats to report.
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+fn main() -> Result<()> {
+    let (args, description) = parse_described::<Args>();
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    let secrets = Secrets::load()?;
     
-    init_echo_server_module(EchoServerModuleConfig::default())?;
+    // One gRPC server per listen address
+    let listen = parse_listen_addresses(&args.listen, args.port)?;
+    let servers = listen
+        .iter()
+        .map(|address| ProtocolServerConfig {
+            protocol: Protocol::Grpc,
+            listen_address: address.to_string(),
+        })
+        .collect();
+    let runtime = RuntimeConfig {
+        service_registry: ServiceRegistryConfig {
+            url: args.registry_url.clone(),
+        },
+        servers,
+    };
     
     // Configure runtime with gRPC protocol server
     let config = Config {
-        runtime: RuntimeConfig {
-            service_registry: ServiceRegistryConfig {
-                url: args.registry_url,
-            },
-            servers: vec![
-                ProtocolServerConfig {
-                    protocol: Protocol::Grpc,
-                    listen_address: format!("0.0.0.0:{}", args.port),
-                },
-            ],
-        },
+        runtime,
         modules: vec![
             ModuleConfig {
-                id: ModuleID::from("echo"),
+                id: echo_module_id(),
                 enabled: true,
                 servers: vec![],
             },
         ],
     };
     
-    run_with_config(config).await
+    // Report every problem at once, before anything is set up
+    let mut check = check_args(&args, &secrets);
+    config.validate(&mut check);
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+    
+    bootstrap(&args.bootstrap)?;
+    let description = description
+        .with_secrets(PAYLOAD_KEYS_VARIABLE, &secrets.payload_keys)
+        .with_secrets(SIGNING_KEYS_VARIABLE, &secrets.signing_keys);
+    announce_startup(&args.bootstrap, &description.merge(config.describe()))?;
+    
+    // First to be acquired, so last to be dropped: the file goes away only
+    // once the registry, modules and servers below have shut down. With
+    // --supervise the parent holds it: it's the unit operators start and stop.
+    let _pid_file = args.pid_file.as_deref().map(PidFile::acquire).transpose()?;
+    
+    // The child runs the modules; this process only supervises it
+    if args.supervise {
+        return supervise(&args).await;
+    }
+    
+    // Local development without the external registry process
+    let _registry = match args.registry.as_str() {
+        "http" => None,
+        "inmem" => spawn_inmem_registry(&args.registry_url)?,
+        other => return Err(Error::Validation {
+            message: format!("Unknown registry '{}' (expected http or inmem)", other),
+        }),
+    };
+    
+    let adaptive_concurrency = match args.adaptive_limit.as_deref() {
+        None => None,
+        Some("aimd") => Some(ControllerKind::Aimd(AimdConfig::default())),
+        Some("gradient") => Some(ControllerKind::Gradient(GradientConfig::default())),
+        Some(other) => return Err(Error::Validation {
+            message: format!("Unknown adaptive limit '{}' (expected aimd or gradient)", other),
+        }),
+    }
+    .map(|controller| AdaptiveConcurrencyConfig { controller, ..Default::default() });
+    
+    let response_decoration = response_decoration(&args)?;
+    let id_strategy: IdStrategy = args.id_strategy.parse()?;
+    // Ctrl-C during a slow start (history migration, registry) aborts it
+    let cancellation = cancel_on_ctrl_c();
+    init_echo_server_module(EchoServerModuleConfig {
+        service: EchoServiceConfig {
+            sessions: SessionConfig {
+                idle_timeout: Duration::from_secs(args.session_idle_secs),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        adaptive_concurrency,
+        priority_lanes: args.priority_lanes.map(|max_concurrent| PriorityLanesConfig {
+            max_concurrent,
+            max_queued: args.max_queued,
+            ..Default::default()
+        }),
+        slow_start: args.slow_start_secs.map(|secs| SlowStartConfig {
+            initial: args.slow_start_from,
+            max_concurrent: args.slow_start_to,
+            ramp: Duration::from_secs(secs),
+            ..Default::default()
+        }),
+        byte_quota: (args.byte_quota.is_some() || args.byte_accounting).then(|| ByteQuotaConfig {
+            limit_bytes: args.byte_quota,
+            window: Duration::from_secs(args.byte_quota_window_secs),
+            ..Default::default()
+        }),
+        audit_sink: match &args.record {
+            Some(path) => Some(Arc::new(NdjsonAuditSink::create(path)?) as Arc<dyn AuditSink>),
+            None => None,
+        },
+        json_transcoding: if args.json_transcoding {
+            JsonTranscoding::AllServers
+        } else if !args.json_transcoding_ports.is_empty() {
+            JsonTranscoding::Ports(args.json_transcoding_ports.clone())
+        } else {
+            JsonTranscoding::Disabled
+        },
+        cors_origins: args.cors_origins.clone(),
+        mdns_advertise: args.mdns,
+        // Consul/etcd: the module publishes itself (the framework speaks HSU registry only)
+        registry_backend: module_registry_backend(&config.runtime)?,
+        advertise_host: args.advertise_host,
+        self_test: args.self_test.then(|| SelfTestConfig { listen: listen.clone(), ..Default::default() }),
+        response_decoration,
+        scheduler: (args.scheduler || args.job_store.is_some()).then(|| SchedulerConfig {
+            store_path: args.job_store.clone(),
+            ..Default::default()
+        }),
+        history_store: history_store(&args)?,
+        history_retention: (args.history_max_age_secs.is_some() || args.history_max_entries.is_some())
+            .then(|| RetentionConfig {
+                max_age: args.history_max_age_secs.map(Duration::from_secs),
+                max_entries: args.history_max_entries,
+                ..Default::default()
+            }),
+        id_generator: Some(id_strategy.generator()?),
+        payload_keys: payload_keys(&secrets.payload_keys)?,
+        request_signing: signature_verifier(&secrets.signing_keys, Duration::from_secs(args.signature_max_skew_secs))?,
+        maintenance_drain_timeout: Duration::from_secs(args.maintenance_drain_secs),
+        canary: args.canary_percent.map(|percent| CanaryConfig {
+            percent,
+            decoration: Some(ResponseDecoration::default().with_prefix(args.canary_prefix.as_str())),
+            ..Default::default()
+        }),
+        chaos: args.command.as_ref().map(|Command::Chaos(chaos)| chaos.config()),
+        stdio_pipe: args.serve_stdio,
+        cancellation,
+        ..Default::default()
+    })?;
+    
+    // Fail fast if an enabled module consumes a service nothing provides
+    validate_module_dependencies(&config, &[])?;
+
+    // Root of every span in the process (see echo_contract::spans);
+    // a Consul/etcd URL stays with the echo modules
+    run_with_config(framework_config(config))
+        .instrument(info_span!("bin", name = env!("CARGO_BIN_NAME"), pid = std::process::id()))
+        .await
+}
+
+/// Flags the supervising parent keeps to itself (long names).
+///
+/// The logging flags are the parent's too: two processes can't share a
+/// rotating log file or a shipping connection, so the child logs to the
+/// terminal (stderr with `--pipe`) with the default settings.
+const PARENT_ONLY_FLAGS: [&str; 14] = [
+    "supervise",
+    "health-interval-secs",
+    "max-restarts",
+    "pipe",
+    "admin-addr",
+    "pid-file",
+    "config-dump",
+    "validate-config",
+    "log",
+    "log-format",
+    "log-file",
+    "log-stderr",
+    "log-rotation",
+    "log-ship",
+];
+
+/// Runs this server as a managed unit: a child process with the same
+/// flags (but the parent-only ones), supervised until Ctrl-C.
+async fn supervise(args: &Args) -> Result<()> {
+    let address = parse_listen_addresses(&args.listen, args.port)?
+        .into_iter()
+        .find(|address| address.port() != 0)
+        .ok_or_else(|| Error::Validation {
+            message: "--supervise needs a fixed port (--port or --listen)".to_string(),
+        })?;
+    // A wildcard bind is checked over loopback
+    let ip: IpAddr = match address.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => Ipv4Addr::LOCALHOST.into(),
+        IpAddr::V6(ip) if ip.is_unspecified() => Ipv6Addr::LOCALHOST.into(),
+        ip => ip,
+    };
+    let program = std::env::current_exe().map_err(|e| Error::Validation {
+        message: format!("Can't find our own executable to supervise: {}", e),
+    })?;
+    let mut child_args = child_args(std::env::args_os().skip(1));
+    let pipe = args.pipe.then(|| Arc::new(PipeChannel::new(echo_module_id().to_string())));
+    if pipe.is_some() {
+        // stdout carries the frames
+        child_args.push("--serve-stdio".into());
+        child_args.push("--log-stderr".into());
+    }
+    let health_interval = Duration::from_secs(args.health_interval_secs);
+    let unit = ManagedUnit::new(ManagedUnitConfig {
+        name: echo_module_id().to_string(),
+        program,
+        args: child_args,
+        health_address: format!("http://{}", SocketAddr::new(ip, address.port())),
+        health_interval,
+        max_restarts: args.max_restarts,
+        pipe: pipe.clone(),
+        ..Default::default()
+    });
+    
+    let cancellation = cancel_on_ctrl_c();
+    let supervision = async {
+        match pipe {
+            Some(pipe) => {
+                // The parent's own calls route over the pipe like any Direct call
+                let gateways = new_standalone_echo_service_gateways(GatewayOptions {
+                    pipe: Some(pipe.clone()),
+                    ..Default::default()
+                });
+                tokio::select! {
+                    result = unit.run(&cancellation) => result,
+                    never = probe_pipe(&pipe, gateways, health_interval) => match never {},
+                }
+            }
+            None => unit.run(&cancellation).await,
+        }
+    };
+    supervision
+        .instrument(info_span!("bin", name = env!("CARGO_BIN_NAME"), pid = std::process::id()))
+        .await
+}
+
+/// Calls `get_info` through `gateways` (Direct, so over the child's
+/// pipe) every `interval`, logging each new child instance it reaches
+/// and how long the round trip took.
+async fn probe_pipe(
+    pipe: &Arc<PipeChannel>,
+    gateways: Arc<dyn EchoServiceGateways>,
+    interval: Duration,
+) -> std::convert::Infallible {
+    let mut reached = None;
+    loop {
+        tokio::time::sleep(interval).await;
+        if !pipe.is_attached() {
+            continue;
+        }
+        let started = Instant::now();
+        let info = match gateways.get_service(Protocol::Direct).await {
+            Ok(service) => service.get_info().await,
+            Err(e) => Err(e),
+        };
+        match info {
+            Ok(info) if reached.as_ref() != Some(&info.instance_id) => {
+                info!("✅ Reached {} instance {} over the stdio pipe in {:?}",
+                    pipe.name(), info.instance_id, started.elapsed());
+                reached = Some(info.instance_id);
+            }
+            Ok(_) => {}
+            Err(e) => debug!("Pipe probe of {} failed: {}", pipe.name(), e),
+        }
+    }
+}
+
+/// This process's arguments without [`PARENT_ONLY_FLAGS`]: the child's.
+///
+/// Which flags take a value comes from the parser, so a value is always
+/// kept or dropped with its flag, whether given as `--port 50051`,
+/// `--port=50051`, `-p 50051` or `-p50051`.
+fn child_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let command = Args::command();
+    let mut child = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let text = arg.to_string_lossy().into_owned();
+        if text == "--" {
+            child.push(arg);
+            child.extend(args);
+            break;
+        }
+        let (flag, separate_value) = if let Some(long) = text.strip_prefix("--") {
+            let (name, inline_value) = match long.split_once('=') {
+                Some((name, _)) => (name, true),
+                None => (long, false),
+            };
+            (command.get_arguments().find(|flag| flag.get_long() == Some(name)), !inline_value)
+        } else if let Some(short) = text.strip_prefix('-').and_then(|rest| rest.chars().next()) {
+            (command.get_arguments().find(|flag| flag.get_short() == Some(short)), text.len() == 2)
+        } else {
+            (None, false)
+        };
+        let value = match flag {
+            Some(flag) if separate_value && flag.get_action().takes_values() => args.next(),
+            _ => None,
+        };
+        let parent_only = flag.and_then(|flag| flag.get_long()).is_some_and(|long| PARENT_ONLY_FLAGS.contains(&long));
+        if !parent_only {
+            child.push(arg);
+            child.extend(value);
+        }
+    }
+    child
+}
+
+/// Keys read from the environment (see [`SECRETS_HELP`]).
+struct Secrets {
+    payload_keys: Vec<Secret<String>>,
+    signing_keys: Vec<Secret<String>>,
+}
+
+impl Secrets {
+    fn load() -> Result<Self> {
+        Ok(Self {
+            payload_keys: load_secrets(PAYLOAD_KEYS_VARIABLE)?,
+            signing_keys: load_secrets(SIGNING_KEYS_VARIABLE)?,
+        })
+    }
+}
+
+/// Cross-flag rules clap can't express.
+fn check_args(args: &Args, secrets: &Secrets) -> ConfigCheck {
+    let mut check = ConfigCheck::new();
+    check.require(matches!(args.registry.as_str(), "http" | "inmem"), "--registry", || {
+        format!("unknown registry '{}' (expected http or inmem)", args.registry)
+    });
+    if let Some(algorithm) = &args.adaptive_limit {
+        check.require(matches!(algorithm.as_str(), "aimd" | "gradient"), "--adaptive-limit", || {
+            format!("unknown algorithm '{}' (expected aimd or gradient)", algorithm)
+        });
+    }
+    check.require(args.priority_lanes != Some(0), "--priority-lanes", || "must admit at least 1 call".to_string());
+    if args.slow_start_secs.is_some() {
+        check.require(args.slow_start_from <= args.slow_start_to, "--slow-start-from", || {
+            format!("{} is above --slow-start-to ({})", args.slow_start_from, args.slow_start_to)
+        });
+    }
+    if args.byte_quota.is_some() || args.byte_accounting {
+        check.require(args.byte_quota_window_secs > 0, "--byte-quota-window-secs", || "must be positive".to_string());
+    }
+    check.require(args.session_idle_secs > 0, "--session-idle-secs", || "must be positive".to_string());
+    if let Some(percent) = args.canary_percent {
+        check.require(percent <= 100, "--canary-percent", || format!("{} is above 100", percent));
+    }
+    if let Some(Command::Chaos(chaos)) = &args.command {
+        check.require_ok("chaos", chaos.config().validate());
+    }
+
+    // A JSON port must be one the server binds (dynamic ports can't be named up front)
+    let ports: Vec<u16> = match parse_listen_addresses(&args.listen, args.port) {
+        Ok(addresses) => addresses.iter().map(|address| address.port()).collect(),
+        Err(e) => {
+            check.problem("--listen", e.to_string());
+            Vec::new()
+        }
+    };
+    if args.supervise {
+        check.require(ports.iter().any(|port| *port != 0), "--supervise", || {
+            "the child is health-checked on a fixed port (--port or --listen)".to_string()
+        });
+        check.require(args.health_interval_secs > 0, "--health-interval-secs", || "must be positive".to_string());
+    }
+    if args.serve_stdio {
+        // Log lines on stdout would corrupt the frames
+        check.require(args.bootstrap.log_stderr || args.bootstrap.log_file.is_some(), "--serve-stdio", || {
+            "needs --log-stderr or --log-file".to_string()
+        });
+    }
+    if !args.cors_origins.is_empty() {
+        check.require(args.json_transcoding || !args.json_transcoding_ports.is_empty(), "--cors-origin", || {
+            "needs --json-transcoding or --json-transcoding-port".to_string()
+        });
+    }
+    for port in &args.json_transcoding_ports {
+        check.require(ports.contains(port), "--json-transcoding-port", || {
+            format!("{} is not a port the server listens on ({:?})", port, ports)
+        });
+    }
+
+    #[cfg(feature = "sqlite")]
+    let history = args.history || args.history_db.is_some();
+    #[cfg(not(feature = "sqlite"))]
+    let history = args.history;
+    if args.history_max_age_secs.is_some() || args.history_max_entries.is_some() {
+        check.require(history, "--history-max-age-secs/--history-max-entries", || {
+            "history retention needs --history or --history-db".to_string()
+        });
+    }
+
+    check.require_ok("--id-strategy", args.id_strategy.parse::<IdStrategy>());
+    for key in &secrets.payload_keys {
+        check.require_ok(PAYLOAD_KEYS_VARIABLE, PayloadKey::parse(key.expose()));
+    }
+    for key in &secrets.signing_keys {
+        check.require_ok(SIGNING_KEYS_VARIABLE, SigningKey::parse(key.expose()));
+    }
+    if !secrets.signing_keys.is_empty() {
+        check.require(args.signature_max_skew_secs > 0, "--signature-max-skew-secs", || "must be positive".to_string());
+    }
+    for entry in &args.response_metadata {
+        check.require(entry.contains('='), "--response-metadata", || format!("'{}' is not KEY=VALUE", entry));
+    }
+    check
+}
+
+/// Builds the response decoration from the `--tag-responses`/`--response-*` flags.
+fn response_decoration(args: &Args) -> Result<Option<ResponseDecoration>> {
+    let decorated = args.tag_responses
+        || args.response_prefix.is_some()
+        || args.response_suffix.is_some()
+        || !args.response_metadata.is_empty();
+    if !decorated {
+        return Ok(None);
+    }
+    let mut decoration = if args.tag_responses {
+        ResponseDecoration::instance().with_request_ids()
+    } else {
+        ResponseDecoration::default()
+    };
+    if let Some(prefix) = &args.response_prefix {
+        decoration = decoration.with_prefix(prefix.as_str());
+    }
+    if let Some(suffix) = &args.response_suffix {
+        decoration = decoration.with_suffix(suffix.as_str());
+    }
+    for entry in &args.response_metadata {
+        let (key, value) = entry.split_once('=').ok_or_else(|| Error::Validation {
+            message: format!("Invalid --response-metadata '{}' (expected KEY=VALUE)", entry),
+        })?;
+        decoration = decoration.with_metadata(key.trim().to_ascii_lowercase(), value.trim());
+    }
+    Ok(Some(decoration))
+}
+
+/// Builds the keyring of `ECHO_PAYLOAD_KEYS` (encryption off without keys).
+fn payload_keys(keys: &[Secret<String>]) -> Result<Option<Arc<PayloadKeyring>>> {
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    let mut keyring = PayloadKeyring::new();
+    for key in keys {
+        keyring = keyring.with_key(PayloadKey::parse(key.expose())?);
+    }
+    Ok(Some(Arc::new(keyring)))
+}
+
+/// Builds the verifier of `ECHO_SIGNING_KEYS` (signatures unchecked without keys).
+fn signature_verifier(keys: &[Secret<String>], max_skew: Duration) -> Result<Option<SignatureVerifier>> {
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    let mut verifier = SignatureVerifier::new().with_max_skew(max_skew);
+    for key in keys {
+        verifier = verifier.with_key(SigningKey::parse(key.expose())?);
+    }
+    Ok(Some(verifier))
+}
+
+/// Opens the history store from the `--history`/`--history-db` flags.
+fn history_store(args: &Args) -> Result<Option<Arc<dyn HistoryStore>>> {
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = &args.history_db {
+        return Ok(Some(Arc::new(echo_server::SqliteHistoryStore::open(path)?)));
+    }
+    Ok(args.history.then(|| Arc::new(InMemoryHistoryStore::new()) as Arc<dyn HistoryStore>))
 }
@@ -0,0 +1,109 @@
+//! Echo Host - Direct closure to a module loaded at runtime (experimental).
+//!
+//! # What This Demonstrates
+//!
+//! Like echo-direct-cli, but the echo server isn't linked in: it's built
+//! separately as a `cdylib` (`echo-server-plugin`) and loaded from
+//! `--plugin` at startup. The client module can't tell the difference -
+//! it calls the plugin by direct closure, through the stable ABI of
+//! `echo_plugin`:
+//!
+//! ```text
+//! main.rs (this file)
+//!     ↓ calls
+//! echo_plugin::init(path) + echo_client::init()
+//!     ↓ loads the plugin, registers descriptors
+//! Framework Registry → run_with_config
+//!     ↓
+//! echo-client ──Direct──→ module "echo" ──ABI──→ libecho_server_plugin.so
+//! ```
+//!
+//! ```bash
+//! cargo build --release -p echo-server-plugin
+//! cargo run --release --bin echo-host -- --plugin target/release/libecho_server_plugin.so
+//! ```
+
+use std::path::PathBuf;
+use clap::Parser;
+use hsu_module_api::{Config, ModuleConfig, run_with_config};
+use tracing::{info_span, Instrument};
+use hsu_common::{ModuleID, Result};
+
+use echo_plugin::{init_echo_plugin_module, EchoPluginModuleConfig};
+use echo_client::{init_echo_client_module, EchoClientModuleConfig};
+use echo_api::{cancel_on_ctrl_c, validate_module_dependencies};
+use echo_contract::{echo_module_id, ECHO_CLIENT_MODULE_ID};
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, parse_described, BootstrapArgs, ConfigCheck, ConfigDescription,
+    Describe, Runtimes, Validate,
+};
+
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Echo plugin host demo")]
+struct Args {
+    /// Plugin library serving the echo module
+    /// (e.g. target/release/libecho_server_plugin.so)
+    #[arg(long)]
+    plugin: PathBuf,
+
+    /// Print the reply and how it was delivered (protocol, endpoint,
+    /// latency, attempts) as one JSON line on stdout
+    #[arg(long)]
+    json: bool,
+
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+}
+
+fn main() -> Result<()> {
+    let (args, description) = parse_described::<Args>();
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    // Configure, and report every problem before anything is set up
+    let config = Config {
+        runtime: Default::default(),
+        modules: vec![
+            ModuleConfig {
+                id: echo_module_id(),
+                enabled: true,
+                servers: vec![],
+            },
+            ModuleConfig {
+                id: ModuleID::from(ECHO_CLIENT_MODULE_ID),
+                enabled: true,
+                servers: vec![],
+            },
+        ],
+    };
+    let mut check = ConfigCheck::new();
+    config.validate(&mut check);
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+
+    bootstrap(&args.bootstrap)?;
+
+    // Load the plugin and register modules; Ctrl-C during their start aborts it
+    let cancellation = cancel_on_ctrl_c();
+    init_echo_plugin_module(EchoPluginModuleConfig {
+        path: args.plugin,
+        ..Default::default()
+    })?;
+    init_echo_client_module(EchoClientModuleConfig {
+        json_output: args.json,
+        cancellation,
+        ..Default::default()
+    })?;
+
+    // Fail fast if an enabled module consumes a service nothing provides
+    validate_module_dependencies(&config, &[])?;
+
+    announce_startup(&args.bootstrap, &description.merge(config.describe()))?;
+    // Root of every span in the process (see echo_contract::spans)
+    run_with_config(config)
+        .instrument(info_span!("bin", name = env!("CARGO_BIN_NAME"), pid = std::process::id()))
+        .await
+}
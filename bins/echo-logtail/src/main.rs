@@ -0,0 +1,240 @@
+//! Echo Logtail - Merges the shipped log events of client and server processes.
+//!
+//! # What This Demonstrates
+//!
+//! 1. **Cross-process correlation** - One request followed through client and server
+//! 2. **Log shipping** - The `--log-ship` JSON lines of every echo binary
+//! 3. **Collector** - Receives events over UDP/TCP and keeps them in a file
+//!
+//! # Architecture
+//!
+//! ```text
+//! echo-grpc-cli --log-ship cli.ndjson ─┐ x-echo-trace: TRACE_ID/REQUEST_ID
+//! echo-grpc-srv --log-ship srv.ndjson ─┘ (same IDs on both sides)
+//!     ↓
+//! echo-logtail cli.ndjson srv.ndjson --request 7d1c...
+//!     ├── parse every line (ShippedEvent), skip what isn't one
+//!     ├── keep the events of the request / trace asked for
+//!     └── order by timestamp → one timeline across processes
+//!
+//! echo-grpc-* --log-ship udp://localhost:5140
+//!     ↓
+//! echo-logtail --listen udp://0.0.0.0:5140 --output events.ndjson   (live, in arrival order)
+//! ```
+//!
+//! Timestamps come from each process's clock: on different hosts the
+//! order is only as good as their clock sync.
+
+use std::path::{Path, PathBuf};
+use clap::Parser;
+use hsu_common::{Error, Result};
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, UdpSocket};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, parse_described, BootstrapArgs, ConfigCheck, ConfigDescription,
+    LogShipTarget, Runtimes, ShippedEvent,
+};
+
+/// Largest datagram a UDP sender can ship.
+const MAX_DATAGRAM: usize = 64 * 1024;
+
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Merges shipped echo log events and follows one request across processes")]
+struct Args {
+    /// Files of shipped events (`--log-ship PATH` of each process, or a collector's --output)
+    #[arg(required_unless_present = "listen")]
+    inputs: Vec<PathBuf>,
+
+    /// Collect events live instead: udp://ADDR:PORT or tcp://ADDR:PORT
+    #[arg(long, value_name = "TARGET", conflicts_with = "inputs")]
+    listen: Option<String>,
+
+    /// With --listen, also append every received event to this file
+    #[arg(long, value_name = "PATH", requires = "listen")]
+    output: Option<PathBuf>,
+
+    /// Only events of this request (the request ID of a call)
+    #[arg(long, value_name = "ID")]
+    request: Option<String>,
+
+    /// Only events of this trace (all calls of one client run)
+    #[arg(long, value_name = "ID")]
+    trace: Option<String>,
+
+    /// Print the matching events as JSON lines instead of a timeline
+    #[arg(long)]
+    json: bool,
+
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+}
+
+fn main() -> Result<()> {
+    let (args, description) = parse_described::<Args>();
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    let mut check = ConfigCheck::new();
+    if let Some(listen) = &args.listen {
+        check.require_ok("--listen", listen_target(listen));
+    }
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+
+    bootstrap(&args.bootstrap)?;
+    announce_startup(&args.bootstrap, &description)?;
+
+    let filter = Filter { request: args.request.clone(), trace: args.trace.clone() };
+    match &args.listen {
+        Some(listen) => follow(listen_target(listen)?, args.output.as_deref(), &filter, args.json).await,
+        None => merge(&args.inputs, &filter, args.json).await,
+    }
+}
+
+/// Which events to show.
+struct Filter {
+    request: Option<String>,
+    trace: Option<String>,
+}
+
+impl Filter {
+    fn matches(&self, event: &ShippedEvent) -> bool {
+        let wanted = |id: &Option<String>, event_id: &Option<String>| id.is_none() || id == event_id;
+        wanted(&self.request, &event.request_id) && wanted(&self.trace, &event.trace_id)
+    }
+}
+
+/// Prints the matching events of all `inputs`, ordered by timestamp.
+async fn merge(inputs: &[PathBuf], filter: &Filter, json: bool) -> Result<()> {
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    for path in inputs {
+        let text = tokio::fs::read_to_string(path).await.map_err(|e| Error::Validation {
+            message: format!("Event file {}: {}", path.display(), e),
+        })?;
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            match ShippedEvent::from_json(line) {
+                Some(event) => events.push(event),
+                None => skipped += 1,
+            }
+        }
+    }
+    let total = events.len();
+    events.retain(|event| filter.matches(event));
+    // Stable: events of one process logged in the same millisecond keep their order
+    events.sort_by_key(|event| event.timestamp_ms);
+
+    let start = events.first().map_or(0, |event| event.timestamp_ms);
+    for event in &events {
+        print_event(event, start, json);
+    }
+    info!("[EchoLogtail] ✅ {} of {} event(s) from {} file(s) shown, {} line(s) skipped",
+        events.len(), total, inputs.len(), skipped);
+    Ok(())
+}
+
+/// Prints matching events as they arrive at `target`, appending all of
+/// them to `output`.
+async fn follow(target: LogShipTarget, output: Option<&Path>, filter: &Filter, json: bool) -> Result<()> {
+    let mut output = match output {
+        Some(path) => Some(OpenOptions::new().create(true).append(true).open(path).await.map_err(|e| {
+            Error::Validation { message: format!("Output {}: {}", path.display(), e) }
+        })?),
+        None => None,
+    };
+    let (lines, mut received) = mpsc::channel::<String>(1024);
+    match target {
+        LogShipTarget::Udp(address) => {
+            let socket = UdpSocket::bind(&address).await.map_err(|e| listen_error(&address, e))?;
+            info!("[EchoLogtail] Listening on udp://{}", address);
+            tokio::spawn(receive_datagrams(socket, lines));
+        }
+        LogShipTarget::Tcp(address) => {
+            let listener = TcpListener::bind(&address).await.map_err(|e| listen_error(&address, e))?;
+            info!("[EchoLogtail] Listening on tcp://{}", address);
+            tokio::spawn(accept_connections(listener, lines));
+        }
+        LogShipTarget::File(_) => unreachable!("listen targets are validated"),
+    }
+
+    let mut start = None;
+    loop {
+        let line = tokio::select! {
+            line = received.recv() => match line {
+                Some(line) => line,
+                None => return Ok(()),
+            },
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        if let Some(file) = output.as_mut() {
+            if let Err(e) = file.write_all(format!("{}\n", line).as_bytes()).await {
+                warn!("[EchoLogtail] Can't append to the output file: {}", e);
+            }
+        }
+        let Some(event) = ShippedEvent::from_json(&line) else {
+            continue;
+        };
+        if filter.matches(&event) {
+            print_event(&event, *start.get_or_insert(event.timestamp_ms), json);
+        }
+    }
+}
+
+async fn receive_datagrams(socket: UdpSocket, lines: mpsc::Sender<String>) {
+    let mut buffer = vec![0; MAX_DATAGRAM];
+    while let Ok(length) = socket.recv(&mut buffer).await {
+        let line = String::from_utf8_lossy(&buffer[..length]).trim_end().to_string();
+        if lines.send(line).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn accept_connections(listener: TcpListener, lines: mpsc::Sender<String>) {
+    while let Ok((stream, peer)) = listener.accept().await {
+        info!("[EchoLogtail] Sender connected: {}", peer);
+        let lines = lines.clone();
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(stream).lines();
+            while let Ok(Some(line)) = reader.next_line().await {
+                if lines.send(line).await.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+/// A `--listen` value: UDP or TCP only.
+fn listen_target(value: &str) -> Result<LogShipTarget> {
+    match value.parse()? {
+        LogShipTarget::File(_) => Err(Error::Validation {
+            message: format!("'{}' is not udp://ADDR:PORT or tcp://ADDR:PORT", value),
+        }),
+        target => Ok(target),
+    }
+}
+
+fn listen_error(address: &str, e: std::io::Error) -> Error {
+    Error::Validation { message: format!("Can't listen on {}: {}", address, e) }
+}
+
+/// Prints one timeline line (time relative to `start`), or the JSON event.
+fn print_event(event: &ShippedEvent, start: u64, json: bool) {
+    if json {
+        println!("{}", event.to_json());
+        return;
+    }
+    let offset = event.timestamp_ms.saturating_sub(start) as f64 / 1000.0;
+    let request = event.request_id.as_deref().map_or_else(|| "-".to_string(), |id| id.chars().take(8).collect());
+    let fields: String = event.fields.iter().map(|(key, value)| format!(" {}={}", key, value)).collect();
+    println!("+{:>8.3}s {:<16} {:>5} {:<8} {}{}",
+        offset, format!("{}[{}]", event.process, event.pid), event.level, request, event.message, fields);
+}
@@ -0,0 +1,90 @@
+//! Echo Registry - Service registry for running the demo from this workspace.
+//!
+//! # What This Demonstrates
+//!
+//! 1. **Self-contained demo** - Registry + server + client, no hsu-core binaries
+//! 2. **Framework registry API** - Served by hsu-service-registry's `RegistryServer`
+//! 3. **Optional persistence** - Registrations survive a registry restart
+//!
+//! # Architecture
+//!
+//! ```text
+//! Process: echo-registry (:8080)
+//! └── serve_registry (hsu-service-registry RegistryServer)
+//!     └── InMemoryServiceRegistry ──(--persist)──→ registry.json
+//!
+//! echo-grpc-srv ──publish──→ :8080 ←──discover── echo-grpc-cli
+//! ```
+//!
+//! A drop-in for `hsu-registry` during development:
+//!
+//! ```bash
+//! cargo run --bin echo-registry -- --persist registry.json
+//! cargo run --bin echo-grpc-srv
+//! cargo run --bin echo-grpc-cli
+//! ```
+
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+use clap::Parser;
+use hsu_common::{Error, Result};
+use tracing::info;
+
+use echo_api::InMemoryServiceRegistry;
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, parse_described, serve_registry, BootstrapArgs, ConfigCheck,
+    ConfigDescription, Runtimes,
+};
+
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Service registry for the echo demo")]
+struct Args {
+    /// Address to serve the registry API on
+    #[arg(long, default_value = "0.0.0.0:8080")]
+    listen: SocketAddr,
+
+    /// Persist registrations to this JSON file (loaded on start)
+    #[arg(long, value_name = "PATH")]
+    persist: Option<PathBuf>,
+
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+}
+
+fn main() -> Result<()> {
+    let (args, description) = parse_described::<Args>();
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    let mut check = ConfigCheck::new();
+    if let Some(path) = &args.persist {
+        let directory = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+        check.require(directory.map_or(true, |directory| directory.is_dir()), "--persist", || {
+            format!("directory of {} doesn't exist", path.display())
+        });
+    }
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+
+    bootstrap(&args.bootstrap)?;
+    announce_startup(&args.bootstrap, &description)?;
+
+    let registry = match &args.persist {
+        Some(path) => InMemoryServiceRegistry::persistent(path)?,
+        None => InMemoryServiceRegistry::new(),
+    };
+    let listener = TcpListener::bind(args.listen)
+        .map_err(|e| Error::Protocol(format!("Failed to bind registry {}: {}", args.listen, e)))?;
+
+    tokio::select! {
+        result = serve_registry(listener, Arc::new(registry)) => result,
+        _ = tokio::signal::ctrl_c() => {
+            info!("[EchoRegistry] ✅ Shutting down");
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Echo Replay - Replays captured echo traffic against a target.
+//!
+//! # What This Demonstrates
+//!
+//! 1. **Regression testing** - Recorded responses compared with replayed ones
+//! 2. **Performance testing** - Original pacing, sped up, or flat out
+//! 3. **Protocol independence** - The same capture against Direct or gRPC
+//!
+//! # Architecture
+//!
+//! ```text
+//! echo-grpc-srv --record capture.ndjson      (RecordingEchoService → NdjsonAuditSink)
+//!     ↓ capture.ndjson
+//! echo-replay capture.ndjson --target grpc --speed 2
+//!     ├── each call is sent at its recorded offset / speed (concurrently,
+//!     │   so overlapping calls overlap again)
+//!     └── replayed response vs. recorded response → report
+//! ```
+//!
+//! Exits with an error if any response differs from the capture (unless
+//! `--no-compare`), so it can gate CI.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use clap::Parser;
+use hsu_common::{Error, Result};
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+use echo_api::{read_capture, replay_call, responses_match};
+use echo_api_grpc::generated::echo_service_client::EchoServiceClient;
+use echo_api_grpc::{ChannelPool, EchoGrpcGateway};
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, parse_described, BootstrapArgs, ConfigCheck, ConfigDescription,
+    Runtimes,
+};
+use echo_contract::EchoService;
+use echo_server::EchoServiceImpl;
+
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Replays captured echo traffic")]
+struct Args {
+    /// Capture file written by `echo-grpc-srv --record`
+    capture: PathBuf,
+
+    /// Target: direct (in-process echo service) or grpc
+    #[arg(long, default_value = "grpc")]
+    target: String,
+
+    /// gRPC server address (host:port or URI)
+    #[arg(long, default_value = "localhost:50051")]
+    address: String,
+
+    /// Replay speed: 1 = original pacing, 2 = twice as fast, 0 = as fast as possible
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// Don't compare responses with the capture (performance runs)
+    #[arg(long)]
+    no_compare: bool,
+
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+}
+
+fn main() -> Result<()> {
+    let (args, description) = parse_described::<Args>();
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    let mut check = ConfigCheck::new();
+    check.require(args.speed.is_finite() && args.speed >= 0.0, "--speed", || {
+        format!("{} is not 0 or a positive factor", args.speed)
+    });
+    check.require(matches!(args.target.as_str(), "direct" | "grpc"), "--target", || {
+        format!("unknown target '{}' (expected direct or grpc)", args.target)
+    });
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+
+    bootstrap(&args.bootstrap)?;
+    announce_startup(&args.bootstrap, &description)?;
+
+    let records = read_capture(&args.capture)?;
+    let target = connect(&args)?;
+    info!("[EchoReplay] Replaying {} call(s) from {} against {} (speed {})",
+        records.len(), args.capture.display(), args.target, args.speed);
+
+    let started = Instant::now();
+    let mut calls = JoinSet::new();
+    for (index, record) in records.into_iter().enumerate() {
+        if args.speed > 0.0 {
+            let due = started + record.offset.div_f64(args.speed);
+            tokio::time::sleep_until(due.into()).await;
+        }
+        let target = target.clone();
+        calls.spawn(async move {
+            let call_started = Instant::now();
+            let outcome = replay_call(&*target, &record).await;
+            (index, record, outcome, call_started.elapsed())
+        });
+    }
+
+    let mut report = Report::default();
+    while let Some(joined) = calls.join_next().await {
+        let (index, record, outcome, latency) = joined
+            .map_err(|e| Error::Protocol(format!("Replay task failed: {}", e)))?;
+        let Some(result) = outcome else {
+            report.skipped += 1;
+            continue;
+        };
+        report.latencies.push(latency);
+        if result.is_err() {
+            report.failed += 1;
+        }
+        if !args.no_compare && !responses_match(&record, &result) {
+            report.mismatched += 1;
+            warn!("[EchoReplay] Call #{} ({}) differs: recorded {:?} / {:?}, replayed {:?}",
+                index + 1, record.method, record.response, record.error, result);
+        }
+    }
+    report.log(started.elapsed());
+
+    if report.mismatched > 0 {
+        return Err(Error::Validation {
+            message: format!("{} of {} replayed response(s) differ from the capture",
+                report.mismatched, report.latencies.len()),
+        });
+    }
+    Ok(())
+}
+
+/// Creates the replay target.
+fn connect(args: &Args) -> Result<Arc<dyn EchoService>> {
+    match args.target.as_str() {
+        "direct" => Ok(Arc::new(EchoServiceImpl::new())),
+        "grpc" => {
+            let channel = ChannelPool::default().channel(&args.address)?;
+            Ok(Arc::new(EchoGrpcGateway::from_client(EchoServiceClient::new(channel))))
+        }
+        other => Err(Error::Validation {
+            message: format!("Unknown target '{}' (expected direct or grpc)", other),
+        }),
+    }
+}
+
+/// Replay outcome.
+#[derive(Default)]
+struct Report {
+    latencies: Vec<Duration>,
+    failed: usize,
+    mismatched: usize,
+    skipped: usize,
+}
+
+impl Report {
+    fn log(&mut self, elapsed: Duration) {
+        self.latencies.sort();
+        let percentile = |p: f64| {
+            let index = ((self.latencies.len() as f64 * p).ceil() as usize).saturating_sub(1);
+            self.latencies.get(index).copied().unwrap_or_default()
+        };
+        info!("[EchoReplay] ✅ {} call(s) in {:?}: {} failed, {} differ, {} skipped; latency p50 {:?}, p99 {:?}",
+            self.latencies.len(), elapsed, self.failed, self.mismatched, self.skipped,
+            percentile(0.50), percentile(0.99));
+    }
+}
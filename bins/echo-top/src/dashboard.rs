@@ -0,0 +1,358 @@
+//! What the dashboard shows, computed from two consecutive scrapes.
+//!
+//! Counters only mean something as rates, so every figure is over the
+//! last interval: requests/s, latency percentiles of the calls that
+//! finished in it, the protocol mix. A counter that went down (the
+//! process restarted) counts from zero.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::scrape::{HealthCheck, Sample, Scrape};
+
+/// Request rates kept for the sparkline.
+pub const RATE_HISTORY: usize = 120;
+
+/// Percentiles shown, as quantiles.
+pub const QUANTILES: [(&str, f64); 4] = [("p50", 0.5), ("p90", 0.9), ("p99", 0.99), ("p99.9", 0.999)];
+
+/// State of one limiter that sheds load, breaker-style: `Closed` lets
+/// calls through, `Open` rejected some during the interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    /// Closed, but at its limit (calls in flight or queued).
+    Saturated,
+    Open,
+}
+
+impl BreakerState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Saturated => "saturated",
+            BreakerState::Open => "open",
+        }
+    }
+}
+
+/// One breaker row: the limiter, its state and the detail behind it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breaker {
+    pub name: String,
+    pub state: BreakerState,
+    pub detail: String,
+}
+
+/// Health of one module: its checks, failing first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleHealth {
+    pub module: String,
+    pub checks: Vec<HealthCheck>,
+}
+
+impl ModuleHealth {
+    pub fn healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.failure.is_none())
+    }
+}
+
+/// One frame of the dashboard.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Frame {
+    /// `"server"` or `"client"`: whose calls are counted.
+    pub side: String,
+    pub requests_per_sec: f64,
+    /// Share of the interval's calls that failed (0 without calls).
+    pub error_ratio: f64,
+    /// Per [`QUANTILES`], in seconds; `None` without calls.
+    pub latencies: Vec<(&'static str, Option<f64>)>,
+    /// Requests/s per protocol, busiest first.
+    pub protocols: Vec<(String, f64)>,
+    pub breakers: Vec<Breaker>,
+    pub healthy: bool,
+    pub modules: Vec<ModuleHealth>,
+}
+
+/// The dashboard of one admin endpoint: the last scrape and rate history.
+#[derive(Default)]
+pub struct Dashboard {
+    last: Option<Scrape>,
+    pub frame: Option<Frame>,
+    pub rates: VecDeque<u64>,
+}
+
+impl Dashboard {
+    /// Takes a new scrape; the frame needs two.
+    pub fn update(&mut self, scrape: Scrape) {
+        if let Some(last) = &self.last {
+            let frame = Frame::between(last, &scrape);
+            if self.rates.len() == RATE_HISTORY {
+                self.rates.pop_front();
+            }
+            self.rates.push_back(frame.requests_per_sec.round() as u64);
+            self.frame = Some(frame);
+        }
+        self.last = Some(scrape);
+    }
+}
+
+impl Frame {
+    /// The frame of the interval from `last` to `now`.
+    pub fn between(last: &Scrape, now: &Scrape) -> Frame {
+        let seconds = now.at.duration_since(last.at).as_secs_f64().max(1e-3);
+        let delta = |name: &str, filter: &dyn Fn(&Sample) -> bool| counter_delta(last.sum(name, filter), now.sum(name, filter));
+
+        // The server's view when this process serves, else the client's
+        let side = if now.sum("echo_requests_total", &|s: &Sample| s.label("side") == "server") > 0.0 { "server" } else { "client" };
+        let on_side = |sample: &Sample| sample.label("side") == side;
+
+        let requests = delta("echo_requests_total", &on_side);
+        let responses = delta("echo_responses_total", &on_side);
+        let error_ratio = if requests > 0.0 { ((requests - responses) / requests).clamp(0.0, 1.0) } else { 0.0 };
+
+        let mut protocols: BTreeMap<String, f64> = BTreeMap::new();
+        for sample in now.samples.iter().filter(|s| s.name == "echo_requests_total" && on_side(s)) {
+            let protocol = sample.label("protocol").to_string();
+            let calls = delta("echo_requests_total", &|s: &Sample| on_side(s) && s.label("protocol") == protocol);
+            protocols.insert(protocol, calls / seconds);
+        }
+        let mut protocols: Vec<_> = protocols.into_iter().collect();
+        protocols.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let buckets = duration_buckets(last, now, &on_side);
+        let latencies = QUANTILES.iter().map(|(name, q)| (*name, quantile(*q, &buckets))).collect();
+
+        let mut modules: BTreeMap<&str, Vec<HealthCheck>> = BTreeMap::new();
+        for check in &now.checks {
+            modules.entry(check.module.as_str()).or_default().push(check.clone());
+        }
+        let modules = modules
+            .into_iter()
+            .map(|(module, mut checks)| {
+                checks.sort_by_key(|check| check.failure.is_none());
+                ModuleHealth { module: module.to_string(), checks }
+            })
+            .collect();
+
+        Frame {
+            side: side.to_string(),
+            requests_per_sec: requests / seconds,
+            error_ratio,
+            latencies,
+            protocols,
+            breakers: breakers(last, now),
+            healthy: now.healthy,
+            modules,
+        }
+    }
+}
+
+/// How much a counter grew; a reset (restart) counts from zero.
+fn counter_delta(before: f64, after: f64) -> f64 {
+    if after >= before { after - before } else { after }
+}
+
+/// Cumulative `echo_request_duration_seconds` bucket growth, summed over
+/// protocols, by upper bound.
+fn duration_buckets(last: &Scrape, now: &Scrape, filter: &dyn Fn(&Sample) -> bool) -> Vec<(f64, f64)> {
+    let bound = |sample: &Sample| -> Option<f64> {
+        match sample.label("le") {
+            "+Inf" => Some(f64::INFINITY),
+            le => le.parse().ok(),
+        }
+    };
+    let mut bounds: Vec<f64> = now.samples.iter()
+        .filter(|s| s.name == "echo_request_duration_seconds_bucket" && filter(s))
+        .filter_map(bound)
+        .collect();
+    bounds.sort_by(f64::total_cmp);
+    bounds.dedup();
+
+    bounds
+        .into_iter()
+        .map(|le| {
+            let in_bucket = |s: &Sample| filter(s) && bound(s) == Some(le);
+            let calls = counter_delta(
+                last.sum("echo_request_duration_seconds_bucket", in_bucket),
+                now.sum("echo_request_duration_seconds_bucket", in_bucket),
+            );
+            (le, calls)
+        })
+        .collect()
+}
+
+/// Like PromQL's `histogram_quantile`: linear within the bucket holding
+/// the quantile; the highest finite bound if it falls into `+Inf`.
+fn quantile(q: f64, buckets: &[(f64, f64)]) -> Option<f64> {
+    let total = buckets.last().map(|(_, count)| *count).filter(|count| *count > 0.0)?;
+    let rank = q * total;
+    let mut lower = (0.0, 0.0);
+    for &(le, count) in buckets {
+        if count >= rank {
+            if le.is_infinite() {
+                return Some(lower.0);
+            }
+            let share = if count > lower.1 { (rank - lower.1) / (count - lower.1) } else { 1.0 };
+            return Some(lower.0 + (le - lower.0) * share);
+        }
+        lower = (le, count);
+    }
+    Some(lower.0)
+}
+
+/// The limiters that shed load: adaptive concurrency, priority lanes and
+/// caller quotas. Echo has no separate circuit breaker; these are what
+/// trips and rejects calls.
+fn breakers(last: &Scrape, now: &Scrape) -> Vec<Breaker> {
+    let gauge = |name: &str, filter: &dyn Fn(&Sample) -> bool| now.sum(name, filter);
+    let grew = |name: &str, filter: &dyn Fn(&Sample) -> bool| counter_delta(last.sum(name, filter), now.sum(name, filter));
+    let any = |name: &str| now.samples.iter().any(|s| s.name == name);
+    let mut breakers = Vec::new();
+
+    if any("echo_concurrency_limit") {
+        let limit = gauge("echo_concurrency_limit", &|_| true);
+        let in_flight = gauge("echo_concurrency_in_flight", &|_| true);
+        let rejected = grew("echo_concurrency_rejected_total", &|_| true);
+        breakers.push(Breaker {
+            name: "adaptive concurrency".to_string(),
+            state: state(rejected, limit > 0.0 && in_flight >= limit),
+            detail: format!("{}/{} in flight, {} rejected", in_flight, limit, rejected),
+        });
+    }
+
+    let mut priorities: Vec<&str> = now.samples.iter()
+        .filter(|s| s.name == "echo_priority_admitted_total")
+        .map(|s| s.label("priority"))
+        .collect();
+    // One sample per lane and label set: one row per lane
+    priorities.sort_unstable();
+    priorities.dedup();
+    for priority in priorities {
+        let lane = |s: &Sample| s.label("priority") == priority;
+        let queued = gauge("echo_priority_queue_depth", &lane);
+        let rejected = grew("echo_priority_rejected_total", &lane);
+        breakers.push(Breaker {
+            name: format!("priority {}", priority),
+            state: state(rejected, queued > 0.0),
+            detail: format!("{} queued, {} rejected", queued, rejected),
+        });
+    }
+
+    let mut callers: Vec<&str> = now.samples.iter()
+        .filter(|s| s.name == "echo_caller_quota_rejected_total")
+        .map(|s| s.label("caller"))
+        .collect();
+    callers.sort_unstable();
+    callers.dedup();
+    for caller in callers {
+        let rejected = grew("echo_caller_quota_rejected_total", &|s: &Sample| s.label("caller") == caller);
+        breakers.push(Breaker {
+            name: format!("quota {}", caller),
+            state: state(rejected, false),
+            detail: format!("{} rejected", rejected),
+        });
+    }
+    breakers
+}
+
+fn state(rejected: f64, saturated: bool) -> BreakerState {
+    if rejected > 0.0 {
+        BreakerState::Open
+    } else if saturated {
+        BreakerState::Saturated
+    } else {
+        BreakerState::Closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use crate::scrape::{parse_health, parse_metrics};
+
+    fn scrape(at: Instant, metrics: &str, health: &str) -> Scrape {
+        let (healthy, checks) = parse_health(health);
+        Scrape { at, samples: parse_metrics(metrics), healthy, checks }
+    }
+
+    fn server_metrics(grpc: u64, direct: u64, fast: u64, slow: u64) -> String {
+        format!(r#"# TYPE echo_requests_total counter
+echo_requests_total{{side="server",protocol="grpc",service="service"}} {grpc}
+echo_requests_total{{side="server",protocol="direct",service="service"}} {direct}
+echo_responses_total{{side="server",protocol="grpc",service="service"}} {grpc}
+echo_responses_total{{side="server",protocol="direct",service="service"}} {direct}
+echo_request_duration_seconds_bucket{{side="server",protocol="grpc",service="service",le="0.01"}} {fast}
+echo_request_duration_seconds_bucket{{side="server",protocol="grpc",service="service",le="0.1"}} {all}
+echo_request_duration_seconds_bucket{{side="server",protocol="grpc",service="service",le="+Inf"}} {all}
+echo_concurrency_limit 10
+echo_concurrency_in_flight 3
+echo_concurrency_rejected_total {slow}
+echo_caller_quota_rejected_total{{caller="cli \"1\""}} 0
+"#, all = fast + slow)
+    }
+
+    #[test]
+    fn test_frame_over_one_interval() {
+        let start = Instant::now();
+        let health = "failing\necho/self-test/grpc:50051: failing: wrong reply (1.0ms)\necho/self-test/direct: passing (0.1ms)\n";
+        let mut dashboard = Dashboard::default();
+        dashboard.update(scrape(start, &server_metrics(100, 10, 50, 0), health));
+        assert!(dashboard.frame.is_none());
+        dashboard.update(scrape(start + Duration::from_secs(2), &server_metrics(180, 30, 90, 40), health));
+
+        let frame = dashboard.frame.unwrap();
+        assert_eq!(frame.side, "server");
+        assert_eq!(frame.requests_per_sec, 50.0);
+        assert_eq!(frame.protocols, vec![("grpc".to_string(), 40.0), ("direct".to_string(), 10.0)]);
+        // 40 of 80 calls within 10ms: p50 at the bucket's top, p90 between 10 and 100ms
+        assert_eq!(frame.latencies[0], ("p50", Some(0.01)));
+        assert!((frame.latencies[1].1.unwrap() - 0.082).abs() < 1e-9);
+
+        assert_eq!(frame.breakers[0].state, BreakerState::Open);
+        assert_eq!(frame.breakers[1].name, "quota cli \"1\"");
+        assert_eq!(frame.breakers[1].state, BreakerState::Closed);
+
+        assert!(!frame.healthy);
+        assert!(!frame.modules[0].healthy());
+        assert_eq!(frame.modules[0].checks[0].name, "self-test/grpc:50051");
+        assert_eq!(frame.modules[0].checks[0].failure.as_deref(), Some("wrong reply"));
+    }
+
+    #[test]
+    fn test_one_breaker_row_per_lane_and_caller() {
+        let metrics = r#"echo_priority_admitted_total{priority="high",method="echo"} 5
+echo_priority_admitted_total{priority="low",method="echo"} 3
+echo_priority_admitted_total{priority="high",method="echo_bytes"} 1
+echo_priority_rejected_total{priority="low",method="echo"} 2
+echo_caller_quota_rejected_total{caller="cli",method="echo"} 0
+echo_caller_quota_rejected_total{caller="cli",method="echo_bytes"} 1
+"#;
+        let start = Instant::now();
+        let frame = Frame::between(&scrape(start, metrics, "ok
+"), &scrape(start + Duration::from_secs(1), metrics, "ok
+"));
+
+        let names: Vec<_> = frame.breakers.iter().map(|breaker| breaker.name.as_str()).collect();
+        assert_eq!(names, ["priority high", "priority low", "quota cli"]);
+        assert_eq!(frame.breakers[2].detail, "0 rejected");
+    }
+
+    #[test]
+    fn test_restart_counts_from_zero() {
+        let start = Instant::now();
+        let mut dashboard = Dashboard::default();
+        dashboard.update(scrape(start, &server_metrics(1000, 0, 1000, 0), "ok\n"));
+        dashboard.update(scrape(start + Duration::from_secs(1), &server_metrics(5, 0, 5, 0), "ok\n"));
+
+        let frame = dashboard.frame.unwrap();
+        assert_eq!(frame.requests_per_sec, 5.0);
+        assert!(frame.healthy);
+    }
+
+    #[test]
+    fn test_no_calls_no_percentiles() {
+        assert_eq!(quantile(0.99, &[(0.01, 0.0), (f64::INFINITY, 0.0)]), None);
+        assert_eq!(quantile(0.99, &[(0.01, 0.0), (f64::INFINITY, 4.0)]), Some(0.01));
+    }
+}
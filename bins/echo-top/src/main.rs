@@ -0,0 +1,205 @@
+//! Echo Top - Live terminal dashboard of running echo processes.
+//!
+//! # What This Demonstrates
+//!
+//! 1. **Admin endpoint as the only interface** - `/metrics` and `/health` (`--admin-addr`), nothing else
+//! 2. **Rates from counters** - requests/s, failures and protocol mix between two scrapes
+//! 3. **Latency percentiles** - p50..p99.9 from the `echo_request_duration_seconds` histogram
+//! 4. **Load shedding at a glance** - the concurrency, priority and quota limiters as breakers
+//!
+//! # Architecture
+//!
+//! ```text
+//! echo-grpc-srv --admin-addr 127.0.0.1:9090     echo-grpc-cli --admin-addr 127.0.0.1:9091
+//!         ↑ GET /metrics, /health every --interval-ms    ↑
+//! echo-top --admin-url http://127.0.0.1:9090 --admin-url http://127.0.0.1:9091
+//!     ├── scrape.rs     one Scrape per endpoint and tick
+//!     ├── dashboard.rs  Frame between the last two scrapes
+//!     └── ui.rs         ratatui, one tab per endpoint (←/→, q quits)
+//! ```
+//!
+//! `--once` prints one frame as text instead (after two scrapes), for
+//! scripts and terminals without a TUI.
+
+mod dashboard;
+mod scrape;
+mod ui;
+
+use std::time::Duration;
+use clap::Parser;
+use futures::future::join_all;
+use hsu_common::{Error, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use tokio::sync::mpsc;
+
+use echo_bootstrap::{
+    announce_startup, bootstrap, finish_validation, parse_described, BootstrapArgs, ConfigCheck, ConfigDescription, Runtimes,
+};
+
+use crate::dashboard::Dashboard;
+use crate::scrape::AdminClient;
+
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Live dashboard over the admin endpoints of running echo processes")]
+struct Args {
+    /// Admin endpoint of an echo process (its --admin-addr); repeat for more, one tab each
+    #[arg(long, value_name = "URL", default_value = "http://127.0.0.1:9090")]
+    admin_url: Vec<String>,
+
+    /// Milliseconds between scrapes (the interval rates are computed over)
+    #[arg(long, default_value_t = 1000)]
+    interval_ms: u64,
+
+    /// Print one frame per endpoint as text and exit
+    #[arg(long)]
+    once: bool,
+
+    #[command(flatten)]
+    bootstrap: BootstrapArgs,
+}
+
+fn main() -> Result<()> {
+    let (mut args, description) = parse_described::<Args>();
+    // Log lines on the terminal would tear through the dashboard
+    if !args.once && args.bootstrap.log_file.is_none() {
+        args.bootstrap.log = "off".to_string();
+    }
+    Runtimes::build(&args.bootstrap.runtime_layout())?.block_on(run(args, description))
+}
+
+async fn run(args: Args, description: ConfigDescription) -> Result<()> {
+    let mut check = ConfigCheck::new();
+    for url in &args.admin_url {
+        check.require_ok("--admin-url", AdminClient::new(url));
+    }
+    check.require(args.interval_ms > 0, "--interval-ms", || "must be positive".to_string());
+    if finish_validation(&args.bootstrap, check)? {
+        return Ok(());
+    }
+
+    bootstrap(&args.bootstrap)?;
+    announce_startup(&args.bootstrap, &description)?;
+
+    let interval = Duration::from_millis(args.interval_ms);
+    // A scrape that takes longer than the interval would hold up the next tick
+    let clients = args.admin_url.iter()
+        .map(|url| Ok(AdminClient::new(url)?.with_timeout(interval)))
+        .collect::<Result<Vec<_>>>()?;
+    if args.once {
+        print_once(&clients, interval).await
+    } else {
+        watch(&clients, interval).await
+    }
+}
+
+/// Scrapes every endpoint twice, `interval` apart, and prints the frames.
+async fn print_once(clients: &[AdminClient], interval: Duration) -> Result<()> {
+    let mut dashboards: Vec<Dashboard> = clients.iter().map(|_| Dashboard::default()).collect();
+    for round in 0..2 {
+        if round > 0 {
+            tokio::time::sleep(interval).await;
+        }
+        for (client, dashboard) in clients.iter().zip(&mut dashboards) {
+            dashboard.update(client.scrape().await.map_err(|e| Error::Protocol(format!("{}: {}", client.url(), e)))?);
+        }
+    }
+
+    for (client, dashboard) in clients.iter().zip(&dashboards) {
+        let Some(frame) = &dashboard.frame else { continue };
+        println!("{} ({}, {})", client.url(), frame.side, if frame.healthy { "healthy" } else { "failing" });
+        println!("  requests: {:.1}/s, {:.1}% failed", frame.requests_per_sec, frame.error_ratio * 100.0);
+        let latencies: Vec<_> = frame.latencies.iter()
+            .map(|(name, seconds)| format!("{} {}", name, seconds.map_or_else(|| "-".to_string(), ui::format_seconds)))
+            .collect();
+        println!("  latency: {}", latencies.join(", "));
+        for (protocol, rate) in &frame.protocols {
+            println!("  protocol {}: {:.1}/s", protocol, rate);
+        }
+        for breaker in &frame.breakers {
+            println!("  breaker {}: {} ({})", breaker.name, breaker.state.as_str(), breaker.detail);
+        }
+        for module in &frame.modules {
+            for check in &module.checks {
+                let status = check.failure.as_ref().map_or_else(|| "passing".to_string(), |reason| format!("failing: {}", reason));
+                println!("  module {} {}: {}", module.module, check.name, status);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Keys the dashboard reacts to.
+enum Key {
+    Quit,
+    Next,
+    Previous,
+}
+
+/// Runs the TUI until `q`, Esc or Ctrl-C.
+async fn watch(clients: &[AdminClient], interval: Duration) -> Result<()> {
+    let mut dashboards: Vec<Dashboard> = clients.iter().map(|_| Dashboard::default()).collect();
+    let mut errors: Vec<Option<String>> = vec![None; clients.len()];
+    let mut selected = 0;
+
+    // Raw mode: Ctrl-C is a key like the others, read on a blocking thread
+    let (keys, mut key_events) = mpsc::unbounded_channel();
+    std::thread::spawn(move || read_keys(keys));
+
+    let mut terminal = ui::init().map_err(|e| Error::Protocol(format!("Failed to set up the terminal: {}", e)))?;
+    let mut ticks = tokio::time::interval(interval);
+    let result = loop {
+        tokio::select! {
+            _ = ticks.tick() => {
+                // Endpoints scraped together; a hung one fails at the timeout
+                let scrapes = join_all(clients.iter().map(AdminClient::scrape)).await;
+                for ((scrape, dashboard), error) in scrapes.into_iter().zip(&mut dashboards).zip(&mut errors) {
+                    match scrape {
+                        Ok(scrape) => {
+                            dashboard.update(scrape);
+                            *error = None;
+                        }
+                        Err(e) => *error = Some(e.to_string()),
+                    }
+                }
+            }
+            key = key_events.recv() => match key {
+                Some(Key::Next) => selected = (selected + 1) % clients.len(),
+                Some(Key::Previous) => selected = (selected + clients.len() - 1) % clients.len(),
+                Some(Key::Quit) | None => break Ok(()),
+            },
+        }
+
+        let view = ui::View {
+            urls: clients.iter().map(AdminClient::url).collect(),
+            selected,
+            dashboard: &dashboards[selected],
+            error: errors[selected].as_deref(),
+        };
+        if let Err(e) = terminal.draw(|term| ui::draw(term, &view)) {
+            break Err(Error::Protocol(format!("Failed to draw: {}", e)));
+        }
+    };
+    ui::restore();
+    result
+}
+
+/// Forwards key presses until the dashboard is gone.
+fn read_keys(keys: mpsc::UnboundedSender<Key>) {
+    while let Ok(event) = event::read() {
+        let Event::Key(press) = event else { continue };
+        if press.kind != KeyEventKind::Press {
+            continue;
+        }
+        let key = match press.code {
+            KeyCode::Char('q') | KeyCode::Esc => Key::Quit,
+            KeyCode::Char('c') if press.modifiers.contains(KeyModifiers::CONTROL) => Key::Quit,
+            KeyCode::Right | KeyCode::Tab => Key::Next,
+            KeyCode::Left | KeyCode::BackTab => Key::Previous,
+            _ => continue,
+        };
+        if keys.send(key).is_err() {
+            break;
+        }
+    }
+}
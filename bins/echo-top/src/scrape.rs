@@ -0,0 +1,171 @@
+//! Reading an admin endpoint: `/metrics` (Prometheus text) and `/health`.
+
+use std::time::{Duration, Instant};
+use hsu_common::{Error, Result};
+use hyper::client::HttpConnector;
+use hyper::{Client, StatusCode, Uri};
+
+/// One Prometheus sample: `name{labels} value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub name: String,
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+}
+
+impl Sample {
+    /// The value of label `name` (empty if absent, as in PromQL).
+    pub fn label(&self, name: &str) -> &str {
+        self.labels.iter().find(|(key, _)| key == name).map_or("", |(_, value)| value)
+    }
+}
+
+/// One health check line of `/health`: `module/name: status (latency)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheck {
+    pub module: String,
+    pub name: String,
+    /// `None` if passing, else why it fails.
+    pub failure: Option<String>,
+}
+
+/// Everything read from an admin endpoint at one point in time.
+#[derive(Debug, Clone)]
+pub struct Scrape {
+    pub at: Instant,
+    pub samples: Vec<Sample>,
+    pub healthy: bool,
+    pub checks: Vec<HealthCheck>,
+}
+
+impl Scrape {
+    /// Sum of the samples named `name` whose labels pass `filter`.
+    pub fn sum(&self, name: &str, filter: impl Fn(&Sample) -> bool) -> f64 {
+        self.samples.iter().filter(|sample| sample.name == name && filter(sample)).map(|sample| sample.value).sum()
+    }
+}
+
+/// How long a GET may take unless [`AdminClient::with_timeout`] says otherwise.
+pub const DEFAULT_SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Reads `/metrics` and `/health` of one admin endpoint (`--admin-addr`).
+pub struct AdminClient {
+    client: Client<HttpConnector>,
+    base_url: String,
+    timeout: Duration,
+}
+
+impl AdminClient {
+    pub fn new(url: &str) -> Result<Self> {
+        let base_url = url.trim_end_matches('/').to_string();
+        base_url.parse::<Uri>().map_err(|e| Error::Validation {
+            message: format!("Invalid admin URL '{}': {}", url, e),
+        })?;
+        Ok(Self { client: Client::new(), base_url, timeout: DEFAULT_SCRAPE_TIMEOUT })
+    }
+
+    /// Fails each GET not answered (body included) within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn scrape(&self) -> Result<Scrape> {
+        let (_, metrics) = self.get("/metrics").await?;
+        let (status, health) = self.get("/health").await?;
+        let (healthy, checks) = parse_health(&health);
+        Ok(Scrape {
+            at: Instant::now(),
+            samples: parse_metrics(&metrics),
+            healthy: healthy && status == StatusCode::OK,
+            checks,
+        })
+    }
+
+    /// GETs `path`; 503 is an answer too (`/health` while failing).
+    async fn get(&self, path: &str) -> Result<(StatusCode, String)> {
+        tokio::time::timeout(self.timeout, self.fetch(path)).await
+            .map_err(|_| Error::Protocol(format!("GET {}: no answer within {:?}", path, self.timeout)))?
+    }
+
+    async fn fetch(&self, path: &str) -> Result<(StatusCode, String)> {
+        let uri: Uri = format!("{}{}", self.base_url, path).parse().map_err(|e| Error::Protocol(format!("{}", e)))?;
+        let response = self.client.get(uri).await.map_err(|e| Error::Protocol(format!("GET {}: {}", path, e)))?;
+        let status = response.status();
+        if status != StatusCode::OK && status != StatusCode::SERVICE_UNAVAILABLE {
+            return Err(Error::Protocol(format!("GET {}: HTTP {}", path, status)));
+        }
+        let body = hyper::body::to_bytes(response.into_body()).await
+            .map_err(|e| Error::Protocol(format!("GET {}: {}", path, e)))?;
+        Ok((status, String::from_utf8_lossy(&body).into_owned()))
+    }
+}
+
+/// Parses the Prometheus text format; lines that aren't samples are skipped.
+pub fn parse_metrics(text: &str) -> Vec<Sample> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(parse_sample)
+        .collect()
+}
+
+fn parse_sample(line: &str) -> Option<Sample> {
+    let (series, value) = line.rsplit_once(' ')?;
+    let value = match value {
+        "+Inf" => f64::INFINITY,
+        value => value.parse().ok()?,
+    };
+    let Some((name, labels)) = series.split_once('{') else {
+        return Some(Sample { name: series.to_string(), labels: Vec::new(), value });
+    };
+    Some(Sample { name: name.to_string(), labels: parse_labels(labels.strip_suffix('}')?)?, value })
+}
+
+/// `key="value",...` with `\"`, `\\` and `\n` escapes in values.
+fn parse_labels(text: &str) -> Option<Vec<(String, String)>> {
+    let mut labels = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (key, after) = rest.split_once("=\"")?;
+        let mut value = String::new();
+        let mut chars = after.char_indices();
+        let end = loop {
+            match chars.next()? {
+                (_, '\\') => match chars.next()?.1 {
+                    'n' => value.push('\n'),
+                    escaped => value.push(escaped),
+                },
+                (at, '"') => break at,
+                (_, c) => value.push(c),
+            }
+        };
+        labels.push((key.trim_start_matches(',').to_string(), value));
+        rest = after[end + 1..].trim_start_matches(',');
+    }
+    Some(labels)
+}
+
+/// Parses `/health`: `ok`/`failing`, then one line per check.
+pub fn parse_health(text: &str) -> (bool, Vec<HealthCheck>) {
+    let mut lines = text.lines();
+    let healthy = lines.next().map(str::trim) == Some("ok");
+    let checks = lines
+        .filter_map(|line| {
+            let (check, status) = line.split_once(": ")?;
+            let (module, name) = check.split_once('/')?;
+            // Drop the latency: "passing (1.2ms)", "failing: reason (1.2ms)"
+            let status = status.rsplit_once(" (").map_or(status, |(status, _)| status);
+            let failure = match status {
+                "passing" => None,
+                status => Some(status.strip_prefix("failing: ").unwrap_or(status).to_string()),
+            };
+            Some(HealthCheck { module: module.to_string(), name: name.to_string(), failure })
+        })
+        .collect();
+    (healthy, checks)
+}
@@ -0,0 +1,226 @@
+//! Drawing the dashboard with ratatui.
+//!
+//! ```text
+//! ┌ http://127.0.0.1:9090 │ http://127.0.0.1:9091 ────────────── ok ┐
+//! ┌ Requests (server) ────────────┐┌ Latency ──────────────────────┐
+//! │ 1234.0/s  0.1% failed  ▂▃▅▇█▆  ││ p50 0.4ms  p90 1.2ms  ...      │
+//! ┌ Protocols ────────────────────┐┌ Breakers ─────────────────────┐
+//! │ grpc   ██████████ 80%          ││ adaptive concurrency  closed   │
+//! ┌ Modules ──────────────────────────────────────────────────────────┐
+//! │ ✔ echo  self-test/direct passing                                  │
+//! ```
+
+use std::io::{self, Stdout};
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Position, Title};
+use ratatui::widgets::{Block, Borders, Gauge, Paragraph, Row, Sparkline, Table, Tabs};
+use ratatui::{Frame as TermFrame, Terminal};
+
+use crate::dashboard::{BreakerState, Dashboard, Frame};
+
+/// What one screen shows: every endpoint's tab, the selected dashboard.
+pub struct View<'a> {
+    pub urls: Vec<&'a str>,
+    pub selected: usize,
+    pub dashboard: &'a Dashboard,
+    /// Why the last scrape of the selected endpoint failed, if it did.
+    pub error: Option<&'a str>,
+}
+
+/// The terminal the dashboard draws on.
+pub type Screen = Terminal<CrosstermBackend<Stdout>>;
+
+/// Switches to raw mode and the alternate screen; a panic restores the
+/// terminal before its message is printed.
+pub fn init() -> io::Result<Screen> {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        hook(info);
+    }));
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(io::stdout()))
+}
+
+/// Undoes [`init`]; failures are ignored, there is nothing left to do.
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen);
+}
+
+pub fn draw(term: &mut TermFrame, view: &View) {
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(7),
+            Constraint::Min(6),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .split(term.size());
+    let (tabs, top, middle, modules, footer) = (areas[0], areas[1], areas[2], areas[3], areas[4]);
+
+    let health = match view.dashboard.frame.as_ref().map(|frame| frame.healthy) {
+        Some(true) => Span::styled(" ok ", Style::default().fg(Color::Green)),
+        Some(false) => Span::styled(" failing ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        None => Span::raw(" ... "),
+    };
+    term.render_widget(
+        Tabs::new(view.urls.clone())
+            .select(view.selected)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" echo-top ")
+                    .title(Title::from(health).position(Position::Bottom).alignment(Alignment::Right)),
+            ),
+        tabs,
+    );
+
+    let hint = match view.error {
+        Some(error) => Span::styled(error.to_string(), Style::default().fg(Color::Red)),
+        None => Span::styled("q quit  ←/→ endpoint", Style::default().fg(Color::DarkGray)),
+    };
+    term.render_widget(Paragraph::new(Line::from(hint)), footer);
+
+    let Some(frame) = &view.dashboard.frame else {
+        term.render_widget(Paragraph::new("Waiting for two scrapes...").block(Block::default().borders(Borders::ALL)), top);
+        return;
+    };
+
+    let [requests, latency] = halves(top);
+    draw_requests(term, requests, frame, view.dashboard);
+    draw_latency(term, latency, frame);
+    let [protocols, breakers] = halves(middle);
+    draw_protocols(term, protocols, frame);
+    draw_breakers(term, breakers, frame);
+    draw_modules(term, modules, frame);
+}
+
+fn halves(area: Rect) -> [Rect; 2] {
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    [areas[0], areas[1]]
+}
+
+fn draw_requests(term: &mut TermFrame, area: Rect, frame: &Frame, dashboard: &Dashboard) {
+    let block = Block::default().borders(Borders::ALL).title(format!(" Requests ({}) ", frame.side));
+    let inner = block.inner(area);
+    term.render_widget(block, area);
+
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(inner);
+    let (summary, sparkline) = (areas[0], areas[1]);
+    let failed = Style::default().fg(if frame.error_ratio > 0.0 { Color::Red } else { Color::DarkGray });
+    term.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(format!("{:.1}/s", frame.requests_per_sec), Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("  "),
+            Span::styled(format!("{:.1}% failed", frame.error_ratio * 100.0), failed),
+        ])),
+        summary,
+    );
+    // Newest on the right, as many as fit
+    let rates: Vec<u64> = dashboard.rates.iter().copied().collect();
+    let shown = &rates[rates.len().saturating_sub(sparkline.width as usize)..];
+    term.render_widget(Sparkline::default().data(shown).style(Style::default().fg(Color::Cyan)), sparkline);
+}
+
+fn draw_latency(term: &mut TermFrame, area: Rect, frame: &Frame) {
+    let rows = frame.latencies.iter().map(|(name, seconds)| {
+        Row::new(vec![name.to_string(), seconds.map_or_else(|| "-".to_string(), format_seconds)])
+    });
+    term.render_widget(
+        Table::new(rows, [Constraint::Length(6), Constraint::Min(10)])
+            .block(Block::default().borders(Borders::ALL).title(" Latency ")),
+        area,
+    );
+}
+
+fn draw_protocols(term: &mut TermFrame, area: Rect, frame: &Frame) {
+    let block = Block::default().borders(Borders::ALL).title(" Protocols ");
+    let inner = block.inner(area);
+    term.render_widget(block, area);
+
+    let total: f64 = frame.protocols.iter().map(|(_, rate)| rate).sum();
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(frame.protocols.iter().map(|_| Constraint::Length(1)))
+        .split(inner);
+    for ((protocol, rate), row) in frame.protocols.iter().zip(rows.iter()) {
+        let share = if total > 0.0 { rate / total } else { 0.0 };
+        term.render_widget(
+            Gauge::default()
+                .ratio(share)
+                .label(format!("{} {:.1}/s ({:.0}%)", protocol, rate, share * 100.0))
+                .gauge_style(Style::default().fg(Color::Blue)),
+            *row,
+        );
+    }
+}
+
+fn draw_breakers(term: &mut TermFrame, area: Rect, frame: &Frame) {
+    let rows = frame.breakers.iter().map(|breaker| {
+        let color = match breaker.state {
+            BreakerState::Closed => Color::Green,
+            BreakerState::Saturated => Color::Yellow,
+            BreakerState::Open => Color::Red,
+        };
+        Row::new(vec![
+            Span::raw(breaker.name.clone()),
+            Span::styled(breaker.state.as_str(), Style::default().fg(color)),
+            Span::raw(breaker.detail.clone()),
+        ])
+    });
+    term.render_widget(
+        Table::new(rows, [Constraint::Percentage(40), Constraint::Length(10), Constraint::Min(10)])
+            .block(Block::default().borders(Borders::ALL).title(" Breakers ")),
+        area,
+    );
+}
+
+fn draw_modules(term: &mut TermFrame, area: Rect, frame: &Frame) {
+    let rows = frame.modules.iter().flat_map(|module| {
+        module.checks.iter().enumerate().map(move |(index, check)| {
+            let mark = match (index, module.healthy()) {
+                (0, true) => Span::styled("✔", Style::default().fg(Color::Green)),
+                (0, false) => Span::styled("✘", Style::default().fg(Color::Red)),
+                _ => Span::raw(""),
+            };
+            let name = if index == 0 { module.module.clone() } else { String::new() };
+            let status = match &check.failure {
+                None => Span::styled("passing", Style::default().fg(Color::Green)),
+                Some(reason) => Span::styled(format!("failing: {}", reason), Style::default().fg(Color::Red)),
+            };
+            Row::new(vec![mark, Span::raw(name), Span::raw(check.name.clone()), status])
+        })
+    });
+    term.render_widget(
+        Table::new(rows, [Constraint::Length(2), Constraint::Length(16), Constraint::Percentage(40), Constraint::Min(10)])
+            .block(Block::default().borders(Borders::ALL).title(" Modules ")),
+        area,
+    );
+}
+
+/// `0.4ms`, `12ms`, `1.25s`.
+pub fn format_seconds(seconds: f64) -> String {
+    if seconds < 0.01 {
+        format!("{:.1}ms", seconds * 1000.0)
+    } else if seconds < 1.0 {
+        format!("{:.0}ms", seconds * 1000.0)
+    } else {
+        format!("{:.2}s", seconds)
+    }
+}
@@ -1,5 +1,7 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tonic_build::compile_protos("../../api/proto/echoservice.proto")?;
+    // Map proto `bytes` fields to `bytes::Bytes` (zero-copy) instead of `Vec<u8>`
+    tonic_build::configure()
+        .bytes(["."])
+        .compile(&["../../api/proto/echoservice.proto"], &["../../api/proto"])?;
     Ok(())
 }
-
@@ -0,0 +1,508 @@
+//! gRPC channel tuning and pooling.
+//!
+//! # Architecture
+//!
+//! Long-lived clients behind NATs or load balancers lose idle TCP
+//! connections **silently** - the next call hangs until it times out.
+//! HTTP/2 keepalive pings detect that early, and evicting channels that
+//! sat idle for too long avoids reusing a connection that's likely dead:
+//!
+//! ```text
+//! ChannelPool::channel("localhost:50051")
+//!     ├─ cached & recently used → reuse
+//!     └─ missing or idle > idle_timeout → new Endpoint (keepalive settings)
+//!                                             ↓ connect_lazy
+//!                                          Channel
+//! ```
+//!
+//! When a call finds the server unreachable (e.g. during a restart), the
+//! caller asks the pool to [`ChannelPool::reconnect`]. One caller dials
+//! with exponential backoff and jitter while the others wait for its
+//! result, and every state change is broadcast as a [`ConnectivityEvent`]:
+//!
+//! ```text
+//! Ready ──call fails (UNAVAILABLE)──→ Connecting ──dial fails──→ TransientFailure
+//!   ↑                                     ↑                            │
+//!   └────────────dial succeeds────────────┴──── sleep(backoff ± jitter)┘
+//! ```
+//!
+//! On shutdown the owner calls [`ChannelPool::close`]: cached channels
+//! are dropped, connectivity subscribers see the stream end, and later
+//! checkouts fail. A pool dropped without `close()` logs the channels it
+//! abandons.
+//!
+//! # Rust Learning Note
+//!
+//! `tonic::transport::Channel` is cheap to clone - every clone shares the
+//! same underlying HTTP/2 connection. The pool hands out clones.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use hsu_common::{Error, Result};
+use tokio::sync::broadcast;
+use tonic::transport::{Channel, Endpoint};
+use tracing::{debug, info, warn};
+
+/// Backoff between reconnect attempts.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Dial attempts per reconnect before giving up.
+    pub max_attempts: u32,
+    /// Delay after the first failed attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound for the (doubling) delay.
+    pub max_backoff: Duration,
+    /// Fraction of the delay to randomize (0.0 - 1.0), so clients don't
+    /// reconnect in lockstep after a server restart.
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Returns the delay after failed attempt number `attempt` (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let base = self.initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        // Scale into [1 - jitter, 1 + jitter]
+        base.mul_f64(1.0 - jitter + 2.0 * jitter * random_fraction())
+    }
+}
+
+/// Returns a random number in `[0, 1)`.
+///
+/// Every `RandomState` is seeded differently, which is plenty for jitter
+/// and saves a dependency on `rand`.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Connection state of a pooled channel, as in gRPC's connectivity model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectivityState {
+    /// Channel created, no connection attempted yet.
+    Idle,
+    /// Dialing after a failure.
+    Connecting,
+    /// Connected.
+    Ready,
+    /// A dial attempt failed; retrying after a backoff.
+    TransientFailure,
+    /// All attempts failed; the next failing call starts over.
+    Failed,
+}
+
+/// A connectivity state change of the channel to `address`.
+#[derive(Debug, Clone)]
+pub struct ConnectivityEvent {
+    /// Address of the channel.
+    pub address: String,
+    /// New state.
+    pub state: ConnectivityState,
+    /// Dial attempt the event belongs to (0 outside reconnects).
+    pub attempt: u32,
+}
+
+/// Connection tuning for gateway-owned gRPC channels.
+#[derive(Debug, Clone)]
+pub struct GrpcChannelOptions {
+    /// Interval between HTTP/2 keepalive pings (disabled if `None`).
+    pub keepalive_interval: Option<Duration>,
+    /// How long to wait for a ping acknowledgement before closing the connection.
+    pub keepalive_timeout: Duration,
+    /// Send pings even when no call is in flight (permit-without-stream).
+    pub keepalive_while_idle: bool,
+    /// TCP-level keepalive (disabled if `None`).
+    pub tcp_keepalive: Option<Duration>,
+    /// Timeout for establishing the connection.
+    pub connect_timeout: Duration,
+    /// Evict channels unused for this long (never if `None`).
+    pub idle_timeout: Option<Duration>,
+    /// Backoff for reconnecting broken channels.
+    pub reconnect: ReconnectPolicy,
+}
+
+impl Default for GrpcChannelOptions {
+    fn default() -> Self {
+        Self {
+            keepalive_interval: Some(Duration::from_secs(30)),
+            keepalive_timeout: Duration::from_secs(10),
+            keepalive_while_idle: true,
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            connect_timeout: Duration::from_secs(5),
+            idle_timeout: Some(Duration::from_secs(300)),
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
+}
+
+impl GrpcChannelOptions {
+    /// Builds a tonic endpoint for `address` (`host:port` or a full URI).
+    pub fn endpoint(&self, address: &str) -> Result<Endpoint> {
+        let uri = if address.contains("://") {
+            address.to_string()
+        } else {
+            format!("http://{}", address)
+        };
+
+        let mut endpoint = Endpoint::from_shared(uri)
+            .map_err(|e| Error::Validation {
+                message: format!("Invalid gRPC address '{}': {}", address, e),
+            })?
+            .connect_timeout(self.connect_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .keep_alive_timeout(self.keepalive_timeout)
+            .keep_alive_while_idle(self.keepalive_while_idle);
+        if let Some(interval) = self.keepalive_interval {
+            endpoint = endpoint.http2_keep_alive_interval(interval);
+        }
+        Ok(endpoint)
+    }
+}
+
+struct PooledChannel {
+    channel: Channel,
+    /// Bumped on every reconnect, so concurrent callers that saw the same
+    /// broken channel reconnect it only once.
+    generation: u64,
+    last_used: Instant,
+}
+
+/// Cache of tuned channels, keyed by address.
+pub struct ChannelPool {
+    options: GrpcChannelOptions,
+    channels: Mutex<HashMap<String, PooledChannel>>,
+    /// Serializes reconnects (one dial loop at a time).
+    reconnecting: tokio::sync::Mutex<()>,
+    /// Dropped on close, which ends every subscription.
+    events: Mutex<Option<broadcast::Sender<ConnectivityEvent>>>,
+    closed: AtomicBool,
+}
+
+impl ChannelPool {
+    /// Creates an empty pool.
+    pub fn new(options: GrpcChannelOptions) -> Self {
+        let (events, _) = broadcast::channel(64);
+        Self {
+            options,
+            channels: Mutex::new(HashMap::new()),
+            reconnecting: tokio::sync::Mutex::new(()),
+            events: Mutex::new(Some(events)),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the pool's channel options.
+    pub fn options(&self) -> &GrpcChannelOptions {
+        &self.options
+    }
+
+    /// Subscribes to connectivity state changes of all pooled channels.
+    ///
+    /// The subscription ends (`RecvError::Closed`) when the pool is closed.
+    pub fn subscribe(&self) -> broadcast::Receiver<ConnectivityEvent> {
+        match &*self.events.lock().unwrap() {
+            Some(events) => events.subscribe(),
+            // Closed: hand out an already-ended subscription
+            None => broadcast::channel(1).1,
+        }
+    }
+
+    /// Returns a channel to `address`, creating it if missing or idle.
+    ///
+    /// The connection is established lazily, on the first call.
+    pub fn channel(&self, address: &str) -> Result<Channel> {
+        self.checkout(address).map(|(channel, _)| channel)
+    }
+
+    /// Like [`channel`](Self::channel), also returning the channel's
+    /// generation for a later [`reconnect`](Self::reconnect).
+    pub fn checkout(&self, address: &str) -> Result<(Channel, u64)> {
+        let now = Instant::now();
+        let mut channels = self.channels.lock().unwrap();
+        self.ensure_open(address)?;
+        self.evict_idle(&mut channels, now);
+
+        if let Some(pooled) = channels.get_mut(address) {
+            pooled.last_used = now;
+            return Ok((pooled.channel.clone(), pooled.generation));
+        }
+
+        debug!("[ChannelPool] Creating channel to {}", address);
+        let channel = self.options.endpoint(address)?.connect_lazy();
+        channels.insert(address.to_string(), PooledChannel {
+            channel: channel.clone(),
+            generation: 0,
+            last_used: now,
+        });
+        drop(channels);
+        self.emit(address, ConnectivityState::Idle, 0);
+        Ok((channel, 0))
+    }
+
+    /// Replaces the broken channel of `generation` with a fresh connection.
+    ///
+    /// Dials with exponential backoff and jitter. If another caller already
+    /// replaced that generation, returns its channel without dialing.
+    pub async fn reconnect(&self, address: &str, generation: u64) -> Result<(Channel, u64)> {
+        let _reconnecting = self.reconnecting.lock().await;
+        self.ensure_open(address)?;
+
+        if let Some(pooled) = self.channels.lock().unwrap().get(address) {
+            if pooled.generation != generation {
+                return Ok((pooled.channel.clone(), pooled.generation));
+            }
+        }
+
+        let endpoint = self.options.endpoint(address)?;
+        let policy = &self.options.reconnect;
+        let mut attempt = 1;
+        loop {
+            self.emit(address, ConnectivityState::Connecting, attempt);
+            match endpoint.connect().await {
+                Ok(channel) => {
+                    let generation = generation + 1;
+                    let mut channels = self.channels.lock().unwrap();
+                    // Closed while dialing: don't resurrect the pool
+                    self.ensure_open(address)?;
+                    channels.insert(address.to_string(), PooledChannel {
+                        channel: channel.clone(),
+                        generation,
+                        last_used: Instant::now(),
+                    });
+                    drop(channels);
+                    self.emit(address, ConnectivityState::Ready, attempt);
+                    return Ok((channel, generation));
+                }
+                Err(e) if attempt < policy.max_attempts => {
+                    let backoff = policy.backoff(attempt);
+                    debug!("[ChannelPool] Dial {} attempt {} failed ({}), retrying in {:?}",
+                        address, attempt, e, backoff);
+                    self.emit(address, ConnectivityState::TransientFailure, attempt);
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    self.emit(address, ConnectivityState::Failed, attempt);
+                    return Err(echo_contract::unavailable(format!(
+                        "reconnecting to {} failed after {} attempts: {}", address, attempt, e)));
+                }
+            }
+        }
+    }
+
+    /// Closes the pool: drops every cached channel and ends connectivity
+    /// subscriptions. Later checkouts and reconnects fail.
+    ///
+    /// Connections close once gateways holding clones of the channels are
+    /// dropped too. Returns the number of channels closed; idempotent.
+    pub fn close(&self) -> usize {
+        let channels = {
+            let mut channels = self.channels.lock().unwrap();
+            self.closed.store(true, Ordering::SeqCst);
+            std::mem::take(&mut *channels)
+        };
+        self.events.lock().unwrap().take();
+        if !channels.is_empty() {
+            info!("[ChannelPool] ✅ Closed {} channels", channels.len());
+        }
+        channels.len()
+    }
+
+    /// Returns `true` once [`close`](Self::close) was called.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of cached channels.
+    pub fn len(&self) -> usize {
+        self.channels.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no channels are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn emit(&self, address: &str, state: ConnectivityState, attempt: u32) {
+        match state {
+            ConnectivityState::Ready if attempt > 0 => {
+                info!("[ChannelPool] ✅ Reconnected to {} (attempt {})", address, attempt)
+            }
+            ConnectivityState::Failed => warn!("[ChannelPool] Giving up on {} after {} attempts", address, attempt),
+            _ => debug!("[ChannelPool] {} → {:?}", address, state),
+        }
+        // No subscribers (or a closed pool) is fine
+        if let Some(events) = &*self.events.lock().unwrap() {
+            let _ = events.send(ConnectivityEvent {
+                address: address.to_string(),
+                state,
+                attempt,
+            });
+        }
+    }
+
+    fn ensure_open(&self, address: &str) -> Result<()> {
+        if self.is_closed() {
+            return Err(Error::Protocol(format!("Channel pool is closed (address {})", address)));
+        }
+        Ok(())
+    }
+
+    fn evict_idle(&self, channels: &mut HashMap<String, PooledChannel>, now: Instant) {
+        if let Some(idle_timeout) = self.options.idle_timeout {
+            channels.retain(|address, pooled| {
+                let keep = now.duration_since(pooled.last_used) < idle_timeout;
+                if !keep {
+                    debug!("[ChannelPool] Evicting idle channel to {}", address);
+                }
+                keep
+            });
+        }
+    }
+}
+
+impl Default for ChannelPool {
+    fn default() -> Self {
+        Self::new(GrpcChannelOptions::default())
+    }
+}
+
+impl Drop for ChannelPool {
+    fn drop(&mut self) {
+        let channels = self.channels.get_mut().unwrap();
+        if !self.closed.load(Ordering::SeqCst) && !channels.is_empty() {
+            let addresses: Vec<&str> = channels.keys().map(String::as_str).collect();
+            warn!("[ChannelPool] Dropped without close(), abandoning {} channels: {}",
+                addresses.len(), addresses.join(", "));
+        }
+    }
+}
+
+impl std::fmt::Debug for ChannelPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelPool")
+            .field("options", &self.options)
+            .field("channels", &self.len())
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_accepts_host_port_and_uri() {
+        let options = GrpcChannelOptions::default();
+
+        assert_eq!(options.endpoint("localhost:50051").unwrap().uri().to_string(), "http://localhost:50051/");
+        assert_eq!(options.endpoint("https://example.com").unwrap().uri().scheme_str(), Some("https"));
+        assert!(options.endpoint("not a uri").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reuses_and_evicts_channels() {
+        let pool = ChannelPool::new(GrpcChannelOptions::default());
+        pool.channel("localhost:50051").unwrap();
+        pool.channel("localhost:50051").unwrap();
+        assert_eq!(pool.len(), 1);
+
+        let pool = ChannelPool::new(GrpcChannelOptions {
+            idle_timeout: Some(Duration::ZERO),
+            ..Default::default()
+        });
+        pool.channel("localhost:50051").unwrap();
+        pool.channel("localhost:50052").unwrap();
+        // The first channel was idle (timeout zero) and got evicted
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_close_drops_channels_and_ends_subscriptions() {
+        let pool = ChannelPool::new(GrpcChannelOptions::default());
+        let mut events = pool.subscribe();
+        pool.channel("localhost:50051").unwrap();
+
+        assert_eq!(pool.close(), 1);
+        assert!(pool.is_empty());
+        assert_eq!(pool.close(), 0);
+
+        // The Idle event was sent before the close; then the stream ends
+        assert_eq!(events.recv().await.unwrap().state, ConnectivityState::Idle);
+        assert!(matches!(events.recv().await, Err(broadcast::error::RecvError::Closed)));
+        assert!(matches!(pool.subscribe().recv().await, Err(broadcast::error::RecvError::Closed)));
+
+        assert!(pool.channel("localhost:50051").unwrap_err().to_string().contains("closed"));
+        assert!(pool.reconnect("localhost:50051", 0).await.unwrap_err().to_string().contains("closed"));
+    }
+
+    #[test]
+    fn test_backoff_doubles_within_jitter() {
+        let policy = ReconnectPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+            jitter: 0.2,
+            ..Default::default()
+        };
+
+        for _ in 0..100 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(80) && first <= Duration::from_millis(120));
+            let third = policy.backoff(3);
+            assert!(third >= Duration::from_millis(320) && third <= Duration::from_millis(480));
+            let capped = policy.backoff(40);
+            assert!(capped <= Duration::from_millis(1200));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_reconnect_emits_events() {
+        let pool = ChannelPool::new(GrpcChannelOptions {
+            connect_timeout: Duration::from_millis(200),
+            reconnect: ReconnectPolicy {
+                max_attempts: 2,
+                initial_backoff: Duration::from_millis(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mut events = pool.subscribe();
+
+        // Nothing listens on port 1
+        let (_, generation) = pool.checkout("127.0.0.1:1").unwrap();
+        let error = pool.reconnect("127.0.0.1:1", generation).await.unwrap_err();
+        assert!(echo_contract::is_unavailable(&error));
+
+        let mut states = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            states.push(event.state);
+        }
+        assert_eq!(states, vec![
+            ConnectivityState::Idle,
+            ConnectivityState::Connecting,
+            ConnectivityState::TransientFailure,
+            ConnectivityState::Connecting,
+            ConnectivityState::Failed,
+        ]);
+    }
+}
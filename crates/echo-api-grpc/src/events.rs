@@ -0,0 +1,141 @@
+//! gRPC adapters for echo events (server streaming).
+//!
+//! # Rust Learning Note
+//!
+//! ## Server Streaming
+//!
+//! ```text
+//! EchoEventsGrpcGateway ──Subscribe()──→ EchoEventsGrpcHandler
+//!        ←── EchoEventMessage ───────────  (one per published event)
+//!        ←── EchoEventMessage ───────────
+//! ```
+//!
+//! The handler returns a `Stream` as the response body; tonic sends each
+//! item as it is produced and ends the call when the stream ends.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use tonic::transport::Channel;
+use tonic::{Request, Response, Status};
+use tracing::debug;
+
+use hsu_common::{Error, Result};
+use echo_contract::{EchoEvent, EchoEventStream, EchoEvents};
+use crate::generated::{
+    EchoEventMessage, SubscribeRequest,
+    echo_events_client::EchoEventsClient,
+    echo_events_server::EchoEvents as EchoEventsTrait,
+};
+use crate::handler::{from_unix_ms, to_unix_ms};
+
+/// gRPC handler adapter for echo events.
+#[derive(Clone)]
+pub struct EchoEventsGrpcHandler {
+    events: Arc<dyn EchoEvents>,
+}
+
+impl EchoEventsGrpcHandler {
+    /// Creates a handler streaming from `events`.
+    pub fn new(events: Arc<dyn EchoEvents>) -> Self {
+        Self { events }
+    }
+}
+
+#[tonic::async_trait]
+impl EchoEventsTrait for EchoEventsGrpcHandler {
+    type SubscribeStream = Pin<Box<dyn Stream<Item = std::result::Result<EchoEventMessage, Status>> + Send>>;
+
+    /// Streams events until the client disconnects or the module stops.
+    async fn subscribe(
+        &self,
+        _request: Request<SubscribeRequest>,
+    ) -> std::result::Result<Response<Self::SubscribeStream>, Status> {
+        debug!("gRPC Subscribe request");
+
+        let events = self.events
+            .subscribe()
+            .await
+            .map_err(|e| Status::internal(format!("Subscribe failed: {}", e)))?;
+        let messages = events.map(|event| {
+            event
+                .map(to_message)
+                .map_err(|e| Status::internal(format!("Event stream error: {}", e)))
+        });
+
+        Ok(Response::new(Box::pin(messages)))
+    }
+}
+
+/// gRPC gateway for subscribing to remote echo events.
+pub struct EchoEventsGrpcGateway {
+    client: EchoEventsClient<Channel>,
+}
+
+impl EchoEventsGrpcGateway {
+    /// Creates a gateway over `channel`.
+    pub fn new(channel: Channel) -> Self {
+        Self { client: EchoEventsClient::new(channel) }
+    }
+}
+
+#[async_trait]
+impl EchoEvents for EchoEventsGrpcGateway {
+    async fn subscribe(&self) -> Result<EchoEventStream> {
+        debug!("[EchoEventsGrpcGateway] Subscribing");
+
+        let mut client = self.client.clone();
+        let messages = client
+            .subscribe(SubscribeRequest {})
+            .await
+            .map_err(|status| Error::Protocol(format!("gRPC error: {}", status)))?
+            .into_inner();
+
+        let events = messages.map(|message| {
+            message
+                .map_err(|status| Error::Protocol(format!("gRPC stream error: {}", status)))
+                .and_then(from_message)
+        });
+        Ok(Box::pin(events))
+    }
+}
+
+/// Protocol boundary: contract event → proto message.
+fn to_message(event: EchoEvent) -> EchoEventMessage {
+    EchoEventMessage {
+        method: event.method.as_str().to_string(),
+        request_bytes: event.request_bytes,
+        success: event.success,
+        at_unix_ms: to_unix_ms(Some(event.at)),
+    }
+}
+
+/// Protocol boundary: proto message → contract event.
+fn from_message(message: EchoEventMessage) -> Result<EchoEvent> {
+    Ok(EchoEvent {
+        method: message.method.parse()?,
+        request_bytes: message.request_bytes,
+        success: message.success,
+        at: from_unix_ms(message.at_unix_ms).unwrap_or(std::time::UNIX_EPOCH),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+    use echo_contract::EchoMethod;
+
+    #[test]
+    fn test_event_round_trip() {
+        let event = EchoEvent {
+            method: EchoMethod::EchoWithSession,
+            request_bytes: 42,
+            success: false,
+            at: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+        };
+
+        assert_eq!(from_message(to_message(event.clone())).unwrap(), event);
+    }
+}
@@ -4,13 +4,38 @@
 //!
 //! This is the **client-side adapter** - calls remote gRPC service!
 
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, UNIX_EPOCH};
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use tonic::metadata::MetadataValue;
 use tonic::transport::Channel;
-use tracing::{debug, error};
+use tonic_types::StatusExt;
+use tracing::{debug, error, instrument, warn, Span};
 
-use hsu_common::Result;
-use echo_contract::EchoService;
-use crate::generated::{EchoRequest, echo_service_client::EchoServiceClient};
+use hsu_common::{ModuleID, Protocol, Result, ServiceID};
+use hsu_module_api::{GatewayFactoryFuncs, ServiceConnector, ServiceGatewayFactory};
+use echo_contract::{
+    attach_response_metadata, current_request_id, deadline_exceeded, format_transforms, integrity_error, invalid_field,
+    invalid_response, overloaded, record_attempt, unavailable,
+    ByteStream, EchoErrorKind, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, Priority, ProtocolCapabilities, RequestContext, ScheduledEcho, ServerInfo, SessionEcho,
+    ACCEPT_LANGUAGE_METADATA_KEY, CALLER_METADATA_KEY, CHECKSUM_METADATA_KEY, PRIORITY_METADATA_KEY, PROBE_METADATA_KEY,
+    RESPONSE_METADATA_PREFIX, TRACE_METADATA_KEY, TRANSFORM_METADATA_KEY,
+};
+use crate::generated::{
+    EchoRequest, EchoBytesRequest, EchoReliableRequest, EchoFileChunk, EchoSessionRequest, GetInfoRequest,
+    ScheduleEchoRequest, CancelScheduledEchoRequest, ExportHistoryRequest, ImportHistoryChunk, EchoBatchRequest,
+    echo_batch_result::Outcome, echo_service_client::EchoServiceClient,
+};
+use crate::integrity::{insert_checksum, verify_message_checksum};
+use crate::signing::SigningKey;
+use crate::handler::{from_history_message, from_unix_ms, to_history_request, RETRY_AFTER_METADATA_KEY};
 
 /// gRPC gateway for calling remote Echo service.
 ///
@@ -29,6 +54,9 @@ use crate::generated::{EchoRequest, echo_service_client::EchoServiceClient};
 /// ```
 pub struct EchoGrpcGateway {
     client: EchoServiceClient<Channel>,
+    deadline: Option<Duration>,
+    integrity: bool,
+    signing: Option<Arc<SigningKey>>,
 }
 
 impl EchoGrpcGateway {
@@ -37,7 +65,8 @@ impl EchoGrpcGateway {
     /// # Rust Learning Note
     ///
     /// This is the **only** way to create an `EchoGrpcGateway`.
-    /// Used by `ServiceGatewayFactory<C>` and the old `EchoGrpcGatewayFactory`.
+    /// Used by `ServiceGatewayFactory<C>` closures and the deprecated
+    /// `EchoGrpcGatewayFactory`.
     ///
     /// # Example
     ///
@@ -49,7 +78,163 @@ impl EchoGrpcGateway {
     /// let gateway = EchoGrpcGateway::from_client(client);
     /// ```
     pub fn from_client(client: EchoServiceClient<Channel>) -> Self {
-        Self { client }
+        Self { client, deadline: None, integrity: false, signing: None }
+    }
+    
+    /// Bounds every unary call by `deadline` (unbounded if `None`).
+    ///
+    /// The deadline is sent as the `grpc-timeout` header, so the server
+    /// can give up too, and enforced locally in case the server doesn't.
+    pub fn with_deadline(mut self, deadline: Option<Duration>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+    
+    /// Checksums the messages of unary calls both ways (see
+    /// [`crate::integrity`]); a corrupted one fails the call with
+    /// [`echo_contract::integrity_error`].
+    pub fn with_integrity(mut self, integrity: bool) -> Self {
+        self.integrity = integrity;
+        self
+    }
+    
+    /// Signs every request with `key` (unsigned if `None`; see
+    /// [`crate::signing`]). Requests then always carry a checksum,
+    /// binding the message to the signature.
+    pub fn with_signing(mut self, key: Option<Arc<SigningKey>>) -> Self {
+        self.signing = key;
+        self
+    }
+    
+    /// What this adapter supports, for [`GatewayCapabilities`]: every
+    /// method including the streams, no compression (tonic is built
+    /// without it), authenticated if requests are `signed`.
+    ///
+    /// [`GatewayCapabilities`]: echo_contract::GatewayCapabilities
+    pub fn capabilities(signed: bool) -> ProtocolCapabilities {
+        ProtocolCapabilities {
+            protocol: Protocol::Grpc,
+            wired: true,
+            streaming: true,
+            compression: Some(false),
+            auth: Some(signed),
+            encrypted: false,
+            note: None,
+        }
+    }
+    
+    /// Checks a reply against its checksum in integrity mode.
+    ///
+    /// A reply without one wasn't checked by the server: rejected as an
+    /// invalid response, not reported as corruption.
+    fn verify_reply<T: prost::Message>(&self, response: &tonic::Response<T>) -> Result<()> {
+        if !self.integrity {
+            return Ok(());
+        }
+        match verify_message_checksum(response.metadata(), response.get_ref(), "reply") {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(invalid_response(format!(
+                "reply carries no {} header (the server doesn't check integrity)", CHECKSUM_METADATA_KEY,
+            ))),
+            Err(reason) => Err(integrity_error(reason)),
+        }
+    }
+    
+    /// Builds a request for `message` to `rpc`, checksummed in integrity
+    /// mode or when signing.
+    fn request<T: prost::Message>(&self, rpc: &str, message: T) -> tonic::Request<T> {
+        record_attempt();
+        let encoded = (self.integrity || self.signing.is_some()).then(|| prost::Message::encode_to_vec(&message));
+        let mut request = tonic::Request::new(message);
+        if let Some(deadline) = self.deadline {
+            request.set_timeout(deadline);
+        }
+        // The call's span was opened before its context was entered
+        Span::current().record("request_id", current_request_id().as_str());
+        for (key, value) in context_headers(&RequestContext::current()) {
+            match value.parse::<MetadataValue<_>>() {
+                Ok(value) => {
+                    request.metadata_mut().insert(key, value);
+                }
+                Err(_) => warn!("{} '{}' is not a valid header value, leaving it out", key, value),
+            }
+        }
+        if let Some(bytes) = &encoded {
+            insert_checksum(request.metadata_mut(), bytes);
+        }
+        // Last, so the signature covers the checksum
+        if let Some(key) = &self.signing {
+            key.sign(&format!("/proto.EchoService/{}", rpc), request.metadata_mut());
+        }
+        request
+    }
+    
+    async fn call<T: prost::Message>(
+        &self,
+        call: impl Future<Output = std::result::Result<tonic::Response<T>, tonic::Status>>,
+    ) -> Result<T> {
+        Ok(self.call_response(call).await?.into_inner())
+    }
+    
+    /// Like `call`, keeping the response metadata.
+    async fn call_response<T: prost::Message>(
+        &self,
+        call: impl Future<Output = std::result::Result<tonic::Response<T>, tonic::Status>>,
+    ) -> Result<tonic::Response<T>> {
+        let response = match self.deadline {
+            Some(deadline) => tokio::time::timeout(deadline, call)
+                .await
+                .map_err(|_| deadline_exceeded(format!("gRPC call exceeded {:?}", deadline)))?,
+            None => call.await,
+        };
+        let response = response.map_err(to_protocol_error)?;
+        self.verify_reply(&response)?;
+        // Hand the server's `x-echo-meta-*` entries to the caller's scope
+        for (key, value) in response.metadata().clone().into_headers().iter() {
+            if let (Some(key), Ok(value)) = (key.as_str().strip_prefix(RESPONSE_METADATA_PREFIX), value.to_str()) {
+                attach_response_metadata(key, value);
+            }
+        }
+        Ok(response)
+    }
+    
+    /// Echoes `messages` in one EchoBatch round trip.
+    ///
+    /// The outer error means the whole batch failed (transport, deadline);
+    /// otherwise each message gets the result a separate `echo` would
+    /// have had, errors mapped the same way.
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "echo_batch", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    pub async fn echo_batch(&self, messages: Vec<Arc<str>>) -> Result<Vec<Result<Arc<str>>>> {
+        debug!("Calling: {} messages", messages.len());
+        
+        let count = messages.len();
+        let request = self.request("EchoBatch", EchoBatchRequest {
+            messages: messages.iter().map(|message| message.to_string()).collect(),
+        });
+        let mut client = self.client.clone();
+        
+        let response = self.call(client.echo_batch(request)).await?;
+        if response.results.len() != count {
+            return Err(hsu_common::Error::Protocol(format!(
+                "EchoBatch returned {} results for {} messages", response.results.len(), count,
+            )));
+        }
+        
+        Ok(response.results.into_iter().map(|result| match result.outcome {
+            Some(Outcome::Message(message)) => Ok(message.into()),
+            Some(Outcome::Error(error)) => {
+                let mut status = tonic::Status::new(tonic::Code::from(error.code), error.message);
+                if error.retry_after_ms > 0 {
+                    if let Ok(value) = format!("{}ms", error.retry_after_ms).parse() {
+                        status.metadata_mut().insert(RETRY_AFTER_METADATA_KEY, value);
+                    }
+                }
+                Err(to_protocol_error(status))
+            }
+            None => Err(hsu_common::Error::Protocol("EchoBatch result carries no outcome".to_string())),
+        }).collect())
     }
 }
 
@@ -68,7 +253,7 @@ impl EchoGrpcGateway {
 ///
 /// **Without Mutex:**
 /// ```rust,ignore
-/// pub async fn echo(&self, message: String) -> Result<String> {
+/// pub async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
 ///     self.client.echo(...)  // ❌ Error: needs &mut self
 /// }
 /// ```
@@ -77,7 +262,7 @@ impl EchoGrpcGateway {
 /// ```rust,ignore
 /// client: tokio::sync::Mutex<EchoServiceClient<Channel>>
 /// 
-/// pub async fn echo(&self, message: String) -> Result<String> {
+/// pub async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
 ///     let mut client = self.client.lock().await;
 ///     client.echo(...)  // ✅ Works!
 /// }
@@ -86,33 +271,409 @@ impl EchoGrpcGateway {
 /// This is a common pattern in async Rust!
 #[async_trait]
 impl EchoService for EchoGrpcGateway {
-    async fn echo(&self, message: String) -> Result<String> {
-        debug!("[EchoGrpcGateway] EchoService trait call: {}", message);
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "echo", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        debug!("Calling: {}", message);
         
-        let request = tonic::Request::new(EchoRequest { message });
+        // Protocol boundary: Arc<str> (contract) → String (prost)
+        let request = self.request("Echo", EchoRequest { message: message.to_string() });
         
         // Clone the client - tonic clients are cheap to clone
         // (they use Arc internally)
         let mut client = self.client.clone();
         
+        let response = self.call(client.echo(request)).await?;
+        
+        Ok(response.message.into())
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "echo_bytes", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        debug!("Calling: {} bytes", payload.len());
+        
+        let request = self.request("EchoBytes", EchoBytesRequest { payload });
+        let mut client = self.client.clone();
+        
+        let response = self.call(client.echo_bytes(request)).await?;
+        
+        Ok(response.payload)
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "echo_reliable", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        debug!("Calling: key={}", idempotency_key);
+        
+        let request = self.request("EchoReliable", EchoReliableRequest {
+            message: message.to_string(),
+            idempotency_key,
+        });
+        let mut client = self.client.clone();
+        
+        let response = self.call(client.echo_reliable(request)).await?;
+        
+        Ok(EchoAck {
+            message: response.message.into(),
+            idempotency_key: response.idempotency_key,
+            duplicate: response.duplicate,
+        })
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "echo_file", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        debug!("Stream opened");
+        
+        // A failing source ends the upload early; remember why, so we don't
+        // report the digest of a truncated file as success.
+        let source_error = Arc::new(Mutex::new(None));
+        let outbound = Outbound(Box::pin(chunks.scan(source_error.clone(), |slot, chunk| {
+            futures::future::ready(match chunk {
+                Ok(data) => Some(EchoFileChunk { data }),
+                Err(e) => {
+                    *slot.lock().unwrap() = Some(e);
+                    None
+                }
+            })
+        })));
+        
+        let mut client = self.client.clone();
         let response = client
-            .echo(request)
+            .echo_file(outbound)
+            .await
+            .map_err(to_protocol_error)?
+            .into_inner();
+        
+        if let Some(e) = source_error.lock().unwrap().take() {
+            return Err(e);
+        }
+        
+        Ok(FileDigest {
+            byte_count: response.byte_count,
+            chunk_count: response.chunk_count,
+            sha256: response.sha256,
+        })
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "echo_with_session", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        debug!("Calling: session={}", session_id);
+        
+        let request = self.request("EchoWithSession", EchoSessionRequest {
+            session_id,
+            message: message.to_string(),
+        });
+        let mut client = self.client.clone();
+        
+        let response = self.call(client.echo_with_session(request)).await?;
+        
+        Ok(SessionEcho {
+            message: response.message.into(),
+            session_id: response.session_id,
+            count: response.count,
+            previous_seen: from_unix_ms(response.previous_seen_unix_ms),
+        })
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "get_info", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn get_info(&self) -> Result<ServerInfo> {
+        debug!("Calling");
+        
+        let request = self.request("GetInfo", GetInfoRequest {});
+        let mut client = self.client.clone();
+        
+        let response = self.call(client.get_info(request)).await?;
+        
+        Ok(ServerInfo {
+            module_id: response.module_id,
+            instance_id: response.instance_id,
+            version: response.version,
+            git_hash: response.git_hash,
+            uptime: Duration::from_millis(response.uptime_ms),
+            features: response.features,
+        })
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "schedule_echo", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        debug!("Calling: schedule={}", schedule);
+        
+        let request = self.request("ScheduleEcho", ScheduleEchoRequest {
+            message: message.to_string(),
+            schedule: schedule.to_string(),
+        });
+        let mut client = self.client.clone();
+        
+        let response = self.call(client.schedule_echo(request)).await?;
+        
+        Ok(ScheduledEcho {
+            job_id: response.job_id,
+            next_run: UNIX_EPOCH + Duration::from_millis(response.next_run_unix_ms),
+        })
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "cancel_scheduled_echo", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        debug!("Calling: job={}", job_id);
+        
+        let request = self.request("CancelScheduledEcho", CancelScheduledEchoRequest { job_id });
+        let mut client = self.client.clone();
+        
+        let response = self.call(client.cancel_scheduled_echo(request)).await?;
+        
+        Ok(response.cancelled)
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "get_history", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        debug!("Calling: {:?}", query);
+        
+        let request = self.request("GetHistory", to_history_request(query));
+        let mut client = self.client.clone();
+        
+        let response = self.call(client.get_history(request)).await?;
+        
+        Ok(HistoryPage {
+            entries: response.entries.into_iter().map(from_history_message).collect::<Result<_>>()?,
+            next_cursor: (!response.next_cursor.is_empty()).then_some(response.next_cursor),
+        })
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "stream_history", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        debug!("Calling: {:?}", query);
+        
+        // Like echo_file, no deadline: a large history takes as long as it takes
+        let mut client = self.client.clone();
+        let messages = client
+            .stream_history(to_history_request(query))
+            .await
+            .map_err(to_protocol_error)?
+            .into_inner();
+        
+        let entries = messages.map(|message| message.map_err(to_protocol_error).and_then(from_history_message));
+        Ok(Box::pin(entries))
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "export_history", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        debug!("Calling: {} {:?}", format, query);
+        
+        // No deadline, like stream_history
+        let mut client = self.client.clone();
+        let request = ExportHistoryRequest {
+            format: format.as_str().to_string(),
+            query: Some(to_history_request(query)),
+        };
+        let chunks = client
+            .export_history(request)
             .await
-            .map_err(|e| {
-                error!("gRPC call failed: {}", e);
-                hsu_common::Error::Protocol(format!("gRPC error: {}", e))
-            })?;
+            .map_err(to_protocol_error)?
+            .into_inner();
+        
+        Ok(Box::pin(chunks.map(|chunk| chunk.map(|chunk| chunk.data).map_err(to_protocol_error))))
+    }
+    
+    #[instrument(name = "grpc_gateway", level = "debug", skip_all, fields(
+        method = "import_history", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        debug!("Stream opened: {}", format);
+        
+        // Like echo_file, a failing source ends the upload early - here
+        // without the last chunk, so the server imports nothing
+        let source_error = Arc::new(Mutex::new(None));
+        let state = (data, Some(format.as_str().to_string()), source_error.clone(), false);
+        let outbound = Outbound(Box::pin(futures::stream::unfold(state, |(mut data, mut format, source_error, done)| async move {
+            if done {
+                return None;
+            }
+            let (bytes, last) = match data.next().await {
+                Some(Ok(bytes)) => (bytes, false),
+                Some(Err(e)) => {
+                    *source_error.lock().unwrap() = Some(e);
+                    return None;
+                }
+                None => (Bytes::new(), true),
+            };
+            // The format goes with the first chunk only
+            let chunk = ImportHistoryChunk { format: format.take().unwrap_or_default(), data: bytes, last };
+            Some((chunk, (data, format, source_error, last)))
+        })));
+        
+        // No deadline, like echo_file
+        let mut client = self.client.clone();
+        let response = client.import_history(outbound).await;
+        if let Some(e) = source_error.lock().unwrap().take() {
+            return Err(e);
+        }
+        let response = response.map_err(to_protocol_error)?.into_inner();
         
-        Ok(response.into_inner().message)
+        Ok(HistoryImportReport {
+            imported: response.imported,
+            duplicates: response.duplicates,
+        })
     }
 }
 
-/// Factory for creating EchoGrpcGateway instances.
+/// Headers carrying `context` to the server (defaults are left out).
 ///
-/// # Rust Learning Note
+/// Shared with [`crate::EchoJsonGateway`], which sends the same headers
+/// over HTTP.
+pub(crate) fn context_headers(context: &RequestContext) -> Vec<(&'static str, String)> {
+    let mut headers = Vec::new();
+    if context.priority != Priority::default() {
+        headers.push((PRIORITY_METADATA_KEY, context.priority.as_str().to_string()));
+    }
+    if !context.transforms.is_empty() {
+        headers.push((TRANSFORM_METADATA_KEY, format_transforms(&context.transforms)));
+    }
+    if let Some(caller) = &context.caller {
+        headers.push((CALLER_METADATA_KEY, caller.clone()));
+    }
+    if let Some(trace) = context.trace_header() {
+        headers.push((TRACE_METADATA_KEY, trace));
+    }
+    if let Some(accept_language) = &context.accept_language {
+        headers.push((ACCEPT_LANGUAGE_METADATA_KEY, accept_language.clone()));
+    }
+    if context.probe {
+        headers.push((PROBE_METADATA_KEY, "1".to_string()));
+    }
+    headers
+}
+
+/// A request stream for tonic's client-streaming calls.
+///
+/// Passing a boxed `dyn Stream` straight to the generated client makes
+/// rustc fail to prove the `async_trait` future `Send` ("higher-ranked
+/// lifetime error"); behind a named type the bound is trivially met.
+struct Outbound<T>(BoxStream<'static, T>);
+
+impl<T> Stream for Outbound<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.0.as_mut().poll_next(cx)
+    }
+}
+
+/// Converts a failed gRPC call into a framework error.
+///
+/// `DEADLINE_EXCEEDED` maps to the contract's deadline error, the same
+/// one Direct calls return. `UNAVAILABLE` (connection refused or reset)
+/// maps to [`echo_contract::unavailable`], so callers can reconnect; the
+/// server's own unavailable errors (see [`echo_contract::in_maintenance`])
+/// keep their message.
+/// `RESOURCE_EXHAUSTED` maps to [`echo_contract::overloaded`], keeping the
+/// server's back-off (its `RetryInfo` detail, else the `retry-after` hint).
+/// `INVALID_ARGUMENT` with a `BadRequest` detail maps back to the
+/// validation error the service returned ([`echo_contract::invalid_field`]
+/// if it named a field), and an `INTERNAL` [`echo_contract::storage_error`]
+/// to itself.
+pub(crate) fn to_protocol_error(status: tonic::Status) -> hsu_common::Error {
+    error!("gRPC call failed: {}", status);
+    match status.code() {
+        tonic::Code::DeadlineExceeded => deadline_exceeded(status.message()),
+        // The server's own UNAVAILABLE error (e.g. maintenance) passes through
+        tonic::Code::Unavailable if EchoErrorKind::classify(status.message()) == Some(EchoErrorKind::Unavailable) => {
+            hsu_common::Error::Protocol(status.message().to_string())
+        }
+        tonic::Code::Unavailable => unavailable(format!("gRPC error: {}", status)),
+        tonic::Code::DataLoss => integrity_error(status.message()),
+        tonic::Code::Internal if EchoErrorKind::classify(status.message()) == Some(EchoErrorKind::Storage) => {
+            hsu_common::Error::Protocol(status.message().to_string())
+        }
+        tonic::Code::ResourceExhausted => {
+            // RetryInfo first; older servers only send the header
+            let retry_after = status
+                .get_details_retry_info()
+                .and_then(|info| info.retry_delay)
+                .or_else(|| {
+                    status
+                        .metadata()
+                        .get(RETRY_AFTER_METADATA_KEY)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(parse_retry_after)
+                })
+                .unwrap_or(DEFAULT_RETRY_AFTER);
+            overloaded(retry_after, status.message())
+        }
+        tonic::Code::InvalidArgument => match status.get_details_bad_request() {
+            // The first violation; the handler never sends more
+            Some(bad_request) => match bad_request.field_violations.into_iter().next() {
+                Some(violation) if !violation.field.is_empty() => invalid_field(&violation.field, violation.description),
+                Some(violation) => hsu_common::Error::Validation { message: violation.description },
+                None => hsu_common::Error::Validation { message: status.message().to_string() },
+            },
+            None => hsu_common::Error::Protocol(format!("gRPC error: {}", status)),
+        },
+        _ => hsu_common::Error::Protocol(format!("gRPC error: {}", status)),
+    }
+}
+
+/// Back-off used when an overloaded server sends no `retry-after` hint.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Parses a `retry-after` hint: `250ms`, or whole seconds like `2`.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    match value.strip_suffix("ms") {
+        Some(millis) => millis.parse().ok().map(Duration::from_millis),
+        None => value.parse().ok().map(Duration::from_secs),
+    }
+}
+
+/// Factory for creating EchoGrpcGateway instances (deprecated).
+///
+/// # Upgrading
+///
+/// Gateways are built by `ServiceGatewayFactory<dyn EchoService>` from
+/// per-protocol closures (`GatewayFactoryFuncs`), which is how echo-api
+/// assembles them. Until this type is removed (0.2.0) it adapts itself
+/// to that pattern, so old call sites keep working:
+///
+/// ```rust,ignore
+/// // Before
+/// let gateway = EchoGrpcGatewayFactory::new().create_gateway(address).await?;
+///
+/// // Step 1: same factory, new pattern (still warns)
+/// let factory = EchoGrpcGatewayFactory::new().service_gateway_factory(module_id, service_id, connector);
+///
+/// // Step 2: no deprecated types left
+/// let funcs = GatewayFactoryFuncs {
+///     direct: None,
+///     grpc: Some(Box::new(|channel| {
+///         Ok(Arc::new(EchoGrpcGateway::from_client(EchoServiceClient::new(channel))) as Arc<dyn EchoService>)
+///     })),
+///     http: None,
+/// };
+/// let factory = ServiceGatewayFactory::<dyn EchoService>::new(module_id, service_id, connector, funcs);
+/// let gateway = factory.new_service_gateway(Protocol::Grpc).await?;
+/// ```
 ///
-/// This factory implements the `ProtocolGatewayFactory` trait,
-/// matching Go's `GatewayFactoryFunc` pattern!
+/// Better still, let `echo_api::EchoServiceGatewaysImpl` build them: it
+/// adds Direct, deadlines, pooling and the other gateway options.
 ///
 /// **Go equivalent:**
 /// ```go
@@ -124,44 +685,72 @@ impl EchoService for EchoGrpcGateway {
 ///     return &grpcGateway1{grpcClient: grpcClient, logger: logger}
 /// }
 /// ```
-///
-/// **Rust version:**
-/// ```rust,ignore
-/// #[async_trait]
-/// impl ProtocolGatewayFactory for EchoGrpcGatewayFactory {
-///     async fn create_gateway(&self, address: String) -> Result<ServiceGateway> {
-///         let gateway = EchoGrpcGateway::connect(address).await?;
-///         Ok(ServiceGateway::Grpc(GrpcGateway::Echo(Arc::new(gateway))))
-///     }
-/// }
-/// ```
-///
-/// ## Usage
-///
-/// ```rust,ignore
-/// use hsu_module_management::{GatewayConfig, ProtocolGatewayFactory};
-/// use echo_api_grpc::EchoGrpcGatewayFactory;
-///
-/// let config = GatewayConfig::new(ServiceID::from("echo-service"), Protocol::Grpc)
-///     .with_factory(Arc::new(EchoGrpcGatewayFactory));
-/// ```
+#[deprecated(
+    since = "0.1.0",
+    note = "build gateways with `ServiceGatewayFactory<dyn EchoService>` (see `service_gateway_factory`); removed in 0.2.0"
+)]
 pub struct EchoGrpcGatewayFactory;
 
+#[allow(deprecated)]
 impl EchoGrpcGatewayFactory {
     /// Creates a new factory instance.
     pub fn new() -> Self {
         Self
     }
+
+    /// Connects to `address` (`http://host:port`) and returns a gateway
+    /// over the new channel - the old entry point.
+    pub async fn create_gateway(&self, address: String) -> Result<Arc<dyn EchoService>> {
+        let channel = Channel::from_shared(address.clone())
+            .map_err(|e| hsu_common::Error::Protocol(format!("Invalid gRPC address '{}': {}", address, e)))?
+            .connect()
+            .await
+            .map_err(|e| unavailable(format!("can't connect to {}: {}", address, e)))?;
+        self.create_gateway_from_channel(channel)
+    }
+
+    /// Returns a gateway over an existing `channel`.
+    pub fn create_gateway_from_channel(&self, channel: Channel) -> Result<Arc<dyn EchoService>> {
+        debug!("Creating gRPC gateway (deprecated factory)");
+        Ok(Arc::new(EchoGrpcGateway::from_client(EchoServiceClient::new(channel))))
+    }
+
+    /// This factory as the gRPC entry of the new pattern's closures
+    /// (no Direct or HTTP entry).
+    pub fn factory_funcs(&self) -> GatewayFactoryFuncs<dyn EchoService> {
+        let factory = Self;
+        GatewayFactoryFuncs {
+            direct: None,
+            grpc: Some(Box::new(move |channel| factory.create_gateway_from_channel(channel))),
+            http: None,
+        }
+    }
+
+    /// A `ServiceGatewayFactory` handing out this factory's gateways for
+    /// `Grpc` (and `Auto`), connected by `service_connector`.
+    pub fn service_gateway_factory(
+        &self,
+        module_id: ModuleID,
+        service_id: ServiceID,
+        service_connector: Arc<dyn ServiceConnector>,
+    ) -> ServiceGatewayFactory<dyn EchoService> {
+        ServiceGatewayFactory::new(module_id, service_id, service_connector, self.factory_funcs())
+    }
 }
 
+#[allow(deprecated)]
 impl Default for EchoGrpcGatewayFactory {
     fn default() -> Self {
         Self::new()
     }
 }
 
-// DEPRECATED: Old trait implementation removed
-// Use ServiceGatewayFactory<C> pattern instead (see echo-api crate)
+#[allow(deprecated)]
+impl From<EchoGrpcGatewayFactory> for GatewayFactoryFuncs<dyn EchoService> {
+    fn from(factory: EchoGrpcGatewayFactory) -> Self {
+        factory.factory_funcs()
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -177,6 +766,63 @@ mod tests {
     }
     
     #[test]
+    fn test_deadline_exceeded_status_maps_to_contract_error() {
+        let error = to_protocol_error(tonic::Status::deadline_exceeded("too slow"));
+        assert!(echo_contract::is_deadline_exceeded(&error));
+        
+        let error = to_protocol_error(tonic::Status::unavailable("down"));
+        assert!(!echo_contract::is_deadline_exceeded(&error));
+        assert!(echo_contract::is_unavailable(&error));
+        
+        let error = to_protocol_error(tonic::Status::data_loss("request checksum mismatch"));
+        assert!(echo_contract::is_integrity_error(&error));
+        
+        let error = to_protocol_error(tonic::Status::unavailable("UNAVAILABLE: maintenance: upgrading"));
+        assert_eq!(echo_contract::maintenance_reason(&error), Some("upgrading"));
+        
+        let error = to_protocol_error(tonic::Status::internal("STORAGE_ERROR: Job store jobs.json: disk full"));
+        assert!(echo_contract::is_storage_error(&error));
+        assert!(!echo_contract::is_storage_error(&to_protocol_error(tonic::Status::internal("Service error: boom"))));
+    }
+    
+    #[test]
+    fn test_resource_exhausted_keeps_retry_after() {
+        let mut status = tonic::Status::resource_exhausted("busy");
+        status.metadata_mut().insert(RETRY_AFTER_METADATA_KEY, "250ms".parse().unwrap());
+        let error = to_protocol_error(status);
+        assert!(echo_contract::is_overloaded(&error));
+        assert_eq!(echo_contract::retry_after(&error), Some(Duration::from_millis(250)));
+        
+        let error = to_protocol_error(tonic::Status::resource_exhausted("busy"));
+        assert_eq!(echo_contract::retry_after(&error), Some(DEFAULT_RETRY_AFTER));
+        assert_eq!(parse_retry_after("2"), Some(Duration::from_secs(2)));
+    }
+    
+    #[test]
+    fn test_error_details_survive_the_wire() {
+        use tonic_types::ErrorDetails;
+        
+        // RetryInfo wins over the header
+        let details = ErrorDetails::with_retry_info(Some(Duration::from_millis(750)));
+        let mut status = tonic::Status::with_error_details(tonic::Code::ResourceExhausted, "busy", details);
+        status.metadata_mut().insert(RETRY_AFTER_METADATA_KEY, "250ms".parse().unwrap());
+        assert_eq!(echo_contract::retry_after(&to_protocol_error(status)), Some(Duration::from_millis(750)));
+        
+        let details = ErrorDetails::with_bad_request_violation("idempotency_key", "must not be empty");
+        let status = tonic::Status::with_error_details(tonic::Code::InvalidArgument, "bad", details);
+        let error = to_protocol_error(status);
+        assert_eq!(
+            echo_contract::field_violation(&error),
+            Some(echo_contract::FieldViolation::new("idempotency_key", "must not be empty")),
+        );
+        
+        // Without details it stays a protocol error, as from any other server
+        let error = to_protocol_error(tonic::Status::invalid_argument("bad"));
+        assert!(matches!(error, hsu_common::Error::Protocol(_)));
+    }
+    
+    #[test]
+    #[allow(deprecated)]
     fn test_factory_creation() {
         let factory = EchoGrpcGatewayFactory::new();
         let _ = factory; // Use it
@@ -184,5 +830,21 @@ mod tests {
         let factory2 = EchoGrpcGatewayFactory::default();
         let _ = factory2;
     }
+    
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn test_deprecated_factory_adapts_to_factory_funcs() {
+        let funcs: GatewayFactoryFuncs<dyn EchoService> = EchoGrpcGatewayFactory::new().into();
+        assert!(funcs.direct.is_none());
+        assert!(funcs.http.is_none());
+        
+        // Nothing listens there: the gateway is built, its calls fail as UNAVAILABLE
+        let channel = tonic::transport::Endpoint::from_static("http://127.0.0.1:1").connect_lazy();
+        let gateway = (funcs.grpc.unwrap())(channel).unwrap();
+        assert!(echo_contract::is_unavailable(&gateway.echo("hi".into()).await.unwrap_err()));
+        
+        let error = EchoGrpcGatewayFactory::new().create_gateway("http://127.0.0.1:1".to_string()).await.unwrap_err();
+        assert!(echo_contract::is_unavailable(&error));
+    }
 }
 
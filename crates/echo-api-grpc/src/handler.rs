@@ -18,14 +18,36 @@
 //!
 //! **Key insight:** Domain code doesn't know about gRPC!
 
-use tonic::{Request, Response, Status};
+use std::collections::BTreeMap;
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::{Code, Request, Response, Status, Streaming};
+use tonic_types::{ErrorDetails, StatusExt};
+use std::pin::Pin;
 use std::sync::Arc;
-use tracing::{debug, error};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use futures::{Stream, StreamExt};
+use tracing::{debug, error, instrument, warn, Span};
 
-use echo_contract::EchoService;
+use echo_contract::{
+    collect_response_metadata, field_violation, is_integrity_error, is_overloaded, is_storage_error, is_unavailable, parse_transforms, retry_after, ByteStream,
+    EchoErrorKind, EchoSchedule, EchoService, FieldViolation, HistoryEntry, HistoryExportFormat, HistoryQuery, RequestContext,
+    ACCEPT_LANGUAGE_METADATA_KEY, CALLER_METADATA_KEY, CHECKSUM_METADATA_KEY, CONTENT_LANGUAGE_METADATA_KEY, PRIORITY_METADATA_KEY, PROBE_METADATA_KEY,
+    RESPONSE_METADATA_PREFIX, TRACE_METADATA_KEY, TRANSFORM_METADATA_KEY,
+};
 #[cfg(test)]
 use echo_server::EchoServiceImpl;  // Test-only import from echo-server
-use crate::generated::{EchoRequest, EchoResponse, echo_service_server::EchoService as EchoServiceTrait};
+use crate::integrity::{insert_checksum, verify_message_checksum};
+use crate::generated::{
+    EchoRequest, EchoResponse, EchoBytesRequest, EchoBytesResponse,
+    EchoReliableRequest, EchoReliableResponse,
+    EchoFileChunk, EchoFileResponse, EchoSessionRequest, EchoSessionResponse,
+    GetInfoRequest, GetInfoResponse, ScheduleEchoRequest, ScheduleEchoResponse,
+    CancelScheduledEchoRequest, CancelScheduledEchoResponse,
+    GetHistoryRequest, GetHistoryResponse, HistoryEntryMessage, ExportHistoryRequest, HistoryExportChunk,
+    ImportHistoryChunk, ImportHistoryResponse, EchoBatchRequest, EchoBatchResponse, EchoBatchResult, EchoBatchError,
+    echo_batch_result::Outcome,
+    echo_service_server::EchoService as EchoServiceTrait,
+};
 
 /// gRPC handler adapter for Echo service.
 ///
@@ -89,29 +111,615 @@ impl EchoServiceTrait for EchoGrpcHandler {
     /// ```
     ///
     /// **Solution:** Implement `From<Error> for Status`
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "echo", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
     async fn echo(
         &self,
         request: Request<EchoRequest>,
     ) -> Result<Response<EchoResponse>, Status> {
-        let message = request.into_inner().message;
+        // Protocol boundary: String (prost) → Arc<str> (contract)
+        let context = request_context(&request)?;
+        let integrity = integrity_requested(&request)?;
+        let message: Arc<str> = request.into_inner().message.into();
         debug!("gRPC Echo request: {}", message);
 
         // Call domain service
-        let result = self.service
-            .echo(message)
-            .await
-            .map_err(|e| {
-                error!("Echo service error: {}", e);
-                Status::internal(format!("Service error: {}", e))
-            })?;
+        let (result, metadata) = collect_response_metadata(context.scope(self.service.echo(message))).await;
+        let result = result.map_err(|e| localized_status(e, &metadata))?;
 
-        Ok(Response::new(EchoResponse { message: result }))
+        Ok(with_checksum(with_metadata(Response::new(EchoResponse { message: result.to_string() }), metadata), integrity))
     }
+
+    /// Handles EchoBytes gRPC requests.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "echo_bytes", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo_bytes(
+        &self,
+        request: Request<EchoBytesRequest>,
+    ) -> Result<Response<EchoBytesResponse>, Status> {
+        let context = request_context(&request)?;
+        let integrity = integrity_requested(&request)?;
+        let payload = request.into_inner().payload;
+        debug!("gRPC EchoBytes request: {} bytes", payload.len());
+
+        let (payload, metadata) = collect_response_metadata(context.scope(self.service.echo_bytes(payload))).await;
+        let payload = payload.map_err(|e| localized_status(e, &metadata))?;
+
+        Ok(with_checksum(with_metadata(Response::new(EchoBytesResponse { payload }), metadata), integrity))
+    }
+
+    /// Handles EchoReliable gRPC requests.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "echo_reliable", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo_reliable(
+        &self,
+        request: Request<EchoReliableRequest>,
+    ) -> Result<Response<EchoReliableResponse>, Status> {
+        let context = request_context(&request)?;
+        let integrity = integrity_requested(&request)?;
+        let EchoReliableRequest { message, idempotency_key } = request.into_inner();
+        debug!("gRPC EchoReliable request: key={}", idempotency_key);
+
+        let (ack, metadata) = collect_response_metadata(
+            context.scope(self.service.echo_reliable(message.into(), idempotency_key)),
+        ).await;
+        let ack = ack.map_err(|e| localized_status(e, &metadata))?;
+
+        Ok(with_checksum(with_metadata(Response::new(EchoReliableResponse {
+            message: ack.message.to_string(),
+            idempotency_key: ack.idempotency_key,
+            duplicate: ack.duplicate,
+        }), metadata), integrity))
+    }
+
+    /// Handles EchoFile client-streaming requests.
+    ///
+    /// The inbound gRPC stream is adapted into the contract's `ByteStream`,
+    /// so the domain service consumes chunks as they arrive.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "echo_file", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo_file(
+        &self,
+        request: Request<Streaming<EchoFileChunk>>,
+    ) -> Result<Response<EchoFileResponse>, Status> {
+        debug!("gRPC EchoFile stream opened");
+
+        let context = request_context(&request)?;
+        let chunks = request.into_inner().map(|chunk| {
+            chunk
+                .map(|c| c.data)
+                .map_err(|status| hsu_common::Error::Protocol(format!("gRPC stream error: {}", status)))
+        });
+
+        let (digest, metadata) = collect_response_metadata(
+            context.scope(self.service.echo_file(Box::pin(chunks))),
+        ).await;
+        let digest = digest.map_err(|e| localized_status(e, &metadata))?;
+
+        Ok(with_metadata(Response::new(EchoFileResponse {
+            byte_count: digest.byte_count,
+            chunk_count: digest.chunk_count,
+            sha256: digest.sha256,
+        }), metadata))
+    }
+
+    /// Handles EchoWithSession gRPC requests.
+    ///
+    /// Session state lives in the service, not the connection, so a client
+    /// that reconnects (or switches to Direct) keeps its session.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "echo_with_session", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo_with_session(
+        &self,
+        request: Request<EchoSessionRequest>,
+    ) -> Result<Response<EchoSessionResponse>, Status> {
+        let context = request_context(&request)?;
+        let integrity = integrity_requested(&request)?;
+        let EchoSessionRequest { session_id, message } = request.into_inner();
+        debug!("gRPC EchoWithSession request: session={}", session_id);
+
+        let (echo, metadata) = collect_response_metadata(
+            context.scope(self.service.echo_with_session(session_id, message.into())),
+        ).await;
+        let echo = echo.map_err(|e| localized_status(e, &metadata))?;
+
+        Ok(with_checksum(with_metadata(Response::new(EchoSessionResponse {
+            message: echo.message.to_string(),
+            session_id: echo.session_id,
+            count: echo.count,
+            previous_seen_unix_ms: to_unix_ms(echo.previous_seen),
+        }), metadata), integrity))
+    }
+
+    /// Handles GetInfo gRPC requests.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "get_info", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn get_info(
+        &self,
+        request: Request<GetInfoRequest>,
+    ) -> Result<Response<GetInfoResponse>, Status> {
+        debug!("gRPC GetInfo request");
+
+        let context = request_context(&request)?;
+        let integrity = integrity_requested(&request)?;
+        let (info, metadata) = collect_response_metadata(context.scope(self.service.get_info())).await;
+        let info = info.map_err(|e| localized_status(e, &metadata))?;
+
+        Ok(with_checksum(with_metadata(Response::new(GetInfoResponse {
+            module_id: info.module_id,
+            instance_id: info.instance_id,
+            version: info.version,
+            git_hash: info.git_hash,
+            uptime_ms: info.uptime.as_millis() as u64,
+            features: info.features,
+        }), metadata), integrity))
+    }
+
+    /// Handles ScheduleEcho gRPC requests.
+    ///
+    /// The schedule arrives as text; one that doesn't parse is rejected
+    /// as `INVALID_ARGUMENT` before it reaches the service.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "schedule_echo", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn schedule_echo(
+        &self,
+        request: Request<ScheduleEchoRequest>,
+    ) -> Result<Response<ScheduleEchoResponse>, Status> {
+        let context = request_context(&request)?;
+        let integrity = integrity_requested(&request)?;
+        let ScheduleEchoRequest { message, schedule } = request.into_inner();
+        debug!("gRPC ScheduleEcho request: schedule={}", schedule);
+        let schedule: EchoSchedule = schedule.parse().map_err(|e: hsu_common::Error| Status::invalid_argument(e.to_string()))?;
+
+        let (scheduled, metadata) = collect_response_metadata(
+            context.scope(self.service.schedule_echo(message.into(), schedule)),
+        ).await;
+        let scheduled = scheduled.map_err(|e| localized_status(e, &metadata))?;
+
+        Ok(with_checksum(with_metadata(Response::new(ScheduleEchoResponse {
+            job_id: scheduled.job_id,
+            next_run_unix_ms: to_unix_ms(Some(scheduled.next_run)),
+        }), metadata), integrity))
+    }
+
+    /// Handles CancelScheduledEcho gRPC requests.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "cancel_scheduled_echo", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn cancel_scheduled_echo(
+        &self,
+        request: Request<CancelScheduledEchoRequest>,
+    ) -> Result<Response<CancelScheduledEchoResponse>, Status> {
+        let context = request_context(&request)?;
+        let integrity = integrity_requested(&request)?;
+        let CancelScheduledEchoRequest { job_id } = request.into_inner();
+        debug!("gRPC CancelScheduledEcho request: job={}", job_id);
+
+        let (cancelled, metadata) = collect_response_metadata(
+            context.scope(self.service.cancel_scheduled_echo(job_id)),
+        ).await;
+        let cancelled = cancelled.map_err(|e| localized_status(e, &metadata))?;
+
+        Ok(with_checksum(with_metadata(Response::new(CancelScheduledEchoResponse { cancelled }), metadata), integrity))
+    }
+
+    /// Handles GetHistory gRPC requests.
+    ///
+    /// A cursor that doesn't parse is rejected as `INVALID_ARGUMENT`.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "get_history", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn get_history(
+        &self,
+        request: Request<GetHistoryRequest>,
+    ) -> Result<Response<GetHistoryResponse>, Status> {
+        let context = request_context(&request)?;
+        let integrity = integrity_requested(&request)?;
+        let query = from_history_request(request.into_inner())?;
+        debug!("gRPC GetHistory request: {:?}", query);
+
+        let (page, metadata) = collect_response_metadata(
+            context.scope(self.service.get_history(query)),
+        ).await;
+        let page = page.map_err(|e| localized_status(e, &metadata))?;
+
+        Ok(with_checksum(with_metadata(Response::new(GetHistoryResponse {
+            entries: page.entries.into_iter().map(to_history_message).collect(),
+            next_cursor: page.next_cursor.unwrap_or_default(),
+        }), metadata), integrity))
+    }
+
+    type StreamHistoryStream = Pin<Box<dyn Stream<Item = Result<HistoryEntryMessage, Status>> + Send>>;
+
+    /// Handles StreamHistory gRPC requests (server streaming).
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "stream_history", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn stream_history(
+        &self,
+        request: Request<GetHistoryRequest>,
+    ) -> Result<Response<Self::StreamHistoryStream>, Status> {
+        let context = request_context(&request)?;
+        integrity_requested(&request)?;
+        let query = from_history_request(request.into_inner())?;
+        debug!("gRPC StreamHistory request: {:?}", query);
+
+        let (entries, metadata) = collect_response_metadata(
+            context.scope(self.service.stream_history(query)),
+        ).await;
+        let entries = entries.map_err(|e| localized_status(e, &metadata))?;
+        let messages = entries.map(|entry| entry.map(to_history_message).map_err(to_status));
+
+        Ok(with_metadata(Response::new(Box::pin(messages) as Self::StreamHistoryStream), metadata))
+    }
+
+    type ExportHistoryStream = Pin<Box<dyn Stream<Item = Result<HistoryExportChunk, Status>> + Send>>;
+
+    /// Handles ExportHistory gRPC requests (server streaming).
+    ///
+    /// An unknown format or a bad cursor is rejected as `INVALID_ARGUMENT`.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "export_history", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn export_history(
+        &self,
+        request: Request<ExportHistoryRequest>,
+    ) -> Result<Response<Self::ExportHistoryStream>, Status> {
+        let context = request_context(&request)?;
+        integrity_requested(&request)?;
+        let request = request.into_inner();
+        let format = from_format_name(&request.format)?;
+        let query = from_history_request(request.query.unwrap_or_default())?;
+        debug!("gRPC ExportHistory request: {} {:?}", format, query);
+
+        let (chunks, metadata) = collect_response_metadata(
+            context.scope(self.service.export_history(format, query)),
+        ).await;
+        let chunks = chunks.map_err(|e| localized_status(e, &metadata))?;
+        let messages = chunks.map(|chunk| chunk.map(|data| HistoryExportChunk { data }).map_err(to_status));
+
+        Ok(with_metadata(Response::new(Box::pin(messages) as Self::ExportHistoryStream), metadata))
+    }
+
+    /// Handles ImportHistory client-streaming requests.
+    ///
+    /// The format comes with the first chunk. A malformed file is
+    /// rejected as `INVALID_ARGUMENT`, naming the bad line; a stream
+    /// broken off before its last chunk imports nothing.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "import_history", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn import_history(
+        &self,
+        request: Request<Streaming<ImportHistoryChunk>>,
+    ) -> Result<Response<ImportHistoryResponse>, Status> {
+        let context = request_context(&request)?;
+        let mut chunks = request.into_inner();
+        let Some(first) = chunks.message().await? else {
+            return Err(Status::invalid_argument("ImportHistory stream ended before its last chunk"));
+        };
+        let format = from_format_name(&first.format)?;
+        debug!("gRPC ImportHistory stream opened: {}", format);
+        let data = import_data(first, chunks);
+
+        let (report, metadata) = collect_response_metadata(
+            context.scope(self.service.import_history(format, data)),
+        ).await;
+        let report = report.map_err(|e| localized_status(e, &metadata))?;
+
+        Ok(with_metadata(Response::new(ImportHistoryResponse {
+            imported: report.imported,
+            duplicates: report.duplicates,
+        }), metadata))
+    }
+
+    /// Handles EchoBatch gRPC requests.
+    ///
+    /// Every message is echoed like a separate Echo call (concurrently,
+    /// all in the request's context), so quotas, limits and history see
+    /// each one. A failed echo fails only its own result.
+    #[instrument(name = "grpc_handler", level = "debug", skip_all, fields(
+        method = "echo_batch", protocol = "grpc", request_id = tracing::field::Empty,
+    ))]
+    async fn echo_batch(
+        &self,
+        request: Request<EchoBatchRequest>,
+    ) -> Result<Response<EchoBatchResponse>, Status> {
+        let context = request_context(&request)?;
+        let integrity = integrity_requested(&request)?;
+        let messages = request.into_inner().messages;
+        debug!("gRPC EchoBatch request: {} messages", messages.len());
+        if messages.len() > MAX_BATCH_MESSAGES {
+            return Err(Status::invalid_argument(format!(
+                "EchoBatch carries {} messages, at most {} allowed", messages.len(), MAX_BATCH_MESSAGES,
+            )));
+        }
+
+        let echoes = messages.into_iter().map(|message| self.service.echo(message.into()));
+        let (results, metadata) = collect_response_metadata(context.scope(futures::future::join_all(echoes))).await;
+        let results = results.into_iter().map(|result| EchoBatchResult {
+            outcome: Some(match result {
+                Ok(message) => Outcome::Message(message.to_string()),
+                Err(e) => Outcome::Error(to_batch_error(e)),
+            }),
+        }).collect();
+
+        Ok(with_checksum(with_metadata(Response::new(EchoBatchResponse { results }), metadata), integrity))
+    }
+}
+
+/// Most messages one EchoBatch request may carry.
+pub const MAX_BATCH_MESSAGES: usize = 1024;
+
+/// Converts the error of one batched echo into what a separate Echo call
+/// would have failed with.
+fn to_batch_error(e: hsu_common::Error) -> EchoBatchError {
+    let status = to_status(e);
+    let retry_after_ms = status
+        .metadata()
+        .get(RETRY_AFTER_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_suffix("ms"))
+        .and_then(|millis| millis.parse().ok())
+        .unwrap_or(0);
+    EchoBatchError { code: status.code() as i32, message: status.message().to_string(), retry_after_ms }
+}
+
+/// The file carried by an ImportHistory stream, starting with `first`.
+///
+/// Ends after the chunk marked `last`. A stream ending before it ends
+/// with an error instead, so the truncated file is never imported.
+fn import_data(first: ImportHistoryChunk, rest: Streaming<ImportHistoryChunk>) -> ByteStream {
+    let chunks = futures::stream::iter([Ok(first)]).chain(rest).boxed();
+    Box::pin(futures::stream::unfold((chunks, false), |(mut chunks, done)| async move {
+        if done {
+            return None;
+        }
+        let error = match chunks.next().await {
+            Some(Ok(chunk)) => {
+                let last = chunk.last;
+                return Some((Ok(chunk.data), (chunks, last)));
+            }
+            Some(Err(status)) => format!("gRPC stream error: {}", status),
+            None => "ImportHistory stream ended before its last chunk".to_string(),
+        };
+        Some((Err(hsu_common::Error::Protocol(error)), (chunks, true)))
+    }))
+}
+
+/// Protocol boundary: format name → contract format (`""` = the default).
+#[allow(clippy::result_large_err)] // fails with the `Status` the handler returns
+fn from_format_name(name: &str) -> Result<HistoryExportFormat, Status> {
+    match name {
+        "" => Ok(HistoryExportFormat::default()),
+        name => name.parse().map_err(|e: hsu_common::Error| Status::invalid_argument(e.to_string())),
+    }
+}
+
+/// Protocol boundary: proto request → contract query.
+#[allow(clippy::result_large_err)] // fails with the `Status` the handler returns
+pub(crate) fn from_history_request(request: GetHistoryRequest) -> Result<HistoryQuery, Status> {
+    let query = HistoryQuery {
+        cursor: (!request.cursor.is_empty()).then_some(request.cursor),
+        limit: request.limit,
+        since: from_unix_ms(request.since_unix_ms),
+        until: from_unix_ms(request.until_unix_ms),
+        contains: (!request.contains.is_empty()).then_some(request.contains),
+    };
+    query.cursor_id().map_err(|e| Status::invalid_argument(e.to_string()))?;
+    Ok(query)
+}
+
+/// Protocol boundary: contract query → proto request.
+pub(crate) fn to_history_request(query: HistoryQuery) -> GetHistoryRequest {
+    GetHistoryRequest {
+        cursor: query.cursor.unwrap_or_default(),
+        limit: query.limit,
+        since_unix_ms: to_unix_ms(query.since),
+        until_unix_ms: to_unix_ms(query.until),
+        contains: query.contains.unwrap_or_default(),
+    }
+}
+
+/// Protocol boundary: contract entry → proto message.
+pub(crate) fn to_history_message(entry: HistoryEntry) -> HistoryEntryMessage {
+    HistoryEntryMessage {
+        id: entry.id,
+        record_id: entry.record_id,
+        method: entry.method.as_str().to_string(),
+        message: entry.message,
+        request_bytes: entry.request_bytes,
+        success: entry.success,
+        at_unix_ms: to_unix_ms(Some(entry.at)),
+    }
+}
+
+/// Protocol boundary: proto message → contract entry.
+pub(crate) fn from_history_message(message: HistoryEntryMessage) -> hsu_common::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: message.id,
+        record_id: message.record_id,
+        method: message.method.parse()?,
+        message: message.message,
+        request_bytes: message.request_bytes,
+        success: message.success,
+        at: from_unix_ms(message.at_unix_ms).unwrap_or(UNIX_EPOCH),
+    })
+}
+
+/// Encodes an optional timestamp as Unix milliseconds (`0` = none).
+pub(crate) fn to_unix_ms(time: Option<SystemTime>) -> u64 {
+    time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Decodes [`to_unix_ms`].
+pub(crate) fn from_unix_ms(millis: u64) -> Option<SystemTime> {
+    (millis > 0).then(|| UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+/// Reads the caller's request context from the gRPC metadata.
+///
+/// A missing or unknown priority falls back to the default, a missing
+/// caller to anonymous. A malformed transform chain is rejected: silently
+/// echoing the untransformed text would look like a server bug.
+#[allow(clippy::result_large_err)] // fails with the `Status` the handler returns
+fn request_context<T>(request: &Request<T>) -> Result<RequestContext, Status> {
+    let metadata = request.metadata();
+    let priority = metadata
+        .get(PRIORITY_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default();
+    let transforms = match metadata.get(TRANSFORM_METADATA_KEY) {
+        Some(value) => {
+            let chain = value.to_str().map_err(|_| Status::invalid_argument("x-echo-transform must be ASCII"))?;
+            parse_transforms(chain).map_err(|e| Status::invalid_argument(e.to_string()))?
+        }
+        None => Vec::new(),
+    };
+    let mut context = RequestContext::new(priority).with_transforms(transforms);
+    let caller = metadata.get(CALLER_METADATA_KEY).and_then(|value| value.to_str().ok());
+    if let Some(caller) = caller.filter(|caller| !caller.is_empty()) {
+        context = context.with_caller(caller);
+    }
+    if let Some(trace) = metadata.get(TRACE_METADATA_KEY).and_then(|value| value.to_str().ok()) {
+        context = context.with_trace_header(trace);
+    }
+    let accept_language = metadata.get(ACCEPT_LANGUAGE_METADATA_KEY).and_then(|value| value.to_str().ok());
+    if let Some(accept_language) = accept_language.filter(|value| !value.is_empty()) {
+        context = context.with_accept_language(accept_language);
+    }
+    if metadata.contains_key(PROBE_METADATA_KEY) {
+        context = context.as_probe();
+    }
+    Span::current().record("request_id", context.request_id.as_deref().unwrap_or("-"));
+    Ok(context)
+}
+
+/// Checks the message of a request sent in integrity mode (see
+/// [`crate::integrity`]), returning whether it was.
+///
+/// A corrupted message fails with `DATA_LOSS` before the service sees it.
+#[allow(clippy::result_large_err)] // fails with the `Status` the handler returns
+fn integrity_requested<T: prost::Message>(request: &Request<T>) -> Result<bool, Status> {
+    verify_message_checksum(request.metadata(), request.get_ref(), "request").map_err(|reason| {
+        warn!("Rejecting corrupted request: {}", reason);
+        Status::data_loss(reason)
+    })
+}
+
+/// Adds the reply's checksum to a response in integrity mode.
+fn with_checksum<T: prost::Message>(mut response: Response<T>, integrity: bool) -> Response<T> {
+    if integrity {
+        let encoded = prost::Message::encode_to_vec(response.get_ref());
+        insert_checksum(response.metadata_mut(), &encoded);
+    }
+    response
+}
+
+/// Sends the response metadata the service attached as `x-echo-meta-*` headers.
+///
+/// Entries that aren't valid header names or values are dropped.
+fn with_metadata<T>(mut response: Response<T>, metadata: BTreeMap<String, String>) -> Response<T> {
+    for (key, value) in metadata {
+        let key = MetadataKey::from_bytes(format!("{}{}", RESPONSE_METADATA_PREFIX, key).as_bytes());
+        match (key, MetadataValue::try_from(value.as_str())) {
+            (Ok(key), Ok(value)) => {
+                response.metadata_mut().insert(key, value);
+            }
+            _ => warn!("Dropping response metadata {:?}: not a valid gRPC header", value),
+        }
+    }
+    response
+}
+
+/// Metadata key carrying the back-off hint of `RESOURCE_EXHAUSTED`
+/// responses, formatted like `250ms`.
+pub const RETRY_AFTER_METADATA_KEY: &str = "retry-after";
+
+/// Converts a domain error into a gRPC status.
+///
+/// An [`echo_contract::overloaded`] error becomes `RESOURCE_EXHAUSTED`
+/// with a `RetryInfo` detail (and a `retry-after` hint for clients that
+/// don't read details), so clients back off instead of retrying at once;
+/// an [`echo_contract::limit_reached`] error is `RESOURCE_EXHAUSTED` too,
+/// without the hint.
+/// A validation error becomes `INVALID_ARGUMENT` with a `BadRequest`
+/// detail naming the field (see [`echo_contract::invalid_field`]).
+/// An [`echo_contract::integrity_error`] (e.g. a payload that failed
+/// decryption) becomes `DATA_LOSS`, which the gateway maps back.
+/// An [`echo_contract::storage_error`] becomes `INTERNAL` with its text
+/// as is, so the gateway can tell it from other internal errors.
+///
+/// Details travel as a `google.rpc.Status` in `grpc-status-details-bin`.
+fn to_status(e: hsu_common::Error) -> Status {
+    error_status(e, None)
+}
+
+/// Like [`to_status`], for an error the service returned along with
+/// `metadata`: if it rendered its text from the message catalog (see
+/// [`echo_contract::messages`]), the text also travels as a
+/// `LocalizedMessage` detail in the language it names.
+fn localized_status(e: hsu_common::Error, metadata: &BTreeMap<String, String>) -> Status {
+    error_status(e, metadata.get(CONTENT_LANGUAGE_METADATA_KEY).map(String::as_str))
+}
+
+fn error_status(e: hsu_common::Error, locale: Option<&str>) -> Status {
+    if is_integrity_error(&e) {
+        warn!("Rejecting corrupted request: {}", e);
+        return Status::data_loss(e.to_string());
+    }
+    // The message keeps the reason (e.g. maintenance) for the client
+    if is_unavailable(&e) {
+        warn!("Echo service unavailable: {}", e);
+        return Status::unavailable(e.to_string());
+    }
+    if is_overloaded(&e) {
+        let retry_after = retry_after(&e);
+        warn!("Echo service overloaded, asking client to retry after {:?}", retry_after);
+        let mut details = retry_after.map_or_else(ErrorDetails::new, |hint| ErrorDetails::with_retry_info(Some(hint)));
+        if let (Some(locale), hsu_common::Error::Protocol(message)) = (locale, &e) {
+            if let Some(detail) = EchoErrorKind::detail(message) {
+                details.set_localized_message(locale, detail);
+            }
+        }
+        let mut status = Status::with_error_details(Code::ResourceExhausted, e.to_string(), details);
+        if let Some(Ok(value)) = retry_after.map(|hint| MetadataValue::try_from(format!("{}ms", hint.as_millis()))) {
+            status.metadata_mut().insert(RETRY_AFTER_METADATA_KEY, value);
+        }
+        return status;
+    }
+    if let hsu_common::Error::Validation { message } = &e {
+        debug!("Rejecting invalid request: {}", e);
+        // A violation without a field still says what was wrong
+        let violation = field_violation(&e).unwrap_or_else(|| FieldViolation::new("", message.clone()));
+        let mut details = ErrorDetails::new();
+        if let Some(locale) = locale {
+            details.set_localized_message(locale, violation.description.clone());
+        }
+        details.add_bad_request_violation(violation.field, violation.description);
+        return Status::with_error_details(Code::InvalidArgument, e.to_string(), details);
+    }
+    if is_storage_error(&e) {
+        error!("Echo service storage failed: {}", e);
+        return Status::internal(e.to_string());
+    }
+    error!("Echo service error: {}", e);
+    Status::internal(format!("Service error: {}", e))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::integrity::message_checksum;
 
     #[tokio::test]
     async fn test_grpc_handler() {
@@ -125,5 +733,253 @@ mod tests {
         let response = handler.echo(request).await.unwrap();
         assert_eq!(response.into_inner().message, "Hello via gRPC!");
     }
-}
+    
+    #[tokio::test]
+    async fn test_response_metadata_sent_as_headers() {
+        let decoration = echo_server::ResponseDecoration::default().with_metadata("instance-id", "a1");
+        let service = echo_server::DecoratedEchoService::new(Arc::new(EchoServiceImpl::new()), decoration);
+        let handler = EchoGrpcHandler::new(Arc::new(service));
+
+        let response = handler.echo(Request::new(EchoRequest { message: "Hi".to_string() })).await.unwrap();
+        assert_eq!(response.metadata().get("x-echo-meta-instance-id").unwrap(), "a1");
+    }
+    
+    #[tokio::test]
+    async fn test_integrity_checksums_both_ways() {
+        let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new()));
+        let request = |checksum: &str| {
+            let mut request = Request::new(EchoRequest { message: "Hi".to_string() });
+            request.metadata_mut().insert(CHECKSUM_METADATA_KEY, checksum.parse().unwrap());
+            request
+        };
+
+        let checksum = |message: &str| message_checksum(&EchoRequest { message: message.to_string() });
+
+        let response = handler.echo(request(&checksum("Hi"))).await.unwrap();
+        assert_eq!(verify_message_checksum(response.metadata(), response.get_ref(), "reply"), Ok(true));
+
+        let status = handler.echo(request(&checksum("Ho"))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DataLoss);
+
+        // Without a checksum the response carries none either
+        let response = handler.echo(Request::new(EchoRequest { message: "Hi".to_string() })).await.unwrap();
+        assert!(response.metadata().get(CHECKSUM_METADATA_KEY).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_integrity_covers_other_unary_rpcs() {
+        let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new()));
+        let message = EchoSessionRequest { session_id: "s1".to_string(), message: "Hi".to_string() };
+        let request = |checksum: String| {
+            let mut request = Request::new(message.clone());
+            request.metadata_mut().insert(CHECKSUM_METADATA_KEY, checksum.parse().unwrap());
+            request
+        };
+
+        let response = handler.echo_with_session(request(message_checksum(&message))).await.unwrap();
+        assert_eq!(verify_message_checksum(response.metadata(), response.get_ref(), "reply"), Ok(true));
+
+        let tampered = EchoSessionRequest { session_id: "s2".to_string(), ..message.clone() };
+        let status = handler.echo_with_session(request(message_checksum(&tampered))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DataLoss);
+    }
+
+    #[test]
+    fn test_storage_error_keeps_its_prefix() {
+        let status = to_status(echo_contract::storage_error("History database h.db: disk I/O error"));
+        assert_eq!(status.code(), Code::Internal);
+        assert_eq!(status.message(), "STORAGE_ERROR: History database h.db: disk I/O error");
+    }
+    
+    #[tokio::test]
+    async fn test_grpc_session_counter() {
+        let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new()));
+        let request = || Request::new(EchoSessionRequest {
+            session_id: "s1".to_string(),
+            message: "Hello".to_string(),
+        });
 
+        let first = handler.echo_with_session(request()).await.unwrap().into_inner();
+        assert_eq!(first.count, 1);
+        assert_eq!(first.previous_seen_unix_ms, 0);
+
+        let second = handler.echo_with_session(request()).await.unwrap().into_inner();
+        assert_eq!(second.count, 2);
+        assert!(from_unix_ms(second.previous_seen_unix_ms).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_grpc_history_pages() {
+        let store = Arc::new(echo_server::InMemoryHistoryStore::new());
+        let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new().with_history(store)));
+
+        let request = GetHistoryRequest { limit: 10, ..Default::default() };
+        let page = handler.get_history(Request::new(request)).await.unwrap().into_inner();
+        assert!(page.entries.is_empty());
+        assert_eq!(page.next_cursor, "");
+
+        let request = GetHistoryRequest { cursor: "bogus".to_string(), ..Default::default() };
+        let status = handler.get_history(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_export_history() {
+        use echo_contract::{EchoEvent, EchoMethod};
+        use echo_server::HistoryStore;
+        let store = Arc::new(echo_server::InMemoryHistoryStore::new());
+        let at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        let entry = HistoryEntry {
+            id: 0,
+            record_id: "r1".to_string(),
+            method: EchoMethod::Echo,
+            message: "hi".to_string(),
+            request_bytes: 2,
+            success: true,
+            at,
+        };
+        store.append(entry, EchoEvent { method: EchoMethod::Echo, request_bytes: 2, success: true, at }).unwrap();
+        let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new().with_history(store)));
+
+        let request = ExportHistoryRequest { format: "csv".to_string(), query: None };
+        let chunks: Vec<_> = handler.export_history(Request::new(request)).await.unwrap().into_inner().collect().await;
+        let file: Vec<u8> = chunks.into_iter().flat_map(|chunk| chunk.unwrap().data).collect();
+        assert_eq!(
+            String::from_utf8(file).unwrap(),
+            "id,method,message,request_bytes,success,at_unix_ms,record_id\n1,echo,hi,2,true,1700000000000,r1\n",
+        );
+
+        let request = ExportHistoryRequest { format: "xml".to_string(), query: None };
+        let status = handler.export_history(Request::new(request)).await.err().unwrap();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_echo_batch() {
+        let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new()));
+
+        let mut request = Request::new(EchoBatchRequest { messages: vec!["a".to_string(), "b".to_string()] });
+        request.metadata_mut().insert(TRANSFORM_METADATA_KEY, "upper".parse().unwrap());
+        let results = handler.echo_batch(request).await.unwrap().into_inner().results;
+        let outcomes: Vec<_> = results.into_iter().map(|result| result.outcome.unwrap()).collect();
+        assert_eq!(outcomes, vec![Outcome::Message("A".to_string()), Outcome::Message("B".to_string())]);
+
+        let error = to_batch_error(echo_contract::overloaded(Duration::from_millis(250), "busy"));
+        assert_eq!((error.code, error.retry_after_ms), (tonic::Code::ResourceExhausted as i32, 250));
+
+        let messages = vec![String::new(); MAX_BATCH_MESSAGES + 1];
+        let status = handler.echo_batch(Request::new(EchoBatchRequest { messages })).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let entry = HistoryEntry {
+            id: 7,
+            record_id: "r7".to_string(),
+            method: echo_contract::EchoMethod::EchoReliable,
+            message: "hi".to_string(),
+            request_bytes: 2,
+            success: false,
+            at: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+        };
+        assert_eq!(from_history_message(to_history_message(entry.clone())).unwrap(), entry);
+
+        let query = HistoryQuery::new()
+            .with_cursor("42")
+            .with_limit(5)
+            .with_time_range(Some(UNIX_EPOCH + Duration::from_millis(1)), None)
+            .with_contains("x");
+        assert_eq!(from_history_request(to_history_request(query.clone())).unwrap(), query);
+    }
+
+    #[test]
+    fn test_overloaded_maps_to_resource_exhausted() {
+        let status = to_status(echo_contract::overloaded(std::time::Duration::from_millis(250), "busy"));
+        
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(status.metadata().get(RETRY_AFTER_METADATA_KEY).unwrap(), "250ms");
+        let retry_info = status.get_details_retry_info().unwrap();
+        assert_eq!(retry_info.retry_delay, Some(std::time::Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn test_limit_reached_maps_to_resource_exhausted_without_hint() {
+        let status = to_status(echo_contract::limit_reached("too many jobs"));
+
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert!(status.metadata().get(RETRY_AFTER_METADATA_KEY).is_none());
+        assert!(status.get_details_retry_info().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_validation_maps_to_bad_request() {
+        let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new()));
+        let request = EchoReliableRequest { message: "Hi".to_string(), idempotency_key: String::new() };
+        let status = handler.echo_reliable(Request::new(request)).await.unwrap_err();
+        
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let violations = status.get_details_bad_request().unwrap().field_violations;
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].field, "idempotency_key");
+        assert_eq!(violations[0].description, "must not be empty");
+        
+        // No field named: the whole message is the description
+        let status = to_status(hsu_common::Error::Validation { message: "line 3: unknown method".to_string() });
+        assert_eq!(status.get_details_bad_request().unwrap().field_violations[0].description, "line 3: unknown method");
+        assert!(status.get_details_localized_message().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_validation_localized_by_accept_language() {
+        let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new()));
+        let mut request = Request::new(EchoReliableRequest { message: "Hi".to_string(), idempotency_key: String::new() });
+        request.metadata_mut().insert(ACCEPT_LANGUAGE_METADATA_KEY, "de-DE, en;q=0.8".parse().unwrap());
+        let status = handler.echo_reliable(request).await.unwrap_err();
+
+        assert!(status.message().ends_with("darf nicht leer sein"), "{}", status.message());
+        assert_eq!(status.get_details_bad_request().unwrap().field_violations[0].description, "darf nicht leer sein");
+        let localized = status.get_details_localized_message().unwrap();
+        assert_eq!(localized.locale, "de");
+        assert_eq!(localized.message, "darf nicht leer sein");
+    }
+
+    #[test]
+    fn test_overloaded_localized_message_drops_the_hint() {
+        let metadata = BTreeMap::from([(CONTENT_LANGUAGE_METADATA_KEY.to_string(), "fr".to_string())]);
+        let error = echo_contract::overloaded(std::time::Duration::from_millis(250), "quota dépassé");
+        let status = localized_status(error, &metadata);
+
+        let localized = status.get_details_localized_message().unwrap();
+        assert_eq!((localized.locale.as_str(), localized.message.as_str()), ("fr", "quota dépassé"));
+        assert!(status.get_details_retry_info().is_some());
+    }
+    
+    #[test]
+    fn test_request_context_from_metadata() {
+        let mut request = Request::new(());
+        assert_eq!(request_context(&request).unwrap().priority, echo_contract::Priority::Normal);
+        assert!(request_context(&request).unwrap().transforms.is_empty());
+        
+        request.metadata_mut().insert(PRIORITY_METADATA_KEY, "high".parse().unwrap());
+        assert_eq!(request_context(&request).unwrap().priority, echo_contract::Priority::High);
+        
+        request.metadata_mut().insert(PRIORITY_METADATA_KEY, "bogus".parse().unwrap());
+        assert_eq!(request_context(&request).unwrap().priority, echo_contract::Priority::Normal);
+        
+        request.metadata_mut().insert(TRANSFORM_METADATA_KEY, "nfc,upper:tr".parse().unwrap());
+        assert_eq!(request_context(&request).unwrap().transforms, parse_transforms("nfc,upper:tr").unwrap());
+        
+        request.metadata_mut().insert(CALLER_METADATA_KEY, "tenant-a".parse().unwrap());
+        assert_eq!(request_context(&request).unwrap().caller.as_deref(), Some("tenant-a"));
+
+        request.metadata_mut().insert(TRACE_METADATA_KEY, "4bf92f35/7d1c".parse().unwrap());
+        assert_eq!(request_context(&request).unwrap().request_id.as_deref(), Some("7d1c"));
+
+        request.metadata_mut().insert(ACCEPT_LANGUAGE_METADATA_KEY, "es".parse().unwrap());
+        assert_eq!(request_context(&request).unwrap().accept_language.as_deref(), Some("es"));
+        
+        request.metadata_mut().insert(TRANSFORM_METADATA_KEY, "shout".parse().unwrap());
+        assert_eq!(request_context(&request).unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,104 @@
+//! End-to-end message checksums (integrity mode).
+//!
+//! # Architecture
+//!
+//! TLS protects a connection, not the path: a buggy proxy, adapter or
+//! codec can still hand over a different message than was sent. In
+//! integrity mode the client sends the SHA-256 of its request message
+//! (its protobuf encoding) as metadata, and the server checks it and
+//! answers the same way:
+//!
+//! ```text
+//! EchoGrpcGateway::with_integrity(true)          EchoGrpcHandler
+//!   x-echo-checksum: sha256(message)  ──────→    checksum matches? else DATA_LOSS
+//!                                                   ↓ echo
+//!   checksum matches? else INTEGRITY_ERROR ←──   x-echo-checksum: sha256(reply)
+//! ```
+//!
+//! The server only checks (and answers with) a checksum when the request
+//! carries one, so integrity is the client's choice and clients without
+//! it are unaffected. Every request and reply sent as a single message is
+//! covered, so all unary rpcs are; the items of a stream carry no headers
+//! of their own and aren't. Either side's mismatch reaches the caller as
+//! [`echo_contract::integrity_error`]. A reply without a checksum wasn't
+//! corrupted but wasn't checked either (a server from before integrity
+//! mode, or a proxy dropping the header): the client rejects it as
+//! [`echo_contract::invalid_response`].
+//!
+//! Both sides hash the encoding of the message as they know it, so they
+//! must agree on the schema: a field one side doesn't know is a mismatch.
+
+use prost::Message;
+use sha2::{Digest, Sha256};
+use tonic::metadata::{MetadataMap, MetadataValue};
+
+use echo_contract::CHECKSUM_METADATA_KEY;
+
+/// Returns the lowercase hex SHA-256 of `bytes`.
+pub fn checksum(bytes: &[u8]) -> String {
+    to_hex(&Sha256::digest(bytes))
+}
+
+/// Lowercase hex digits of `bytes`.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Returns the checksum of the protobuf encoding of `message`.
+pub fn message_checksum(message: &impl Message) -> String {
+    checksum(&message.encode_to_vec())
+}
+
+/// Adds the checksum of `bytes` to `metadata`.
+pub(crate) fn insert_checksum(metadata: &mut MetadataMap, bytes: &[u8]) {
+    let value = MetadataValue::try_from(checksum(bytes)).expect("hex is a valid header value");
+    metadata.insert(CHECKSUM_METADATA_KEY, value);
+}
+
+/// Checks `bytes` against the checksum in `metadata`: `Ok(false)` if
+/// there is none, otherwise why it doesn't match (`what` names the
+/// message in the reason).
+pub(crate) fn verify_checksum(metadata: &MetadataMap, bytes: &[u8], what: &str) -> Result<bool, String> {
+    let Some(value) = metadata.get(CHECKSUM_METADATA_KEY) else {
+        return Ok(false);
+    };
+    let expected = value.to_str().unwrap_or_default();
+    let actual = checksum(bytes);
+    if !expected.eq_ignore_ascii_case(&actual) {
+        return Err(format!("{} checksum mismatch (sent {}, received {})", what, expected, actual));
+    }
+    Ok(true)
+}
+
+/// [`verify_checksum`] for the encoding of `message`.
+pub(crate) fn verify_message_checksum(metadata: &MetadataMap, message: &impl Message, what: &str) -> Result<bool, String> {
+    verify_checksum(metadata, &message.encode_to_vec(), what)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_round_trip() {
+        assert_eq!(checksum(b"hello world"), "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+
+        let mut metadata = MetadataMap::new();
+        // No checksum is not a mismatch
+        assert_eq!(verify_checksum(&metadata, b"hello", "request"), Ok(false));
+        insert_checksum(&mut metadata, b"hello");
+        assert_eq!(verify_checksum(&metadata, b"hello", "request"), Ok(true));
+        assert!(verify_checksum(&metadata, b"hellp", "request").unwrap_err().contains("request checksum mismatch"));
+    }
+
+    #[test]
+    fn test_message_checksum_covers_every_field() {
+        use crate::generated::EchoReliableRequest;
+        let request = |key: &str| EchoReliableRequest { message: "hi".to_string(), idempotency_key: key.to_string() };
+
+        let mut metadata = MetadataMap::new();
+        insert_checksum(&mut metadata, &request("k1").encode_to_vec());
+        assert_eq!(verify_message_checksum(&metadata, &request("k1"), "request"), Ok(true));
+        assert!(verify_message_checksum(&metadata, &request("k2"), "request").is_err());
+    }
+}
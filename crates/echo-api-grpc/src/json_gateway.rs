@@ -0,0 +1,334 @@
+//! JSON/HTTP gateway (client adapter for JSON transcoding).
+//!
+//! # Architecture
+//!
+//! The native counterpart of the browser client in `echo-api-wasm`: it
+//! POSTs JSON to the [`crate::JsonTranscodingService`] in front of a gRPC
+//! server, over plain HTTP/1.1:
+//!
+//! ```text
+//! EchoJsonGateway::echo("hi")
+//!     ↓ POST http://host:port/proto.EchoService/Echo
+//!       content-type: application/json, x-echo-priority/-transform/-caller/-trace
+//!       {"message":"hi"}
+//! JsonTranscodingService → EchoGrpcHandler → EchoService
+//!     ↓ 200 {"message":"hi"} + x-echo-meta-* headers
+//!     ↓ 429 {"code":8,"message":..} + Retry-After → echo_contract::overloaded
+//! ```
+//!
+//! Errors come back as the gRPC status the handler produced, so they map
+//! to the same contract errors as over gRPC. Only the transcoded rpcs are
+//! available; `echo_bytes`, `echo_file` and the history streams fail
+//! with a protocol error.
+//!
+//! Mostly useful where HTTP/2 is not an option (proxies that only pass
+//! HTTP/1.1), and as the second contestant of protocol racing (see
+//! `echo_api::GatewayOptions::protocol_racing`).
+
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request, StatusCode};
+use serde_json::{json, Value};
+use tracing::debug;
+
+use hsu_common::{Error, Protocol, Result};
+use echo_contract::{
+    attach_response_metadata, deadline_exceeded, record_attempt, unavailable, ByteStream, EchoAck, EchoSchedule,
+    EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream,
+    ProtocolCapabilities, RequestContext, ScheduledEcho, ServerInfo, SessionEcho, RESPONSE_METADATA_PREFIX,
+};
+use crate::gateway::{context_headers, to_protocol_error};
+use crate::generated::HistoryEntryMessage;
+use crate::handler::{from_history_message, from_unix_ms, to_history_request, RETRY_AFTER_METADATA_KEY};
+
+/// JSON/HTTP gateway for calling a remote Echo service with transcoding enabled.
+pub struct EchoJsonGateway {
+    client: Client<HttpConnector>,
+    /// `http://host:port`
+    base_url: String,
+    deadline: Option<Duration>,
+}
+
+impl EchoJsonGateway {
+    /// Creates a gateway to `address` (`host:port` or a full `http://` URL).
+    pub fn new(address: &str) -> Self {
+        let base_url = if address.contains("://") { address.to_string() } else { format!("http://{}", address) };
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            deadline: None,
+        }
+    }
+
+    /// Bounds every call by `deadline` (unbounded if `None`).
+    pub fn with_deadline(mut self, deadline: Option<Duration>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// What this adapter supports: the transcoded unary rpcs only, never
+    /// compressed or signed.
+    pub fn capabilities() -> ProtocolCapabilities {
+        ProtocolCapabilities {
+            protocol: Protocol::Http,
+            wired: true,
+            streaming: false,
+            compression: Some(false),
+            auth: Some(false),
+            encrypted: false,
+            note: Some("transcoded unary rpcs only".to_string()),
+        }
+    }
+
+    /// POSTs `body` to the transcoded rpc `method`, returning the JSON reply.
+    async fn call(&self, method: &str, body: Value) -> Result<Value> {
+        record_attempt();
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{}/proto.EchoService/{}", self.base_url, method))
+            .header(hyper::header::CONTENT_TYPE, "application/json");
+        for (key, value) in context_headers(&RequestContext::current()) {
+            request = request.header(key, value);
+        }
+        let request = request
+            .body(Body::from(body.to_string()))
+            .map_err(|e| Error::Protocol(format!("Invalid HTTP request: {}", e)))?;
+
+        let exchange = async {
+            let response = self.client.request(request).await
+                .map_err(|e| unavailable(format!("HTTP error: {}", e)))?;
+            let (parts, body) = response.into_parts();
+            let body = hyper::body::to_bytes(body).await
+                .map_err(|e| unavailable(format!("HTTP error: {}", e)))?;
+            Ok::<_, Error>((parts, body))
+        };
+        let (parts, body) = match self.deadline {
+            Some(deadline) => tokio::time::timeout(deadline, exchange)
+                .await
+                .map_err(|_| deadline_exceeded(format!("HTTP call exceeded {:?}", deadline)))??,
+            None => exchange.await?,
+        };
+
+        if !parts.status.is_success() {
+            return Err(to_error(parts.status, &parts.headers, &body));
+        }
+        // Hand the server's `x-echo-meta-*` entries to the caller's scope
+        for (key, value) in parts.headers.iter() {
+            if let (Some(key), Ok(value)) = (key.as_str().strip_prefix(RESPONSE_METADATA_PREFIX), value.to_str()) {
+                attach_response_metadata(key, value);
+            }
+        }
+        serde_json::from_slice(&body).map_err(|e| Error::Protocol(format!("Invalid JSON response to {}: {}", method, e)))
+    }
+}
+
+/// Rebuilds the gRPC status of a failed call from its error body, so it
+/// maps to the same contract error as over gRPC.
+///
+/// A body that isn't a status (a gRPC server without transcoding, a
+/// proxy's error page) becomes a protocol error naming the HTTP status.
+fn to_error(status: StatusCode, headers: &hyper::HeaderMap, body: &[u8]) -> Error {
+    let Some((code, message)) = serde_json::from_slice::<Value>(body).ok().and_then(|body| {
+        Some((body["code"].as_i64()? as i32, body["message"].as_str().unwrap_or_default().to_string()))
+    }) else {
+        return Error::Protocol(format!("HTTP {} without a JSON error body", status));
+    };
+    let mut status = tonic::Status::new(tonic::Code::from(code), message);
+    // HTTP's Retry-After is whole seconds; the gRPC hint is "<n>ms"
+    let retry_after = headers
+        .get(hyper::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|secs| secs.trim().parse::<u64>().ok());
+    if let Some(value) = retry_after.and_then(|secs| format!("{}ms", secs * 1000).parse().ok()) {
+        status.metadata_mut().insert(RETRY_AFTER_METADATA_KEY, value);
+    }
+    to_protocol_error(status)
+}
+
+fn string(body: &Value, name: &str) -> String {
+    body[name].as_str().unwrap_or_default().to_string()
+}
+
+/// A `uint64` field: proto3 JSON writes it as a string.
+fn uint64(body: &Value, name: &str) -> Result<u64> {
+    match &body[name] {
+        Value::Null => Ok(0),
+        Value::String(value) => value.parse().map_err(|_| invalid_field(name)),
+        value => value.as_u64().ok_or_else(|| invalid_field(name)),
+    }
+}
+
+fn invalid_field(name: &str) -> Error {
+    Error::Protocol(format!("Field '{}' of the JSON response is not an unsigned integer", name))
+}
+
+fn not_transcoded(method: &str) -> Error {
+    Error::Protocol(format!("{} is not available as JSON, use gRPC", method))
+}
+
+#[async_trait]
+impl EchoService for EchoJsonGateway {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        debug!("[EchoJsonGateway] Echo call: {}", message);
+        let response = self.call("Echo", json!({ "message": &*message })).await?;
+        Ok(string(&response, "message").into())
+    }
+
+    async fn echo_bytes(&self, _payload: Bytes) -> Result<Bytes> {
+        Err(not_transcoded("EchoBytes"))
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let response = self.call("EchoReliable", json!({
+            "message": &*message,
+            "idempotency_key": idempotency_key,
+        })).await?;
+        Ok(EchoAck {
+            message: string(&response, "message").into(),
+            idempotency_key: string(&response, "idempotencyKey"),
+            duplicate: response["duplicate"].as_bool().unwrap_or_default(),
+        })
+    }
+
+    async fn echo_file(&self, _chunks: ByteStream) -> Result<FileDigest> {
+        Err(not_transcoded("EchoFile"))
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let response = self.call("EchoWithSession", json!({
+            "session_id": session_id,
+            "message": &*message,
+        })).await?;
+        Ok(SessionEcho {
+            message: string(&response, "message").into(),
+            session_id: string(&response, "sessionId"),
+            count: uint64(&response, "count")?,
+            previous_seen: from_unix_ms(uint64(&response, "previousSeenUnixMs")?),
+        })
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        let response = self.call("GetInfo", json!({})).await?;
+        Ok(ServerInfo {
+            module_id: string(&response, "moduleId"),
+            instance_id: string(&response, "instanceId"),
+            version: string(&response, "version"),
+            git_hash: string(&response, "gitHash"),
+            uptime: Duration::from_millis(uint64(&response, "uptimeMs")?),
+            features: response["features"]
+                .as_array()
+                .map(|features| features.iter().filter_map(|feature| feature.as_str().map(str::to_string)).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        let response = self.call("ScheduleEcho", json!({
+            "message": &*message,
+            "schedule": schedule.to_string(),
+        })).await?;
+        Ok(ScheduledEcho {
+            job_id: string(&response, "jobId"),
+            next_run: UNIX_EPOCH + Duration::from_millis(uint64(&response, "nextRunUnixMs")?),
+        })
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        let response = self.call("CancelScheduledEcho", json!({ "job_id": job_id })).await?;
+        Ok(response["cancelled"].as_bool().unwrap_or_default())
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        let request = to_history_request(query);
+        let response = self.call("GetHistory", json!({
+            "cursor": request.cursor,
+            "limit": request.limit,
+            "since_unix_ms": request.since_unix_ms.to_string(),
+            "until_unix_ms": request.until_unix_ms.to_string(),
+            "contains": request.contains,
+        })).await?;
+        let entries = response["entries"].as_array().cloned().unwrap_or_default();
+        let entries = entries.iter().map(|entry| {
+            from_history_message(HistoryEntryMessage {
+                id: uint64(entry, "id")?,
+                record_id: string(entry, "recordId"),
+                method: string(entry, "method"),
+                message: string(entry, "message"),
+                request_bytes: uint64(entry, "requestBytes")?,
+                success: entry["success"].as_bool().unwrap_or_default(),
+                at_unix_ms: uint64(entry, "atUnixMs")?,
+            })
+        }).collect::<Result<_>>()?;
+        let next_cursor = string(&response, "nextCursor");
+        Ok(HistoryPage { entries, next_cursor: (!next_cursor.is_empty()).then_some(next_cursor) })
+    }
+
+    async fn stream_history(&self, _query: HistoryQuery) -> Result<HistoryStream> {
+        Err(not_transcoded("StreamHistory"))
+    }
+
+    async fn export_history(&self, _format: HistoryExportFormat, _query: HistoryQuery) -> Result<ByteStream> {
+        Err(not_transcoded("ExportHistory"))
+    }
+
+    async fn import_history(&self, _format: HistoryExportFormat, _data: ByteStream) -> Result<HistoryImportReport> {
+        Err(not_transcoded("ImportHistory"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use echo_server::EchoServiceImpl;
+    use crate::generated::echo_service_server::EchoServiceServer;
+    use crate::{EchoGrpcHandler, JsonTranscodingService};
+
+    /// Serves gRPC with JSON transcoding on an ephemeral port (HTTP/1.1 too).
+    async fn start_server() -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let handler = Arc::new(EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new())));
+        let service = JsonTranscodingService::new(EchoServiceServer::new((*handler).clone()), handler);
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .accept_http1(true)
+                .add_service(service)
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        address
+    }
+
+    #[tokio::test]
+    async fn test_json_gateway_round_trip() {
+        let gateway = EchoJsonGateway::new(&start_server().await.to_string());
+
+        let reply = RequestContext::default()
+            .with_transforms(echo_contract::parse_transforms("upper").unwrap())
+            .scope(gateway.echo("hello".into()))
+            .await
+            .unwrap();
+        assert_eq!(&*reply, "HELLO");
+
+        let session = gateway.echo_with_session(String::new(), "hi".into()).await.unwrap();
+        assert_eq!((session.count, session.previous_seen), (1, None));
+
+        let error = gateway.echo_bytes(Bytes::from_static(b"hi")).await.unwrap_err();
+        assert!(error.to_string().contains("not available as JSON"));
+    }
+
+    #[test]
+    fn test_error_body_maps_to_contract_error() {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(hyper::header::RETRY_AFTER, "2".parse().unwrap());
+        let error = to_error(StatusCode::TOO_MANY_REQUESTS, &headers, br#"{"code":8,"message":"busy"}"#);
+        assert!(echo_contract::is_overloaded(&error));
+        assert_eq!(echo_contract::retry_after(&error), Some(Duration::from_secs(2)));
+
+        let error = to_error(StatusCode::BAD_GATEWAY, &hyper::HeaderMap::new(), b"<html>");
+        assert!(error.to_string().contains("HTTP 502"));
+    }
+}
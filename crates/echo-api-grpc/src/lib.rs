@@ -9,6 +9,10 @@
 //! 2. ✅ gRPC client adapter (`EchoGrpcGateway`)
 //! 3. ✅ Protocol-specific code (protobuf, tonic)
 //! 4. ✅ Factory functions (thin wrappers)
+//! 5. ✅ JSON transcoding on the gRPC port (`JsonTranscodingService`)
+//! 6. ✅ JSON/HTTP client adapter for transcoding ports (`EchoJsonGateway`)
+//! 7. ✅ End-to-end message checksums (`integrity`)
+//! 8. ✅ Signed requests with replay protection (`signing`)
 //!
 //! # What Moved Out
 //!
@@ -50,7 +54,27 @@ pub mod generated {
 
 pub mod handler;
 pub mod gateway;
+pub mod channel;
+pub mod reconnect;
+pub mod events;
+pub mod transcoding;
+pub mod json_gateway;
+pub mod integrity;
+pub mod signing;
 
-pub use handler::EchoGrpcHandler;
-pub use gateway::{EchoGrpcGateway, EchoGrpcGatewayFactory};
+#[cfg(test)]
+mod wire_snapshots;
+
+pub use handler::{EchoGrpcHandler, MAX_BATCH_MESSAGES, RETRY_AFTER_METADATA_KEY};
+pub use gateway::EchoGrpcGateway;
+#[allow(deprecated)]
+pub use gateway::EchoGrpcGatewayFactory;
+pub use channel::{
+    ChannelPool, ConnectivityEvent, ConnectivityState, GrpcChannelOptions, ReconnectPolicy,
+};
+pub use reconnect::ReconnectingGrpcGateway;
+pub use events::{EchoEventsGrpcGateway, EchoEventsGrpcHandler};
+pub use transcoding::JsonTranscodingService;
+pub use json_gateway::EchoJsonGateway;
+pub use signing::{SignatureVerifier, SignedService, SigningKey};
 
@@ -0,0 +1,231 @@
+//! Self-healing gRPC gateway.
+//!
+//! # Architecture
+//!
+//! During a server restart every call on the old connection fails with
+//! `UNAVAILABLE`. Instead of surfacing that transport error to every
+//! caller, this gateway asks the [`ChannelPool`] to reconnect and retries
+//! the call once on the fresh channel:
+//!
+//! ```text
+//! ReconnectingGrpcGateway::echo
+//!     ↓ pool.checkout(address)
+//! EchoGrpcGateway (current channel) ──UNAVAILABLE──→ pool.reconnect (backoff + jitter)
+//!     ↓ Ok                                              ↓
+//! response                              EchoGrpcGateway (new channel) → retry once
+//! ```
+//!
+//! Only unary calls are retried. `echo_file` consumes its input stream,
+//! so it can't be replayed - it fails, and the next unary call reconnects.
+//! `schedule_echo` isn't retried either: it isn't idempotent, and a
+//! failed call may still have created the job.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::Bytes;
+use echo_contract::{
+    is_unavailable, maintenance_reason, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use hsu_common::Result;
+use tonic::transport::Channel;
+use tracing::warn;
+
+use crate::channel::ChannelPool;
+use crate::gateway::EchoGrpcGateway;
+use crate::signing::SigningKey;
+use crate::generated::echo_service_client::EchoServiceClient;
+
+/// gRPC gateway that reconnects broken channels from a [`ChannelPool`].
+pub struct ReconnectingGrpcGateway {
+    pool: Arc<ChannelPool>,
+    address: String,
+    deadline: Option<Duration>,
+    integrity: bool,
+    signing: Option<Arc<SigningKey>>,
+}
+
+impl ReconnectingGrpcGateway {
+    /// Creates a gateway to `address`, using channels from `pool`.
+    pub fn new(pool: Arc<ChannelPool>, address: impl Into<String>) -> Self {
+        Self {
+            pool,
+            address: address.into(),
+            deadline: None,
+            integrity: false,
+            signing: None,
+        }
+    }
+
+    /// Bounds every unary call attempt by `deadline` (unbounded if `None`).
+    pub fn with_deadline(mut self, deadline: Option<Duration>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Checksums messages end to end (see [`EchoGrpcGateway::with_integrity`]).
+    pub fn with_integrity(mut self, integrity: bool) -> Self {
+        self.integrity = integrity;
+        self
+    }
+
+    /// Signs every request (see [`EchoGrpcGateway::with_signing`]); a
+    /// retry is signed afresh, with a new nonce.
+    pub fn with_signing(mut self, key: Option<Arc<SigningKey>>) -> Self {
+        self.signing = key;
+        self
+    }
+
+    fn gateway(&self, channel: Channel) -> EchoGrpcGateway {
+        EchoGrpcGateway::from_client(EchoServiceClient::new(channel))
+            .with_deadline(self.deadline)
+            .with_integrity(self.integrity)
+            .with_signing(self.signing.clone())
+    }
+
+    /// Runs `call`, reconnecting and retrying once if the server is unreachable.
+    async fn with_reconnect<T, F, Fut>(&self, call: F) -> Result<T>
+    where
+        F: Fn(EchoGrpcGateway) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let (channel, generation) = self.pool.checkout(&self.address)?;
+        match call(self.gateway(channel)).await {
+            // A server in maintenance answered; a new connection won't change that
+            Err(e) if is_unavailable(&e) && maintenance_reason(&e).is_none() => {
+                warn!("[ReconnectingGrpcGateway] {} unreachable ({}), reconnecting", self.address, e);
+                let (channel, _) = self.pool.reconnect(&self.address, generation).await?;
+                call(self.gateway(channel)).await
+            }
+            result => result,
+        }
+    }
+
+    /// Echoes `messages` in one EchoBatch round trip (see
+    /// [`EchoGrpcGateway::echo_batch`]), reconnecting like `echo`.
+    pub async fn echo_batch(&self, messages: Vec<Arc<str>>) -> Result<Vec<Result<Arc<str>>>> {
+        self.with_reconnect(|gateway| {
+            let messages = messages.clone();
+            async move { gateway.echo_batch(messages).await }
+        }).await
+    }
+}
+
+#[async_trait]
+impl EchoService for ReconnectingGrpcGateway {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.with_reconnect(|gateway| {
+            let message = message.clone();
+            async move { gateway.echo(message).await }
+        }).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.with_reconnect(|gateway| {
+            let payload = payload.clone();
+            async move { gateway.echo_bytes(payload).await }
+        }).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        // Retrying is safe: the server deduplicates by idempotency key
+        self.with_reconnect(|gateway| {
+            let message = message.clone();
+            let idempotency_key = idempotency_key.clone();
+            async move { gateway.echo_reliable(message, idempotency_key).await }
+        }).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let (channel, _) = self.pool.checkout(&self.address)?;
+        self.gateway(channel).echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        // Only retried when the server was unreachable, so the message
+        // wasn't counted yet
+        self.with_reconnect(|gateway| {
+            let session_id = session_id.clone();
+            let message = message.clone();
+            async move { gateway.echo_with_session(session_id, message).await }
+        }).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.with_reconnect(|gateway| async move { gateway.get_info().await }).await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        // Not retried: UNAVAILABLE may come after the server created the
+        // job, and a retry would schedule it twice
+        let (channel, _) = self.pool.checkout(&self.address)?;
+        self.gateway(channel).schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.with_reconnect(|gateway| {
+            let job_id = job_id.clone();
+            async move { gateway.cancel_scheduled_echo(job_id).await }
+        }).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.with_reconnect(|gateway| {
+            let query = query.clone();
+            async move { gateway.get_history(query).await }
+        }).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.with_reconnect(|gateway| {
+            let query = query.clone();
+            async move { gateway.stream_history(query).await }
+        }).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.with_reconnect(|gateway| {
+            let query = query.clone();
+            async move { gateway.export_history(format, query).await }
+        }).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        // Like echo_file, not retried: the data can only be sent once
+        let (channel, _) = self.pool.checkout(&self.address)?;
+        self.gateway(channel).import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::{ConnectivityState, GrpcChannelOptions, ReconnectPolicy};
+
+    #[tokio::test]
+    async fn test_unreachable_server_triggers_reconnect() {
+        let pool = Arc::new(ChannelPool::new(GrpcChannelOptions {
+            connect_timeout: Duration::from_millis(200),
+            reconnect: ReconnectPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        }));
+        let mut events = pool.subscribe();
+
+        // Nothing listens on port 1
+        let gateway = ReconnectingGrpcGateway::new(pool.clone(), "127.0.0.1:1");
+        let error = gateway.echo("hello".into()).await.unwrap_err();
+        assert!(is_unavailable(&error));
+
+        let mut states = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            states.push(event.state);
+        }
+        assert!(states.contains(&ConnectivityState::Connecting));
+        assert_eq!(states.last(), Some(&ConnectivityState::Failed));
+    }
+}
@@ -0,0 +1,554 @@
+//! Signed requests with replay protection.
+//!
+//! # Architecture
+//!
+//! A caller name in `x-echo-caller` is only a claim. With signing, each
+//! client module holds a secret shared with the server, and its gateway
+//! signs every request; the server checks the signature in [`SignedService`],
+//! before the handler (and the quota, history, ...) sees the request:
+//!
+//! ```text
+//! EchoGrpcGateway::with_signing(key)                 SignedService
+//!   POST /proto.EchoService/Echo                       known caller?
+//!   x-echo-caller:    client-a                         timestamp within max skew?
+//!   x-echo-timestamp: 1760000000000         ──────→    HMAC matches?
+//!   x-echo-nonce:     9f3c...-2a                       nonce not seen before?
+//!   x-echo-checksum:  sha256(message)                     ↓ else UNAUTHENTICATED
+//!   x-echo-signature: HMAC-SHA256(secret, path, x-echo-*)
+//!                                                    EchoGrpcHandler
+//! ```
+//!
+//! The signature covers the rpc path and every other `x-echo-*` header:
+//! the caller, timestamp, nonce and message checksum, but also the
+//! priority, transforms and trace. A captured request can't be sent again
+//! (the nonce is remembered until its timestamp is too old to be accepted
+//! anyway), nor sent to another rpc, with a different message or with
+//! other echo headers. Headers outside `x-echo-*` aren't signed, so
+//! proxies may still add theirs.
+//!
+//! The check runs before the message is decoded, so the body is bound
+//! through the integrity checksum: signed unary requests always carry
+//! one, and the handler checks it (see [`crate::integrity`]). Streams are
+//! authenticated and replay-protected, but their messages aren't bound to
+//! the signature.
+//!
+//! # Rust Learning Note
+//!
+//! tonic's `Interceptor` sees a `Request<()>` - the metadata, but neither
+//! the message nor the path - so it can't tell which rpc was signed.
+//! [`SignedService`] wraps the generated server as a `tower::Service` over
+//! the `http::Request` instead (like [`crate::transcoding`]), and rejects
+//! the call with a `Status` before the message is even decoded.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use hsu_common::{Error, Result};
+use sha2::Sha256;
+use tonic::body::BoxBody;
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::metadata::{KeyRef, MetadataMap, MetadataValue};
+use tonic::server::NamedService;
+use tonic::transport::Body;
+use tonic::Status;
+use tracing::warn;
+
+use echo_contract::{
+    CALLER_METADATA_KEY, NONCE_METADATA_KEY, SIGNATURE_METADATA_KEY, TIMESTAMP_METADATA_KEY, Secret,
+};
+
+use crate::integrity::to_hex;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Shortest accepted secret, in bytes.
+pub const MIN_SECRET_BYTES: usize = 16;
+
+/// Longest accepted nonce; bounds what one request can add to the cache.
+const MAX_NONCE_LEN: usize = 64;
+
+/// Headers covered by the signature, besides the rpc path.
+const SIGNED_HEADER_PREFIX: &str = "x-echo-";
+
+/// A caller's signing secret, shared between its gateway and the server.
+#[derive(Debug, Clone)]
+pub struct SigningKey {
+    caller: String,
+    secret: Secret<Vec<u8>>,
+}
+
+impl SigningKey {
+    /// Creates the key of `caller`; the secret needs [`MIN_SECRET_BYTES`].
+    pub fn new(caller: impl Into<String>, secret: impl Into<Vec<u8>>) -> Result<Self> {
+        let caller = caller.into();
+        let secret = Secret::new(secret.into());
+        if caller.is_empty() || MetadataValue::try_from(caller.as_str()).is_err() {
+            return Err(Error::Validation {
+                message: format!("Invalid signing key caller '{}'", caller),
+            });
+        }
+        let length = secret.expose().len();
+        if length < MIN_SECRET_BYTES {
+            return Err(Error::Validation {
+                message: format!("Signing secret of '{}' is {} bytes (at least {})", caller, length, MIN_SECRET_BYTES),
+            });
+        }
+        Ok(Self { caller, secret })
+    }
+
+    /// Parses `CALLER:HEX`, the secret as hex digits.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::Validation {
+            message: format!("Invalid signing key (expected CALLER:<hex secret>): {}", reason),
+        };
+        let (caller, hex) = spec.split_once(':').ok_or_else(|| invalid("no ':'"))?;
+        if hex.len() % 2 != 0 {
+            return Err(invalid("odd number of hex digits"));
+        }
+        let secret = from_hex(hex).ok_or_else(|| invalid("secret isn't hex"))?;
+        Self::new(caller, secret)
+    }
+
+    /// The caller this key signs as.
+    pub fn caller(&self) -> &str {
+        &self.caller
+    }
+
+    /// Signs the request for `path` in `metadata` as this caller, now.
+    ///
+    /// Replaces any `x-echo-caller` already there: the signed caller is
+    /// the one the server will see. No `x-echo-*` header may be added
+    /// after signing.
+    pub(crate) fn sign(&self, path: &str, metadata: &mut MetadataMap) {
+        self.sign_at(path, metadata, unix_millis(), &next_nonce());
+    }
+
+    fn sign_at(&self, path: &str, metadata: &mut MetadataMap, timestamp: u64, nonce: &str) {
+        let caller = MetadataValue::try_from(self.caller.as_str()).expect("caller was validated as a header value");
+        metadata.insert(CALLER_METADATA_KEY, caller);
+        metadata.insert(TIMESTAMP_METADATA_KEY, MetadataValue::from(timestamp));
+        metadata.insert(NONCE_METADATA_KEY, MetadataValue::try_from(nonce).expect("nonce is hex"));
+        let signature = to_hex(&self.mac(path, metadata).finalize().into_bytes());
+        metadata.insert(SIGNATURE_METADATA_KEY, MetadataValue::try_from(signature).expect("signature is hex"));
+    }
+
+    /// The MAC over `path` and the `x-echo-*` headers of `metadata` (but
+    /// the signature), one `name:value` line each, sorted by name.
+    fn mac(&self, path: &str, metadata: &MetadataMap) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(self.secret.expose()).expect("HMAC takes keys of any length");
+        mac.update(b"v2\n");
+        mac.update(path.as_bytes());
+        mac.update(b"\n");
+        for (name, value) in signed_headers(metadata) {
+            mac.update(name.as_bytes());
+            mac.update(b":");
+            mac.update(value);
+            mac.update(b"\n");
+        }
+        mac
+    }
+}
+
+/// The `x-echo-*` headers of `metadata` but the signature, sorted by
+/// name; the values of a repeated header keep their order.
+fn signed_headers(metadata: &MetadataMap) -> Vec<(&str, &[u8])> {
+    let mut headers = Vec::new();
+    for key in metadata.keys() {
+        let signed = |name: &str| name.starts_with(SIGNED_HEADER_PREFIX) && name != SIGNATURE_METADATA_KEY;
+        match key {
+            KeyRef::Ascii(key) if signed(key.as_str()) => {
+                headers.extend(metadata.get_all(key).iter().map(|value| (key.as_str(), value.as_encoded_bytes())));
+            }
+            KeyRef::Binary(key) if signed(key.as_str()) => {
+                headers.extend(metadata.get_all_bin(key).iter().map(|value| (key.as_str(), value.as_encoded_bytes())));
+            }
+            _ => {}
+        }
+    }
+    // Stable, so repeated values stay in order
+    headers.sort_by(|(a, _), (b, _)| a.cmp(b));
+    headers
+}
+
+/// Checks request signatures on the server (see the module docs).
+///
+/// Clones share the nonce cache, so one verifier can guard several
+/// servers of a module.
+#[derive(Clone)]
+pub struct SignatureVerifier {
+    keys: Arc<HashMap<String, SigningKey>>,
+    max_skew: Duration,
+    nonces: Arc<Mutex<NonceCache>>,
+}
+
+impl SignatureVerifier {
+    /// Default for [`Self::with_max_skew`].
+    pub const DEFAULT_MAX_SKEW: Duration = Duration::from_secs(30);
+    /// Default for [`Self::with_max_nonces`].
+    pub const DEFAULT_MAX_NONCES: usize = 100_000;
+
+    /// Creates a verifier without keys (rejecting every request).
+    pub fn new() -> Self {
+        Self {
+            keys: Arc::new(HashMap::new()),
+            max_skew: Self::DEFAULT_MAX_SKEW,
+            nonces: Arc::new(Mutex::new(NonceCache::new(Self::DEFAULT_MAX_NONCES))),
+        }
+    }
+
+    /// Accepts requests signed with `key`, replacing an earlier key of
+    /// the same caller.
+    pub fn with_key(mut self, key: SigningKey) -> Self {
+        Arc::make_mut(&mut self.keys).insert(key.caller.clone(), key);
+        self
+    }
+
+    /// Accepts timestamps at most this far from the server's clock.
+    ///
+    /// Nonces are remembered this long past their timestamp, so a larger
+    /// skew tolerates worse clocks at the cost of a larger cache.
+    pub fn with_max_skew(mut self, max_skew: Duration) -> Self {
+        self.max_skew = max_skew;
+        self
+    }
+
+    /// Remembers at most this many nonces; requests beyond it are shed as
+    /// overloaded until old nonces expire (forgetting them early would
+    /// let their requests be replayed).
+    pub fn with_max_nonces(self, max_nonces: usize) -> Self {
+        self.nonces.lock().expect("nonce cache lock poisoned").capacity = max_nonces;
+        self
+    }
+
+    /// Number of callers with a key.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether no caller has a key.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Checks the signature of the request for `path` in `metadata`,
+    /// consuming its nonce.
+    #[allow(clippy::result_large_err)] // fails with the `Status` the server returns as is
+    pub fn verify(&self, path: &str, metadata: &MetadataMap) -> std::result::Result<(), Status> {
+        self.verify_at(path, metadata, unix_millis()).map_err(|status| {
+            let caller = metadata.get(CALLER_METADATA_KEY).and_then(|value| value.to_str().ok()).unwrap_or("?");
+            warn!("[SignatureVerifier] Rejected request from '{}': {}", caller, status.message());
+            status
+        })
+    }
+
+    #[allow(clippy::result_large_err)] // like `verify`
+    fn verify_at(&self, path: &str, metadata: &MetadataMap, now: u64) -> std::result::Result<(), Status> {
+        let header = |key: &str| metadata.get(key).and_then(|value| value.to_str().ok());
+        let Some(signature) = header(SIGNATURE_METADATA_KEY) else {
+            return Err(Status::unauthenticated(format!("request is not signed (no {} header)", SIGNATURE_METADATA_KEY)));
+        };
+        let caller = header(CALLER_METADATA_KEY).unwrap_or_default();
+        let Some(key) = self.keys.get(caller) else {
+            return Err(Status::unauthenticated(format!("no signing key for caller '{}'", caller)));
+        };
+        let Some(timestamp) = header(TIMESTAMP_METADATA_KEY).and_then(|value| value.parse::<u64>().ok()) else {
+            return Err(Status::unauthenticated(format!("missing or invalid {} header", TIMESTAMP_METADATA_KEY)));
+        };
+        let max_skew = self.max_skew.as_millis() as u64;
+        if timestamp.abs_diff(now) > max_skew {
+            return Err(Status::unauthenticated(format!(
+                "request was signed {}ms away from the server's clock (at most {:?})",
+                timestamp.abs_diff(now),
+                self.max_skew
+            )));
+        }
+        let nonce = header(NONCE_METADATA_KEY).unwrap_or_default();
+        if nonce.is_empty() || nonce.len() > MAX_NONCE_LEN {
+            return Err(Status::unauthenticated(format!("missing or invalid {} header", NONCE_METADATA_KEY)));
+        }
+        // `verify_slice` compares in constant time
+        let signature = from_hex(signature).unwrap_or_default();
+        if key.mac(path, metadata).verify_slice(&signature).is_err() {
+            return Err(Status::unauthenticated("request signature doesn't match"));
+        }
+
+        // Only signed requests get this far, so forgeries can't fill the cache
+        let mut nonces = self.nonces.lock().expect("nonce cache lock poisoned");
+        match nonces.insert(format!("{}\n{}", caller, nonce), timestamp.saturating_add(max_skew), now) {
+            NonceOutcome::Fresh => Ok(()),
+            NonceOutcome::Replayed => Err(Status::unauthenticated("replayed request (nonce already used)")),
+            NonceOutcome::Full => Err(Status::resource_exhausted("too many recent signed requests to track")),
+        }
+    }
+}
+
+impl Default for SignatureVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for SignatureVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignatureVerifier")
+            .field("callers", &self.keys.keys().collect::<Vec<_>>())
+            .field("max_skew", &self.max_skew)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A gRPC server behind signature checks (see the module docs).
+///
+/// Without a verifier, requests go through unchecked.
+#[derive(Clone)]
+pub struct SignedService<S> {
+    inner: S,
+    verifier: Option<SignatureVerifier>,
+}
+
+impl<S> SignedService<S> {
+    /// Wraps `inner` (a generated server), checking requests with
+    /// `verifier` if any.
+    pub fn new(inner: S, verifier: Option<SignatureVerifier>) -> Self {
+        Self { inner, verifier }
+    }
+}
+
+impl<S: NamedService> NamedService for SignedService<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S> Service<http::Request<Body>> for SignedService<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<BoxBody>, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        if let Some(verifier) = &self.verifier {
+            let metadata = MetadataMap::from_headers(request.headers().clone());
+            if let Err(status) = verifier.verify(request.uri().path(), &metadata) {
+                return Box::pin(async move { Ok(status.to_http()) });
+            }
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
+
+enum NonceOutcome {
+    Fresh,
+    Replayed,
+    Full,
+}
+
+/// Nonces seen recently, with when they may be forgotten (Unix millis).
+struct NonceCache {
+    expiries: HashMap<String, u64>,
+    capacity: usize,
+    inserts: u64,
+}
+
+impl NonceCache {
+    /// Expired nonces are also swept every this many inserts.
+    const SWEEP_EVERY: u64 = 1024;
+
+    fn new(capacity: usize) -> Self {
+        Self { expiries: HashMap::new(), capacity, inserts: 0 }
+    }
+
+    fn insert(&mut self, nonce: String, expires: u64, now: u64) -> NonceOutcome {
+        // An expired entry still means a replay: its timestamp would have been rejected
+        if self.expiries.contains_key(&nonce) {
+            return NonceOutcome::Replayed;
+        }
+        self.inserts += 1;
+        if self.expiries.len() >= self.capacity || self.inserts % Self::SWEEP_EVERY == 0 {
+            self.expiries.retain(|_, expires| *expires >= now);
+        }
+        if self.expiries.len() >= self.capacity {
+            return NonceOutcome::Full;
+        }
+        self.expiries.insert(nonce, expires);
+        NonceOutcome::Fresh
+    }
+}
+
+/// Returns a nonce unique to this process (random prefix, then a counter).
+fn next_nonce() -> String {
+    static PREFIX: OnceLock<u64> = OnceLock::new();
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let prefix = PREFIX.get_or_init(|| {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        hasher.finish()
+    });
+    format!("{:016x}-{:x}", prefix, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as u64).unwrap_or_default()
+}
+
+/// Decodes hex digits (either case); `None` if `hex` isn't hex.
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| std::str::from_utf8(pair).ok().and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::{PRIORITY_METADATA_KEY, TRANSFORM_METADATA_KEY};
+    use crate::integrity::insert_checksum;
+
+    const NOW: u64 = 1_760_000_000_000;
+    const PATH: &str = "/proto.EchoService/Echo";
+
+    fn key() -> SigningKey {
+        SigningKey::parse("client-a:000102030405060708090a0b0c0d0e0f").unwrap()
+    }
+
+    fn signed(message: &[u8], timestamp: u64, nonce: &str) -> MetadataMap {
+        let mut metadata = MetadataMap::new();
+        insert_checksum(&mut metadata, message);
+        key().sign_at(PATH, &mut metadata, timestamp, nonce);
+        metadata
+    }
+
+    #[test]
+    fn test_signature_covers_path_and_echo_headers() {
+        let verifier = SignatureVerifier::new().with_key(key());
+        let mut metadata = MetadataMap::new();
+        metadata.insert(PRIORITY_METADATA_KEY, "low".parse().unwrap());
+        insert_checksum(&mut metadata, b"hello");
+        key().sign_at(PATH, &mut metadata, NOW, "n-1");
+        let rejected = |path: &str, metadata: &MetadataMap| {
+            verifier.verify_at(path, metadata, NOW).unwrap_err().message().contains("doesn't match")
+        };
+
+        assert!(rejected("/proto.EchoService/EchoReliable", &metadata));
+        let mut raised = metadata.clone();
+        raised.insert(PRIORITY_METADATA_KEY, "high".parse().unwrap());
+        assert!(rejected(PATH, &raised));
+        let mut transformed = metadata.clone();
+        transformed.insert(TRANSFORM_METADATA_KEY, "upper".parse().unwrap());
+        assert!(rejected(PATH, &transformed));
+
+        // Other headers aren't signed: proxies may add theirs
+        let mut proxied = metadata.clone();
+        proxied.insert("x-forwarded-for", "10.0.0.1".parse().unwrap());
+        assert!(verifier.verify_at(PATH, &proxied, NOW).is_ok());
+    }
+
+    #[test]
+    fn test_signed_request_is_accepted_once() {
+        let verifier = SignatureVerifier::new().with_key(key());
+        let metadata = signed(b"hello", NOW, "n-1");
+        assert_eq!(metadata.get(CALLER_METADATA_KEY).unwrap(), "client-a");
+        assert!(verifier.verify_at(PATH, &metadata, NOW + 10).is_ok());
+
+        let replay = verifier.verify_at(PATH, &metadata, NOW + 20).unwrap_err();
+        assert_eq!(replay.code(), tonic::Code::Unauthenticated);
+        assert!(replay.message().contains("replayed"));
+        // A clone shares the cache
+        assert!(verifier.clone().verify_at(PATH, &metadata, NOW + 20).unwrap_err().message().contains("replayed"));
+        assert!(verifier.verify_at(PATH, &signed(b"hello", NOW, "n-2"), NOW + 20).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_forged_stale_and_unknown_requests() {
+        let verifier = SignatureVerifier::new().with_key(key());
+        let rejected = |metadata: &MetadataMap| verifier.verify_at(PATH, metadata, NOW).unwrap_err().message().to_string();
+
+        assert!(rejected(&MetadataMap::new()).contains("not signed"));
+        let mut other_message = signed(b"hello", NOW, "n-1");
+        insert_checksum(&mut other_message, b"hellp");
+        assert!(rejected(&other_message).contains("doesn't match"));
+        let mut other_caller = signed(b"hello", NOW, "n-2");
+        other_caller.insert(CALLER_METADATA_KEY, "client-b".parse().unwrap());
+        assert!(rejected(&other_caller).contains("no signing key for caller 'client-b'"));
+        assert!(rejected(&signed(b"hello", NOW - 31_000, "n-3")).contains("away from the server's clock"));
+        assert!(rejected(&signed(b"hello", NOW, &"n".repeat(65))).contains("x-echo-nonce"));
+
+        // None of them used up a nonce
+        assert!(verifier.verify_at(PATH, &signed(b"hello", NOW, "n-1"), NOW).is_ok());
+    }
+
+    #[test]
+    fn test_full_nonce_cache_sheds_until_nonces_expire() {
+        let verifier = SignatureVerifier::new().with_key(key()).with_max_nonces(2);
+        assert!(verifier.verify_at(PATH, &signed(b"a", NOW, "n-1"), NOW).is_ok());
+        assert!(verifier.verify_at(PATH, &signed(b"b", NOW, "n-2"), NOW).is_ok());
+        let full = verifier.verify_at(PATH, &signed(b"c", NOW, "n-3"), NOW).unwrap_err();
+        assert_eq!(full.code(), tonic::Code::ResourceExhausted);
+
+        let later = NOW + 31_000;
+        assert!(verifier.verify_at(PATH, &signed(b"c", later, "n-3"), later).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_server_accepts_only_signed_requests() {
+        use echo_contract::EchoService;
+        use echo_server::EchoServiceImpl;
+        use crate::generated::echo_service_client::EchoServiceClient;
+        use crate::generated::echo_service_server::EchoServiceServer;
+        use crate::{EchoGrpcGateway, EchoGrpcHandler, EchoJsonGateway, JsonTranscodingService};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let verifier = SignatureVerifier::new().with_key(key());
+        let handler = Arc::new(EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new())));
+        let service = JsonTranscodingService::new(
+            SignedService::new(EchoServiceServer::new((*handler).clone()), Some(verifier.clone())),
+            handler,
+        )
+        .with_signature_verifier(Some(verifier));
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .accept_http1(true)
+                .add_service(service)
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        let client = EchoServiceClient::connect(format!("http://{}", address)).await.unwrap();
+
+        let signed = EchoGrpcGateway::from_client(client.clone()).with_signing(Some(Arc::new(key())));
+        assert_eq!(&*signed.echo("hello".into()).await.unwrap(), "hello");
+        assert_eq!(&*signed.echo("hello".into()).await.unwrap(), "hello");
+        signed.get_info().await.unwrap();
+
+        let unsigned = EchoGrpcGateway::from_client(client);
+        let error = unsigned.echo("hello".into()).await.unwrap_err();
+        assert!(error.to_string().contains("not signed"), "{}", error);
+        let error = EchoJsonGateway::new(&address).echo("hello".into()).await.unwrap_err();
+        assert!(error.to_string().contains("not signed"), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_keys() {
+        assert!(SigningKey::parse("client-a").is_err());
+        assert!(SigningKey::parse("client-a:0001").is_err());
+        assert!(SigningKey::parse("client-a:zz0102030405060708090a0b0c0d0e0f").is_err());
+        assert!(SigningKey::parse(":000102030405060708090a0b0c0d0e0f").is_err());
+        assert!(!format!("{:?}", key()).contains("0001"));
+    }
+}
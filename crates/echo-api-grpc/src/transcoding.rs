@@ -0,0 +1,553 @@
+//! JSON transcoding on the gRPC port.
+//!
+//! # Architecture
+//!
+//! Wraps the tonic-generated `EchoServiceServer`. Requests are told apart
+//! by content type, on the same path:
+//!
+//! ```text
+//! POST /proto.EchoService/Echo
+//!   content-type: application/grpc  → EchoServiceServer (unchanged)
+//!   content-type: application/json  → JSON → EchoRequest → EchoGrpcHandler → JSON
+//! ```
+//!
+//! Both paths end in the same [`EchoGrpcHandler`], so metadata (priority,
+//! deadlines) and error mapping behave the same; only the encoding
+//! differs. Field names follow the proto3 JSON mapping: `lowerCamelCase`
+//! on output, either form on input, `uint64` as a string.
+//!
+//! Only the unary rpcs without `bytes` fields are transcoded (`Echo`,
+//! `EchoReliable`, `EchoWithSession`, `GetInfo`, `ScheduleEcho`,
+//! `CancelScheduledEcho`, `GetHistory`). `EchoBytes`, the streaming
+//! `EchoFile`, `StreamHistory`, `ExportHistory` and `ImportHistory`, and
+//! `EchoBatch` (a gRPC client optimization) answer `501` - use gRPC for
+//! those.
+//!
+//! Bodies are read up to [`MAX_JSON_BODY_BYTES`], tonic's default
+//! decoding limit, so a JSON caller can't send more than a gRPC one.
+//!
+//! With a [`SignatureVerifier`], JSON requests are checked like gRPC
+//! ones: the `SignedService` guarding the generated server never sees them.
+//!
+//! CORS is opt-in as well: with [`JsonTranscodingService::with_cors_origins`],
+//! JSON responses allow the listed origins and CORS preflights from them
+//! are answered here, so browser clients (`echo-api-wasm`) can `fetch()`
+//! from a page served elsewhere. Without it no CORS headers are sent and
+//! browsers refuse cross-origin calls; `*` allows any origin.
+//!
+//! # Rust Learning Note
+//!
+//! tonic services are `tower::Service`s over `http::Request`, so a
+//! wrapper service is all it takes to look at a request before the gRPC
+//! codec does. `NamedService` passes the inner service's name through, so
+//! the router still mounts it at `/proto.EchoService/`.
+//!
+//! ## Comparison with Golang
+//!
+//! The Go version would run grpc-gateway as a separate reverse proxy; here
+//! it's a wrapper on the same port, with no generated REST code.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use serde_json::{json, Value};
+use tonic::body::{boxed, BoxBody};
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::metadata::MetadataMap;
+use tonic::server::NamedService;
+use tonic::transport::Body;
+use hyper::body::HttpBody;
+use tonic::{Code, Request, Status};
+use tracing::debug;
+use echo_contract::RESPONSE_METADATA_PREFIX;
+
+use crate::generated::{
+    CancelScheduledEchoRequest, EchoReliableRequest, EchoRequest, EchoSessionRequest, GetHistoryRequest,
+    GetInfoRequest, ScheduleEchoRequest,
+    echo_service_server::EchoService as EchoServiceTrait,
+};
+use crate::handler::{EchoGrpcHandler, RETRY_AFTER_METADATA_KEY};
+use crate::signing::SignatureVerifier;
+
+/// Serves JSON requests next to gRPC ones (see the module docs).
+#[derive(Clone)]
+pub struct JsonTranscodingService<S> {
+    inner: S,
+    handler: Arc<EchoGrpcHandler>,
+    verifier: Option<SignatureVerifier>,
+    cors_origins: Arc<[String]>,
+}
+
+impl<S> JsonTranscodingService<S> {
+    /// Wraps `inner` (the generated server for `handler`).
+    pub fn new(inner: S, handler: Arc<EchoGrpcHandler>) -> Self {
+        Self { inner, handler, verifier: None, cors_origins: Arc::from(Vec::new()) }
+    }
+
+    /// Lets browser pages from `origins` call the JSON endpoints (`*`:
+    /// any origin; none allowed if empty, the default).
+    pub fn with_cors_origins(mut self, origins: Vec<String>) -> Self {
+        self.cors_origins = Arc::from(origins);
+        self
+    }
+
+    /// The `access-control-allow-origin` value for `request`, if its
+    /// origin is allowed.
+    fn allowed_origin<B>(&self, request: &http::Request<B>) -> Option<http::HeaderValue> {
+        if self.cors_origins.iter().any(|allowed| allowed == "*") {
+            return Some(http::HeaderValue::from_static("*"));
+        }
+        let origin = request.headers().get(http::header::ORIGIN)?;
+        let listed = self.cors_origins.iter().any(|allowed| origin.to_str().is_ok_and(|origin| origin == allowed));
+        listed.then(|| origin.clone())
+    }
+
+    /// Rejects JSON requests without a valid signature (see
+    /// [`crate::signing`]); `inner` should check gRPC ones itself.
+    pub fn with_signature_verifier(mut self, verifier: Option<SignatureVerifier>) -> Self {
+        self.verifier = verifier;
+        self
+    }
+}
+
+impl<S: NamedService> NamedService for JsonTranscodingService<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S> Service<http::Request<Body>> for JsonTranscodingService<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<BoxBody>, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let allowed_origin = self.allowed_origin(&request);
+        if is_cors_preflight(&request) {
+            if let Some(origin) = allowed_origin {
+                return Box::pin(async move { Ok(cors_preflight_response(origin)) });
+            }
+        }
+        if !is_json(&request) {
+            return Box::pin(self.inner.call(request));
+        }
+        if let Some(verifier) = &self.verifier {
+            if let Err(status) = verifier.verify(request.uri().path(), &MetadataMap::from_headers(request.headers().clone())) {
+                return Box::pin(async move { Ok(with_cors(error_response(status), allowed_origin)) });
+            }
+        }
+        let handler = self.handler.clone();
+        Box::pin(async move { Ok(with_cors(transcode(&handler, request).await, allowed_origin)) })
+    }
+}
+
+/// Largest JSON request body read (tonic's default decoding limit).
+pub const MAX_JSON_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+fn is_json<B>(request: &http::Request<B>) -> bool {
+    request
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// A browser asking whether it may POST JSON (never sent by gRPC clients).
+fn is_cors_preflight<B>(request: &http::Request<B>) -> bool {
+    request.method() == http::Method::OPTIONS
+        && request.headers().contains_key(http::header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+fn cors_preflight_response(origin: http::HeaderValue) -> http::Response<BoxBody> {
+    http::Response::builder()
+        .status(http::StatusCode::NO_CONTENT)
+        .header(http::header::ACCESS_CONTROL_ALLOW_ORIGIN, origin)
+        .header(http::header::VARY, "origin")
+        .header(http::header::ACCESS_CONTROL_ALLOW_METHODS, "POST")
+        .header(http::header::ACCESS_CONTROL_ALLOW_HEADERS, "content-type, x-echo-priority, x-echo-transform, x-echo-caller, x-echo-trace")
+        .header(http::header::ACCESS_CONTROL_MAX_AGE, "600")
+        .body(boxed(Body::empty()))
+        .expect("static response parts are valid")
+}
+
+/// Lets the browser hand `response` to the page, if its origin is allowed.
+fn with_cors(mut response: http::Response<BoxBody>, origin: Option<http::HeaderValue>) -> http::Response<BoxBody> {
+    if let Some(origin) = origin {
+        response.headers_mut().insert(http::header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        response.headers_mut().insert(http::header::VARY, http::HeaderValue::from_static("origin"));
+    }
+    response
+}
+
+async fn transcode(handler: &EchoGrpcHandler, request: http::Request<Body>) -> http::Response<BoxBody> {
+    let (parts, body) = request.into_parts();
+    let method = parts.uri.path().rsplit('/').next().unwrap_or_default().to_string();
+    debug!("JSON {} request on the gRPC port", method);
+
+    if parts.method != http::Method::POST {
+        return error_response(Status::new(Code::Unimplemented, "JSON requests must be POST"));
+    }
+    let body: Value = match read_body(body, MAX_JSON_BODY_BYTES).await {
+        Ok(bytes) if bytes.is_empty() => json!({}),
+        Ok(bytes) => match serde_json::from_slice(&bytes) {
+            Ok(body) => body,
+            Err(e) => return error_response(Status::invalid_argument(format!("Invalid JSON: {}", e))),
+        },
+        Err(status) => return error_response(status),
+    };
+    let metadata = MetadataMap::from_headers(parts.headers);
+
+    match dispatch(handler, &method, &body, metadata).await {
+        Ok((response, metadata)) => {
+            let mut response = json_response(http::StatusCode::OK, &response);
+            // The server's `x-echo-meta-*` entries, as plain headers browsers may read
+            let mut exposed = vec![RETRY_AFTER_METADATA_KEY.to_string()];
+            for (key, value) in metadata.into_headers().iter() {
+                if key.as_str().starts_with(RESPONSE_METADATA_PREFIX) {
+                    exposed.push(key.to_string());
+                    response.headers_mut().append(key.clone(), value.clone());
+                }
+            }
+            if let Ok(exposed) = http::HeaderValue::from_str(&exposed.join(", ")) {
+                response.headers_mut().insert(http::header::ACCESS_CONTROL_EXPOSE_HEADERS, exposed);
+            }
+            response
+        }
+        Err(status) => error_response(status),
+    }
+}
+
+/// Reads `body`, failing like tonic's decoder once it exceeds `limit`.
+#[allow(clippy::result_large_err)] // the `Status` is the HTTP error as is
+async fn read_body(mut body: Body, limit: usize) -> Result<Vec<u8>, Status> {
+    let too_large = |size: u64| Status::out_of_range(format!(
+        "Error, message length too large: found {} bytes, the limit is: {} bytes", size, limit
+    ));
+    // A declared length is refused before anything is read
+    if let Some(size) = body.size_hint().upper().filter(|size| *size > limit as u64) {
+        return Err(too_large(size));
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| Status::invalid_argument(format!("Failed to read body: {}", e)))?;
+        if bytes.len() + chunk.len() > limit {
+            return Err(too_large((bytes.len() + chunk.len()) as u64));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+#[allow(clippy::result_large_err)] // fails with the `Status` the gRPC path would return
+async fn dispatch(
+    handler: &EchoGrpcHandler,
+    method: &str,
+    body: &Value,
+    metadata: MetadataMap,
+) -> Result<(Value, MetadataMap), Status> {
+    // Requests of different types, so a fn rather than a closure
+    fn request<T>(message: T, metadata: &MetadataMap) -> Request<T> {
+        let mut request = Request::new(message);
+        *request.metadata_mut() = metadata.clone();
+        request
+    }
+
+    match method {
+        "Echo" => {
+            let message = EchoRequest { message: string_field(body, "message")? };
+            let (metadata, response, _) = handler.echo(request(message, &metadata)).await?.into_parts();
+            Ok((json!({ "message": response.message }), metadata))
+        }
+        "EchoReliable" => {
+            let message = EchoReliableRequest {
+                message: string_field(body, "message")?,
+                idempotency_key: string_field(body, "idempotency_key")?,
+            };
+            let (metadata, response, _) = handler.echo_reliable(request(message, &metadata)).await?.into_parts();
+            Ok((json!({
+                "message": response.message,
+                "idempotencyKey": response.idempotency_key,
+                "duplicate": response.duplicate,
+            }), metadata))
+        }
+        "EchoWithSession" => {
+            let message = EchoSessionRequest {
+                session_id: string_field(body, "session_id")?,
+                message: string_field(body, "message")?,
+            };
+            let (metadata, response, _) = handler.echo_with_session(request(message, &metadata)).await?.into_parts();
+            Ok((json!({
+                "message": response.message,
+                "sessionId": response.session_id,
+                "count": response.count.to_string(),
+                "previousSeenUnixMs": response.previous_seen_unix_ms.to_string(),
+            }), metadata))
+        }
+        "GetInfo" => {
+            let (metadata, response, _) = handler.get_info(request(GetInfoRequest {}, &metadata)).await?.into_parts();
+            Ok((json!({
+                "moduleId": response.module_id,
+                "instanceId": response.instance_id,
+                "version": response.version,
+                "gitHash": response.git_hash,
+                "uptimeMs": response.uptime_ms.to_string(),
+                "features": response.features,
+            }), metadata))
+        }
+        "ScheduleEcho" => {
+            let message = ScheduleEchoRequest {
+                message: string_field(body, "message")?,
+                schedule: string_field(body, "schedule")?,
+            };
+            let (metadata, response, _) = handler.schedule_echo(request(message, &metadata)).await?.into_parts();
+            Ok((json!({
+                "jobId": response.job_id,
+                "nextRunUnixMs": response.next_run_unix_ms.to_string(),
+            }), metadata))
+        }
+        "CancelScheduledEcho" => {
+            let message = CancelScheduledEchoRequest { job_id: string_field(body, "job_id")? };
+            let (metadata, response, _) = handler.cancel_scheduled_echo(request(message, &metadata)).await?.into_parts();
+            Ok((json!({ "cancelled": response.cancelled }), metadata))
+        }
+        "GetHistory" => {
+            let message = GetHistoryRequest {
+                cursor: string_field(body, "cursor")?,
+                limit: u32::try_from(uint_field(body, "limit")?)
+                    .map_err(|_| Status::invalid_argument("Field 'limit' is out of range"))?,
+                since_unix_ms: uint_field(body, "since_unix_ms")?,
+                until_unix_ms: uint_field(body, "until_unix_ms")?,
+                contains: string_field(body, "contains")?,
+            };
+            let (metadata, response, _) = handler.get_history(request(message, &metadata)).await?.into_parts();
+            let entries: Vec<Value> = response.entries.into_iter().map(|entry| json!({
+                "id": entry.id.to_string(),
+                "recordId": entry.record_id,
+                "method": entry.method,
+                "message": entry.message,
+                "requestBytes": entry.request_bytes.to_string(),
+                "success": entry.success,
+                "atUnixMs": entry.at_unix_ms.to_string(),
+            })).collect();
+            Ok((json!({ "entries": entries, "nextCursor": response.next_cursor }), metadata))
+        }
+        "EchoBytes" | "EchoFile" | "StreamHistory" | "ExportHistory" | "ImportHistory" | "EchoBatch" => Err(Status::unimplemented(format!(
+            "{} is not available as JSON, use gRPC", method
+        ))),
+        _ => Err(Status::unimplemented(format!("Unknown method {}", method))),
+    }
+}
+
+/// Reads a string field by its proto name or its `lowerCamelCase` JSON name.
+///
+/// A missing field is the proto3 default (`""`).
+#[allow(clippy::result_large_err)]
+fn string_field(body: &Value, name: &str) -> Result<String, Status> {
+    match body.get(name).or_else(|| body.get(to_lower_camel_case(name))) {
+        None | Some(Value::Null) => Ok(String::new()),
+        Some(Value::String(value)) => Ok(value.clone()),
+        Some(_) => Err(Status::invalid_argument(format!("Field '{}' must be a string", name))),
+    }
+}
+
+/// Reads an unsigned integer field, as a JSON number or a decimal string
+/// (proto3 JSON writes `uint64` as a string).
+///
+/// A missing field is the proto3 default (`0`).
+#[allow(clippy::result_large_err)]
+fn uint_field(body: &Value, name: &str) -> Result<u64, Status> {
+    let invalid = || Status::invalid_argument(format!("Field '{}' must be an unsigned integer", name));
+    match body.get(name).or_else(|| body.get(to_lower_camel_case(name))) {
+        None | Some(Value::Null) => Ok(0),
+        Some(Value::Number(value)) => value.as_u64().ok_or_else(invalid),
+        Some(Value::String(value)) => value.parse().map_err(|_| invalid()),
+        Some(_) => Err(invalid()),
+    }
+}
+
+fn to_lower_camel_case(name: &str) -> String {
+    let mut parts = name.split('_');
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.push(first.to_ascii_uppercase());
+            camel.extend(chars);
+        }
+    }
+    camel
+}
+
+fn json_response(status: http::StatusCode, body: &Value) -> http::Response<BoxBody> {
+    http::Response::builder()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header(http::header::ACCESS_CONTROL_EXPOSE_HEADERS, "retry-after")
+        .body(boxed(Body::from(body.to_string())))
+        .expect("static response parts are valid")
+}
+
+/// Maps a status to HTTP the way grpc-gateway does, with a
+/// `google.rpc.Status`-shaped body.
+fn error_response(status: Status) -> http::Response<BoxBody> {
+    let mut response = json_response(http_status(status.code()), &json!({
+        "code": status.code() as i32,
+        "message": status.message(),
+        "details": [],
+    }));
+
+    // HTTP's Retry-After is whole seconds; the gRPC hint is "<n>ms"
+    let retry_after_secs = status
+        .metadata()
+        .get(RETRY_AFTER_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_suffix("ms"))
+        .and_then(|millis| millis.parse::<u64>().ok())
+        .map(|millis| ((millis + 999) / 1000).max(1));
+    if let Some(secs) = retry_after_secs {
+        response.headers_mut().insert(http::header::RETRY_AFTER, secs.into());
+    }
+    response
+}
+
+fn http_status(code: Code) -> http::StatusCode {
+    use http::StatusCode;
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Cancelled => StatusCode::from_u16(499).expect("499 is a valid status code"),
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_server::EchoServiceImpl;
+    use crate::generated::echo_service_server::EchoServiceServer;
+
+    fn service() -> JsonTranscodingService<EchoServiceServer<EchoGrpcHandler>> {
+        let handler = Arc::new(EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new())));
+        JsonTranscodingService::new(EchoServiceServer::new((*handler).clone()), handler)
+    }
+
+    async fn post(method: &str, body: &str) -> (http::StatusCode, Value) {
+        let request = http::Request::post(format!("/proto.EchoService/{}", method))
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = service().call(request).await.unwrap();
+        let status = response.status();
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_json_echo() {
+        let (status, body) = post("Echo", r#"{"message":"Hello via JSON!"}"#).await;
+        assert_eq!(status, http::StatusCode::OK);
+        assert_eq!(body, json!({ "message": "Hello via JSON!" }));
+    }
+
+    #[tokio::test]
+    async fn test_json_accepts_both_field_name_forms() {
+        let (_, snake) = post("EchoWithSession", r#"{"session_id":"s1","message":"a"}"#).await;
+        let (_, camel) = post("EchoWithSession", r#"{"sessionId":"s2","message":"b"}"#).await;
+        assert_eq!(snake["sessionId"], "s1");
+        assert_eq!(camel["sessionId"], "s2");
+        assert_eq!(camel["count"], "1");
+    }
+
+    #[tokio::test]
+    async fn test_json_errors() {
+        let (status, body) = post("EchoBytes", "{}").await;
+        assert_eq!(status, http::StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(body["code"], Code::Unimplemented as i32);
+
+        let (status, _) = post("Echo", r#"{"message":42}"#).await;
+        assert_eq!(status, http::StatusCode::BAD_REQUEST);
+        let (status, _) = post("GetHistory", r#"{"limit":"many"}"#).await;
+        assert_eq!(status, http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_is_refused() {
+        let message = "x".repeat(MAX_JSON_BODY_BYTES);
+        let (status, body) = post("Echo", &format!(r#"{{"message":"{}"}}"#, message)).await;
+        assert_eq!(status, http::StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], Code::OutOfRange as i32);
+
+        // Without a declared length, reading stops at the limit
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            while sender.send_data(bytes::Bytes::from_static(&[b' '; 1024])).await.is_ok() {}
+        });
+        assert_eq!(read_body(body, 4096).await.unwrap_err().code(), Code::OutOfRange);
+    }
+
+    fn from_origin(request: http::request::Builder, origin: &str) -> http::Request<Body> {
+        request
+            .header(http::header::ORIGIN, origin)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(http::header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::from("{}"))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_answered_without_grpc() {
+        let mut service = service().with_cors_origins(vec!["https://app.example".to_string()]);
+        let request = from_origin(http::Request::options("/proto.EchoService/Echo"), "https://app.example");
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.status(), http::StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()[http::header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example");
+
+        let request = from_origin(http::Request::post("/proto.EchoService/Echo"), "https://app.example");
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.headers()[http::header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example");
+    }
+
+    #[tokio::test]
+    async fn test_cors_is_opt_in() {
+        // Not configured: no CORS headers, preflights aren't answered
+        let request = from_origin(http::Request::post("/proto.EchoService/Echo"), "https://app.example");
+        let response = service().call(request).await.unwrap();
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert!(!response.headers().contains_key(http::header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let request = from_origin(http::Request::options("/proto.EchoService/Echo"), "https://app.example");
+        let response = service().call(request).await.unwrap();
+        assert_ne!(response.status(), http::StatusCode::NO_CONTENT);
+
+        // Other origins than the listed ones get nothing either
+        let mut listed = service().with_cors_origins(vec!["https://app.example".to_string()]);
+        let request = from_origin(http::Request::post("/proto.EchoService/Echo"), "https://evil.example");
+        let response = listed.call(request).await.unwrap();
+        assert!(!response.headers().contains_key(http::header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let mut any = service().with_cors_origins(vec!["*".to_string()]);
+        let request = from_origin(http::Request::post("/proto.EchoService/Echo"), "https://evil.example");
+        let response = any.call(request).await.unwrap();
+        assert_eq!(response.headers()[http::header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+
+    #[test]
+    fn test_lower_camel_case() {
+        assert_eq!(to_lower_camel_case("previous_seen_unix_ms"), "previousSeenUnixMs");
+        assert_eq!(to_lower_camel_case("message"), "message");
+    }
+}
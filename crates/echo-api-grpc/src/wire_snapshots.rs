@@ -0,0 +1,195 @@
+//! Wire-compatibility snapshot tests.
+//!
+//! Encodes one representative instance of every proto message and
+//! compares the bytes with `snapshots/wire.snap`. Every field is set to a
+//! non-default value, so a renumbered field or a changed type (say
+//! `uint64` → `int32`, or `string` → `bytes` with a different tag) changes
+//! the bytes and fails plain `cargo test` - long before a Go peer would
+//! misread the message.
+//!
+//! The snapshots are also decoded back, so a change that happens to keep
+//! the bytes but not the meaning fails too.
+//!
+//! For an intended wire change, regenerate with
+//! `UPDATE_WIRE_SNAPSHOTS=1 cargo test -p echo-api-grpc wire_snapshots`
+//! and review the diff.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use bytes::Bytes;
+use prost::Message;
+
+use crate::generated::*;
+
+const UNIX_MS: u64 = 1_700_000_000_000;
+
+fn snapshot_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("snapshots/wire.snap")
+}
+
+/// Message name, its encoding, and a check that bytes decode back to it.
+type Sample = (&'static str, Vec<u8>, Box<dyn Fn(&[u8]) -> bool>);
+
+fn sample<M: Message + Default + PartialEq + 'static>(message: M) -> Sample {
+    let name = std::any::type_name::<M>().rsplit("::").next().unwrap_or_default();
+    let bytes = message.encode_to_vec();
+    (name, bytes, Box::new(move |bytes: &[u8]| M::decode(bytes).ok().as_ref() == Some(&message)))
+}
+
+fn history_entry() -> HistoryEntryMessage {
+    HistoryEntryMessage {
+        id: 9,
+        record_id: "r9".to_string(),
+        method: "echo".to_string(),
+        message: "hi".to_string(),
+        request_bytes: 2,
+        success: true,
+        at_unix_ms: UNIX_MS,
+    }
+}
+
+fn samples() -> Vec<Sample> {
+    vec![
+        sample(EchoRequest { message: "hello".to_string() }),
+        sample(EchoResponse { message: "hello".to_string() }),
+        sample(EchoBytesRequest { payload: Bytes::from_static(&[0x00, 0xff]) }),
+        sample(EchoBytesResponse { payload: Bytes::from_static(&[0x00, 0xff]) }),
+        sample(EchoReliableRequest { message: "hi".to_string(), idempotency_key: "k1".to_string() }),
+        sample(EchoReliableResponse {
+            message: "hi".to_string(),
+            idempotency_key: "k1".to_string(),
+            duplicate: true,
+        }),
+        sample(EchoFileChunk { data: Bytes::from_static(b"abc") }),
+        sample(EchoFileResponse { byte_count: 300, chunk_count: 2, sha256: "ab".to_string() }),
+        sample(EchoSessionRequest { session_id: "s1".to_string(), message: "hi".to_string() }),
+        sample(EchoSessionResponse {
+            message: "hi".to_string(),
+            session_id: "s1".to_string(),
+            count: 3,
+            previous_seen_unix_ms: UNIX_MS,
+        }),
+        sample(GetInfoRequest {}),
+        sample(GetInfoResponse {
+            module_id: "echo".to_string(),
+            instance_id: "i1".to_string(),
+            version: "0.1.0".to_string(),
+            git_hash: "ab".to_string(),
+            uptime_ms: 5000,
+            features: vec!["grpc".to_string(), "mdns".to_string()],
+        }),
+        sample(ScheduleEchoRequest { message: "hi".to_string(), schedule: "every 5m".to_string() }),
+        sample(ScheduleEchoResponse { job_id: "j1".to_string(), next_run_unix_ms: UNIX_MS }),
+        sample(CancelScheduledEchoRequest { job_id: "j1".to_string() }),
+        sample(CancelScheduledEchoResponse { cancelled: true }),
+        sample(GetHistoryRequest {
+            cursor: "9".to_string(),
+            limit: 2,
+            since_unix_ms: UNIX_MS,
+            until_unix_ms: UNIX_MS,
+            contains: "hi".to_string(),
+        }),
+        sample(GetHistoryResponse { entries: vec![history_entry()], next_cursor: "9".to_string() }),
+        sample(history_entry()),
+        sample(ExportHistoryRequest {
+            format: "csv".to_string(),
+            query: Some(GetHistoryRequest { limit: 2, ..Default::default() }),
+        }),
+        sample(HistoryExportChunk { data: Bytes::from_static(b"9,echo\n") }),
+        sample(ImportHistoryChunk { format: "csv".to_string(), data: Bytes::from_static(b"9,echo\n"), last: true }),
+        sample(ImportHistoryResponse { imported: 3, duplicates: 1 }),
+        sample(EchoBatchRequest { messages: vec!["a".to_string(), "b".to_string()] }),
+        sample(EchoBatchResponse {
+            results: vec![
+                EchoBatchResult { outcome: Some(echo_batch_result::Outcome::Message("a".to_string())) },
+                EchoBatchResult {
+                    outcome: Some(echo_batch_result::Outcome::Error(EchoBatchError {
+                        code: 8,
+                        message: "busy".to_string(),
+                        retry_after_ms: 250,
+                    })),
+                },
+            ],
+        }),
+        sample(SubscribeRequest {}),
+        sample(EchoEventMessage {
+            method: "echo".to_string(),
+            request_bytes: 5,
+            success: true,
+            at_unix_ms: UNIX_MS,
+        }),
+    ]
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("snapshot is valid hex"))
+        .collect()
+}
+
+/// Reads `name hex` lines, skipping comments.
+fn read_snapshots() -> BTreeMap<String, String> {
+    let snapshots = std::fs::read_to_string(snapshot_path()).expect("snapshots/wire.snap is readable");
+    snapshots
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .map(|line| {
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap_or_default().to_string();
+            (name, words.next().unwrap_or_default().to_string())
+        })
+        .collect()
+}
+
+fn write_snapshots(samples: &[Sample]) {
+    let existing = std::fs::read_to_string(snapshot_path()).unwrap_or_default();
+    // Keep the header comment
+    let mut contents: String = existing
+        .lines()
+        .take_while(|line| line.starts_with('#'))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    for (name, bytes, _) in samples {
+        contents.push_str(&format!("{} {}\n", name, to_hex(bytes)));
+    }
+    std::fs::write(snapshot_path(), contents).expect("snapshots/wire.snap is writable");
+}
+
+#[test]
+fn test_wire_encoding_matches_snapshots() {
+    let samples = samples();
+    if std::env::var_os("UPDATE_WIRE_SNAPSHOTS").is_some() {
+        write_snapshots(&samples);
+        return;
+    }
+
+    let snapshots = read_snapshots();
+    let mut problems = Vec::new();
+    for (name, bytes, _) in &samples {
+        match snapshots.get(*name) {
+            Some(expected) if *expected == to_hex(bytes) => {}
+            Some(expected) => problems.push(format!("{}: snapshot {}, encoded {}", name, expected, to_hex(bytes))),
+            None => problems.push(format!("{}: no snapshot (new message? run with UPDATE_WIRE_SNAPSHOTS=1)", name)),
+        }
+    }
+    for name in snapshots.keys().filter(|name| !samples.iter().any(|(sample, _, _)| *sample == name.as_str())) {
+        problems.push(format!("{}: snapshot of a message that no longer exists", name));
+    }
+
+    assert!(problems.is_empty(), "Wire encoding changed (breaks Go interop):\n  {}", problems.join("\n  "));
+}
+
+#[test]
+fn test_snapshots_decode_to_samples() {
+    let snapshots = read_snapshots();
+    for (name, _, round_trips) in samples() {
+        if let Some(hex) = snapshots.get(name) {
+            assert!(round_trips(&from_hex(hex)), "{}: snapshot bytes decode to a different message", name);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Go ↔ Rust conformance suite.
+//!
+//! Runs the golden cases in `api/conformance/cases.json` (shared with the
+//! Go implementation) over real gRPC connections:
+//!
+//! ```text
+//! cargo test -p echo-api-grpc --features conformance --test conformance
+//!
+//! rust_client_rust_server   always - keeps the golden file honest
+//! rust_client_go_server     ECHO_CONFORMANCE_GO_SERVER=host:port
+//! go_client_rust_server     ECHO_CONFORMANCE_GO_CLIENT="go run ./cmd/conformance"
+//!                           (called with --address <host:port> --cases <file>)
+//! ```
+//!
+//! A direction whose variable is unset is skipped. Cases compare gRPC
+//! status codes and the expected response fields; error messages differ
+//! between the languages and are not compared.
+
+#![cfg(feature = "conformance")]
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use bytes::Bytes;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Channel, Server};
+use tonic::{Request, Status};
+
+use echo_api_grpc::generated::{
+    EchoBytesRequest, EchoFileChunk, EchoReliableRequest, EchoRequest, EchoSessionRequest,
+    echo_service_client::EchoServiceClient, echo_service_server::EchoServiceServer,
+};
+use echo_api_grpc::EchoGrpcHandler;
+use echo_server::EchoServiceImpl;
+
+fn cases_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../api/conformance/cases.json")
+}
+
+fn load_cases() -> Vec<Value> {
+    let golden = std::fs::read_to_string(cases_path()).expect("golden cases are readable");
+    let golden: Value = serde_json::from_str(&golden).expect("golden cases are valid JSON");
+    golden["cases"].as_array().expect("golden file has a cases array").clone()
+}
+
+/// Starts the Rust echo server on an ephemeral port.
+async fn start_rust_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new()));
+    tokio::spawn(
+        Server::builder()
+            .add_service(EchoServiceServer::new(handler))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    address
+}
+
+async fn connect(address: &str) -> EchoServiceClient<Channel> {
+    let uri = if address.contains("://") { address.to_string() } else { format!("http://{}", address) };
+    EchoServiceClient::connect(uri).await.expect("conformance server is reachable")
+}
+
+/// Runs every case, returning one line per failure.
+async fn run_cases(client: &mut EchoServiceClient<Channel>) -> Vec<String> {
+    let mut failures = Vec::new();
+    for case in load_cases() {
+        if let Err(failure) = run_case(client, &case).await {
+            failures.push(format!("{}: {}", case["name"].as_str().unwrap_or("?"), failure));
+        }
+    }
+    failures
+}
+
+async fn run_case(client: &mut EchoServiceClient<Channel>, case: &Value) -> Result<(), String> {
+    let request = &case["request"];
+    let string = |name: &str| request[name].as_str().unwrap_or_default().to_string();
+
+    let outcome: Result<Value, Status> = match case["rpc"].as_str().unwrap_or_default() {
+        "Echo" => client
+            .echo(with_metadata(EchoRequest { message: string("message") }, case))
+            .await
+            .map(|r| json!({ "message": r.into_inner().message })),
+        "EchoBytes" => client
+            .echo_bytes(with_metadata(EchoBytesRequest { payload: from_hex(&string("payload_hex"))? }, case))
+            .await
+            .map(|r| json!({ "payload_hex": to_hex(&r.into_inner().payload) })),
+        "EchoReliable" => client
+            .echo_reliable(with_metadata(EchoReliableRequest {
+                message: string("message"),
+                idempotency_key: string("idempotency_key"),
+            }, case))
+            .await
+            .map(|r| {
+                let r = r.into_inner();
+                json!({ "message": r.message, "idempotency_key": r.idempotency_key, "duplicate": r.duplicate })
+            }),
+        "EchoFile" => {
+            let chunks = request["chunks_hex"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|chunk| from_hex(chunk.as_str().unwrap_or_default()).map(|data| EchoFileChunk { data }))
+                .collect::<Result<Vec<_>, _>>()?;
+            client
+                .echo_file(with_metadata(tokio_stream::iter(chunks), case))
+                .await
+                .map(|r| {
+                    let r = r.into_inner();
+                    json!({ "byte_count": r.byte_count, "chunk_count": r.chunk_count, "sha256": r.sha256 })
+                })
+        }
+        "EchoWithSession" => client
+            .echo_with_session(with_metadata(EchoSessionRequest {
+                session_id: string("session_id"),
+                message: string("message"),
+            }, case))
+            .await
+            .map(|r| {
+                let r = r.into_inner();
+                json!({ "message": r.message, "session_id": r.session_id, "count": r.count })
+            }),
+        other => return Err(format!("unknown rpc '{}'", other)),
+    };
+
+    let expect = &case["expect"];
+    let expected_code = expect["code"].as_str().unwrap_or("Ok");
+    let actual_code = match &outcome {
+        Ok(_) => "Ok".to_string(),
+        Err(status) => format!("{:?}", status.code()),
+    };
+    if actual_code != expected_code {
+        return Err(format!("expected code {}, got {} ({:?})", expected_code, actual_code, outcome));
+    }
+
+    let (Ok(actual), Some(expected)) = (&outcome, expect["response"].as_object()) else {
+        return Ok(());
+    };
+    let ignored: Vec<&str> = expect["ignore_fields"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    for (field, value) in expected.iter().filter(|(field, _)| !ignored.contains(&field.as_str())) {
+        if &actual[field] != value {
+            return Err(format!("field '{}': expected {}, got {}", field, value, actual[field]));
+        }
+    }
+    Ok(())
+}
+
+/// Wraps `message` in a request carrying the case's metadata.
+fn with_metadata<T>(message: T, case: &Value) -> Request<T> {
+    let mut request = Request::new(message);
+    for (key, value) in case["metadata"].as_object().into_iter().flatten() {
+        let key = tonic::metadata::MetadataKey::from_bytes(key.as_bytes()).expect("valid metadata key");
+        let value = value.as_str().unwrap_or_default().parse().expect("valid metadata value");
+        request.metadata_mut().insert(key, value);
+    }
+    request
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Bytes, String> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .map(Bytes::from)
+        .ok_or_else(|| format!("invalid hex '{}'", hex))
+}
+
+#[tokio::test]
+async fn rust_client_rust_server() {
+    let address = start_rust_server().await;
+    let failures = run_cases(&mut connect(&address.to_string()).await).await;
+    assert!(failures.is_empty(), "Rust server doesn't match the golden cases:\n{}", failures.join("\n"));
+}
+
+#[tokio::test]
+async fn rust_client_go_server() {
+    let Ok(address) = std::env::var("ECHO_CONFORMANCE_GO_SERVER") else {
+        eprintln!("skipped: ECHO_CONFORMANCE_GO_SERVER is not set");
+        return;
+    };
+    let failures = run_cases(&mut connect(&address).await).await;
+    assert!(failures.is_empty(), "Go server at {} differs:\n{}", address, failures.join("\n"));
+}
+
+#[tokio::test]
+async fn go_client_rust_server() {
+    let Ok(command) = std::env::var("ECHO_CONFORMANCE_GO_CLIENT") else {
+        eprintln!("skipped: ECHO_CONFORMANCE_GO_CLIENT is not set");
+        return;
+    };
+    let address = start_rust_server().await;
+
+    let mut words = command.split_whitespace();
+    let program = words.next().expect("ECHO_CONFORMANCE_GO_CLIENT names a command");
+    let output = tokio::process::Command::new(program)
+        .args(words)
+        .arg("--address")
+        .arg(address.to_string())
+        .arg("--cases")
+        .arg(cases_path())
+        .output()
+        .await
+        .expect("Go conformance client runs");
+
+    assert!(
+        output.status.success(),
+        "Go client failed against the Rust server:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr),
+    );
+}
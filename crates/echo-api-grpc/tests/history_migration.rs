@@ -0,0 +1,111 @@
+//! Moving the history between two instances over real gRPC connections.
+//!
+//! ```text
+//! cargo test -p echo-api-grpc --test history_migration
+//! ```
+//!
+//! Exercises the server-streaming export and the client-streaming
+//! import end to end: gateway → HTTP/2 → handler → history store.
+
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use bytes::Bytes;
+use futures::{stream, StreamExt};
+use hsu_common::{Error, Result};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+
+use echo_api_grpc::generated::{echo_service_client::EchoServiceClient, echo_service_server::EchoServiceServer};
+use echo_api_grpc::{EchoGrpcGateway, EchoGrpcHandler};
+use echo_contract::{ByteStream, EchoEvent, EchoMethod, EchoService, HistoryExportFormat, HistoryImportReport, HistoryQuery};
+use echo_server::{EchoServiceImpl, HistoryEntry, HistoryStore, InMemoryHistoryStore};
+
+/// Starts an echo server with its own history on an ephemeral port.
+async fn start_instance() -> (EchoGrpcGateway, Arc<InMemoryHistoryStore>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let store = Arc::new(InMemoryHistoryStore::new());
+    let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new().with_history(store.clone())));
+    tokio::spawn(
+        Server::builder()
+            .add_service(EchoServiceServer::new(handler))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    let client = EchoServiceClient::connect(format!("http://{}", address)).await.unwrap();
+    (EchoGrpcGateway::from_client(client), store)
+}
+
+fn record(store: &InMemoryHistoryStore, messages: &[&str]) {
+    for (i, message) in messages.iter().enumerate() {
+        let at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000 + i as u64);
+        let size = message.len() as u64;
+        let entry = HistoryEntry {
+            id: 0,
+            record_id: format!("r{}", i),
+            method: EchoMethod::Echo,
+            message: message.to_string(),
+            request_bytes: size,
+            success: true,
+            at,
+        };
+        store.append(entry, EchoEvent { method: EchoMethod::Echo, request_bytes: size, success: true, at }).unwrap();
+    }
+}
+
+async fn export(gateway: &EchoGrpcGateway, format: HistoryExportFormat) -> Vec<u8> {
+    let chunks = gateway.export_history(format, HistoryQuery::new()).await.unwrap();
+    let chunks: Vec<Bytes> = chunks.map(|chunk| chunk.unwrap()).collect().await;
+    chunks.concat()
+}
+
+/// `file` as `size`-byte chunks, so records straddle gRPC messages.
+fn upload(file: &[u8], size: usize) -> ByteStream {
+    let chunks: Vec<Result<Bytes>> = file.chunks(size).map(|c| Ok(Bytes::copy_from_slice(c))).collect();
+    Box::pin(stream::iter(chunks))
+}
+
+#[tokio::test]
+async fn test_history_moves_to_a_fresh_instance() {
+    let (old, old_store) = start_instance().await;
+    let (new, new_store) = start_instance().await;
+    record(&old_store, &["hello", "with, comma", "multi\nline \"quoted\""]);
+
+    let csv = export(&old, HistoryExportFormat::Csv).await;
+    let report = new.import_history(HistoryExportFormat::Csv, upload(&csv, 7)).await.unwrap();
+    assert_eq!(report, HistoryImportReport { imported: 3, duplicates: 0 });
+    assert_eq!(new.get_history(HistoryQuery::new()).await.unwrap(), old.get_history(HistoryQuery::new()).await.unwrap());
+    // Published where they were recorded, not again
+    assert!(new_store.pending_events(10).unwrap().is_empty());
+
+    // The same entries in the other format are all duplicates
+    let ndjson = export(&old, HistoryExportFormat::Ndjson).await;
+    let report = new.import_history(HistoryExportFormat::Ndjson, upload(&ndjson, 5)).await.unwrap();
+    assert_eq!(report, HistoryImportReport { imported: 0, duplicates: 3 });
+}
+
+#[tokio::test]
+async fn test_bad_file_imports_nothing() {
+    let (instance, store) = start_instance().await;
+
+    let file = b"id,method,message,request_bytes,success,at_unix_ms\n1,echo,ok,2,true,5\n2,echo,bad,2,maybe,6\n";
+    let error = instance.import_history(HistoryExportFormat::Csv, upload(file, 10)).await.unwrap_err();
+    assert!(error.to_string().contains("line 3"), "{}", error);
+    assert!(store.query(&HistoryQuery::new()).unwrap().entries.is_empty());
+}
+
+#[tokio::test]
+async fn test_failing_upload_imports_nothing() {
+    let (instance, store) = start_instance().await;
+
+    // A complete first record, then the source fails
+    let chunks: Vec<Result<Bytes>> = vec![
+        Ok(Bytes::from_static(b"{\"id\":1,\"method\":\"echo\",\"message\":\"ok\",\"request_bytes\":2,\"success\":true,\"at_unix_ms\":5}\n")),
+        Err(Error::Protocol("disk unplugged".to_string())),
+    ];
+    let error = instance.import_history(HistoryExportFormat::Ndjson, Box::pin(stream::iter(chunks))).await.unwrap_err();
+    assert!(error.to_string().contains("disk unplugged"), "{}", error);
+
+    // The upload ended without its last chunk, not as a one-line file
+    assert!(store.query(&HistoryQuery::new()).unwrap().entries.is_empty());
+}
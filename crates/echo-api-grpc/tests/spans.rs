@@ -0,0 +1,143 @@
+//! Span hierarchy of one echo call over a real gRPC connection.
+//!
+//! ```text
+//! cargo test -p echo-api-grpc --test spans
+//! ```
+//!
+//! Checks the conventions of `echo_contract::spans`: the client's
+//! gateway span nests under its call, the server's service span under
+//! its handler, and both sides carry the same `request_id`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{info_span, Instrument, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use echo_api_grpc::generated::{echo_service_client::EchoServiceClient, echo_service_server::EchoServiceServer};
+use echo_api_grpc::{EchoGrpcGateway, EchoGrpcHandler};
+use echo_contract::spans::{MODULE_ID_FIELD, PROTOCOL_FIELD, REQUEST_ID_FIELD};
+use echo_contract::{EchoService, Priority, RequestContext, ECHO_MODULE_ID};
+use echo_server::EchoServiceImpl;
+
+/// A span as recorded, with its nearest recorded ancestor.
+#[derive(Debug, Clone)]
+struct SpanRecord {
+    name: &'static str,
+    parent: Option<&'static str>,
+    fields: BTreeMap<String, String>,
+}
+
+impl SpanRecord {
+    fn field(&self, name: &str) -> &str {
+        self.fields.get(name).map(String::as_str).unwrap_or_default()
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut BTreeMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+/// Records the spans of this workspace (not tonic's, hyper's or h2's).
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<SpanRecord>>>,
+    /// Live span ID → index in `spans` (IDs are reused once closed).
+    live: Arc<Mutex<HashMap<u64, usize>>>,
+}
+
+fn ours(metadata: &Metadata<'_>) -> bool {
+    metadata.target().starts_with("echo_") || metadata.target() == module_path!()
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !ours(attrs.metadata()) {
+            return;
+        }
+        let parent = ctx
+            .span(id)
+            .and_then(|span| span.scope().skip(1).find(|ancestor| ours(ancestor.metadata())))
+            .map(|ancestor| ancestor.name());
+        let mut fields = BTreeMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let mut spans = self.spans.lock().unwrap();
+        self.live.lock().unwrap().insert(id.into_u64(), spans.len());
+        spans.push(SpanRecord { name: attrs.metadata().name(), parent, fields });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        if let Some(&index) = self.live.lock().unwrap().get(&id.into_u64()) {
+            values.record(&mut FieldVisitor(&mut self.spans.lock().unwrap()[index].fields));
+        }
+    }
+
+    fn on_close(&self, id: Id, _ctx: Context<'_, S>) {
+        self.live.lock().unwrap().remove(&id.into_u64());
+    }
+}
+
+impl Recorder {
+    fn find(&self, name: &str) -> SpanRecord {
+        let spans = self.spans.lock().unwrap();
+        spans.iter().find(|span| span.name == name).cloned().unwrap_or_else(|| panic!("no {} span in {:?}", name, spans))
+    }
+}
+
+async fn start_server() -> EchoGrpcGateway {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new()));
+    tokio::spawn(
+        Server::builder()
+            .add_service(EchoServiceServer::new(handler))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    let client = EchoServiceClient::connect(format!("http://{}", address)).await.unwrap();
+    EchoGrpcGateway::from_client(client)
+}
+
+#[tokio::test]
+async fn echo_call_spans_nest_and_share_the_request_id() {
+    let recorder = Recorder::default();
+    // Thread-local; the current-thread runtime runs the server here too
+    let _subscriber = tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+
+    let gateway = start_server().await;
+    let context = RequestContext::new(Priority::Normal).with_trace("4bf92f35", "7d1c");
+    let reply = async { context.scope(gateway.echo("hi".into())).await }
+        .instrument(info_span!("client_call"))
+        .await
+        .unwrap();
+    assert_eq!(&*reply, "hi");
+
+    // Client side: the gateway under the call
+    let client = recorder.find("grpc_gateway");
+    assert_eq!(client.parent, Some("client_call"));
+    assert_eq!((client.field("method"), client.field(PROTOCOL_FIELD)), ("echo", "grpc"));
+    assert_eq!(client.field(REQUEST_ID_FIELD), "7d1c");
+
+    // Server side: a new root, joined by the request ID
+    let handler = recorder.find("grpc_handler");
+    assert_eq!(handler.parent, None);
+    assert_eq!((handler.field(PROTOCOL_FIELD), handler.field(REQUEST_ID_FIELD)), ("grpc", "7d1c"));
+
+    let service = recorder.find("echo_service");
+    assert_eq!(service.parent, Some("grpc_handler"));
+    assert_eq!((service.field(MODULE_ID_FIELD), service.field("method")), (ECHO_MODULE_ID, "echo"));
+}
@@ -0,0 +1,192 @@
+//! JSON bodies of the transcoded rpcs (target-independent).
+//!
+//! Mirrors `echo_api_grpc::transcoding` on the server: proto field names
+//! on input, `lowerCamelCase` on output, `uint64` as a string, errors as
+//! a `google.rpc.Status`-shaped body with an HTTP status.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use echo_contract::{EchoAck, EchoErrorKind};
+use serde_json::{json, Value};
+
+/// Response of `EchoWithSession`.
+///
+/// `echo_contract::SessionEcho` carries a `SystemTime`, which
+/// `wasm32-unknown-unknown` doesn't have; the timestamp stays in Unix
+/// milliseconds here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionReply {
+    /// The echoed message.
+    pub message: Arc<str>,
+    /// The session the message was counted in.
+    pub session_id: String,
+    /// Number of messages received in this session, including this one.
+    pub count: u64,
+    /// When the session's previous message was received (`0` for the first).
+    pub previous_seen_unix_ms: u64,
+}
+
+/// A failed call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallError {
+    /// The contract error kind, if the failure maps to one.
+    pub kind: Option<EchoErrorKind>,
+    /// HTTP status (`0` if the request never got a response).
+    pub status: u16,
+    /// Error text from the server or the browser.
+    pub message: String,
+}
+
+impl CallError {
+    /// The request failed before a response arrived (network, CORS, ...).
+    pub fn transport(message: impl fmt::Display) -> Self {
+        Self {
+            kind: Some(EchoErrorKind::Unavailable),
+            status: 0,
+            message: message.to_string(),
+        }
+    }
+
+    /// Back-off hint of an overloaded server.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self.kind? {
+            EchoErrorKind::Overloaded { retry_after } => retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Some(kind) => f.write_str(&kind.message(&self.message)),
+            None if self.status != 0 => write!(f, "HTTP {}: {}", self.status, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+pub fn echo_request(message: &str) -> String {
+    json!({ "message": message }).to_string()
+}
+
+pub fn echo_reliable_request(message: &str, idempotency_key: &str) -> String {
+    json!({ "message": message, "idempotency_key": idempotency_key }).to_string()
+}
+
+pub fn echo_with_session_request(session_id: &str, message: &str) -> String {
+    json!({ "session_id": session_id, "message": message }).to_string()
+}
+
+pub fn echo_response(body: &str) -> Result<String, CallError> {
+    let body = parse(body)?;
+    Ok(string(&body, "message"))
+}
+
+pub fn echo_reliable_response(body: &str) -> Result<EchoAck, CallError> {
+    let body = parse(body)?;
+    Ok(EchoAck {
+        message: string(&body, "message").into(),
+        idempotency_key: string(&body, "idempotencyKey"),
+        duplicate: body["duplicate"].as_bool().unwrap_or_default(),
+    })
+}
+
+pub fn echo_with_session_response(body: &str) -> Result<SessionReply, CallError> {
+    let body = parse(body)?;
+    Ok(SessionReply {
+        message: string(&body, "message").into(),
+        session_id: string(&body, "sessionId"),
+        count: uint64(&body, "count")?,
+        previous_seen_unix_ms: uint64(&body, "previousSeenUnixMs")?,
+    })
+}
+
+/// Maps a non-2xx response to a [`CallError`].
+///
+/// The kind follows the server's gRPC → HTTP mapping (504, 503, 429);
+/// `retry_after_secs` is the `Retry-After` header, if exposed.
+pub fn error_response(status: u16, retry_after_secs: Option<&str>, body: &str) -> CallError {
+    let message = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|body| body["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| body.to_string());
+    let kind = match status {
+        504 => Some(EchoErrorKind::DeadlineExceeded),
+        503 => Some(EchoErrorKind::Unavailable),
+        429 => Some(EchoErrorKind::Overloaded {
+            retry_after: retry_after_secs
+                .and_then(|secs| secs.trim().parse().ok())
+                .map(Duration::from_secs),
+        }),
+        _ => None,
+    };
+    CallError { kind, status, message }
+}
+
+fn parse(body: &str) -> Result<Value, CallError> {
+    serde_json::from_str(body).map_err(|e| CallError {
+        kind: None,
+        status: 200,
+        message: format!("Invalid JSON response: {}", e),
+    })
+}
+
+/// A string field; missing is the proto3 default (`""`).
+fn string(body: &Value, name: &str) -> String {
+    body[name].as_str().unwrap_or_default().to_string()
+}
+
+/// A `uint64` field (a JSON string per the proto3 mapping).
+fn uint64(body: &Value, name: &str) -> Result<u64, CallError> {
+    match &body[name] {
+        Value::Null => Ok(0),
+        Value::String(value) => value.parse().map_err(|_| invalid(name)),
+        value => value.as_u64().ok_or_else(|| invalid(name)),
+    }
+}
+
+fn invalid(name: &str) -> CallError {
+    CallError {
+        kind: None,
+        status: 200,
+        message: format!("Field '{}' is not a uint64", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_response_reads_uint64_strings() {
+        let reply = echo_with_session_response(
+            r#"{"message":"hi","sessionId":"s1","count":"3","previousSeenUnixMs":"1700000000000"}"#,
+        )
+        .unwrap();
+        assert_eq!((reply.session_id.as_str(), reply.count), ("s1", 3));
+        assert_eq!(reply.previous_seen_unix_ms, 1_700_000_000_000);
+    }
+
+    #[test]
+    fn test_reliable_response() {
+        let ack = echo_reliable_response(r#"{"message":"hi","idempotencyKey":"k1","duplicate":true}"#).unwrap();
+        assert_eq!(ack, EchoAck { message: "hi".into(), idempotency_key: "k1".to_string(), duplicate: true });
+    }
+
+    #[test]
+    fn test_error_response_maps_kinds() {
+        let overloaded = error_response(429, Some("2"), r#"{"code":8,"message":"queue full","details":[]}"#);
+        assert_eq!(overloaded.retry_after(), Some(Duration::from_secs(2)));
+        assert_eq!(overloaded.to_string(), "OVERLOADED: retry_after_ms=2000: queue full");
+
+        let deadline = error_response(504, None, r#"{"code":4,"message":"too slow"}"#);
+        assert_eq!(deadline.kind, Some(EchoErrorKind::DeadlineExceeded));
+
+        let other = error_response(400, None, "not json");
+        assert_eq!(other.to_string(), "HTTP 400: not json");
+    }
+}
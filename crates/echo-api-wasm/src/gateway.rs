@@ -0,0 +1,126 @@
+//! Echo gateway over `fetch()`.
+
+use std::sync::Arc;
+use echo_contract::{EchoAck, Priority, CALLER_METADATA_KEY, PRIORITY_METADATA_KEY, TRANSFORM_METADATA_KEY};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Request, RequestInit, RequestMode, Response};
+
+use crate::codec::{self, CallError, SessionReply};
+
+/// Calls the transcoded rpcs of an echo server from the browser.
+///
+/// The counterpart of `EchoGrpcGateway` for `wasm32`: same calls, same
+/// priority metadata, but JSON over `fetch()` instead of gRPC over tonic.
+/// Cheap to clone.
+#[derive(Debug, Clone)]
+pub struct EchoFetchGateway {
+    /// e.g. `http://localhost:50051` (the gRPC port with JSON transcoding)
+    base_url: Arc<str>,
+    priority: Option<Priority>,
+    /// Transform chain, e.g. `nfc,upper`; checked by the server
+    transforms: Option<Arc<str>>,
+    /// Caller name the server accounts bytes to
+    caller: Option<Arc<str>>,
+}
+
+impl EchoFetchGateway {
+    /// Creates a gateway for the server at `base_url`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').into(),
+            priority: None,
+            transforms: None,
+            caller: None,
+        }
+    }
+
+    /// Sends `priority` with every call (`x-echo-priority` header).
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Asks the server to transform every reply (`x-echo-transform` header).
+    ///
+    /// An empty chain sends no header. A malformed one fails each call
+    /// with `INVALID_ARGUMENT`.
+    pub fn with_transforms(mut self, chain: &str) -> Self {
+        self.transforms = (!chain.is_empty()).then(|| chain.into());
+        self
+    }
+
+    /// Names the caller the server accounts echoed bytes to (`x-echo-caller` header).
+    pub fn with_caller(mut self, caller: &str) -> Self {
+        self.caller = (!caller.is_empty()).then(|| caller.into());
+        self
+    }
+
+    /// Echoes `message`.
+    pub async fn echo(&self, message: &str) -> Result<String, CallError> {
+        let body = self.post("Echo", codec::echo_request(message)).await?;
+        codec::echo_response(&body)
+    }
+
+    /// Echoes `message` with at-least-once semantics (see `EchoService::echo_reliable`).
+    pub async fn echo_reliable(&self, message: &str, idempotency_key: &str) -> Result<EchoAck, CallError> {
+        let body = self.post("EchoReliable", codec::echo_reliable_request(message, idempotency_key)).await?;
+        codec::echo_reliable_response(&body)
+    }
+
+    /// Echoes `message` within a session (see `EchoService::echo_with_session`).
+    pub async fn echo_with_session(&self, session_id: &str, message: &str) -> Result<SessionReply, CallError> {
+        let body = self.post("EchoWithSession", codec::echo_with_session_request(session_id, message)).await?;
+        codec::echo_with_session_response(&body)
+    }
+
+    async fn post(&self, method: &str, body: String) -> Result<String, CallError> {
+        let url = format!("{}/proto.EchoService/{}", self.base_url, method);
+        let init = RequestInit::new();
+        init.set_method("POST");
+        init.set_mode(RequestMode::Cors);
+        init.set_body(&body.into());
+
+        let request = Request::new_with_str_and_init(&url, &init).map_err(js_error)?;
+        let headers = request.headers();
+        headers.set("content-type", "application/json").map_err(js_error)?;
+        if let Some(priority) = self.priority {
+            headers.set(PRIORITY_METADATA_KEY, priority.as_str()).map_err(js_error)?;
+        }
+        if let Some(transforms) = &self.transforms {
+            headers.set(TRANSFORM_METADATA_KEY, transforms).map_err(js_error)?;
+        }
+        if let Some(caller) = &self.caller {
+            headers.set(CALLER_METADATA_KEY, caller).map_err(js_error)?;
+        }
+
+        let window = web_sys::window().ok_or_else(|| CallError::transport("No window (not running in a browser)"))?;
+        let response: Response = JsFuture::from(window.fetch_with_request(&request))
+            .await
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(js_error)?;
+        let text = JsFuture::from(response.text().map_err(js_error)?)
+            .await
+            .map_err(js_error)?
+            .as_string()
+            .unwrap_or_default();
+
+        if response.ok() {
+            Ok(text)
+        } else {
+            let retry_after = response.headers().get("retry-after").ok().flatten();
+            Err(codec::error_response(response.status(), retry_after.as_deref(), &text))
+        }
+    }
+}
+
+/// `fetch()` rejects with a `TypeError` on network and CORS failures.
+fn js_error(error: wasm_bindgen::JsValue) -> CallError {
+    let message = error
+        .dyn_ref::<js_sys::Error>()
+        .map(|error| String::from(error.message()))
+        .or_else(|| error.as_string())
+        .unwrap_or_else(|| format!("{:?}", error));
+    CallError::transport(message)
+}
@@ -0,0 +1,128 @@
+//! Browser Client for Echo Service (Layer 3, `wasm32`)
+//!
+//! # Architecture
+//!
+//! A `wasm32-unknown-unknown` build of an echo client. tonic needs
+//! `tokio::net`, which doesn't exist in the browser, so this crate talks
+//! to the **JSON transcoding** of the server's gRPC port over `fetch()`:
+//!
+//! ```text
+//! JavaScript ──→ EchoClient (wasm-bindgen)
+//!                    ↓
+//!                EchoFetchGateway ──fetch()──→ POST /proto.EchoService/Echo
+//!                                              content-type: application/json
+//!                                                   ↓
+//!                                 echo-grpc-srv --json-transcoding --cors-origin <page origin>
+//! ```
+//!
+//! The dependency tree has no tonic, tokio or hsu-common: the domain
+//! types and error kinds come from `echo-contract` with only its `alloc`
+//! feature. `Echo`, `EchoReliable` and `EchoWithSession` are available;
+//! `EchoBytes` and `EchoFile` are gRPC-only on the server.
+//!
+//! ## Build
+//!
+//! ```bash
+//! cargo run --bin echo-grpc-srv -- --json-transcoding
+//! wasm-pack build crates/echo-api-wasm --target web
+//! python3 -m http.server -d crates/echo-api-wasm 8000   # open /demo/
+//! ```
+//!
+//! ## Rust Learning Note
+//!
+//! JS promises aren't `Send`, so [`EchoFetchGateway`] doesn't implement
+//! `EchoService` (an `async_trait` with `Send` futures); it offers the
+//! same calls as inherent `async fn`s. `wasm_bindgen_futures` turns them
+//! into promises for JavaScript.
+
+use js_sys::{Promise, Reflect};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+use echo_contract::Priority;
+
+pub mod codec;
+pub mod gateway;
+
+pub use codec::{CallError, SessionReply};
+pub use gateway::EchoFetchGateway;
+
+/// Echo client for JavaScript.
+///
+/// ```js
+/// import init, { EchoClient } from "../pkg/echo_api_wasm.js";
+/// await init();
+/// const client = new EchoClient("http://localhost:50051");
+/// console.log(await client.echo("Hello from the browser!"));
+/// ```
+#[wasm_bindgen]
+pub struct EchoClient {
+    gateway: EchoFetchGateway,
+}
+
+#[wasm_bindgen]
+impl EchoClient {
+    /// Creates a client for the server at `base_url`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(base_url: &str) -> EchoClient {
+        EchoClient { gateway: EchoFetchGateway::new(base_url) }
+    }
+
+    /// Sends `priority` (`high`, `normal`, `low`) with every call.
+    #[wasm_bindgen(js_name = setPriority)]
+    pub fn set_priority(&mut self, priority: &str) -> Result<(), JsValue> {
+        let priority = Priority::from_name(priority)
+            .ok_or_else(|| js_sys::Error::new(&format!("Unknown priority '{}'", priority)))?;
+        self.gateway = self.gateway.clone().with_priority(priority);
+        Ok(())
+    }
+
+    /// Asks the server to transform every reply, e.g. `"nfc,upper"` (see
+    /// `echo_contract::transform`); `""` turns it off.
+    #[wasm_bindgen(js_name = setTransforms)]
+    pub fn set_transforms(&mut self, chain: &str) {
+        self.gateway = self.gateway.clone().with_transforms(chain);
+    }
+
+    /// Names the caller the server accounts echoed bytes to; `""` calls anonymously.
+    #[wasm_bindgen(js_name = setCaller)]
+    pub fn set_caller(&mut self, caller: &str) {
+        self.gateway = self.gateway.clone().with_caller(caller);
+    }
+
+    /// Echoes `message`; resolves to the echoed string.
+    pub fn echo(&self, message: String) -> Promise {
+        let gateway = self.gateway.clone();
+        future_to_promise(async move {
+            let reply = gateway.echo(&message).await.map_err(to_js)?;
+            Ok(JsValue::from(reply))
+        })
+    }
+
+    /// Echoes `message` within `sessionId`; resolves to
+    /// `{ message, sessionId, count }`.
+    #[wasm_bindgen(js_name = echoWithSession)]
+    pub fn echo_with_session(&self, session_id: String, message: String) -> Promise {
+        let gateway = self.gateway.clone();
+        future_to_promise(async move {
+            let reply = gateway.echo_with_session(&session_id, &message).await.map_err(to_js)?;
+            let object = js_sys::Object::new();
+            Reflect::set(&object, &"message".into(), &JsValue::from(&*reply.message))?;
+            Reflect::set(&object, &"sessionId".into(), &reply.session_id.into())?;
+            Reflect::set(&object, &"count".into(), &JsValue::from(reply.count as f64))?;
+            Ok(object.into())
+        })
+    }
+}
+
+/// A JS `Error` with `kind` (`OVERLOADED`, ...), `status` and `retryAfterMs`.
+fn to_js(error: CallError) -> JsValue {
+    let js_error = js_sys::Error::new(&error.to_string());
+    let kind = error.kind.map_or(JsValue::NULL, |kind| kind.code().into());
+    let retry_after = error.retry_after().map_or(JsValue::NULL, |retry_after| (retry_after.as_millis() as f64).into());
+    // Setting properties on a fresh Error object can't fail
+    let _ = Reflect::set(&js_error, &"kind".into(), &kind);
+    let _ = Reflect::set(&js_error, &"status".into(), &error.status.into());
+    let _ = Reflect::set(&js_error, &"retryAfterMs".into(), &retry_after);
+    js_error.into()
+}
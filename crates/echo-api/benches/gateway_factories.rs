@@ -0,0 +1,93 @@
+//! Allocation benchmark for `get_service`.
+//!
+//! The gateway factories and capabilities are built once per
+//! direct-closure state, but each `get_service` call still assembles its
+//! gateway: the reconnecting gRPC gateway over the shared channel pool,
+//! and the decorators the options ask for. This counts the heap
+//! allocations of real `get_service` calls on standalone gateways (the
+//! module's gateways need a `ServiceConnector` from hsu-core), against
+//! a caller holding on to the service it got - what hot loops should do.
+//!
+//! No call is sent: channels connect on their first request.
+//!
+//! Run with:
+//! ```bash
+//! cargo bench -p echo-api --bench gateway_factories
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use echo_api::{new_standalone_echo_service_gateways, GatewayOptions};
+use echo_contract::EchoServiceGateways;
+use hsu_common::Protocol;
+
+/// Global allocator wrapper that counts allocations and allocated bytes.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: usize = 10_000;
+
+/// Runs `call` ITERATIONS times; returns allocations and bytes per call.
+fn measure(mut call: impl FnMut()) -> (usize, usize) {
+    let (allocations, bytes) = (ALLOCATIONS.load(Ordering::Relaxed), ALLOCATED_BYTES.load(Ordering::Relaxed));
+    for _ in 0..ITERATIONS {
+        call();
+    }
+    (
+        (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS,
+        (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes) / ITERATIONS,
+    )
+}
+
+/// Allocations per `get_service(Grpc)` call on gateways with `options`.
+fn measure_get_service(runtime: &tokio::runtime::Runtime, options: GatewayOptions) -> (usize, usize) {
+    let gateways = new_standalone_echo_service_gateways(GatewayOptions {
+        grpc_address: Some("127.0.0.1:50051".to_string()),
+        ..options
+    });
+    // Warm up: the channel pool entry and any lazily built shared state
+    runtime.block_on(gateways.get_service(Protocol::Grpc)).unwrap();
+    measure(|| {
+        runtime.block_on(gateways.get_service(Protocol::Grpc)).unwrap();
+    })
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+    println!("get_service - heap allocations per call");
+    let (allocations, bytes) = measure_get_service(&runtime, GatewayOptions::default());
+    println!("  default options:  {:>4} allocations, {:>6} bytes/call", allocations, bytes);
+
+    let coalescing = GatewayOptions { coalescing: true, ..Default::default() };
+    let (allocations, bytes) = measure_get_service(&runtime, coalescing);
+    println!("  with coalescing:  {:>4} allocations, {:>6} bytes/call", allocations, bytes);
+
+    let gateways = new_standalone_echo_service_gateways(GatewayOptions {
+        grpc_address: Some("127.0.0.1:50051".to_string()),
+        ..Default::default()
+    });
+    let held = runtime.block_on(gateways.get_service(Protocol::Grpc)).unwrap();
+    let (allocations, bytes) = measure(|| {
+        let _service = held.clone();
+    });
+    println!("  service held:     {:>4} allocations, {:>6} bytes/call", allocations, bytes);
+}
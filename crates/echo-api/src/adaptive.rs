@@ -0,0 +1,539 @@
+//! Adaptive Concurrency Limits (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A fixed concurrency limit is either too low (wasted capacity) or too
+//! high (queues build up, latency explodes). An adaptive limiter, à la
+//! Netflix `concurrency-limits`, **learns** the limit from observed
+//! latency and rejects calls beyond it:
+//!
+//! ```text
+//! handler call
+//!     ↓
+//! AdaptiveConcurrencyEchoService
+//!     ├─ in_flight >= controller.limit() → overloaded(retry_after)
+//!     └─ call inner, measure latency
+//!            ↓ Sample { latency, in_flight, dropped }
+//!        ConcurrencyController::on_sample → new limit
+//! ```
+//!
+//! Two controllers are provided; implement [`ConcurrencyController`] to
+//! plug in another:
+//!
+//! | Controller           | Grows when                  | Shrinks when                         |
+//! |----------------------|-----------------------------|--------------------------------------|
+//! | [`AimdController`]     | latency is fine (+1)        | timeout/error or slow (× backoff)    |
+//! | [`GradientController`] | short RTT ≈ long RTT        | short RTT rises above long-term RTT  |
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::Result;
+use echo_contract::{
+    overloaded, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport,
+    HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use tracing::debug;
+
+/// One completed call, as seen by a controller.
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    /// Time the call took.
+    pub latency: Duration,
+    /// Calls in flight when this one started (including itself).
+    pub in_flight: usize,
+    /// The call failed (error or timeout) - a congestion signal.
+    pub dropped: bool,
+}
+
+/// Decides how many calls may be in flight.
+pub trait ConcurrencyController: Send + Sync {
+    /// Returns the current limit.
+    fn limit(&self) -> usize;
+
+    /// Updates the limit from a completed call.
+    fn on_sample(&self, sample: Sample);
+
+    /// Returns a short name for logs and metrics.
+    fn name(&self) -> &'static str;
+}
+
+/// Settings for [`AimdController`].
+#[derive(Debug, Clone)]
+pub struct AimdConfig {
+    /// Starting limit.
+    pub initial_limit: usize,
+    /// Lower bound.
+    pub min_limit: usize,
+    /// Upper bound.
+    pub max_limit: usize,
+    /// Calls slower than this count as congestion.
+    pub latency_threshold: Duration,
+    /// Multiplier applied on congestion (0.5 - 1.0).
+    pub backoff_ratio: f64,
+}
+
+impl Default for AimdConfig {
+    fn default() -> Self {
+        Self {
+            initial_limit: 20,
+            min_limit: 1,
+            max_limit: 200,
+            latency_threshold: Duration::from_millis(100),
+            backoff_ratio: 0.9,
+        }
+    }
+}
+
+/// Additive-increase / multiplicative-decrease controller.
+pub struct AimdController {
+    config: AimdConfig,
+    limit: AtomicUsize,
+}
+
+impl AimdController {
+    /// Creates a controller starting at `config.initial_limit`.
+    pub fn new(config: AimdConfig) -> Self {
+        let limit = config.initial_limit.clamp(config.min_limit.max(1), config.max_limit.max(1));
+        Self { config, limit: AtomicUsize::new(limit) }
+    }
+}
+
+impl ConcurrencyController for AimdController {
+    fn limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    fn on_sample(&self, sample: Sample) {
+        let config = &self.config;
+        let _ = self.limit.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |limit| {
+            let next = if sample.dropped || sample.latency > config.latency_threshold {
+                (limit as f64 * config.backoff_ratio) as usize
+            } else if sample.in_flight * 2 >= limit {
+                // Only grow while the limit is actually being used
+                limit + 1
+            } else {
+                limit
+            };
+            Some(next.clamp(config.min_limit.max(1), config.max_limit.max(1)))
+        });
+    }
+
+    fn name(&self) -> &'static str {
+        "aimd"
+    }
+}
+
+/// Settings for [`GradientController`].
+#[derive(Debug, Clone)]
+pub struct GradientConfig {
+    /// Starting limit.
+    pub initial_limit: usize,
+    /// Lower bound.
+    pub min_limit: usize,
+    /// Upper bound.
+    pub max_limit: usize,
+    /// Weight of a new limit against the old one (0.0 - 1.0).
+    pub smoothing: f64,
+    /// Samples averaged into the long-term RTT.
+    pub long_window: u32,
+}
+
+impl Default for GradientConfig {
+    fn default() -> Self {
+        Self {
+            initial_limit: 20,
+            min_limit: 1,
+            max_limit: 200,
+            smoothing: 0.2,
+            long_window: 600,
+        }
+    }
+}
+
+struct GradientState {
+    limit: f64,
+    long_rtt: Option<f64>,
+}
+
+/// Gradient controller: compares the latest RTT with the long-term RTT.
+///
+/// `limit = limit × clamp(long / short, 0.5, 1.0) + sqrt(limit)`, smoothed.
+/// While latency is stable the gradient is 1 and the `sqrt` headroom
+/// lets the limit grow; rising latency pulls it down.
+pub struct GradientController {
+    config: GradientConfig,
+    state: Mutex<GradientState>,
+}
+
+impl GradientController {
+    /// Creates a controller starting at `config.initial_limit`.
+    pub fn new(config: GradientConfig) -> Self {
+        let limit = config.initial_limit as f64;
+        Self {
+            config,
+            state: Mutex::new(GradientState { limit, long_rtt: None }),
+        }
+    }
+}
+
+impl ConcurrencyController for GradientController {
+    fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit as usize
+    }
+
+    fn on_sample(&self, sample: Sample) {
+        let config = &self.config;
+        let mut state = self.state.lock().unwrap();
+        let short = sample.latency.as_secs_f64().max(1e-6);
+        let long = match state.long_rtt {
+            Some(long) => {
+                let factor = 2.0 / (f64::from(config.long_window.max(1)) + 1.0);
+                long + (short - long) * factor
+            }
+            None => short,
+        };
+        state.long_rtt = Some(long);
+
+        // Don't grow a limit that isn't being used
+        if !sample.dropped && (sample.in_flight as f64) < state.limit / 2.0 {
+            return;
+        }
+
+        let gradient = if sample.dropped { 0.5 } else { (long / short).clamp(0.5, 1.0) };
+        let target = state.limit * gradient + state.limit.sqrt();
+        let limit = state.limit * (1.0 - config.smoothing) + target * config.smoothing;
+        state.limit = limit.clamp(config.min_limit.max(1) as f64, config.max_limit.max(1) as f64);
+    }
+
+    fn name(&self) -> &'static str {
+        "gradient"
+    }
+}
+
+/// Built-in or custom controller selection.
+#[derive(Clone)]
+pub enum ControllerKind {
+    /// [`AimdController`].
+    Aimd(AimdConfig),
+    /// [`GradientController`].
+    Gradient(GradientConfig),
+    /// Any other [`ConcurrencyController`].
+    Custom(Arc<dyn ConcurrencyController>),
+}
+
+impl ControllerKind {
+    /// Creates the controller.
+    pub fn build(&self) -> Arc<dyn ConcurrencyController> {
+        match self {
+            ControllerKind::Aimd(config) => Arc::new(AimdController::new(config.clone())),
+            ControllerKind::Gradient(config) => Arc::new(GradientController::new(config.clone())),
+            ControllerKind::Custom(controller) => controller.clone(),
+        }
+    }
+}
+
+impl std::fmt::Debug for ControllerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControllerKind::Aimd(config) => f.debug_tuple("Aimd").field(config).finish(),
+            ControllerKind::Gradient(config) => f.debug_tuple("Gradient").field(config).finish(),
+            ControllerKind::Custom(controller) => write!(f, "Custom({})", controller.name()),
+        }
+    }
+}
+
+/// Adaptive limiter settings.
+#[derive(Debug, Clone)]
+pub struct AdaptiveConcurrencyConfig {
+    /// Algorithm deciding the limit.
+    pub controller: ControllerKind,
+    /// Back-off hint sent with rejected calls.
+    pub retry_after: Duration,
+}
+
+impl Default for AdaptiveConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            controller: ControllerKind::Gradient(GradientConfig::default()),
+            retry_after: Duration::from_millis(50),
+        }
+    }
+}
+
+/// Adaptive limiter metrics.
+#[derive(Debug, Default)]
+pub struct AdaptiveConcurrencyMetrics {
+    limit: AtomicUsize,
+    in_flight: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+impl AdaptiveConcurrencyMetrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide metrics.
+    pub fn global() -> Arc<AdaptiveConcurrencyMetrics> {
+        static GLOBAL: OnceLock<Arc<AdaptiveConcurrencyMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(AdaptiveConcurrencyMetrics::new())).clone()
+    }
+
+    /// Returns the number of rejected calls.
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Renders the metrics in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP echo_concurrency_limit Current adaptive concurrency limit");
+        let _ = writeln!(out, "# TYPE echo_concurrency_limit gauge");
+        let _ = writeln!(out, "echo_concurrency_limit {}", self.limit.load(Ordering::Relaxed));
+        let _ = writeln!(out, "# HELP echo_concurrency_in_flight Calls in flight under the adaptive limit");
+        let _ = writeln!(out, "# TYPE echo_concurrency_in_flight gauge");
+        let _ = writeln!(out, "echo_concurrency_in_flight {}", self.in_flight.load(Ordering::Relaxed));
+        let _ = writeln!(out, "# HELP echo_concurrency_rejected_total Calls rejected by the adaptive limit");
+        let _ = writeln!(out, "# TYPE echo_concurrency_rejected_total counter");
+        let _ = writeln!(out, "echo_concurrency_rejected_total {}", self.rejected());
+        out
+    }
+}
+
+/// Decorator that enforces a [`ConcurrencyController`]'s limit.
+pub struct AdaptiveConcurrencyEchoService {
+    inner: Arc<dyn EchoService>,
+    controller: Arc<dyn ConcurrencyController>,
+    retry_after: Duration,
+    in_flight: AtomicUsize,
+    metrics: Arc<AdaptiveConcurrencyMetrics>,
+}
+
+/// Decrements the in-flight count when a call ends (or is cancelled).
+struct InFlight<'a> {
+    service: &'a AdaptiveConcurrencyEchoService,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let now = self.service.in_flight.fetch_sub(1, Ordering::Relaxed) - 1;
+        self.service.metrics.in_flight.store(now, Ordering::Relaxed);
+    }
+}
+
+impl AdaptiveConcurrencyEchoService {
+    /// Wraps `inner`, recording into the global metrics.
+    pub fn new(inner: Arc<dyn EchoService>, config: &AdaptiveConcurrencyConfig) -> Self {
+        Self::with_metrics(inner, config, AdaptiveConcurrencyMetrics::global())
+    }
+
+    /// Wraps `inner`, recording into `metrics`.
+    pub fn with_metrics(
+        inner: Arc<dyn EchoService>,
+        config: &AdaptiveConcurrencyConfig,
+        metrics: Arc<AdaptiveConcurrencyMetrics>,
+    ) -> Self {
+        let controller = config.controller.build();
+        metrics.limit.store(controller.limit(), Ordering::Relaxed);
+        debug!("[AdaptiveConcurrency] Using {} controller, initial limit {}",
+            controller.name(), controller.limit());
+        Self {
+            inner,
+            controller,
+            retry_after: config.retry_after,
+            in_flight: AtomicUsize::new(0),
+            metrics,
+        }
+    }
+
+    /// Returns the current limit.
+    pub fn limit(&self) -> usize {
+        self.controller.limit()
+    }
+
+    /// Takes an in-flight slot, or rejects the call if at the limit.
+    fn admit(&self) -> Result<(usize, InFlight<'_>)> {
+        let limit = self.controller.limit();
+        let admitted = self.in_flight.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+            (n < limit).then_some(n + 1)
+        });
+        match admitted {
+            Ok(previous) => {
+                self.metrics.in_flight.store(previous + 1, Ordering::Relaxed);
+                Ok((previous + 1, InFlight { service: self }))
+            }
+            Err(_) => {
+                self.metrics.rejected.fetch_add(1, Ordering::Relaxed);
+                Err(overloaded(self.retry_after, format!("adaptive concurrency limit {} reached", limit)))
+            }
+        }
+    }
+
+    /// Runs `call` if under the limit, feeding its latency to the controller.
+    async fn limited<T>(&self, call: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        let (in_flight, _guard) = self.admit()?;
+
+        let started = Instant::now();
+        let result = call.await;
+        self.controller.on_sample(Sample {
+            latency: started.elapsed(),
+            in_flight,
+            dropped: result.is_err(),
+        });
+        self.metrics.limit.store(self.controller.limit(), Ordering::Relaxed);
+        result
+    }
+}
+
+#[async_trait]
+impl EchoService for AdaptiveConcurrencyEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.limited(self.inner.echo(message)).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.limited(self.inner.echo_bytes(payload)).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.limited(self.inner.echo_reliable(message, idempotency_key)).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        // Upload time depends on the client, not on server load - count
+        // the call, but don't let it skew the latency samples
+        let _guard = self.admit()?;
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.limited(self.inner.echo_with_session(session_id, message)).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(latency_ms: u64, in_flight: usize, dropped: bool) -> Sample {
+        Sample { latency: Duration::from_millis(latency_ms), in_flight, dropped }
+    }
+
+    #[test]
+    fn test_aimd_grows_and_backs_off() {
+        let controller = AimdController::new(AimdConfig { initial_limit: 10, ..Default::default() });
+
+        controller.on_sample(sample(5, 10, false));
+        assert_eq!(controller.limit(), 11);
+        controller.on_sample(sample(5, 1, false));
+        assert_eq!(controller.limit(), 11, "idle limit doesn't grow");
+        controller.on_sample(sample(500, 11, false));
+        assert_eq!(controller.limit(), 9);
+        controller.on_sample(sample(5, 9, true));
+        assert_eq!(controller.limit(), 8);
+    }
+
+    #[test]
+    fn test_gradient_shrinks_when_latency_rises() {
+        let controller = GradientController::new(GradientConfig { initial_limit: 20, ..Default::default() });
+        for _ in 0..50 {
+            controller.on_sample(sample(10, 20, false));
+        }
+        let steady = controller.limit();
+        assert!(steady > 20, "stable latency grows the limit ({})", steady);
+
+        for _ in 0..20 {
+            controller.on_sample(sample(100, steady, false));
+        }
+        assert!(controller.limit() < steady);
+    }
+
+    /// Fixed-limit controller for exercising the decorator.
+    struct FixedController(usize);
+
+    impl ConcurrencyController for FixedController {
+        fn limit(&self) -> usize {
+            self.0
+        }
+
+        fn on_sample(&self, _sample: Sample) {}
+
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+    }
+
+    struct SlowService;
+
+    #[async_trait]
+    impl EchoService for SlowService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_beyond_limit() {
+        let metrics = Arc::new(AdaptiveConcurrencyMetrics::new());
+        let config = AdaptiveConcurrencyConfig {
+            controller: ControllerKind::Custom(Arc::new(FixedController(1))),
+            ..Default::default()
+        };
+        let service = Arc::new(AdaptiveConcurrencyEchoService::with_metrics(Arc::new(SlowService), &config, metrics.clone()));
+
+        let first = {
+            let service = service.clone();
+            tokio::spawn(async move { service.echo("first".into()).await })
+        };
+        tokio::task::yield_now().await;
+
+        let error = service.echo("second".into()).await.unwrap_err();
+        assert!(echo_contract::is_overloaded(&error));
+        assert_eq!(metrics.rejected(), 1);
+
+        first.await.unwrap().unwrap();
+        service.echo("third".into()).await.unwrap();
+    }
+}
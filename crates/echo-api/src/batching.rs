@@ -0,0 +1,473 @@
+//! Client-Side Batching (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Many small echo calls each pay a full gRPC round trip. With batching,
+//! the client holds `echo()` calls for up to `linger`, or until
+//! `max_messages` have gathered, and sends them as **one** `EchoBatch`
+//! request. Each reply goes back to the call that sent the message:
+//!
+//! ```text
+//! echo("a") ─┐                                  ┌─→ "a"
+//! echo("b") ─┼─→ EchoBatcher (per route) ───────┼─→ "b"
+//! echo("c") ─┘     ↓ max_messages or linger     └─→ "c"   (one oneshot per call)
+//!             EchoBatch(["a", "b", "c"]) → server
+//! ```
+//!
+//! A batch carries one set of headers, so calls are grouped by their
+//! [`RequestContext::sharing_key`] (priority, transforms, caller): a
+//! high-priority echo never rides in a low-priority batch, while request
+//! IDs, unique to every call, don't split batches. The batch itself goes
+//! out with that key as its context, untraced. A batch holds at most `max_messages` calls, and never more than
+//! the server's `MAX_BATCH_MESSAGES`. The server echoes every message
+//! like a separate call (quotas, limits and history see each one), and a
+//! failed message fails only its own call.
+//!
+//! Only `echo` is batched, and only over gRPC. Batched calls give up
+//! hedging and per-call size metrics, and wait up to `linger` longer
+//! when traffic is light - batching pays off under load. The server must
+//! know `EchoBatch` (servers from before it answer `UNIMPLEMENTED`).
+//!
+//! # Rust Learning Note
+//!
+//! The batcher is a spawned task owning the pending calls; callers talk
+//! to it through an mpsc queue and wait on a `oneshot` for their own
+//! reply. Nothing is shared but the queue, so there is no lock held
+//! across the network call. The task ends once the batcher is dropped.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::{Error, Result};
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, RequestContext, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+use crate::coalescing::copy_error;
+
+/// When to send a batch.
+#[derive(Debug, Clone)]
+pub struct BatchingConfig {
+    /// Send as soon as this many calls are waiting (capped at the
+    /// server's `MAX_BATCH_MESSAGES`).
+    pub max_messages: usize,
+    /// Longest time the first call of a batch waits for company.
+    pub linger: Duration,
+}
+
+impl Default for BatchingConfig {
+    fn default() -> Self {
+        Self {
+            max_messages: 64,
+            linger: Duration::from_millis(5),
+        }
+    }
+}
+
+/// Sends a batch of echoes in one request (the `EchoBatch` rpc).
+///
+/// The outer error fails the whole batch; otherwise there is one result
+/// per message, in order.
+#[async_trait]
+pub trait EchoBatchSender: Send + Sync {
+    async fn echo_batch(&self, messages: Vec<Arc<str>>) -> Result<Vec<Result<Arc<str>>>>;
+}
+
+#[cfg(feature = "grpc")]
+#[async_trait]
+impl EchoBatchSender for echo_api_grpc::EchoGrpcGateway {
+    async fn echo_batch(&self, messages: Vec<Arc<str>>) -> Result<Vec<Result<Arc<str>>>> {
+        echo_api_grpc::EchoGrpcGateway::echo_batch(self, messages).await
+    }
+}
+
+#[cfg(feature = "grpc")]
+#[async_trait]
+impl EchoBatchSender for echo_api_grpc::ReconnectingGrpcGateway {
+    async fn echo_batch(&self, messages: Vec<Arc<str>>) -> Result<Vec<Result<Arc<str>>>> {
+        echo_api_grpc::ReconnectingGrpcGateway::echo_batch(self, messages).await
+    }
+}
+
+/// Batching counters.
+#[derive(Debug, Default)]
+pub struct BatchingMetrics {
+    batches: AtomicU64,
+    messages: AtomicU64,
+    failed_batches: AtomicU64,
+}
+
+impl BatchingMetrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide metrics.
+    pub fn global() -> Arc<BatchingMetrics> {
+        static GLOBAL: OnceLock<Arc<BatchingMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(BatchingMetrics::new())).clone()
+    }
+
+    /// Returns the number of batches sent.
+    pub fn batches(&self) -> u64 {
+        self.batches.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of echo calls sent in batches.
+    pub fn messages(&self) -> u64 {
+        self.messages.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of batches that failed as a whole.
+    pub fn failed_batches(&self) -> u64 {
+        self.failed_batches.load(Ordering::Relaxed)
+    }
+
+    /// Renders the counters in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        for (name, help, value) in [
+            ("echo_batching_batches_total", "EchoBatch requests sent by the client", self.batches()),
+            ("echo_batching_messages_total", "Echo calls sent in EchoBatch requests", self.messages()),
+            ("echo_batching_failed_batches_total", "EchoBatch requests that failed as a whole", self.failed_batches()),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        out
+    }
+}
+
+/// A call waiting for its batch.
+struct Pending {
+    message: Arc<str>,
+    reply: oneshot::Sender<Result<Arc<str>>>,
+}
+
+/// Calls of one sharing key waiting to be sent.
+struct Group {
+    /// When the first call's linger is over.
+    due: Instant,
+    calls: Vec<Pending>,
+}
+
+/// The sender batches currently go through.
+type SenderSlot = Arc<Mutex<Arc<dyn EchoBatchSender>>>;
+
+/// Gathers echo calls into batches for one route.
+pub struct EchoBatcher {
+    sender: SenderSlot,
+    config: BatchingConfig,
+    metrics: Arc<BatchingMetrics>,
+    /// Queue of the batcher task, started by the first call.
+    queue: OnceLock<mpsc::UnboundedSender<(RequestContext, Pending)>>,
+}
+
+impl EchoBatcher {
+    /// Creates a batcher sending through `sender`, recording into the
+    /// global [`BatchingMetrics`].
+    pub fn new(sender: Arc<dyn EchoBatchSender>, config: BatchingConfig) -> Arc<Self> {
+        Self::with_metrics(sender, config, BatchingMetrics::global())
+    }
+
+    /// Creates a batcher recording into `metrics`.
+    pub fn with_metrics(sender: Arc<dyn EchoBatchSender>, config: BatchingConfig, metrics: Arc<BatchingMetrics>) -> Arc<Self> {
+        Arc::new(Self { sender: Arc::new(Mutex::new(sender)), config, metrics, queue: OnceLock::new() })
+    }
+
+    /// Sends the batches from now on through `sender`, e.g. a gateway over
+    /// the channel a route was re-resolved to. Batches already sent finish
+    /// on the old one.
+    pub fn set_sender(&self, sender: Arc<dyn EchoBatchSender>) {
+        *self.sender.lock().unwrap() = sender;
+    }
+
+    /// Echoes `message` as part of the next batch of the current
+    /// [`RequestContext`].
+    pub async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let queue = self.queue.get_or_init(|| {
+            let (queue, calls) = mpsc::unbounded_channel();
+            tokio::spawn(run_batcher(calls, self.sender.clone(), self.config.clone(), self.metrics.clone()));
+            queue
+        });
+        let (reply, response) = oneshot::channel();
+        queue
+            .send((RequestContext::current().sharing_key(), Pending { message, reply }))
+            .map_err(|_| Error::Protocol("Echo batcher has stopped".to_string()))?;
+        response
+            .await
+            .map_err(|_| Error::Protocol("Echo batch ended without a result for this call".to_string()))?
+    }
+}
+
+/// Collects calls from `calls` and sends them in batches until the
+/// batcher is dropped.
+async fn run_batcher(
+    mut calls: mpsc::UnboundedReceiver<(RequestContext, Pending)>,
+    sender: SenderSlot,
+    config: BatchingConfig,
+    metrics: Arc<BatchingMetrics>,
+) {
+    // The server rejects a larger EchoBatch as a whole
+    #[cfg(feature = "grpc")]
+    let max_messages = config.max_messages.clamp(1, echo_api_grpc::MAX_BATCH_MESSAGES);
+    #[cfg(not(feature = "grpc"))]
+    let max_messages = config.max_messages.max(1);
+    let mut groups: HashMap<RequestContext, Group> = HashMap::new();
+    loop {
+        let next_due = groups.values().map(|group| group.due).min();
+        let linger_over = async {
+            match next_due {
+                Some(due) => tokio::time::sleep_until(due).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            call = calls.recv() => {
+                let Some((context, pending)) = call else { break };
+                let group = groups
+                    .entry(context.clone())
+                    .or_insert_with(|| Group { due: Instant::now() + config.linger, calls: Vec::new() });
+                group.calls.push(pending);
+                if group.calls.len() >= max_messages {
+                    if let Some(group) = groups.remove(&context) {
+                        send_batch(context, group.calls, &sender, &metrics);
+                    }
+                }
+            }
+            () = linger_over => {
+                let now = Instant::now();
+                let due: Vec<RequestContext> = groups
+                    .iter()
+                    .filter(|(_, group)| group.due <= now)
+                    .map(|(context, _)| context.clone())
+                    .collect();
+                for context in due {
+                    if let Some(group) = groups.remove(&context) {
+                        send_batch(context, group.calls, &sender, &metrics);
+                    }
+                }
+            }
+        }
+    }
+    // The batcher is gone: send what is still waiting
+    for (context, group) in groups {
+        send_batch(context, group.calls, &sender, &metrics);
+    }
+}
+
+/// Sends one batch in the background and hands each call its result.
+fn send_batch(context: RequestContext, calls: Vec<Pending>, sender: &SenderSlot, metrics: &Arc<BatchingMetrics>) {
+    let sender = sender.lock().unwrap().clone();
+    let metrics = metrics.clone();
+    tokio::spawn(async move {
+        let (messages, replies): (Vec<_>, Vec<_>) = calls.into_iter().map(|call| (call.message, call.reply)).unzip();
+        let count = messages.len();
+        debug!("[EchoBatcher] Sending a batch of {} echoes", count);
+        metrics.batches.fetch_add(1, Ordering::Relaxed);
+        metrics.messages.fetch_add(count as u64, Ordering::Relaxed);
+        match context.scope(sender.echo_batch(messages)).await {
+            Ok(results) => {
+                // A short result list leaves the rest without a reply: they fail
+                for (reply, result) in replies.into_iter().zip(results) {
+                    let _ = reply.send(result);
+                }
+            }
+            Err(e) => {
+                metrics.failed_batches.fetch_add(1, Ordering::Relaxed);
+                warn!("[EchoBatcher] Batch of {} echoes failed: {}", count, e);
+                for reply in replies {
+                    let _ = reply.send(Err(copy_error(&e)));
+                }
+            }
+        }
+    });
+}
+
+/// Decorator sending `echo` calls through an [`EchoBatcher`].
+///
+/// Every other method goes to `inner` unchanged.
+pub struct BatchingEchoService {
+    inner: Arc<dyn EchoService>,
+    batcher: Arc<EchoBatcher>,
+}
+
+impl BatchingEchoService {
+    /// Wraps `inner`, batching its echo calls in `batcher`.
+    pub fn new(inner: Arc<dyn EchoService>, batcher: Arc<EchoBatcher>) -> Self {
+        Self { inner, batcher }
+    }
+}
+
+#[async_trait]
+impl EchoService for BatchingEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.batcher.echo(message).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.inner.echo_bytes(payload).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.inner.echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.inner.echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::Priority;
+
+    /// Records batch sizes; "fail" fails its message, "down" the batch.
+    #[derive(Default)]
+    struct MockSender {
+        batches: Mutex<Vec<usize>>,
+    }
+
+    #[async_trait]
+    impl EchoBatchSender for MockSender {
+        async fn echo_batch(&self, messages: Vec<Arc<str>>) -> Result<Vec<Result<Arc<str>>>> {
+            self.batches.lock().unwrap().push(messages.len());
+            if messages.iter().any(|message| &**message == "down") {
+                return Err(echo_contract::unavailable("connection refused"));
+            }
+            let priority = RequestContext::current().priority;
+            Ok(messages.into_iter().map(|message| match &*message {
+                "fail" => Err(echo_contract::overloaded(Duration::from_millis(10), "busy")),
+                _ => Ok(format!("{} {}", priority.as_str(), message).into()),
+            }).collect())
+        }
+    }
+
+    fn batcher(max_messages: usize, linger: Duration) -> (Arc<EchoBatcher>, Arc<MockSender>, Arc<BatchingMetrics>) {
+        let sender = Arc::new(MockSender::default());
+        let metrics = Arc::new(BatchingMetrics::new());
+        let config = BatchingConfig { max_messages, linger };
+        (EchoBatcher::with_metrics(sender.clone(), config, metrics.clone()), sender, metrics)
+    }
+
+    #[tokio::test]
+    async fn test_full_batches_go_out_at_once() {
+        // A linger this long would hang the test: only max_messages sends
+        let (batcher, sender, metrics) = batcher(3, Duration::from_secs(3600));
+
+        let replies = futures::future::join_all(
+            ["a", "b", "c", "d", "e", "f"].map(|message| batcher.echo(message.into())),
+        ).await;
+        let replies: Vec<_> = replies.into_iter().map(|reply| reply.unwrap().to_string()).collect();
+        assert_eq!(replies, ["normal a", "normal b", "normal c", "normal d", "normal e", "normal f"]);
+        assert_eq!(*sender.batches.lock().unwrap(), [3, 3]);
+        assert_eq!((metrics.batches(), metrics.messages()), (2, 6));
+    }
+
+    #[tokio::test]
+    async fn test_linger_flushes_per_context() {
+        let (batcher, sender, _) = batcher(64, Duration::from_millis(20));
+
+        let (a, b, c) = tokio::join!(
+            batcher.echo("a".into()),
+            batcher.echo("b".into()),
+            RequestContext::new(Priority::High).scope(batcher.echo("c".into())),
+        );
+        assert_eq!((&*a.unwrap(), &*b.unwrap(), &*c.unwrap()), ("normal a", "normal b", "high c"));
+        let mut batches = sender.batches.lock().unwrap().clone();
+        batches.sort();
+        assert_eq!(batches, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_traced_calls_share_a_batch() {
+        let (batcher, sender, _) = batcher(2, Duration::from_secs(3600));
+        let call = |request_id: &str| RequestContext::new(Priority::Normal).with_trace("4bf92f35", request_id);
+
+        let (a, b) = tokio::join!(
+            call("7d1c").scope(batcher.echo("a".into())),
+            call("9e2f").scope(batcher.echo("b".into())),
+        );
+        assert_eq!((&*a.unwrap(), &*b.unwrap()), ("normal a", "normal b"));
+        assert_eq!(*sender.batches.lock().unwrap(), [2]);
+    }
+
+    #[tokio::test]
+    async fn test_batches_follow_a_replaced_sender() {
+        let (batcher, first, _) = batcher(1, Duration::from_millis(20));
+        batcher.echo("a".into()).await.unwrap();
+
+        let second = Arc::new(MockSender::default());
+        batcher.set_sender(second.clone());
+        batcher.echo("b".into()).await.unwrap();
+        assert_eq!((first.batches.lock().unwrap().len(), second.batches.lock().unwrap().len()), (1, 1));
+    }
+
+    #[cfg(feature = "grpc")]
+    #[tokio::test]
+    async fn test_batches_stay_within_the_server_limit() {
+        let (batcher, sender, _) = batcher(usize::MAX, Duration::from_secs(3600));
+
+        let calls = (0..echo_api_grpc::MAX_BATCH_MESSAGES).map(|_| batcher.echo("a".into()));
+        futures::future::try_join_all(calls).await.unwrap();
+        assert_eq!(*sender.batches.lock().unwrap(), [echo_api_grpc::MAX_BATCH_MESSAGES]);
+    }
+
+    #[tokio::test]
+    async fn test_errors_reach_the_right_calls() {
+        let (batcher, _, metrics) = batcher(2, Duration::from_millis(20));
+
+        let (ok, failed) = tokio::join!(batcher.echo("a".into()), batcher.echo("fail".into()));
+        assert_eq!(&*ok.unwrap(), "normal a");
+        assert!(echo_contract::is_overloaded(&failed.unwrap_err()));
+
+        // A failed batch fails every call in it
+        let (a, b) = tokio::join!(batcher.echo("a".into()), batcher.echo("down".into()));
+        assert!(echo_contract::is_unavailable(&a.unwrap_err()));
+        assert!(echo_contract::is_unavailable(&b.unwrap_err()));
+        assert_eq!(metrics.failed_batches(), 1);
+    }
+}
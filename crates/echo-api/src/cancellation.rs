@@ -0,0 +1,118 @@
+//! Cancellation-safe Module Start (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! `run_with_config` starts the modules one by one and only listens for
+//! Ctrl-C once they all run. A start that waits - the client on the
+//! registry, the server on its history migration or self-test - used to
+//! sit out its timeouts before the process noticed the signal. The
+//! binaries now hand every module config one [`CancellationToken`],
+//! cancelled on Ctrl-C, and `start()` runs its waits under it:
+//!
+//! ```text
+//! bin: cancel_on_ctrl_c() ──→ token ──→ EchoClientModuleConfig.cancellation
+//!                                        ↓
+//! EchoClientModule::start
+//!   until_cancelled(&token, "waiting for the echo service", echo.service())
+//!       ├─ service ready   → Ok(service)
+//!       └─ Ctrl-C          → Err("start cancelled while waiting for ...")
+//!                            → run_with_config returns, process exits
+//! ```
+//!
+//! Blocking steps (a SQLite migration) can't be interrupted halfway, so
+//! they run on the blocking pool under `until_cancelled`: a cancelled start
+//! stops waiting for them, and they finish on their own thread. A step cut
+//! off with an effect elsewhere (a registry publish) is undone by the
+//! module before it returns the error. [`check_cancelled`] covers the
+//! gaps between steps.
+//!
+//! A token that is never cancelled (the config default) changes nothing.
+//!
+//! # Rust Learning Note
+//!
+//! Cancelling an `async` step is dropping its future: `tokio::select!`
+//! drops the losing branch, which closes whatever connection or timer the
+//! step held. That's why a waiting start can be cut short safely, and a
+//! blocking call (which owns its thread until it returns) can't.
+
+use std::future::Future;
+use hsu_common::{Error, Result};
+use tracing::warn;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Returns a token cancelled on the first Ctrl-C.
+///
+/// Call from inside the runtime, before `run_with_config`. The framework's
+/// own Ctrl-C handling (stopping the started modules) still applies.
+pub fn cancel_on_ctrl_c() -> CancellationToken {
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            cancel.cancel();
+        }
+    });
+    token
+}
+
+/// The error a cancelled start fails with.
+fn cancelled(step: &str) -> Error {
+    Error::Protocol(format!("start cancelled while {}", step))
+}
+
+/// Fails if `token` is cancelled - between steps that can't be cut short.
+pub fn check_cancelled(token: &CancellationToken, step: &str) -> Result<()> {
+    if token.is_cancelled() {
+        return Err(cancelled(step));
+    }
+    Ok(())
+}
+
+/// Runs `future` until it completes or `token` is cancelled, whichever
+/// comes first; `step` names it in the error (`"waiting for the registry"`).
+pub async fn until_cancelled<T, F>(token: &CancellationToken, step: &str, future: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    tokio::select! {
+        // Checked first: an already cancelled start doesn't begin the step
+        biased;
+        _ = token.cancelled() => {
+            warn!("Start cancelled while {}", step);
+            Err(cancelled(step))
+        }
+        result = future => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_cuts_a_waiting_step_short() {
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        });
+
+        let never = std::future::pending::<Result<()>>();
+        let error = tokio::time::timeout(Duration::from_secs(5), until_cancelled(&token, "waiting for the registry", never))
+            .await
+            .expect("cancellation should end the step")
+            .unwrap_err();
+        assert!(error.to_string().contains("start cancelled while waiting for the registry"));
+        assert!(check_cancelled(&token, "migrating").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_uncancelled_token_changes_nothing() {
+        let token = CancellationToken::new();
+        assert_eq!(until_cancelled(&token, "echoing", async { Ok(7) }).await.unwrap(), 7);
+        check_cancelled(&token, "migrating").unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+//! Gateway Capabilities for the Admin Endpoint (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Every gateway set registers itself when it's built; the admin endpoint
+//! asks each of them what its protocols support on `GET /capabilities`:
+//!
+//! ```text
+//! new_echo_service_gateways_with_options ──register──→ CapabilitiesRegistry::global()
+//!                                                           ↓ GET /capabilities
+//!                                               gateways.capabilities() per set
+//! ```
+//!
+//! The registry keeps weak references: dropping the gateways drops them
+//! from the report too.
+
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use echo_contract::{EchoServiceGateways, GatewayCapabilities};
+
+/// Process-wide list of the gateway sets built so far.
+#[derive(Default)]
+pub struct CapabilitiesRegistry {
+    gateways: Mutex<Vec<Weak<dyn EchoServiceGateways>>>,
+}
+
+impl CapabilitiesRegistry {
+    /// Returns the process-wide registry.
+    pub fn global() -> Arc<CapabilitiesRegistry> {
+        static GLOBAL: OnceLock<Arc<CapabilitiesRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(CapabilitiesRegistry::default())).clone()
+    }
+
+    /// Reports on `gateways` for as long as they live.
+    pub fn register(&self, gateways: &Arc<dyn EchoServiceGateways>) {
+        let mut registered = self.gateways.lock().unwrap();
+        registered.retain(|weak| weak.strong_count() > 0);
+        registered.push(Arc::downgrade(gateways));
+    }
+
+    /// Capabilities of every live gateway set, in registration order.
+    pub fn collect(&self) -> Vec<GatewayCapabilities> {
+        let mut registered = self.gateways.lock().unwrap();
+        registered.retain(|weak| weak.strong_count() > 0);
+        registered
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|gateways| gateways.capabilities())
+            .collect()
+    }
+
+    /// Renders the report served by the admin endpoint.
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for capabilities in self.collect() {
+            out.push_str(&format!("{}\n\n", capabilities));
+        }
+        if out.is_empty() {
+            out.push_str("no gateways in this process\n");
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use echo_contract::{CallInfo, EchoEvents, EchoService, EchoServiceHandlers, ProtocolCapabilities};
+    use hsu_common::{ModuleID, Protocol, Result, ServiceID};
+
+    struct MockGateways;
+
+    #[async_trait]
+    impl EchoServiceGateways for MockGateways {
+        fn module_id(&self) -> ModuleID {
+            ModuleID::from("echo")
+        }
+
+        fn service_ids(&self) -> Vec<ServiceID> {
+            vec![ServiceID::from("service")]
+        }
+
+        fn enable_direct_closure(&self, _handlers: EchoServiceHandlers) {}
+
+        async fn get_service(&self, _protocol: Protocol) -> Result<Arc<dyn EchoService>> {
+            Err(hsu_common::Error::Protocol("not supported by mock".to_string()))
+        }
+
+        fn capabilities(&self) -> GatewayCapabilities {
+            GatewayCapabilities {
+                module_id: ModuleID::from("echo"),
+                service_id: ServiceID::from("service"),
+                protocols: vec![ProtocolCapabilities::missing(Protocol::Direct, "mock")],
+                standby: None,
+            }
+        }
+
+        async fn get_events(&self, _protocol: Protocol) -> Result<Arc<dyn EchoEvents>> {
+            Err(hsu_common::Error::Protocol("not supported by mock".to_string()))
+        }
+
+        async fn echo_with_info(&self, _protocol: Protocol, _message: Arc<str>) -> Result<(Arc<str>, CallInfo)> {
+            Err(hsu_common::Error::Protocol("not supported by mock".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_reports_live_gateways_only() {
+        let registry = CapabilitiesRegistry::default();
+        let kept: Arc<dyn EchoServiceGateways> = Arc::new(MockGateways);
+        registry.register(&kept);
+        {
+            let dropped: Arc<dyn EchoServiceGateways> = Arc::new(MockGateways);
+            registry.register(&dropped);
+        }
+
+        assert_eq!(registry.collect().len(), 1);
+        assert!(registry.render_text().starts_with("module: echo\nPROTOCOL"));
+        drop(kept);
+        assert_eq!(registry.render_text(), "no gateways in this process\n");
+    }
+}
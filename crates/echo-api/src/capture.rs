@@ -0,0 +1,499 @@
+//! Traffic Capture and Replay (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! ```text
+//! caller ──→ RecordingEchoService ──→ inner EchoService
+//!                 │ CaptureRecord (request, response or error, timing)
+//!                 ↓
+//!            AuditSink ── NdjsonAuditSink → capture.ndjson (one JSON object per line)
+//!
+//! bins/echo-replay: read_capture → replay_call(target) → responses_match
+//! bins/echo-diff:   read_capture → observe_call(left, right) → diff_observations
+//! ```
+//!
+//! Requests and responses are encoded by the same functions on both
+//! sides, so a replayed response compares field by field with the
+//! recorded one. A line looks like:
+//!
+//! ```text
+//! {"offset_us":1520,"method":"echo","request":{"message":"hi"},
+//!  "response":{"message":"hi"},"error":null,"latency_us":85}
+//! ```
+//!
+//! Binary payloads are lowercase hex. `echo_file` streams are captured
+//! only up to a size limit; larger ones are recorded as `truncated` and
+//! not replayed.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use bytes::Bytes;
+use echo_contract::{
+    ByteStream, EchoAck, EchoMethod, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport,
+    HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use futures::StreamExt;
+use hsu_common::{Error, Result};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+/// Default limit of a captured `echo_file` stream.
+pub const DEFAULT_MAX_FILE_BYTES: usize = 1024 * 1024;
+
+/// Response fields that legitimately differ between runs.
+///
+/// Session counts and duplicate flags depend on the target's state.
+const VOLATILE_FIELDS: &[&str] = &["count", "duplicate"];
+
+/// One captured call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureRecord {
+    /// When the call started, relative to the start of the capture.
+    pub offset: Duration,
+    /// The method that was called.
+    pub method: EchoMethod,
+    /// Encoded request arguments.
+    pub request: Value,
+    /// Encoded response (`None` if the call failed).
+    pub response: Option<Value>,
+    /// The error message, if the call failed.
+    pub error: Option<String>,
+    /// How long the call took.
+    pub latency: Duration,
+}
+
+impl CaptureRecord {
+    /// Encodes the record as one NDJSON line (without the newline).
+    pub fn to_json(&self) -> Value {
+        json!({
+            "offset_us": self.offset.as_micros() as u64,
+            "method": self.method.as_str(),
+            "request": self.request,
+            "response": self.response,
+            "error": self.error,
+            "latency_us": self.latency.as_micros() as u64,
+        })
+    }
+
+    /// Decodes a line written by [`to_json`](Self::to_json).
+    pub fn from_json(line: &str) -> Result<Self> {
+        let invalid = |message: String| Error::Validation { message };
+        let value: Value = serde_json::from_str(line)
+            .map_err(|e| invalid(format!("Invalid capture line: {}", e)))?;
+        let micros = |name: &str| {
+            value[name].as_u64()
+                .map(Duration::from_micros)
+                .ok_or_else(|| invalid(format!("Capture line has no '{}'", name)))
+        };
+
+        Ok(Self {
+            offset: micros("offset_us")?,
+            method: value["method"].as_str().unwrap_or_default().parse()?,
+            request: value["request"].clone(),
+            response: Some(value["response"].clone()).filter(|v| !v.is_null()),
+            error: value["error"].as_str().map(str::to_string),
+            latency: micros("latency_us")?,
+        })
+    }
+}
+
+/// Destination of captured calls.
+///
+/// Recording must not slow down or fail the call, so `record` has no
+/// result: sinks log their own errors.
+pub trait AuditSink: Send + Sync {
+    /// Stores one captured call.
+    fn record(&self, record: &CaptureRecord);
+}
+
+/// Writes each record as one JSON line to a file.
+pub struct NdjsonAuditSink {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl NdjsonAuditSink {
+    /// Creates (truncates) the capture file at `path`.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path).map_err(|e| Error::Validation {
+            message: format!("Capture file {}: {}", path.display(), e),
+        })?;
+        info!("[Capture] Recording echo traffic to {}", path.display());
+        Ok(Self { path, writer: Mutex::new(BufWriter::new(file)) })
+    }
+}
+
+impl AuditSink for NdjsonAuditSink {
+    fn record(&self, record: &CaptureRecord) {
+        let mut writer = self.writer.lock().unwrap();
+        // Flush per line, so a killed server leaves a readable capture
+        let result = writeln!(writer, "{}", record.to_json()).and_then(|()| writer.flush());
+        if let Err(e) = result {
+            warn!("[Capture] Failed to write to {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Reads all records of a capture file.
+pub fn read_capture(path: impl AsRef<Path>) -> Result<Vec<CaptureRecord>> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| Error::Validation {
+        message: format!("Capture file {}: {}", path.display(), e),
+    })?;
+
+    let mut records = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| Error::Validation {
+            message: format!("Capture file {}: {}", path.display(), e),
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = CaptureRecord::from_json(&line).map_err(|e| Error::Validation {
+            message: format!("{}:{}: {}", path.display(), number + 1, e),
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Decorator that records every call to an [`AuditSink`].
+pub struct RecordingEchoService {
+    inner: Arc<dyn EchoService>,
+    sink: Arc<dyn AuditSink>,
+    started: Instant,
+    max_file_bytes: usize,
+}
+
+impl RecordingEchoService {
+    /// Wraps `inner`, recording to `sink`.
+    pub fn new(inner: Arc<dyn EchoService>, sink: Arc<dyn AuditSink>) -> Self {
+        Self {
+            inner,
+            sink,
+            started: Instant::now(),
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+        }
+    }
+
+    /// Captures `echo_file` streams up to `max_file_bytes`.
+    pub fn with_max_file_bytes(mut self, max_file_bytes: usize) -> Self {
+        self.max_file_bytes = max_file_bytes;
+        self
+    }
+
+    fn record<T>(&self, method: EchoMethod, started: Instant, request: Value, result: &Result<T>, encode: impl Fn(&T) -> Value) {
+        let (response, error) = match result {
+            Ok(response) => (Some(encode(response)), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        self.sink.record(&CaptureRecord {
+            offset: started.duration_since(self.started),
+            method,
+            request,
+            response,
+            error,
+            latency: started.elapsed(),
+        });
+    }
+}
+
+#[async_trait]
+impl EchoService for RecordingEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let started = Instant::now();
+        let request = json!({ "message": &*message });
+        let result = self.inner.echo(message).await;
+        self.record(EchoMethod::Echo, started, request, &result, encode_echo);
+        result
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let started = Instant::now();
+        let request = json!({ "payload": to_hex(&payload) });
+        let result = self.inner.echo_bytes(payload).await;
+        self.record(EchoMethod::EchoBytes, started, request, &result, encode_bytes);
+        result
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let started = Instant::now();
+        let request = json!({ "message": &*message, "idempotency_key": &idempotency_key });
+        let result = self.inner.echo_reliable(message, idempotency_key).await;
+        self.record(EchoMethod::EchoReliable, started, request, &result, encode_ack);
+        result
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let started = Instant::now();
+
+        // Tee the chunks (cheap `Bytes` clones) while the service consumes them
+        let captured = Arc::new(Mutex::new((Vec::new(), 0usize, false)));
+        let max_file_bytes = self.max_file_bytes;
+        let tee = captured.clone();
+        let chunks: ByteStream = Box::pin(chunks.inspect(move |chunk| {
+            if let Ok(chunk) = chunk {
+                let mut tee = tee.lock().unwrap();
+                let (kept, size, truncated) = &mut *tee;
+                *size += chunk.len();
+                if *size > max_file_bytes {
+                    *truncated = true;
+                    kept.clear();
+                } else if !*truncated {
+                    kept.push(chunk.clone());
+                }
+            }
+        }));
+
+        let result = self.inner.echo_file(chunks).await;
+        let request = {
+            let (kept, _, truncated) = &*captured.lock().unwrap();
+            if *truncated {
+                json!({ "truncated": true })
+            } else {
+                json!({ "chunks": kept.iter().map(|chunk| to_hex(chunk)).collect::<Vec<_>>() })
+            }
+        };
+        self.record(EchoMethod::EchoFile, started, request, &result, encode_digest);
+        result
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let started = Instant::now();
+        let request = json!({ "session_id": &session_id, "message": &*message });
+        let result = self.inner.echo_with_session(session_id, message).await;
+        self.record(EchoMethod::EchoWithSession, started, request, &result, encode_session);
+        result
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+/// Sends the captured request of `record` to `service`.
+///
+/// Returns the response encoded like the recorded one, or `None` if the
+/// call isn't replayable (a truncated `echo_file`).
+pub async fn replay_call(service: &dyn EchoService, record: &CaptureRecord) -> Option<Result<Value>> {
+    let request = &record.request;
+    let string = |name: &str| request[name].as_str().unwrap_or_default().to_string();
+
+    let response = match record.method {
+        EchoMethod::Echo => service.echo(string("message").into()).await.map(|r| encode_echo(&r)),
+        EchoMethod::EchoBytes => match from_hex(&string("payload")) {
+            Ok(payload) => service.echo_bytes(payload).await.map(|r| encode_bytes(&r)),
+            Err(e) => Err(e),
+        },
+        EchoMethod::EchoReliable => service
+            .echo_reliable(string("message").into(), string("idempotency_key"))
+            .await
+            .map(|r| encode_ack(&r)),
+        EchoMethod::EchoFile => {
+            if request["truncated"].as_bool() == Some(true) {
+                return None;
+            }
+            let chunks: Result<Vec<Bytes>> = request["chunks"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|chunk| from_hex(chunk.as_str().unwrap_or_default()))
+                .collect();
+            match chunks {
+                Ok(chunks) => {
+                    let stream: ByteStream = Box::pin(futures::stream::iter(chunks.into_iter().map(Ok)));
+                    service.echo_file(stream).await.map(|r| encode_digest(&r))
+                }
+                Err(e) => Err(e),
+            }
+        }
+        EchoMethod::EchoWithSession => service
+            .echo_with_session(string("session_id"), string("message").into())
+            .await
+            .map(|r| encode_session(&r)),
+    };
+    Some(response)
+}
+
+/// Whether a replayed outcome matches the recorded one.
+///
+/// Both failed, or both succeeded with equal responses apart from
+/// state-dependent fields (session counts, duplicate flags).
+pub fn responses_match(record: &CaptureRecord, replayed: &Result<Value>) -> bool {
+    match (&record.response, replayed) {
+        (Some(recorded), Ok(replayed)) => strip_volatile(recorded) == strip_volatile(replayed),
+        (None, Err(_)) => true,
+        _ => false,
+    }
+}
+
+/// `response` without its state-dependent fields.
+pub(crate) fn strip_volatile(response: &Value) -> Value {
+    let mut response = response.clone();
+    if let Some(fields) = response.as_object_mut() {
+        for field in VOLATILE_FIELDS {
+            fields.remove(*field);
+        }
+    }
+    response
+}
+
+fn encode_echo(message: &Arc<str>) -> Value {
+    json!({ "message": &**message })
+}
+
+fn encode_bytes(payload: &Bytes) -> Value {
+    json!({ "payload": to_hex(payload) })
+}
+
+fn encode_ack(ack: &EchoAck) -> Value {
+    json!({ "message": &*ack.message, "idempotency_key": &ack.idempotency_key, "duplicate": ack.duplicate })
+}
+
+fn encode_digest(digest: &FileDigest) -> Value {
+    json!({ "byte_count": digest.byte_count, "chunk_count": digest.chunk_count, "sha256": &digest.sha256 })
+}
+
+fn encode_session(echo: &SessionEcho) -> Value {
+    json!({ "message": &*echo.message, "session_id": &echo.session_id, "count": echo.count })
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Bytes> {
+    let invalid = || Error::Validation { message: "Invalid hex payload in capture".to_string() };
+    if hex.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()).ok_or_else(invalid))
+        .collect::<Result<Vec<u8>>>()
+        .map(Bytes::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockService;
+
+    #[async_trait]
+    impl EchoService for MockService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_file(&self, mut chunks: ByteStream) -> Result<FileDigest> {
+            let (mut byte_count, mut chunk_count) = (0, 0);
+            while let Some(chunk) = chunks.next().await {
+                byte_count += chunk?.len() as u64;
+                chunk_count += 1;
+            }
+            Ok(FileDigest { byte_count, chunk_count, sha256: String::new() })
+        }
+    }
+
+    #[derive(Default)]
+    struct MemorySink(Mutex<Vec<CaptureRecord>>);
+
+    impl AuditSink for MemorySink {
+        fn record(&self, record: &CaptureRecord) {
+            self.0.lock().unwrap().push(record.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let sink = Arc::new(MemorySink::default());
+        let service = RecordingEchoService::new(Arc::new(MockService), sink.clone());
+
+        service.echo("Hello".into()).await.unwrap();
+        service.echo_bytes(Bytes::from_static(&[0, 255])).await.unwrap();
+        service.echo_reliable("Hello".into(), "k1".to_string()).await.unwrap_err();
+        let chunks: ByteStream = Box::pin(futures::stream::iter(vec![
+            Ok(Bytes::from_static(b"ab")),
+            Ok(Bytes::from_static(b"c")),
+        ]));
+        service.echo_file(chunks).await.unwrap();
+
+        let records = sink.0.lock().unwrap().clone();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[1].request, json!({ "payload": "00ff" }));
+        assert!(records[2].response.is_none() && records[2].error.is_some());
+        assert_eq!(records[3].request, json!({ "chunks": ["6162", "63"] }));
+
+        // Same target, same outcomes
+        for record in &records {
+            let replayed = replay_call(&MockService, record).await.unwrap();
+            assert!(responses_match(record, &replayed), "{:?} vs {:?}", record, replayed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_large_file_is_truncated() {
+        let sink = Arc::new(MemorySink::default());
+        let service = RecordingEchoService::new(Arc::new(MockService), sink.clone()).with_max_file_bytes(2);
+
+        let chunks: ByteStream = Box::pin(futures::stream::iter(vec![Ok(Bytes::from_static(b"abc"))]));
+        service.echo_file(chunks).await.unwrap();
+
+        let record = sink.0.lock().unwrap()[0].clone();
+        assert_eq!(record.request, json!({ "truncated": true }));
+        assert!(replay_call(&MockService, &record).await.is_none());
+    }
+
+    #[test]
+    fn test_record_json_round_trip() {
+        let record = CaptureRecord {
+            offset: Duration::from_micros(1520),
+            method: EchoMethod::EchoWithSession,
+            request: json!({ "session_id": "s1", "message": "hi" }),
+            response: Some(json!({ "message": "hi", "session_id": "s1", "count": 3 })),
+            error: None,
+            latency: Duration::from_micros(85),
+        };
+        let line = record.to_json().to_string();
+        assert_eq!(CaptureRecord::from_json(&line).unwrap(), record);
+
+        // Session counts depend on the target's state
+        let replayed = Ok(json!({ "message": "hi", "session_id": "s1", "count": 1 }));
+        assert!(responses_match(&record, &replayed));
+        assert!(!responses_match(&record, &Ok(json!({ "message": "bye", "session_id": "s1", "count": 3 }))));
+    }
+}
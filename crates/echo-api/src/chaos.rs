@@ -0,0 +1,523 @@
+//! Fault Injection for Resilience Demos (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Retries, hedging, reconnects and circuit breakers only show their worth
+//! when something goes wrong. `echo-grpc-srv chaos` makes things go wrong
+//! on purpose - a share of the calls is slowed down, a share fails, and
+//! every so often the calls in flight are cut off - and the operator can
+//! turn the faults up, down or off while a client is running:
+//!
+//! ```text
+//! Direct / gRPC handler
+//!     ↓
+//! ChaosEchoService ── Chaos (delay=20%:500ms,error=5%,drop=30s)
+//!     ├─ 20% of the calls → sleep 500ms first
+//!     ├─  5% of the calls → UNAVAILABLE: chaos: injected error
+//!     └─ every 30s        → calls in flight and open streams fail with
+//!     ↓                     UNAVAILABLE: chaos: connection dropped
+//! EchoServiceImpl             ↓
+//!                           ChaosMetrics → GET /metrics
+//!
+//! POST /maintenance/echo/chaos -d 'error=50%'   ← change the faults at runtime
+//! POST /maintenance/echo/chaos -d off
+//! ```
+//!
+//! The gRPC listener belongs to the framework, so connection drops are
+//! simulated one level up: the affected calls fail with the same
+//! `UNAVAILABLE` error a client sees when its connection resets, which
+//! is what triggers its reconnect and retry logic. `get_info` is never
+//! faulted, so an operator can still identify the instance.
+//!
+//! # Rust Learning Note
+//!
+//! A drop is a counter in a `tokio::sync::watch` channel. Every call
+//! subscribes when it starts - which marks the current value as seen -
+//! and races its own future against `changed()` in `tokio::select!`, so
+//! only drops that happen while it runs cut it off. The losing branch is
+//! simply dropped, cancelling the call.
+
+use std::collections::hash_map::RandomState;
+use std::fmt::{self, Write};
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use hsu_common::{Error, Result};
+use tokio::sync::watch;
+use tracing::{info, warn};
+use echo_contract::{
+    format_duration, parse_duration, unavailable, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest,
+    HistoryExportFormat, HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo,
+    SessionEcho,
+};
+use crate::maintenance::MaintenanceRegistry;
+
+/// Operation that changes the faults (body: a [`ChaosConfig`], `off`).
+pub const CHAOS_OPERATION: &str = "chaos";
+
+/// Detail of the errors injected into a share of the calls.
+pub const INJECTED_ERROR: &str = "chaos: injected error";
+
+/// Detail of the errors of calls cut off by a connection drop.
+pub const CONNECTION_DROPPED: &str = "chaos: connection dropped";
+
+/// Which faults are injected.
+///
+/// Written as text (admin operation, startup log) it is a comma-separated
+/// list of `delay=PERCENT%:DURATION`, `error=PERCENT%` and
+/// `drop=INTERVAL`, or `off`:
+///
+/// ```text
+/// delay=20%:500ms,error=5%,drop=30s
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosConfig {
+    /// Share of the calls delayed by `delay`, in percent.
+    pub delay_percent: u8,
+    /// How long a delayed call waits before it runs.
+    pub delay: Duration,
+    /// Share of the calls failing as `UNAVAILABLE`, in percent.
+    pub error_percent: u8,
+    /// Cut off the calls in flight this often (never if `None`).
+    pub drop_every: Option<Duration>,
+}
+
+impl ChaosConfig {
+    /// Whether any fault is injected.
+    pub fn is_active(&self) -> bool {
+        (self.delay_percent > 0 && !self.delay.is_zero()) || self.error_percent > 0 || self.drop_every.is_some()
+    }
+
+    /// Checks the shares are percentages and drops have an interval.
+    pub fn validate(&self) -> Result<()> {
+        for (name, percent) in [("delay", self.delay_percent), ("error", self.error_percent)] {
+            if percent > 100 {
+                return Err(Error::Validation { message: format!("Chaos {} share {}% is above 100%", name, percent) });
+            }
+        }
+        if self.drop_every.is_some_and(|every| every.is_zero()) {
+            return Err(Error::Validation { message: "Chaos drop interval must be positive".to_string() });
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ChaosConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let mut config = ChaosConfig::default();
+        if s.is_empty() || s == "off" {
+            return Ok(config);
+        }
+        let invalid = |reason: &str| Error::Validation { message: format!("Invalid chaos '{}': {}", s, reason) };
+        let percent = |value: &str| -> Result<u8> {
+            value.strip_suffix('%').unwrap_or(value).parse().map_err(|_| invalid("expected a share like 10%"))
+        };
+        let duration = |value: &str| parse_duration(value).map_err(|_| invalid("expected a duration like 200ms"));
+        for entry in s.split(',').map(str::trim) {
+            match entry.split_once('=') {
+                Some(("delay", value)) => {
+                    let (share, delay) = value.split_once(':').ok_or_else(|| invalid("expected delay=PERCENT%:DURATION"))?;
+                    config.delay_percent = percent(share)?;
+                    config.delay = duration(delay)?;
+                }
+                Some(("error", value)) => config.error_percent = percent(value)?,
+                Some(("drop", value)) => config.drop_every = Some(duration(value)?),
+                _ => return Err(invalid("expected delay=PERCENT%:DURATION, error=PERCENT%, drop=INTERVAL or off")),
+            }
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl fmt::Display for ChaosConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries = Vec::new();
+        if self.delay_percent > 0 && !self.delay.is_zero() {
+            entries.push(format!("delay={}%:{}", self.delay_percent, format_duration(self.delay)));
+        }
+        if self.error_percent > 0 {
+            entries.push(format!("error={}%", self.error_percent));
+        }
+        if let Some(every) = self.drop_every {
+            entries.push(format!("drop={}", format_duration(every)));
+        }
+        if entries.is_empty() {
+            return f.write_str("off");
+        }
+        f.write_str(&entries.join(","))
+    }
+}
+
+/// Faults injected so far.
+#[derive(Debug, Default)]
+pub struct ChaosMetrics {
+    delayed: AtomicU64,
+    errors: AtomicU64,
+    dropped: AtomicU64,
+    drops: AtomicU64,
+}
+
+impl ChaosMetrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide metrics.
+    pub fn global() -> Arc<ChaosMetrics> {
+        static GLOBAL: OnceLock<Arc<ChaosMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(ChaosMetrics::new())).clone()
+    }
+
+    /// Returns the number of calls delayed.
+    pub fn delayed(&self) -> u64 {
+        self.delayed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of calls failed with an injected error.
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of calls and streams cut off by a drop.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of connection drops.
+    pub fn drops(&self) -> u64 {
+        self.drops.load(Ordering::Relaxed)
+    }
+
+    /// Renders the counters in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP echo_chaos_faults_total Calls hit by an injected fault");
+        let _ = writeln!(out, "# TYPE echo_chaos_faults_total counter");
+        for (fault, value) in [("delay", self.delayed()), ("error", self.errors()), ("dropped", self.dropped())] {
+            let _ = writeln!(out, "echo_chaos_faults_total{{fault=\"{}\"}} {}", fault, value);
+        }
+        let _ = writeln!(out, "# HELP echo_chaos_connection_drops_total Simulated connection drops");
+        let _ = writeln!(out, "# TYPE echo_chaos_connection_drops_total counter");
+        let _ = writeln!(out, "echo_chaos_connection_drops_total {}", self.drops());
+        out
+    }
+}
+
+/// The faults currently injected, changeable at runtime.
+pub struct Chaos {
+    config: watch::Sender<ChaosConfig>,
+    /// Bumped on every connection drop.
+    drops: watch::Sender<u64>,
+    metrics: Arc<ChaosMetrics>,
+}
+
+impl Chaos {
+    /// Injects `config`'s faults, counting them in the global [`ChaosMetrics`].
+    pub fn new(config: ChaosConfig) -> Result<Arc<Self>> {
+        Self::with_metrics(config, ChaosMetrics::global())
+    }
+
+    /// Injects `config`'s faults, counting them in `metrics`.
+    pub fn with_metrics(config: ChaosConfig, metrics: Arc<ChaosMetrics>) -> Result<Arc<Self>> {
+        config.validate()?;
+        Ok(Arc::new(Self {
+            config: watch::channel(config).0,
+            drops: watch::channel(0).0,
+            metrics,
+        }))
+    }
+
+    /// Returns the faults currently injected.
+    pub fn config(&self) -> ChaosConfig {
+        *self.config.borrow()
+    }
+
+    /// Replaces the faults (`ChaosConfig::default()` turns them off).
+    pub fn set(&self, config: ChaosConfig) -> Result<()> {
+        config.validate()?;
+        self.config.send_replace(config);
+        info!("[Chaos] Injecting: {}", config);
+        Ok(())
+    }
+
+    /// Cuts off every call in flight and every open stream.
+    pub fn drop_connections(&self) {
+        self.drops.send_modify(|drops| *drops += 1);
+        self.metrics.drops.fetch_add(1, Ordering::Relaxed);
+        warn!("[Chaos] Dropping connections");
+    }
+
+    /// Drops the connections at the configured interval, following
+    /// changes; runs until aborted.
+    pub async fn run_drops(&self) {
+        let mut changes = self.config.subscribe();
+        loop {
+            let every = changes.borrow_and_update().drop_every;
+            match every {
+                Some(every) => tokio::select! {
+                    _ = tokio::time::sleep(every) => self.drop_connections(),
+                    _ = changes.changed() => {}
+                },
+                // The sender lives in self, so this only waits for a change
+                None => {
+                    let _ = changes.changed().await;
+                }
+            }
+        }
+    }
+}
+
+/// Whether a call falls into a share of `percent`.
+///
+/// Every `RandomState` is seeded differently, which is plenty for a demo
+/// and saves a dependency on `rand`.
+fn roll(percent: u8) -> bool {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    hasher.finish() % 100 < u64::from(percent)
+}
+
+/// Registers [`CHAOS_OPERATION`] for `module`: the argument replaces the
+/// faults (the current ones are reported if empty).
+pub fn register_chaos_operation(module: &str, chaos: Arc<Chaos>) {
+    MaintenanceRegistry::global().register_operation(module, CHAOS_OPERATION, Arc::new(move |argument: String| {
+        let chaos = chaos.clone();
+        Box::pin(async move {
+            if !argument.trim().is_empty() {
+                chaos.set(argument.parse()?)?;
+            }
+            Ok(format!("Chaos: {}", chaos.config()))
+        })
+    }));
+}
+
+/// Decorator injecting the faults of a [`Chaos`].
+pub struct ChaosEchoService {
+    inner: Arc<dyn EchoService>,
+    chaos: Arc<Chaos>,
+}
+
+impl ChaosEchoService {
+    /// Injects `chaos`'s faults into the calls to `inner`.
+    pub fn new(inner: Arc<dyn EchoService>, chaos: Arc<Chaos>) -> Self {
+        Self { inner, chaos }
+    }
+
+    /// Delays and/or fails the call, by the configured shares.
+    async fn inject(&self) -> Result<()> {
+        let config = self.chaos.config();
+        if config.delay_percent > 0 && roll(config.delay_percent) {
+            self.chaos.metrics.delayed.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(config.delay).await;
+        }
+        if config.error_percent > 0 && roll(config.error_percent) {
+            self.chaos.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            return Err(unavailable(INJECTED_ERROR));
+        }
+        Ok(())
+    }
+
+    /// Runs `call` after the injected faults, unless a drop cuts it off.
+    async fn run<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        let mut drops = self.chaos.drops.subscribe();
+        let faulted = async {
+            self.inject().await?;
+            call.await
+        };
+        tokio::select! {
+            result = faulted => result,
+            Ok(()) = drops.changed() => {
+                self.chaos.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+                Err(unavailable(CONNECTION_DROPPED))
+            }
+        }
+    }
+
+    /// Ends `stream` with a [`CONNECTION_DROPPED`] error at the next drop.
+    fn cut<T: Send + 'static>(
+        &self,
+        stream: Pin<Box<dyn Stream<Item = Result<T>> + Send>>,
+    ) -> Pin<Box<dyn Stream<Item = Result<T>> + Send>> {
+        let metrics = self.chaos.metrics.clone();
+        let state = Some((stream, self.chaos.drops.subscribe()));
+        Box::pin(futures::stream::unfold(state, move |state| {
+            let metrics = metrics.clone();
+            async move {
+                let (mut stream, mut drops) = state?;
+                tokio::select! {
+                    item = stream.next() => item.map(|item| (item, Some((stream, drops)))),
+                    Ok(()) = drops.changed() => {
+                        metrics.dropped.fetch_add(1, Ordering::Relaxed);
+                        Some((Err(unavailable(CONNECTION_DROPPED)), None))
+                    }
+                }
+            }
+        }))
+    }
+}
+
+#[async_trait]
+impl EchoService for ChaosEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.run(self.inner.echo(message)).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.run(self.inner.echo_bytes(payload)).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.run(self.inner.echo_reliable(message, idempotency_key)).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.run(self.inner.echo_file(chunks)).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.run(self.inner.echo_with_session(session_id, message)).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.run(self.inner.schedule_echo(message, schedule)).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.run(self.inner.cancel_scheduled_echo(job_id)).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.run(self.inner.get_history(query)).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        let stream = self.run(self.inner.stream_history(query)).await?;
+        Ok(self.cut(stream))
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        let stream = self.run(self.inner.export_history(format, query)).await?;
+        Ok(self.cut(stream))
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.run(self.inner.import_history(format, data)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::{is_unavailable, HistoryEntry};
+
+    /// Echoes after `delay`; streams history forever.
+    struct SlowService {
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl EchoService for SlowService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            tokio::time::sleep(self.delay).await;
+            Ok(message)
+        }
+
+        async fn stream_history(&self, _query: HistoryQuery) -> Result<HistoryStream> {
+            Ok(Box::pin(futures::stream::pending::<Result<HistoryEntry>>()))
+        }
+    }
+
+    fn service(config: ChaosConfig, delay: Duration) -> (ChaosEchoService, Arc<Chaos>, Arc<ChaosMetrics>) {
+        let metrics = Arc::new(ChaosMetrics::new());
+        let chaos = Chaos::with_metrics(config, metrics.clone()).unwrap();
+        let service = ChaosEchoService::new(Arc::new(SlowService { delay }), chaos.clone());
+        (service, chaos, metrics)
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let config: ChaosConfig = "delay=20%:500ms, error=5%,drop=30s".parse().unwrap();
+        assert_eq!(config, ChaosConfig {
+            delay_percent: 20,
+            delay: Duration::from_millis(500),
+            error_percent: 5,
+            drop_every: Some(Duration::from_secs(30)),
+        });
+        assert_eq!(config.to_string(), "delay=20%:500ms,error=5%,drop=30s");
+        assert_eq!("off".parse::<ChaosConfig>().unwrap(), ChaosConfig::default());
+        assert_eq!(ChaosConfig::default().to_string(), "off");
+        assert!(!ChaosConfig::default().is_active());
+
+        for invalid in ["error=150%", "delay=10%", "drop=0s", "latency=1s", "error=lots"] {
+            assert!(invalid.parse::<ChaosConfig>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_delays_by_share() {
+        let config = ChaosConfig { error_percent: 100, ..Default::default() };
+        let (service, chaos, metrics) = service(config, Duration::ZERO);
+        let error = service.echo("hi".into()).await.unwrap_err();
+        assert!(is_unavailable(&error) && error.to_string().contains(INJECTED_ERROR));
+
+        chaos.set(ChaosConfig { delay_percent: 100, delay: Duration::from_millis(50), ..Default::default() }).unwrap();
+        let started = std::time::Instant::now();
+        assert_eq!(&*service.echo("hi".into()).await.unwrap(), "hi");
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        chaos.set(ChaosConfig::default()).unwrap();
+        assert_eq!(&*service.echo("hi".into()).await.unwrap(), "hi");
+        assert_eq!((metrics.errors(), metrics.delayed()), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_drops_cut_off_calls_and_streams() {
+        let config = ChaosConfig { drop_every: Some(Duration::from_millis(50)), ..Default::default() };
+        let (service, chaos, metrics) = service(config, Duration::from_secs(60));
+        let drops = tokio::spawn({
+            let chaos = chaos.clone();
+            async move { chaos.run_drops().await }
+        });
+
+        let mut stream = service.stream_history(HistoryQuery::default()).await.unwrap();
+        let error = service.echo("hi".into()).await.unwrap_err();
+        assert!(is_unavailable(&error) && error.to_string().contains(CONNECTION_DROPPED));
+        assert!(is_unavailable(&stream.next().await.unwrap().unwrap_err()));
+        assert!(stream.next().await.is_none());
+        assert_eq!((metrics.drops(), metrics.dropped()), (1, 2));
+        assert!(metrics.render_prometheus().contains("echo_chaos_faults_total{fault=\"dropped\"} 2"));
+        drops.abort();
+    }
+
+    #[tokio::test]
+    async fn test_operation_changes_the_faults() {
+        let chaos = Chaos::with_metrics(ChaosConfig::default(), Arc::new(ChaosMetrics::new())).unwrap();
+        register_chaos_operation("chaos-test", chaos.clone());
+        let registry = MaintenanceRegistry::global();
+
+        let summary = registry.run_operation("chaos-test", CHAOS_OPERATION, "error=50%".into()).await.unwrap().unwrap();
+        assert_eq!(summary, "Chaos: error=50%");
+        assert_eq!(chaos.config().error_percent, 50);
+        assert!(registry.run_operation("chaos-test", CHAOS_OPERATION, "error=500%".into()).await.unwrap().is_err());
+
+        let summary = registry.run_operation("chaos-test", CHAOS_OPERATION, "off".into()).await.unwrap().unwrap();
+        assert_eq!(summary, "Chaos: off");
+        registry.clear("chaos-test");
+    }
+}
@@ -0,0 +1,378 @@
+//! Client-Side Request Coalescing (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! When a server slows down, clients retry - and many callers in one
+//! process often retry the **same** message at the same time. With
+//! coalescing, identical `echo()` calls that overlap share one outbound
+//! request ("single flight"):
+//!
+//! ```text
+//! caller A: echo("ping") ──┐ leader
+//! caller B: echo("ping") ──┼─→ InFlightEchoes["grpc host:50051", "ping", ctx]
+//! caller C: echo("ping") ──┘          ↓ one request
+//!                                 gateway → server
+//!                                     ↓ "ping"
+//!                          A, B and C all get the reply (or the error)
+//! ```
+//!
+//! Calls only coalesce when everything that shapes the reply matches:
+//! the route (protocol and endpoint), the message and the
+//! [`RequestContext::sharing_key`] (priority, transforms, caller). The
+//! per-call parts (request ID, languages) don't count: every client call
+//! has its own request ID. The request goes out with the leader's context.
+//! Only `echo` is coalesced - the other methods have side effects or
+//! unique inputs.
+//!
+//! A call that must reach the server itself (e.g. a health probe) opts
+//! out with [`without_coalescing`].
+//!
+//! # Rust Learning Note
+//!
+//! The shared call is a [`futures::future::Shared`]: whichever waiter
+//! polls it drives the request, and every waiter gets a clone of the
+//! output. That needs a `Clone` output, so the error travels as
+//! `Arc<Error>` and each waiter gets its own copy back.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use hsu_common::{Error, Result};
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, RequestContext, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+tokio::task_local! {
+    static BYPASS: ();
+}
+
+/// Runs `future` with coalescing turned off: its `echo` calls always
+/// send their own request.
+pub async fn without_coalescing<F: Future>(future: F) -> F::Output {
+    BYPASS.scope((), future).await
+}
+
+fn bypassed() -> bool {
+    BYPASS.try_with(|_| ()).is_ok()
+}
+
+/// Coalescing counters.
+#[derive(Debug, Default)]
+pub struct CoalescingMetrics {
+    sent: AtomicU64,
+    coalesced: AtomicU64,
+    bypassed: AtomicU64,
+}
+
+impl CoalescingMetrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide metrics.
+    pub fn global() -> Arc<CoalescingMetrics> {
+        static GLOBAL: OnceLock<Arc<CoalescingMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(CoalescingMetrics::new())).clone()
+    }
+
+    /// Returns the number of calls that sent their own request.
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of calls that shared another call's request.
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of calls that opted out.
+    pub fn bypassed(&self) -> u64 {
+        self.bypassed.load(Ordering::Relaxed)
+    }
+
+    /// Renders the counters in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP echo_coalescing_calls_total Client echo calls by coalescing outcome");
+        let _ = writeln!(out, "# TYPE echo_coalescing_calls_total counter");
+        for (outcome, value) in [("sent", self.sent()), ("coalesced", self.coalesced()), ("bypassed", self.bypassed())] {
+            let _ = writeln!(out, "echo_coalescing_calls_total{{outcome=\"{}\"}} {}", outcome, value);
+        }
+        out
+    }
+}
+
+type Outcome = std::result::Result<Arc<str>, Arc<Error>>;
+type SharedEcho = Shared<BoxFuture<'static, Outcome>>;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CallKey {
+    route: Arc<str>,
+    message: Arc<str>,
+    /// [`RequestContext::sharing_key`] of the calls.
+    context: RequestContext,
+}
+
+/// `echo` calls currently in flight, shared by all gateways of one
+/// [`crate::EchoServiceGatewaysImpl`].
+#[derive(Default)]
+pub struct InFlightEchoes {
+    calls: Mutex<HashMap<CallKey, SharedEcho>>,
+}
+
+impl InFlightEchoes {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct calls in flight.
+    pub fn len(&self) -> usize {
+        self.calls.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no call is in flight.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Decorator sharing one request among identical concurrent `echo` calls.
+pub struct CoalescingEchoService {
+    inner: Arc<dyn EchoService>,
+    in_flight: Arc<InFlightEchoes>,
+    /// Protocol and endpoint of `inner`; calls on other routes never mix.
+    route: Arc<str>,
+    metrics: Arc<CoalescingMetrics>,
+}
+
+impl CoalescingEchoService {
+    /// Wraps `inner` (reached via `route`), recording into the global
+    /// [`CoalescingMetrics`].
+    pub fn new(inner: Arc<dyn EchoService>, in_flight: Arc<InFlightEchoes>, route: impl Into<Arc<str>>) -> Self {
+        Self::with_metrics(inner, in_flight, route, CoalescingMetrics::global())
+    }
+
+    /// Wraps `inner`, recording into `metrics`.
+    pub fn with_metrics(
+        inner: Arc<dyn EchoService>,
+        in_flight: Arc<InFlightEchoes>,
+        route: impl Into<Arc<str>>,
+        metrics: Arc<CoalescingMetrics>,
+    ) -> Self {
+        Self { inner, in_flight, route: route.into(), metrics }
+    }
+}
+
+/// Gives each waiter its own copy of a shared error (`Error` isn't `Clone`).
+///
+/// `Protocol` errors keep their message, so [`echo_contract::error_kind`]
+/// (overloaded, deadline exceeded, ...) still classifies them.
+pub(crate) fn copy_error(error: &Error) -> Error {
+    match error {
+        Error::Validation { message } => Error::Validation { message: message.clone() },
+        Error::Protocol(message) => Error::Protocol(message.clone()),
+        other => Error::Protocol(other.to_string()),
+    }
+}
+
+#[async_trait]
+impl EchoService for CoalescingEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        if bypassed() {
+            self.metrics.bypassed.fetch_add(1, Ordering::Relaxed);
+            return self.inner.echo(message).await;
+        }
+
+        let context = RequestContext::current();
+        let key = CallKey { route: self.route.clone(), message: message.clone(), context: context.sharing_key() };
+        let call = {
+            let mut calls = self.in_flight.calls.lock().unwrap();
+            match calls.get(&key) {
+                Some(call) => {
+                    self.metrics.coalesced.fetch_add(1, Ordering::Relaxed);
+                    call.clone()
+                }
+                None => {
+                    self.metrics.sent.fetch_add(1, Ordering::Relaxed);
+                    let inner = self.inner.clone();
+                    // Any waiter may drive it: pin the leader's context
+                    let call = context.scope(async move { inner.echo(message).await.map_err(Arc::new) })
+                        .boxed()
+                        .shared();
+                    calls.insert(key.clone(), call.clone());
+                    call
+                }
+            }
+        };
+
+        let outcome = call.clone().await;
+        // The first waiter to finish retires the call; later identical
+        // calls send a fresh request
+        let mut calls = self.in_flight.calls.lock().unwrap();
+        if calls.get(&key).is_some_and(|current| current.ptr_eq(&call)) {
+            calls.remove(&key);
+        }
+        outcome.map_err(|error| copy_error(&error))
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.inner.echo_bytes(payload).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.inner.echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.inner.echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+    use echo_contract::Priority;
+
+    /// Counts `echo` requests; each takes 50ms.
+    #[derive(Default)]
+    struct SlowService {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EchoService for SlowService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            if &*message == "fail" {
+                return Err(echo_contract::overloaded(Duration::from_millis(10), "busy"));
+            }
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    fn coalescing(inner: Arc<SlowService>) -> (CoalescingEchoService, Arc<CoalescingMetrics>) {
+        let metrics = Arc::new(CoalescingMetrics::new());
+        let service = CoalescingEchoService::with_metrics(inner, Arc::new(InFlightEchoes::new()), "grpc a:1", metrics.clone());
+        (service, metrics)
+    }
+
+    #[tokio::test]
+    async fn test_identical_calls_share_one_request() {
+        let inner = Arc::new(SlowService::default());
+        let (service, metrics) = coalescing(inner.clone());
+
+        let (a, b, c) = tokio::join!(
+            service.echo("ping".into()),
+            service.echo("ping".into()),
+            service.echo("pong".into()),
+        );
+        assert_eq!((&*a.unwrap(), &*b.unwrap(), &*c.unwrap()), ("ping", "ping", "pong"));
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 2);
+        assert_eq!((metrics.sent(), metrics.coalesced()), (2, 1));
+        assert!(service.in_flight.is_empty());
+
+        // Finished calls are not reused
+        service.echo("ping".into()).await.unwrap();
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_errors_fan_out_and_contexts_stay_apart() {
+        let inner = Arc::new(SlowService::default());
+        let (service, metrics) = coalescing(inner.clone());
+        let high = RequestContext::new(Priority::High);
+
+        let (a, b, c) = tokio::join!(
+            service.echo("fail".into()),
+            service.echo("fail".into()),
+            high.scope(service.echo("fail".into())),
+        );
+        for result in [a, b, c] {
+            assert!(echo_contract::is_overloaded(&result.unwrap_err()));
+        }
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 2);
+        assert_eq!(metrics.coalesced(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_calls_coalesce_despite_their_request_ids() {
+        let inner = Arc::new(SlowService::default());
+        let (service, metrics) = coalescing(inner.clone());
+        // Like the client module: one trace, a fresh request ID per call
+        let call = |request_id: &str| RequestContext::new(Priority::Normal).with_trace("4bf92f35", request_id);
+
+        let (a, b) = tokio::join!(
+            call("7d1c").scope(service.echo("ping".into())),
+            call("9e2f").with_accept_language("de").scope(service.echo("ping".into())),
+        );
+        assert_eq!((&*a.unwrap(), &*b.unwrap()), ("ping", "ping"));
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.coalesced(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_without_coalescing_sends_its_own_request() {
+        let inner = Arc::new(SlowService::default());
+        let (service, metrics) = coalescing(inner.clone());
+
+        let (a, b) = tokio::join!(
+            service.echo("ping".into()),
+            without_coalescing(service.echo("ping".into())),
+        );
+        a.unwrap();
+        b.unwrap();
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 2);
+        assert_eq!(metrics.bypassed(), 1);
+    }
+}
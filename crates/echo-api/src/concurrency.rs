@@ -0,0 +1,256 @@
+//! Concurrency Limits for Direct Closure (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Direct calls run on the **caller's** task - there is no server-side
+//! queue or connection limit like on the gRPC path. A flood of in-process
+//! calls could therefore starve the server module's other work.
+//!
+//! ```text
+//! Client Module
+//!     ↓ echo()
+//! ConcurrencyLimitedEchoService   (semaphore: N permits)
+//!     ↓ at most N calls in flight
+//! EchoServiceImpl
+//! ```
+//!
+//! The limiter is applied once, when direct closure is enabled, so every
+//! gateway handed out shares the same permits.
+
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::{Error, Result};
+use echo_contract::{
+    overloaded, ByteStream, EchoAck, EchoSchedule, EchoService, EchoServiceHandlers, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use tokio::sync::{Semaphore, SemaphorePermit, TryAcquireError};
+use tracing::debug;
+
+/// Per-service concurrency limits for the Direct protocol.
+///
+/// Field names mirror [`EchoServiceHandlers`]; `None` means unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct DirectConcurrencyLimits {
+    /// Maximum in-flight direct calls to the echo service.
+    pub service: Option<usize>,
+    /// Reject calls beyond the limit with this back-off hint instead of
+    /// queueing them (see [`echo_contract::overloaded`]).
+    pub reject_with_retry_after: Option<Duration>,
+}
+
+impl DirectConcurrencyLimits {
+    /// Checks the limit admits calls at all: `Some(0)` would park every
+    /// direct call forever.
+    pub fn validate(&self) -> Result<()> {
+        if self.service == Some(0) {
+            return Err(Error::Validation {
+                message: "Direct concurrency limit must be positive (None for unlimited)".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Applies the configured limits to a set of handlers.
+pub fn limit_direct_handlers(
+    handlers: EchoServiceHandlers,
+    limits: &DirectConcurrencyLimits,
+) -> EchoServiceHandlers {
+    match limits.service {
+        Some(max) => {
+            debug!("[DirectConcurrency] Limiting direct echo service to {} concurrent calls", max);
+            let mut limited = ConcurrencyLimitedEchoService::new(handlers.service, max);
+            if let Some(retry_after) = limits.reject_with_retry_after {
+                limited = limited.rejecting_when_full(retry_after);
+            }
+            EchoServiceHandlers { service: Arc::new(limited), ..handlers }
+        }
+        None => handlers,
+    }
+}
+
+/// Decorator that bounds the number of concurrent calls to an `EchoService`.
+///
+/// # Rust Learning Note
+///
+/// ## Decorator Pattern
+///
+/// The limiter implements `EchoService` itself and forwards every call
+/// to the inner service while holding a semaphore permit. Callers can't
+/// tell the difference - it's just another `Arc<dyn EchoService>`!
+pub struct ConcurrencyLimitedEchoService {
+    inner: Arc<dyn EchoService>,
+    permits: Semaphore,
+    reject_with_retry_after: Option<Duration>,
+}
+
+impl ConcurrencyLimitedEchoService {
+    /// Wraps `inner`, allowing at most `max_concurrent` calls in flight.
+    pub fn new(inner: Arc<dyn EchoService>, max_concurrent: usize) -> Self {
+        Self {
+            inner,
+            permits: Semaphore::new(max_concurrent),
+            reject_with_retry_after: None,
+        }
+    }
+
+    /// Rejects calls that find no free permit instead of making them wait.
+    pub fn rejecting_when_full(mut self, retry_after: Duration) -> Self {
+        self.reject_with_retry_after = Some(retry_after);
+        self
+    }
+
+    /// Returns the number of currently available permits.
+    pub fn available_permits(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    async fn acquire(&self) -> Result<SemaphorePermit<'_>> {
+        if let Some(retry_after) = self.reject_with_retry_after {
+            return match self.permits.try_acquire() {
+                Ok(permit) => Ok(permit),
+                Err(TryAcquireError::NoPermits) => Err(overloaded(retry_after, "direct concurrency limit reached")),
+                Err(TryAcquireError::Closed) => Err(Error::Protocol("Direct concurrency limiter closed".to_string())),
+            };
+        }
+        self.permits.acquire().await.map_err(|_| Error::Protocol(
+            "Direct concurrency limiter closed".to_string(),
+        ))
+    }
+}
+
+#[async_trait]
+impl EchoService for ConcurrencyLimitedEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let _permit = self.acquire().await?;
+        self.inner.echo(message).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let _permit = self.acquire().await?;
+        self.inner.echo_bytes(payload).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let _permit = self.acquire().await?;
+        self.inner.echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        // The permit is held until the whole stream is consumed
+        let _permit = self.acquire().await?;
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let _permit = self.acquire().await?;
+        self.inner.echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Records the highest number of concurrent `echo` calls.
+    #[derive(Default)]
+    struct SlowService {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EchoService for SlowService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_limits_concurrent_calls() {
+        let inner = Arc::new(SlowService::default());
+        let limited = Arc::new(ConcurrencyLimitedEchoService::new(inner.clone(), 2));
+
+        let calls: Vec<_> = (0..8)
+            .map(|_| {
+                let limited = limited.clone();
+                tokio::spawn(async move { limited.echo("Hello".into()).await })
+            })
+            .collect();
+        for call in calls {
+            call.await.unwrap().unwrap();
+        }
+
+        assert_eq!(inner.max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(limited.available_permits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_when_full() {
+        let inner = Arc::new(SlowService::default());
+        let limited = Arc::new(ConcurrencyLimitedEchoService::new(inner, 1)
+            .rejecting_when_full(Duration::from_millis(50)));
+
+        let first = {
+            let limited = limited.clone();
+            tokio::spawn(async move { limited.echo("first".into()).await })
+        };
+        tokio::task::yield_now().await;
+
+        let error = limited.echo("second".into()).await.unwrap_err();
+        assert!(echo_contract::is_overloaded(&error));
+        assert_eq!(echo_contract::retry_after(&error), Some(Duration::from_millis(50)));
+        first.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_zero_limit_is_invalid() {
+        let limits = DirectConcurrencyLimits { service: Some(0), ..Default::default() };
+        assert!(matches!(limits.validate(), Err(Error::Validation { .. })));
+        assert!(DirectConcurrencyLimits::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,152 @@
+//! Deadline-aware Direct Calls (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A gRPC call carries a deadline and fails with `DEADLINE_EXCEEDED` when
+//! it runs out. In-process calls have no transport to enforce that, so the
+//! Direct factory wraps the handler instead:
+//!
+//! ```text
+//! Client Module
+//!     ↓ echo()
+//! DeadlineEchoService   (tokio::time::timeout)
+//!     ↓
+//! EchoServiceImpl
+//! ```
+//!
+//! Both paths return [`echo_contract::deadline_exceeded`], so callers
+//! handle timeouts the same way regardless of protocol.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::Result;
+use echo_contract::{
+    deadline_exceeded, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+/// Decorator that fails unary calls exceeding a per-call deadline.
+///
+/// `echo_file` is not bounded: an upload takes as long as its source,
+/// which a fixed per-call deadline can't account for.
+pub struct DeadlineEchoService {
+    inner: Arc<dyn EchoService>,
+    deadline: Duration,
+}
+
+impl DeadlineEchoService {
+    /// Wraps `inner`, bounding every unary call by `deadline`.
+    pub fn new(inner: Arc<dyn EchoService>, deadline: Duration) -> Self {
+        Self { inner, deadline }
+    }
+
+    async fn bounded<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        tokio::time::timeout(self.deadline, call)
+            .await
+            .map_err(|_| deadline_exceeded(format!("direct call exceeded {:?}", self.deadline)))?
+    }
+}
+
+#[async_trait]
+impl EchoService for DeadlineEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.bounded(self.inner.echo(message)).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.bounded(self.inner.echo_bytes(payload)).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.bounded(self.inner.echo_reliable(message, idempotency_key)).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.bounded(self.inner.echo_with_session(session_id, message)).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.bounded(self.inner.get_info()).await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.bounded(self.inner.schedule_echo(message, schedule)).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.bounded(self.inner.cancel_scheduled_echo(job_id)).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.bounded(self.inner.get_history(query)).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        // Bounds opening the stream, not reading it
+        self.bounded(self.inner.stream_history(query)).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.bounded(self.inner.export_history(format, query)).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        // Unbounded, like echo_file: the upload takes as long as the client needs
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::is_deadline_exceeded;
+
+    /// Sleeps for `delay` before echoing.
+    struct SlowService {
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl EchoService for SlowService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            tokio::time::sleep(self.delay).await;
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fast_call_succeeds() {
+        let service = DeadlineEchoService::new(
+            Arc::new(SlowService { delay: Duration::ZERO }),
+            Duration::from_secs(1),
+        );
+
+        assert_eq!(&*service.echo("Hello".into()).await.unwrap(), "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_slow_call_exceeds_deadline() {
+        let service = DeadlineEchoService::new(
+            Arc::new(SlowService { delay: Duration::from_secs(5) }),
+            Duration::from_millis(10),
+        );
+
+        let error = service.echo("Hello".into()).await.unwrap_err();
+        assert!(is_deadline_exceeded(&error));
+    }
+}
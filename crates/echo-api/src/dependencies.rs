@@ -0,0 +1,238 @@
+//! Inter-Module Dependency Declarations (Layer 5)
+//!
+//! # Architecture
+//!
+//! Each module's wiring declares what it provides and what it consumes,
+//! next to registering its descriptor:
+//!
+//! ```text
+//! echo         provides  echo/service, echo/events
+//! echo-client  consumes  echo/service
+//! echo-monitor consumes  echo/events      provides echo-monitor/monitor
+//! ```
+//!
+//! Before `run_with_config`, the binary checks the configured module set
+//! with [`validate_module_dependencies`]. A missing provider is reported
+//! up front, with every problem listed, instead of surfacing as a
+//! gateway error in the middle of some module's `start()`.
+//!
+//! A consumed service is satisfied by an **enabled** module in the config
+//! that provides it, or by a module the binary reaches remotely (gRPC via
+//! the service registry) - those are passed in as `remote`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+use echo_contract::{EchoServiceId, ECHO_MODULE_ID};
+use hsu_common::{Error, ModuleID, Result};
+use hsu_module_api::Config;
+
+/// A service of a module, written `module/service`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ServiceRef {
+    pub module: String,
+    pub service: String,
+}
+
+impl ServiceRef {
+    /// Creates a reference to `service` of `module`.
+    pub fn new(module: impl Into<String>, service: impl Into<String>) -> Self {
+        Self { module: module.into(), service: service.into() }
+    }
+}
+
+impl fmt::Display for ServiceRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.module, self.service)
+    }
+}
+
+impl FromStr for ServiceRef {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once('/') {
+            Some((module, service)) if !module.is_empty() && !service.is_empty() => {
+                Ok(Self::new(module, service))
+            }
+            _ => Err(Error::Validation {
+                message: format!("Invalid service reference '{}' (expected module/service)", s),
+            }),
+        }
+    }
+}
+
+/// What a module provides and consumes.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleDependencies {
+    /// Service IDs this module provides.
+    pub provides: Vec<String>,
+    /// Services of other modules this module consumes.
+    pub consumes: Vec<ServiceRef>,
+}
+
+impl ModuleDependencies {
+    /// Declares a provided service.
+    pub fn provides(mut self, service: impl Into<String>) -> Self {
+        self.provides.push(service.into());
+        self
+    }
+
+    /// Declares a consumed service of the echo module.
+    pub fn consumes_echo(mut self, service: EchoServiceId) -> Self {
+        self.consumes.push(ServiceRef::new(ECHO_MODULE_ID, service.as_str()));
+        self
+    }
+
+    /// Declares a consumed service, e.g. `"echo/service"`.
+    ///
+    /// # Panics
+    ///
+    /// If `service` isn't `module/service` - declarations are constants.
+    pub fn consumes(mut self, service: &str) -> Self {
+        self.consumes.push(service.parse().expect("service reference must be module/service"));
+        self
+    }
+}
+
+/// Registry of module dependency declarations.
+#[derive(Default)]
+pub struct DependencyRegistry {
+    modules: RwLock<BTreeMap<String, ModuleDependencies>>,
+}
+
+impl DependencyRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide registry (filled by the wiring `init_*` functions).
+    pub fn global() -> Arc<DependencyRegistry> {
+        static GLOBAL: OnceLock<Arc<DependencyRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(DependencyRegistry::new())).clone()
+    }
+
+    /// Records the declaration of `module`, replacing any previous one.
+    pub fn declare(&self, module: &ModuleID, dependencies: ModuleDependencies) {
+        self.modules.write().unwrap().insert(module.to_string(), dependencies);
+    }
+
+    /// Returns the modules `module` consumes services of, without duplicates.
+    pub fn consumed_modules(&self, module: &str) -> Vec<String> {
+        let modules = self.modules.read().unwrap();
+        let mut consumed: Vec<String> = modules
+            .get(module)
+            .map(|declaration| declaration.consumes.iter().map(|service| service.module.clone()).collect())
+            .unwrap_or_default();
+        consumed.sort();
+        consumed.dedup();
+        consumed
+    }
+
+    /// Checks that every consumed service of the `enabled` modules is
+    /// provided by another enabled module or reachable in `remote`.
+    ///
+    /// Returns all problems in one error, one per line.
+    pub fn validate(&self, enabled: &[String], remote: &[String]) -> Result<()> {
+        let modules = self.modules.read().unwrap();
+        let mut problems = Vec::new();
+
+        for module in enabled {
+            let Some(declaration) = modules.get(module) else {
+                continue;
+            };
+            for needed in &declaration.consumes {
+                if remote.contains(&needed.module) {
+                    continue;
+                }
+                if !enabled.contains(&needed.module) {
+                    problems.push(format!(
+                        "{} consumes {}, but module '{}' is not enabled (or reachable remotely)",
+                        module, needed, needed.module
+                    ));
+                    continue;
+                }
+                let provided = modules
+                    .get(&needed.module)
+                    .is_some_and(|provider| provider.provides.contains(&needed.service));
+                if !provided {
+                    problems.push(format!(
+                        "{} consumes {}, but module '{}' doesn't provide '{}'",
+                        module, needed, needed.module, needed.service
+                    ));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(Error::Validation {
+            message: format!("Unsatisfied module dependencies:\n  - {}", problems.join("\n  - ")),
+        })
+    }
+}
+
+/// Validates the enabled modules of `config` against the global registry.
+///
+/// `remote` lists modules served by other processes (reached over gRPC).
+/// Call right before `run_with_config`.
+pub fn validate_module_dependencies(config: &Config, remote: &[ModuleID]) -> Result<()> {
+    let enabled: Vec<String> = config.modules
+        .iter()
+        .filter(|module| module.enabled)
+        .map(|module| module.id.to_string())
+        .collect();
+    let remote: Vec<String> = remote.iter().map(|module| module.to_string()).collect();
+    DependencyRegistry::global().validate(&enabled, &remote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> DependencyRegistry {
+        let registry = DependencyRegistry::new();
+        registry.declare(&ModuleID::from("echo"), ModuleDependencies::default()
+            .provides("service")
+            .provides("events"));
+        registry.declare(&ModuleID::from("echo-client"), ModuleDependencies::default()
+            .consumes("echo/service"));
+        registry.declare(&ModuleID::from("echo-stats"), ModuleDependencies::default()
+            .consumes("echo/stats"));
+        registry
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_local_and_remote_providers() {
+        let registry = registry();
+        registry.validate(&ids(&["echo", "echo-client"]), &[]).unwrap();
+        registry.validate(&ids(&["echo-client"]), &ids(&["echo"])).unwrap();
+    }
+
+    #[test]
+    fn test_reports_every_problem() {
+        let registry = registry();
+
+        let error = registry.validate(&ids(&["echo-client", "echo-stats"]), &[]).unwrap_err();
+        let report = error.to_string();
+        assert!(report.contains("echo-client consumes echo/service, but module 'echo' is not enabled"));
+        assert!(report.contains("echo-stats consumes echo/stats"));
+
+        let error = registry.validate(&ids(&["echo", "echo-stats"]), &[]).unwrap_err();
+        assert!(error.to_string().contains("doesn't provide 'stats'"));
+    }
+
+    #[test]
+    fn test_service_ref_parsing() {
+        assert_eq!("echo/service".parse::<ServiceRef>().unwrap(), ServiceRef::new("echo", "service"));
+        assert!("echo".parse::<ServiceRef>().is_err());
+        assert!("/service".parse::<ServiceRef>().is_err());
+    }
+}
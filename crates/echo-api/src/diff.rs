@@ -0,0 +1,214 @@
+//! Cross-Target Diffing (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Replays the same call against two targets and compares what a caller
+//! would see from each - reply, error kind and response metadata:
+//!
+//! ```text
+//! bins/echo-diff corpus.ndjson --left direct --right grpc:localhost:50051
+//!     for each CaptureRecord:
+//!         observe_call(left)  ─┐  replay_call inside count_attempts +
+//!         observe_call(right) ─┘  collect_response_metadata (as for CallInfo)
+//!         diff_observations(left, right) → [Divergence]
+//! ```
+//!
+//! Replies are compared like [`responses_match`](crate::responses_match)
+//! compares a replay with its capture: state-dependent fields (session
+//! counts, duplicate flags) are ignored. Errors compare by their
+//! [`EchoErrorKind`](echo_contract::EchoErrorKind) code, since each
+//! protocol words the detail differently; errors of no contract kind
+//! compare by their `hsu_common::Error` variant (`VALIDATION`,
+//! `PROTOCOL`, ...). Metadata keys that differ by design (instance and request IDs)
+//! are skipped via [`DiffOptions::ignored_metadata`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::time::{Duration, Instant};
+use echo_contract::{collect_response_metadata, count_attempts, error_kind, EchoService};
+use hsu_common::{Error, Result};
+use serde_json::Value;
+
+use crate::capture::{replay_call, strip_volatile, CaptureRecord};
+
+/// Metadata keys that differ between any two instances.
+pub const DEFAULT_IGNORED_METADATA: &[&str] = &["instance-id", "request-id"];
+
+/// What a caller saw of one replayed call (the `CallInfo` of a replay).
+#[derive(Debug)]
+pub struct Observation {
+    /// Encoded reply, or the error.
+    pub outcome: Result<Value>,
+    /// Metadata the serving instance attached.
+    pub server_metadata: BTreeMap<String, String>,
+    /// Requests sent, retries and hedged attempts included.
+    pub attempts: u32,
+    /// Wall time of the call.
+    pub latency: Duration,
+}
+
+/// Replays `record` against `service`, observing it like `echo_with_info`.
+///
+/// `None` for calls that can't be replayed (truncated `echo_file`
+/// captures).
+pub async fn observe_call(service: &dyn EchoService, record: &CaptureRecord) -> Option<Observation> {
+    let started = Instant::now();
+    let ((outcome, attempts), server_metadata) =
+        collect_response_metadata(count_attempts(replay_call(service, record))).await;
+    Some(Observation {
+        outcome: outcome?,
+        server_metadata,
+        attempts,
+        latency: started.elapsed(),
+    })
+}
+
+/// Comparison settings.
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Metadata keys not compared (neither presence nor value).
+    pub ignored_metadata: BTreeSet<String>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            ignored_metadata: DEFAULT_IGNORED_METADATA.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Also skips metadata `key`.
+    pub fn with_ignored_metadata(mut self, key: impl Into<String>) -> Self {
+        self.ignored_metadata.insert(key.into());
+        self
+    }
+}
+
+/// One way two observations of the same call differ.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Divergence {
+    /// Both succeeded with different replies.
+    Response { left: Value, right: Value },
+    /// One side succeeded, the other failed.
+    Outcome { left: String, right: String },
+    /// Both failed, with different error kinds.
+    Error { left: String, right: String },
+    /// A metadata entry is missing on one side or has another value.
+    Metadata { key: String, left: Option<String>, right: Option<String> },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::Response { left, right } => write!(f, "reply {} vs {}", left, right),
+            Divergence::Outcome { left, right } => write!(f, "outcome {} vs {}", left, right),
+            Divergence::Error { left, right } => write!(f, "error {} vs {}", left, right),
+            Divergence::Metadata { key, left, right } => write!(f, "metadata '{}' {:?} vs {:?}", key, left, right),
+        }
+    }
+}
+
+/// Lists how `right` differs from `left` (empty if they agree).
+pub fn diff_observations(left: &Observation, right: &Observation, options: &DiffOptions) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    match (&left.outcome, &right.outcome) {
+        (Ok(left), Ok(right)) => {
+            if strip_volatile(left) != strip_volatile(right) {
+                divergences.push(Divergence::Response { left: left.clone(), right: right.clone() });
+            }
+        }
+        (Err(left), Err(right)) => {
+            let (left, right) = (error_code(left), error_code(right));
+            if left != right {
+                divergences.push(Divergence::Error { left, right });
+            }
+        }
+        (left, right) => divergences.push(Divergence::Outcome { left: describe(left), right: describe(right) }),
+    }
+
+    let keys: BTreeSet<&String> = left.server_metadata.keys().chain(right.server_metadata.keys()).collect();
+    for key in keys {
+        if options.ignored_metadata.contains(key) {
+            continue;
+        }
+        let (left, right) = (left.server_metadata.get(key), right.server_metadata.get(key));
+        if left != right {
+            divergences.push(Divergence::Metadata { key: key.clone(), left: left.cloned(), right: right.cloned() });
+        }
+    }
+    divergences
+}
+
+/// The contract error code, else the error's variant in upper case.
+fn error_code(error: &Error) -> String {
+    if let Some(kind) = error_kind(error) {
+        return kind.code().to_string();
+    }
+    match error {
+        Error::Validation { .. } => "VALIDATION".to_string(),
+        Error::Protocol(_) => "PROTOCOL".to_string(),
+        // The variant name leads the Debug output: `Io(..)` → `IO`
+        other => {
+            let debug = format!("{:?}", other);
+            let variant: String = debug.chars().take_while(char::is_ascii_alphanumeric).collect();
+            variant.to_ascii_uppercase()
+        }
+    }
+}
+
+fn describe(outcome: &Result<Value>) -> String {
+    match outcome {
+        Ok(reply) => format!("reply {}", reply),
+        Err(e) => format!("{} ({})", error_code(e), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::unavailable;
+    use serde_json::json;
+
+    fn observation(outcome: Result<Value>, metadata: &[(&str, &str)]) -> Observation {
+        Observation {
+            outcome,
+            server_metadata: metadata.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            attempts: 1,
+            latency: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_equal_observations_agree() {
+        let left = observation(Ok(json!({"message": "hi", "count": 1})), &[("instance-id", "a1"), ("server-version", "0.1.0")]);
+        let right = observation(Ok(json!({"message": "hi", "count": 7})), &[("instance-id", "b2"), ("server-version", "0.1.0")]);
+
+        assert!(diff_observations(&left, &right, &DiffOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_divergences_are_reported() {
+        let options = DiffOptions::default();
+
+        let left = observation(Ok(json!({"message": "hi"})), &[("server-version", "0.1.0")]);
+        let right = observation(Ok(json!({"message": "[v2] hi"})), &[("server-version", "0.2.0"), ("variant", "v2")]);
+        let divergences = diff_observations(&left, &right, &options);
+        assert_eq!(divergences.len(), 3);
+        assert!(matches!(divergences[0], Divergence::Response { .. }));
+        assert_eq!(divergences[2], Divergence::Metadata { key: "variant".into(), left: None, right: Some("v2".into()) });
+        assert_eq!(diff_observations(&left, &right, &options.with_ignored_metadata("variant")).len(), 2);
+
+        let failed = observation(Err(unavailable("connection refused")), &[]);
+        let refused = observation(Err(Error::Validation { message: "too long".into() }), &[]);
+        let divergences = diff_observations(&failed, &refused, &DiffOptions::default());
+        assert_eq!(divergences, vec![Divergence::Error { left: "UNAVAILABLE".into(), right: "VALIDATION".into() }]);
+        let broken = observation(Err(Error::Protocol("connection reset".into())), &[]);
+        let divergences = diff_observations(&refused, &broken, &DiffOptions::default());
+        assert_eq!(divergences, vec![Divergence::Error { left: "VALIDATION".into(), right: "PROTOCOL".into() }]);
+        let also_refused = observation(Err(Error::Validation { message: "empty".into() }), &[]);
+        assert!(diff_observations(&refused, &also_refused, &DiffOptions::default()).is_empty());
+        assert!(matches!(diff_observations(&left, &failed, &DiffOptions::default())[0], Divergence::Outcome { .. }));
+    }
+}
@@ -0,0 +1,508 @@
+//! Application-Layer Payload Encryption (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! TLS ends at the first proxy, and a Direct call never sees it at all.
+//! With a key shared between two modules, the client gateway seals each
+//! message before any transport sees it and the server opens it before
+//! its service stack does - whatever carries the bytes in between:
+//!
+//! ```text
+//! client: echo("ping")
+//!     ↓ EncryptingEchoService (key "k1")
+//! "enc:v1:k1:<base64(nonce ‖ XChaCha20-Poly1305(ping))>"
+//!     ↓ Direct / gRPC / JSON / batches - opaque text
+//! DecryptingEchoService (keyring) → opens with "k1"
+//!     ↓ "ping" → service stack → "ping"
+//! sealed with "k1" → client opens the reply
+//! ```
+//!
+//! The envelope names its key, so a server holds one key per client
+//! module ([`PayloadKeyring`]) and each client only its own
+//! ([`GatewayOptions::payload_key`](crate::GatewayOptions::payload_key)).
+//! Requests and replies are bound to their direction (associated data),
+//! and a reply to the nonce of its request, so neither a request nor the
+//! reply to another request can be passed off as the reply. A server
+//! with keys refuses plain messages; one without answers them in plain
+//! text. Tampering fails with [`echo_contract::integrity_error`].
+//!
+//! Encrypted: the messages of `echo`, `echo_bytes`, `echo_reliable`,
+//! `echo_with_session` and `schedule_echo`. Not encrypted: file uploads,
+//! history queries and exports, server info.
+//!
+//! # Rust Learning Note
+//!
+//! [`PayloadKey`] keeps its key bytes in a [`Secret`], whose `Debug`
+//! prints `Secret(<redacted>)`: the key can derive `Debug` without putting
+//! key material into every log line that formats the options. The cipher
+//! is set up per message (XChaCha20's key setup is a copy), so nothing
+//! but the `Secret` holds the key for long, and it is zeroized on drop.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use base64::Engine;
+use bytes::Bytes;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hsu_common::{Error, Result};
+use echo_contract::{
+    integrity_error, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, Secret, ServerInfo, SessionEcho,
+};
+
+/// Prefix of sealed messages (`enc:v1:<key-id>:<base64>`).
+pub const ENVELOPE_PREFIX: &str = "enc:v1:";
+
+/// Associated data of sealed requests.
+const REQUEST: &[u8] = b"echo-request";
+/// Associated data of sealed replies, followed by the request's nonce.
+const REPLY: &[u8] = b"echo-reply";
+
+/// Length of an XChaCha20 nonce.
+const NONCE_LEN: usize = 24;
+
+/// A 256-bit XChaCha20-Poly1305 key shared by two modules.
+#[derive(Debug, Clone)]
+pub struct PayloadKey {
+    id: String,
+    key: Secret<[u8; 32]>,
+}
+
+impl PayloadKey {
+    /// Creates key `id` from its 32 bytes.
+    pub fn new(id: impl Into<String>, key: [u8; 32]) -> Result<Self> {
+        Self::from_secret(id, Secret::new(key))
+    }
+
+    /// [`new`](Self::new), with the key already in its `Secret`.
+    fn from_secret(id: impl Into<String>, key: Secret<[u8; 32]>) -> Result<Self> {
+        let id = id.into();
+        if id.is_empty() || id.contains(':') {
+            return Err(Error::Validation {
+                message: format!("Invalid payload key ID '{}': must be non-empty, without ':'", id),
+            });
+        }
+        Ok(Self { id, key })
+    }
+
+    /// Parses `ID:HEX`, the 32-byte key as 64 hex digits.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::Validation {
+            message: format!("Invalid payload key (expected ID:<64 hex digits>): {}", reason),
+        };
+        let (id, hex) = spec.split_once(':').ok_or_else(|| invalid("no ':'"))?;
+        if hex.len() != 64 {
+            return Err(invalid(&format!("key has {} hex digits", hex.len())));
+        }
+        let mut key = Secret::new([0u8; 32]);
+        for (byte, pair) in key.expose_mut().iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid("key isn't hex"))?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid("key isn't hex"))?;
+        }
+        Self::from_secret(id, key)
+    }
+
+    /// Returns the key ID, sent in every envelope.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The cipher for one message.
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(self.key.expose()))
+    }
+
+    /// Seals `plaintext` with associated data `aad` into an envelope,
+    /// returning it and the nonce it was sealed with.
+    fn seal(&self, plaintext: &[u8], aad: &[u8]) -> (String, XNonce) {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher()
+            .encrypt(&nonce, Payload { msg: plaintext, aad })
+            .expect("XChaCha20-Poly1305 encrypts messages of any size");
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        (format!("{}{}:{}", ENVELOPE_PREFIX, self.id, BASE64.encode(sealed)), nonce)
+    }
+
+    /// Opens the base64 body of an envelope sealed with `aad`, returning
+    /// the plaintext and the nonce it was sealed with.
+    fn open(&self, body: &str, aad: &[u8]) -> Result<(Vec<u8>, XNonce)> {
+        let sealed = BASE64.decode(body).map_err(|e| integrity_error(format!("envelope isn't base64: {}", e)))?;
+        if sealed.len() < NONCE_LEN {
+            return Err(integrity_error("envelope is too short"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = *XNonce::from_slice(nonce);
+        let plaintext = self.cipher()
+            .decrypt(&nonce, Payload { msg: ciphertext, aad })
+            .map_err(|_| integrity_error(format!("envelope sealed with key '{}' failed authentication", self.id)))?;
+        Ok((plaintext, nonce))
+    }
+}
+
+/// Associated data of the reply to the request sealed with `request_nonce`.
+fn reply_aad(request_nonce: &XNonce) -> Vec<u8> {
+    [REPLY, request_nonce.as_slice()].concat()
+}
+
+/// Seals the reply to a request a [`PayloadKeyring`] opened.
+struct ReplySealer<'a> {
+    key: &'a PayloadKey,
+    request_nonce: XNonce,
+}
+
+impl ReplySealer<'_> {
+    fn seal(&self, reply: &[u8]) -> String {
+        self.key.seal(reply, &reply_aad(&self.request_nonce)).0
+    }
+}
+
+/// Splits an envelope into key ID and base64 body (`None` for plain text).
+fn envelope(text: &str) -> Option<(&str, &str)> {
+    text.strip_prefix(ENVELOPE_PREFIX)?.split_once(':')
+}
+
+/// Keys a server accepts, by ID (one per client module).
+#[derive(Debug, Clone, Default)]
+pub struct PayloadKeyring {
+    keys: HashMap<String, PayloadKey>,
+}
+
+impl PayloadKeyring {
+    /// Creates an empty keyring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `key`, replacing one with the same ID.
+    pub fn with_key(mut self, key: PayloadKey) -> Self {
+        self.keys.insert(key.id.clone(), key);
+        self
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the keyring holds no key.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Opens `text` if it is an envelope, returning the plaintext and
+    /// what seals the reply; `None` for plain text, which only a keyring
+    /// without keys accepts.
+    fn open(&self, text: &str) -> Result<Option<(Vec<u8>, ReplySealer<'_>)>> {
+        let Some((id, body)) = envelope(text) else {
+            if !self.is_empty() {
+                return Err(Error::Validation {
+                    message: "Plain message refused: this module only accepts sealed messages".to_string(),
+                });
+            }
+            return Ok(None);
+        };
+        let key = self.keys.get(id).ok_or_else(|| Error::Validation {
+            message: format!("Message sealed with unknown payload key '{}'", id),
+        })?;
+        let (plaintext, request_nonce) = key.open(body, REQUEST)?;
+        Ok(Some((plaintext, ReplySealer { key, request_nonce })))
+    }
+}
+
+/// Decodes an opened message as text.
+fn utf8(plaintext: Vec<u8>) -> Result<Arc<str>> {
+    String::from_utf8(plaintext)
+        .map(Arc::from)
+        .map_err(|_| integrity_error("sealed message isn't UTF-8"))
+}
+
+/// Client-side decorator sealing messages with the key shared with the
+/// target module, and opening the replies.
+pub struct EncryptingEchoService {
+    inner: Arc<dyn EchoService>,
+    key: Arc<PayloadKey>,
+}
+
+impl EncryptingEchoService {
+    /// Wraps `inner`, sealing with `key`.
+    pub fn new(inner: Arc<dyn EchoService>, key: Arc<PayloadKey>) -> Self {
+        Self { inner, key }
+    }
+
+    /// Seals a request, returning it and its nonce (the reply is bound to it).
+    fn seal(&self, message: &[u8]) -> (Arc<str>, XNonce) {
+        let (sealed, nonce) = self.key.seal(message, REQUEST);
+        (sealed.into(), nonce)
+    }
+
+    /// Opens the reply to the request sealed with `request_nonce`.
+    fn open(&self, reply: &str, request_nonce: &XNonce) -> Result<Vec<u8>> {
+        match envelope(reply) {
+            Some((id, body)) if id == self.key.id => Ok(self.key.open(body, &reply_aad(request_nonce))?.0),
+            Some((id, _)) => Err(integrity_error(format!("reply sealed with key '{}', expected '{}'", id, self.key.id))),
+            None => Err(integrity_error("reply isn't sealed (does the server hold the payload key?)")),
+        }
+    }
+}
+
+#[async_trait]
+impl EchoService for EncryptingEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let (sealed, nonce) = self.seal(message.as_bytes());
+        let reply = self.inner.echo(sealed).await?;
+        utf8(self.open(&reply, &nonce)?)
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let (sealed, nonce) = self.seal(&payload);
+        let reply = self.inner.echo_bytes(Bytes::from(sealed.to_string())).await?;
+        let reply = std::str::from_utf8(&reply).map_err(|_| integrity_error("reply isn't sealed"))?;
+        Ok(self.open(reply, &nonce)?.into())
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let (sealed, nonce) = self.seal(message.as_bytes());
+        let ack = self.inner.echo_reliable(sealed, idempotency_key).await?;
+        Ok(EchoAck { message: utf8(self.open(&ack.message, &nonce)?)?, ..ack })
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let (sealed, nonce) = self.seal(message.as_bytes());
+        let echo = self.inner.echo_with_session(session_id, sealed).await?;
+        Ok(SessionEcho { message: utf8(self.open(&echo.message, &nonce)?)?, ..echo })
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(self.seal(message.as_bytes()).0, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+/// Server-side decorator opening sealed messages before the service
+/// sees them, and sealing the replies with the same key.
+///
+/// Plain messages are refused, unless the keyring is empty: then they
+/// pass through in plain text.
+pub struct DecryptingEchoService {
+    inner: Arc<dyn EchoService>,
+    keyring: Arc<PayloadKeyring>,
+}
+
+impl DecryptingEchoService {
+    /// Wraps `inner`, opening messages sealed with a key of `keyring`.
+    pub fn new(inner: Arc<dyn EchoService>, keyring: Arc<PayloadKeyring>) -> Self {
+        Self { inner, keyring }
+    }
+}
+
+#[async_trait]
+impl EchoService for DecryptingEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        match self.keyring.open(&message)? {
+            Some((plaintext, sealer)) => {
+                let reply = self.inner.echo(utf8(plaintext)?).await?;
+                Ok(sealer.seal(reply.as_bytes()).into())
+            }
+            None => self.inner.echo(message).await,
+        }
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        // Bytes that aren't UTF-8 are no envelope: plain
+        match self.keyring.open(std::str::from_utf8(&payload).unwrap_or_default())? {
+            Some((plaintext, sealer)) => {
+                let reply = self.inner.echo_bytes(plaintext.into()).await?;
+                Ok(sealer.seal(&reply).into())
+            }
+            None => self.inner.echo_bytes(payload).await,
+        }
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        match self.keyring.open(&message)? {
+            Some((plaintext, sealer)) => {
+                let ack = self.inner.echo_reliable(utf8(plaintext)?, idempotency_key).await?;
+                Ok(EchoAck { message: sealer.seal(ack.message.as_bytes()).into(), ..ack })
+            }
+            None => self.inner.echo_reliable(message, idempotency_key).await,
+        }
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        match self.keyring.open(&message)? {
+            Some((plaintext, sealer)) => {
+                let echo = self.inner.echo_with_session(session_id, utf8(plaintext)?).await?;
+                Ok(SessionEcho { message: sealer.seal(echo.message.as_bytes()).into(), ..echo })
+            }
+            None => self.inner.echo_with_session(session_id, message).await,
+        }
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        let message = match self.keyring.open(&message)? {
+            Some((plaintext, _)) => utf8(plaintext)?,
+            None => message,
+        };
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Echoes, remembering the last message it saw.
+    #[derive(Default)]
+    struct MockService {
+        seen: Mutex<Option<Arc<str>>>,
+    }
+
+    #[async_trait]
+    impl EchoService for MockService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            *self.seen.lock().unwrap() = Some(message.clone());
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    fn key(id: &str, byte: u8) -> PayloadKey {
+        PayloadKey::new(id, [byte; 32]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_hides_the_message_in_transit() {
+        let server = Arc::new(MockService::default());
+        let keyring = Arc::new(PayloadKeyring::new().with_key(key("client-a", 1)).with_key(key("client-b", 2)));
+        let handler = Arc::new(DecryptingEchoService::new(server.clone(), keyring));
+        let client = EncryptingEchoService::new(handler, Arc::new(key("client-b", 2)));
+
+        assert_eq!(&*client.echo("secret".into()).await.unwrap(), "secret");
+        assert_eq!(server.seen.lock().unwrap().as_deref(), Some("secret"));
+        assert_eq!(&client.echo_bytes(Bytes::from_static(b"\x00\xff")).await.unwrap()[..], b"\x00\xff");
+        assert_eq!(&*client.echo_reliable("again".into(), "k".into()).await.unwrap().message, "again");
+
+        // Plain callers still get plain replies
+        let plain = DecryptingEchoService::new(server.clone(), Arc::new(PayloadKeyring::new()));
+        assert_eq!(&*plain.echo("hello".into()).await.unwrap(), "hello");
+
+        // Reflected back unopened, the sealed request is no valid reply
+        let reflected = EncryptingEchoService::new(server.clone(), Arc::new(key("client-b", 2)));
+        let error = reflected.echo("secret".into()).await.unwrap_err();
+        assert!(echo_contract::is_integrity_error(&error), "{}", error);
+        assert!(server.seen.lock().unwrap().as_deref().unwrap().starts_with("enc:v1:client-b:"));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_or_unknown_keys_are_rejected() {
+        let server = Arc::new(MockService::default());
+        let keyring = Arc::new(PayloadKeyring::new().with_key(key("client-a", 1)));
+        let handler: Arc<dyn EchoService> = Arc::new(DecryptingEchoService::new(server, keyring));
+
+        // Same ID, different key material
+        let forged = EncryptingEchoService::new(handler.clone(), Arc::new(key("client-a", 9)));
+        assert!(echo_contract::is_integrity_error(&forged.echo("x".into()).await.unwrap_err()));
+
+        let unknown = EncryptingEchoService::new(handler.clone(), Arc::new(key("client-z", 1)));
+        assert!(matches!(unknown.echo("x".into()).await.unwrap_err(), Error::Validation { .. }));
+
+        // A server holding keys doesn't take plain text
+        assert!(matches!(handler.echo("x".into()).await.unwrap_err(), Error::Validation { .. }));
+        let binary = handler.echo_bytes(Bytes::from_static(b"\xff")).await.unwrap_err();
+        assert!(matches!(binary, Error::Validation { .. }));
+    }
+
+    #[test]
+    fn test_replies_are_bound_to_their_request() {
+        let keyring = PayloadKeyring::new().with_key(key("client-a", 1));
+        let client = EncryptingEchoService::new(Arc::new(MockService::default()), Arc::new(key("client-a", 1)));
+        let (first, first_nonce) = client.seal(b"one");
+        let (second, _) = client.seal(b"two");
+
+        // The reply to the second request doesn't pass for the first's
+        let (_, sealer) = keyring.open(&second).unwrap().unwrap();
+        let error = client.open(&sealer.seal(b"two"), &first_nonce).unwrap_err();
+        assert!(echo_contract::is_integrity_error(&error));
+
+        let (_, sealer) = keyring.open(&first).unwrap().unwrap();
+        assert_eq!(client.open(&sealer.seal(b"one"), &first_nonce).unwrap(), b"one");
+    }
+
+    #[test]
+    fn test_parse_key_spec() {
+        let key = PayloadKey::parse(&format!("client-a:{}", "0f".repeat(32))).unwrap();
+        assert_eq!(key.id(), "client-a");
+        assert!(!format!("{:?}", key).contains("0f"));
+
+        assert!(PayloadKey::parse("client-a").is_err());
+        assert!(PayloadKey::parse("client-a:0f0f").is_err());
+        assert!(PayloadKey::parse(&format!("client-a:{}", "zz".repeat(32))).is_err());
+        assert!(PayloadKey::parse(&format!(":{}", "0f".repeat(32))).is_err());
+    }
+}
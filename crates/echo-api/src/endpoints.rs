@@ -0,0 +1,128 @@
+//! Bound Endpoint Reporting (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! With port 0 (or several listen addresses) the configured address isn't
+//! the address clients should use - only the protocol server knows the
+//! port it actually bound. The handlers registrar reads it back and
+//! records it here:
+//!
+//! ```text
+//! ProtocolServer (bound [::]:0 → port 41234)
+//!     ↓ server.port()
+//! EchoHandlersRegistrar
+//!     ↓ record
+//! BoundEndpoints  ──→ EchoServerModule::bound_endpoints(), tests
+//! ```
+//!
+//! Tests bind port 0 and use [`BoundEndpoints::wait_for`] to learn the
+//! port, so parallel test runs never fight over a fixed port.
+
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+use hsu_common::Protocol;
+use tokio::sync::Notify;
+
+/// A protocol server endpoint the echo service is reachable on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundEndpoint {
+    /// Protocol served.
+    pub protocol: Protocol,
+    /// Actually bound port (never 0 once the server is bound).
+    pub port: u16,
+}
+
+/// Registry of bound endpoints.
+#[derive(Default)]
+pub struct BoundEndpoints {
+    endpoints: RwLock<Vec<BoundEndpoint>>,
+    recorded: Notify,
+}
+
+impl BoundEndpoints {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide registry.
+    pub fn global() -> Arc<BoundEndpoints> {
+        static GLOBAL: OnceLock<Arc<BoundEndpoints>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(BoundEndpoints::new())).clone()
+    }
+
+    /// Records an endpoint (duplicates are ignored).
+    pub fn record(&self, endpoint: BoundEndpoint) {
+        let mut endpoints = self.endpoints.write().unwrap();
+        if !endpoints.contains(&endpoint) {
+            endpoints.push(endpoint);
+        }
+        drop(endpoints);
+        self.recorded.notify_waiters();
+    }
+
+    /// Waits until a port is recorded for `protocol`.
+    ///
+    /// Returns `None` if none is recorded within `timeout`.
+    pub async fn wait_for(&self, protocol: Protocol, timeout: Duration) -> Option<u16> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                // Register interest before checking, so no record is missed
+                let recorded = self.recorded.notified();
+                if let Some(port) = self.ports(&protocol).first() {
+                    return *port;
+                }
+                recorded.await;
+            }
+        })
+        .await
+        .ok()
+    }
+
+    /// Returns all recorded endpoints.
+    pub fn list(&self) -> Vec<BoundEndpoint> {
+        self.endpoints.read().unwrap().clone()
+    }
+
+    /// Returns the ports bound for `protocol`.
+    pub fn ports(&self, protocol: &Protocol) -> Vec<u16> {
+        self.endpoints
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|e| &e.protocol == protocol)
+            .map(|e| e.port)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_each_endpoint_once() {
+        let endpoints = BoundEndpoints::new();
+        endpoints.record(BoundEndpoint { protocol: Protocol::Grpc, port: 41234 });
+        endpoints.record(BoundEndpoint { protocol: Protocol::Grpc, port: 41235 });
+        endpoints.record(BoundEndpoint { protocol: Protocol::Grpc, port: 41234 });
+
+        assert_eq!(endpoints.list().len(), 2);
+        assert_eq!(endpoints.ports(&Protocol::Grpc), vec![41234, 41235]);
+        assert!(endpoints.ports(&Protocol::Http).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_port() {
+        let endpoints = Arc::new(BoundEndpoints::new());
+        assert_eq!(endpoints.wait_for(Protocol::Grpc, Duration::from_millis(10)).await, None);
+
+        let recorder = endpoints.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            recorder.record(BoundEndpoint { protocol: Protocol::Grpc, port: 41234 });
+        });
+
+        assert_eq!(endpoints.wait_for(Protocol::Grpc, Duration::from_secs(5)).await, Some(41234));
+    }
+}
@@ -0,0 +1,267 @@
+//! Echo Event Bus (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! ```text
+//! gRPC handler / Direct caller
+//!     ↓ echo()
+//! EventEmittingEchoService ──publish──→ EchoEventBus (tokio broadcast)
+//!     ↓                                    ├─→ subscribe() (Direct)
+//! EchoServiceImpl                          └─→ EchoEventsGrpcHandler (gRPC stream)
+//! ```
+//!
+//! The bus implements `EchoEvents` itself, so the Direct path hands it
+//! out as-is and the gRPC path streams from it.
+//!
+//! Decorators keep the bus alive for as long as the service, so its
+//! subscriptions don't end by themselves: the server module calls
+//! [`EchoEventBus::close`] when it stops, which ends every stream (and so
+//! every gRPC `Subscribe` call) handed out until then.
+//!
+//! # Rust Learning Note
+//!
+//! A `broadcast` channel keeps the last `capacity` events for every
+//! receiver. Publishing never blocks: a receiver that falls behind gets
+//! `RecvError::Lagged` and continues from the oldest retained event.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::Result;
+use echo_contract::{
+    ByteStream, EchoAck, EchoEvent, EchoEventStream, EchoEvents, EchoMethod, EchoSchedule, EchoService, FileDigest,
+    HistoryExportFormat, HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo,
+    SessionEcho,
+};
+use futures::StreamExt;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+use tracing::warn;
+
+/// In-process publisher of echo events.
+pub struct EchoEventBus {
+    sender: broadcast::Sender<EchoEvent>,
+    /// Set by `close()`, cleared by `open()`; subscriptions watch it.
+    closed: watch::Sender<bool>,
+}
+
+impl EchoEventBus {
+    /// Creates a bus retaining up to `capacity` events per slow subscriber.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        let (closed, _) = watch::channel(false);
+        Self { sender, closed }
+    }
+
+    /// Publishes `event` to all current subscribers.
+    pub fn publish(&self, event: EchoEvent) {
+        // No subscribers is not an error
+        let _ = self.sender.send(event);
+    }
+
+    /// Returns the number of current subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Ends every subscription; new ones end at once until [`open`](Self::open).
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    /// Accepts subscriptions again after [`close`](Self::close) (module restart).
+    pub fn open(&self) {
+        self.closed.send_replace(false);
+    }
+}
+
+impl Default for EchoEventBus {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+#[async_trait]
+impl EchoEvents for EchoEventBus {
+    async fn subscribe(&self) -> Result<EchoEventStream> {
+        let receiver = self.sender.subscribe();
+        let mut closed = self.closed.subscribe();
+        let events = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((Ok(event), receiver)),
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("[EchoEventBus] Subscriber lagged, skipped {} events", missed);
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Box::pin(events.take_until(async move {
+            let _ = closed.wait_for(|closed| *closed).await;
+        })))
+    }
+}
+
+/// Decorator that publishes an event for every call to the inner service.
+pub struct EventEmittingEchoService {
+    inner: Arc<dyn EchoService>,
+    bus: Arc<EchoEventBus>,
+}
+
+impl EventEmittingEchoService {
+    /// Wraps `inner`, publishing to `bus`.
+    pub fn new(inner: Arc<dyn EchoService>, bus: Arc<EchoEventBus>) -> Self {
+        Self { inner, bus }
+    }
+
+    fn emit<T>(&self, method: EchoMethod, request_bytes: usize, result: &Result<T>) {
+        self.bus.publish(EchoEvent {
+            method,
+            request_bytes: request_bytes as u64,
+            success: result.is_ok(),
+            at: SystemTime::now(),
+        });
+    }
+}
+
+#[async_trait]
+impl EchoService for EventEmittingEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let size = message.len();
+        let result = self.inner.echo(message).await;
+        self.emit(EchoMethod::Echo, size, &result);
+        result
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let size = payload.len();
+        let result = self.inner.echo_bytes(payload).await;
+        self.emit(EchoMethod::EchoBytes, size, &result);
+        result
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let size = message.len();
+        let result = self.inner.echo_reliable(message, idempotency_key).await;
+        self.emit(EchoMethod::EchoReliable, size, &result);
+        result
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let result = self.inner.echo_file(chunks).await;
+        let size = result.as_ref().map_or(0, |digest| digest.byte_count as usize);
+        self.emit(EchoMethod::EchoFile, size, &result);
+        result
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let size = message.len();
+        let result = self.inner.echo_with_session(session_id, message).await;
+        self.emit(EchoMethod::EchoWithSession, size, &result);
+        result
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        // Not an echo yet: each run is published when it happens
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        // Not an echo call: imported entries publish no events
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockService;
+
+    #[async_trait]
+    impl EchoService for MockService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            Ok(message)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_call_is_published() {
+        let bus = Arc::new(EchoEventBus::default());
+        let service = EventEmittingEchoService::new(Arc::new(MockService), bus.clone());
+        let mut events = bus.subscribe().await.unwrap();
+
+        service.echo("Hello".into()).await.unwrap();
+        service.echo_bytes(Bytes::from_static(b"xy")).await.unwrap_err();
+
+        let first = events.next().await.unwrap().unwrap();
+        assert_eq!(first.method, EchoMethod::Echo);
+        assert_eq!(first.request_bytes, 5);
+        assert!(first.success);
+
+        let second = events.next().await.unwrap().unwrap();
+        assert_eq!(second.method, EchoMethod::EchoBytes);
+        assert!(!second.success);
+    }
+
+    #[tokio::test]
+    async fn test_lagging_subscriber_skips_events() {
+        let bus = EchoEventBus::new(2);
+        let mut events = bus.subscribe().await.unwrap();
+
+        for size in 0..5 {
+            bus.publish(EchoEvent {
+                method: EchoMethod::Echo,
+                request_bytes: size,
+                success: true,
+                at: SystemTime::now(),
+            });
+        }
+
+        // Only the two newest events were retained
+        assert_eq!(events.next().await.unwrap().unwrap().request_bytes, 3);
+        assert_eq!(events.next().await.unwrap().unwrap().request_bytes, 4);
+    }
+
+    #[tokio::test]
+    async fn test_close_ends_subscriptions() {
+        let bus = EchoEventBus::default();
+        let mut events = bus.subscribe().await.unwrap();
+
+        bus.close();
+        assert!(events.next().await.is_none());
+        assert!(bus.subscribe().await.unwrap().next().await.is_none());
+
+        bus.open();
+        let mut events = bus.subscribe().await.unwrap();
+        bus.publish(EchoEvent {
+            method: EchoMethod::Echo,
+            request_bytes: 1,
+            success: true,
+            at: SystemTime::now(),
+        });
+        assert_eq!(events.next().await.unwrap().unwrap().request_bytes, 1);
+    }
+}
@@ -1,20 +1,560 @@
 //! Echo Service Gateways Implementation (Layer 3/5 Boundary)
 //!
 //! Reusable implementation of `EchoServiceGateways` trait.
+//!
+//! Without the `grpc` feature only Direct gateways are assembled (the
+//! gRPC factory is `None`), so Direct-only builds don't compile
+//! echo-api-grpc.
+//!
+//! `close()` releases the channel pool and ends event subscriptions
+//! handed out earlier; gateways dropped without it log what they abandon.
+//!
+//! With [`GatewayOptions::warm_standby`] the gateways connect and probe
+//! the standby transport as soon as they are created, and keep it warm
+//! until `close()` (see [`crate::warm_standby`]).
+//!
+//! With [`GatewayOptions::pipe`] a module that has no Direct handler in
+//! this process is still called "directly": over the stdio pipe of the
+//! supervised child running it (see [`crate::pipe`]).
+//!
+//! Code running outside the framework (the C bindings in
+//! `echo-client-ffi`) gets the same gateways from
+//! [`new_standalone_echo_service_gateways`]: without a `ServiceConnector`
+//! only calls to a configured or discovered address can be routed.
 
+#[cfg(feature = "grpc")]
+use std::collections::HashMap;
+#[cfg(feature = "grpc")]
+use std::sync::Mutex;
+use std::cell::Cell;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use async_trait::async_trait;
-use hsu_common::{ModuleID, ServiceID, Protocol, Result};
+use futures::StreamExt;
+use hsu_common::{Error, ModuleID, ServiceID, Protocol, Result};
 use hsu_module_api::{ServiceConnector, ServiceGatewayFactory, GatewayFactoryFuncs};
-use echo_contract::{EchoService, EchoServiceGateways, EchoServiceHandlers};
-use echo_api_grpc::EchoGrpcGateway;
-use tracing::debug;
+use echo_contract::{
+    collect_response_metadata, count_attempts, current_request_id, echo_module_id, protocol_field, CallInfo,
+    EchoEventStream, EchoEvents, EchoService, EchoServiceGateways, EchoServiceHandlers, EchoServiceId,
+    GatewayCapabilities, ProtocolCapabilities, ProtocolRace,
+};
+#[cfg(feature = "grpc")]
+use echo_api_grpc::{
+    ChannelPool, EchoEventsGrpcGateway, EchoGrpcGateway, EchoJsonGateway, ReconnectingGrpcGateway, SigningKey,
+};
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+use crate::batching::BatchingConfig;
+#[cfg(feature = "grpc")]
+use crate::batching::{BatchingEchoService, EchoBatchSender, EchoBatcher};
+use crate::capabilities::CapabilitiesRegistry;
+use crate::coalescing::{CoalescingEchoService, InFlightEchoes};
+use crate::deadline::DeadlineEchoService;
+#[cfg(feature = "encryption")]
+use crate::encryption::{EncryptingEchoService, PayloadKey};
+use crate::hedging::HedgingPolicy;
+#[cfg(feature = "grpc")]
+use crate::hedging::HedgingEchoService;
+use crate::latency_budget::{LatencyBudgetEchoService, LatencyBudgets};
+use crate::metrics::{SizeLabels, SizeMetrics, SizeMetricsEchoService};
+use crate::pipe::PipeChannel;
+#[cfg(feature = "grpc")]
+use crate::mirroring::{MirrorShadow, MirroringEchoService};
+#[cfg(feature = "grpc")]
+use crate::racing::RacingEchoService;
+#[cfg(feature = "grpc")]
+use crate::rediscovery::{DiscoveredAddress, RediscoveringEchoService};
+use crate::registry_backend::RegistryBackend;
+#[cfg(feature = "grpc")]
+use crate::tasks::spawn_tracked;
+use crate::validation::{ResponseValidator, ValidatingEchoService};
+#[cfg(feature = "grpc")]
+use crate::warm_standby::{FallbackEchoService, WarmStandby, WarmStandbyConfig};
+
+/// Wraps a client-side gateway with size instrumentation.
+fn instrument(service: Arc<dyn EchoService>, protocol: &'static str) -> Arc<dyn EchoService> {
+    let labels = SizeLabels { side: "client", protocol, service: EchoServiceId::Service.as_str() };
+    Arc::new(SizeMetricsEchoService::new(service, labels, SizeMetrics::global()))
+}
+
+/// Wraps a gRPC gateway with hedging, if enabled.
+#[cfg(feature = "grpc")]
+fn hedge(service: Arc<dyn EchoService>, policy: Option<&HedgingPolicy>) -> Arc<dyn EchoService> {
+    match policy {
+        Some(policy) => Arc::new(HedgingEchoService::new(service, policy.clone())),
+        None => service,
+    }
+}
+
+/// Seals a gateway's messages with `key`, if set.
+#[cfg(feature = "encryption")]
+fn encrypt(service: Arc<dyn EchoService>, key: Option<&Arc<PayloadKey>>) -> Arc<dyn EchoService> {
+    match key {
+        Some(key) => Arc::new(EncryptingEchoService::new(service, key.clone())),
+        None => service,
+    }
+}
+
+/// Checks a gateway's replies with `validators`, if any.
+fn validate(service: Arc<dyn EchoService>, validators: &[ResponseValidator]) -> Arc<dyn EchoService> {
+    if validators.is_empty() {
+        return service;
+    }
+    Arc::new(ValidatingEchoService::new(service, validators.to_vec()))
+}
+
+/// Times a gateway's calls against `budgets`, if any.
+fn budget(
+    service: Arc<dyn EchoService>,
+    budgets: &LatencyBudgets,
+    protocol_used: Protocol,
+    endpoint: &str,
+) -> Arc<dyn EchoService> {
+    if budgets.is_empty() {
+        return service;
+    }
+    Arc::new(LatencyBudgetEchoService::new(service, budgets.clone(), protocol_used, endpoint.to_string()))
+}
+
+tokio::task_local! {
+    /// The factory `ServiceGatewayFactory::new_service_gateway` called.
+    static PICKED: Cell<Option<Protocol>>;
+}
+
+/// Records that the `protocol` factory built the gateway being handed out.
+fn picked(protocol: Protocol) {
+    let _ = PICKED.try_with(|picked| picked.set(Some(protocol)));
+}
+
+/// The Direct and gRPC factories gateways to `module_id` are built with
+/// (also what [`gateway_capabilities`] reports on).
+fn service_factory_funcs(
+    module_id: &ModuleID,
+    options: &GatewayOptions,
+    #[cfg(feature = "grpc")] batchers: Batchers,
+    direct_handler: Option<Arc<dyn EchoService>>,
+) -> GatewayFactoryFuncs<dyn EchoService> {
+    let deadline = options.deadline;
+    #[cfg(feature = "grpc")]
+    let integrity = options.integrity;
+    #[cfg(feature = "grpc")]
+    let signing_key = options.signing_key.clone();
+    #[cfg(feature = "grpc")]
+    let hedging = options.hedging.clone();
+    #[cfg(feature = "grpc")]
+    let batching = options.batching.clone();
+    #[cfg(feature = "grpc")]
+    let registry_route = format!("registry:{}", module_id);
+    #[cfg(not(feature = "grpc"))]
+    let _ = module_id;
+
+    // An in-process handler wins over a child's pipe
+    let direct = match (direct_handler, &options.pipe) {
+        (Some(handler), _) => Some((handler, "direct")),
+        (None, Some(pipe)) => Some((pipe.service(), "pipe")),
+        (None, None) => None,
+    };
+
+    GatewayFactoryFuncs {
+        // Direct factory
+        direct: direct.map(|(handler, transport)| {
+            Box::new(move || {
+                debug!("Using {} handler", transport);
+                picked(Protocol::Direct);
+                let service = match deadline {
+                    Some(deadline) => Arc::new(DeadlineEchoService::new(handler.clone(), deadline)) as Arc<dyn EchoService>,
+                    None => handler.clone(),
+                };
+                Ok(instrument(service, transport))
+            }) as Box<dyn Fn() -> Result<Arc<dyn EchoService>> + Send + Sync>
+        }),
+        
+        // gRPC factory
+        #[cfg(feature = "grpc")]
+        grpc: Some(Box::new(move |channel| {
+            debug!("Creating gRPC gateway");
+            picked(Protocol::Grpc);
+            let client = echo_api_grpc::generated::echo_service_client::EchoServiceClient::new(channel);
+            let gateway = EchoGrpcGateway::from_client(client.clone())
+                .with_deadline(deadline)
+                .with_integrity(integrity)
+                .with_signing(signing_key.clone());
+            let service = instrument(hedge(Arc::new(gateway), hedging.as_ref()), "grpc");
+            // Each gateway gets the channel the registry resolved last, so
+            // the route's batches move over to it too
+            Ok(batch(service, batching.as_ref(), &batchers, &registry_route, true, || {
+                Arc::new(
+                    EchoGrpcGateway::from_client(client)
+                        .with_deadline(deadline)
+                        .with_integrity(integrity)
+                        .with_signing(signing_key.clone()),
+                )
+            }))
+        }) as Box<dyn Fn(tonic::transport::Channel) -> Result<Arc<dyn EchoService>> + Send + Sync>),
+        #[cfg(not(feature = "grpc"))]
+        grpc: None,
+        
+        // HTTP factory
+        http: None,
+    }
+}
+
+/// The factories event subscriptions (the server-streaming contract) are
+/// built with, per protocol - used by the registry path and, for gRPC, on
+/// a configured address too, so both hand out the same gateway.
+fn events_factory_funcs(direct_events: Option<Arc<dyn EchoEvents>>) -> GatewayFactoryFuncs<dyn EchoEvents> {
+    GatewayFactoryFuncs {
+        // Direct: subscribe to the server's in-process bus
+        direct: direct_events.map(|events| {
+            Box::new(move || Ok(events.clone()))
+                as Box<dyn Fn() -> Result<Arc<dyn EchoEvents>> + Send + Sync>
+        }),
+        
+        // gRPC: server-streaming subscription
+        #[cfg(feature = "grpc")]
+        grpc: Some(Box::new(|channel| {
+            Ok(Arc::new(EchoEventsGrpcGateway::new(channel)) as Arc<dyn EchoEvents>)
+        }) as Box<dyn Fn(tonic::transport::Channel) -> Result<Arc<dyn EchoEvents>> + Send + Sync>),
+        #[cfg(not(feature = "grpc"))]
+        grpc: None,
+        
+        // JSON transcoding carries unary calls only
+        http: None,
+    }
+}
+
+/// What gateways to `module_id` built with `options` can do, per
+/// protocol; `direct_handler` and `direct_events` are the module's service
+/// and event bus if it runs in this process.
+///
+/// Assembled from the factories [`EchoServiceGateways::get_service`] and
+/// [`get_events`](EchoServiceGateways::get_events) use and what their
+/// adapters support, so a process without gateways of its own (the CLI's
+/// `check`) can ask too. A protocol streams only if it carries events too.
+pub fn gateway_capabilities(
+    module_id: ModuleID,
+    options: &GatewayOptions,
+    direct_handler: Option<Arc<dyn EchoService>>,
+    direct_events: Option<Arc<dyn EchoEvents>>,
+) -> GatewayCapabilities {
+    let direct_handler_missing = direct_handler.is_none();
+    let funcs = service_factory_funcs(
+        &module_id,
+        options,
+        #[cfg(feature = "grpc")]
+        Batchers::default(),
+        direct_handler,
+    );
+    
+    let direct = if funcs.direct.is_some() {
+        let over_pipe = direct_handler_missing && options.pipe.is_some();
+        ProtocolCapabilities {
+            protocol: Protocol::Direct,
+            wired: true,
+            // Events don't cross the pipe
+            streaming: !over_pipe,
+            compression: None,
+            auth: None,
+            encrypted: false,
+            note: over_pipe.then(|| "over the stdio pipe of a supervised child".to_string()),
+        }
+    } else {
+        ProtocolCapabilities::missing(Protocol::Direct, format!("module '{}' isn't in this process", module_id))
+    };
+    #[cfg(feature = "grpc")]
+    let grpc = match funcs.grpc {
+        Some(_) => EchoGrpcGateway::capabilities(options.signing_key.is_some()),
+        None => ProtocolCapabilities::missing(Protocol::Grpc, "no gRPC gateway factory"),
+    };
+    #[cfg(not(feature = "grpc"))]
+    let grpc = ProtocolCapabilities::missing(Protocol::Grpc, "echo-api built without the grpc feature");
+    let http = match funcs.http {
+        Some(_) => ProtocolCapabilities {
+            protocol: Protocol::Http,
+            wired: true,
+            streaming: false,
+            compression: None,
+            auth: None,
+            encrypted: false,
+            note: None,
+        },
+        // JSON/HTTP can still carry Auto calls by winning a protocol race
+        None if cfg!(feature = "grpc") && options.protocol_racing => ProtocolCapabilities::missing(
+            Protocol::Http,
+            "no HTTP gateway factory (Auto may pick JSON/HTTP by protocol racing)",
+        ),
+        None => ProtocolCapabilities::missing(Protocol::Http, "no HTTP gateway factory"),
+    };
+    
+    // Payloads are sealed before any transport sees them
+    #[cfg(feature = "encryption")]
+    let encrypted = options.payload_key.is_some();
+    #[cfg(not(feature = "encryption"))]
+    let encrypted = false;
+    let events = events_factory_funcs(direct_events);
+    let carries_events = |protocol| match protocol {
+        Protocol::Direct => events.direct.is_some(),
+        Protocol::Grpc => events.grpc.is_some(),
+        _ => events.http.is_some(),
+    };
+    let protocols = [direct, grpc, http]
+        .into_iter()
+        .map(|entry| ProtocolCapabilities {
+            encrypted: entry.wired && encrypted,
+            streaming: entry.streaming && carries_events(entry.protocol),
+            ..entry
+        })
+        .collect();
+    // The standby's state is known only once the gateways connect it
+    GatewayCapabilities { module_id, service_id: EchoServiceId::Service.into(), protocols, standby: None }
+}
+
+/// Batchers by route, shared by all gateways handed out.
+#[cfg(feature = "grpc")]
+type Batchers = Arc<Mutex<HashMap<String, Arc<EchoBatcher>>>>;
+
+/// Sends a gRPC gateway's echo calls through the batcher of `route`, if
+/// batching is enabled (`sender` creates the batcher on first use, and
+/// with `replace_sender` replaces the sender of an existing one).
+#[cfg(feature = "grpc")]
+fn batch(
+    service: Arc<dyn EchoService>,
+    config: Option<&BatchingConfig>,
+    batchers: &Batchers,
+    route: &str,
+    replace_sender: bool,
+    sender: impl FnOnce() -> Arc<dyn EchoBatchSender>,
+) -> Arc<dyn EchoService> {
+    let Some(config) = config else {
+        return service;
+    };
+    let mut batchers = batchers.lock().unwrap();
+    let batcher = match batchers.get(route) {
+        Some(batcher) => {
+            if replace_sender {
+                batcher.set_sender(sender());
+            }
+            batcher.clone()
+        }
+        None => {
+            let batcher = EchoBatcher::new(sender(), config.clone());
+            batchers.insert(route.to_string(), batcher.clone());
+            batcher
+        }
+    };
+    Arc::new(BatchingEchoService::new(service, batcher))
+}
+
+/// Events whose subscriptions end when the gateways are closed.
+struct ClosableEvents {
+    inner: Arc<dyn EchoEvents>,
+    closed: watch::Receiver<bool>,
+}
+
+#[async_trait]
+impl EchoEvents for ClosableEvents {
+    async fn subscribe(&self) -> Result<EchoEventStream> {
+        let events = self.inner.subscribe().await?;
+        let mut closed = self.closed.clone();
+        Ok(Box::pin(events.take_until(async move {
+            // Also ends if the gateways are gone
+            let _ = closed.wait_for(|closed| *closed).await;
+        })))
+    }
+}
+
+/// How gateways find the gRPC server when no address is configured.
+#[derive(Debug, Clone, Default)]
+pub enum Discovery {
+    /// Through the framework's service registry.
+    #[default]
+    Registry,
+    /// Browse mDNS, waiting at most `timeout` for an advertisement.
+    #[cfg(feature = "mdns")]
+    Mdns { timeout: Duration },
+    /// Ask a registry the framework doesn't speak (Consul, etcd).
+    Backend(Arc<dyn RegistryBackend>),
+}
+
+impl Discovery {
+    /// mDNS with the default browse timeout (5s).
+    #[cfg(feature = "mdns")]
+    pub fn mdns() -> Self {
+        Discovery::Mdns { timeout: Duration::from_secs(5) }
+    }
+}
+
+/// Where mirrored calls go (see [`crate::mirroring`]).
+#[cfg(feature = "grpc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MirrorTarget {
+    /// The JSON/HTTP adapter at `host:port` (a server with JSON transcoding).
+    Http(String),
+    /// The gRPC server at `host:port`.
+    Grpc(String),
+}
+
+/// Shadow traffic settings.
+#[cfg(feature = "grpc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MirrorConfig {
+    /// Where the copies go.
+    pub target: MirrorTarget,
+    /// Shadow calls running at once; further calls aren't mirrored.
+    pub max_in_flight: usize,
+}
+
+#[cfg(feature = "grpc")]
+impl MirrorConfig {
+    /// Mirrors to `target`, at most 64 shadow calls at once.
+    pub fn new(target: MirrorTarget) -> Self {
+        Self { target, max_in_flight: 64 }
+    }
+}
+
+/// Options applied to every gateway handed out.
+#[derive(Debug, Clone, Default)]
+pub struct GatewayOptions {
+    /// Per-call deadline (unbounded if `None`).
+    ///
+    /// Enforced on both Direct and gRPC calls; a missed deadline is
+    /// reported as [`echo_contract::deadline_exceeded`] either way.
+    pub deadline: Option<Duration>,
+    /// Address of a gRPC server to dial directly (`host:port`).
+    ///
+    /// When set, gRPC gateways use a channel from `channel_pool` instead
+    /// of the framework-created one, and reconnect it when it breaks.
+    pub grpc_address: Option<String>,
+    /// Pool of tuned (keepalive, idle eviction, reconnect) channels.
+    ///
+    /// Shared, so the owner can subscribe to its connectivity events.
+    #[cfg(feature = "grpc")]
+    pub channel_pool: Arc<ChannelPool>,
+    /// Hedge idempotent gRPC calls that are slower than the policy's delay
+    /// (disabled if `None`). Direct calls are never hedged.
+    pub hedging: Option<HedgingPolicy>,
+    /// How to find the gRPC server without `grpc_address`.
+    ///
+    /// With [`Discovery::Mdns`] or [`Discovery::Backend`] the address is
+    /// looked up once and then used like a configured `grpc_address`,
+    /// until its instance refuses a call for maintenance (see
+    /// [`crate::rediscovery`]).
+    pub discovery: Discovery,
+    /// Let identical concurrent `echo()` calls share one request (see
+    /// [`crate::coalescing`]; opt out per call with
+    /// [`crate::without_coalescing`]).
+    pub coalescing: bool,
+    /// Send gRPC echo calls in `EchoBatch` requests (see
+    /// [`crate::batching`]; unary if `None`). Direct calls are never batched.
+    pub batching: Option<BatchingConfig>,
+    /// For `Auto` without a Direct handler, send the first echo over gRPC
+    /// and JSON/HTTP (the server's JSON transcoding on the same address)
+    /// and keep the transport that answers first (see [`crate::racing`]).
+    /// Needs a known address; the outcome is reported in
+    /// [`CallInfo::protocol_race`].
+    pub protocol_racing: bool,
+    /// Checks applied to every echoed reply (see [`crate::validation`]);
+    /// a rejected reply fails the call with
+    /// [`echo_contract::invalid_response`].
+    pub validators: Vec<ResponseValidator>,
+    /// Checksum the messages of unary gRPC calls, batches included, end to
+    /// end (see `echo_api_grpc::integrity`); corruption fails the call
+    /// with [`echo_contract::integrity_error`].
+    pub integrity: bool,
+    /// Sign every gRPC request as this key's caller, for servers that
+    /// verify signatures (see `echo_api_grpc::signing`; unsigned if
+    /// `None`). JSON/HTTP calls aren't signed, so such a server never
+    /// wins a protocol race for them.
+    #[cfg(feature = "grpc")]
+    pub signing_key: Option<Arc<SigningKey>>,
+    /// Seal messages with this key, shared with the target module, before
+    /// any transport sees them - Direct calls included (see
+    /// [`crate::encryption`]; plain text if `None`).
+    #[cfg(feature = "encryption")]
+    pub payload_key: Option<Arc<PayloadKey>>,
+    /// Copy `echo` and `echo_bytes` calls to a second endpoint and compare
+    /// the replies, which are otherwise ignored (see [`crate::mirroring`];
+    /// no mirroring if `None`).
+    #[cfg(feature = "grpc")]
+    pub mirror: Option<MirrorConfig>,
+    /// How long each echo method may take; slower calls are logged with
+    /// their [`CallInfo`] and counted, not failed (see
+    /// [`crate::latency_budget`]; untimed if empty).
+    pub latency_budgets: LatencyBudgets,
+    /// Keep a second transport connected and probed next to the one in
+    /// use; calls safe to repeat that fail as `UNAVAILABLE` are resent
+    /// over it (see [`crate::warm_standby`]; no standby if `None`). Its
+    /// state is reported in [`EchoServiceGateways::capabilities`].
+    #[cfg(feature = "grpc")]
+    pub warm_standby: Option<WarmStandbyConfig>,
+    /// Without a Direct handler in this process, serve Direct (and
+    /// `Auto`) calls over the stdio pipe of the supervised child running
+    /// the module (see [`crate::pipe`]; only `echo`, `echo_bytes` and
+    /// `get_info` are carried).
+    pub pipe: Option<Arc<PipeChannel>>,
+}
 
 /// Implementation of EchoServiceGateways.
 pub struct EchoServiceGatewaysImpl {
     module_id: ModuleID,
-    service_connector: Arc<dyn ServiceConnector>,
+    /// `None` for standalone gateways (no framework channel to fall back on).
+    service_connector: Option<Arc<dyn ServiceConnector>>,
     service_handlers: std::sync::RwLock<Option<EchoServiceHandlers>>,
+    options: GatewayOptions,
+    /// Address found by mDNS or registry-backend discovery; forgotten
+    /// once its instance is in maintenance (see [`crate::rediscovery`]).
+    #[cfg(feature = "grpc")]
+    discovered: DiscoveredAddress,
+    /// Echo calls in flight, shared by all gateways handed out.
+    in_flight: Arc<InFlightEchoes>,
+    #[cfg(feature = "grpc")]
+    batchers: Batchers,
+    /// Outcome of the first `Auto` echo's transport race.
+    #[cfg(feature = "grpc")]
+    race: Arc<std::sync::OnceLock<ProtocolRace>>,
+    /// JSON/HTTP gateways by address, built once.
+    #[cfg(feature = "grpc")]
+    json_gateways: Mutex<HashMap<String, Arc<dyn EchoService>>>,
+    /// Standby transport, connected when the gateways are created.
+    #[cfg(feature = "grpc")]
+    standby: tokio::sync::OnceCell<Arc<WarmStandby>>,
+    /// When connecting the standby last failed; not retried for a probe interval.
+    #[cfg(feature = "grpc")]
+    standby_failed: Mutex<Option<Instant>>,
+    /// Shadow gateway of `GatewayOptions::mirror`, built on first use.
+    #[cfg(feature = "grpc")]
+    mirror_shadow: std::sync::OnceLock<MirrorShadow>,
+    /// Set by `close()`; event subscriptions watch it.
+    closed: watch::Sender<bool>,
+    /// Built on first use, rebuilt after the direct closure changes.
+    factories: std::sync::RwLock<Option<Arc<Factories>>>,
+}
+
+/// Gateway factories and what they support, built once instead of per
+/// `get_service`/`get_events` call (the boxed closures are allocated here).
+///
+/// The factories are `None` without a `ServiceConnector`.
+struct Factories {
+    service: Option<ServiceGatewayFactory<dyn EchoService>>,
+    events: Option<ServiceGatewayFactory<dyn EchoEvents>>,
+    capabilities: GatewayCapabilities,
+}
+
+impl Factories {
+    fn service(&self, module_id: &ModuleID) -> Result<&ServiceGatewayFactory<dyn EchoService>> {
+        self.service.as_ref().ok_or_else(|| standalone_error(module_id))
+    }
+
+    fn events(&self, module_id: &ModuleID) -> Result<&ServiceGatewayFactory<dyn EchoEvents>> {
+        self.events.as_ref().ok_or_else(|| standalone_error(module_id))
+    }
+}
+
+fn standalone_error(module_id: &ModuleID) -> Error {
+    Error::Validation {
+        message: format!("Standalone gateways to module '{}' need a gRPC address or a discovery", module_id),
+    }
 }
 
 impl EchoServiceGatewaysImpl {
@@ -23,11 +563,419 @@ impl EchoServiceGatewaysImpl {
         module_id: ModuleID,
         service_connector: Arc<dyn ServiceConnector>,
     ) -> Self {
+        Self::with_connector(module_id, Some(service_connector))
+    }
+
+    /// Creates gateways routing only to the address in their options
+    /// (see [`new_standalone_echo_service_gateways`]).
+    pub fn standalone(module_id: ModuleID) -> Self {
+        Self::with_connector(module_id, None)
+    }
+
+    fn with_connector(module_id: ModuleID, service_connector: Option<Arc<dyn ServiceConnector>>) -> Self {
         Self {
             module_id,
             service_connector,
             service_handlers: std::sync::RwLock::new(None),
+            options: GatewayOptions::default(),
+            #[cfg(feature = "grpc")]
+            discovered: DiscoveredAddress::default(),
+            in_flight: Arc::new(InFlightEchoes::new()),
+            #[cfg(feature = "grpc")]
+            batchers: Batchers::default(),
+            #[cfg(feature = "grpc")]
+            race: Arc::new(std::sync::OnceLock::new()),
+            #[cfg(feature = "grpc")]
+            json_gateways: Mutex::new(HashMap::new()),
+            #[cfg(feature = "grpc")]
+            standby: tokio::sync::OnceCell::new(),
+            #[cfg(feature = "grpc")]
+            standby_failed: Mutex::new(None),
+            #[cfg(feature = "grpc")]
+            mirror_shadow: std::sync::OnceLock::new(),
+            closed: watch::Sender::new(false),
+            factories: std::sync::RwLock::new(None),
+        }
+    }
+
+    /// Applies `options` to every gateway handed out.
+    pub fn with_options(mut self, options: GatewayOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn ensure_open(&self) -> Result<()> {
+        if *self.closed.borrow() {
+            return Err(Error::Protocol(format!("Gateways to module '{}' are closed", self.module_id)));
         }
+        Ok(())
+    }
+
+    /// Returns the factories for the current direct closure, building
+    /// them on first use.
+    fn factories(&self) -> Arc<Factories> {
+        if let Some(factories) = self.factories.read().unwrap().as_ref() {
+            return factories.clone();
+        }
+        // Lock order: factories, then handlers (as in enable_direct_closure)
+        let mut cached = self.factories.write().unwrap();
+        if let Some(factories) = cached.as_ref() {
+            return factories.clone();
+        }
+        let (direct_handler, direct_events) = match self.service_handlers.read().unwrap().as_ref() {
+            Some(handlers) => (Some(handlers.service.clone()), handlers.events.clone()),
+            None => (None, None),
+        };
+        debug!("Building gateway factories");
+        let factories = Arc::new(Factories {
+            service: self.service_connector.clone().map(|connector| ServiceGatewayFactory::<dyn EchoService>::new(
+                self.module_id.clone(),
+                EchoServiceId::Service.into(),
+                connector,
+                service_factory_funcs(
+                    &self.module_id,
+                    &self.options,
+                    #[cfg(feature = "grpc")]
+                    self.batchers.clone(),
+                    direct_handler.clone(),
+                ),
+            )),
+            events: self.service_connector.clone().map(|connector| ServiceGatewayFactory::<dyn EchoEvents>::new(
+                self.module_id.clone(),
+                EchoServiceId::Events.into(),
+                connector,
+                events_factory_funcs(direct_events.clone()),
+            )),
+            capabilities: gateway_capabilities(self.module_id.clone(), &self.options, direct_handler, direct_events),
+        });
+        *cached = Some(factories.clone());
+        factories
+    }
+
+    fn closable(&self, events: Arc<dyn EchoEvents>) -> Arc<dyn EchoEvents> {
+        Arc::new(ClosableEvents { inner: events, closed: self.closed.subscribe() })
+    }
+
+    /// Picks the service gateway for `protocol`, also returning the
+    /// protocol it uses and where it goes (for [`CallInfo`]).
+    async fn route(&self, protocol: Protocol) -> Result<(Arc<dyn EchoService>, Protocol, String)> {
+        let (service, protocol_used, endpoint) = self.route_gateway(protocol).await?;
+        // Sealed, validated and timed the same whichever transport answers
+        #[cfg(feature = "grpc")]
+        let service = match self.standby().await {
+            Some(standby) if standby.protocol() != protocol_used => Arc::new(FallbackEchoService::new(service, standby)),
+            _ => service,
+        };
+        #[cfg(feature = "encryption")]
+        let service = encrypt(service, self.options.payload_key.as_ref());
+        // Validators judge the primary reply only
+        #[cfg(feature = "grpc")]
+        let service = self.mirror(service);
+        let service = validate(service, &self.options.validators);
+        // Coalesced calls are neither sent nor measured...
+        let service = if self.options.coalescing {
+            let route = format!("{:?} {}", protocol_used, endpoint);
+            Arc::new(CoalescingEchoService::new(service, self.in_flight.clone(), route))
+        } else {
+            service
+        };
+        // ...but the caller waits for them all the same
+        let service = budget(service, &self.options.latency_budgets, protocol_used, &endpoint);
+        Ok((service, protocol_used, endpoint))
+    }
+
+    async fn route_gateway(&self, protocol: Protocol) -> Result<(Arc<dyn EchoService>, Protocol, String)> {
+        self.ensure_open()?;
+        
+        // Get direct handler if available
+        let direct_handler = self.service_handlers
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|h| h.service.clone());
+        // Otherwise "Direct" may still reach a child over its pipe
+        let direct_endpoint = match (&direct_handler, &self.options.pipe) {
+            (None, Some(pipe)) => Some(format!("pipe:{}", pipe.name())),
+            (Some(_), _) => Some("in-process".to_string()),
+            (None, None) => None,
+        };
+        
+        // A protocol without a factory fails as PROTOCOL_UNSUPPORTED,
+        // naming the ones that would work
+        let factories = self.factories();
+        factories.capabilities.require(protocol)?;
+        
+        // A configured (or discovered) address bypasses the framework
+        // channel, so our keepalive settings apply (Auto still prefers a
+        // direct handler or pipe)
+        #[cfg(feature = "grpc")]
+        {
+            let use_grpc = protocol == Protocol::Grpc
+                || (protocol == Protocol::Auto && direct_endpoint.is_none());
+            if use_grpc {
+                if let Some(address) = self.grpc_address().await? {
+                    let (service, protocol_used) = self.address_gateway(protocol, &address)?;
+                    let service = match self.options.grpc_address {
+                        Some(_) => service,
+                        None => Arc::new(RediscoveringEchoService::new(service, address.clone(), self.discovered.clone())),
+                    };
+                    return Ok((service, protocol_used, address));
+                }
+            }
+        }
+        
+        // Standalone gateways have no framework factory: Direct (and an
+        // Auto without an address) reaches the module over a child's pipe
+        if factories.service.is_none() && matches!(protocol, Protocol::Direct | Protocol::Auto) {
+            let funcs = service_factory_funcs(
+                &self.module_id,
+                &self.options,
+                #[cfg(feature = "grpc")]
+                self.batchers.clone(),
+                direct_handler,
+            );
+            if let Some(direct) = funcs.direct {
+                return Ok((direct()?, Protocol::Direct, direct_endpoint.unwrap_or_default()));
+            }
+        }
+        
+        // The framework picks the factory (Direct first for Auto); its gRPC
+        // channel comes from the registry, with the address hidden from us
+        let factory = factories.service(&self.module_id)?;
+        let (service, picked) = PICKED.scope(Cell::new(None), async {
+            let service = factory.new_service_gateway(protocol).await?;
+            Ok::<_, Error>((service, PICKED.with(Cell::get)))
+        }).await?;
+        let protocol_used = picked.unwrap_or(protocol);
+        let endpoint = match (protocol_used, direct_endpoint) {
+            (Protocol::Direct, endpoint) => endpoint.unwrap_or_else(|| "in-process".to_string()),
+            _ => format!("registry:{}", self.module_id),
+        };
+        
+        debug!("✅ Service gateway created");
+        Ok((service, protocol_used, endpoint))
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl EchoServiceGatewaysImpl {
+    /// Address to dial ourselves: configured, or discovered via mDNS or
+    /// a registry backend (once, and again after a maintenance refusal).
+    ///
+    /// `None` means the framework channel (registry discovery) is used.
+    async fn grpc_address(&self) -> Result<Option<String>> {
+        if let Some(address) = &self.options.grpc_address {
+            return Ok(Some(address.clone()));
+        }
+        let discovered = self.discovered.lock().unwrap().clone();
+        if discovered.is_some() {
+            return Ok(discovered);
+        }
+        let module_id = self.module_id.to_string();
+        // Concurrent first calls may each look up; the last answer is kept
+        let address = match &self.options.discovery {
+            Discovery::Registry => return Ok(None),
+            #[cfg(feature = "mdns")]
+            Discovery::Mdns { timeout } => crate::mdns::browse(&module_id, *timeout).await?,
+            Discovery::Backend(backend) => {
+                let apis = backend.discover(&module_id).await?;
+                // Instances in maintenance mode refuse new calls: only as a last resort
+                apis.into_iter()
+                    .filter(|api| api.protocol == Protocol::Grpc)
+                    .min_by_key(|api| api.maintenance.is_some())
+                    .map(|api| api.address)
+                    .ok_or_else(|| hsu_common::Error::Protocol(format!(
+                        "Module '{}' publishes no gRPC API in the {} registry", module_id, backend.name(),
+                    )))?
+            }
+        };
+        *self.discovered.lock().unwrap() = Some(address.clone());
+        Ok(Some(address))
+    }
+
+    /// The gateway to `address` for `protocol`: gRPC, or with protocol
+    /// racing, whichever transport won (both while undecided).
+    fn address_gateway(&self, protocol: Protocol, address: &str) -> Result<(Arc<dyn EchoService>, Protocol)> {
+        let grpc = self.pooled_grpc_service(address)?;
+        if protocol != Protocol::Auto || !self.options.protocol_racing {
+            return Ok((grpc, Protocol::Grpc));
+        }
+        Ok(match self.race.get().map(|race| race.winner) {
+            Some(Protocol::Http) => (self.json_service(address), Protocol::Http),
+            Some(_) => (grpc, Protocol::Grpc),
+            // Reported as gRPC until an echo decided (see echo_with_info)
+            None => {
+                let racer = RacingEchoService::new(grpc, self.json_service(address), self.race.clone());
+                (Arc::new(racer), Protocol::Grpc)
+            }
+        })
+    }
+
+    /// Mirrors `service` to the shadow of the options, shared by every
+    /// gateway handed out (with its in-flight limit).
+    fn mirror(&self, service: Arc<dyn EchoService>) -> Arc<dyn EchoService> {
+        let Some(config) = &self.options.mirror else {
+            return service;
+        };
+        let shadow = self.mirror_shadow.get_or_init(|| mirror_shadow(config, &self.options));
+        Arc::new(MirroringEchoService::with_shadow(service, shadow.clone()))
+    }
+
+    /// The warm standby, connected (and its probes started) on first use -
+    /// right after the gateways are created, see [`share`].
+    ///
+    /// `None` without [`GatewayOptions::warm_standby`], or if it can't be
+    /// connected yet - calls go ahead without it. A failed connection is
+    /// retried once a probe interval has passed, not on every call.
+    async fn standby(&self) -> Option<Arc<WarmStandby>> {
+        let config = self.options.warm_standby.as_ref()?;
+        if let Some(standby) = self.standby.get() {
+            return Some(standby.clone());
+        }
+        if self.standby_failed.lock().unwrap().is_some_and(|at| at.elapsed() < config.probe_interval) {
+            return None;
+        }
+        let standby = self.standby.get_or_try_init(|| async {
+            let (service, endpoint) = match (config.protocol, self.grpc_address().await?) {
+                (Protocol::Grpc, Some(address)) => (unbatched_grpc_service(&address, &self.options), address),
+                (Protocol::Grpc, None) => (
+                    self.factories().service(&self.module_id)?.new_service_gateway(Protocol::Grpc).await?,
+                    format!("registry:{}", self.module_id),
+                ),
+                (Protocol::Http, Some(address)) => (self.json_service(&address), address),
+                (protocol, _) => return Err(Error::Protocol(format!(
+                    "No {:?} standby to module '{}' (gRPC, or JSON/HTTP with a known address)", protocol, self.module_id,
+                ))),
+            };
+            let standby = WarmStandby::new(config.protocol, endpoint, service);
+            spawn_tracked(
+                &self.module_id.to_string(),
+                "warm-standby",
+                standby.keep_warm(config.probe_interval, self.closed.subscribe()),
+            );
+            Ok::<_, Error>(standby)
+        }).await;
+        match standby {
+            Ok(standby) => Some(standby.clone()),
+            Err(e) => {
+                warn!("[EchoServiceGateways] Warm standby to module {} not connected (retrying in {:?}): {}",
+                    self.module_id, config.probe_interval, e);
+                *self.standby_failed.lock().unwrap() = Some(Instant::now());
+                None
+            }
+        }
+    }
+
+    /// The JSON/HTTP gateway to the transcoding port at `address`, built
+    /// on first use.
+    fn json_service(&self, address: &str) -> Arc<dyn EchoService> {
+        self.json_gateways
+            .lock()
+            .unwrap()
+            .entry(address.to_string())
+            .or_insert_with(|| {
+                debug!("Creating JSON/HTTP gateway to {}", address);
+                instrument(Arc::new(EchoJsonGateway::new(address).with_deadline(self.options.deadline)), "http")
+            })
+            .clone()
+    }
+
+    /// Builds a self-healing gRPC gateway over pooled channels to `address`.
+    fn pooled_grpc_service(&self, address: &str) -> Result<Arc<dyn EchoService>> {
+        debug!("Creating gRPC gateway to {}", address);
+        let service = unbatched_grpc_service(address, &self.options);
+        Ok(batch(service, self.options.batching.as_ref(), &self.batchers, address, false, || {
+            Arc::new(batch_sender(address, &self.options))
+        }))
+    }
+}
+
+/// The gRPC echo service the gateways hand out for a `grpc_address`:
+/// pooled self-healing channels, deadline, hedging, size metrics,
+/// batching, payload encryption, mirroring, response validation.
+///
+/// Needs no `ServiceConnector`, for tools dialing one address. Only calls
+/// through the returned service are batched together; the module's
+/// gateways (also [`new_standalone_echo_service_gateways`]) coalesce,
+/// time and fail over on top of this.
+#[cfg(feature = "grpc")]
+pub fn grpc_echo_service(address: &str, options: &GatewayOptions) -> Arc<dyn EchoService> {
+    let service = unbatched_grpc_service(address, options);
+    let service = batch(service, options.batching.as_ref(), &Batchers::default(), address, false, || {
+        Arc::new(batch_sender(address, options))
+    });
+    #[cfg(feature = "encryption")]
+    let service = encrypt(service, options.payload_key.as_ref());
+    validate(mirror(service, options), &options.validators)
+}
+
+/// Copies calls of `service` to the mirror target, if any.
+///
+/// The shadow gateway isn't instrumented (its traffic would count as the
+/// client's own in the size metrics) and is sealed like the primary.
+#[cfg(feature = "grpc")]
+fn mirror(service: Arc<dyn EchoService>, options: &GatewayOptions) -> Arc<dyn EchoService> {
+    match &options.mirror {
+        Some(config) => Arc::new(MirroringEchoService::with_shadow(service, mirror_shadow(config, options))),
+        None => service,
+    }
+}
+
+/// Builds the shadow gateway of `config`.
+#[cfg(feature = "grpc")]
+fn mirror_shadow(config: &MirrorConfig, options: &GatewayOptions) -> MirrorShadow {
+    let shadow: Arc<dyn EchoService> = match &config.target {
+        MirrorTarget::Http(address) => Arc::new(EchoJsonGateway::new(address.as_str()).with_deadline(options.deadline)),
+        MirrorTarget::Grpc(address) => Arc::new(
+            ReconnectingGrpcGateway::new(options.channel_pool.clone(), address)
+                .with_deadline(options.deadline)
+                .with_signing(options.signing_key.clone()),
+        ),
+    };
+    #[cfg(feature = "encryption")]
+    let shadow = encrypt(shadow, options.payload_key.as_ref());
+    MirrorShadow::new(shadow, config.max_in_flight)
+}
+
+#[cfg(feature = "grpc")]
+fn unbatched_grpc_service(address: &str, options: &GatewayOptions) -> Arc<dyn EchoService> {
+    let gateway = ReconnectingGrpcGateway::new(options.channel_pool.clone(), address)
+        .with_deadline(options.deadline)
+        .with_integrity(options.integrity)
+        .with_signing(options.signing_key.clone());
+    instrument(hedge(Arc::new(gateway), options.hedging.as_ref()), "grpc")
+}
+
+/// Sends the batches of `address`, reconnecting like the unary gateway.
+#[cfg(feature = "grpc")]
+fn batch_sender(address: &str, options: &GatewayOptions) -> ReconnectingGrpcGateway {
+    ReconnectingGrpcGateway::new(options.channel_pool.clone(), address)
+        .with_deadline(options.deadline)
+        .with_integrity(options.integrity)
+        .with_signing(options.signing_key.clone())
+}
+
+impl EchoServiceGatewaysImpl {
+    /// The race that picked the transport of an `Auto` call, if any.
+    fn protocol_race(&self, protocol: Protocol) -> Option<ProtocolRace> {
+        #[cfg(feature = "grpc")]
+        if protocol == Protocol::Auto && self.options.protocol_racing {
+            return self.race.get().cloned();
+        }
+        #[cfg(not(feature = "grpc"))]
+        let _ = protocol;
+        None
+    }
+
+    /// Whether an `Auto` call would still race its transport.
+    fn race_pending(&self, protocol: Protocol) -> bool {
+        #[cfg(feature = "grpc")]
+        if protocol == Protocol::Auto && self.options.protocol_racing {
+            return self.race.get().is_none();
+        }
+        #[cfg(not(feature = "grpc"))]
+        let _ = protocol;
+        false
     }
 }
 
@@ -38,54 +986,125 @@ impl EchoServiceGateways for EchoServiceGatewaysImpl {
     }
     
     fn service_ids(&self) -> Vec<ServiceID> {
-        vec![ServiceID::from("service")]
+        echo_gateway_service_ids()
     }
     
+    #[tracing::instrument(name = "gateway", level = "debug", skip_all, fields(module_id = %self.module_id))]
     fn enable_direct_closure(&self, handlers: EchoServiceHandlers) {
-        debug!("[EchoServiceGateways] Enabling direct closure for module {}", self.module_id);
+        debug!("Enabling direct closure");
+        let mut factories = self.factories.write().unwrap();
         *self.service_handlers.write().unwrap() = Some(handlers);
+        // Rebuilt with the direct factories on next use
+        *factories = None;
     }
     
+    #[tracing::instrument(name = "gateway", level = "debug", skip_all, fields(
+        module_id = %self.module_id,
+        service_id = %EchoServiceId::Service,
+        protocol = %protocol_field(protocol),
+    ))]
     async fn get_service(&self, protocol: Protocol) -> Result<Arc<dyn EchoService>> {
-        debug!("[EchoServiceGateways] Getting service with protocol {:?}", protocol);
+        let (service, _, _) = self.route(protocol).await?;
+        Ok(service)
+    }
+    
+    fn capabilities(&self) -> GatewayCapabilities {
+        let capabilities = self.factories().capabilities.clone();
+        #[cfg(feature = "grpc")]
+        let capabilities = GatewayCapabilities {
+            standby: self.standby.get().map(|standby| standby.status()),
+            ..capabilities
+        };
+        capabilities
+    }
+    
+    #[tracing::instrument(name = "gateway", level = "debug", skip_all, fields(
+        module_id = %self.module_id,
+        service_id = %EchoServiceId::Service,
+        protocol = %protocol_field(protocol),
+        request_id = tracing::field::Empty,
+    ))]
+    async fn echo_with_info(&self, protocol: Protocol, message: Arc<str>) -> Result<(Arc<str>, CallInfo)> {
+        tracing::Span::current().record("request_id", current_request_id().as_str());
+        let race_pending = self.race_pending(protocol);
+        let (service, protocol_used, endpoint) = self.route(protocol).await?;
+        let started = Instant::now();
+        let ((reply, attempts), server_metadata) =
+            collect_response_metadata(count_attempts(service.echo(message))).await;
+        // A racing echo learns its transport only once one answered
+        let protocol_used = match self.protocol_race(protocol) {
+            Some(race) if race_pending && protocol_used == Protocol::Grpc => race.winner,
+            _ => protocol_used,
+        };
+        let info = CallInfo {
+            protocol_used,
+            endpoint,
+            latency: started.elapsed(),
+            // Direct calls don't pass a wire gateway that counts them
+            attempts: attempts.max(1),
+            server_metadata,
+            protocol_race: self.protocol_race(protocol),
+        };
+        debug!("echo via {:?} to {} took {:?} ({} attempts)",
+            info.protocol_used, info.endpoint, info.latency, info.attempts);
+        Ok((reply?, info))
+    }
+    
+    #[tracing::instrument(name = "gateway", level = "debug", skip_all, fields(
+        module_id = %self.module_id,
+        service_id = %EchoServiceId::Events,
+        protocol = %protocol_field(protocol),
+    ))]
+    async fn get_events(&self, protocol: Protocol) -> Result<Arc<dyn EchoEvents>> {
+        self.ensure_open()?;
         
-        // Get direct handler if available
-        let direct_handler = self.service_handlers
+        let direct_events = self.service_handlers
             .read()
             .unwrap()
             .as_ref()
-            .map(|h| h.service.clone());
+            .and_then(|h| h.events.clone());
         
-        // Create the generic factory
-        let factory = ServiceGatewayFactory::<dyn EchoService>::new(
-            self.module_id.clone(),
-            ServiceID::from("service"),
-            self.service_connector.clone(),
-            GatewayFactoryFuncs {
-                // Direct factory
-                direct: direct_handler.map(|handler| {
-                    Box::new(move || {
-                        debug!("[EchoServiceGateways] Using direct handler");
-                        Ok(handler.clone())
-                    }) as Box<dyn Fn() -> Result<Arc<dyn EchoService>> + Send + Sync>
-                }),
-                
-                // gRPC factory
-                grpc: Some(Box::new(|channel| {
-                    debug!("[EchoServiceGateways] Creating gRPC gateway");
-                    let client = echo_api_grpc::generated::echo_service_client::EchoServiceClient::new(channel);
-                    let gateway = EchoGrpcGateway::from_client(client);
-                    Ok(Arc::new(gateway) as Arc<dyn EchoService>)
-                }) as Box<dyn Fn(tonic::transport::Channel) -> Result<Arc<dyn EchoService>> + Send + Sync>),
-                
-                // HTTP factory
-                http: None,
-            },
-        );
+        #[cfg(feature = "grpc")]
+        {
+            let use_grpc = protocol == Protocol::Grpc
+                || (protocol == Protocol::Auto && direct_events.is_none());
+            if use_grpc {
+                if let Some(address) = self.grpc_address().await? {
+                    // Same constructor as the factory path, on our pooled channel
+                    if let Some(grpc) = &events_factory_funcs(None).grpc {
+                        let (channel, _) = self.options.channel_pool.checkout(&address)?;
+                        return Ok(self.closable(grpc(channel)?));
+                    }
+                }
+            }
+        }
         
-        let service = factory.new_service_gateway(protocol).await?;
-        debug!("[EchoServiceGateways] ✅ Service gateway created successfully");
-        Ok(service)
+        Ok(self.closable(self.factories().events(&self.module_id)?.new_service_gateway(protocol).await?))
+    }
+
+    #[tracing::instrument(name = "gateway", level = "debug", skip_all, fields(module_id = %self.module_id))]
+    async fn close(&self) -> Result<()> {
+        if self.closed.send_replace(true) {
+            return Ok(());
+        }
+        let subscriptions = self.closed.receiver_count();
+        self.factories.write().unwrap().take();
+        self.service_handlers.write().unwrap().take();
+        #[cfg(feature = "grpc")]
+        self.options.channel_pool.close();
+        info!("✅ Closed gateways ({} event watches cancelled)", subscriptions);
+        Ok(())
+    }
+}
+
+impl Drop for EchoServiceGatewaysImpl {
+    fn drop(&mut self) {
+        // Event watches keep a receiver; anything else is released with us
+        let subscriptions = self.closed.receiver_count();
+        if !*self.closed.borrow() && subscriptions > 0 {
+            warn!("Gateways to module {} dropped without close(), {} event watches left running",
+                self.module_id, subscriptions);
+        }
     }
 }
 
@@ -111,7 +1130,50 @@ impl EchoServiceGateways for EchoServiceGatewaysImpl {
 pub fn new_echo_service_gateways(
     service_connector: Arc<dyn ServiceConnector>,
 ) -> Arc<dyn EchoServiceGateways> {
-    let module_id = ModuleID::from("echo");  // Hard-coded - this is echo-specific code!
-    Arc::new(EchoServiceGatewaysImpl::new(module_id, service_connector))
+    new_echo_service_gateways_with_options(service_connector, GatewayOptions::default())
+}
+
+/// Service IDs the echo gateways ask the echo module for.
+pub fn echo_gateway_service_ids() -> Vec<ServiceID> {
+    EchoServiceId::ALL.into_iter().map(ServiceID::from).collect()
+}
+
+/// Like [`new_echo_service_gateways`], applying `options` to every gateway.
+pub fn new_echo_service_gateways_with_options(
+    service_connector: Arc<dyn ServiceConnector>,
+    options: GatewayOptions,
+) -> Arc<dyn EchoServiceGateways> {
+    let module_id = echo_module_id();  // Hard-coded - this is echo-specific code!
+    share(EchoServiceGatewaysImpl::new(module_id, service_connector).with_options(options))
+}
+
+/// Echo gateways for code running outside the framework: calls go to
+/// `options.grpc_address` (or the address `options.discovery` finds)
+/// through the same decorators as the module's gateways.
+///
+/// With neither, every call fails: there is no framework channel.
+pub fn new_standalone_echo_service_gateways(options: GatewayOptions) -> Arc<dyn EchoServiceGateways> {
+    share(EchoServiceGatewaysImpl::standalone(echo_module_id()).with_options(options))
+}
+
+/// Registers `gateways` for the capabilities API and connects their warm
+/// standby right away, so the first failure already finds it probed.
+///
+/// Outside a tokio runtime the standby connects on the first call instead.
+fn share(gateways: EchoServiceGatewaysImpl) -> Arc<dyn EchoServiceGateways> {
+    let gateways = Arc::new(gateways);
+    #[cfg(feature = "grpc")]
+    if gateways.options.warm_standby.is_some() && tokio::runtime::Handle::try_current().is_ok() {
+        // Weak: gateways dropped before connecting don't wait for it
+        let connecting = Arc::downgrade(&gateways);
+        spawn_tracked(&gateways.module_id.to_string(), "warm-standby-connect", async move {
+            if let Some(gateways) = connecting.upgrade() {
+                gateways.standby().await;
+            }
+        });
+    }
+    let gateways: Arc<dyn EchoServiceGateways> = gateways;
+    CapabilitiesRegistry::global().register(&gateways);
+    gateways
 }
 
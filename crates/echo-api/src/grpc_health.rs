@@ -0,0 +1,145 @@
+//! gRPC Health Service (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Serves the standard `grpc.health.v1.Health` service next to the echo
+//! service, answered from the [`HealthRegistry`] the admin `/health`
+//! reads - so a failing self-test or maintenance mode reports
+//! `NOT_SERVING` to any gRPC health checker (a supervisor, Kubernetes,
+//! `grpc_health_probe`):
+//!
+//! ```text
+//! HealthRegistry::global()  ←── record("echo", "maintenance", Failing(..))
+//!     ↓ is_healthy()
+//! EchoHealthService::check("" | "echo.EchoService") → NOT_SERVING
+//! ```
+//!
+//! `watch` polls the registry and sends the status whenever it changes.
+//! [`check_grpc_health`] is the client side.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use futures::Stream;
+use hsu_common::{Error, Result};
+use tonic::{Request, Response, Status};
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::health_server::{Health, HealthServer};
+use tonic_health::pb::{HealthCheckRequest, HealthCheckResponse};
+
+use crate::health::HealthRegistry;
+
+/// How often `watch` looks for a status change.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `grpc.health.v1.Health`, answered from a [`HealthRegistry`].
+#[derive(Clone)]
+pub struct EchoHealthService {
+    registry: Arc<HealthRegistry>,
+    /// Service names checked besides `""` (the whole server).
+    services: Vec<String>,
+}
+
+impl EchoHealthService {
+    /// Answers for `services` (and `""`) from `registry`.
+    pub fn new(registry: Arc<HealthRegistry>, services: Vec<String>) -> Self {
+        Self { registry, services }
+    }
+
+    /// Wraps it for a tonic router.
+    pub fn into_server(self) -> HealthServer<Self> {
+        HealthServer::new(self)
+    }
+
+    fn status(&self, service: &str) -> std::result::Result<ServingStatus, Status> {
+        if !service.is_empty() && !self.services.iter().any(|known| known == service) {
+            return Err(Status::not_found(format!("unknown service '{}'", service)));
+        }
+        Ok(if self.registry.is_healthy() { ServingStatus::Serving } else { ServingStatus::NotServing })
+    }
+}
+
+fn response(status: ServingStatus) -> HealthCheckResponse {
+    HealthCheckResponse { status: status as i32 }
+}
+
+type WatchStream = Pin<Box<dyn Stream<Item = std::result::Result<HealthCheckResponse, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Health for EchoHealthService {
+    async fn check(
+        &self,
+        request: Request<HealthCheckRequest>,
+    ) -> std::result::Result<Response<HealthCheckResponse>, Status> {
+        let status = self.status(&request.into_inner().service)?;
+        Ok(Response::new(response(status)))
+    }
+
+    type WatchStream = WatchStream;
+
+    async fn watch(
+        &self,
+        request: Request<HealthCheckRequest>,
+    ) -> std::result::Result<Response<Self::WatchStream>, Status> {
+        let service = request.into_inner().service;
+        // An unknown service fails the call rather than the stream
+        self.status(&service)?;
+        let this = self.clone();
+        let stream = futures::stream::unfold((this, service, None), |(this, service, last)| async move {
+            loop {
+                if last.is_some() {
+                    tokio::time::sleep(WATCH_INTERVAL).await;
+                }
+                let status = this.status(&service).unwrap_or(ServingStatus::ServiceUnknown);
+                if last != Some(status) {
+                    return Some((Ok(response(status)), (this, service, Some(status))));
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Asks the health service at `address` (`http://host:port`) whether
+/// `service` (`""` for the whole server) is serving.
+///
+/// Fails if it can't be reached within `timeout`; `Ok(false)` means it
+/// answered with anything but `SERVING`.
+pub async fn check_grpc_health(address: &str, service: &str, timeout: Duration) -> Result<bool> {
+    let unreachable = |detail: String| Error::Protocol(format!("Health check of {} failed: {}", address, detail));
+    let check = async {
+        let channel = tonic::transport::Endpoint::from_shared(address.to_string())
+            .map_err(|e| unreachable(e.to_string()))?
+            .connect_timeout(timeout)
+            .connect()
+            .await
+            .map_err(|e| unreachable(e.to_string()))?;
+        let reply = HealthClient::new(channel)
+            .check(HealthCheckRequest { service: service.to_string() })
+            .await
+            .map_err(|status| unreachable(status.message().to_string()))?;
+        Ok(reply.into_inner().status == ServingStatus::Serving as i32)
+    };
+    tokio::time::timeout(timeout, check)
+        .await
+        .map_err(|_| unreachable(format!("no answer within {:?}", timeout)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::HealthStatus;
+
+    #[tokio::test]
+    async fn test_status_follows_the_registry() {
+        let registry = Arc::new(HealthRegistry::default());
+        let health = EchoHealthService::new(registry.clone(), vec!["echo.EchoService".to_string()]);
+        let check = |service: &str| health.check(Request::new(HealthCheckRequest { service: service.to_string() }));
+
+        assert_eq!(check("").await.unwrap().into_inner().status, ServingStatus::Serving as i32);
+        registry.record("echo", "maintenance", HealthStatus::Failing("in maintenance".to_string()), Duration::ZERO);
+        assert_eq!(check("echo.EchoService").await.unwrap().into_inner().status, ServingStatus::NotServing as i32);
+        assert_eq!(check("other.Service").await.unwrap_err().code(), tonic::Code::NotFound);
+    }
+}
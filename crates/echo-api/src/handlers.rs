@@ -6,33 +6,126 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use async_trait::async_trait;
 use hsu_common::{Result, ServiceID, Protocol, Error};
-use hsu_module_api::{ProtocolToServicesMap};
-use hsu_module_proto::{ProtocolServer, ProtocolServerHandlersVisitor, grpc_server::GrpcServiceAdder};
-use echo_contract::{EchoService, EchoServiceHandlers};
-use echo_api_grpc::EchoGrpcHandler;
-use tracing::{debug, trace, warn};
+use hsu_module_api::{HandlersRegistrarOptions, ProtocolToServicesMap};
+use hsu_module_proto::{ProtocolServer, ProtocolServerHandlersVisitor};
+#[cfg(feature = "grpc")]
+use hsu_module_proto::grpc_server::GrpcServiceAdder;
+use echo_contract::{protocol_field, EchoEvents, EchoService, EchoServiceHandlers, EchoServiceId};
+#[cfg(feature = "grpc")]
+use echo_api_grpc::{EchoEventsGrpcHandler, EchoGrpcHandler, JsonTranscodingService, SignatureVerifier, SignedService};
+#[cfg(feature = "grpc")]
+use echo_api_grpc::generated::echo_service_server::EchoServiceServer;
+use tracing::{debug, debug_span, instrument, trace, warn};
+
+use crate::endpoints::{BoundEndpoint, BoundEndpoints};
+#[cfg(feature = "grpc")]
+use crate::grpc_health::EchoHealthService;
+#[cfg(feature = "grpc")]
+use crate::health::HealthRegistry;
+#[cfg(feature = "grpc")]
+use crate::metrics::{SizeLabels, SizeMetrics, SizeMetricsEchoService};
+
+/// Which gRPC servers also accept JSON requests (see
+/// [`JsonTranscodingService`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum JsonTranscoding {
+    /// gRPC only.
+    #[default]
+    Disabled,
+    /// Every gRPC server of the module.
+    AllServers,
+    /// Only the gRPC servers bound to these ports.
+    Ports(Vec<u16>),
+}
+
+impl JsonTranscoding {
+    /// Whether the server bound to `port` transcodes JSON.
+    pub fn applies_to(&self, port: u16) -> bool {
+        match self {
+            JsonTranscoding::Disabled => false,
+            JsonTranscoding::AllServers => true,
+            JsonTranscoding::Ports(ports) => ports.contains(&port),
+        }
+    }
+}
 
 /// Handlers registrar for Echo services.
 pub struct EchoHandlersRegistrar {
     protocol_servers: Vec<Arc<dyn ProtocolServer>>,
+    endpoints: Arc<BoundEndpoints>,
+    json_transcoding: JsonTranscoding,
+    cors_origins: Vec<String>,
+    #[cfg(feature = "grpc")]
+    signature_verifier: Option<SignatureVerifier>,
 }
 
 impl EchoHandlersRegistrar {
     /// Creates a new Echo handlers registrar.
     pub fn new(protocol_servers: Vec<Arc<dyn ProtocolServer>>) -> Result<Self> {
         debug!("Creating EchoHandlersRegistrar with {} servers", protocol_servers.len());
-        Ok(Self { protocol_servers })
+        Ok(Self {
+            protocol_servers,
+            endpoints: BoundEndpoints::global(),
+            json_transcoding: JsonTranscoding::default(),
+            cors_origins: Vec::new(),
+            #[cfg(feature = "grpc")]
+            signature_verifier: None,
+        })
+    }
+
+    /// Records bound endpoints into `endpoints` instead of the global registry.
+    pub fn with_endpoints(mut self, endpoints: Arc<BoundEndpoints>) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Lets the selected gRPC servers accept JSON requests as well.
+    pub fn with_json_transcoding(mut self, json_transcoding: JsonTranscoding) -> Self {
+        self.json_transcoding = json_transcoding;
+        self
+    }
+
+    /// Browser origins allowed to call the JSON endpoints (see
+    /// `JsonTranscodingService::with_cors_origins`; none by default).
+    pub fn with_cors_origins(mut self, origins: Vec<String>) -> Self {
+        self.cors_origins = origins;
+        self
+    }
+
+    /// Rejects gRPC and JSON requests to the echo service without a valid
+    /// signature (see `echo_api_grpc::signing`; nothing checked if `None`).
+    #[cfg(feature = "grpc")]
+    pub fn with_signature_verifier(mut self, verifier: Option<SignatureVerifier>) -> Self {
+        self.signature_verifier = verifier;
+        self
+    }
+
+    /// Returns the endpoints of all protocol servers.
+    ///
+    /// Reports the **bound** port, so port 0 resolves to the ephemeral
+    /// port the server actually got.
+    pub fn bound_endpoints(&self) -> Vec<BoundEndpoint> {
+        self.protocol_servers
+            .iter()
+            .map(|server| BoundEndpoint { protocol: server.protocol(), port: server.port() })
+            .collect()
     }
 
     /// Registers Echo service handlers with all protocol servers.
+    #[instrument(name = "handlers_registrar", level = "debug", skip_all, fields(servers = self.protocol_servers.len()))]
     pub fn register_handlers(&self, handlers: EchoServiceHandlers) -> Result<ProtocolToServicesMap> {
-        debug!("Registering Echo service handlers with {} servers", self.protocol_servers.len());
+        debug!("Registering Echo service handlers");
         
         let mut protocol_map: HashMap<Protocol, Vec<ServiceID>> = HashMap::new();
         
         // Create visitor for handler registration
         let visitor = Arc::new(ServiceHandlersVisitor {
             service: handlers.service.clone(),
+            events: handlers.events.clone(),
+            json_transcoding: self.json_transcoding.clone(),
+            cors_origins: self.cors_origins.clone(),
+            #[cfg(feature = "grpc")]
+            signature_verifier: self.signature_verifier.clone(),
         });
         
         // Register service with all servers
@@ -40,7 +133,8 @@ impl EchoHandlersRegistrar {
         // within an async runtime. This moves the blocking operation to a separate thread.
         for server in &self.protocol_servers {
             let protocol = server.protocol();
-            trace!("Registering service with {:?} server on port {}", protocol, server.port());
+            let _span = debug_span!("handler", protocol = %protocol_field(protocol), port = server.port()).entered();
+            trace!("Registering service");
             
             // Call the protocol-specific registration method
             // block_in_place allows us to call block_on from within an async context
@@ -53,6 +147,7 @@ impl EchoHandlersRegistrar {
                     Protocol::Http => {
                         handle.block_on(visitor.register_handlers_http(server.clone()))
                     }
+                    // Not in ECHO_HANDLER_PROTOCOLS
                     _ => {
                         warn!("Unsupported protocol: {:?}", protocol);
                         return Ok(());
@@ -62,12 +157,19 @@ impl EchoHandlersRegistrar {
             
             result?;
             
-            protocol_map
-                .entry(protocol)
-                .or_insert_with(Vec::new)
-                .push(ServiceID::from("service"));
+            // Several servers may share a protocol (one per listen address)
+            let services = protocol_map.entry(protocol).or_insert_with(Vec::new);
+            for service_id in echo_handler_service_ids(&handlers) {
+                if !services.contains(&service_id) {
+                    services.push(service_id);
+                }
+            }
             
-            debug!("✅ Registered service with {:?} server", protocol);
+            debug!("✅ Registered service");
+        }
+        
+        for endpoint in self.bound_endpoints() {
+            self.endpoints.record(endpoint);
         }
         
         debug!("✅ All Echo handlers registered. Protocol map: {:?}", protocol_map.keys().collect::<Vec<_>>());
@@ -75,28 +177,106 @@ impl EchoHandlersRegistrar {
     }
 }
 
+/// A module descriptor's handlers registrar, for handlers `SH`.
+pub type HandlersRegistrarFn<SH> = fn(HandlersRegistrarOptions<SH>) -> Result<ProtocolToServicesMap>;
+
+/// Protocols the registrar registers the echo handlers with; servers of
+/// other protocols are skipped.
+pub const ECHO_HANDLER_PROTOCOLS: [Protocol; 2] = [Protocol::Grpc, Protocol::Http];
+
+/// Service IDs the registrar publishes for `handlers`: the service, and
+/// the events if the module publishes them.
+pub fn echo_handler_service_ids(handlers: &EchoServiceHandlers) -> Vec<ServiceID> {
+    let mut service_ids = vec![ServiceID::from(EchoServiceId::Service)];
+    if handlers.events.is_some() {
+        service_ids.push(ServiceID::from(EchoServiceId::Events));
+    }
+    service_ids
+}
+
 /// Service adder for Echo gRPC service.
 /// 
 /// Implements GrpcServiceAdder to add Echo service to a tonic Router.
+/// Also adds the events stream, if the module publishes events, and the
+/// gRPC health service (see [`crate::grpc_health`]).
+#[cfg(feature = "grpc")]
 struct EchoGrpcServiceAdder {
     handler: Arc<EchoGrpcHandler>,
+    events: Option<EchoEventsGrpcHandler>,
+    json_transcoding: bool,
+    cors_origins: Vec<String>,
+    signature_verifier: Option<SignatureVerifier>,
+}
+
+/// Checks signatures if the module verifies them, else lets requests by.
+#[cfg(feature = "grpc")]
+type EchoServer = SignedService<EchoServiceServer<EchoGrpcHandler>>;
+
+#[cfg(feature = "grpc")]
+impl EchoGrpcServiceAdder {
+    fn echo_server(&self) -> EchoServer {
+        SignedService::new(EchoServiceServer::new((*self.handler).clone()), self.signature_verifier.clone())
+    }
+
+    fn json_transcoding(&self) -> JsonTranscodingService<EchoServer> {
+        JsonTranscodingService::new(self.echo_server(), self.handler.clone())
+            .with_signature_verifier(self.signature_verifier.clone())
+            .with_cors_origins(self.cors_origins.clone())
+    }
+
+    fn add_echo(&self, router: tonic::transport::server::Router) -> tonic::transport::server::Router {
+        if self.json_transcoding {
+            router.add_service(self.json_transcoding())
+        } else {
+            router.add_service(self.echo_server())
+        }
+    }
+    
+    /// `grpc.health.v1.Health` for the server and the echo service.
+    fn health(&self) -> tonic_health::pb::health_server::HealthServer<EchoHealthService> {
+        use tonic::server::NamedService;
+        let services = vec![<EchoServiceServer<EchoGrpcHandler> as NamedService>::NAME.to_string()];
+        EchoHealthService::new(HealthRegistry::global(), services).into_server()
+    }
+    
+    fn add_events(&self, router: tonic::transport::server::Router) -> tonic::transport::server::Router {
+        use echo_api_grpc::generated::echo_events_server::EchoEventsServer;
+        match &self.events {
+            Some(events) => router.add_service(EchoEventsServer::new(events.clone())),
+            None => router,
+        }
+    }
 }
 
+#[cfg(feature = "grpc")]
 impl GrpcServiceAdder for EchoGrpcServiceAdder {
-    fn add_to_server(&self, mut server: tonic::transport::Server) -> tonic::transport::server::Router {
-        use echo_api_grpc::generated::echo_service_server::EchoServiceServer;
-        server.add_service(EchoServiceServer::new((*self.handler).clone()))
+    fn add_to_server(&self, server: tonic::transport::Server) -> tonic::transport::server::Router {
+        // JSON clients are usually plain HTTP/1; gRPC still negotiates HTTP/2
+        let mut server = server.accept_http1(self.json_transcoding);
+        let router = if self.json_transcoding {
+            server.add_service(self.json_transcoding())
+        } else {
+            server.add_service(self.echo_server())
+        };
+        // Once per server: the router's first adder brings the health service
+        self.add_events(router).add_service(self.health())
     }
     
+    /// Note: HTTP/1 is a server setting, so with transcoding on a router
+    /// another adder created, JSON works for HTTP/2 clients only.
     fn add_to_router(&self, router: tonic::transport::server::Router) -> tonic::transport::server::Router {
-        use echo_api_grpc::generated::echo_service_server::EchoServiceServer;
-        router.add_service(EchoServiceServer::new((*self.handler).clone()))
+        self.add_events(self.add_echo(router))
     }
 }
 
 /// Visitor for registering service handlers.
 struct ServiceHandlersVisitor {
     service: Arc<dyn EchoService>,
+    events: Option<Arc<dyn EchoEvents>>,
+    json_transcoding: JsonTranscoding,
+    cors_origins: Vec<String>,
+    #[cfg(feature = "grpc")]
+    signature_verifier: Option<SignatureVerifier>,
 }
 
 #[async_trait]
@@ -110,17 +290,7 @@ impl ProtocolServerHandlersVisitor for ServiceHandlersVisitor {
             });
         }
         
-        // Create gRPC handler
-        let handler = Arc::new(EchoGrpcHandler::new(self.service.clone()));
-        
-        // Create service adder that knows how to add Echo service to Router
-        let service_adder = Arc::new(EchoGrpcServiceAdder { handler });
-        
-        // Register the service adder with the gRPC server
-        server.add_grpc_service_adder(service_adder).await?;
-        
-        debug!("✅ Echo service gRPC handler registered");
-        Ok(())
+        self.register_grpc(server).await
     }
     
     async fn register_handlers_http(&self, server: Arc<dyn ProtocolServer>) -> Result<()> {
@@ -135,6 +305,46 @@ impl ProtocolServerHandlersVisitor for ServiceHandlersVisitor {
     }
 }
 
+impl ServiceHandlersVisitor {
+    #[cfg(feature = "grpc")]
+    async fn register_grpc(&self, server: Arc<dyn ProtocolServer>) -> Result<()> {
+        // Instrument the service, then create the gRPC handler
+        let labels = SizeLabels { side: "server", protocol: "grpc", service: "service" };
+        let service = Arc::new(SizeMetricsEchoService::new(
+            self.service.clone(),
+            labels,
+            SizeMetrics::global(),
+        ));
+        let handler = Arc::new(EchoGrpcHandler::new(service));
+        
+        // Create service adder that knows how to add Echo service to Router
+        let json_transcoding = self.json_transcoding.applies_to(server.port());
+        if json_transcoding {
+            debug!("JSON transcoding enabled on gRPC server port {}", server.port());
+        }
+        let service_adder = Arc::new(EchoGrpcServiceAdder {
+            handler,
+            events: self.events.clone().map(EchoEventsGrpcHandler::new),
+            json_transcoding,
+            cors_origins: self.cors_origins.clone(),
+            signature_verifier: self.signature_verifier.clone(),
+        });
+        
+        // Register the service adder with the gRPC server
+        server.add_grpc_service_adder(service_adder).await?;
+        
+        debug!("✅ Echo service gRPC handler registered");
+        Ok(())
+    }
+    
+    #[cfg(not(feature = "grpc"))]
+    async fn register_grpc(&self, _server: Arc<dyn ProtocolServer>) -> Result<()> {
+        Err(Error::Validation {
+            message: "gRPC support not compiled in (enable the `grpc` feature of echo-api)".to_string(),
+        })
+    }
+}
+
 /// Factory function for creating an Echo handlers registrar.
 pub fn new_echo_handlers_registrar(
     protocol_servers: Vec<Arc<dyn ProtocolServer>>,
@@ -0,0 +1,137 @@
+//! Health Checks (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Modules record the outcome of named checks here; the admin endpoint
+//! serves them as `GET /health` (200 if every check passes, 503
+//! otherwise) and they are logged when recorded:
+//!
+//! ```text
+//! EchoServerModule::start()
+//!     ↓ self-test (direct, grpc:50051, ...)
+//! HealthRegistry::global().record("echo", "self-test/direct", Ok(..))
+//!     ↓
+//! GET /health  →  200 "ok" + one line per check
+//! ```
+//!
+//! A check is replaced when recorded again under the same name, so the
+//! report always shows the latest outcome.
+//!
+//! ## Comparison with Golang
+//!
+//! The Go example has no health report; its closest equivalent is the
+//! gRPC health service, which only knows SERVING/NOT_SERVING per service.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+/// Outcome of one health check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The check passed.
+    Passing,
+    /// The check failed, with the reason.
+    Failing(String),
+}
+
+/// Latest outcome of a named check of a module.
+#[derive(Debug, Clone)]
+pub struct HealthCheck {
+    /// Module the check belongs to.
+    pub module: String,
+    /// Check name (e.g. `self-test/direct`).
+    pub name: String,
+    /// Whether it passed.
+    pub status: HealthStatus,
+    /// How long the check took.
+    pub latency: Duration,
+    /// When it was recorded.
+    pub checked_at: SystemTime,
+}
+
+impl HealthCheck {
+    /// Returns `true` if the check passed.
+    pub fn is_passing(&self) -> bool {
+        self.status == HealthStatus::Passing
+    }
+}
+
+/// Process-wide record of health checks.
+#[derive(Default)]
+pub struct HealthRegistry {
+    checks: Mutex<BTreeMap<(String, String), HealthCheck>>,
+}
+
+impl HealthRegistry {
+    /// Returns the process-wide registry.
+    pub fn global() -> Arc<HealthRegistry> {
+        static GLOBAL: OnceLock<Arc<HealthRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(HealthRegistry::default())).clone()
+    }
+
+    /// Records the outcome of `module`'s check `name`, replacing the previous one.
+    pub fn record(&self, module: &str, name: &str, status: HealthStatus, latency: Duration) {
+        let check = HealthCheck {
+            module: module.to_string(),
+            name: name.to_string(),
+            status,
+            latency,
+            checked_at: SystemTime::now(),
+        };
+        self.checks.lock().unwrap().insert((module.to_string(), name.to_string()), check);
+    }
+
+    /// Forgets the checks of `module` (e.g. when it stops).
+    pub fn clear(&self, module: &str) {
+        self.checks.lock().unwrap().retain(|(owner, _), _| owner != module);
+    }
+
+    /// All checks, ordered by module and name.
+    pub fn checks(&self) -> Vec<HealthCheck> {
+        self.checks.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Returns `true` if no recorded check is failing.
+    pub fn is_healthy(&self) -> bool {
+        self.checks.lock().unwrap().values().all(HealthCheck::is_passing)
+    }
+
+    /// Renders the report served by the admin endpoint.
+    pub fn render_text(&self) -> String {
+        let mut out = String::from(if self.is_healthy() { "ok\n" } else { "failing\n" });
+        for check in self.checks() {
+            let status = match &check.status {
+                HealthStatus::Passing => "passing".to_string(),
+                HealthStatus::Failing(reason) => format!("failing: {}", reason),
+            };
+            let _ = writeln!(out, "{}/{}: {} ({:.1}ms)",
+                check.module, check.name, status, check.latency.as_secs_f64() * 1000.0);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latest_outcome_wins() {
+        let registry = HealthRegistry::default();
+        assert!(registry.is_healthy());
+
+        registry.record("echo", "self-test/direct", HealthStatus::Failing("wrong reply".to_string()), Duration::ZERO);
+        registry.record("echo", "self-test/grpc:50051", HealthStatus::Passing, Duration::from_millis(2));
+        assert!(!registry.is_healthy());
+        assert!(registry.render_text().contains("echo/self-test/direct: failing: wrong reply"));
+
+        registry.record("echo", "self-test/direct", HealthStatus::Passing, Duration::ZERO);
+        assert!(registry.is_healthy());
+        assert_eq!(registry.checks().len(), 2);
+
+        registry.clear("echo");
+        assert!(registry.checks().is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+//! Hedged gRPC Calls (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A few slow calls (GC pause, busy worker, lost packet) dominate tail
+//! latency. Hedging sends a second copy of a call that hasn't answered
+//! within `delay` and takes whichever response arrives first:
+//!
+//! ```text
+//! t=0      attempt 1 → target[0] ─────────────────────┐ (still running)
+//! t=delay  attempt 2 → target[1 % n] ──→ response ✅   │
+//!                                          ↓           ↓
+//!                                    returned     cancelled (dropped)
+//! ```
+//!
+//! With several targets (one gateway per endpoint) each hedge goes to the
+//! next one; with a single target the hedge goes to the same endpoint.
+//!
+//! # Safeguards
+//!
+//! Only idempotent methods are hedged - sending them twice is harmless:
+//!
+//! | Method          | Hedged | Why |
+//! |-----------------|--------|-----|
+//! | `echo`          | ✅     | Pure |
+//! | `echo_bytes`    | ✅     | Pure |
+//! | `echo_reliable` | ❌     | A second copy would be acknowledged as `duplicate` |
+//! | `echo_file`     | ❌     | The input stream can only be consumed once |
+//!
+//! Errors are not hedged either: hedging is for slow calls, retries are
+//! for failed ones.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{FuturesUnordered, StreamExt};
+use hsu_common::Result;
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use tracing::debug;
+
+/// When and how often to hedge.
+#[derive(Debug, Clone)]
+pub struct HedgingPolicy {
+    /// Send the next attempt if no response arrived within this delay.
+    pub delay: Duration,
+    /// Total attempts per call, including the first one.
+    pub max_attempts: u32,
+}
+
+impl Default for HedgingPolicy {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(50),
+            max_attempts: 2,
+        }
+    }
+}
+
+/// Hedging counters.
+#[derive(Debug, Default)]
+pub struct HedgingStats {
+    /// Extra attempts sent.
+    pub hedges: AtomicU64,
+    /// Calls answered by an extra attempt.
+    pub hedge_wins: AtomicU64,
+}
+
+/// Decorator that hedges idempotent calls across `targets`.
+pub struct HedgingEchoService {
+    targets: Vec<Arc<dyn EchoService>>,
+    policy: HedgingPolicy,
+    stats: Arc<HedgingStats>,
+}
+
+impl HedgingEchoService {
+    /// Hedges calls to a single target.
+    pub fn new(target: Arc<dyn EchoService>, policy: HedgingPolicy) -> Self {
+        Self::with_targets(vec![target], policy)
+    }
+
+    /// Hedges calls across `targets`; attempt `n` goes to `targets[n % len]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `targets` is empty.
+    pub fn with_targets(targets: Vec<Arc<dyn EchoService>>, policy: HedgingPolicy) -> Self {
+        assert!(!targets.is_empty(), "HedgingEchoService needs at least one target");
+        Self {
+            targets,
+            policy,
+            stats: Arc::new(HedgingStats::default()),
+        }
+    }
+
+    /// Returns the hedging counters.
+    pub fn stats(&self) -> Arc<HedgingStats> {
+        self.stats.clone()
+    }
+
+    async fn hedged<T, F, Fut>(&self, method: &str, call: F) -> Result<T>
+    where
+        F: Fn(Arc<dyn EchoService>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut in_flight = FuturesUnordered::new();
+        in_flight.push(tag(0, call(self.targets[0].clone())));
+        let mut attempts = 1;
+
+        loop {
+            let can_hedge = attempts < self.policy.max_attempts;
+            tokio::select! {
+                Some((attempt, result)) = in_flight.next() => match result {
+                    Ok(response) => {
+                        if attempt > 0 {
+                            self.stats.hedge_wins.fetch_add(1, Ordering::Relaxed);
+                            debug!("[Hedging] {} answered by attempt {}", method, attempt + 1);
+                        }
+                        // Dropping `in_flight` cancels the slower attempts
+                        return Ok(response);
+                    }
+                    Err(e) if in_flight.is_empty() => return Err(e),
+                    Err(e) => debug!("[Hedging] {} attempt {} failed ({}), awaiting others", method, attempt + 1, e),
+                },
+                _ = tokio::time::sleep(self.policy.delay), if can_hedge => {
+                    let target = self.targets[attempts as usize % self.targets.len()].clone();
+                    debug!("[Hedging] {} slower than {:?}, sending attempt {}", method, self.policy.delay, attempts + 1);
+                    self.stats.hedges.fetch_add(1, Ordering::Relaxed);
+                    in_flight.push(tag(attempts, call(target)));
+                    attempts += 1;
+                }
+            }
+        }
+    }
+}
+
+async fn tag<T>(attempt: u32, call: impl Future<Output = T>) -> (u32, T) {
+    (attempt, call.await)
+}
+
+#[async_trait]
+impl EchoService for HedgingEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.hedged("echo", |target| {
+            let message = message.clone();
+            async move { target.echo(message).await }
+        }).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.hedged("echo_bytes", |target| {
+            let payload = payload.clone();
+            async move { target.echo_bytes(payload).await }
+        }).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.targets[0].echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.targets[0].echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        // Not hedged: a second attempt would count the message twice
+        self.targets[0].echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.targets[0].get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        // Not idempotent: a hedge would schedule the job twice
+        self.targets[0].schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.targets[0].cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.targets[0].get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.targets[0].stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.targets[0].export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        // Not hedged: the data can only be sent once
+        self.targets[0].import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    /// Answers after `latency`, counting calls.
+    struct SlowService {
+        latency: Duration,
+        calls: AtomicU32,
+        reply: &'static str,
+    }
+
+    impl SlowService {
+        fn new(latency: Duration, reply: &'static str) -> Arc<Self> {
+            Arc::new(Self { latency, calls: AtomicU32::new(0), reply })
+        }
+    }
+
+    #[async_trait]
+    impl EchoService for SlowService {
+        async fn echo(&self, _message: Arc<str>) -> Result<Arc<str>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.latency).await;
+            Ok(self.reply.into())
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.latency).await;
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    fn policy() -> HedgingPolicy {
+        HedgingPolicy { delay: Duration::from_millis(10), max_attempts: 2 }
+    }
+
+    #[tokio::test]
+    async fn test_slow_call_is_answered_by_hedge() {
+        let slow = SlowService::new(Duration::from_secs(5), "slow");
+        let fast = SlowService::new(Duration::ZERO, "fast");
+        let service = HedgingEchoService::with_targets(vec![slow.clone(), fast.clone()], policy());
+
+        let response = service.echo("hi".into()).await.unwrap();
+
+        assert_eq!(&*response, "fast");
+        assert_eq!(service.stats().hedges.load(Ordering::Relaxed), 1);
+        assert_eq!(service.stats().hedge_wins.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fast_call_is_not_hedged() {
+        let fast = SlowService::new(Duration::ZERO, "fast");
+        let service = HedgingEchoService::new(fast.clone(), policy());
+
+        service.echo("hi".into()).await.unwrap();
+
+        assert_eq!(fast.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(service.stats().hedges.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reliable_echo_is_never_hedged() {
+        let slow = SlowService::new(Duration::from_millis(50), "slow");
+        let service = HedgingEchoService::new(slow.clone(), policy());
+
+        service.echo_reliable("hi".into(), "key-1".to_string()).await.unwrap();
+
+        assert_eq!(slow.calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,115 @@
+//! Instance Info for the Admin Endpoint (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Server modules register the service that answers `get_info`; the
+//! admin endpoint asks each of them on `GET /info`:
+//!
+//! ```text
+//! echo-server wiring ──register("echo", service)──→ InfoRegistry::global()
+//!                                                        ↓ GET /info
+//!                                              service.get_info() per module
+//! ```
+//!
+//! Register the innermost service: the admin query shouldn't wait in
+//! priority lanes or count against concurrency limits.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use echo_contract::{EchoService, ServerInfo};
+
+/// Cargo features echo-api was built with.
+pub fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "grpc") {
+        features.push("grpc");
+    }
+    if cfg!(feature = "mdns") {
+        features.push("mdns");
+    }
+    if cfg!(feature = "registry-backends") {
+        features.push("registry-backends");
+    }
+    if cfg!(feature = "tower") {
+        features.push("tower");
+    }
+    if cfg!(feature = "encryption") {
+        features.push("encryption");
+    }
+    features
+}
+
+/// Process-wide map of modules to the service describing them.
+#[derive(Default)]
+pub struct InfoRegistry {
+    services: Mutex<BTreeMap<String, Arc<dyn EchoService>>>,
+}
+
+impl InfoRegistry {
+    /// Returns the process-wide registry.
+    pub fn global() -> Arc<InfoRegistry> {
+        static GLOBAL: OnceLock<Arc<InfoRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(InfoRegistry::default())).clone()
+    }
+
+    /// Answers info queries for `module` with `service.get_info()`.
+    pub fn register(&self, module: &str, service: Arc<dyn EchoService>) {
+        self.services.lock().unwrap().insert(module.to_string(), service);
+    }
+
+    /// Info of every registered module; failed queries are skipped.
+    pub async fn collect(&self) -> Vec<ServerInfo> {
+        let services: Vec<_> = self.services.lock().unwrap().values().cloned().collect();
+        let mut infos = Vec::new();
+        for service in services {
+            if let Ok(info) = service.get_info().await {
+                infos.push(info);
+            }
+        }
+        infos
+    }
+
+    /// Renders the report served by the admin endpoint.
+    pub async fn render_text(&self) -> String {
+        let mut out = String::new();
+        for info in self.collect().await {
+            out.push_str(&render_info(&info));
+        }
+        out
+    }
+}
+
+/// Renders `info` as `key: value` lines.
+pub fn render_info(info: &ServerInfo) -> String {
+    format!(
+        "module_id: {}\ninstance_id: {}\nversion: {}\ngit_hash: {}\nuptime: {}s\nfeatures: {}\n",
+        info.module_id,
+        info.instance_id,
+        info.version,
+        info.git_hash,
+        info.uptime.as_secs(),
+        info.features.join(", "),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_render_info() {
+        let info = ServerInfo {
+            module_id: "echo".to_string(),
+            instance_id: "a1b2".to_string(),
+            version: "0.1.0".to_string(),
+            git_hash: "abc123".to_string(),
+            uptime: Duration::from_millis(61_500),
+            features: vec!["grpc".to_string(), "mdns".to_string()],
+        };
+        let text = render_info(&info);
+        assert!(text.contains("instance_id: a1b2\n"));
+        assert!(text.contains("uptime: 61s\n"));
+        assert!(text.ends_with("features: grpc, mdns\n"));
+    }
+}
@@ -0,0 +1,207 @@
+//! Thread-pool Isolation for Direct Closure (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Direct calls normally run on the caller's tokio worker. If the domain
+//! logic is CPU-heavy, that blocks the shared runtime - including the
+//! tasks serving gRPC requests!
+//!
+//! ```text
+//! Client Module (shared runtime)
+//!     ↓ echo()
+//! IsolatedEchoService
+//!     ↓ spawn onto dedicated runtime, await the JoinHandle
+//! EchoServiceImpl (runs on "echo-direct-cpu" threads)
+//! ```
+//!
+//! The caller only awaits a `JoinHandle`, so its worker stays free while
+//! the heavy work runs on the dedicated pool.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::{Error, Result};
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, EchoServiceHandlers, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use tokio::runtime::{Handle, Runtime};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// Thread name used by the dedicated pool.
+pub const ISOLATED_THREAD_NAME: &str = "echo-direct-cpu";
+
+/// Which Direct services run on a dedicated thread pool.
+///
+/// Field names mirror [`EchoServiceHandlers`].
+#[derive(Debug, Clone)]
+pub struct DirectIsolationConfig {
+    /// Mark the echo service as CPU-bound (run it on the dedicated pool).
+    pub service: bool,
+    /// Number of worker threads in the dedicated pool.
+    pub worker_threads: usize,
+}
+
+impl Default for DirectIsolationConfig {
+    fn default() -> Self {
+        Self {
+            service: false,
+            worker_threads: 2,
+        }
+    }
+}
+
+/// Moves CPU-bound services onto a dedicated thread pool.
+pub fn isolate_direct_handlers(
+    handlers: EchoServiceHandlers,
+    config: &DirectIsolationConfig,
+) -> Result<EchoServiceHandlers> {
+    if !config.service {
+        return Ok(handlers);
+    }
+
+    debug!("[DirectIsolation] Running direct echo service on {} dedicated threads",
+        config.worker_threads);
+    let isolated = IsolatedEchoService::new(handlers.service, config.worker_threads)?;
+    Ok(EchoServiceHandlers { service: Arc::new(isolated), ..handlers })
+}
+
+/// Decorator that runs every call on a dedicated tokio runtime.
+pub struct IsolatedEchoService {
+    inner: Arc<dyn EchoService>,
+    handle: Handle,
+    // Kept alive for the lifetime of the decorator (see Drop)
+    runtime: Option<Runtime>,
+}
+
+impl IsolatedEchoService {
+    /// Wraps `inner`, creating a pool with `worker_threads` threads.
+    pub fn new(inner: Arc<dyn EchoService>, worker_threads: usize) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(worker_threads.max(1))
+            .thread_name(ISOLATED_THREAD_NAME)
+            .enable_all()
+            .build()
+            .map_err(|e| Error::Validation {
+                message: format!("Failed to create direct call thread pool: {}", e),
+            })?;
+
+        Ok(Self {
+            inner,
+            handle: runtime.handle().clone(),
+            runtime: Some(runtime),
+        })
+    }
+
+    async fn join<T>(task: JoinHandle<Result<T>>) -> Result<T> {
+        task.await.map_err(|e| Error::Protocol(format!("Isolated direct call failed: {}", e)))?
+    }
+}
+
+impl Drop for IsolatedEchoService {
+    /// # Rust Learning Note
+    ///
+    /// Dropping a `Runtime` blocks until its workers stop, which panics
+    /// inside async code. `shutdown_background()` never blocks, so the
+    /// decorator can be dropped from anywhere.
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+#[async_trait]
+impl EchoService for IsolatedEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let inner = self.inner.clone();
+        Self::join(self.handle.spawn(async move { inner.echo(message).await })).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let inner = self.inner.clone();
+        Self::join(self.handle.spawn(async move { inner.echo_bytes(payload).await })).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let inner = self.inner.clone();
+        Self::join(self.handle.spawn(async move {
+            inner.echo_reliable(message, idempotency_key).await
+        })).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let inner = self.inner.clone();
+        Self::join(self.handle.spawn(async move { inner.echo_file(chunks).await })).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let inner = self.inner.clone();
+        Self::join(self.handle.spawn(async move {
+            inner.echo_with_session(session_id, message).await
+        })).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        // Cheap and not CPU-bound: no need for the pool
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        // Only stores the job: no need for the pool
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        // A store lookup, not CPU-bound: no need for the pool
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Echoes the name of the thread it runs on.
+    struct ThreadNameService;
+
+    #[async_trait]
+    impl EchoService for ThreadNameService {
+        async fn echo(&self, _message: Arc<str>) -> Result<Arc<str>> {
+            Ok(std::thread::current().name().unwrap_or_default().into())
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_on_dedicated_pool() {
+        let isolated = IsolatedEchoService::new(Arc::new(ThreadNameService), 1).unwrap();
+
+        let thread_name = isolated.echo("Hello".into()).await.unwrap();
+        assert_eq!(&*thread_name, ISOLATED_THREAD_NAME);
+    }
+}
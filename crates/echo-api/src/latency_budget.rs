@@ -0,0 +1,366 @@
+//! Client-Side Latency Budgets (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A deadline cancels a call that takes too long; a latency budget only
+//! *notices* it. The client declares how long each method may take, the
+//! gateways time every call against it, and the calls over budget are
+//! counted - the raw material of an SLO ("99% of echoes within 50ms")
+//! without failing a single call:
+//!
+//! ```text
+//! caller: echo("ping")
+//!     ↓
+//! LatencyBudgetEchoService ── LatencyBudgets (echo=50ms,echo_bytes=200ms)
+//!     ↓ timed, attempts and server metadata collected
+//! coalescing → validation → ... → gateway → server
+//!     ↓ reply after 73ms
+//! 73ms > 50ms: WARN with the CallInfo (protocol, endpoint, attempts, server)
+//!     ↓
+//! LatencyBudgetMetrics → GET /metrics
+//!     echo_latency_budget_calls_total{method="echo"}
+//!     echo_latency_budget_violations_total{method="echo"}
+//! ```
+//!
+//! Budgets apply to the five echo methods; failed calls count like
+//! successful ones, since a slow failure is just as slow. The decorator is
+//! the outermost one the gateways add, so it measures what the caller
+//! waits for - coalesced calls included.
+//!
+//! # Rust Learning Note
+//!
+//! The [`CallInfo`] of a violation needs the attempts and server metadata
+//! of the call, which live in task-locals scoped by [`count_attempts`] and
+//! [`collect_response_metadata`]. Those scopes nest: the decorator opens
+//! its own, and `echo_with_info` around it still sees everything.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::{Error, Protocol, Result};
+use tracing::warn;
+use echo_contract::{
+    collect_response_metadata, count_attempts, format_duration, parse_duration, ByteStream, CallInfo, EchoAck,
+    EchoMethod, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+/// How long each echo method may take.
+///
+/// Written as text (flag, startup log) it is a comma-separated list of
+/// `METHOD=DURATION`:
+///
+/// ```text
+/// echo=50ms,echo_bytes=200ms
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyBudgets {
+    budgets: HashMap<EchoMethod, Duration>,
+}
+
+impl LatencyBudgets {
+    /// Creates an empty set: no call is over budget.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows `method` calls to take up to `budget`.
+    pub fn with_budget(mut self, method: EchoMethod, budget: Duration) -> Self {
+        self.budgets.insert(method, budget);
+        self
+    }
+
+    /// Returns the budget of `method`, if it has one.
+    pub fn get(&self, method: EchoMethod) -> Option<Duration> {
+        self.budgets.get(&method).copied()
+    }
+
+    /// Whether no method has a budget.
+    pub fn is_empty(&self) -> bool {
+        self.budgets.is_empty()
+    }
+}
+
+impl FromStr for LatencyBudgets {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let mut budgets = LatencyBudgets::new();
+        if s.is_empty() {
+            return Ok(budgets);
+        }
+        let invalid = |reason: String| Error::Validation { message: format!("Invalid latency budgets '{}': {}", s, reason) };
+        for entry in s.split(',').map(str::trim) {
+            let (name, budget) = entry.split_once('=').ok_or_else(|| invalid("expected METHOD=DURATION".to_string()))?;
+            let method = EchoMethod::from_name(name.trim()).ok_or_else(|| {
+                let names: Vec<_> = EchoMethod::ALL.iter().map(EchoMethod::as_str).collect();
+                invalid(format!("unknown method '{}' (expected {})", name.trim(), names.join(", ")))
+            })?;
+            let budget = parse_duration(budget.trim()).map_err(|_| invalid("expected a duration like 50ms".to_string()))?;
+            if budget.is_zero() {
+                return Err(invalid(format!("the {} budget must be positive", method)));
+            }
+            budgets = budgets.with_budget(method, budget);
+        }
+        Ok(budgets)
+    }
+}
+
+impl fmt::Display for LatencyBudgets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<_> = EchoMethod::ALL
+            .into_iter()
+            .filter_map(|method| self.get(method).map(|budget| format!("{}={}", method, format_duration(budget))))
+            .collect();
+        f.write_str(&entries.join(","))
+    }
+}
+
+/// Budgeted calls and violations by method.
+#[derive(Debug, Default)]
+pub struct LatencyBudgetMetrics {
+    /// `(calls, violations)` by method name.
+    methods: Mutex<BTreeMap<&'static str, (u64, u64)>>,
+}
+
+impl LatencyBudgetMetrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide metrics.
+    pub fn global() -> Arc<LatencyBudgetMetrics> {
+        static GLOBAL: OnceLock<Arc<LatencyBudgetMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(LatencyBudgetMetrics::new())).clone()
+    }
+
+    /// Returns the number of `method` calls timed against a budget.
+    pub fn calls(&self, method: EchoMethod) -> u64 {
+        self.methods.lock().unwrap().get(method.as_str()).map_or(0, |(calls, _)| *calls)
+    }
+
+    /// Returns the number of `method` calls over their budget.
+    pub fn violations(&self, method: EchoMethod) -> u64 {
+        self.methods.lock().unwrap().get(method.as_str()).map_or(0, |(_, violations)| *violations)
+    }
+
+    fn record(&self, method: EchoMethod, violated: bool) {
+        let mut methods = self.methods.lock().unwrap();
+        let (calls, violations) = methods.entry(method.as_str()).or_default();
+        *calls += 1;
+        *violations += u64::from(violated);
+    }
+
+    /// Renders the counters in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let methods = self.methods.lock().unwrap();
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP echo_latency_budget_calls_total Client calls timed against a latency budget, by method");
+        let _ = writeln!(out, "# TYPE echo_latency_budget_calls_total counter");
+        for (method, (calls, _)) in methods.iter() {
+            let _ = writeln!(out, "echo_latency_budget_calls_total{{method=\"{}\"}} {}", method, calls);
+        }
+        let _ = writeln!(out, "# HELP echo_latency_budget_violations_total Client calls slower than their latency budget, by method");
+        let _ = writeln!(out, "# TYPE echo_latency_budget_violations_total counter");
+        for (method, (_, violations)) in methods.iter() {
+            let _ = writeln!(out, "echo_latency_budget_violations_total{{method=\"{}\"}} {}", method, violations);
+        }
+        out
+    }
+}
+
+/// Decorator timing echo calls against [`LatencyBudgets`].
+pub struct LatencyBudgetEchoService {
+    inner: Arc<dyn EchoService>,
+    budgets: LatencyBudgets,
+    /// Transport and destination of `inner`, for the [`CallInfo`] of violations.
+    protocol_used: Protocol,
+    endpoint: String,
+    metrics: Arc<LatencyBudgetMetrics>,
+}
+
+impl LatencyBudgetEchoService {
+    /// Wraps `inner` (reached via `protocol_used` at `endpoint`),
+    /// recording into the global [`LatencyBudgetMetrics`].
+    pub fn new(inner: Arc<dyn EchoService>, budgets: LatencyBudgets, protocol_used: Protocol, endpoint: String) -> Self {
+        Self::with_metrics(inner, budgets, protocol_used, endpoint, LatencyBudgetMetrics::global())
+    }
+
+    /// Wraps `inner`, recording into `metrics`.
+    pub fn with_metrics(
+        inner: Arc<dyn EchoService>,
+        budgets: LatencyBudgets,
+        protocol_used: Protocol,
+        endpoint: String,
+        metrics: Arc<LatencyBudgetMetrics>,
+    ) -> Self {
+        Self { inner, budgets, protocol_used, endpoint, metrics }
+    }
+
+    /// Runs `call`, reporting it if it took longer than `method`'s budget.
+    async fn timed<T>(&self, method: EchoMethod, call: impl Future<Output = Result<T>>) -> Result<T> {
+        let Some(budget) = self.budgets.get(method) else {
+            return call.await;
+        };
+        let started = Instant::now();
+        let ((result, attempts), server_metadata) = collect_response_metadata(count_attempts(call)).await;
+        let latency = started.elapsed();
+        let violated = latency > budget;
+        self.metrics.record(method, violated);
+        if violated {
+            let info = CallInfo {
+                protocol_used: self.protocol_used,
+                endpoint: self.endpoint.clone(),
+                latency,
+                attempts: attempts.max(1),
+                server_metadata,
+                protocol_race: None,
+            };
+            warn!("[LatencyBudget] {} took {:?}, over its {:?} budget ({}): {:?}",
+                method, latency, budget, if result.is_ok() { "ok" } else { "failed" }, info);
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl EchoService for LatencyBudgetEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.timed(EchoMethod::Echo, self.inner.echo(message)).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.timed(EchoMethod::EchoBytes, self.inner.echo_bytes(payload)).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.timed(EchoMethod::EchoReliable, self.inner.echo_reliable(message, idempotency_key)).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.timed(EchoMethod::EchoFile, self.inner.echo_file(chunks)).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.timed(EchoMethod::EchoWithSession, self.inner.echo_with_session(session_id, message)).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::{attach_response_metadata, record_attempt};
+
+    /// Echoes after `delay`, as one attempt of an instance tagging its replies.
+    struct SlowService {
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl EchoService for SlowService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            record_attempt();
+            attach_response_metadata("instance-id", "a1");
+            tokio::time::sleep(self.delay).await;
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, _payload: Bytes) -> Result<Bytes> {
+            tokio::time::sleep(self.delay).await;
+            Err(Error::Protocol("not supported by mock".to_string()))
+        }
+    }
+
+    fn budgeted(delay: Duration, budgets: &str, metrics: &Arc<LatencyBudgetMetrics>) -> LatencyBudgetEchoService {
+        LatencyBudgetEchoService::with_metrics(
+            Arc::new(SlowService { delay }),
+            budgets.parse().unwrap(),
+            Protocol::Direct,
+            "in-process".to_string(),
+            metrics.clone(),
+        )
+    }
+
+    #[test]
+    fn test_parse_budgets() {
+        let budgets: LatencyBudgets = "echo=50ms, echo_bytes=2s".parse().unwrap();
+        assert_eq!(budgets.get(EchoMethod::Echo), Some(Duration::from_millis(50)));
+        assert_eq!(budgets.get(EchoMethod::EchoFile), None);
+        assert_eq!(budgets.to_string().parse::<LatencyBudgets>().unwrap(), budgets);
+        assert!("".parse::<LatencyBudgets>().unwrap().is_empty());
+
+        for invalid in ["echo", "echo=fast", "echo=0ms", "get_info=50ms"] {
+            assert!(invalid.parse::<LatencyBudgets>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_counts_calls_over_budget() {
+        let metrics = Arc::new(LatencyBudgetMetrics::new());
+        let fast = budgeted(Duration::ZERO, "echo=1s", &metrics);
+        let slow = budgeted(Duration::from_millis(30), "echo=10ms,echo_bytes=10ms", &metrics);
+
+        assert_eq!(&*fast.echo("hi".into()).await.unwrap(), "hi");
+        assert_eq!(&*slow.echo("hi".into()).await.unwrap(), "hi");
+        // Slow failures are violations too
+        assert!(slow.echo_bytes(Bytes::from_static(b"hi")).await.is_err());
+
+        assert_eq!(metrics.calls(EchoMethod::Echo), 2);
+        assert_eq!(metrics.violations(EchoMethod::Echo), 1);
+        assert_eq!(metrics.violations(EchoMethod::EchoBytes), 1);
+        let rendered = metrics.render_prometheus();
+        assert!(rendered.contains("echo_latency_budget_violations_total{method=\"echo\"} 1"));
+        assert!(rendered.contains("echo_latency_budget_calls_total{method=\"echo_bytes\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn test_caller_still_sees_attempts_and_metadata() {
+        let metrics = Arc::new(LatencyBudgetMetrics::new());
+        let service = budgeted(Duration::ZERO, "echo=1s", &metrics);
+
+        // As echo_with_info does around the decorator
+        let ((reply, attempts), metadata) =
+            collect_response_metadata(count_attempts(service.echo("hi".into()))).await;
+
+        assert!(reply.is_ok());
+        assert_eq!(attempts, 1);
+        assert_eq!(metadata["instance-id"], "a1");
+        assert_eq!(metrics.calls(EchoMethod::Echo), 1);
+        assert_eq!(metrics.calls(EchoMethod::EchoBytes), 0);
+    }
+}
@@ -10,6 +10,72 @@
 //! 1. ✅ `EchoServiceGatewaysImpl` - Reusable gateway provider
 //! 2. ✅ `EchoHandlersRegistrar` - Reusable handler registrar
 //! 3. ✅ `echo_direct_closure_enable` - Direct closure enabler
+//! 4. ✅ `ConcurrencyLimitedEchoService` - Direct-path concurrency limits
+//! 5. ✅ `IsolatedEchoService` - Dedicated thread pool for CPU-bound direct calls
+//! 6. ✅ `DeadlineEchoService` - Per-call deadlines for direct calls
+//! 7. ✅ `SizeMetricsEchoService` - Request/response size metrics by protocol
+//! 8. ✅ `PanicGuardEchoService`/`PanicGuardModule` - Panic isolation
+//! 9. ✅ `TaskRegistry` - Per-module background task tracking
+//! 10. ✅ `BoundEndpoints` - Actually bound ports (port 0, multiple addresses)
+//! 11. ✅ `HedgingEchoService` - Hedged gRPC calls for tail latency
+//! 12. ✅ `PriorityEchoService` - Weighted priority lanes in front of the service
+//! 13. ✅ `AdaptiveConcurrencyEchoService` - Latency-driven concurrency limits
+//! 14. ✅ `RuntimeAssignments` - Protocol vs module runtime assignment
+//! 15. ✅ `EchoEventBus` - Echo activity notifications (Direct and gRPC)
+//! 16. ✅ `DependencyRegistry` - Declared inter-module dependencies, validated before start
+//! 17. ✅ `RecordingEchoService` - Traffic capture to an `AuditSink` (NDJSON), replay helpers
+//! 18. ✅ `InMemoryServiceRegistry` - Registry store for local development (`--registry inmem`)
+//! 19. ✅ `MdnsAdvertisement`/`Discovery` - mDNS discovery for LAN demos (no registry)
+//! 20. ✅ `RegistryBackend` - HSU registry, Consul, etcd or in-memory, selected by the registry URL
+//! 21. ✅ `EchoTowerService`/`TowerEchoService` - `tower::Service` adapters for standard middleware
+//! 22. ✅ `TypedServiceClient` - `client.call(|svc| svc.echo(msg))` without gateways or protocols
+//! 23. ✅ `HealthRegistry` - Named module checks (server self-test), served as `GET /health`
+//! 24. ✅ `InfoRegistry` - Instance identity and build info per module, served as `GET /info`
+//! 25. ✅ `MaintenanceRegistry` - Background task runs and manual operations, served as `/maintenance`
+//! 26. ✅ `ByteQuotaEchoService` - Per-caller byte accounting and quotas, served as `/quotas`
+//! 27. ✅ `SlowStartEchoService` - Admission limit ramping up after each module start
+//! 28. ✅ `CoalescingEchoService` - Identical in-flight client echoes share one request
+//! 29. ✅ `BatchingEchoService` - Client echoes gathered into `EchoBatch` requests
+//! 30. ✅ Protocol racing - `Auto` keeps whichever of gRPC and JSON/HTTP answers first
+//! 31. ✅ `ValidatingEchoService` - Client-side reply checks failing as `INVALID_RESPONSE`
+//! 32. ✅ `EncryptingEchoService` / `DecryptingEchoService` - Payload encryption per module pair
+//! 33. ✅ `MaintenanceModeEchoService` - Maintenance mode refusing new calls as `UNAVAILABLE`
+//! 34. ✅ `TrafficSplitEchoService` - Canary share of calls to a second implementation, metrics per variant
+//! 35. ✅ `MirroringEchoService` - Shadow copies of client echoes to a second endpoint, replies compared
+//! 36. ✅ `diff_observations` - Cross-target comparison of replies, errors and metadata (`echo-diff`)
+//! 37. ✅ `ChaosEchoService` - Injected delays, errors and connection drops, toggled at runtime
+//! 38. ✅ `LatencyBudgetEchoService` - Per-method client latency budgets, violations logged and counted
+//! 39. ✅ `CapabilitiesRegistry` - Per-protocol gateway capabilities, served as `GET /capabilities`
+//! 40. ✅ `ServiceMap` - Gateways by contract type, so providers take new domain services unedited
+//! 41. ✅ `WiringDescriptor` - Lints a module's service IDs and protocols across declaration, registrar and gateways (tests)
+//! 42. ✅ `until_cancelled` - Module starts cut short by Ctrl-C instead of waiting out timeouts
+//! 43. ✅ `StartupCoordinator` - Independent modules started concurrently, in dependency order, with a report
+//! 44. ✅ `WarmStandby` - Second transport kept connected, unavailable calls resent over it at once
+//! 45. ✅ `ManagedUnit` - Child process supervised over the gRPC health service, restarted on crash
+//! 46. ✅ `PipeChannel` - Near-direct calls to a supervised child over its stdin/stdout, without TCP
+//! 47. ✅ `RediscoveringEchoService` - Discovered instance forgotten once it refuses calls for maintenance
+//!
+//! ## Cargo Features
+//!
+//! | Feature             | Default | Enables                                           | Pulls in                           |
+//! |---------------------|---------|---------------------------------------------------|------------------------------------|
+//! | `grpc`              | ✅      | gRPC gateways, handlers, health, `ManagedUnit`    | echo-api-grpc, tonic, tonic-health |
+//! | `mdns`              | ✅      | `MdnsAdvertisement`, `Discovery::Mdns`            | mdns-sd                            |
+//! | `registry-backends` | ✅      | HTTP/Consul/etcd `RegistryBackend`s               | hyper client, base64               |
+//! | `tower`             | ✅      | `EchoTowerService`, `TowerEchoService`            | tower                              |
+//! | `encryption`        | ✅      | `PayloadKey`, payload encryption decorators       | chacha20poly1305                   |
+//!
+//! An application embedding only Direct echo uses
+//! `default-features = false`: gateways then hand out Direct services
+//! only and the registrar rejects gRPC servers. Metrics, limits, capture
+//! and the in-memory registry have no dependencies of their own and are
+//! always built. (There is no HTTP/WebSocket adapter or auth layer in
+//! this workspace yet, hence no features for them.)
+//!
+//! The features trim what echo-api itself compiles. `hsu-module-api` and
+//! the client/monitor crates (channel pool) still depend on tonic, so a
+//! binary only sheds it once those are gated as well. echo-bootstrap and
+//! echo-registry already depend on echo-api without default features.
 //!
 //! ## Why Separate from echo-api-grpc?
 //!
@@ -25,8 +91,150 @@
 pub mod gateways;
 pub mod handlers;
 pub mod direct_closure;
+pub mod concurrency;
+pub mod isolation;
+pub mod deadline;
+pub mod metrics;
+pub mod panic;
+pub mod tasks;
+pub mod endpoints;
+pub mod hedging;
+pub mod priority;
+pub mod quota;
+pub mod slow_start;
+pub mod coalescing;
+pub mod batching;
+pub mod racing;
+pub mod rediscovery;
+pub mod validation;
+pub mod adaptive;
+pub mod runtimes;
+pub mod events;
+pub mod dependencies;
+pub mod capture;
+pub mod diff;
+pub mod registry;
+#[cfg(feature = "mdns")]
+pub mod mdns;
+pub mod registry_backend;
+#[cfg(feature = "registry-backends")]
+pub mod remote_registries;
+#[cfg(feature = "tower")]
+pub mod tower_adapter;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod typed_client;
+pub mod health;
+pub mod info;
+pub mod maintenance;
+pub mod maintenance_mode;
+pub mod traffic_split;
+pub mod mirroring;
+pub mod chaos;
+pub mod latency_budget;
+pub mod capabilities;
+pub mod service_map;
+#[cfg(any(test, feature = "wiring-lint"))]
+pub mod wiring_lint;
+pub mod cancellation;
+pub mod startup;
+pub mod warm_standby;
+pub mod pipe;
+#[cfg(feature = "grpc")]
+pub mod grpc_health;
+#[cfg(feature = "grpc")]
+pub mod managed_unit;
 
-pub use gateways::{EchoServiceGatewaysImpl, new_echo_service_gateways};
-pub use handlers::{EchoHandlersRegistrar, new_echo_handlers_registrar};
-pub use direct_closure::echo_direct_closure_enabler;
+pub use gateways::{
+    Discovery, EchoServiceGatewaysImpl, GatewayOptions, echo_gateway_service_ids, gateway_capabilities,
+    new_echo_service_gateways, new_echo_service_gateways_with_options, new_standalone_echo_service_gateways,
+};
+pub use capabilities::CapabilitiesRegistry;
+pub use service_map::ServiceMap;
+#[cfg(any(test, feature = "wiring-lint"))]
+pub use wiring_lint::{WiringDescriptor, WiringIssue};
+pub use cancellation::{CancellationToken, cancel_on_ctrl_c, check_cancelled, until_cancelled};
+pub use startup::{
+    CoordinatedModule, ModuleStartup, StartOutcome, StartupCoordinator, StartupReport, DEFAULT_MODULE_START_TIMEOUT,
+    START_UNWIND_TIMEOUT,
+};
+#[cfg(feature = "grpc")]
+pub use gateways::{grpc_echo_service, MirrorConfig, MirrorTarget};
+pub use handlers::{
+    EchoHandlersRegistrar, HandlersRegistrarFn, JsonTranscoding, ECHO_HANDLER_PROTOCOLS, echo_handler_service_ids,
+    new_echo_handlers_registrar,
+};
+pub use direct_closure::{echo_direct_closure_enabler, DirectClosureEnablerFn};
+pub use concurrency::{ConcurrencyLimitedEchoService, DirectConcurrencyLimits, limit_direct_handlers};
+pub use isolation::{IsolatedEchoService, DirectIsolationConfig, isolate_direct_handlers};
+pub use deadline::DeadlineEchoService;
+pub use metrics::{DurationHistogram, SizeMetrics, SizeMetricsEchoService, SizeLabels, SizeSeries, DURATION_BUCKETS};
+pub use panic::{PanicGuardEchoService, PanicGuardModule, PanicPolicy, PanicRegistry, catch_panic};
+pub use tasks::{TaskInfo, TaskRegistry, spawn_tracked};
+pub use endpoints::{BoundEndpoint, BoundEndpoints};
+pub use hedging::{HedgingEchoService, HedgingPolicy, HedgingStats};
+pub use adaptive::{
+    AdaptiveConcurrencyConfig, AdaptiveConcurrencyEchoService, AdaptiveConcurrencyMetrics,
+    AimdConfig, AimdController, ConcurrencyController, ControllerKind,
+    GradientConfig, GradientController, Sample,
+};
+pub use capture::{
+    AuditSink, CaptureRecord, NdjsonAuditSink, RecordingEchoService,
+    read_capture, replay_call, responses_match,
+};
+pub use diff::{
+    DiffOptions, Divergence, Observation, diff_observations, observe_call, DEFAULT_IGNORED_METADATA,
+};
+pub use dependencies::{
+    DependencyRegistry, ModuleDependencies, ServiceRef, validate_module_dependencies,
+};
+#[cfg(feature = "mdns")]
+pub use mdns::{MdnsAdvertisement, ECHO_SERVICE_TYPE};
+pub use registry_backend::{
+    RegistryBackend, HEARTBEAT_INTERVAL, REGISTRATION_TTL, framework_config, grpc_api, module_registry_backend,
+    registry_backend,
+};
+#[cfg(feature = "registry-backends")]
+pub use remote_registries::{ConsulRegistryBackend, EtcdRegistryBackend, HttpRegistryBackend};
+pub use registry::{InMemoryServiceRegistry, RegisteredApi, protocol_name};
+#[cfg(feature = "encryption")]
+pub use encryption::{DecryptingEchoService, EncryptingEchoService, PayloadKey, PayloadKeyring};
+#[cfg(feature = "tower")]
+pub use tower_adapter::{EchoRequest, EchoResponse, EchoTowerService, TowerEchoService};
+pub use typed_client::{ServiceSource, TypedServiceClient};
+pub use health::{HealthCheck, HealthRegistry, HealthStatus};
+pub use info::{InfoRegistry, enabled_features, render_info};
+pub use maintenance::{MaintenanceOperation, MaintenanceRegistry, MaintenanceStats};
+pub use maintenance_mode::{
+    MaintenanceMode, MaintenanceModeEchoService, MaintenanceModePermit, register_maintenance_operations,
+    ENTER_MAINTENANCE_OPERATION, LEAVE_MAINTENANCE_OPERATION,
+};
+pub use events::{EchoEventBus, EventEmittingEchoService};
+pub use runtimes::{RuntimeAssignment, RuntimeAssignments, RuntimeRole};
+pub use priority::{LanePermit, PriorityEchoService, PriorityLanesConfig, PriorityMetrics, PriorityScheduler};
+pub use quota::{
+    ByteLedger, ByteQuotaConfig, ByteQuotaEchoService, CallerUsage, ANONYMOUS_CALLER, OVERFLOW_CALLER,
+};
+pub use slow_start::{SlowStart, SlowStartConfig, SlowStartEchoService, SlowStartPermit};
+pub use coalescing::{CoalescingEchoService, CoalescingMetrics, InFlightEchoes, without_coalescing};
+pub use batching::{BatchingConfig, BatchingEchoService, BatchingMetrics, EchoBatchSender, EchoBatcher};
+pub use racing::RacingEchoService;
+pub use rediscovery::RediscoveringEchoService;
+pub use validation::{ReplyCheck, ResponseValidator, ValidatingEchoService, ValidationMetrics};
+pub use traffic_split::{
+    TrafficSplit, TrafficSplitEchoService, TrafficSplitMetrics, VariantStats, register_traffic_split_operation,
+    TRAFFIC_SPLIT_OPERATION, VARIANT_METADATA_KEY,
+};
+pub use mirroring::{MirrorMetrics, MirrorShadow, MirrorStats, MirroringEchoService};
+pub use chaos::{
+    Chaos, ChaosConfig, ChaosEchoService, ChaosMetrics, register_chaos_operation, CHAOS_OPERATION, CONNECTION_DROPPED,
+    INJECTED_ERROR,
+};
+pub use latency_budget::{LatencyBudgetEchoService, LatencyBudgetMetrics, LatencyBudgets};
+pub use warm_standby::{FallbackEchoService, WarmStandby, WarmStandbyConfig};
+pub use pipe::{PipeChannel, PipeEchoService, serve_pipe};
+#[cfg(feature = "grpc")]
+pub use grpc_health::{EchoHealthService, check_grpc_health};
+#[cfg(feature = "grpc")]
+pub use managed_unit::{ManagedUnit, ManagedUnitConfig, RestartBackoff};
 
@@ -0,0 +1,204 @@
+//! Maintenance Tasks and Operations (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Long-running modules do housekeeping in the background (pruning old
+//! history, compacting a database). They report each run here, and may
+//! register operations an operator can trigger by hand through the admin
+//! endpoint:
+//!
+//! ```text
+//! retention task ──record_run("echo", "history-retention", Ok(120))──→ MaintenanceRegistry::global()
+//!                                                                          ↓ GET /metrics
+//!                                          echo_maintenance_items_total{module="echo",task="history-retention"} 120
+//!
+//! EchoServerModule::start() ──register_operation("echo", "purge-history", op)
+//! POST /maintenance/echo/purge-history ──→ op("") ──→ 200 "pruned 120 entries"
+//! POST /maintenance/echo/enter-maintenance -d 'disk swap' ──→ op("disk swap")
+//! ```
+//!
+//! Run statistics outlive the module (Prometheus counters never go
+//! down); operations are removed with [`MaintenanceRegistry::clear`] when
+//! the module stops, so nothing runs against a stopped module.
+//!
+//! ## Comparison with Golang
+//!
+//! | Golang                                   | Rust                                  |
+//! |------------------------------------------|---------------------------------------|
+//! | `map[string]func(ctx) (string, error)`   | `BTreeMap<_, MaintenanceOperation>`   |
+//! | `http.HandleFunc("/maintenance/", ...)`  | a route in echo-bootstrap's admin     |
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+use futures::future::BoxFuture;
+use hsu_common::Result;
+
+use crate::metrics::escape_label;
+
+/// A manually triggered maintenance operation; resolves to a short
+/// human-readable summary.
+///
+/// The argument is the request body (empty if none); most operations
+/// ignore it.
+pub type MaintenanceOperation = Arc<dyn Fn(String) -> BoxFuture<'static, Result<String>> + Send + Sync>;
+
+/// Accumulated runs of one background task.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaintenanceStats {
+    /// Completed runs, failed ones included.
+    pub runs: u64,
+    /// Runs that returned an error.
+    pub failures: u64,
+    /// Items the successful runs processed (e.g. pruned rows).
+    pub items: u64,
+    /// When the last run finished.
+    pub last_run: Option<SystemTime>,
+    /// Error of the last run, if it failed.
+    pub last_error: Option<String>,
+}
+
+/// Process-wide record of maintenance tasks and operations.
+#[derive(Default)]
+pub struct MaintenanceRegistry {
+    tasks: Mutex<BTreeMap<(String, String), MaintenanceStats>>,
+    operations: Mutex<BTreeMap<(String, String), MaintenanceOperation>>,
+}
+
+impl MaintenanceRegistry {
+    /// Returns the process-wide registry.
+    pub fn global() -> Arc<MaintenanceRegistry> {
+        static GLOBAL: OnceLock<Arc<MaintenanceRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(MaintenanceRegistry::default())).clone()
+    }
+
+    /// Records a run of `module`'s `task`, with the number of items it processed.
+    pub fn record_run(&self, module: &str, task: &str, result: &Result<u64>) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let stats = tasks.entry((module.to_string(), task.to_string())).or_default();
+        stats.runs += 1;
+        stats.last_run = Some(SystemTime::now());
+        match result {
+            Ok(items) => {
+                stats.items += items;
+                stats.last_error = None;
+            }
+            Err(e) => {
+                stats.failures += 1;
+                stats.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Runs of `module`'s `task` so far.
+    pub fn stats(&self, module: &str, task: &str) -> Option<MaintenanceStats> {
+        self.tasks.lock().unwrap().get(&(module.to_string(), task.to_string())).cloned()
+    }
+
+    /// Makes `operation` available as `module`'s `name`, replacing a previous one.
+    pub fn register_operation(&self, module: &str, name: &str, operation: MaintenanceOperation) {
+        self.operations.lock().unwrap().insert((module.to_string(), name.to_string()), operation);
+    }
+
+    /// Registered operations as `module/name`, ordered.
+    pub fn operations(&self) -> Vec<String> {
+        self.operations.lock().unwrap().keys().map(|(module, name)| format!("{}/{}", module, name)).collect()
+    }
+
+    /// Runs `module`'s operation `name` with `argument`; `None` if there
+    /// is no such operation.
+    pub async fn run_operation(&self, module: &str, name: &str, argument: String) -> Option<Result<String>> {
+        // Not holding the lock while the operation runs
+        let operation = self.operations.lock().unwrap().get(&(module.to_string(), name.to_string())).cloned()?;
+        Some(operation(argument).await)
+    }
+
+    /// Removes the operations of `module` (e.g. when it stops); its run
+    /// statistics stay.
+    pub fn clear(&self, module: &str) {
+        self.operations.lock().unwrap().retain(|(owner, _), _| owner != module);
+    }
+
+    /// Renders the report served by the admin endpoint.
+    pub fn render_text(&self) -> String {
+        let mut out = String::from("operations:\n");
+        for operation in self.operations() {
+            let _ = writeln!(out, "  POST /maintenance/{}", operation);
+        }
+        out.push_str("tasks:\n");
+        for ((module, task), stats) in self.tasks.lock().unwrap().iter() {
+            let last = match &stats.last_error {
+                Some(error) => format!("last run failed: {}", error),
+                None => "last run ok".to_string(),
+            };
+            let _ = writeln!(out, "  {}/{}: {} runs, {} failed, {} items, {}",
+                module, task, stats.runs, stats.failures, stats.items, last);
+        }
+        out
+    }
+
+    /// Renders the task statistics in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let tasks = self.tasks.lock().unwrap();
+        let mut out = String::new();
+        let mut counter = |name: &str, help: &str, value: fn(&MaintenanceStats) -> u64| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for ((module, task), stats) in tasks.iter() {
+                let _ = writeln!(out, "{}{{module=\"{}\",task=\"{}\"}} {}",
+                    name, escape_label(module), escape_label(task), value(stats));
+            }
+        };
+        counter("echo_maintenance_runs_total", "Completed maintenance task runs", |s| s.runs);
+        counter("echo_maintenance_failures_total", "Maintenance task runs that failed", |s| s.failures);
+        counter("echo_maintenance_items_total", "Items processed by maintenance tasks (e.g. pruned rows)", |s| s.items);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hsu_common::Error;
+
+    #[test]
+    fn test_runs_are_counted() {
+        let registry = MaintenanceRegistry::default();
+        registry.record_run("echo", "history-retention", &Ok(120));
+        registry.record_run("echo", "history-retention", &Err(Error::Protocol("disk full".to_string())));
+
+        let stats = registry.stats("echo", "history-retention").unwrap();
+        assert_eq!((stats.runs, stats.failures, stats.items), (2, 1, 120));
+        assert!(stats.last_error.unwrap().contains("disk full"));
+
+        let metrics = registry.render_prometheus();
+        assert!(metrics.contains("echo_maintenance_items_total{module=\"echo\",task=\"history-retention\"} 120\n"));
+        assert!(metrics.contains("echo_maintenance_failures_total{module=\"echo\",task=\"history-retention\"} 1\n"));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        let registry = MaintenanceRegistry::default();
+        registry.record_run("echo \"east\"", "purge\\all", &Ok(1));
+
+        let metrics = registry.render_prometheus();
+        assert!(metrics.contains("echo_maintenance_runs_total{module=\"echo \\\"east\\\"\",task=\"purge\\\\all\"} 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_operations_run_until_cleared() {
+        let registry = MaintenanceRegistry::default();
+        registry.register_operation("echo", "purge", Arc::new(|argument| {
+            Box::pin(async move { Ok(format!("pruned 3{}", argument)) })
+        }));
+        assert_eq!(registry.operations(), vec!["echo/purge"]);
+
+        assert_eq!(registry.run_operation("echo", "purge", String::new()).await.unwrap().unwrap(), "pruned 3");
+        assert_eq!(registry.run_operation("echo", "purge", " rows".into()).await.unwrap().unwrap(), "pruned 3 rows");
+        assert!(registry.run_operation("echo", "other", String::new()).await.is_none());
+
+        registry.clear("echo");
+        assert!(registry.run_operation("echo", "purge", String::new()).await.is_none());
+    }
+}
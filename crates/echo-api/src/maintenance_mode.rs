@@ -0,0 +1,343 @@
+//! Maintenance Mode (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Before an upgrade or a disk swap the operator takes an instance out of
+//! rotation without stopping it: new calls are refused with a typed
+//! `UNAVAILABLE: maintenance: <reason>` error, calls already running
+//! finish, and health and the registry entry tell clients to go elsewhere:
+//!
+//! ```text
+//! POST /maintenance/echo/enter-maintenance -d 'disk swap'
+//!     ↓
+//! MaintenanceMode::enter("disk swap") ──→ subscribers (module watcher)
+//!     │                                      ├─ HealthRegistry "maintenance" → Failing (GET /health 503)
+//!     │                                      └─ registry entry republished with `maintenance`
+//!     ↓
+//! Direct / gRPC handler
+//!     ↓
+//! MaintenanceModeEchoService ── active → in_maintenance(reason)   (UNAVAILABLE)
+//!     ↓                       └─ else  → admitted, counted in flight
+//! EchoServiceImpl
+//! ```
+//!
+//! `enter-maintenance` answers once the in-flight calls have drained (or
+//! the drain timeout passed), so a deploy script can wait on it before
+//! stopping the process. `leave-maintenance` reverses everything.
+//!
+//! Streaming calls count as in flight until their stream is handed out,
+//! not until it is consumed. `get_info` is always answered, so an
+//! operator can still identify the instance.
+//!
+//! # Rust Learning Note
+//!
+//! The reason lives in a `tokio::sync::watch` channel: the decorator
+//! reads it with `borrow()` on every call (no await, no lock held across
+//! one), and the module's watcher task wakes on `changed()` without
+//! polling.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::Result;
+use tokio::sync::{watch, Notify};
+use tracing::info;
+use echo_contract::{
+    in_maintenance, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use crate::maintenance::MaintenanceRegistry;
+
+/// Operation that enters maintenance mode (body: the reason).
+pub const ENTER_MAINTENANCE_OPERATION: &str = "enter-maintenance";
+
+/// Operation that leaves maintenance mode.
+pub const LEAVE_MAINTENANCE_OPERATION: &str = "leave-maintenance";
+
+/// Reason used when `enter-maintenance` is posted without a body.
+pub const DEFAULT_MAINTENANCE_REASON: &str = "maintenance mode";
+
+/// Maintenance switch of a module, with its in-flight call count.
+///
+/// Shared between the module (which toggles it and publishes the state)
+/// and the service decorator (which enforces it).
+pub struct MaintenanceMode {
+    reason: watch::Sender<Option<String>>,
+    in_flight: AtomicUsize,
+    drained: Notify,
+}
+
+/// An admitted call; counts as in flight until dropped.
+pub struct MaintenanceModePermit {
+    mode: Arc<MaintenanceMode>,
+}
+
+impl Drop for MaintenanceModePermit {
+    fn drop(&mut self) {
+        if self.mode.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.mode.drained.notify_waiters();
+        }
+    }
+}
+
+impl MaintenanceMode {
+    /// Creates a switch in serving state.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            reason: watch::Sender::new(None),
+            in_flight: AtomicUsize::new(0),
+            drained: Notify::new(),
+        })
+    }
+
+    /// Enters maintenance mode (or replaces the reason if already in it).
+    pub fn enter(&self, reason: impl Into<String>) {
+        let reason = reason.into();
+        info!("[MaintenanceMode] Entering maintenance mode: {}", reason);
+        self.reason.send_replace(Some(reason));
+    }
+
+    /// Leaves maintenance mode; returns `false` if it was not active.
+    pub fn leave(&self) -> bool {
+        let was_active = self.reason.send_replace(None).is_some();
+        if was_active {
+            info!("[MaintenanceMode] Leaving maintenance mode");
+        }
+        was_active
+    }
+
+    /// Returns the reason while in maintenance mode.
+    pub fn reason(&self) -> Option<String> {
+        self.reason.borrow().clone()
+    }
+
+    /// Returns `true` while in maintenance mode.
+    pub fn is_active(&self) -> bool {
+        self.reason.borrow().is_some()
+    }
+
+    /// Returns the number of admitted calls still running.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Returns a receiver that sees every change of the reason.
+    pub fn subscribe(&self) -> watch::Receiver<Option<String>> {
+        self.reason.subscribe()
+    }
+
+    /// Admits a call, or rejects it with [`echo_contract::in_maintenance`].
+    pub fn try_admit(self: &Arc<Self>) -> Result<MaintenanceModePermit> {
+        // Counted before the check: a drain that starts after `enter` then
+        // either sees this call or the call sees the reason
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let permit = MaintenanceModePermit { mode: self.clone() };
+        match self.reason() {
+            Some(reason) => Err(in_maintenance(reason)),
+            None => Ok(permit),
+        }
+    }
+
+    /// Waits until no admitted call is running; returns `false` if calls
+    /// were still running after `timeout`.
+    pub async fn wait_drained(&self, timeout: Duration) -> bool {
+        let drained = async {
+            loop {
+                let notified = self.drained.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(timeout, drained).await.is_ok()
+    }
+}
+
+/// Registers the `enter-maintenance` and `leave-maintenance` operations
+/// of `module` with [`MaintenanceRegistry::global`].
+///
+/// `enter-maintenance` takes the reason as its argument and answers once
+/// the calls in flight have finished, waiting at most `drain_timeout`.
+pub fn register_maintenance_operations(module: &str, mode: Arc<MaintenanceMode>, drain_timeout: Duration) {
+    let registry = MaintenanceRegistry::global();
+
+    let entering = mode.clone();
+    registry.register_operation(module, ENTER_MAINTENANCE_OPERATION, Arc::new(move |reason: String| {
+        let mode = entering.clone();
+        Box::pin(async move {
+            let reason = if reason.is_empty() { DEFAULT_MAINTENANCE_REASON.to_string() } else { reason };
+            mode.enter(reason.clone());
+            Ok(if mode.wait_drained(drain_timeout).await {
+                format!("in maintenance mode ({}), no calls in flight", reason)
+            } else {
+                format!("in maintenance mode ({}), {} calls still in flight after {:?}",
+                    reason, mode.in_flight(), drain_timeout)
+            })
+        })
+    }));
+
+    registry.register_operation(module, LEAVE_MAINTENANCE_OPERATION, Arc::new(move |_| {
+        let mode = mode.clone();
+        Box::pin(async move {
+            Ok(if mode.leave() { "serving".to_string() } else { "not in maintenance mode".to_string() })
+        })
+    }));
+}
+
+/// Decorator refusing new calls while its [`MaintenanceMode`] is active.
+pub struct MaintenanceModeEchoService {
+    inner: Arc<dyn EchoService>,
+    mode: Arc<MaintenanceMode>,
+}
+
+impl MaintenanceModeEchoService {
+    /// Wraps `inner`, admitting calls through `mode`.
+    pub fn new(inner: Arc<dyn EchoService>, mode: Arc<MaintenanceMode>) -> Self {
+        Self { inner, mode }
+    }
+}
+
+#[async_trait]
+impl EchoService for MaintenanceModeEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.echo(message).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.echo_bytes(payload).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        let _permit = self.mode.try_admit()?;
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    /// Echoes once released, so a call can be held in flight.
+    struct GatedService {
+        gate: tokio::sync::Mutex<Option<oneshot::Receiver<()>>>,
+    }
+
+    #[async_trait]
+    impl EchoService for GatedService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            let gate = self.gate.lock().await.take();
+            if let Some(gate) = gate {
+                let _ = gate.await;
+            }
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_calls_finish_new_ones_are_refused() {
+        let (release, gate) = oneshot::channel();
+        let mode = MaintenanceMode::new();
+        let service = Arc::new(MaintenanceModeEchoService::new(
+            Arc::new(GatedService { gate: tokio::sync::Mutex::new(Some(gate)) }), mode.clone()));
+
+        let running = tokio::spawn({
+            let service = service.clone();
+            async move { service.echo("in flight".into()).await }
+        });
+        while mode.in_flight() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        mode.enter("disk swap");
+        let error = service.echo("new".into()).await.unwrap_err();
+        assert!(echo_contract::is_unavailable(&error));
+        assert_eq!(echo_contract::maintenance_reason(&error), Some("disk swap"));
+        assert!(!mode.wait_drained(Duration::from_millis(10)).await);
+
+        release.send(()).unwrap();
+        assert_eq!(&*running.await.unwrap().unwrap(), "in flight");
+        assert!(mode.wait_drained(Duration::from_secs(1)).await);
+
+        assert!(mode.leave());
+        assert!(!mode.leave());
+        assert_eq!(&*service.echo("back".into()).await.unwrap(), "back");
+    }
+
+    #[tokio::test]
+    async fn test_operations_toggle_the_mode() {
+        let mode = MaintenanceMode::new();
+        let mut changes = mode.subscribe();
+        register_maintenance_operations("maintenance-mode-test", mode.clone(), Duration::from_secs(1));
+        let registry = MaintenanceRegistry::global();
+
+        let summary = registry.run_operation("maintenance-mode-test", ENTER_MAINTENANCE_OPERATION, String::new())
+            .await.unwrap().unwrap();
+        assert_eq!(summary, "in maintenance mode (maintenance mode), no calls in flight");
+        changes.changed().await.unwrap();
+        assert_eq!(changes.borrow_and_update().as_deref(), Some(DEFAULT_MAINTENANCE_REASON));
+
+        let summary = registry.run_operation("maintenance-mode-test", LEAVE_MAINTENANCE_OPERATION, String::new())
+            .await.unwrap().unwrap();
+        assert_eq!(summary, "serving");
+        assert!(!mode.is_active());
+        registry.clear("maintenance-mode-test");
+    }
+}
@@ -0,0 +1,417 @@
+//! Managed Units - Supervised Child Processes (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! HSU's process management runs a module as a **managed unit**: a child
+//! process the parent starts, health-checks and restarts, while the
+//! parent is what the operator starts and stops. [`ManagedUnit`] does
+//! that for any binary serving the gRPC health service (see
+//! [`crate::grpc_health`]):
+//!
+//! ```text
+//! echo-grpc-srv --supervise --port 50051       (parent)
+//!   ManagedUnit::run
+//!     ├── spawn: echo-grpc-srv --port 50051     (child, without --supervise)
+//!     ├── every health_interval: grpc.health.v1.Health/Check
+//!     │     ├── NOT_SERVING → reported, left running
+//!     │     └── unhealthy_threshold failures in a row → kill, restart
+//!     ├── child exits (crash) → restart after a backoff
+//!     └── Ctrl-C → SIGINT to the child, wait stop_timeout, then kill
+//! ```
+//!
+//! The unit's state is recorded in the [`HealthRegistry`] as check
+//! `managed-unit` of the unit, so the parent's admin `/health` reports the
+//! child. A child answering `NOT_SERVING` says so on purpose (maintenance
+//! mode, a failed self-test): it's reported as failing, but a restart
+//! wouldn't change its answer, so only checks it doesn't answer count.
+//! The backoff starts over once a child came up; after `max_restarts`
+//! restarts the parent gives up and fails.
+//!
+//! With a [`PipeChannel`] the child's stdin/stdout are piped and attached
+//! to it on every start (see [`crate::pipe`]), so the parent's gateways
+//! reach each new child without TCP.
+//!
+//! ## Comparison with Golang
+//!
+//! The Go HSU core manages units with its `processmanagement` package
+//! (standard/integrated units); this is the minimal integrated unit:
+//! one child, health-checked over gRPC.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+use hsu_common::{Error, Result};
+use tokio::process::{Child, Command};
+use tracing::{info, warn};
+
+use crate::cancellation::CancellationToken;
+use crate::grpc_health::check_grpc_health;
+use crate::health::{HealthRegistry, HealthStatus};
+use crate::pipe::PipeChannel;
+
+/// Health check name of a unit.
+const MANAGED_UNIT_CHECK: &str = "managed-unit";
+
+/// Delay before each restart: doubling from `initial`, capped at `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartBackoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl RestartBackoff {
+    /// Delay before the restart following `failures` failed starts in a row.
+    pub fn delay(&self, failures: u32) -> Duration {
+        self.initial.saturating_mul(1u32 << failures.min(16)).min(self.max)
+    }
+}
+
+impl Default for RestartBackoff {
+    fn default() -> Self {
+        Self { initial: Duration::from_millis(500), max: Duration::from_secs(30) }
+    }
+}
+
+/// How to run and supervise a unit.
+#[derive(Debug, Clone)]
+pub struct ManagedUnitConfig {
+    /// Unit name in logs and health checks (the child's module ID).
+    pub name: String,
+    /// Executable of the child.
+    pub program: PathBuf,
+    /// Its arguments.
+    pub args: Vec<OsString>,
+    /// Its gRPC health service (`http://127.0.0.1:50051`).
+    pub health_address: String,
+    /// How often it's health-checked.
+    pub health_interval: Duration,
+    /// How long one check may take.
+    pub health_timeout: Duration,
+    /// Failed checks in a row after which a healthy child is restarted.
+    pub unhealthy_threshold: u32,
+    /// How long a new child has to become healthy.
+    pub start_timeout: Duration,
+    /// Delay before restarts.
+    pub restart_backoff: RestartBackoff,
+    /// Give up after this many restarts (never if `None`).
+    pub max_restarts: Option<u32>,
+    /// How long a child may take to exit after SIGINT before it's killed.
+    pub stop_timeout: Duration,
+    /// Call each child over its stdin/stdout through this channel (stdio
+    /// inherited if `None`).
+    pub pipe: Option<Arc<PipeChannel>>,
+}
+
+impl Default for ManagedUnitConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            program: PathBuf::new(),
+            args: Vec::new(),
+            health_address: "http://127.0.0.1:50051".to_string(),
+            health_interval: Duration::from_secs(2),
+            health_timeout: Duration::from_secs(1),
+            unhealthy_threshold: 3,
+            start_timeout: Duration::from_secs(30),
+            restart_backoff: RestartBackoff::default(),
+            max_restarts: None,
+            stop_timeout: Duration::from_secs(10),
+            pipe: None,
+        }
+    }
+}
+
+/// Why a child's supervision ended.
+enum Outcome {
+    /// The parent is stopping.
+    Stopped,
+    /// The child exited by itself.
+    Exited(ExitStatus),
+    /// The child stopped answering health checks.
+    Unhealthy(String),
+}
+
+/// A child process kept running and healthy.
+pub struct ManagedUnit {
+    config: ManagedUnitConfig,
+    restarts: AtomicU32,
+}
+
+impl ManagedUnit {
+    /// Creates the unit; nothing runs before [`run`](Self::run).
+    pub fn new(config: ManagedUnitConfig) -> Self {
+        Self { config, restarts: AtomicU32::new(0) }
+    }
+
+    /// How often the child was restarted so far.
+    pub fn restarts(&self) -> u32 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    /// Runs the child, restarting it on crashes and failed health checks,
+    /// until `cancellation` is cancelled (then it's stopped and `Ok`
+    /// returned) or `max_restarts` is exceeded.
+    pub async fn run(&self, cancellation: &CancellationToken) -> Result<()> {
+        let name = &self.config.name;
+        let mut failures = 0;
+        loop {
+            let mut child = self.spawn()?;
+            let (outcome, came_up) = self.supervise(&mut child, cancellation).await;
+            if let Some(pipe) = &self.config.pipe {
+                pipe.detach();
+            }
+            let reason = match outcome {
+                Outcome::Stopped => {
+                    self.stop(&mut child).await;
+                    HealthRegistry::global().clear(name);
+                    return Ok(());
+                }
+                Outcome::Exited(status) => format!("exited ({})", status),
+                Outcome::Unhealthy(reason) => {
+                    self.stop(&mut child).await;
+                    reason
+                }
+            };
+            self.record(HealthStatus::Failing(reason.clone()));
+            failures = if came_up { 0 } else { failures + 1 };
+
+            if self.config.max_restarts.is_some_and(|max| self.restarts() >= max) {
+                return Err(Error::Protocol(format!(
+                    "Managed unit {} gave up after {} restarts: {}", name, self.restarts(), reason
+                )));
+            }
+            let restarts = self.restarts.fetch_add(1, Ordering::Relaxed) + 1;
+            let delay = self.config.restart_backoff.delay(failures);
+            warn!("[ManagedUnit] {} {}; restarting in {:?} (restart #{})", name, reason, delay, restarts);
+            tokio::select! {
+                _ = cancellation.cancelled() => return Ok(()),
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+
+    fn spawn(&self) -> Result<Child> {
+        let mut command = Command::new(&self.config.program);
+        command
+            .args(&self.config.args)
+            // A parent dying without stopping it doesn't leave it running
+            .kill_on_drop(true);
+        if self.config.pipe.is_some() {
+            command.stdin(Stdio::piped()).stdout(Stdio::piped());
+        }
+        let mut child = command.spawn().map_err(|e| Error::Protocol(format!(
+            "Failed to start managed unit {} ({}): {}", self.config.name, self.config.program.display(), e
+        )))?;
+        if let Some(pipe) = &self.config.pipe {
+            let (stdout, stdin) = child.stdout.take().zip(child.stdin.take())
+                .expect("stdin and stdout are piped");
+            pipe.attach(stdout, stdin);
+        }
+        info!("[ManagedUnit] Started {} (pid {})", self.config.name, child.id().unwrap_or_default());
+        Ok(child)
+    }
+
+    /// Watches `child` until it exits, turns unhealthy or the parent
+    /// stops; also returns whether it answered a health check at some point.
+    async fn supervise(&self, child: &mut Child, cancellation: &CancellationToken) -> (Outcome, bool) {
+        let config = &self.config;
+        let started = Instant::now();
+        let mut up = false;
+        let mut failed_checks = 0;
+        let mut ticks = tokio::time::interval(config.health_interval);
+        loop {
+            tokio::select! {
+                biased;
+                _ = cancellation.cancelled() => return (Outcome::Stopped, up),
+                status = child.wait() => {
+                    let outcome = match status {
+                        Ok(status) => Outcome::Exited(status),
+                        Err(e) => Outcome::Unhealthy(format!("can't be waited for: {}", e)),
+                    };
+                    return (outcome, up);
+                }
+                _ = ticks.tick() => {
+                    let started_at = Instant::now();
+                    let check = check_grpc_health(&config.health_address, "", config.health_timeout).await;
+                    match check {
+                        Ok(serving) => {
+                            if !up {
+                                let state = if serving { "healthy" } else { "up, not serving" };
+                                info!("[ManagedUnit] ✅ {} is {} after {:?}", config.name, state, started.elapsed());
+                            }
+                            up = true;
+                            failed_checks = 0;
+                            // Answered: not serving is the child's choice, not a fault
+                            let status = if serving {
+                                HealthStatus::Passing
+                            } else {
+                                HealthStatus::Failing("not serving".to_string())
+                            };
+                            self.record_with_latency(status, started_at.elapsed());
+                        }
+                        Err(e) => {
+                            let reason = e.to_string();
+                            if !up {
+                                // Still starting: only the start timeout counts
+                                if started.elapsed() >= config.start_timeout {
+                                    let reason = format!("not up within {:?} ({})", config.start_timeout, reason);
+                                    return (Outcome::Unhealthy(reason), false);
+                                }
+                                continue;
+                            }
+                            failed_checks += 1;
+                            warn!("[ManagedUnit] {} health check failed ({}/{}): {}",
+                                config.name, failed_checks, config.unhealthy_threshold, reason);
+                            self.record_with_latency(HealthStatus::Failing(reason.clone()), started_at.elapsed());
+                            if failed_checks >= config.unhealthy_threshold {
+                                let reason = format!("failed {} health checks in a row ({})", failed_checks, reason);
+                                return (Outcome::Unhealthy(reason), true);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Asks `child` to exit (SIGINT, like Ctrl-C), killing it after
+    /// `stop_timeout`.
+    async fn stop(&self, child: &mut Child) {
+        #[cfg(unix)]
+        if let Some(pid) = child.id() {
+            // SAFETY: signals a process we spawned and haven't reaped yet
+            unsafe { libc::kill(pid as libc::pid_t, libc::SIGINT) };
+        }
+        #[cfg(not(unix))]
+        let _ = child.start_kill();
+        match tokio::time::timeout(self.config.stop_timeout, child.wait()).await {
+            Ok(status) => info!("[ManagedUnit] {} stopped ({:?})", self.config.name, status),
+            Err(_) => {
+                warn!("[ManagedUnit] {} didn't stop within {:?}; killing it", self.config.name, self.config.stop_timeout);
+                let _ = child.kill().await;
+            }
+        }
+    }
+
+    fn record(&self, status: HealthStatus) {
+        self.record_with_latency(status, Duration::ZERO);
+    }
+
+    fn record_with_latency(&self, status: HealthStatus, latency: Duration) {
+        HealthRegistry::global().record(&self.config.name, MANAGED_UNIT_CHECK, status, latency);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let backoff = RestartBackoff { initial: Duration::from_millis(100), max: Duration::from_secs(1) };
+        assert_eq!(backoff.delay(0), Duration::from_millis(100));
+        assert_eq!(backoff.delay(2), Duration::from_millis(400));
+        assert_eq!(backoff.delay(4), Duration::from_secs(1));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[cfg(unix)]
+    fn unit(name: &str, script: &str) -> ManagedUnit {
+        ManagedUnit::new(ManagedUnitConfig {
+            name: name.to_string(),
+            program: PathBuf::from("sh"),
+            args: vec!["-c".into(), script.into()],
+            // Nothing listens there: the child never turns healthy
+            health_address: "http://127.0.0.1:1".to_string(),
+            health_interval: Duration::from_millis(20),
+            health_timeout: Duration::from_millis(20),
+            restart_backoff: RestartBackoff { initial: Duration::from_millis(1), max: Duration::from_millis(5) },
+            max_restarts: Some(2),
+            stop_timeout: Duration::from_secs(2),
+            ..Default::default()
+        })
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_crashing_child_is_restarted_until_the_limit() {
+        let unit = unit("test-crashing-unit", "exit 3");
+
+        let error = unit.run(&CancellationToken::new()).await.unwrap_err();
+        assert!(error.to_string().contains("gave up after 2 restarts"), "{}", error);
+        assert_eq!(unit.restarts(), 2);
+        assert!(!HealthRegistry::global().checks().iter()
+            .find(|check| check.module == "test-crashing-unit")
+            .unwrap()
+            .is_passing());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancellation_stops_the_child() {
+        let unit = unit("test-stopped-unit", "sleep 30");
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            cancel.cancel();
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), unit.run(&token)).await.unwrap().unwrap();
+        assert_eq!(unit.restarts(), 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_not_serving_child_is_left_running() {
+        let registry = Arc::new(HealthRegistry::default());
+        registry.record("echo", "maintenance", HealthStatus::Failing("disk swap".to_string()), Duration::ZERO);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(crate::grpc_health::EchoHealthService::new(registry, Vec::new()).into_server())
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        let unit = ManagedUnit::new(ManagedUnitConfig {
+            health_address: format!("http://{}", address),
+            health_timeout: Duration::from_secs(1),
+            unhealthy_threshold: 1,
+            start_timeout: Duration::from_millis(50),
+            ..unit("test-not-serving-unit", "sleep 30").config
+        });
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            cancel.cancel();
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), unit.run(&token)).await.unwrap().unwrap();
+        assert_eq!(unit.restarts(), 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pipe_is_attached_while_the_child_runs() {
+        let pipe = Arc::new(PipeChannel::new("test-piped-unit"));
+        let unit = ManagedUnit::new(ManagedUnitConfig {
+            pipe: Some(pipe.clone()),
+            ..unit("test-piped-unit", "sleep 30").config
+        });
+        let token = CancellationToken::new();
+        let running = tokio::spawn({
+            let token = token.clone();
+            async move { unit.run(&token).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(pipe.is_attached());
+        token.cancel();
+        tokio::time::timeout(Duration::from_secs(5), running).await.unwrap().unwrap().unwrap();
+        assert!(!pipe.is_attached());
+    }
+}
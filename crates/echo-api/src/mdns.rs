@@ -0,0 +1,186 @@
+//! mDNS/Zeroconf Discovery (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! An alternative to the service registry for LAN demos: the server
+//! module advertises its gRPC endpoints on the local network, the client
+//! gateways browse for them. No registry process, no addresses to type:
+//!
+//! ```text
+//! laptop A: echo-grpc-srv --mdns
+//!     EchoServerModule::start
+//!         └── MdnsAdvertisement ──multicast──→ _hsu-echo._tcp.local.
+//!                                                 TXT module=echo protocol=grpc
+//!
+//! laptop B: echo-grpc-cli --discovery mdns
+//!     EchoServiceGatewaysImpl (first gRPC gateway)
+//!         └── browse(module "echo") ──→ 192.168.1.20:50051 ──→ pooled channel
+//! ```
+//!
+//! The resolved address is used like a configured `grpc_address`
+//! (pooled, self-healing channel). It is resolved once per gateways
+//! instance; restart the client after the server moved.
+//!
+//! ## Rust Learning Note
+//!
+//! `mdns-sd` runs its own daemon thread and hands events over `flume`
+//! channels, which can be awaited (`recv_async`) - no extra runtime glue.
+//! Dropping [`MdnsAdvertisement`] unregisters (sends the "goodbye"
+//! packets) and stops the daemon.
+
+use std::net::IpAddr;
+use std::time::Duration;
+use hsu_common::{Error, Protocol, Result};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use tracing::{debug, info, warn};
+
+use crate::endpoints::BoundEndpoint;
+
+/// DNS-SD service type the echo modules advertise under.
+pub const ECHO_SERVICE_TYPE: &str = "_hsu-echo._tcp.local.";
+
+/// Registered mDNS advertisement; withdrawn on drop.
+pub struct MdnsAdvertisement {
+    daemon: ServiceDaemon,
+    fullnames: Vec<String>,
+}
+
+impl MdnsAdvertisement {
+    /// Advertises the gRPC `endpoints` of `module_id`.
+    ///
+    /// Addresses of all interfaces are announced, so a client on any
+    /// attached network can connect.
+    pub fn advertise(module_id: &str, endpoints: &[BoundEndpoint]) -> Result<Self> {
+        let daemon = ServiceDaemon::new()
+            .map_err(|e| Error::Protocol(format!("Failed to start mDNS daemon: {}", e)))?;
+        let host = hostname();
+
+        let mut fullnames = Vec::new();
+        for endpoint in endpoints.iter().filter(|endpoint| endpoint.protocol == Protocol::Grpc) {
+            let instance = instance_name(module_id, &host, endpoint.port);
+            let properties = [("module", module_id), ("protocol", "grpc")];
+            let service = ServiceInfo::new(
+                ECHO_SERVICE_TYPE,
+                &instance,
+                &format!("{}.local.", host),
+                "",
+                endpoint.port,
+                &properties[..],
+            )
+            .map_err(|e| Error::Protocol(format!("Invalid mDNS service '{}': {}", instance, e)))?
+            .enable_addr_auto();
+
+            fullnames.push(service.get_fullname().to_string());
+            daemon.register(service)
+                .map_err(|e| Error::Protocol(format!("Failed to advertise '{}': {}", instance, e)))?;
+            info!("[mDNS] ✅ Advertising module '{}' (gRPC port {}) as '{}'", module_id, endpoint.port, instance);
+        }
+
+        Ok(Self { daemon, fullnames })
+    }
+}
+
+impl Drop for MdnsAdvertisement {
+    fn drop(&mut self) {
+        for fullname in &self.fullnames {
+            if let Err(e) = self.daemon.unregister(fullname) {
+                warn!("[mDNS] Failed to withdraw '{}': {}", fullname, e);
+            }
+        }
+        // Let the daemon send the goodbye packets before it stops
+        if let Err(e) = self.daemon.shutdown() {
+            debug!("[mDNS] Daemon shutdown: {}", e);
+        }
+    }
+}
+
+/// Browses for a gRPC endpoint advertised by `module_id`.
+///
+/// Returns the first resolved `host:port`, or an error after `timeout`.
+pub async fn browse(module_id: &str, timeout: Duration) -> Result<String> {
+    let daemon = ServiceDaemon::new()
+        .map_err(|e| Error::Protocol(format!("Failed to start mDNS daemon: {}", e)))?;
+    let events = daemon.browse(ECHO_SERVICE_TYPE)
+        .map_err(|e| Error::Protocol(format!("Failed to browse {}: {}", ECHO_SERVICE_TYPE, e)))?;
+    debug!("[mDNS] Browsing {} for module '{}'", ECHO_SERVICE_TYPE, module_id);
+
+    let found = tokio::time::timeout(timeout, async {
+        while let Ok(event) = events.recv_async().await {
+            let ServiceEvent::ServiceResolved(service) = event else {
+                continue;
+            };
+            let advertised_module = service.get_property_val_str("module").unwrap_or_default();
+            if advertised_module != module_id || service.get_property_val_str("protocol") != Some("grpc") {
+                continue;
+            }
+            if let Some(ip) = preferred_address(service.get_addresses().iter().copied()) {
+                return Some(grpc_address(ip, service.get_port()));
+            }
+        }
+        None
+    })
+    .await;
+    let _ = daemon.shutdown();
+
+    match found {
+        Ok(Some(address)) => {
+            info!("[mDNS] ✅ Found module '{}' at {}", module_id, address);
+            Ok(address)
+        }
+        _ => Err(Error::Protocol(format!(
+            "No mDNS advertisement of module '{}' within {:?} (is the server running with mDNS enabled?)",
+            module_id, timeout,
+        ))),
+    }
+}
+
+/// Picks the address to dial: IPv4 first (link-local IPv6 needs a scope ID).
+fn preferred_address(addresses: impl Iterator<Item = IpAddr>) -> Option<IpAddr> {
+    let mut addresses: Vec<IpAddr> = addresses.collect();
+    addresses.sort_by_key(|ip| (!ip.is_ipv4(), *ip));
+    addresses.into_iter().next()
+}
+
+/// `host:port`, bracketing IPv6 addresses.
+fn grpc_address(ip: IpAddr, port: u16) -> String {
+    match ip {
+        IpAddr::V4(ip) => format!("{}:{}", ip, port),
+        IpAddr::V6(ip) => format!("[{}]:{}", ip, port),
+    }
+}
+
+/// Unique per host and port, so several servers can advertise the same module.
+fn instance_name(module_id: &str, host: &str, port: u16) -> String {
+    format!("{}@{}:{}", module_id, host, port)
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grpc_address_brackets_ipv6() {
+        assert_eq!(grpc_address("192.168.1.20".parse().unwrap(), 50051), "192.168.1.20:50051");
+        assert_eq!(grpc_address("fe80::1".parse().unwrap(), 50051), "[fe80::1]:50051");
+    }
+
+    #[test]
+    fn test_preferred_address_is_ipv4() {
+        let addresses = ["fe80::1", "192.168.1.20", "10.0.0.5"].map(|ip| ip.parse::<IpAddr>().unwrap());
+        assert_eq!(preferred_address(addresses.into_iter()), Some("10.0.0.5".parse().unwrap()));
+        assert_eq!(preferred_address(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_instance_name_is_unique_per_endpoint() {
+        assert_ne!(instance_name("echo", "laptop", 50051), instance_name("echo", "laptop", 50052));
+    }
+}
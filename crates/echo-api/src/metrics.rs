@@ -0,0 +1,397 @@
+//! Request/Response Size Metrics (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Size instrumentation is a **cross-cutting** concern: every adapter
+//! would otherwise repeat the same counting code. Instead, one decorator
+//! is installed where services enter and leave the framework:
+//!
+//! ```text
+//! Client side                          Server side
+//! ───────────                          ───────────
+//! EchoServiceGateways::get_service     EchoHandlersRegistrar
+//!     ↓ wraps Direct/gRPC gateway          ↓ wraps handler before EchoGrpcHandler
+//! SizeMetricsEchoService               SizeMetricsEchoService
+//!     ↓ records into                       ↓ records into
+//!          └──────── SizeMetrics (global) ────────┘
+//! ```
+//!
+//! Series are tagged by side, protocol and service ID, and rendered in
+//! the Prometheus text format by [`SizeMetrics::render_prometheus`]. The
+//! same decorator times the echo calls, failed ones included, into the
+//! `echo_request_duration_seconds` histogram (see [`DURATION_BUCKETS`]).
+//! Probes ([`RequestContext::probe`], the server's start-up self-test)
+//! are left out on both sides.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use hsu_common::Result;
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, RequestContext, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+/// Escapes a label value (a caller, module or task name) for the
+/// Prometheus text format.
+pub(crate) fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Labels identifying one size series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SizeLabels {
+    /// `"client"` (gateway) or `"server"` (handler).
+    pub side: &'static str,
+    /// Protocol name, e.g. `"direct"` or `"grpc"`.
+    pub protocol: &'static str,
+    /// Service ID, e.g. `"service"`.
+    pub service: &'static str,
+}
+
+/// Accumulated sizes for one series.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SizeSeries {
+    /// Number of requests sent/received.
+    pub requests: u64,
+    /// Total request payload bytes.
+    pub request_bytes: u64,
+    /// Number of successful responses.
+    pub responses: u64,
+    /// Total response payload bytes.
+    pub response_bytes: u64,
+}
+
+/// Upper bounds, in seconds, of the `echo_request_duration_seconds`
+/// buckets (plus `+Inf`).
+pub const DURATION_BUCKETS: [f64; 12] = [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+/// Call durations for one series.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DurationHistogram {
+    /// Calls per bucket of [`DURATION_BUCKETS`], not cumulative; slower
+    /// calls are only in `count`.
+    pub buckets: [u64; DURATION_BUCKETS.len()],
+    /// Number of calls timed.
+    pub count: u64,
+    /// Total time of those calls, in seconds.
+    pub sum_seconds: f64,
+}
+
+impl DurationHistogram {
+    fn record(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        if let Some(bucket) = DURATION_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[bucket] += 1;
+        }
+        self.count += 1;
+        self.sum_seconds += seconds;
+    }
+}
+
+/// Registry of request/response sizes.
+#[derive(Default)]
+pub struct SizeMetrics {
+    series: Mutex<BTreeMap<SizeLabels, SizeSeries>>,
+    durations: Mutex<BTreeMap<SizeLabels, DurationHistogram>>,
+}
+
+impl SizeMetrics {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide registry used by gateways and handlers.
+    pub fn global() -> Arc<SizeMetrics> {
+        static GLOBAL: OnceLock<Arc<SizeMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(SizeMetrics::new())).clone()
+    }
+
+    /// Records one request of `bytes` bytes.
+    pub fn record_request(&self, labels: SizeLabels, bytes: usize) {
+        let mut series = self.series.lock().unwrap();
+        let entry = series.entry(labels).or_default();
+        entry.requests += 1;
+        entry.request_bytes += bytes as u64;
+    }
+
+    /// Records one successful response of `bytes` bytes.
+    pub fn record_response(&self, labels: SizeLabels, bytes: usize) {
+        let mut series = self.series.lock().unwrap();
+        let entry = series.entry(labels).or_default();
+        entry.responses += 1;
+        entry.response_bytes += bytes as u64;
+    }
+
+    /// Records how long one call took, successful or not.
+    pub fn record_duration(&self, labels: SizeLabels, duration: Duration) {
+        self.durations.lock().unwrap().entry(labels).or_default().record(duration);
+    }
+
+    /// Returns the current value of one series.
+    pub fn get(&self, labels: SizeLabels) -> SizeSeries {
+        self.series.lock().unwrap().get(&labels).copied().unwrap_or_default()
+    }
+
+    /// Returns the call durations of one series.
+    pub fn durations(&self, labels: SizeLabels) -> DurationHistogram {
+        self.durations.lock().unwrap().get(&labels).copied().unwrap_or_default()
+    }
+
+    /// Renders all series in the Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let series = self.series.lock().unwrap();
+        let metrics: [(&str, &str, fn(&SizeSeries) -> u64); 4] = [
+            ("echo_requests_total", "Number of echo requests", |s| s.requests),
+            ("echo_request_bytes_total", "Echo request payload bytes", |s| s.request_bytes),
+            ("echo_responses_total", "Number of successful echo responses", |s| s.responses),
+            ("echo_response_bytes_total", "Echo response payload bytes", |s| s.response_bytes),
+        ];
+
+        let mut out = String::new();
+        for (name, help, value) in metrics {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (labels, s) in series.iter() {
+                let _ = writeln!(out, "{}{{side=\"{}\",protocol=\"{}\",service=\"{}\"}} {}",
+                    name, labels.side, labels.protocol, labels.service, value(s));
+            }
+        }
+
+        let name = "echo_request_duration_seconds";
+        let _ = writeln!(out, "# HELP {} Duration of echo calls, failed ones included", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (labels, histogram) in self.durations.lock().unwrap().iter() {
+            let series = format!("side=\"{}\",protocol=\"{}\",service=\"{}\"", labels.side, labels.protocol, labels.service);
+            let mut cumulative = 0;
+            for (bound, count) in DURATION_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(out, "{}_bucket{{{},le=\"{}\"}} {}", name, series, bound, cumulative);
+            }
+            let _ = writeln!(out, "{}_bucket{{{},le=\"+Inf\"}} {}", name, series, histogram.count);
+            let _ = writeln!(out, "{}_sum{{{}}} {:.6}", name, series, histogram.sum_seconds);
+            let _ = writeln!(out, "{}_count{{{}}} {}", name, series, histogram.count);
+        }
+        out
+    }
+}
+
+/// Decorator that records request/response sizes of every call.
+///
+/// Sizes are payload sizes as seen by the contract (message bytes, binary
+/// payload, streamed file bytes) - not wire sizes, so Direct and gRPC
+/// numbers are directly comparable.
+pub struct SizeMetricsEchoService {
+    inner: Arc<dyn EchoService>,
+    labels: SizeLabels,
+    metrics: Arc<SizeMetrics>,
+}
+
+impl SizeMetricsEchoService {
+    /// Wraps `inner`, recording into `metrics` under `labels`.
+    pub fn new(inner: Arc<dyn EchoService>, labels: SizeLabels, metrics: Arc<SizeMetrics>) -> Self {
+        Self { inner, labels, metrics }
+    }
+
+    /// Runs `call`, recording its duration whatever the outcome.
+    async fn timed<T>(&self, call: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        let started = Instant::now();
+        let result = call.await;
+        if !RequestContext::current_is_probe() {
+            self.metrics.record_duration(self.labels, started.elapsed());
+        }
+        result
+    }
+
+    // Probes (the server's self-test) aren't traffic
+    fn record_request(&self, bytes: usize) {
+        if !RequestContext::current_is_probe() {
+            self.metrics.record_request(self.labels, bytes);
+        }
+    }
+
+    fn record_response(&self, bytes: usize) {
+        if !RequestContext::current_is_probe() {
+            self.metrics.record_response(self.labels, bytes);
+        }
+    }
+}
+
+#[async_trait]
+impl EchoService for SizeMetricsEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.record_request(message.len());
+        let response = self.timed(self.inner.echo(message)).await?;
+        self.record_response(response.len());
+        Ok(response)
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.record_request(payload.len());
+        let response = self.timed(self.inner.echo_bytes(payload)).await?;
+        self.record_response(response.len());
+        Ok(response)
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.record_request(message.len() + idempotency_key.len());
+        let ack = self.timed(self.inner.echo_reliable(message, idempotency_key)).await?;
+        self.record_response(ack.message.len() + ack.idempotency_key.len());
+        Ok(ack)
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        // The stream is consumed by the inner service; count as it passes
+        let streamed = Arc::new(AtomicU64::new(0));
+        let counter = streamed.clone();
+        let chunks: ByteStream = Box::pin(chunks.inspect(move |chunk| {
+            if let Ok(data) = chunk {
+                counter.fetch_add(data.len() as u64, Ordering::Relaxed);
+            }
+        }));
+
+        let result = self.timed(self.inner.echo_file(chunks)).await;
+        self.record_request(streamed.load(Ordering::Relaxed) as usize);
+        let digest = result?;
+        self.record_response(digest.sha256.len() + 2 * std::mem::size_of::<u64>());
+        Ok(digest)
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.record_request(message.len() + session_id.len());
+        let echo = self.timed(self.inner.echo_with_session(session_id, message)).await?;
+        self.record_response(echo.message.len() + echo.session_id.len() + 2 * std::mem::size_of::<u64>());
+        Ok(echo)
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockService;
+
+    #[async_trait]
+    impl EchoService for MockService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    const LABELS: SizeLabels = SizeLabels { side: "client", protocol: "direct", service: "service" };
+
+    #[tokio::test]
+    async fn test_records_sizes() {
+        let metrics = Arc::new(SizeMetrics::new());
+        let service = SizeMetricsEchoService::new(Arc::new(MockService), LABELS, metrics.clone());
+
+        service.echo("Hello".into()).await.unwrap();
+        service.echo_bytes(Bytes::from_static(&[0u8; 10])).await.unwrap();
+
+        assert_eq!(metrics.get(LABELS), SizeSeries {
+            requests: 2,
+            request_bytes: 15,
+            responses: 2,
+            response_bytes: 15,
+        });
+    }
+
+    #[tokio::test]
+    async fn test_probes_are_not_recorded() {
+        let metrics = Arc::new(SizeMetrics::new());
+        let service = SizeMetricsEchoService::new(Arc::new(MockService), LABELS, metrics.clone());
+
+        let probe = RequestContext::default().as_probe();
+        probe.scope(service.echo("self-test".into())).await.unwrap();
+
+        assert_eq!(metrics.get(LABELS), SizeSeries::default());
+        assert_eq!(metrics.durations(LABELS).count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_call_records_request_only() {
+        let metrics = Arc::new(SizeMetrics::new());
+        let service = SizeMetricsEchoService::new(Arc::new(MockService), LABELS, metrics.clone());
+
+        let chunks: ByteStream = Box::pin(futures::stream::empty());
+        assert!(service.echo_file(chunks).await.is_err());
+
+        let series = metrics.get(LABELS);
+        assert_eq!(series.requests, 1);
+        assert_eq!(series.responses, 0);
+    }
+
+    #[test]
+    fn test_render_prometheus() {
+        let metrics = SizeMetrics::new();
+        metrics.record_request(LABELS, 5);
+
+        let text = metrics.render_prometheus();
+        assert!(text.contains("# TYPE echo_request_bytes_total counter"));
+        assert!(text.contains("echo_request_bytes_total{side=\"client\",protocol=\"direct\",service=\"service\"} 5"));
+    }
+
+    #[tokio::test]
+    async fn test_durations_include_failed_calls() {
+        let metrics = Arc::new(SizeMetrics::new());
+        let service = SizeMetricsEchoService::new(Arc::new(MockService), LABELS, metrics.clone());
+
+        service.echo("Hello".into()).await.unwrap();
+        let chunks: ByteStream = Box::pin(futures::stream::empty());
+        assert!(service.echo_file(chunks).await.is_err());
+        metrics.record_duration(LABELS, Duration::from_secs(10));
+
+        let durations = metrics.durations(LABELS);
+        assert_eq!(durations.count, 3);
+        assert_eq!(durations.buckets.iter().sum::<u64>(), 2);
+
+        let text = metrics.render_prometheus();
+        let series = "side=\"client\",protocol=\"direct\",service=\"service\"";
+        assert!(text.contains("# TYPE echo_request_duration_seconds histogram"));
+        assert!(text.contains(&format!("echo_request_duration_seconds_bucket{{{},le=\"2.5\"}} 2", series)));
+        assert!(text.contains(&format!("echo_request_duration_seconds_bucket{{{},le=\"+Inf\"}} 3", series)));
+        assert!(text.contains(&format!("echo_request_duration_seconds_count{{{}}} 3", series)));
+    }
+}
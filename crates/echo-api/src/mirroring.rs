@@ -0,0 +1,389 @@
+//! Shadow Traffic Mirroring (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Before switching clients to a new transport (e.g. the JSON/HTTP
+//! adapter instead of gRPC) or a new backend, send it a copy of the real
+//! traffic and compare: the caller only ever sees the primary's reply,
+//! the shadow's reply is compared and discarded:
+//!
+//! ```text
+//! caller: echo("ping")
+//!     ↓
+//! MirroringEchoService
+//!     ├─ primary (e.g. gRPC) ──→ "ping" ──→ caller
+//!     └─ spawn: shadow (e.g. JSON/HTTP) ──→ "ping"
+//!                   ↓ once both replied
+//!               MirrorMetrics: mirrored, matched, mismatched,
+//!                              shadow errors, dropped, latency of each
+//!                   ↓ GET /metrics
+//! ```
+//!
+//! Only `echo` and `echo_bytes` are mirrored: they have no side effects,
+//! so sending them twice is harmless. The shadow call runs with the
+//! caller's [`RequestContext`] (transformations included), so replies are
+//! comparable. When `max_in_flight` shadow calls are still running, new
+//! ones are dropped (and counted) instead of piling up behind a slow
+//! shadow.
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! go func() { shadowReply, err := shadow.Echo(ctx, msg); compare(<-primaryReply, shadowReply, err) }()
+//! reply, err := primary.Echo(ctx, msg)
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::Result;
+use tokio::sync::oneshot;
+use tracing::{debug, warn};
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport,
+    HistoryPage, HistoryQuery, HistoryStream, RequestContext, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+/// Comparison counters of one mirrored method.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MirrorStats {
+    /// Calls copied to the shadow.
+    pub mirrored: u64,
+    /// Shadow replies equal to the primary's.
+    pub matched: u64,
+    /// Shadow replies that differ (or succeeded where the primary failed).
+    pub mismatched: u64,
+    /// Shadow calls that failed.
+    pub shadow_errors: u64,
+    /// Calls not copied because too many shadow calls were running.
+    pub dropped: u64,
+    /// Total time of the compared primary calls.
+    pub primary_seconds: f64,
+    /// Total time of the compared shadow calls.
+    pub shadow_seconds: f64,
+}
+
+/// Name, help, type and value of one mirroring metric.
+type MirrorMetric = (&'static str, &'static str, &'static str, fn(&MirrorStats) -> f64);
+
+/// Mirroring counters by method.
+#[derive(Debug, Default)]
+pub struct MirrorMetrics {
+    methods: Mutex<BTreeMap<&'static str, MirrorStats>>,
+}
+
+impl MirrorMetrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide metrics.
+    pub fn global() -> Arc<MirrorMetrics> {
+        static GLOBAL: OnceLock<Arc<MirrorMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(MirrorMetrics::new())).clone()
+    }
+
+    /// Returns the counters of `method` (`echo`, `echo_bytes`).
+    pub fn stats(&self, method: &str) -> MirrorStats {
+        self.methods.lock().unwrap().get(method).copied().unwrap_or_default()
+    }
+
+    fn update(&self, method: &'static str, update: impl FnOnce(&mut MirrorStats)) {
+        update(self.methods.lock().unwrap().entry(method).or_default());
+    }
+
+    /// Renders the counters in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let methods = self.methods.lock().unwrap();
+        let metrics: [MirrorMetric; 7] = [
+            ("echo_mirror_calls_total", "Client calls copied to the shadow", "counter", |s| s.mirrored as f64),
+            ("echo_mirror_matches_total", "Shadow replies equal to the primary's", "counter", |s| s.matched as f64),
+            ("echo_mirror_mismatches_total", "Shadow replies differing from the primary's", "counter",
+                |s| s.mismatched as f64),
+            ("echo_mirror_shadow_errors_total", "Shadow calls that failed", "counter", |s| s.shadow_errors as f64),
+            ("echo_mirror_dropped_total", "Calls not mirrored (too many shadow calls running)", "counter",
+                |s| s.dropped as f64),
+            ("echo_mirror_primary_seconds_sum", "Time of the compared primary calls", "counter",
+                |s| s.primary_seconds),
+            ("echo_mirror_shadow_seconds_sum", "Time of the compared shadow calls", "counter", |s| s.shadow_seconds),
+        ];
+        let mut out = String::new();
+        for (name, help, kind, value) in metrics {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (method, stats) in methods.iter() {
+                let _ = writeln!(out, "{}{{method=\"{}\"}} {}", name, method, value(stats));
+            }
+        }
+        out
+    }
+}
+
+/// Hands the primary's outcome to the running shadow call.
+type PrimaryOutcome<T> = oneshot::Sender<(Option<T>, Duration)>;
+
+/// The shadow side of mirroring: the shadow service, its in-flight limit
+/// and the metrics.
+///
+/// Clones share the in-flight count, so every primary mirrored to one
+/// shadow (e.g. all gateways of a module) is capped together.
+#[derive(Clone)]
+pub struct MirrorShadow {
+    service: Arc<dyn EchoService>,
+    max_in_flight: usize,
+    in_flight: Arc<AtomicUsize>,
+    metrics: Arc<MirrorMetrics>,
+}
+
+impl MirrorShadow {
+    /// Sends copies to `service`, at most `max_in_flight` at a time,
+    /// recording into the global [`MirrorMetrics`].
+    pub fn new(service: Arc<dyn EchoService>, max_in_flight: usize) -> Self {
+        Self::with_metrics(service, max_in_flight, MirrorMetrics::global())
+    }
+
+    /// Sends copies to `service`, recording into `metrics`.
+    pub fn with_metrics(service: Arc<dyn EchoService>, max_in_flight: usize, metrics: Arc<MirrorMetrics>) -> Self {
+        Self { service, max_in_flight, in_flight: Arc::new(AtomicUsize::new(0)), metrics }
+    }
+}
+
+/// Decorator copying side-effect-free calls to a shadow service.
+pub struct MirroringEchoService {
+    primary: Arc<dyn EchoService>,
+    shadow: MirrorShadow,
+}
+
+impl MirroringEchoService {
+    /// Mirrors `primary`'s calls to `shadow`, at most `max_in_flight` at
+    /// a time, recording into the global [`MirrorMetrics`].
+    pub fn new(primary: Arc<dyn EchoService>, shadow: Arc<dyn EchoService>, max_in_flight: usize) -> Self {
+        Self::with_shadow(primary, MirrorShadow::new(shadow, max_in_flight))
+    }
+
+    /// Mirrors `primary`'s calls to `shadow`, recording into `metrics`.
+    pub fn with_metrics(
+        primary: Arc<dyn EchoService>,
+        shadow: Arc<dyn EchoService>,
+        max_in_flight: usize,
+        metrics: Arc<MirrorMetrics>,
+    ) -> Self {
+        Self::with_shadow(primary, MirrorShadow::with_metrics(shadow, max_in_flight, metrics))
+    }
+
+    /// Mirrors `primary`'s calls to a `shadow` other primaries may share.
+    pub fn with_shadow(primary: Arc<dyn EchoService>, shadow: MirrorShadow) -> Self {
+        Self { primary, shadow }
+    }
+
+    /// Starts `shadow_call` in the background; it compares its reply with
+    /// the primary outcome sent through the returned sender.
+    ///
+    /// `None` if the call was dropped.
+    fn start_shadow<T, F>(&self, method: &'static str, shadow_call: F) -> Option<PrimaryOutcome<T>>
+    where
+        T: PartialEq + Send + 'static,
+        F: Future<Output = Result<T>> + Send + 'static,
+    {
+        let shadow = &self.shadow;
+        let admitted = shadow.in_flight.fetch_update(Ordering::AcqRel, Ordering::Acquire, |in_flight| {
+            (in_flight < shadow.max_in_flight).then_some(in_flight + 1)
+        });
+        if admitted.is_err() {
+            shadow.metrics.update(method, |stats| stats.dropped += 1);
+            return None;
+        }
+        shadow.metrics.update(method, |stats| stats.mirrored += 1);
+
+        let (primary_outcome, primary_reply) = oneshot::channel::<(Option<T>, Duration)>();
+        let (metrics, in_flight) = (shadow.metrics.clone(), shadow.in_flight.clone());
+        let context = RequestContext::current();
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let shadow = context.scope(shadow_call).await;
+            let shadow_elapsed = started.elapsed();
+            in_flight.fetch_sub(1, Ordering::AcqRel);
+            // The caller went away before the primary answered: nothing to compare
+            let Ok((primary, primary_elapsed)) = primary_reply.await else {
+                return;
+            };
+            metrics.update(method, |stats| {
+                stats.primary_seconds += primary_elapsed.as_secs_f64();
+                stats.shadow_seconds += shadow_elapsed.as_secs_f64();
+                match (&primary, &shadow) {
+                    (_, Err(e)) => {
+                        debug!("[Mirroring] Shadow {} failed: {}", method, e);
+                        stats.shadow_errors += 1;
+                    }
+                    (Some(primary), Ok(shadow)) if primary == shadow => stats.matched += 1,
+                    _ => {
+                        warn!("[Mirroring] Shadow {} reply differs from the primary's", method);
+                        stats.mismatched += 1;
+                    }
+                }
+            });
+        });
+        Some(primary_outcome)
+    }
+}
+
+/// Sends the primary's reply (if it succeeded) to the shadow call.
+fn report<T: Clone>(outcome: Option<PrimaryOutcome<T>>, result: &Result<T>, started: Instant) {
+    if let Some(outcome) = outcome {
+        let _ = outcome.send((result.as_ref().ok().cloned(), started.elapsed()));
+    }
+}
+
+#[async_trait]
+impl EchoService for MirroringEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let shadow = self.shadow.service.clone();
+        let copy = message.clone();
+        let outcome = self.start_shadow("echo", async move { shadow.echo(copy).await });
+        let started = Instant::now();
+        let result = self.primary.echo(message).await;
+        report(outcome, &result, started);
+        result
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let shadow = self.shadow.service.clone();
+        let copy = payload.clone();
+        let outcome = self.start_shadow("echo_bytes", async move { shadow.echo_bytes(copy).await });
+        let started = Instant::now();
+        let result = self.primary.echo_bytes(payload).await;
+        report(outcome, &result, started);
+        result
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.primary.echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.primary.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.primary.echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.primary.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.primary.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.primary.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.primary.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.primary.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.primary.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.primary.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hsu_common::Error;
+
+    /// Echoes with a prefix; `echo_bytes` fails.
+    struct PrefixService(&'static str);
+
+    #[async_trait]
+    impl EchoService for PrefixService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            Ok(format!("{}{}", self.0, message).into())
+        }
+
+        async fn echo_bytes(&self, _payload: Bytes) -> Result<Bytes> {
+            Err(Error::Protocol("unavailable".to_string()))
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    /// Waits until the shadow calls have been compared.
+    async fn settled(metrics: &MirrorMetrics, method: &str, compared: u64) -> MirrorStats {
+        loop {
+            let stats = metrics.stats(method);
+            if stats.matched + stats.mismatched + stats.shadow_errors >= compared {
+                return stats;
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caller_gets_primary_reply_and_shadow_is_compared() {
+        let metrics = Arc::new(MirrorMetrics::new());
+        let mirrored = |shadow: &'static str| MirroringEchoService::with_metrics(
+            Arc::new(PrefixService("")), Arc::new(PrefixService(shadow)), 10, metrics.clone());
+
+        assert_eq!(&*mirrored("").echo("hi".into()).await.unwrap(), "hi");
+        assert_eq!(&*mirrored("v2:").echo("hi".into()).await.unwrap(), "hi");
+        let stats = settled(&metrics, "echo", 2).await;
+        assert_eq!((stats.mirrored, stats.matched, stats.mismatched), (2, 1, 1));
+
+        assert!(mirrored("").echo_bytes(Bytes::from_static(b"x")).await.is_err());
+        assert_eq!(settled(&metrics, "echo_bytes", 1).await.shadow_errors, 1);
+        assert!(metrics.render_prometheus().contains("echo_mirror_mismatches_total{method=\"echo\"} 1"));
+    }
+
+    /// Never answers.
+    struct Hanging;
+
+    #[async_trait]
+    impl EchoService for Hanging {
+        async fn echo(&self, _message: Arc<str>) -> Result<Arc<str>> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_primaries_sharing_a_shadow_share_its_limit() {
+        let metrics = Arc::new(MirrorMetrics::new());
+        let shadow = MirrorShadow::with_metrics(Arc::new(Hanging), 1, metrics.clone());
+        let first = MirroringEchoService::with_shadow(Arc::new(PrefixService("")), shadow.clone());
+        let second = MirroringEchoService::with_shadow(Arc::new(PrefixService("")), shadow);
+
+        assert_eq!(&*first.echo("a".into()).await.unwrap(), "a");
+        assert_eq!(&*second.echo("b".into()).await.unwrap(), "b");
+        let stats = metrics.stats("echo");
+        assert_eq!((stats.mirrored, stats.dropped), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_calls_beyond_max_in_flight_are_dropped() {
+        let metrics = Arc::new(MirrorMetrics::new());
+        let service = MirroringEchoService::with_metrics(
+            Arc::new(PrefixService("")), Arc::new(PrefixService("")), 0, metrics.clone());
+
+        assert_eq!(&*service.echo("hi".into()).await.unwrap(), "hi");
+        let stats = metrics.stats("echo");
+        assert_eq!((stats.mirrored, stats.dropped), (0, 1));
+    }
+}
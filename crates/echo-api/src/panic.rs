@@ -0,0 +1,274 @@
+//! Panic Isolation for Handlers and Module Lifecycles (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A panic inside `EchoServiceImpl` would otherwise unwind through tonic's
+//! connection task - the client sees a **connection reset** instead of an
+//! error. Catching it at the service boundary turns it into a regular
+//! error (gRPC `INTERNAL`):
+//!
+//! ```text
+//! EchoGrpcHandler / Direct gateway
+//!     ↓
+//! PanicGuardEchoService   ← catch_unwind: panic → Err(Internal), counted
+//!     ↓
+//! EchoServiceImpl         ← panics!
+//! ```
+//!
+//! What happens next depends on the [`PanicPolicy`]: the module is either
+//! marked **Degraded** (process keeps running) or the process is aborted.
+//!
+//! The panic message only goes to the log: it may quote internals (paths,
+//! values, `unwrap` sites) the caller has no business seeing.
+//!
+//! # Rust Learning Note
+//!
+//! `catch_unwind` requires the future to be `UnwindSafe`. Our services only
+//! share state behind `Mutex`es (which poison on panic), so asserting it
+//! with `AssertUnwindSafe` is sound here.
+
+use std::any::Any;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::FutureExt;
+use hsu_common::{Error, ModuleID, Result};
+use hsu_module_api::Module;
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use tracing::error;
+
+/// What to do after a panic was caught.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Fail the call, mark the module Degraded and keep running.
+    #[default]
+    Degrade,
+    /// Abort the process (let the supervisor restart it).
+    Abort,
+}
+
+/// Process-wide record of caught panics.
+#[derive(Default)]
+pub struct PanicRegistry {
+    panics: AtomicU64,
+    degraded: Mutex<BTreeSet<String>>,
+}
+
+impl PanicRegistry {
+    /// Returns the process-wide registry.
+    pub fn global() -> Arc<PanicRegistry> {
+        static GLOBAL: OnceLock<Arc<PanicRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(PanicRegistry::default())).clone()
+    }
+
+    /// Number of panics caught so far.
+    pub fn panic_count(&self) -> u64 {
+        self.panics.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if `module` was marked Degraded.
+    pub fn is_degraded(&self, module: &str) -> bool {
+        self.degraded.lock().unwrap().contains(module)
+    }
+
+    /// Modules currently marked Degraded.
+    pub fn degraded_modules(&self) -> Vec<String> {
+        self.degraded.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Renders panic metrics in the Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP echo_panics_total Number of caught panics");
+        let _ = writeln!(out, "# TYPE echo_panics_total counter");
+        let _ = writeln!(out, "echo_panics_total {}", self.panic_count());
+        let _ = writeln!(out, "# HELP echo_module_degraded Module is degraded after a panic");
+        let _ = writeln!(out, "# TYPE echo_module_degraded gauge");
+        for module in self.degraded.lock().unwrap().iter() {
+            let _ = writeln!(out, "echo_module_degraded{{module=\"{}\"}} 1", module);
+        }
+        out
+    }
+
+    fn record(&self, module: &str) {
+        self.panics.fetch_add(1, Ordering::Relaxed);
+        self.degraded.lock().unwrap().insert(module.to_string());
+    }
+}
+
+/// Runs `future`, converting a panic into a generic `Internal` error.
+pub async fn catch_panic<T>(
+    module: &str,
+    policy: PanicPolicy,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            PanicRegistry::global().record(module);
+
+            if policy == PanicPolicy::Abort {
+                error!("[PanicGuard] Module {} panicked: {} - aborting", module, message);
+                std::process::abort();
+            }
+
+            error!("[PanicGuard] Module {} panicked: {} - marked Degraded", module, message);
+            Err(Error::Protocol("Internal error".to_string()))
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Decorator that catches panics of every call.
+pub struct PanicGuardEchoService {
+    inner: Arc<dyn EchoService>,
+    module: String,
+    policy: PanicPolicy,
+}
+
+impl PanicGuardEchoService {
+    /// Wraps `inner`, attributing panics to `module`.
+    pub fn new(inner: Arc<dyn EchoService>, module: impl Into<String>, policy: PanicPolicy) -> Self {
+        Self { inner, module: module.into(), policy }
+    }
+}
+
+#[async_trait]
+impl EchoService for PanicGuardEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        catch_panic(&self.module, self.policy, self.inner.echo(message)).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        catch_panic(&self.module, self.policy, self.inner.echo_bytes(payload)).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        catch_panic(&self.module, self.policy, self.inner.echo_reliable(message, idempotency_key)).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        catch_panic(&self.module, self.policy, self.inner.echo_file(chunks)).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        catch_panic(&self.module, self.policy, self.inner.echo_with_session(session_id, message)).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        catch_panic(&self.module, self.policy, self.inner.get_info()).await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        catch_panic(&self.module, self.policy, self.inner.schedule_echo(message, schedule)).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        catch_panic(&self.module, self.policy, self.inner.cancel_scheduled_echo(job_id)).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        catch_panic(&self.module, self.policy, self.inner.get_history(query)).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        catch_panic(&self.module, self.policy, self.inner.stream_history(query)).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        catch_panic(&self.module, self.policy, self.inner.export_history(format, query)).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        catch_panic(&self.module, self.policy, self.inner.import_history(format, data)).await
+    }
+}
+
+/// Module wrapper that catches panics in `start`/`stop`.
+pub struct PanicGuardModule {
+    inner: Box<dyn Module>,
+    policy: PanicPolicy,
+}
+
+impl PanicGuardModule {
+    /// Wraps `inner`.
+    pub fn new(inner: Box<dyn Module>, policy: PanicPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[async_trait]
+impl Module for PanicGuardModule {
+    fn id(&self) -> &ModuleID {
+        self.inner.id()
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        let module = self.inner.id().to_string();
+        catch_panic(&module, self.policy, self.inner.start()).await
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        let module = self.inner.id().to_string();
+        catch_panic(&module, self.policy, self.inner.stop()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct PanickingService;
+
+    #[async_trait]
+    impl EchoService for PanickingService {
+        async fn echo(&self, _message: Arc<str>) -> Result<Arc<str>> {
+            panic!("boom");
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panic_becomes_error_and_degrades() {
+        let registry = PanicRegistry::global();
+        let before = registry.panic_count();
+        let service = PanicGuardEchoService::new(
+            Arc::new(PanickingService),
+            "panic-test",
+            PanicPolicy::Degrade,
+        );
+
+        let error = service.echo("Hello".into()).await.unwrap_err();
+        assert!(!error.to_string().contains("boom"), "panic text leaked: {}", error);
+        assert!(registry.panic_count() > before);
+        assert!(registry.is_degraded("panic-test"));
+
+        // Other calls keep working
+        assert_eq!(service.echo_bytes(Bytes::from_static(b"ok")).await.unwrap(), "ok");
+    }
+}
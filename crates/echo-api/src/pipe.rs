@@ -0,0 +1,577 @@
+//! Pipe Transport - Near-Direct Calls to a Child Process (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A supervised child (see [`crate::managed_unit`]) runs in another
+//! process, so Direct calls can't reach it - but dialing its gRPC port
+//! over TCP is more than its parent needs. With a pipe the parent calls
+//! the child over the child's stdin/stdout instead:
+//!
+//! ```text
+//! parent                                                   child (--serve-stdio)
+//!   gateways.get_service(Direct | Auto)
+//!     └── PipeEchoService ── stdin ──→ [len][id][method][body] ──→ serve_pipe ──→ EchoService
+//!                         ←─ stdout ── [len][id][status][body] ←──
+//! ```
+//!
+//! A frame is a `u32` length (big endian, of everything after it), a
+//! `u64` call ID, one method byte (requests) or status byte (replies)
+//! and the body - encoded as in the plugin ABI: UTF-8 for `echo`, raw
+//! bytes for `echo_bytes`, JSON for `get_info`. Calls are multiplexed:
+//! the child runs each as it arrives and replies in completion order.
+//! A failed call's status byte names its error kind (a validation
+//! error, `UNAVAILABLE`, `OVERLOADED`, ...) and the body carries the
+//! error text, so the parent rebuilds the error the child returned and
+//! it classifies as it would in-process. Only `echo`, `echo_bytes` and
+//! `get_info` are carried.
+//!
+//! A [`PipeChannel`] outlives the child: the managed unit attaches each
+//! new child's pipes and detaches them when it exits. Calls while no
+//! child is attached, or cut off by its exit, fail as `UNAVAILABLE`
+//! (so a warm standby can take them).
+//!
+//! The child's stdout belongs to the frames; it logs to stderr
+//! (`--log-stderr`).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::Bytes;
+use echo_contract::{
+    unavailable, ByteStream, EchoErrorKind, DEADLINE_EXCEEDED, INTEGRITY_ERROR, INVALID_RESPONSE, OVERLOADED,
+    PROTOCOL_UNSUPPORTED, STORAGE_ERROR, UNAVAILABLE, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use hsu_common::{Error, Result};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, info, warn};
+
+/// Largest frame (its length field) accepted either way.
+pub const MAX_PIPE_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Call ID and method/status byte.
+const HEADER_LEN: usize = 9;
+
+/// Request methods.
+const ECHO: u8 = 0;
+const ECHO_BYTES: u8 = 1;
+const GET_INFO: u8 = 2;
+
+/// Reply statuses.
+const OK: u8 = 0;
+/// The body is the text of an error of no known kind.
+const FAILED: u8 = 1;
+/// The body is the message of an `Error::Validation`.
+const INVALID: u8 = 2;
+/// The body is the text of an error of kind `ERROR_KINDS[status - KIND_BASE]`.
+const KIND_BASE: u8 = 16;
+
+/// Error kinds a reply status can name, by their prefix (append only:
+/// the index is the wire value).
+const ERROR_KINDS: [&str; 7] =
+    [DEADLINE_EXCEEDED, UNAVAILABLE, OVERLOADED, INVALID_RESPONSE, INTEGRITY_ERROR, PROTOCOL_UNSUPPORTED, STORAGE_ERROR];
+
+/// Requests queued for the writer before callers wait.
+const QUEUE_DEPTH: usize = 64;
+
+/// One request or reply.
+struct Frame {
+    id: u64,
+    code: u8,
+    body: Bytes,
+}
+
+impl Frame {
+    fn failed(id: u64, error: &Error) -> Self {
+        // The message alone, so the kind prefix still leads
+        let (code, text) = match error {
+            Error::Validation { message } => (INVALID, message.clone()),
+            Error::Protocol(message) => {
+                let kind = EchoErrorKind::classify(message)
+                    .and_then(|kind| ERROR_KINDS.iter().position(|code| *code == kind.code()));
+                (kind.map_or(FAILED, |index| KIND_BASE + index as u8), message.clone())
+            }
+            other => (FAILED, other.to_string()),
+        };
+        Self { id, code, body: Bytes::from(text) }
+    }
+
+    /// The error a failed reply carries; `None` for a status no version
+    /// of the pipe sends.
+    fn error(&self) -> Option<Error> {
+        let text = String::from_utf8_lossy(&self.body).into_owned();
+        match self.code {
+            FAILED => Some(Error::Protocol(text)),
+            INVALID => Some(Error::Validation { message: text }),
+            code => {
+                let kind = ERROR_KINDS.get(code.checked_sub(KIND_BASE)? as usize)?;
+                // Callers classify by the prefix: the status restores it
+                Some(Error::Protocol(if text.starts_with(kind) {
+                    text
+                } else {
+                    format!("{}: {}", kind, text)
+                }))
+            }
+        }
+    }
+
+    fn check_len(&self) -> Result<()> {
+        let len = HEADER_LEN + self.body.len();
+        if len > MAX_PIPE_FRAME_LEN {
+            return Err(Error::Protocol(format!(
+                "Pipe frame of {} bytes exceeds the {} byte limit", len, MAX_PIPE_FRAME_LEN
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    Error::Protocol(format!("Pipe I/O failed: {}", error))
+}
+
+/// Reads the next frame; `None` once the other side closed the pipe.
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Frame>> {
+    let len = match reader.read_u32().await {
+        Ok(len) => len as usize,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(io_error(e)),
+    };
+    if !(HEADER_LEN..=MAX_PIPE_FRAME_LEN).contains(&len) {
+        return Err(Error::Protocol(format!("Invalid pipe frame length {}", len)));
+    }
+    let mut buffer = vec![0; len];
+    reader.read_exact(&mut buffer).await.map_err(io_error)?;
+    let id = u64::from_be_bytes(buffer[..8].try_into().expect("8 bytes"));
+    let code = buffer[8];
+    Ok(Some(Frame { id, code, body: Bytes::from(buffer).slice(HEADER_LEN..) }))
+}
+
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &Frame) -> Result<()> {
+    let mut header = [0; 4 + HEADER_LEN];
+    header[..4].copy_from_slice(&((HEADER_LEN + frame.body.len()) as u32).to_be_bytes());
+    header[4..12].copy_from_slice(&frame.id.to_be_bytes());
+    header[12] = frame.code;
+    writer.write_all(&header).await.map_err(io_error)?;
+    writer.write_all(&frame.body).await.map_err(io_error)?;
+    // Replies are awaited one by one: nothing may sit in a buffer
+    writer.flush().await.map_err(io_error)
+}
+
+/// Writes queued frames until every sender is gone or the pipe breaks.
+async fn write_frames<W: AsyncWrite + Unpin>(mut writer: W, mut frames: mpsc::Receiver<Frame>) -> Result<()> {
+    while let Some(frame) = frames.recv().await {
+        write_frame(&mut writer, &frame).await?;
+    }
+    Ok(())
+}
+
+fn encode_info(info: &ServerInfo) -> Bytes {
+    serde_json::json!({
+        "module_id": info.module_id,
+        "instance_id": info.instance_id,
+        "version": info.version,
+        "git_hash": info.git_hash,
+        "uptime_ms": info.uptime.as_millis() as u64,
+        "features": info.features,
+    })
+    .to_string()
+    .into()
+}
+
+fn decode_info(bytes: &[u8]) -> Result<ServerInfo> {
+    let invalid = |detail: String| Error::Protocol(format!("Invalid pipe get_info reply: {}", detail));
+    let value: serde_json::Value = serde_json::from_slice(bytes).map_err(|e| invalid(e.to_string()))?;
+    let text = |field: &str| {
+        value[field].as_str().map(str::to_string).ok_or_else(|| invalid(format!("missing '{}'", field)))
+    };
+    Ok(ServerInfo {
+        module_id: text("module_id")?,
+        instance_id: text("instance_id")?,
+        version: text("version")?,
+        git_hash: text("git_hash")?,
+        uptime: Duration::from_millis(value["uptime_ms"].as_u64().ok_or_else(|| invalid("missing 'uptime_ms'".into()))?),
+        features: value["features"]
+            .as_array()
+            .map(|features| features.iter().filter_map(|f| f.as_str().map(str::to_string)).collect())
+            .unwrap_or_default(),
+    })
+}
+
+/// Serves `service` to frames read from `reader`, writing the replies to
+/// `writer` - the child side, on its stdin/stdout.
+///
+/// Returns once the parent closes the pipe and the calls in flight have
+/// replied; fails if the pipe breaks or carries garbage.
+pub async fn serve_pipe<R, W>(service: Arc<dyn EchoService>, mut reader: R, writer: W) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (replies, outgoing) = mpsc::channel(QUEUE_DEPTH);
+    let writing = tokio::spawn(write_frames(writer, outgoing));
+    info!("[Pipe] Serving echo calls on the stdio pipe");
+
+    let mut calls = 0u64;
+    while let Some(request) = read_frame(&mut reader).await? {
+        calls += 1;
+        let (service, replies) = (service.clone(), replies.clone());
+        tokio::spawn(async move {
+            let reply = match dispatch(&service, request.code, request.body).await {
+                Ok(body) => Frame { id: request.id, code: OK, body },
+                Err(e) => Frame::failed(request.id, &e),
+            };
+            let reply = match reply.check_len() {
+                Ok(()) => reply,
+                Err(e) => Frame::failed(request.id, &e),
+            };
+            // Fails only once the writer gave up on a broken pipe
+            let _ = replies.send(reply).await;
+        });
+    }
+    drop(replies);
+    writing.await.map_err(|e| Error::Protocol(format!("Pipe writer was aborted: {}", e)))??;
+    info!("[Pipe] Parent closed the pipe after {} calls", calls);
+    Ok(())
+}
+
+/// Decodes `body` for `method`, calls the service and encodes the reply.
+async fn dispatch(service: &Arc<dyn EchoService>, method: u8, body: Bytes) -> Result<Bytes> {
+    match method {
+        ECHO => {
+            let message = std::str::from_utf8(&body)
+                .map_err(|_| Error::Validation { message: "message is not valid UTF-8".to_string() })?;
+            Ok(Bytes::copy_from_slice(service.echo(message.into()).await?.as_bytes()))
+        }
+        ECHO_BYTES => service.echo_bytes(body).await,
+        GET_INFO => Ok(encode_info(&service.get_info().await?)),
+        other => Err(Error::Protocol(format!("Unknown pipe method {}", other))),
+    }
+}
+
+/// Calls waiting for their reply; `None` once the pipe closed.
+type Pending = Arc<Mutex<Option<HashMap<u64, oneshot::Sender<Frame>>>>>;
+
+/// The pipes of one child.
+struct PipeLink {
+    requests: mpsc::Sender<Frame>,
+    pending: Pending,
+}
+
+impl PipeLink {
+    /// Sends `request`; `None` if the pipe closed before the reply came.
+    async fn call(&self, request: Frame) -> Option<Frame> {
+        let id = request.id;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.pending.lock().unwrap().as_mut()?.insert(id, reply_tx);
+        // A caller giving up (deadline, Ctrl-C) mustn't leave its entry behind
+        let _forget = ForgetOnDrop { pending: &self.pending, id };
+        self.requests.send(request).await.ok()?;
+        reply_rx.await.ok()
+    }
+}
+
+struct ForgetOnDrop<'a> {
+    pending: &'a Pending,
+    id: u64,
+}
+
+impl Drop for ForgetOnDrop<'_> {
+    fn drop(&mut self) {
+        if let Some(pending) = self.pending.lock().unwrap().as_mut() {
+            pending.remove(&self.id);
+        }
+    }
+}
+
+/// Routes replies read from `reader` to their callers until the pipe
+/// closes, then fails the calls still waiting.
+async fn read_replies<R: AsyncRead + Unpin>(name: String, mut reader: R, pending: Pending) {
+    loop {
+        match read_frame(&mut reader).await {
+            Ok(Some(reply)) => {
+                let waiting = pending.lock().unwrap().as_mut().and_then(|pending| pending.remove(&reply.id));
+                match waiting {
+                    Some(caller) => {
+                        let _ = caller.send(reply);
+                    }
+                    // Its caller gave up
+                    None => debug!("[Pipe] Dropping reply to call {} on {}", reply.id, name),
+                }
+            }
+            Ok(None) => {
+                info!("[Pipe] Child closed the pipe of {}", name);
+                break;
+            }
+            Err(e) => {
+                warn!("[Pipe] Pipe of {} failed: {}", name, e);
+                break;
+            }
+        }
+    }
+    // Dropping the senders wakes their callers
+    pending.lock().unwrap().take();
+}
+
+/// The parent's end of a child's stdio pipe, kept across restarts.
+///
+/// Hand [`service`](Self::service) to the gateways (as
+/// [`GatewayOptions::pipe`](crate::GatewayOptions::pipe)) and the channel
+/// itself to the [`ManagedUnit`](crate::ManagedUnit) running the child.
+pub struct PipeChannel {
+    name: String,
+    next_id: AtomicU64,
+    link: Mutex<Option<Arc<PipeLink>>>,
+}
+
+impl PipeChannel {
+    /// Creates a channel to the child `name`; calls fail until a child is
+    /// attached.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), next_id: AtomicU64::new(0), link: Mutex::new(None) }
+    }
+
+    /// The child's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sends calls to a (new) child: requests to `writer` (its stdin),
+    /// replies from `reader` (its stdout). Calls on a previous child's
+    /// pipes fail as `UNAVAILABLE`.
+    pub fn attach<R, W>(&self, reader: R, writer: W)
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (requests, outgoing) = mpsc::channel(QUEUE_DEPTH);
+        let pending: Pending = Arc::new(Mutex::new(Some(HashMap::new())));
+        let name = self.name.clone();
+        tokio::spawn(async move {
+            if let Err(e) = write_frames(writer, outgoing).await {
+                warn!("[Pipe] Can't write to {}: {}", name, e);
+            }
+        });
+        tokio::spawn(read_replies(self.name.clone(), reader, pending.clone()));
+        debug!("[Pipe] Attached {}", self.name);
+        self.link.lock().unwrap().replace(Arc::new(PipeLink { requests, pending }));
+    }
+
+    /// Stops calling the current child (it exited): calls in flight fail
+    /// as `UNAVAILABLE` and its stdin is closed.
+    pub fn detach(&self) {
+        if let Some(link) = self.link.lock().unwrap().take() {
+            link.pending.lock().unwrap().take();
+            debug!("[Pipe] Detached {}", self.name);
+        }
+    }
+
+    /// Whether a child is attached and its pipe still open.
+    pub fn is_attached(&self) -> bool {
+        self.link.lock().unwrap().as_ref().is_some_and(|link| link.pending.lock().unwrap().is_some())
+    }
+
+    /// An `EchoService` calling whichever child is attached.
+    pub fn service(self: &Arc<Self>) -> Arc<dyn EchoService> {
+        Arc::new(PipeEchoService { channel: self.clone() })
+    }
+
+    async fn call(&self, method: u8, body: Bytes) -> Result<Bytes> {
+        let link = self.link.lock().unwrap().clone()
+            .ok_or_else(|| unavailable(format!("no child attached to the pipe of {}", self.name)))?;
+        let request = Frame { id: self.next_id.fetch_add(1, Ordering::Relaxed), code: method, body };
+        request.check_len()?;
+        let reply = link.call(request).await
+            .ok_or_else(|| unavailable(format!("the pipe of {} closed during the call", self.name)))?;
+        match reply.code {
+            OK => Ok(reply.body),
+            other => Err(reply.error()
+                .unwrap_or_else(|| Error::Protocol(format!("Unknown pipe reply status {}", other)))),
+        }
+    }
+}
+
+impl fmt::Debug for PipeChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeChannel")
+            .field("name", &self.name)
+            .field("attached", &self.is_attached())
+            .finish()
+    }
+}
+
+/// A child's service, called over its [`PipeChannel`].
+pub struct PipeEchoService {
+    channel: Arc<PipeChannel>,
+}
+
+impl PipeEchoService {
+    fn not_carried(&self, method: &str) -> Error {
+        debug!("{} isn't carried to {} over its pipe", method, self.channel.name);
+        Error::Protocol(format!("{} isn't carried by the stdio pipe transport", method))
+    }
+}
+
+#[async_trait]
+impl EchoService for PipeEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let reply = self.channel.call(ECHO, Bytes::copy_from_slice(message.as_bytes())).await?;
+        let reply = std::str::from_utf8(&reply).map_err(|_| Error::Protocol("Pipe echo reply is not UTF-8".to_string()))?;
+        Ok(reply.into())
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.channel.call(ECHO_BYTES, payload).await
+    }
+
+    async fn echo_reliable(&self, _message: Arc<str>, _idempotency_key: String) -> Result<EchoAck> {
+        Err(self.not_carried("echo_reliable"))
+    }
+
+    async fn echo_file(&self, _chunks: ByteStream) -> Result<FileDigest> {
+        Err(self.not_carried("echo_file"))
+    }
+
+    async fn echo_with_session(&self, _session_id: String, _message: Arc<str>) -> Result<SessionEcho> {
+        Err(self.not_carried("echo_with_session"))
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        decode_info(&self.channel.call(GET_INFO, Bytes::new()).await?)
+    }
+
+    async fn schedule_echo(&self, _message: Arc<str>, _schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        Err(self.not_carried("schedule_echo"))
+    }
+
+    async fn cancel_scheduled_echo(&self, _job_id: String) -> Result<bool> {
+        Err(self.not_carried("cancel_scheduled_echo"))
+    }
+
+    async fn get_history(&self, _query: HistoryQuery) -> Result<HistoryPage> {
+        Err(self.not_carried("get_history"))
+    }
+
+    async fn stream_history(&self, _query: HistoryQuery) -> Result<HistoryStream> {
+        Err(self.not_carried("stream_history"))
+    }
+
+    async fn export_history(&self, _format: HistoryExportFormat, _query: HistoryQuery) -> Result<ByteStream> {
+        Err(self.not_carried("export_history"))
+    }
+
+    async fn import_history(&self, _format: HistoryExportFormat, _data: ByteStream) -> Result<HistoryImportReport> {
+        Err(self.not_carried("import_history"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::is_unavailable;
+
+    /// Echoes messages (slowly if they start with "slow"); `echo_bytes`
+    /// fails as UNAVAILABLE (or a validation error, if empty).
+    struct MockService;
+
+    #[async_trait]
+    impl EchoService for MockService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            if message.starts_with("slow") {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            if payload.is_empty() {
+                return Err(Error::Validation { message: "payload must not be empty".to_string() });
+            }
+            Err(unavailable("backend down"))
+        }
+
+        async fn get_info(&self) -> Result<ServerInfo> {
+            Ok(ServerInfo {
+                module_id: "echo".to_string(),
+                instance_id: "child".to_string(),
+                version: "0.1.0".to_string(),
+                git_hash: "abc123".to_string(),
+                uptime: Duration::from_millis(1_500),
+                features: vec!["pipe".to_string()],
+            })
+        }
+    }
+
+    /// A channel attached to a "child" serving [`MockService`] in this
+    /// process; the handle ends when the child side is done.
+    fn attached() -> (Arc<PipeChannel>, tokio::task::JoinHandle<Result<()>>) {
+        let (parent_out, child_in) = tokio::io::duplex(1024);
+        let (child_out, parent_in) = tokio::io::duplex(1024);
+        let child = tokio::spawn(serve_pipe(Arc::new(MockService), child_in, child_out));
+        let channel = Arc::new(PipeChannel::new("echo"));
+        channel.attach(parent_in, parent_out);
+        (channel, child)
+    }
+
+    #[tokio::test]
+    async fn test_calls_cross_the_pipe() {
+        let (channel, _child) = attached();
+        let service = channel.service();
+
+        // Replies come back in completion order, each to its own caller
+        let (slow, fast) = tokio::join!(service.echo("slow hello".into()), service.echo("hi".into()));
+        assert_eq!(&*slow.unwrap(), "slow hello");
+        assert_eq!(&*fast.unwrap(), "hi");
+        assert_eq!(service.get_info().await.unwrap().instance_id, "child");
+        // Error kinds survive the process boundary
+        assert!(is_unavailable(&service.echo_bytes(Bytes::from_static(b"x")).await.unwrap_err()));
+        assert!(matches!(service.echo_bytes(Bytes::new()).await.unwrap_err(), Error::Validation { .. }));
+        assert!(service.get_history(HistoryQuery::default()).await.unwrap_err().to_string().contains("isn't carried"));
+    }
+
+    #[test]
+    fn test_failed_replies_name_their_kind() {
+        let reply = Frame::failed(7, &unavailable("draining"));
+        assert_eq!(reply.code, KIND_BASE + 1);
+        // The status restores a prefix the text lacks
+        let bare = Frame { body: Bytes::from_static(b"draining"), ..reply };
+        assert!(is_unavailable(&bare.error().unwrap()));
+        assert_eq!(Frame::failed(7, &Error::Protocol("boom".to_string())).code, FAILED);
+        assert!(Frame { id: 7, code: 99, body: Bytes::new() }.error().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_gateways_route_direct_calls_over_the_pipe() {
+        use hsu_common::Protocol;
+        use echo_contract::EchoServiceGateways;
+        use crate::gateways::{new_standalone_echo_service_gateways, GatewayOptions};
+
+        let (channel, _child) = attached();
+        let gateways = new_standalone_echo_service_gateways(GatewayOptions {
+            pipe: Some(channel),
+            ..Default::default()
+        });
+
+        let service = gateways.get_service(Protocol::Direct).await.unwrap();
+        assert_eq!(&*service.echo("hi".into()).await.unwrap(), "hi");
+        let (reply, call) = gateways.echo_with_info(Protocol::Direct, "hello".into()).await.unwrap();
+        assert_eq!((&*reply, call.protocol_used, call.endpoint.as_str()), ("hello", Protocol::Direct, "pipe:echo"));
+    }
+
+    #[tokio::test]
+    async fn test_calls_without_a_child_are_unavailable() {
+        let (channel, child) = attached();
+        let service = channel.service();
+        assert!(channel.is_attached());
+
+        channel.detach();
+        assert!(is_unavailable(&service.echo("hi".into()).await.unwrap_err()));
+        // The child sees its stdin close and stops
+        tokio::time::timeout(Duration::from_secs(5), child).await.unwrap().unwrap().unwrap();
+        assert!(!channel.is_attached());
+    }
+}
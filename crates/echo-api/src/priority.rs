@@ -0,0 +1,432 @@
+//! Priority Lanes (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Under heavy bulk traffic (e.g. load generation) interactive callers
+//! queue behind thousands of bulk requests. The scheduler admits at most
+//! `max_concurrent` calls and queues the rest in one lane per
+//! [`Priority`]; a freed slot goes to the next lane by weighted
+//! round-robin:
+//!
+//! ```text
+//! Direct / gRPC handler (RequestContext::current().priority)
+//!     ↓
+//! PriorityEchoService
+//!     ├─ High   lane ████████  weight 8
+//!     ├─ Normal lane ███       weight 3
+//!     └─ Low    lane █         weight 1
+//!           ↓ slot freed → smooth weighted round-robin pick
+//! EchoServiceImpl (≤ max_concurrent in flight)
+//! ```
+//!
+//! Weights rather than strict priority: low-priority work slows down
+//! under pressure but is never starved completely.
+//!
+//! # Rust Learning Note
+//!
+//! A waiter is woken by **sending it the slot** (a [`LanePermit`]) over a
+//! oneshot channel. If the waiter gave up meanwhile, `send` hands the
+//! permit back and it goes to the next waiter; if the waiter gives up
+//! right after the send, the permit is dropped with the channel and its
+//! `Drop` passes the slot on. Either way no slot is ever lost.
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::{Error, Result};
+use echo_contract::{
+    overloaded, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport,
+    HistoryPage, HistoryQuery, HistoryStream, Priority, RequestContext, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use tokio::sync::oneshot;
+
+/// Scheduler settings.
+#[derive(Debug, Clone)]
+pub struct PriorityLanesConfig {
+    /// Calls admitted concurrently; the rest wait in their lane.
+    pub max_concurrent: usize,
+    /// Share of freed slots per lane, indexed like [`Priority::ALL`].
+    pub weights: [u32; 3],
+    /// Calls allowed to wait across all lanes (unbounded if `None`).
+    ///
+    /// Beyond that, calls are rejected with [`echo_contract::overloaded`]
+    /// instead of queueing.
+    pub max_queued: Option<usize>,
+    /// Back-off hint sent with rejected calls.
+    pub retry_after: Duration,
+}
+
+impl Default for PriorityLanesConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 16,
+            weights: [8, 3, 1],
+            max_queued: None,
+            retry_after: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Per-priority counters of one lane.
+#[derive(Debug, Default)]
+struct LaneMetrics {
+    admitted: AtomicU64,
+    queued: AtomicU64,
+    rejected: AtomicU64,
+    wait_micros: AtomicU64,
+}
+
+/// Scheduler metrics by priority class.
+#[derive(Debug, Default)]
+pub struct PriorityMetrics {
+    lanes: [LaneMetrics; 3],
+}
+
+impl PriorityMetrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide metrics.
+    pub fn global() -> Arc<PriorityMetrics> {
+        static GLOBAL: OnceLock<Arc<PriorityMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(PriorityMetrics::new())).clone()
+    }
+
+    /// Returns the number of calls admitted for `priority`.
+    pub fn admitted(&self, priority: Priority) -> u64 {
+        self.lanes[priority.index()].admitted.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of calls currently queued for `priority`.
+    pub fn queued(&self, priority: Priority) -> u64 {
+        self.lanes[priority.index()].queued.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of calls rejected (queue full) for `priority`.
+    pub fn rejected(&self, priority: Priority) -> u64 {
+        self.lanes[priority.index()].rejected.load(Ordering::Relaxed)
+    }
+
+    /// Renders the metrics in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let metrics: [(&str, &str, &str, fn(&LaneMetrics) -> String); 4] = [
+            ("echo_priority_admitted_total", "Calls admitted by the priority scheduler", "counter",
+                |m| m.admitted.load(Ordering::Relaxed).to_string()),
+            ("echo_priority_queue_depth", "Calls waiting in a priority lane", "gauge",
+                |m| m.queued.load(Ordering::Relaxed).to_string()),
+            ("echo_priority_rejected_total", "Calls rejected because the lanes were full", "counter",
+                |m| m.rejected.load(Ordering::Relaxed).to_string()),
+            ("echo_priority_wait_seconds_total", "Time spent waiting in a priority lane", "counter",
+                |m| format!("{:.6}", m.wait_micros.load(Ordering::Relaxed) as f64 / 1e6)),
+        ];
+
+        let mut out = String::new();
+        for (name, help, kind, value) in metrics {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for priority in Priority::ALL {
+                let _ = writeln!(out, "{}{{priority=\"{}\"}} {}",
+                    name, priority, value(&self.lanes[priority.index()]));
+            }
+        }
+        out
+    }
+}
+
+struct SchedulerState {
+    in_flight: usize,
+    lanes: [VecDeque<oneshot::Sender<LanePermit>>; 3],
+    /// Smooth weighted round-robin credit per lane.
+    credits: [i64; 3],
+}
+
+/// Weighted admission scheduler shared by all calls to one service.
+pub struct PriorityScheduler {
+    config: PriorityLanesConfig,
+    state: Mutex<SchedulerState>,
+    metrics: Arc<PriorityMetrics>,
+}
+
+/// An admission slot; passed to the next waiter on drop.
+pub struct LanePermit {
+    /// `None` once the slot was handed on or returned.
+    scheduler: Option<Arc<PriorityScheduler>>,
+}
+
+impl Drop for LanePermit {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.scheduler.take() {
+            scheduler.release();
+        }
+    }
+}
+
+impl PriorityScheduler {
+    /// Creates a scheduler recording into `metrics`.
+    pub fn new(config: PriorityLanesConfig, metrics: Arc<PriorityMetrics>) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            state: Mutex::new(SchedulerState {
+                in_flight: 0,
+                lanes: Default::default(),
+                credits: [0; 3],
+            }),
+            metrics,
+        })
+    }
+
+    /// Waits for a slot in the lane of `priority`.
+    pub async fn acquire(self: &Arc<Self>, priority: Priority) -> Result<LanePermit> {
+        let lane = &self.metrics.lanes[priority.index()];
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            let idle = state.lanes.iter().all(VecDeque::is_empty);
+            if idle && state.in_flight < self.config.max_concurrent.max(1) {
+                state.in_flight += 1;
+                lane.admitted.fetch_add(1, Ordering::Relaxed);
+                return Ok(LanePermit { scheduler: Some(self.clone()) });
+            }
+            let queued: usize = state.lanes.iter().map(VecDeque::len).sum();
+            if self.config.max_queued.is_some_and(|max| queued >= max) {
+                lane.rejected.fetch_add(1, Ordering::Relaxed);
+                return Err(overloaded(self.config.retry_after,
+                    format!("{} calls already queued", queued)));
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.lanes[priority.index()].push_back(sender);
+            lane.queued.fetch_add(1, Ordering::Relaxed);
+            receiver
+        };
+
+        let queued_at = Instant::now();
+        let permit = receiver.await.map_err(|_| Error::Protocol(
+            "Priority scheduler dropped a queued call".to_string()));
+        lane.wait_micros.fetch_add(queued_at.elapsed().as_micros() as u64, Ordering::Relaxed);
+        if permit.is_ok() {
+            lane.admitted.fetch_add(1, Ordering::Relaxed);
+        }
+        permit
+    }
+
+    /// Hands a freed slot to the next waiter, or returns it to the pool.
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock().unwrap();
+        // The slot stays in flight - it moves to the waiter
+        let mut permit = LanePermit { scheduler: Some(self.clone()) };
+        while let Some(index) = self.pick_lane(&mut state) {
+            let sender = state.lanes[index].pop_front().expect("picked lane is not empty");
+            self.metrics.lanes[index].queued.fetch_sub(1, Ordering::Relaxed);
+            match sender.send(permit) {
+                Ok(()) => return,
+                // Waiter gave up; offer the slot to the next one
+                Err(returned) => permit = returned,
+            }
+        }
+        // Nobody waiting: disarm the permit (we hold the lock) and free the slot
+        permit.scheduler = None;
+        state.in_flight -= 1;
+    }
+
+    /// Smooth weighted round-robin over the non-empty lanes.
+    fn pick_lane(&self, state: &mut SchedulerState) -> Option<usize> {
+        let mut total = 0;
+        let mut best: Option<usize> = None;
+        for index in 0..3 {
+            if state.lanes[index].is_empty() {
+                continue;
+            }
+            let weight = i64::from(self.config.weights[index].max(1));
+            state.credits[index] += weight;
+            total += weight;
+            if best.map_or(true, |b| state.credits[index] > state.credits[b]) {
+                best = Some(index);
+            }
+        }
+        let best = best?;
+        state.credits[best] -= total;
+        Some(best)
+    }
+}
+
+/// Decorator that admits calls through a [`PriorityScheduler`].
+///
+/// The priority is read from [`RequestContext::current`], which the gRPC
+/// handler sets from request metadata and Direct callers set themselves.
+pub struct PriorityEchoService {
+    inner: Arc<dyn EchoService>,
+    scheduler: Arc<PriorityScheduler>,
+}
+
+impl PriorityEchoService {
+    /// Wraps `inner`, recording into the global [`PriorityMetrics`].
+    pub fn new(inner: Arc<dyn EchoService>, config: PriorityLanesConfig) -> Self {
+        Self::with_metrics(inner, config, PriorityMetrics::global())
+    }
+
+    /// Wraps `inner`, recording into `metrics`.
+    pub fn with_metrics(inner: Arc<dyn EchoService>, config: PriorityLanesConfig, metrics: Arc<PriorityMetrics>) -> Self {
+        Self {
+            inner,
+            scheduler: PriorityScheduler::new(config, metrics),
+        }
+    }
+
+    async fn admit(&self) -> Result<LanePermit> {
+        self.scheduler.acquire(RequestContext::current().priority).await
+    }
+}
+
+#[async_trait]
+impl EchoService for PriorityEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let _permit = self.admit().await?;
+        self.inner.echo(message).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let _permit = self.admit().await?;
+        self.inner.echo_bytes(payload).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let _permit = self.admit().await?;
+        self.inner.echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let _permit = self.admit().await?;
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let _permit = self.admit().await?;
+        self.inner.echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_freed_slots_follow_weights() {
+        let metrics = Arc::new(PriorityMetrics::new());
+        let scheduler = PriorityScheduler::new(
+            PriorityLanesConfig { max_concurrent: 1, weights: [3, 1, 1], ..Default::default() },
+            metrics.clone(),
+        );
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        // Occupy the only slot, then queue 4 high and 4 low waiters
+        let blocker = scheduler.acquire(Priority::Normal).await.unwrap();
+        let mut waiters = Vec::new();
+        for priority in [Priority::Low; 4].into_iter().chain([Priority::High; 4]) {
+            let scheduler = scheduler.clone();
+            let order = order.clone();
+            waiters.push(tokio::spawn(async move {
+                let _permit = scheduler.acquire(priority).await.unwrap();
+                order.lock().unwrap().push(priority);
+            }));
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(metrics.queued(Priority::High), 4);
+
+        drop(blocker);
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+
+        // 3:1 - high wins three of the first four slots, low isn't starved
+        let order = order.lock().unwrap();
+        assert_eq!(order[..4].iter().filter(|p| **p == Priority::High).count(), 3);
+        assert_eq!(order.len(), 8);
+        assert_eq!(metrics.admitted(Priority::Low), 4);
+        assert_eq!(metrics.queued(Priority::Low), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_does_not_leak_slot() {
+        let scheduler = PriorityScheduler::new(
+            PriorityLanesConfig { max_concurrent: 1, ..Default::default() },
+            Arc::new(PriorityMetrics::new()),
+        );
+
+        let blocker = scheduler.acquire(Priority::Normal).await.unwrap();
+        let cancelled = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            scheduler.acquire(Priority::High),
+        ).await;
+        assert!(cancelled.is_err());
+
+        drop(blocker);
+        // The slot is free again despite the abandoned waiter
+        let permit = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            scheduler.acquire(Priority::Low),
+        ).await;
+        assert!(permit.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_rejects_with_retry_after() {
+        let metrics = Arc::new(PriorityMetrics::new());
+        let scheduler = PriorityScheduler::new(
+            PriorityLanesConfig {
+                max_concurrent: 1,
+                max_queued: Some(0),
+                retry_after: Duration::from_millis(250),
+                ..Default::default()
+            },
+            metrics.clone(),
+        );
+
+        let _blocker = scheduler.acquire(Priority::Normal).await.unwrap();
+        let error = scheduler.acquire(Priority::Low).await.err().unwrap();
+
+        assert!(echo_contract::is_overloaded(&error));
+        assert_eq!(echo_contract::retry_after(&error), Some(Duration::from_millis(250)));
+        assert_eq!(metrics.rejected(Priority::Low), 1);
+    }
+
+    #[test]
+    fn test_render_prometheus() {
+        let metrics = PriorityMetrics::new();
+        let text = metrics.render_prometheus();
+        assert!(text.contains("# TYPE echo_priority_queue_depth gauge"));
+        assert!(text.contains("echo_priority_admitted_total{priority=\"high\"} 0"));
+    }
+}
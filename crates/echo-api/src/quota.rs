@@ -0,0 +1,549 @@
+//! Per-Caller Byte Quotas (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Priority lanes and concurrency limits count **calls**; a caller sending
+//! a few megabyte-sized messages costs as much as thousands of small ones
+//! but looks harmless to them. The ledger counts **bytes** echoed per
+//! caller over a rolling window, and can enforce a byte budget:
+//!
+//! ```text
+//! Direct / gRPC handler (RequestContext::current().caller, `x-echo-caller`)
+//!     ↓
+//! ByteQuotaEchoService
+//!     ├─ request > limit          → validation error         (INVALID_ARGUMENT)
+//!     ├─ window + request > limit → overloaded(retry_after)   (RESOURCE_EXHAUSTED)
+//!     └─ charge the request bytes → inner service
+//!                ↓
+//!           ByteLedger (global)
+//!             "tenant-a" [slot][slot][slot]...[slot]  ← window, e.g. 12 × 5s
+//!             "anonymous"[slot][slot]...
+//!                ↓
+//!           GET /metrics, GET /quotas (admin endpoint)
+//! ```
+//!
+//! The window is split into slots: expired slots drop off whole, so usage
+//! declines in steps of one slot instead of resetting all at once.
+//!
+//! Bytes are payload bytes as [`crate::SizeMetricsEchoService`] counts
+//! them, charged when the call is admitted. A streamed file is only known
+//! once it has been read, so it is charged afterwards: an upload may
+//! overshoot the budget once, the next call is rejected.
+//!
+//! Caller accounts are bounded by `max_callers`. When the ledger is full,
+//! the least recently charged caller without usage in the window makes
+//! room; only when every account is busy do new callers share
+//! [`OVERFLOW_CALLER`].
+//!
+//! The caller name is self-declared (there is no authentication), so the
+//! quota protects against runaway clients, not hostile ones.
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! if !ledger.TryCharge(callerFrom(ctx), len(message)) {
+//!     return status.Error(codes.ResourceExhausted, "byte quota exceeded")
+//! }
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use hsu_common::{Error, Result};
+use echo_contract::{
+    overloaded, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport,
+    HistoryPage, HistoryQuery, HistoryStream, Message, RequestContext, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+use crate::metrics::escape_label;
+
+/// Account of calls without a caller name.
+pub const ANONYMOUS_CALLER: &str = "anonymous";
+
+/// Account of callers beyond [`ByteQuotaConfig::max_callers`].
+pub const OVERFLOW_CALLER: &str = "_other";
+
+/// Byte accounting settings.
+#[derive(Debug, Clone)]
+pub struct ByteQuotaConfig {
+    /// Bytes each caller may echo per window (accounting only if `None`).
+    pub limit_bytes: Option<u64>,
+    /// Length of the rolling window.
+    pub window: Duration,
+    /// Slots the window is split into.
+    pub slots: u32,
+    /// Callers tracked by name; idle ones are evicted for new callers,
+    /// which share [`OVERFLOW_CALLER`] while all accounts are busy.
+    ///
+    /// Caller names come from request metadata, so this bounds memory and
+    /// metric cardinality.
+    pub max_callers: usize,
+}
+
+impl Default for ByteQuotaConfig {
+    fn default() -> Self {
+        Self {
+            limit_bytes: None,
+            window: Duration::from_secs(60),
+            slots: 12,
+            max_callers: 1000,
+        }
+    }
+}
+
+/// Byte usage of one caller.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallerUsage {
+    /// Bytes echoed within the current window.
+    pub window_bytes: u64,
+    /// Bytes echoed since the start.
+    pub total_bytes: u64,
+    /// Calls charged.
+    pub calls: u64,
+    /// Calls rejected for exceeding the quota.
+    pub rejected: u64,
+}
+
+#[derive(Debug, Default)]
+struct CallerAccount {
+    /// `(slot number, bytes)`, oldest first.
+    slots: VecDeque<(u64, u64)>,
+    /// Slot of the last charge, to evict the longest idle account.
+    last_slot: u64,
+    usage: CallerUsage,
+}
+
+/// Name, help, type and value of one per-caller metric.
+type UsageMetric = (&'static str, &'static str, &'static str, fn(&CallerUsage) -> u64);
+
+struct LedgerState {
+    config: ByteQuotaConfig,
+    callers: BTreeMap<String, CallerAccount>,
+}
+
+/// Per-caller byte usage over a rolling window, with optional limits.
+pub struct ByteLedger {
+    started: Instant,
+    state: Mutex<LedgerState>,
+    /// Rejections across all callers (cheap to read for metrics).
+    rejected: AtomicU64,
+}
+
+impl ByteLedger {
+    /// Creates an empty ledger.
+    pub fn new(config: ByteQuotaConfig) -> Self {
+        Self {
+            started: Instant::now(),
+            state: Mutex::new(LedgerState { config, callers: BTreeMap::new() }),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the process-wide ledger (accounting only until configured).
+    pub fn global() -> Arc<ByteLedger> {
+        static GLOBAL: OnceLock<Arc<ByteLedger>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(ByteLedger::new(ByteQuotaConfig::default()))).clone()
+    }
+
+    /// Replaces the settings. Usage within the old window is forgotten,
+    /// totals are kept.
+    pub fn configure(&self, config: ByteQuotaConfig) {
+        let mut state = self.state.lock().unwrap();
+        state.config = config;
+        for account in state.callers.values_mut() {
+            account.slots.clear();
+            account.usage.window_bytes = 0;
+        }
+    }
+
+    /// Charges `bytes` to `caller`, unless that would exceed its quota.
+    ///
+    /// Returns [`echo_contract::overloaded`] with the time until the
+    /// oldest slot leaves the window when the quota is exhausted, and a
+    /// validation error when `bytes` alone exceeds it (retrying can't help).
+    pub fn try_charge(&self, caller: Option<&str>, bytes: u64) -> Result<()> {
+        self.charge_at(caller, bytes, true, Instant::now())
+    }
+
+    /// Charges `bytes` to `caller` regardless of its quota.
+    pub fn record(&self, caller: Option<&str>, bytes: u64) {
+        let _ = self.charge_at(caller, bytes, false, Instant::now());
+    }
+
+    /// Returns the usage of `caller` (zero if unknown).
+    pub fn usage(&self, caller: &str) -> CallerUsage {
+        let mut state = self.state.lock().unwrap();
+        let (now, slots) = (self.slot(&state.config, Instant::now()), window_slots(&state.config));
+        state.callers.get_mut(caller).map(|account| {
+            expire(account, now, slots);
+            account.usage
+        }).unwrap_or_default()
+    }
+
+    /// Returns the usage of every caller, by name.
+    pub fn usages(&self) -> Vec<(String, CallerUsage)> {
+        let mut state = self.state.lock().unwrap();
+        let (now, slots) = (self.slot(&state.config, Instant::now()), window_slots(&state.config));
+        state.callers.iter_mut().map(|(caller, account)| {
+            expire(account, now, slots);
+            (caller.clone(), account.usage)
+        }).collect()
+    }
+
+    /// Returns the number of calls rejected across all callers.
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Renders the usage in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let usages = self.usages();
+        let metrics: [UsageMetric; 4] = [
+            ("echo_caller_bytes_total", "Bytes echoed per caller", "counter", |u| u.total_bytes),
+            ("echo_caller_window_bytes", "Bytes echoed per caller within the quota window", "gauge",
+                |u| u.window_bytes),
+            ("echo_caller_calls_total", "Calls charged per caller", "counter", |u| u.calls),
+            ("echo_caller_quota_rejected_total", "Calls rejected by the byte quota", "counter", |u| u.rejected),
+        ];
+
+        let mut out = String::new();
+        for (name, help, kind, value) in metrics {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (caller, usage) in &usages {
+                let _ = writeln!(out, "{}{{caller=\"{}\"}} {}", name, escape_label(caller), value(usage));
+            }
+        }
+        out
+    }
+
+    /// Renders the settings and per-caller usage as text (`GET /quotas`).
+    pub fn render_text(&self) -> String {
+        let (limit, window) = {
+            let state = self.state.lock().unwrap();
+            (state.config.limit_bytes, state.config.window)
+        };
+        let mut out = match limit {
+            Some(limit) => format!("byte quota: {} bytes per {:?} per caller\n", limit, window),
+            None => format!("byte quota: none (accounting over {:?})\n", window),
+        };
+        for (caller, usage) in self.usages() {
+            let _ = writeln!(out, "{}: window={} total={} calls={} rejected={}",
+                caller, usage.window_bytes, usage.total_bytes, usage.calls, usage.rejected);
+        }
+        out
+    }
+
+    /// Number of the slot `at` falls into.
+    fn slot(&self, config: &ByteQuotaConfig, at: Instant) -> u64 {
+        (at.saturating_duration_since(self.started).as_nanos() / slot_nanos(config)) as u64
+    }
+
+    fn charge_at(&self, caller: Option<&str>, bytes: u64, enforce: bool, at: Instant) -> Result<()> {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        let (now, slots) = (self.slot(&state.config, at), window_slots(&state.config));
+        let slot_nanos = slot_nanos(&state.config);
+        let limit = state.config.limit_bytes.filter(|_| enforce);
+
+        if let Some(limit) = limit.filter(|limit| bytes > *limit) {
+            return Err(Error::Validation {
+                message: Message::RequestOverQuota { bytes, limit }.localize(),
+            });
+        }
+
+        let mut name = caller.unwrap_or(ANONYMOUS_CALLER);
+        if !state.callers.contains_key(name) && state.callers.len() >= state.config.max_callers {
+            // Make room by dropping the longest idle accounts, if any are idle
+            for account in state.callers.values_mut() {
+                expire(account, now, slots);
+            }
+            while state.callers.len() >= state.config.max_callers {
+                let idle = state.callers.iter()
+                    .filter(|(_, account)| account.slots.is_empty())
+                    .min_by_key(|(_, account)| account.last_slot)
+                    .map(|(caller, _)| caller.clone());
+                match idle {
+                    Some(idle) => {
+                        state.callers.remove(&idle);
+                    }
+                    None => {
+                        name = OVERFLOW_CALLER;
+                        break;
+                    }
+                }
+            }
+        }
+        if !state.callers.contains_key(name) {
+            state.callers.insert(name.to_string(), CallerAccount::default());
+        }
+        let account = state.callers.get_mut(name).expect("account was just inserted");
+        expire(account, now, slots);
+
+        if let Some(limit) = limit {
+            if account.usage.window_bytes.saturating_add(bytes) > limit {
+                account.usage.rejected += 1;
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                // The oldest slot frees its bytes when it leaves the window
+                let oldest = account.slots.front().map_or(now, |(slot, _)| *slot);
+                let frees_at = Duration::from_nanos(((oldest + slots) as u128 * slot_nanos) as u64);
+                let retry_after = frees_at
+                    .saturating_sub(at.saturating_duration_since(self.started))
+                    .max(Duration::from_millis(1));
+                let message = Message::QuotaExceeded {
+                    caller: name,
+                    used: account.usage.window_bytes,
+                    limit,
+                    window: state.config.window,
+                };
+                return Err(overloaded(retry_after, message.localize()));
+            }
+        }
+
+        match account.slots.back_mut() {
+            Some((slot, slot_bytes)) if *slot == now => *slot_bytes += bytes,
+            _ => account.slots.push_back((now, bytes)),
+        }
+        account.last_slot = now;
+        account.usage.window_bytes += bytes;
+        account.usage.total_bytes += bytes;
+        account.usage.calls += 1;
+        Ok(())
+    }
+}
+
+fn window_slots(config: &ByteQuotaConfig) -> u64 {
+    u64::from(config.slots.max(1))
+}
+
+fn slot_nanos(config: &ByteQuotaConfig) -> u128 {
+    (config.window / config.slots.max(1)).as_nanos().max(1)
+}
+
+/// Drops the slots that left the window ending at slot `now`.
+fn expire(account: &mut CallerAccount, now: u64, slots: u64) {
+    while let Some(&(slot, bytes)) = account.slots.front() {
+        if slot + slots > now {
+            break;
+        }
+        account.slots.pop_front();
+        account.usage.window_bytes -= bytes;
+    }
+}
+
+/// Decorator that charges echoed bytes to the caller of each call.
+///
+/// The caller is read from [`RequestContext::current`], which the gRPC
+/// handler sets from request metadata and Direct callers set themselves.
+pub struct ByteQuotaEchoService {
+    inner: Arc<dyn EchoService>,
+    ledger: Arc<ByteLedger>,
+}
+
+impl ByteQuotaEchoService {
+    /// Wraps `inner`, configuring and charging the global [`ByteLedger`].
+    pub fn new(inner: Arc<dyn EchoService>, config: ByteQuotaConfig) -> Self {
+        let ledger = ByteLedger::global();
+        ledger.configure(config);
+        Self::with_ledger(inner, ledger)
+    }
+
+    /// Wraps `inner`, charging `ledger`.
+    pub fn with_ledger(inner: Arc<dyn EchoService>, ledger: Arc<ByteLedger>) -> Self {
+        Self { inner, ledger }
+    }
+
+    fn charge(&self, bytes: usize) -> Result<()> {
+        self.ledger.try_charge(RequestContext::current().caller.as_deref(), bytes as u64)
+    }
+}
+
+#[async_trait]
+impl EchoService for ByteQuotaEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.charge(message.len())?;
+        self.inner.echo(message).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.charge(payload.len())?;
+        self.inner.echo_bytes(payload).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.charge(message.len())?;
+        self.inner.echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        // Size unknown up front: refuse only if the quota is already used up
+        self.charge(0)?;
+        let streamed = Arc::new(AtomicU64::new(0));
+        let counter = streamed.clone();
+        let chunks: ByteStream = Box::pin(chunks.inspect(move |chunk| {
+            if let Ok(data) = chunk {
+                counter.fetch_add(data.len() as u64, Ordering::Relaxed);
+            }
+        }));
+
+        let result = self.inner.echo_file(chunks).await;
+        self.ledger.record(RequestContext::current().caller.as_deref(), streamed.load(Ordering::Relaxed));
+        result
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.charge(message.len())?;
+        self.inner.echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockService;
+
+    #[async_trait]
+    impl EchoService for MockService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+    }
+
+    fn ledger(limit_bytes: Option<u64>) -> ByteLedger {
+        ByteLedger::new(ByteQuotaConfig {
+            limit_bytes,
+            window: Duration::from_secs(10),
+            slots: 10,
+            max_callers: 2,
+        })
+    }
+
+    #[test]
+    fn test_rolling_window_enforces_the_limit() {
+        let ledger = ledger(Some(100));
+        let start = ledger.started;
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        ledger.charge_at(Some("a"), 60, true, at(0)).unwrap();
+        ledger.charge_at(Some("a"), 30, true, at(5)).unwrap();
+        // Other callers have their own budget
+        ledger.charge_at(Some("b"), 100, true, at(5)).unwrap();
+
+        let error = ledger.charge_at(Some("a"), 20, true, at(9)).unwrap_err();
+        assert!(echo_contract::is_overloaded(&error));
+        // The 60 bytes of second 0 leave the window at second 10
+        assert_eq!(echo_contract::retry_after(&error), Some(Duration::from_secs(1)));
+
+        ledger.charge_at(Some("a"), 20, true, at(10)).unwrap();
+        let usage = ledger.state.lock().unwrap().callers["a"].usage;
+        assert_eq!(usage, CallerUsage { window_bytes: 50, total_bytes: 110, calls: 3, rejected: 1 });
+        assert_eq!(ledger.rejected(), 1);
+    }
+
+    #[test]
+    fn test_accounting_without_limit() {
+        let ledger = ledger(None);
+
+        ledger.try_charge(None, 1_000_000).unwrap();
+        ledger.try_charge(Some("a"), 5).unwrap();
+        // Beyond max_callers (2), new callers share one account
+        ledger.try_charge(Some("b"), 5).unwrap();
+        ledger.try_charge(Some("c"), 7).unwrap();
+
+        assert_eq!(ledger.usage(ANONYMOUS_CALLER).total_bytes, 1_000_000);
+        assert_eq!(ledger.usage("a").total_bytes, 5);
+        assert_eq!(ledger.usage(OVERFLOW_CALLER).total_bytes, 12);
+        assert_eq!(ledger.usage("b"), CallerUsage::default());
+
+        let metrics = ledger.render_prometheus();
+        assert!(metrics.contains("echo_caller_bytes_total{caller=\"anonymous\"} 1000000"));
+        assert!(ledger.render_text().starts_with("byte quota: none"));
+    }
+
+    #[test]
+    fn test_idle_callers_make_room() {
+        let ledger = ledger(Some(100));
+        let start = ledger.started;
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        ledger.charge_at(Some("a"), 10, true, at(0)).unwrap();
+        ledger.charge_at(Some("b"), 10, true, at(5)).unwrap();
+        // Both accounts are busy, so "c" shares the overflow account
+        ledger.charge_at(Some("c"), 10, true, at(6)).unwrap();
+        assert_eq!(ledger.usage(OVERFLOW_CALLER).calls, 1);
+
+        // All three are idle once their slots expired; the longest idle
+        // ones make room for "d"
+        ledger.charge_at(Some("d"), 10, true, at(17)).unwrap();
+        let callers: Vec<_> = ledger.state.lock().unwrap().callers.keys().cloned().collect();
+        assert_eq!(callers, ["_other", "d"]);
+    }
+
+    #[test]
+    fn test_request_over_the_limit_is_invalid() {
+        let ledger = ledger(Some(100));
+
+        let error = ledger.try_charge(Some("a"), 101).unwrap_err();
+        assert!(matches!(error, Error::Validation { .. }));
+        assert!(!echo_contract::is_overloaded(&error));
+        assert_eq!(ledger.usage("a"), CallerUsage::default());
+    }
+
+    #[tokio::test]
+    async fn test_service_charges_the_context_caller() {
+        let ledger = Arc::new(ledger(Some(8)));
+        let service = ByteQuotaEchoService::with_ledger(Arc::new(MockService), ledger.clone());
+        let tenant = RequestContext::default().with_caller("tenant");
+
+        tenant.clone().scope(service.echo("hello".into())).await.unwrap();
+        let error = tenant.scope(service.echo("hello".into())).await.unwrap_err();
+        assert!(echo_contract::is_overloaded(&error));
+        // Anonymous calls are accounted separately
+        service.echo("hello".into()).await.unwrap();
+
+        assert_eq!(ledger.usage("tenant"), CallerUsage { window_bytes: 5, total_bytes: 5, calls: 1, rejected: 1 });
+        assert_eq!(ledger.usage(ANONYMOUS_CALLER).calls, 1);
+    }
+}
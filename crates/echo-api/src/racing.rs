@@ -0,0 +1,222 @@
+//! Protocol Racing (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A module's gRPC port usually serves JSON transcoding too, so an `Auto`
+//! call without a Direct handler can go either way. Which one comes up
+//! faster (a proxy may block HTTP/2, a cold gRPC channel pays its TLS and
+//! HTTP/2 setup) is only known by trying: the first `echo` is sent over
+//! **both**, the first reply wins, and the winner carries every later call:
+//!
+//! ```text
+//! echo("a") (undecided)
+//!     ↓
+//! RacingEchoService
+//!     ├─ gRPC      ──→ "a" ─┐ first Ok wins → ProtocolRace { winner, ... }
+//!     └─ JSON/HTTP ──→ "a" ─┘ the other call is dropped
+//! echo("b") (decided) ──→ winner only
+//! ```
+//!
+//! The race measures the real call, connection setup included, not a
+//! probe the server answers differently. The price: the server may echo
+//! the first message twice. Only `echo` races; other methods go to gRPC
+//! until an echo decided. If both contestants fail, nothing is decided
+//! and the caller gets gRPC's error; the next echo races again.
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! replies := make(chan result, 2)
+//! go func() { r, err := grpc.Echo(ctx, msg); replies <- result{"grpc", r, err} }()
+//! go func() { r, err := http.Echo(ctx, msg); replies <- result{"http", r, err} }()
+//! ```
+
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{FuturesUnordered, StreamExt};
+use hsu_common::{Protocol, Result};
+use tracing::{debug, info};
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport,
+    HistoryPage, HistoryQuery, HistoryStream, ProtocolRace, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+/// Decorator racing the first `echo` over gRPC and JSON/HTTP.
+///
+/// The outcome is kept in the shared `race` cell, so gateways handed out
+/// later skip the race (see `GatewayOptions::protocol_racing`).
+pub struct RacingEchoService {
+    grpc: Arc<dyn EchoService>,
+    http: Arc<dyn EchoService>,
+    race: Arc<OnceLock<ProtocolRace>>,
+}
+
+impl RacingEchoService {
+    /// Races `grpc` against `http`, recording the outcome in `race`.
+    pub fn new(grpc: Arc<dyn EchoService>, http: Arc<dyn EchoService>, race: Arc<OnceLock<ProtocolRace>>) -> Self {
+        Self { grpc, http, race }
+    }
+
+    /// The transport calls go to: the winner, or gRPC while undecided.
+    fn current(&self) -> &Arc<dyn EchoService> {
+        match self.race.get() {
+            Some(race) if race.winner == Protocol::Http => &self.http,
+            _ => &self.grpc,
+        }
+    }
+
+    /// Sends `message` over both transports, returning the first reply.
+    async fn race_echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let started = Instant::now();
+        let mut contestants: FuturesUnordered<_> = [(Protocol::Grpc, &self.grpc), (Protocol::Http, &self.http)]
+            .into_iter()
+            .map(|(protocol, service)| {
+                let message = message.clone();
+                async move { (protocol, service.echo(message).await) }
+            })
+            .collect();
+        let mut failures = Vec::new();
+        let mut grpc_error = None;
+        while let Some((protocol, result)) = contestants.next().await {
+            match result {
+                Ok(reply) => {
+                    let race = ProtocolRace { winner: protocol, decided_after: started.elapsed(), failures };
+                    info!("{:?} won the protocol race after {:?} ({} failed)",
+                        race.winner, race.decided_after, race.failures.len());
+                    // A concurrent first call may have decided already
+                    let _ = self.race.set(race);
+                    return Ok(reply);
+                }
+                Err(e) => {
+                    failures.push((protocol, e.to_string()));
+                    if protocol == Protocol::Grpc {
+                        grpc_error = Some(e);
+                    }
+                }
+            }
+        }
+        debug!("Neither transport answered the protocol race: {:?}", failures);
+        Err(grpc_error.expect("gRPC contestant reports its failure"))
+    }
+}
+
+#[async_trait]
+impl EchoService for RacingEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        if self.race.get().is_some() {
+            return self.current().echo(message).await;
+        }
+        self.race_echo(message).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.current().echo_bytes(payload).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.current().echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.current().echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.current().echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.current().get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.current().schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.current().cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.current().get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.current().stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.current().export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.current().import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Echoes with a prefix after `delay`, counting calls; "down" fails.
+    struct Transport {
+        prefix: &'static str,
+        delay: Duration,
+        calls: AtomicUsize,
+    }
+
+    impl Transport {
+        fn new(prefix: &'static str, delay_ms: u64) -> Arc<Self> {
+            Arc::new(Self { prefix, delay: Duration::from_millis(delay_ms), calls: AtomicUsize::new(0) })
+        }
+    }
+
+    #[async_trait]
+    impl EchoService for Transport {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            if self.prefix == "down" {
+                return Err(echo_contract::unavailable("connection refused"));
+            }
+            Ok(format!("{}{}", self.prefix, message).into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_first_reply_wins_and_carries_later_calls() {
+        let (grpc, http) = (Transport::new("grpc:", 50), Transport::new("http:", 10));
+        let race = Arc::new(OnceLock::new());
+        let service = RacingEchoService::new(grpc.clone(), http.clone(), race.clone());
+
+        assert_eq!(&*service.echo("a".into()).await.unwrap(), "http:a");
+        assert_eq!(race.get().unwrap().winner, Protocol::Http);
+
+        assert_eq!(&*service.echo("b".into()).await.unwrap(), "http:b");
+        assert_eq!((grpc.calls.load(Ordering::SeqCst), http.calls.load(Ordering::SeqCst)), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_failed_contestant_is_recorded() {
+        let race = Arc::new(OnceLock::new());
+        let service = RacingEchoService::new(Transport::new("down", 1), Transport::new("http:", 10), race.clone());
+
+        assert_eq!(&*service.echo("a".into()).await.unwrap(), "http:a");
+        let race = race.get().unwrap();
+        assert_eq!(race.failures.len(), 1);
+        assert_eq!(race.failures[0].0, Protocol::Grpc);
+    }
+
+    #[tokio::test]
+    async fn test_nothing_is_decided_if_both_fail() {
+        let race = Arc::new(OnceLock::new());
+        let service = RacingEchoService::new(Transport::new("down", 1), Transport::new("down", 1), race.clone());
+
+        assert!(echo_contract::is_unavailable(&service.echo("a".into()).await.unwrap_err()));
+        assert!(race.get().is_none());
+    }
+}
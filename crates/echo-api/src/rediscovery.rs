@@ -0,0 +1,213 @@
+//! Rediscovery After Maintenance (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! With [`Discovery::Mdns`](crate::Discovery) or a registry backend, the
+//! gateways look the module's address up once and keep using it. When
+//! that instance enters maintenance mode it refuses every new call (see
+//! [`crate::maintenance_mode`]), while the registry already points at the
+//! other instances - so the first refusal forgets the address:
+//!
+//! ```text
+//! gateway to 10.0.0.1:50051 (discovered)
+//!     ↓
+//! RediscoveringEchoService ── UNAVAILABLE: maintenance: ... → forget 10.0.0.1:50051
+//!     ↓                                                         ↓
+//! EchoGrpcGateway                                 next get_service() discovers again
+//! ```
+//!
+//! The refused call still fails (the caller may retry it), and gateways
+//! handed out before keep their address; only later `get_service` calls
+//! go elsewhere. Other errors keep the address: an instance that is down
+//! is the reconnecting channel's business.
+//!
+//! # Rust Learning Note
+//!
+//! The cache is a `std::sync::Mutex`, not tokio's: it is never held
+//! across an `.await`, so forgetting the address needs no async context.
+
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::Result;
+use tracing::info;
+use echo_contract::{
+    maintenance_reason, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+/// Address found by discovery, shared by the gateways and their decorators.
+pub type DiscoveredAddress = Arc<Mutex<Option<String>>>;
+
+/// Decorator forgetting a discovered address once its instance is in
+/// maintenance (see the module docs).
+pub struct RediscoveringEchoService {
+    inner: Arc<dyn EchoService>,
+    address: String,
+    discovered: DiscoveredAddress,
+}
+
+impl RediscoveringEchoService {
+    /// Wraps `inner`, the gateway to `address` as found in `discovered`.
+    pub fn new(inner: Arc<dyn EchoService>, address: String, discovered: DiscoveredAddress) -> Self {
+        Self { inner, address, discovered }
+    }
+
+    /// Forgets the address if `result` is a maintenance refusal (and the
+    /// address wasn't replaced already).
+    fn check<T>(&self, result: Result<T>) -> Result<T> {
+        if let Some(reason) = result.as_ref().err().and_then(maintenance_reason) {
+            let mut discovered = self.discovered.lock().unwrap();
+            if discovered.as_deref() == Some(self.address.as_str()) {
+                info!("{} is in maintenance ({}), discovering the module again", self.address, reason);
+                *discovered = None;
+            }
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl EchoService for RediscoveringEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        self.check(self.inner.echo(message).await)
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.check(self.inner.echo_bytes(payload).await)
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        self.check(self.inner.echo_reliable(message, idempotency_key).await)
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.check(self.inner.echo_file(chunks).await)
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.check(self.inner.echo_with_session(session_id, message).await)
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.check(self.inner.get_info().await)
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.check(self.inner.schedule_echo(message, schedule).await)
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.check(self.inner.cancel_scheduled_echo(job_id).await)
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.check(self.inner.get_history(query).await)
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.check(self.inner.stream_history(query).await)
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.check(self.inner.export_history(format, query).await)
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.check(self.inner.import_history(format, data).await)
+    }
+}
+
+#[cfg(all(test, feature = "grpc"))]
+mod tests {
+    use super::*;
+    use hsu_common::Protocol;
+    use echo_api_grpc::generated::echo_service_server::EchoServiceServer;
+    use echo_api_grpc::EchoGrpcHandler;
+    use crate::gateways::{new_standalone_echo_service_gateways, Discovery, GatewayOptions};
+    use crate::maintenance_mode::{MaintenanceMode, MaintenanceModeEchoService};
+    use crate::registry::RegisteredApi;
+    use crate::registry_backend::RegistryBackend;
+
+    /// Echoes `<name>:<message>`.
+    struct Instance(&'static str);
+
+    #[async_trait]
+    impl EchoService for Instance {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            Ok(format!("{}:{}", self.0, message).into())
+        }
+    }
+
+    /// Serves `name` over gRPC, behind its maintenance switch.
+    async fn serve(name: &'static str, mode: Arc<MaintenanceMode>) -> String {
+        let service = Arc::new(MaintenanceModeEchoService::new(Arc::new(Instance(name)), mode));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(EchoServiceServer::new(EchoGrpcHandler::new(service)))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        address
+    }
+
+    /// A registry listing instances in order, with their maintenance state.
+    struct Listing(Vec<(String, Arc<MaintenanceMode>)>);
+
+    #[async_trait]
+    impl RegistryBackend for Listing {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        async fn publish(&self, _module_id: &str, _process_id: u32, _apis: Vec<RegisteredApi>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn unpublish(&self, _module_id: &str, _process_id: u32) -> Result<()> {
+            Ok(())
+        }
+
+        async fn discover(&self, _module_id: &str) -> Result<Vec<RegisteredApi>> {
+            Ok(self.0.iter().map(|(address, mode)| RegisteredApi {
+                service_ids: vec!["echo".to_string()],
+                protocol: Protocol::Grpc,
+                address: address.clone(),
+                maintenance: mode.reason(),
+            }).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_instance_entering_maintenance_is_rediscovered() {
+        let (a, b) = (MaintenanceMode::new(), MaintenanceMode::new());
+        let listing = Listing(vec![(serve("a", a.clone()).await, a.clone()), (serve("b", b.clone()).await, b)]);
+        let gateways = new_standalone_echo_service_gateways(GatewayOptions {
+            discovery: Discovery::Backend(Arc::new(listing)),
+            ..Default::default()
+        });
+
+        let service = gateways.get_service(Protocol::Grpc).await.unwrap();
+        assert_eq!(&*service.echo("hi".into()).await.unwrap(), "a:hi");
+
+        a.enter("disk swap");
+        let refused = service.echo("hi".into()).await.unwrap_err();
+        assert_eq!(maintenance_reason(&refused), Some("disk swap"));
+
+        let service = gateways.get_service(Protocol::Grpc).await.unwrap();
+        assert_eq!(&*service.echo("hi".into()).await.unwrap(), "b:hi");
+    }
+
+    #[test]
+    fn test_other_errors_keep_the_address() {
+        let discovered: DiscoveredAddress = Arc::new(Mutex::new(Some("10.0.0.1:50051".to_string())));
+        let address = "10.0.0.1:50051".to_string();
+        let service = RediscoveringEchoService::new(Arc::new(Instance("a")), address, discovered.clone());
+
+        let _ = service.check::<()>(Err(echo_contract::unavailable("connection refused")));
+        assert!(discovered.lock().unwrap().is_some());
+        let _ = service.check::<()>(Err(echo_contract::in_maintenance("disk swap")));
+        assert!(discovered.lock().unwrap().is_none());
+    }
+}
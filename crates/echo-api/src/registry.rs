@@ -0,0 +1,397 @@
+//! In-Memory Service Registry (Layer 5)
+//!
+//! # Architecture
+//!
+//! The demo normally needs the external `hsu-registry` process: the
+//! server publishes its APIs there and the client discovers them. For
+//! local development the same store can live in this workspace:
+//!
+//! ```text
+//! echo-grpc-srv --registry inmem
+//! ├── InMemoryServiceRegistry       ← publish / unpublish / discover
+//! │   └── served on the registry URL (echo_bootstrap::spawn_inmem_registry)
+//! └── ModuleRuntime ──publish──→ http://localhost:8080
+//!
+//! echo-grpc-cli ──discover──→ http://localhost:8080
+//! ```
+//!
+//! [`InMemoryServiceRegistry`] implements the framework's
+//! [`ServiceRegistry`] trait, so it is served with the framework's own
+//! registry server (the same API `hsu-registry` speaks) and the runtime's
+//! registry client can't tell the difference. In-process code (tests) can
+//! use the store directly.
+//!
+//! Publications are keyed by module and process: a process re-publishing
+//! replaces its previous APIs, and several processes may serve the same
+//! module (discover returns all of them).
+//!
+//! With [`InMemoryServiceRegistry::persistent`] every change is also
+//! written to a JSON snapshot file, loaded again on start - so restarting
+//! the `echo-registry` binary doesn't forget the running servers.
+//!
+//! ## Comparison with Golang
+//!
+//! Same idea as the Go registry's in-memory store - a mutex-guarded map.
+//! Persistence is a whole-file snapshot (write to a temp file, rename), not
+//! a database: the registry holds a handful of entries.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use async_trait::async_trait;
+use hsu_common::{Error, ModuleID, Protocol, Result, ServiceID};
+use hsu_service_registry::{RemoteAPI, ServiceRegistry};
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+
+/// An API published by a module: where its services are reachable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredApi {
+    /// Services reachable at `address`.
+    pub service_ids: Vec<String>,
+    /// Protocol spoken at `address`.
+    pub protocol: Protocol,
+    /// Address to connect to, e.g. `localhost:50051`.
+    pub address: String,
+    /// Reason, while the serving module is in maintenance mode.
+    ///
+    /// Such an API still answers, but refuses new calls: discovery
+    /// prefers the other instances.
+    pub maintenance: Option<String>,
+}
+
+impl RegisteredApi {
+    /// Encodes the API as a JSON object.
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "service_ids": self.service_ids,
+            "protocol": protocol_name(&self.protocol),
+            "address": self.address,
+        });
+        if let Some(reason) = &self.maintenance {
+            value["maintenance"] = json!(reason);
+        }
+        value
+    }
+
+    /// Decodes an object written by [`to_json`](Self::to_json).
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let invalid = |message: String| Error::Validation { message };
+        let address = value["address"].as_str()
+            .filter(|address| !address.is_empty())
+            .ok_or_else(|| invalid("Registered API has no address".to_string()))?;
+        let protocol = value["protocol"].as_str().unwrap_or_default();
+
+        Ok(Self {
+            service_ids: value["service_ids"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            protocol: parse_protocol(protocol)
+                .ok_or_else(|| invalid(format!("Unknown protocol '{}' in registered API", protocol)))?,
+            address: address.to_string(),
+            maintenance: value["maintenance"].as_str().map(str::to_string),
+        })
+    }
+}
+
+/// Metadata key of the maintenance reason in a framework [`RemoteAPI`].
+const MAINTENANCE_METADATA_KEY: &str = "echo.maintenance";
+
+impl From<RegisteredApi> for RemoteAPI {
+    fn from(api: RegisteredApi) -> Self {
+        RemoteAPI {
+            service_ids: api.service_ids.iter().map(|id| ServiceID::from(id.as_str())).collect(),
+            protocol: api.protocol,
+            address: api.address,
+            metadata: api.maintenance
+                .map(|reason| (MAINTENANCE_METADATA_KEY.to_string(), reason))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+impl From<RemoteAPI> for RegisteredApi {
+    fn from(mut api: RemoteAPI) -> Self {
+        RegisteredApi {
+            service_ids: api.service_ids.iter().map(ToString::to_string).collect(),
+            protocol: api.protocol,
+            address: api.address,
+            maintenance: api.metadata.remove(MAINTENANCE_METADATA_KEY),
+        }
+    }
+}
+
+/// Lower-case protocol name used on the wire (`grpc`, `http`, ...).
+pub fn protocol_name(protocol: &Protocol) -> String {
+    format!("{:?}", protocol).to_lowercase()
+}
+
+fn parse_protocol(name: &str) -> Option<Protocol> {
+    match name {
+        "grpc" => Some(Protocol::Grpc),
+        "http" => Some(Protocol::Http),
+        "direct" => Some(Protocol::Direct),
+        "auto" => Some(Protocol::Auto),
+        _ => None,
+    }
+}
+
+/// Module → (process → published APIs).
+type Publications = BTreeMap<String, BTreeMap<u32, Vec<RegisteredApi>>>;
+
+/// Registry store: module → (process → published APIs).
+#[derive(Default)]
+pub struct InMemoryServiceRegistry {
+    modules: RwLock<Publications>,
+    /// Snapshot file, rewritten after every change.
+    snapshot: Option<PathBuf>,
+}
+
+impl InMemoryServiceRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry persisted to `path`, loading the publications
+    /// stored there (a missing file starts empty).
+    pub fn persistent(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let modules = match std::fs::read_to_string(&path) {
+            Ok(contents) => parse_snapshot(&contents).map_err(|e| Error::Validation {
+                message: format!("Registry snapshot {}: {}", path.display(), e),
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Publications::new(),
+            Err(e) => return Err(Error::Validation {
+                message: format!("Registry snapshot {}: {}", path.display(), e),
+            }),
+        };
+        info!("[InMemoryRegistry] Persisting to {} ({} module(s) loaded)", path.display(), modules.len());
+        Ok(Self { modules: RwLock::new(modules), snapshot: Some(path) })
+    }
+
+    /// Returns the process-wide registry (the one `--registry inmem` serves).
+    pub fn global() -> Arc<InMemoryServiceRegistry> {
+        static GLOBAL: OnceLock<Arc<InMemoryServiceRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(InMemoryServiceRegistry::new())).clone()
+    }
+
+    /// Publishes the APIs of `module_id` served by `process_id`,
+    /// replacing what that process published before.
+    pub async fn publish(&self, module_id: &str, process_id: u32, apis: Vec<RegisteredApi>) -> Result<()> {
+        if module_id.is_empty() {
+            return Err(Error::Validation { message: "Cannot publish without a module ID".to_string() });
+        }
+        info!("[InMemoryRegistry] ✅ Published {} API(s) of module '{}' (pid {})",
+            apis.len(), module_id, process_id);
+        let mut modules = self.modules.write().unwrap();
+        modules.entry(module_id.to_string()).or_default().insert(process_id, apis);
+        self.save(&modules);
+        Ok(())
+    }
+
+    /// Removes what `process_id` published for `module_id`.
+    ///
+    /// Unpublishing something that isn't published is not an error
+    /// (shutdown after a registry restart).
+    pub async fn unpublish(&self, module_id: &str, process_id: u32) -> Result<()> {
+        let mut modules = self.modules.write().unwrap();
+        if let Some(processes) = modules.get_mut(module_id) {
+            processes.remove(&process_id);
+            if processes.is_empty() {
+                modules.remove(module_id);
+            }
+        }
+        self.save(&modules);
+        debug!("[InMemoryRegistry] Unpublished module '{}' (pid {})", module_id, process_id);
+        Ok(())
+    }
+
+    /// Returns every API published for `module_id`, in process order.
+    pub async fn discover(&self, module_id: &str) -> Result<Vec<RegisteredApi>> {
+        let modules = self.modules.read().unwrap();
+        let apis: Vec<RegisteredApi> = modules
+            .get(module_id)
+            .into_iter()
+            .flat_map(|processes| processes.values().flatten().cloned())
+            .collect();
+        if apis.is_empty() {
+            return Err(Error::Validation {
+                message: format!("Module '{}' not found in registry", module_id),
+            });
+        }
+        Ok(apis)
+    }
+
+    /// Modules with at least one published API.
+    pub fn modules(&self) -> Vec<String> {
+        self.modules.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Writes the snapshot file, if persistent.
+    ///
+    /// Called with the write lock held, so snapshots are written in order.
+    /// A failed write is logged, not returned: the publication itself
+    /// succeeded and is served from memory.
+    fn save(&self, modules: &Publications) {
+        let Some(path) = &self.snapshot else {
+            return;
+        };
+        let contents = snapshot_json(modules).to_string();
+        if let Err(e) = write_atomically(path, &contents) {
+            warn!("[InMemoryRegistry] Failed to write snapshot {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// The framework's view of the store: what its registry server serves.
+#[async_trait]
+impl ServiceRegistry for InMemoryServiceRegistry {
+    async fn publish(&self, module_id: &ModuleID, process_id: u32, apis: Vec<RemoteAPI>) -> Result<()> {
+        let apis = apis.into_iter().map(RegisteredApi::from).collect();
+        InMemoryServiceRegistry::publish(self, &module_id.to_string(), process_id, apis).await
+    }
+
+    async fn unpublish(&self, module_id: &ModuleID, process_id: u32) -> Result<()> {
+        InMemoryServiceRegistry::unpublish(self, &module_id.to_string(), process_id).await
+    }
+
+    async fn discover(&self, module_id: &ModuleID) -> Result<Vec<RemoteAPI>> {
+        let apis = InMemoryServiceRegistry::discover(self, &module_id.to_string()).await?;
+        Ok(apis.into_iter().map(RemoteAPI::from).collect())
+    }
+}
+
+fn snapshot_json(modules: &Publications) -> Value {
+    let publications: Vec<Value> = modules
+        .iter()
+        .flat_map(|(module_id, processes)| processes.iter().map(move |(process_id, apis)| json!({
+            "module_id": module_id,
+            "process_id": process_id,
+            "apis": apis.iter().map(RegisteredApi::to_json).collect::<Vec<_>>(),
+        })))
+        .collect();
+    json!({ "publications": publications })
+}
+
+fn parse_snapshot(contents: &str) -> Result<Publications> {
+    let snapshot: Value = serde_json::from_str(contents)
+        .map_err(|e| Error::Validation { message: format!("invalid JSON: {}", e) })?;
+    let mut modules = Publications::new();
+    for publication in snapshot["publications"].as_array().into_iter().flatten() {
+        let module_id = publication["module_id"].as_str().unwrap_or_default();
+        let process_id = publication["process_id"].as_u64().and_then(|pid| u32::try_from(pid).ok());
+        let (false, Some(process_id)) = (module_id.is_empty(), process_id) else {
+            return Err(Error::Validation { message: format!("invalid publication {}", publication) });
+        };
+        let apis = publication["apis"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(RegisteredApi::from_json)
+            .collect::<Result<Vec<_>>>()?;
+        modules.entry(module_id.to_string()).or_default().insert(process_id, apis);
+    }
+    Ok(modules)
+}
+
+/// Writes `contents` to a temp file next to `path`, then renames it over
+/// `path` - a crash never leaves a half-written snapshot.
+fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grpc(address: &str) -> RegisteredApi {
+        RegisteredApi {
+            service_ids: vec!["service".to_string(), "events".to_string()],
+            protocol: Protocol::Grpc,
+            address: address.to_string(),
+            maintenance: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_discover_returns_published_apis() {
+        let registry = InMemoryServiceRegistry::new();
+        registry.publish("echo", 1, vec![grpc("localhost:50051")]).await.unwrap();
+
+        assert_eq!(registry.discover("echo").await.unwrap(), vec![grpc("localhost:50051")]);
+        assert!(registry.discover("other").await.is_err());
+        assert_eq!(registry.modules(), vec!["echo".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_republish_replaces_only_that_process() {
+        let registry = InMemoryServiceRegistry::new();
+        registry.publish("echo", 1, vec![grpc("localhost:1")]).await.unwrap();
+        registry.publish("echo", 2, vec![grpc("localhost:2")]).await.unwrap();
+        registry.publish("echo", 1, vec![grpc("localhost:3")]).await.unwrap();
+
+        assert_eq!(registry.discover("echo").await.unwrap(), vec![grpc("localhost:3"), grpc("localhost:2")]);
+    }
+
+    #[tokio::test]
+    async fn test_unpublish_removes_module_when_last_process_goes() {
+        let registry = InMemoryServiceRegistry::new();
+        registry.publish("echo", 1, vec![grpc("localhost:1")]).await.unwrap();
+        registry.unpublish("echo", 2).await.unwrap();
+        assert!(registry.discover("echo").await.is_ok());
+
+        registry.unpublish("echo", 1).await.unwrap();
+        assert!(registry.discover("echo").await.is_err());
+        assert!(registry.modules().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_persistent_registry_survives_restart() {
+        let path = std::env::temp_dir().join(format!("echo-registry-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let registry = InMemoryServiceRegistry::persistent(&path).unwrap();
+        registry.publish("echo", 1, vec![grpc("localhost:1")]).await.unwrap();
+        registry.publish("echo-monitor", 2, vec![grpc("localhost:2")]).await.unwrap();
+        registry.unpublish("echo-monitor", 2).await.unwrap();
+        drop(registry);
+
+        let restarted = InMemoryServiceRegistry::persistent(&path).unwrap();
+        assert_eq!(restarted.discover("echo").await.unwrap(), vec![grpc("localhost:1")]);
+        assert_eq!(restarted.modules(), vec!["echo".to_string()]);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(InMemoryServiceRegistry::persistent(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_remote_api_round_trip() {
+        let draining = RegisteredApi { maintenance: Some("disk swap".to_string()), ..grpc("localhost:1") };
+        assert_eq!(RegisteredApi::from(RemoteAPI::from(draining.clone())), draining);
+        assert!(RemoteAPI::from(grpc("localhost:1")).metadata.is_empty());
+    }
+
+    #[test]
+    fn test_registered_api_json_round_trip() {
+        let api = grpc("[::1]:50051");
+        assert_eq!(api.to_json()["protocol"], "grpc");
+        assert_eq!(RegisteredApi::from_json(&api.to_json()).unwrap(), api);
+        assert!(api.to_json().get("maintenance").is_none());
+
+        let draining = RegisteredApi { maintenance: Some("disk swap".to_string()), ..grpc("localhost:1") };
+        assert_eq!(RegisteredApi::from_json(&draining.to_json()).unwrap(), draining);
+
+        assert!(RegisteredApi::from_json(&json!({ "protocol": "grpc" })).is_err());
+        assert!(RegisteredApi::from_json(&json!({ "protocol": "carrier-pigeon", "address": "x" })).is_err());
+    }
+}
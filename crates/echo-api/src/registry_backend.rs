@@ -0,0 +1,233 @@
+//! Registry Backends (Layer 5)
+//!
+//! # Architecture
+//!
+//! Where the echo modules publish and discover APIs is behind one trait,
+//! so the example drops into infrastructure users already run:
+//!
+//! ```text
+//! RuntimeConfig.service_registry.url
+//!     ↓ registry_backend()
+//! ┌────────────────────────────┬─────────────────────────────────────────┐
+//! │ http://localhost:8080      │ HttpRegistryBackend (hsu/echo-registry) │
+//! │ consul://localhost:8500    │ ConsulRegistryBackend (agent HTTP API)  │
+//! │ etcd://localhost:2379      │ EtcdRegistryBackend (v3 JSON gateway)   │
+//! │ inmem                      │ InMemoryServiceRegistry (this process)  │
+//! └────────────────────────────┴─────────────────────────────────────────┘
+//!     ↓ Arc<dyn RegistryBackend>
+//! EchoServerModule (publish on start, unpublish on stop)
+//! EchoServiceGatewaysImpl (Discovery::Backend → discover)
+//! ```
+//!
+//! The framework's own publisher only speaks the HSU registry API; with a
+//! Consul or etcd URL the echo server module publishes itself through
+//! the backend instead, and [`framework_config`] keeps the URL away from
+//! the framework. Registrations there expire unless the module sends a
+//! [`heartbeat`](RegistryBackend::heartbeat) every [`HEARTBEAT_INTERVAL`].
+//!
+//! ## Comparison with Golang
+//!
+//! Go would use an interface with the same three methods. The HSU registry
+//! is reached through the framework's client; the Consul and etcd adapters
+//! talk plain HTTP/JSON rather than pulling in their SDKs.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use hsu_common::{Error, Protocol, Result};
+use hsu_module_api::{Config, RuntimeConfig};
+use tracing::info;
+
+use crate::registry::{InMemoryServiceRegistry, RegisteredApi};
+#[cfg(feature = "registry-backends")]
+use crate::remote_registries::{ConsulRegistryBackend, EtcdRegistryBackend, HttpRegistryBackend};
+
+/// How long a Consul or etcd registration outlives its last heartbeat.
+pub const REGISTRATION_TTL: Duration = Duration::from_secs(30);
+
+/// How often a publishing module renews its registration.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Publishes and discovers module APIs.
+#[async_trait]
+pub trait RegistryBackend: Send + Sync {
+    /// Short name for logs (`http`, `consul`, ...).
+    fn name(&self) -> &'static str;
+
+    /// Publishes the APIs of `module_id` served by `process_id`,
+    /// replacing what that process published before.
+    async fn publish(&self, module_id: &str, process_id: u32, apis: Vec<RegisteredApi>) -> Result<()>;
+
+    /// Removes what `process_id` published for `module_id`.
+    async fn unpublish(&self, module_id: &str, process_id: u32) -> Result<()>;
+
+    /// Returns every API published for `module_id`.
+    async fn discover(&self, module_id: &str) -> Result<Vec<RegisteredApi>>;
+
+    /// Renews what `process_id` published for `module_id`.
+    ///
+    /// Fails if the registration is gone (expired, registry restarted):
+    /// the caller publishes again. Registrations that don't expire need
+    /// nothing.
+    async fn heartbeat(&self, _module_id: &str, _process_id: u32) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl fmt::Debug for dyn RegistryBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RegistryBackend({})", self.name())
+    }
+}
+
+/// Creates the backend selected by the registry URL of `config`.
+pub fn registry_backend(config: &RuntimeConfig) -> Result<Arc<dyn RegistryBackend>> {
+    let url = config.service_registry.url.as_str();
+    let backend: Arc<dyn RegistryBackend> = if url == "inmem" {
+        InMemoryServiceRegistry::global()
+    } else {
+        remote_backend(url)?
+    };
+    info!("[RegistryBackend] Using {} registry at {}", backend.name(), url);
+    Ok(backend)
+}
+
+#[cfg(feature = "registry-backends")]
+fn remote_backend(url: &str) -> Result<Arc<dyn RegistryBackend>> {
+    if let Some(address) = url.strip_prefix("consul://") {
+        Ok(Arc::new(ConsulRegistryBackend::new(format!("http://{}", address))))
+    } else if let Some(address) = url.strip_prefix("etcd://") {
+        Ok(Arc::new(EtcdRegistryBackend::new(format!("http://{}", address))))
+    } else if url.starts_with("http://") {
+        Ok(Arc::new(HttpRegistryBackend::new(url)?))
+    } else {
+        Err(Error::Validation {
+            message: format!("Unsupported registry URL '{}' (expected http://, consul://, etcd:// or inmem)", url),
+        })
+    }
+}
+
+#[cfg(not(feature = "registry-backends"))]
+fn remote_backend(url: &str) -> Result<Arc<dyn RegistryBackend>> {
+    Err(Error::Validation {
+        message: format!("Registry URL '{}' needs the `registry-backends` feature of echo-api", url),
+    })
+}
+
+/// The backend the echo modules must publish to and discover from
+/// themselves: Consul or etcd.
+///
+/// `None` when the framework's registry client handles the URL (HSU
+/// registry over HTTP), so nothing is published twice.
+pub fn module_registry_backend(config: &RuntimeConfig) -> Result<Option<Arc<dyn RegistryBackend>>> {
+    let backend = registry_backend(config)?;
+    Ok(matches!(backend.name(), "consul" | "etcd").then_some(backend))
+}
+
+/// `config` as handed to the framework.
+///
+/// A Consul or etcd URL means nothing to the framework's registry client,
+/// which would publish to it and fail: it gets an empty URL (no registry)
+/// instead, and the echo modules use [`module_registry_backend`] on the
+/// original config.
+pub fn framework_config(mut config: Config) -> Config {
+    if is_module_registry_url(&config.runtime.service_registry.url) {
+        config.runtime.service_registry.url = String::new();
+    }
+    config
+}
+
+fn is_module_registry_url(url: &str) -> bool {
+    url.starts_with("consul://") || url.starts_with("etcd://")
+}
+
+#[async_trait]
+impl RegistryBackend for InMemoryServiceRegistry {
+    fn name(&self) -> &'static str {
+        "inmem"
+    }
+
+    async fn publish(&self, module_id: &str, process_id: u32, apis: Vec<RegisteredApi>) -> Result<()> {
+        InMemoryServiceRegistry::publish(self, module_id, process_id, apis).await
+    }
+
+    async fn unpublish(&self, module_id: &str, process_id: u32) -> Result<()> {
+        InMemoryServiceRegistry::unpublish(self, module_id, process_id).await
+    }
+
+    async fn discover(&self, module_id: &str) -> Result<Vec<RegisteredApi>> {
+        InMemoryServiceRegistry::discover(self, module_id).await
+    }
+}
+
+/// `host:port`, bracketing IPv6 hosts.
+pub(crate) fn join_host_port(host: &str, port: u64) -> String {
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// The gRPC API of a server reachable at `host:port`.
+pub fn grpc_api(host: &str, port: u16, service_ids: &[&str]) -> RegisteredApi {
+    RegisteredApi {
+        service_ids: service_ids.iter().map(|id| id.to_string()).collect(),
+        protocol: Protocol::Grpc,
+        address: join_host_port(host, port.into()),
+        maintenance: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hsu_module_api::ServiceRegistryConfig;
+
+    fn runtime(url: &str) -> RuntimeConfig {
+        RuntimeConfig {
+            service_registry: ServiceRegistryConfig { url: url.to_string() },
+            servers: vec![],
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "registry-backends")]
+    fn test_backend_selected_by_url_scheme() {
+        let name = |url: &str| registry_backend(&runtime(url)).map(|backend| backend.name());
+        assert_eq!(name("http://localhost:8080").unwrap(), "http");
+        assert_eq!(name("consul://localhost:8500").unwrap(), "consul");
+        assert_eq!(name("etcd://localhost:2379").unwrap(), "etcd");
+        assert_eq!(name("inmem").unwrap(), "inmem");
+        assert!(name("zookeeper://localhost:2181").is_err());
+
+        assert!(module_registry_backend(&runtime("http://localhost:8080")).unwrap().is_none());
+        assert!(module_registry_backend(&runtime("consul://localhost:8500")).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_framework_never_sees_consul_or_etcd() {
+        let framework_url = |url: &str| {
+            framework_config(Config { runtime: runtime(url), modules: vec![] }).runtime.service_registry.url
+        };
+        assert_eq!(framework_url("http://localhost:8080"), "http://localhost:8080");
+        assert_eq!(framework_url("consul://localhost:8500"), "");
+        assert_eq!(framework_url("etcd://localhost:2379"), "");
+    }
+
+    #[test]
+    fn test_grpc_api_brackets_ipv6() {
+        assert_eq!(grpc_api("::1", 50051, &["service"]).address, "[::1]:50051");
+        assert_eq!(grpc_api("localhost", 50051, &["service"]).address, "localhost:50051");
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_backend_through_trait() {
+        let backend: Arc<dyn RegistryBackend> = Arc::new(InMemoryServiceRegistry::new());
+        backend.publish("echo", 1, vec![grpc_api("localhost", 50051, &["service"])]).await.unwrap();
+        assert_eq!(backend.discover("echo").await.unwrap()[0].address, "localhost:50051");
+        backend.unpublish("echo", 1).await.unwrap();
+        assert!(backend.discover("echo").await.is_err());
+    }
+}
@@ -0,0 +1,459 @@
+//! Remote Registry Backends (Layer 5)
+//!
+//! [`RegistryBackend`] implementations talking to a registry process
+//! (`registry-backends` feature):
+//!
+//! | Backend                 | Talks to                         | Stored as                          |
+//! |-------------------------|----------------------------------|------------------------------------|
+//! | `HttpRegistryBackend`   | hsu-registry / echo-registry     | the framework's registry client    |
+//! | `ConsulRegistryBackend` | Consul agent HTTP API            | one service instance per API       |
+//! | `EtcdRegistryBackend`   | etcd v3 JSON gateway             | one key per process                |
+//!
+//! Selected by the registry URL scheme in [`crate::registry_backend()`].
+//!
+//! Consul and etcd registrations expire after [`REGISTRATION_TTL`] unless
+//! [`RegistryBackend::heartbeat`] renews them, so a crashed server drops out
+//! of discovery by itself: a Consul TTL check per instance, an etcd lease
+//! per process.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hsu_common::{Error, ModuleID, Result};
+use hsu_service_registry::{RemoteAPI, ServiceRegistry, ServiceRegistryClient};
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request, StatusCode};
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::registry::RegisteredApi;
+use crate::registry_backend::{join_host_port, RegistryBackend, REGISTRATION_TTL};
+
+/// Minimal JSON-over-HTTP client shared by the remote backends.
+struct JsonHttp {
+    client: Client<HttpConnector>,
+    base_url: String,
+}
+
+impl JsonHttp {
+    fn new(base_url: impl Into<String>) -> Self {
+        Self { client: Client::new(), base_url: base_url.into().trim_end_matches('/').to_string() }
+    }
+
+    /// Sends `body` (if any) and returns the status with the decoded
+    /// response (`Null` for an empty body).
+    async fn call(&self, method: Method, path: &str, body: Option<Value>) -> Result<(StatusCode, Value)> {
+        let uri = format!("{}{}", self.base_url, path);
+        debug!("[RegistryBackend] {} {}", method, uri);
+        let request = Request::builder()
+            .method(method)
+            .uri(&uri)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(body.map(|body| Body::from(body.to_string())).unwrap_or_else(Body::empty))
+            .map_err(|e| Error::Protocol(format!("Invalid registry request {}: {}", uri, e)))?;
+        let response = self.client.request(request)
+            .await
+            .map_err(|e| Error::Protocol(format!("Registry {} unreachable: {}", uri, e)))?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(|e| Error::Protocol(format!("Failed to read registry response: {}", e)))?;
+        let value = if body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body)
+                .map_err(|e| Error::Protocol(format!("Invalid registry response from {}: {}", uri, e)))?
+        };
+        Ok((status, value))
+    }
+
+    /// Like [`call`](Self::call), failing on a non-success status.
+    async fn call_ok(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        let (status, value) = self.call(method, path, body).await?;
+        if !status.is_success() {
+            return Err(Error::Protocol(format!("Registry {}{} returned {}: {}", self.base_url, path, status, value)));
+        }
+        Ok(value)
+    }
+}
+
+/// The HSU registry (`hsu-registry`, `echo-registry`), through the
+/// framework's own registry client.
+pub struct HttpRegistryBackend {
+    client: ServiceRegistryClient,
+}
+
+impl HttpRegistryBackend {
+    /// Creates a backend for the registry at `url`.
+    pub fn new(url: &str) -> Result<Self> {
+        Ok(Self { client: ServiceRegistryClient::new(url)? })
+    }
+}
+
+#[async_trait]
+impl RegistryBackend for HttpRegistryBackend {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn publish(&self, module_id: &str, process_id: u32, apis: Vec<RegisteredApi>) -> Result<()> {
+        let apis = apis.into_iter().map(RemoteAPI::from).collect();
+        self.client.publish(&ModuleID::from(module_id), process_id, apis).await
+    }
+
+    async fn unpublish(&self, module_id: &str, process_id: u32) -> Result<()> {
+        self.client.unpublish(&ModuleID::from(module_id), process_id).await
+    }
+
+    async fn discover(&self, module_id: &str) -> Result<Vec<RegisteredApi>> {
+        let apis = self.client.discover(&ModuleID::from(module_id)).await?;
+        Ok(apis.into_iter().map(RegisteredApi::from).collect())
+    }
+}
+
+/// Consul agent: one Consul service instance per published API.
+///
+/// The module ID is the Consul service name; the HSU service IDs, the
+/// protocol and a maintenance reason go into the instance's `Meta`.
+pub struct ConsulRegistryBackend {
+    http: JsonHttp,
+}
+
+impl ConsulRegistryBackend {
+    /// Creates a backend for the Consul agent at `url` (e.g. `http://localhost:8500`).
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { http: JsonHttp::new(url) }
+    }
+
+    /// Consul instance IDs of a process's APIs: `module-pid-index`.
+    fn instance_id(module_id: &str, process_id: u32, index: usize) -> String {
+        format!("{}-{}-{}", module_id, process_id, index)
+    }
+
+    /// Instance IDs currently registered for `module_id` by `process_id`.
+    ///
+    /// Matched on the service name and the `hsu_pid` meta, not on the ID:
+    /// `echo-1-0` is also a prefix of module `echo-1`'s instances.
+    async fn instances_of(&self, module_id: &str, process_id: u32) -> Result<Vec<String>> {
+        let services = self.http.call_ok(Method::GET, "/v1/agent/services", None).await?;
+        Ok(owned_instances(&services, module_id, process_id))
+    }
+}
+
+/// IDs of the instances in `GET /v1/agent/services` owned by `process_id`
+/// of `module_id`.
+fn owned_instances(services: &Value, module_id: &str, process_id: u32) -> Vec<String> {
+    let process_id = process_id.to_string();
+    services
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, service)| {
+            service["Service"].as_str() == Some(module_id)
+                && service["Meta"]["hsu_pid"].as_str() == Some(process_id.as_str())
+        })
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// Consul agent registration of `api` as instance `id`.
+///
+/// Its TTL check turns critical without a heartbeat; Consul then stops
+/// returning it as passing and deregisters it a while later.
+fn consul_registration(module_id: &str, process_id: u32, id: &str, api: &RegisteredApi) -> Result<Value> {
+    let (host, port) = split_host_port(&api.address)?;
+    let mut registration = json!({
+        "ID": id,
+        "Name": module_id,
+        "Address": host,
+        "Port": port,
+        "Meta": {
+            "hsu_services": api.service_ids.join(","),
+            "hsu_protocol": crate::registry::protocol_name(&api.protocol),
+            "hsu_pid": process_id.to_string(),
+        },
+        "Check": {
+            "CheckID": consul_check_id(id),
+            "TTL": format!("{}s", REGISTRATION_TTL.as_secs()),
+            "DeregisterCriticalServiceAfter": format!("{}s", 3 * REGISTRATION_TTL.as_secs()),
+        },
+    });
+    if let Some(reason) = &api.maintenance {
+        registration["Meta"]["hsu_maintenance"] = json!(reason);
+    }
+    Ok(registration)
+}
+
+/// TTL check of instance `id`.
+fn consul_check_id(id: &str) -> String {
+    format!("service:{}", id)
+}
+
+/// Reads an entry of `GET /v1/health/service/{name}`.
+fn consul_api(entry: &Value) -> Result<RegisteredApi> {
+    let service = &entry["Service"];
+    // An empty service address means "the node's address"
+    let host = service["Address"].as_str()
+        .filter(|address| !address.is_empty())
+        .or_else(|| entry["Node"]["Address"].as_str())
+        .unwrap_or_default();
+    let port = service["Port"].as_u64().unwrap_or_default();
+    RegisteredApi::from_json(&json!({
+        "service_ids": service["Meta"]["hsu_services"]
+            .as_str()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|id| !id.is_empty())
+            .collect::<Vec<_>>(),
+        "protocol": service["Meta"]["hsu_protocol"].as_str().unwrap_or("grpc"),
+        "address": join_host_port(host, port),
+        "maintenance": service["Meta"]["hsu_maintenance"],
+    }))
+}
+
+#[async_trait]
+impl RegistryBackend for ConsulRegistryBackend {
+    fn name(&self) -> &'static str {
+        "consul"
+    }
+
+    async fn publish(&self, module_id: &str, process_id: u32, apis: Vec<RegisteredApi>) -> Result<()> {
+        self.unpublish(module_id, process_id).await?;
+        for (index, api) in apis.iter().enumerate() {
+            let id = Self::instance_id(module_id, process_id, index);
+            let registration = consul_registration(module_id, process_id, &id, api)?;
+            self.http.call_ok(Method::PUT, "/v1/agent/service/register", Some(registration)).await?;
+        }
+        // A new TTL check starts critical: pass it, so the instances are discoverable now
+        self.heartbeat(module_id, process_id).await
+    }
+
+    async fn heartbeat(&self, module_id: &str, process_id: u32) -> Result<()> {
+        let instances = self.instances_of(module_id, process_id).await?;
+        if instances.is_empty() {
+            return Err(Error::Protocol(format!("Module '{}' (pid {}) is no longer registered in Consul", module_id, process_id)));
+        }
+        for id in instances {
+            let path = format!("/v1/agent/check/pass/{}", consul_check_id(&id));
+            self.http.call_ok(Method::PUT, &path, None).await?;
+        }
+        Ok(())
+    }
+
+    async fn unpublish(&self, module_id: &str, process_id: u32) -> Result<()> {
+        for id in self.instances_of(module_id, process_id).await? {
+            self.http.call_ok(Method::PUT, &format!("/v1/agent/service/deregister/{}", id), None).await?;
+        }
+        Ok(())
+    }
+
+    async fn discover(&self, module_id: &str) -> Result<Vec<RegisteredApi>> {
+        let entries = self.http
+            .call_ok(Method::GET, &format!("/v1/health/service/{}?passing=true", module_id), None)
+            .await?;
+        let apis = entries.as_array().into_iter().flatten().map(consul_api).collect::<Result<Vec<_>>>()?;
+        not_empty(module_id, apis)
+    }
+}
+
+/// etcd v3 through its JSON gateway: one key per process,
+/// `/hsu/modules/{module}/{pid}` → `{"apis": [...]}`, attached to a lease
+/// that the heartbeat keeps alive.
+pub struct EtcdRegistryBackend {
+    http: JsonHttp,
+    /// Lease of each published key.
+    leases: Mutex<HashMap<String, String>>,
+}
+
+impl EtcdRegistryBackend {
+    /// Creates a backend for the etcd endpoint at `url` (e.g. `http://localhost:2379`).
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { http: JsonHttp::new(url), leases: Mutex::new(HashMap::new()) }
+    }
+
+    /// Grants a lease of [`REGISTRATION_TTL`]; its ID as the gateway writes it.
+    async fn grant_lease(&self) -> Result<String> {
+        let body = json!({ "TTL": REGISTRATION_TTL.as_secs() });
+        let response = self.http.call_ok(Method::POST, "/v3/lease/grant", Some(body)).await?;
+        lease_id(&response["ID"]).ok_or_else(|| Error::Protocol(format!("etcd granted no lease: {}", response)))
+    }
+
+    fn key(module_id: &str, process_id: u32) -> String {
+        format!("{}{}", Self::prefix(module_id), process_id)
+    }
+
+    fn prefix(module_id: &str) -> String {
+        format!("/hsu/modules/{}/", module_id)
+    }
+}
+
+/// A lease ID of the JSON gateway (an int64, written as a string).
+fn lease_id(value: &Value) -> Option<String> {
+    match value {
+        Value::String(id) if !id.is_empty() => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// etcd's `range_end` for "every key starting with `prefix`".
+fn prefix_range_end(prefix: &str) -> Vec<u8> {
+    let mut end = prefix.as_bytes().to_vec();
+    if let Some(last) = end.last_mut() {
+        *last += 1;
+    }
+    end
+}
+
+#[async_trait]
+impl RegistryBackend for EtcdRegistryBackend {
+    fn name(&self) -> &'static str {
+        "etcd"
+    }
+
+    async fn publish(&self, module_id: &str, process_id: u32, apis: Vec<RegisteredApi>) -> Result<()> {
+        let key = Self::key(module_id, process_id);
+        let value = json!({ "apis": apis.iter().map(RegisteredApi::to_json).collect::<Vec<_>>() });
+        let put = |lease: &str| json!({
+            "key": BASE64.encode(&key),
+            "value": BASE64.encode(value.to_string()),
+            "lease": lease,
+        });
+
+        let existing = self.leases.lock().unwrap().get(&key).cloned();
+        if let Some(lease) = existing {
+            if self.http.call_ok(Method::POST, "/v3/kv/put", Some(put(&lease))).await.is_ok() {
+                return Ok(());
+            }
+            // The lease expired meanwhile: put again under a fresh one
+        }
+        let lease = self.grant_lease().await?;
+        self.http.call_ok(Method::POST, "/v3/kv/put", Some(put(&lease))).await?;
+        self.leases.lock().unwrap().insert(key, lease);
+        Ok(())
+    }
+
+    async fn unpublish(&self, module_id: &str, process_id: u32) -> Result<()> {
+        let key = Self::key(module_id, process_id);
+        // Revoking the lease would delete the key too; deleting it is enough
+        self.leases.lock().unwrap().remove(&key);
+        let body = json!({ "key": BASE64.encode(key) });
+        self.http.call_ok(Method::POST, "/v3/kv/deleterange", Some(body)).await.map(drop)
+    }
+
+    async fn heartbeat(&self, module_id: &str, process_id: u32) -> Result<()> {
+        let key = Self::key(module_id, process_id);
+        let lease = self.leases.lock().unwrap().get(&key).cloned();
+        let Some(lease) = lease else {
+            return Err(Error::Protocol(format!("Module '{}' (pid {}) isn't published to etcd", module_id, process_id)));
+        };
+        let response = self.http.call_ok(Method::POST, "/v3/lease/keepalive", Some(json!({ "ID": lease }))).await?;
+        // An expired lease is "renewed" with no TTL: the key is gone with it
+        match response["result"]["TTL"].as_str() {
+            Some(ttl) if ttl != "0" => Ok(()),
+            _ => {
+                self.leases.lock().unwrap().remove(&key);
+                Err(Error::Protocol(format!("etcd lease of module '{}' (pid {}) expired", module_id, process_id)))
+            }
+        }
+    }
+
+    async fn discover(&self, module_id: &str) -> Result<Vec<RegisteredApi>> {
+        let prefix = Self::prefix(module_id);
+        let body = json!({
+            "key": BASE64.encode(&prefix),
+            "range_end": BASE64.encode(prefix_range_end(&prefix)),
+        });
+        let response = self.http.call_ok(Method::POST, "/v3/kv/range", Some(body)).await?;
+
+        let mut apis = Vec::new();
+        for kv in response["kvs"].as_array().into_iter().flatten() {
+            let value = BASE64.decode(kv["value"].as_str().unwrap_or_default())
+                .map_err(|e| Error::Protocol(format!("Invalid etcd value: {}", e)))?;
+            let value: Value = serde_json::from_slice(&value)
+                .map_err(|e| Error::Protocol(format!("Invalid etcd registration: {}", e)))?;
+            for api in value["apis"].as_array().into_iter().flatten() {
+                apis.push(RegisteredApi::from_json(api)?);
+            }
+        }
+        not_empty(module_id, apis)
+    }
+}
+
+fn not_empty(module_id: &str, apis: Vec<RegisteredApi>) -> Result<Vec<RegisteredApi>> {
+    if apis.is_empty() {
+        return Err(Error::Validation {
+            message: format!("Module '{}' not found in registry", module_id),
+        });
+    }
+    Ok(apis)
+}
+
+/// Splits `host:port` / `[v6]:port`.
+fn split_host_port(address: &str) -> Result<(String, u16)> {
+    address
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host.trim_start_matches('[').trim_end_matches(']').to_string(), port.parse().ok()?)))
+        .ok_or_else(|| Error::Validation { message: format!("Address '{}' has no port", address) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry_backend::grpc_api;
+
+    #[test]
+    fn test_consul_entry_round_trip() {
+        let api = grpc_api("10.0.0.5", 50051, &["service", "events"]);
+        let registration = consul_registration("echo", 1, "echo-1-0", &api).unwrap();
+        assert_eq!(registration["Port"], 50051);
+        assert_eq!(registration["Check"]["TTL"], format!("{}s", REGISTRATION_TTL.as_secs()));
+
+        let entry = json!({ "Node": { "Address": "10.0.0.9" }, "Service": registration });
+        assert_eq!(consul_api(&entry).unwrap(), api);
+
+        let draining = RegisteredApi { maintenance: Some("upgrading".to_string()), ..api };
+        let registration = consul_registration("echo", 1, "echo-1-0", &draining).unwrap();
+        assert_eq!(registration["Meta"]["hsu_maintenance"], "upgrading");
+        let entry = json!({ "Node": { "Address": "10.0.0.9" }, "Service": registration });
+        assert_eq!(consul_api(&entry).unwrap(), draining);
+
+        // Empty service address: Consul means the node's address
+        let entry = json!({ "Node": { "Address": "10.0.0.9" }, "Service": { "Port": 1, "Address": "" } });
+        assert_eq!(consul_api(&entry).unwrap().address, "10.0.0.9:1");
+    }
+
+    #[test]
+    fn test_consul_instances_matched_by_name_and_pid() {
+        let services = json!({
+            "echo-1-0": { "Service": "echo", "Meta": { "hsu_pid": "1" } },
+            "echo-1-1": { "Service": "echo", "Meta": { "hsu_pid": "1" } },
+            "echo-monitor-1-0": { "Service": "echo-monitor", "Meta": { "hsu_pid": "1" } },
+            "echo-1-5-0": { "Service": "echo-1", "Meta": { "hsu_pid": "5" } },
+            "echo-12-0": { "Service": "echo", "Meta": { "hsu_pid": "12" } },
+        });
+        let mut owned = owned_instances(&services, "echo", 1);
+        owned.sort();
+        assert_eq!(owned, vec!["echo-1-0".to_string(), "echo-1-1".to_string()]);
+    }
+
+    #[test]
+    fn test_etcd_lease_id_forms() {
+        assert_eq!(lease_id(&json!("7587")).as_deref(), Some("7587"));
+        assert_eq!(lease_id(&json!(7587)).as_deref(), Some("7587"));
+        assert_eq!(lease_id(&json!(null)), None);
+    }
+
+    #[test]
+    fn test_etcd_prefix_range_end() {
+        assert_eq!(prefix_range_end("/hsu/modules/echo/"), b"/hsu/modules/echo0".to_vec());
+    }
+
+    #[test]
+    fn test_split_host_port_handles_ipv6() {
+        assert_eq!(split_host_port("[::1]:50051").unwrap(), ("::1".to_string(), 50051));
+        assert!(split_host_port("localhost").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! Runtime Assignments (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A binary may split its work across several tokio runtimes, so a spike
+//! of protocol traffic can't starve module background tasks (outbox
+//! flushers, connectivity loggers, ...):
+//!
+//! ```text
+//! "echo-protocol" runtime (N workers)     "echo-modules" runtime (M workers)
+//!   ├─ framework: gRPC server, lifecycle    ├─ TaskRegistry::spawn(...)
+//!   └─ admin endpoint                       └─ ...
+//! ```
+//!
+//! The binary's bootstrap builds the runtimes and records them here;
+//! [`TaskRegistry`](crate::TaskRegistry) spawns module tasks on the
+//! [`RuntimeRole::Modules`] runtime when one is assigned, and the admin
+//! endpoint reports every assignment.
+
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+use tokio::runtime::Handle;
+
+/// What a runtime is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeRole {
+    /// Protocol servers and the framework (the `main` runtime).
+    Protocol,
+    /// Module background tasks.
+    Modules,
+}
+
+impl fmt::Display for RuntimeRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RuntimeRole::Protocol => "protocol",
+            RuntimeRole::Modules => "modules",
+        })
+    }
+}
+
+/// A runtime and the role it was assigned.
+#[derive(Debug, Clone)]
+pub struct RuntimeAssignment {
+    /// Role of the runtime.
+    pub role: RuntimeRole,
+    /// Thread name prefix of its workers.
+    pub name: String,
+    /// Handle for spawning onto it and reading its metrics.
+    pub handle: Handle,
+}
+
+/// Registry of runtime assignments.
+#[derive(Default)]
+pub struct RuntimeAssignments {
+    assignments: RwLock<Vec<RuntimeAssignment>>,
+}
+
+impl RuntimeAssignments {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide registry.
+    pub fn global() -> Arc<RuntimeAssignments> {
+        static GLOBAL: OnceLock<Arc<RuntimeAssignments>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(RuntimeAssignments::new())).clone()
+    }
+
+    /// Assigns `handle` to `role`, replacing any previous assignment.
+    pub fn assign(&self, role: RuntimeRole, name: impl Into<String>, handle: Handle) {
+        let mut assignments = self.assignments.write().unwrap();
+        assignments.retain(|a| a.role != role);
+        assignments.push(RuntimeAssignment { role, name: name.into(), handle });
+    }
+
+    /// Returns the runtime assigned to `role`.
+    pub fn handle(&self, role: RuntimeRole) -> Option<Handle> {
+        self.assignments
+            .read()
+            .unwrap()
+            .iter()
+            .find(|a| a.role == role)
+            .map(|a| a.handle.clone())
+    }
+
+    /// Returns all assignments.
+    pub fn list(&self) -> Vec<RuntimeAssignment> {
+        self.assignments.read().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_assign_replaces_role() {
+        let assignments = RuntimeAssignments::new();
+        assert!(assignments.handle(RuntimeRole::Modules).is_none());
+
+        assignments.assign(RuntimeRole::Modules, "first", Handle::current());
+        assignments.assign(RuntimeRole::Modules, "second", Handle::current());
+
+        let list = assignments.list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].name, "second");
+        assert!(assignments.handle(RuntimeRole::Modules).is_some());
+    }
+}
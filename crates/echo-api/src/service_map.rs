@@ -0,0 +1,150 @@
+//! Type-Erased Service Map (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A service provider used to hold one field per contract it knows
+//! (`gateways: Arc<dyn EchoServiceGateways>`), so a module consuming a
+//! second domain service meant editing the provider. A [`ServiceMap`]
+//! holds any number of them instead, keyed by the contract's type:
+//!
+//! ```text
+//! ServiceMap
+//!   TypeId(dyn EchoServiceGateways)    → Arc<dyn EchoServiceGateways>
+//!   TypeId(dyn MonitorServiceGateways) → Arc<dyn MonitorServiceGateways>
+//!       ↓ get::<dyn EchoServiceGateways>()
+//!   Some(Arc<dyn EchoServiceGateways>)   (downcast back to the stored type)
+//! ```
+//!
+//! The providers keep their typed getters (`get_gateways`,
+//! `echo_gateways`) as shims over the map.
+//!
+//! # Rust Learning Note
+//!
+//! `dyn Any` can only downcast to a sized type, and `dyn EchoServiceGateways`
+//! isn't one - so the map stores the `Arc<dyn ...>` itself behind `Any`
+//! and downcasts to `Arc<T>`. `TypeId::of::<T>()` works for unsized `T`,
+//! which keys the entry by the contract rather than by the `Arc`.
+
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use hsu_common::{Error, Result};
+
+/// One stored `Arc<T>`, with `T`'s name for error messages.
+#[derive(Clone)]
+struct Entry {
+    name: &'static str,
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+/// Gateways (or handlers) by contract type.
+#[derive(Clone, Default)]
+pub struct ServiceMap {
+    entries: HashMap<TypeId, Entry>,
+}
+
+impl ServiceMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `service` under `T`, returning the one it replaces.
+    pub fn insert<T: ?Sized + Send + Sync + 'static>(&mut self, service: Arc<T>) -> Option<Arc<T>> {
+        let entry = Entry { name: type_name::<T>(), value: Arc::new(service) };
+        self.entries
+            .insert(TypeId::of::<T>(), entry)
+            .and_then(|previous| previous.value.downcast_ref::<Arc<T>>().cloned())
+    }
+
+    /// Like [`insert`](Self::insert), for building a map in one expression.
+    pub fn with<T: ?Sized + Send + Sync + 'static>(mut self, service: Arc<T>) -> Self {
+        self.insert(service);
+        self
+    }
+
+    /// Returns the service stored under `T`.
+    pub fn get<T: ?Sized + Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.entries.get(&TypeId::of::<T>())?.value.downcast_ref::<Arc<T>>().cloned()
+    }
+
+    /// Like [`get`](Self::get), failing with what's there instead.
+    pub fn require<T: ?Sized + Send + Sync + 'static>(&self) -> Result<Arc<T>> {
+        self.get::<T>().ok_or_else(|| Error::Validation {
+            message: format!("No {} registered (have: {})", type_name::<T>(), self.names().join(", ")),
+        })
+    }
+
+    /// Whether a service is stored under `T`.
+    pub fn contains<T: ?Sized + 'static>(&self) -> bool {
+        self.entries.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Names of the stored contracts, sorted.
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.entries.values().map(|entry| entry.name).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+impl fmt::Debug for ServiceMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    trait Greeter: Send + Sync {
+        fn greet(&self) -> String;
+    }
+
+    trait Counter: Send + Sync {
+        fn count(&self) -> u64;
+    }
+
+    struct English;
+
+    impl Greeter for English {
+        fn greet(&self) -> String {
+            "hello".to_string()
+        }
+    }
+
+    struct Fixed(u64);
+
+    impl Counter for Fixed {
+        fn count(&self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_downcasts_by_contract() {
+        let map = ServiceMap::new()
+            .with::<dyn Greeter>(Arc::new(English))
+            .with::<dyn Counter>(Arc::new(Fixed(7)));
+
+        assert_eq!(map.get::<dyn Greeter>().unwrap().greet(), "hello");
+        assert_eq!(map.get::<dyn Counter>().unwrap().count(), 7);
+        // Keyed by the contract, not the implementation
+        assert!(map.get::<English>().is_none());
+        assert!(!map.contains::<Fixed>());
+    }
+
+    #[test]
+    fn test_insert_replaces_and_require_explains() {
+        let mut map = ServiceMap::new();
+        assert!(map.insert::<dyn Counter>(Arc::new(Fixed(1))).is_none());
+        let previous = map.insert::<dyn Counter>(Arc::new(Fixed(2))).unwrap();
+        assert_eq!((previous.count(), map.require::<dyn Counter>().unwrap().count()), (1, 2));
+
+        let error = map.require::<dyn Greeter>().err().unwrap();
+        assert!(error.to_string().contains("No dyn "), "{}", error);
+        assert!(error.to_string().contains("Greeter registered (have: dyn "), "{}", error);
+    }
+}
@@ -0,0 +1,268 @@
+//! Slow-Start Admission (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Right after a (re)start every cache is cold - the history store's page
+//! cache, connections to future dependencies - while every client that
+//! lost its connection reconnects at once. Slow start admits only a few
+//! concurrent calls at first and raises the limit linearly until the
+//! configured maximum is reached:
+//!
+//! ```text
+//! limit
+//!  max ┤            ┌──────────
+//!      │          ╱
+//!      │       ╱
+//!      │    ╱
+//! init ┤ ╱
+//!      └┴───────────┴──────────→ time
+//!    start()   start() + ramp
+//!
+//! Direct / gRPC handler
+//!     ↓
+//! SlowStartEchoService ── in_flight ≥ limit(now) → overloaded(retry_after)
+//!     ↓
+//! EchoServiceImpl
+//! ```
+//!
+//! The module calls [`SlowStart::begin`] from `start()`, so a stop/start
+//! cycle ramps up again. Until then the limit stays at `initial`.
+//!
+//! Rejected calls carry a retry-after hint: reconnecting clients back off
+//! and spread out instead of hammering the cold server in lockstep.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::Result;
+use echo_contract::{
+    overloaded, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport,
+    HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+/// Ramp settings.
+#[derive(Debug, Clone)]
+pub struct SlowStartConfig {
+    /// Concurrent calls admitted right after start.
+    pub initial: usize,
+    /// Concurrent calls admitted once the ramp is over.
+    pub max_concurrent: usize,
+    /// Time from `initial` to `max_concurrent`.
+    pub ramp: Duration,
+    /// Back-off hint sent with rejected calls.
+    pub retry_after: Duration,
+}
+
+impl Default for SlowStartConfig {
+    fn default() -> Self {
+        Self {
+            initial: 4,
+            max_concurrent: 64,
+            ramp: Duration::from_secs(30),
+            retry_after: Duration::from_millis(200),
+        }
+    }
+}
+
+/// Admission limit ramping up after each start.
+///
+/// Shared between the module (which starts the ramp) and the service
+/// decorator (which enforces it).
+pub struct SlowStart {
+    config: SlowStartConfig,
+    /// Start of the current ramp (`None` before the first `begin`).
+    began: Mutex<Option<Instant>>,
+    in_flight: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+/// An admitted call; frees its slot on drop.
+pub struct SlowStartPermit {
+    slow_start: Arc<SlowStart>,
+}
+
+impl Drop for SlowStartPermit {
+    fn drop(&mut self) {
+        self.slow_start.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl SlowStart {
+    /// Creates a controller holding the limit at `initial` until [`begin`](Self::begin).
+    pub fn new(config: SlowStartConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            began: Mutex::new(None),
+            in_flight: AtomicUsize::new(0),
+            rejected: AtomicU64::new(0),
+        })
+    }
+
+    /// Starts (or restarts) the ramp from `initial`.
+    pub fn begin(&self) {
+        *self.began.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Returns the current admission limit.
+    pub fn limit(&self) -> usize {
+        self.limit_at(Instant::now())
+    }
+
+    /// Returns the number of calls rejected so far.
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Admits a call, or rejects it with [`echo_contract::overloaded`]
+    /// if the current limit is reached.
+    pub fn try_acquire(self: &Arc<Self>) -> Result<SlowStartPermit> {
+        let limit = self.limit();
+        let admitted = self.in_flight.fetch_update(Ordering::AcqRel, Ordering::Acquire, |in_flight| {
+            (in_flight < limit).then_some(in_flight + 1)
+        });
+        match admitted {
+            Ok(_) => Ok(SlowStartPermit { slow_start: self.clone() }),
+            Err(in_flight) => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                Err(overloaded(self.config.retry_after,
+                    format!("slow start: {} of {} calls in flight", in_flight, limit)))
+            }
+        }
+    }
+
+    fn limit_at(&self, now: Instant) -> usize {
+        let SlowStartConfig { initial, max_concurrent, ramp, .. } = self.config;
+        let initial = initial.clamp(1, max_concurrent.max(1));
+        let Some(began) = *self.began.lock().unwrap() else {
+            return initial;
+        };
+        let elapsed = now.saturating_duration_since(began);
+        if elapsed >= ramp {
+            return max_concurrent.max(initial);
+        }
+        let progress = elapsed.as_secs_f64() / ramp.as_secs_f64();
+        initial + ((max_concurrent - initial) as f64 * progress) as usize
+    }
+}
+
+/// Decorator that admits calls within the [`SlowStart`] limit.
+///
+/// Echo and history calls are limited; `get_info` and scheduling stay
+/// cheap and pass. For streaming history calls the slot covers opening
+/// the stream, not reading it.
+pub struct SlowStartEchoService {
+    inner: Arc<dyn EchoService>,
+    slow_start: Arc<SlowStart>,
+}
+
+impl SlowStartEchoService {
+    /// Wraps `inner`, admitting calls through `slow_start`.
+    pub fn new(inner: Arc<dyn EchoService>, slow_start: Arc<SlowStart>) -> Self {
+        Self { inner, slow_start }
+    }
+}
+
+#[async_trait]
+impl EchoService for SlowStartEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let _permit = self.slow_start.try_acquire()?;
+        self.inner.echo(message).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let _permit = self.slow_start.try_acquire()?;
+        self.inner.echo_bytes(payload).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let _permit = self.slow_start.try_acquire()?;
+        self.inner.echo_reliable(message, idempotency_key).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let _permit = self.slow_start.try_acquire()?;
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let _permit = self.slow_start.try_acquire()?;
+        self.inner.echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        let _permit = self.slow_start.try_acquire()?;
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        let _permit = self.slow_start.try_acquire()?;
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        let _permit = self.slow_start.try_acquire()?;
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        let _permit = self.slow_start.try_acquire()?;
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slow_start() -> Arc<SlowStart> {
+        SlowStart::new(SlowStartConfig {
+            initial: 2,
+            max_concurrent: 12,
+            ramp: Duration::from_secs(10),
+            retry_after: Duration::from_millis(50),
+        })
+    }
+
+    #[test]
+    fn test_limit_ramps_linearly_after_begin() {
+        let slow_start = slow_start();
+        assert_eq!(slow_start.limit(), 2);
+
+        slow_start.begin();
+        let began = slow_start.began.lock().unwrap().unwrap();
+        assert_eq!(slow_start.limit_at(began), 2);
+        assert_eq!(slow_start.limit_at(began + Duration::from_secs(5)), 7);
+        assert_eq!(slow_start.limit_at(began + Duration::from_secs(10)), 12);
+        assert_eq!(slow_start.limit_at(began + Duration::from_secs(60)), 12);
+    }
+
+    #[test]
+    fn test_rejects_beyond_the_limit() {
+        let slow_start = slow_start();
+        let first = slow_start.try_acquire().unwrap();
+        let _second = slow_start.try_acquire().unwrap();
+
+        let error = slow_start.try_acquire().err().unwrap();
+        assert!(echo_contract::is_overloaded(&error));
+        assert_eq!(echo_contract::retry_after(&error), Some(Duration::from_millis(50)));
+        assert_eq!(slow_start.rejected(), 1);
+
+        // A finished call frees its slot
+        drop(first);
+        assert!(slow_start.try_acquire().is_ok());
+    }
+}
@@ -0,0 +1,505 @@
+//! Parallel Module Startup (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! `run_with_config` starts the configured modules one after the other.
+//! Once starts do real work (history migration, self-test, registry
+//! publishing), their times add up. Each wiring wraps its module in a
+//! [`CoordinatedModule`]; the first `start()` the framework calls starts
+//! **every** coordinated module, level by level along the declared
+//! dependencies (see [`DependencyRegistry`]), concurrently within a level:
+//!
+//! ```text
+//! framework: start(echo-client) ─┐          start(echo) start(echo-monitor)
+//!                                ↓                ↓          ↓
+//! StartupCoordinator      level 0: echo           (outcome recorded above)
+//!                         level 1: echo-client ‖ echo-monitor
+//!                                ↓
+//!                         StartupReport (logged, admin GET /debug/startup)
+//! ```
+//!
+//! The later `start()` calls return the outcome recorded for their module.
+//! Each start has its own timeout ([`DEFAULT_MODULE_START_TIMEOUT`]); a
+//! module whose dependency failed or timed out isn't started at all.
+//!
+//! A start that outlasts its timeout is cancelled through the module's
+//! [`CancellationToken`] rather than dropped, and gets
+//! [`START_UNWIND_TIMEOUT`] to undo what it began (a registry publish, a
+//! spawned task). If any start of the batch fails, the modules it did
+//! start are stopped again, last level first: a failed startup leaves
+//! nothing half running.
+//!
+//! Dependencies on modules outside the batch (served remotely, or already
+//! running) don't order anything: they were checked by
+//! `validate_module_dependencies` before the start.
+//! A module created only after an earlier batch ran (a framework that
+//! creates and starts modules one by one) forms a batch of its own - the
+//! serial start again, in the framework's order.
+//!
+//! # Rust Learning Note
+//!
+//! The framework owns the `Box<dyn Module>`s, so the coordinator can't
+//! call them directly. Each wrapper shares its module as
+//! `Arc<tokio::sync::Mutex<..>>`, and the coordinator keeps a `Weak` to
+//! it: a module the framework drops without starting just disappears
+//! from the next batch.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use futures::future::join_all;
+use hsu_common::{Error, ModuleID, Result};
+use hsu_module_api::Module;
+use tracing::{info, warn};
+
+use crate::cancellation::CancellationToken;
+use crate::dependencies::DependencyRegistry;
+
+/// How long one module's `start()` may take by default.
+pub const DEFAULT_MODULE_START_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a timed-out `start()` may take to unwind once cancelled,
+/// before it is dropped where it stands.
+pub const START_UNWIND_TIMEOUT: Duration = Duration::from_secs(5);
+
+type SharedModule = Arc<tokio::sync::Mutex<Box<dyn Module>>>;
+type PendingModule = (String, Weak<tokio::sync::Mutex<Box<dyn Module>>>, CancellationToken);
+
+/// How one module's start ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartOutcome {
+    /// `start()` succeeded.
+    Started,
+    /// `start()` failed with this error.
+    Failed(String),
+    /// `start()` didn't finish within the module start timeout.
+    TimedOut(Duration),
+    /// Not started: this dependency didn't start.
+    Skipped { dependency: String },
+    /// Started, then stopped again: this module of the batch didn't start.
+    Stopped { failed: String },
+}
+
+impl fmt::Display for StartOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartOutcome::Started => f.write_str("started"),
+            StartOutcome::Failed(error) => write!(f, "failed: {}", error),
+            StartOutcome::TimedOut(timeout) => write!(f, "timed out after {:?}", timeout),
+            StartOutcome::Skipped { dependency } => write!(f, "skipped: {} didn't start", dependency),
+            StartOutcome::Stopped { failed } => write!(f, "stopped: {} didn't start", failed),
+        }
+    }
+}
+
+/// One module in a [`StartupReport`].
+#[derive(Debug, Clone)]
+pub struct ModuleStartup {
+    pub module: String,
+    /// Dependency level: level 0 starts first, level 1 once it's up, ...
+    pub level: usize,
+    pub outcome: StartOutcome,
+    pub elapsed: Duration,
+}
+
+/// The starts of one batch of modules.
+#[derive(Debug, Clone, Default)]
+pub struct StartupReport {
+    /// In start order (by level, then module ID).
+    pub modules: Vec<ModuleStartup>,
+    /// Wall-clock time of the whole batch.
+    pub total: Duration,
+}
+
+impl StartupReport {
+    /// Whether every module started.
+    pub fn all_started(&self) -> bool {
+        self.modules.iter().all(|module| module.outcome == StartOutcome::Started)
+    }
+
+    /// What the batch would have taken starting one module at a time.
+    pub fn serial(&self) -> Duration {
+        self.modules.iter().map(|module| module.elapsed).sum()
+    }
+}
+
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16} {:>5}  {:>10}  OUTCOME", "MODULE", "LEVEL", "ELAPSED")?;
+        for module in &self.modules {
+            writeln!(f, "{:<16} {:>5}  {:>10}  {}",
+                module.module, module.level, format!("{:.0?}", module.elapsed), module.outcome)?;
+        }
+        write!(f, "{} module(s) in {:.0?} (one at a time: {:.0?})", self.modules.len(), self.total, self.serial())
+    }
+}
+
+/// Starts the coordinated modules of a process.
+pub struct StartupCoordinator {
+    dependencies: Arc<DependencyRegistry>,
+    module_timeout: RwLock<Duration>,
+    /// Coordinated modules not started yet.
+    pending: Mutex<Vec<PendingModule>>,
+    /// Results of the last batch, taken by each module's own `start()`.
+    results: Mutex<HashMap<String, Result<()>>>,
+    /// One batch at a time.
+    batch: tokio::sync::Mutex<()>,
+    reports: RwLock<Vec<StartupReport>>,
+}
+
+impl StartupCoordinator {
+    /// Creates a coordinator ordering starts by `dependencies`.
+    pub fn new(dependencies: Arc<DependencyRegistry>) -> Self {
+        Self {
+            dependencies,
+            module_timeout: RwLock::new(DEFAULT_MODULE_START_TIMEOUT),
+            pending: Mutex::new(Vec::new()),
+            results: Mutex::new(HashMap::new()),
+            batch: tokio::sync::Mutex::new(()),
+            reports: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Returns the process-wide coordinator (ordered by the global
+    /// [`DependencyRegistry`]).
+    pub fn global() -> Arc<StartupCoordinator> {
+        static GLOBAL: OnceLock<Arc<StartupCoordinator>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(StartupCoordinator::new(DependencyRegistry::global()))).clone()
+    }
+
+    /// Sets how long each module's `start()` may take.
+    pub fn set_module_timeout(&self, timeout: Duration) {
+        *self.module_timeout.write().unwrap() = timeout;
+    }
+
+    /// Wraps `module` so its start is coordinated with the others'.
+    ///
+    /// `cancellation` is the token `module`'s start runs under; it is
+    /// cancelled if the start outlasts the module timeout.
+    pub fn coordinate(self: &Arc<Self>, module: Box<dyn Module>, cancellation: CancellationToken) -> CoordinatedModule {
+        let id = module.id().clone();
+        let module: SharedModule = Arc::new(tokio::sync::Mutex::new(module));
+        self.pending.lock().unwrap().push((id.to_string(), Arc::downgrade(&module), cancellation.clone()));
+        CoordinatedModule { id, module, cancellation, coordinator: self.clone() }
+    }
+
+    /// Reports of the batches started so far, oldest first.
+    pub fn reports(&self) -> Vec<StartupReport> {
+        self.reports.read().unwrap().clone()
+    }
+
+    /// Renders the reports for the admin endpoint.
+    pub fn render_text(&self) -> String {
+        let reports = self.reports();
+        if reports.is_empty() {
+            return "no coordinated module started yet\n".to_string();
+        }
+        reports.iter().map(|report| format!("{}\n", report)).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Starts `module`: its outcome from a batch already run, else a new
+    /// batch of every pending module (itself included).
+    async fn start(&self, id: &str, module: &SharedModule, cancellation: &CancellationToken) -> Result<()> {
+        let _batch = self.batch.lock().await;
+        if let Some(result) = self.results.lock().unwrap().remove(id) {
+            return result;
+        }
+
+        let mut batch: Vec<(String, SharedModule, CancellationToken)> = std::mem::take(&mut *self.pending.lock().unwrap())
+            .into_iter()
+            .filter_map(|(id, module, cancellation)| module.upgrade().map(|module| (id, module, cancellation)))
+            .collect();
+        // Not pending: restarted after a stop, it starts on its own
+        if !batch.iter().any(|(pending, _, _)| pending == id) {
+            batch.push((id.to_string(), module.clone(), cancellation.clone()));
+        }
+
+        let (report, mut results) = self.start_batch(batch).await;
+        for line in report.to_string().lines() {
+            info!("[Startup] {}", line);
+        }
+        self.reports.write().unwrap().push(report);
+        let own = results.remove(id).unwrap_or(Ok(()));
+        self.results.lock().unwrap().extend(results);
+        own
+    }
+
+    /// The batch's modules by dependency level, each level sorted by ID.
+    ///
+    /// Modules in a dependency cycle go last, in one level.
+    fn levels(&self, modules: &[String]) -> Vec<Vec<String>> {
+        let mut remaining: BTreeMap<&String, Vec<String>> = modules
+            .iter()
+            .map(|module| {
+                let needs = self.dependencies.consumed_modules(module)
+                    .into_iter()
+                    .filter(|dependency| dependency != module && modules.contains(dependency))
+                    .collect();
+                (module, needs)
+            })
+            .collect();
+
+        let mut levels: Vec<Vec<String>> = Vec::new();
+        while !remaining.is_empty() {
+            let ready: Vec<String> = remaining
+                .iter()
+                .filter(|(_, needs)| needs.iter().all(|dependency| !remaining.contains_key(dependency)))
+                .map(|(module, _)| (*module).clone())
+                .collect();
+            if ready.is_empty() {
+                let cycle: Vec<String> = remaining.keys().map(|module| (*module).clone()).collect();
+                warn!("[Startup] Dependency cycle between {}, starting them together", cycle.join(", "));
+                levels.push(cycle);
+                break;
+            }
+            for module in &ready {
+                remaining.remove(module);
+            }
+            levels.push(ready);
+        }
+        levels
+    }
+
+    /// Starts `batch` level by level, concurrently within a level, and
+    /// stops the started modules again if any start failed.
+    async fn start_batch(
+        &self,
+        batch: Vec<(String, SharedModule, CancellationToken)>,
+    ) -> (StartupReport, HashMap<String, Result<()>>) {
+        let began = Instant::now();
+        let timeout = *self.module_timeout.read().unwrap();
+        let ids: Vec<String> = batch.iter().map(|(id, _, _)| id.clone()).collect();
+        let modules: HashMap<String, (SharedModule, CancellationToken)> = batch
+            .into_iter()
+            .map(|(id, module, cancellation)| (id, (module, cancellation)))
+            .collect();
+
+        let mut report = StartupReport::default();
+        let mut results = HashMap::new();
+        for (level, members) in self.levels(&ids).into_iter().enumerate() {
+            let starts = members.iter().map(|id| {
+                let failed_dependency = self.dependencies.consumed_modules(id)
+                    .into_iter()
+                    .find(|dependency| matches!(results.get(dependency), Some(Err(_))));
+                let (module, cancellation) = modules[id].clone();
+                async move {
+                    let began = Instant::now();
+                    if let Some(dependency) = failed_dependency {
+                        let error = Error::Protocol(format!("module '{}' not started: {} didn't start", id, dependency));
+                        return (StartOutcome::Skipped { dependency }, Err(error), began.elapsed());
+                    }
+                    let start = async { module.lock().await.start().await };
+                    tokio::pin!(start);
+                    let (outcome, result) = match tokio::time::timeout(timeout, &mut start).await {
+                        Ok(Ok(())) => (StartOutcome::Started, Ok(())),
+                        Ok(Err(e)) => (StartOutcome::Failed(e.to_string()), Err(e)),
+                        Err(_) => {
+                            // Cancelled, not dropped: start() takes back what it began
+                            cancellation.cancel();
+                            match tokio::time::timeout(START_UNWIND_TIMEOUT, &mut start).await {
+                                // Came up while being cancelled: it runs
+                                Ok(Ok(())) => (StartOutcome::Started, Ok(())),
+                                Ok(Err(_)) => (
+                                    StartOutcome::TimedOut(timeout),
+                                    Err(Error::Protocol(format!("module '{}' didn't start within {:?}", id, timeout))),
+                                ),
+                                Err(_) => {
+                                    warn!("[Startup] Module '{}' didn't unwind within {:?} of its cancellation, dropping its start",
+                                        id, START_UNWIND_TIMEOUT);
+                                    (
+                                        StartOutcome::TimedOut(timeout),
+                                        Err(Error::Protocol(format!("module '{}' didn't start within {:?}", id, timeout))),
+                                    )
+                                }
+                            }
+                        }
+                    };
+                    (outcome, result, began.elapsed())
+                }
+            });
+            for (id, (outcome, result, elapsed)) in members.iter().zip(join_all(starts).await) {
+                report.modules.push(ModuleStartup { module: id.clone(), level, outcome, elapsed });
+                results.insert(id.clone(), result);
+            }
+        }
+
+        let failed = report.modules.iter()
+            .find(|module| matches!(module.outcome, StartOutcome::Failed(_) | StartOutcome::TimedOut(_)))
+            .map(|module| module.module.clone());
+        if let Some(failed) = failed {
+            // Dependents before their dependencies, like a shutdown
+            for startup in report.modules.iter_mut().rev().filter(|m| m.outcome == StartOutcome::Started) {
+                let (module, _) = &modules[&startup.module];
+                if let Err(e) = module.lock().await.stop().await {
+                    warn!("[Startup] Failed to stop module '{}' after {} didn't start: {}", startup.module, failed, e);
+                }
+                let error = Error::Protocol(format!("module '{}' stopped: {} didn't start", startup.module, failed));
+                results.insert(startup.module.clone(), Err(error));
+                startup.outcome = StartOutcome::Stopped { failed: failed.clone() };
+            }
+        }
+        report.total = began.elapsed();
+        (report, results)
+    }
+}
+
+/// A module whose start is coordinated by a [`StartupCoordinator`].
+pub struct CoordinatedModule {
+    id: ModuleID,
+    module: SharedModule,
+    cancellation: CancellationToken,
+    coordinator: Arc<StartupCoordinator>,
+}
+
+#[async_trait]
+impl Module for CoordinatedModule {
+    fn id(&self) -> &ModuleID {
+        &self.id
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        self.coordinator.start(&self.id.to_string(), &self.module, &self.cancellation).await
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.module.lock().await.stop().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dependencies::ModuleDependencies;
+
+    /// When each module was up.
+    type StartLog = Arc<Mutex<Vec<(String, Instant)>>>;
+
+    /// Takes `delay` to start (unless cancelled), recording when it was
+    /// up; a `Duration::MAX` delay fails the start instead.
+    struct SlowModule {
+        id: ModuleID,
+        delay: Duration,
+        started: StartLog,
+        stopped: Arc<Mutex<Vec<String>>>,
+        cancellation: CancellationToken,
+    }
+
+    #[async_trait]
+    impl Module for SlowModule {
+        fn id(&self) -> &ModuleID {
+            &self.id
+        }
+
+        async fn start(&mut self) -> Result<()> {
+            if self.delay == Duration::MAX {
+                return Err(Error::Protocol("history migration failed".to_string()));
+            }
+            let delay = async { tokio::time::sleep(self.delay).await; Ok(()) };
+            crate::cancellation::until_cancelled(&self.cancellation, "starting", delay).await?;
+            self.started.lock().unwrap().push((self.id.to_string(), Instant::now()));
+            Ok(())
+        }
+
+        async fn stop(&mut self) -> Result<()> {
+            self.stopped.lock().unwrap().push(self.id.to_string());
+            Ok(())
+        }
+    }
+
+    fn coordinator() -> Arc<StartupCoordinator> {
+        let dependencies = DependencyRegistry::new();
+        dependencies.declare(&ModuleID::from("echo"), ModuleDependencies::default().provides("service"));
+        dependencies.declare(&ModuleID::from("echo-client"), ModuleDependencies::default().consumes("echo/service"));
+        dependencies.declare(&ModuleID::from("echo-monitor"), ModuleDependencies::default().consumes("echo/events"));
+        Arc::new(StartupCoordinator::new(Arc::new(dependencies)))
+    }
+
+    fn modules(
+        coordinator: &Arc<StartupCoordinator>,
+        delays: &[(&str, Duration)],
+    ) -> (Vec<CoordinatedModule>, StartLog, Arc<Mutex<Vec<String>>>) {
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+        let modules = delays
+            .iter()
+            .map(|(id, delay)| {
+                let cancellation = CancellationToken::new();
+                let module = SlowModule {
+                    id: ModuleID::from(*id),
+                    delay: *delay,
+                    started: started.clone(),
+                    stopped: stopped.clone(),
+                    cancellation: cancellation.clone(),
+                };
+                coordinator.coordinate(Box::new(module), cancellation)
+            })
+            .collect();
+        (modules, started, stopped)
+    }
+
+    #[tokio::test]
+    async fn test_dependents_start_together_after_their_dependency() {
+        let coordinator = coordinator();
+        let delay = Duration::from_millis(100);
+        let (mut modules, started, _) =
+            modules(&coordinator, &[("echo-client", delay), ("echo", delay), ("echo-monitor", delay)]);
+
+        // The framework's order: the client first
+        for module in &mut modules {
+            module.start().await.unwrap();
+        }
+
+        let report = &coordinator.reports()[0];
+        let levels: Vec<(&str, usize)> = report.modules.iter().map(|m| (m.module.as_str(), m.level)).collect();
+        assert_eq!(levels, vec![("echo", 0), ("echo-client", 1), ("echo-monitor", 1)]);
+        assert!(report.all_started());
+        // Two levels, not three serial starts
+        assert!(report.total < delay * 3 - delay / 2, "took {:?}", report.total);
+        assert_eq!(coordinator.reports().len(), 1);
+
+        let started = started.lock().unwrap();
+        assert_eq!(started[0].0, "echo");
+        assert!(started[1..].iter().all(|(_, at)| *at >= started[0].1));
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_dependency_skips_its_dependents() {
+        let coordinator = coordinator();
+        coordinator.set_module_timeout(Duration::from_millis(50));
+        let (mut modules, _, _) =
+            modules(&coordinator, &[("echo", Duration::from_secs(10)), ("echo-client", Duration::ZERO)]);
+
+        // Cancelled and unwound, not left waiting out its delay
+        let began = Instant::now();
+        let error = modules[0].start().await.unwrap_err();
+        assert!(began.elapsed() < Duration::from_secs(5), "took {:?}", began.elapsed());
+        assert!(error.to_string().contains("didn't start within"));
+        let error = modules[1].start().await.unwrap_err();
+        assert!(error.to_string().contains("not started: echo didn't start"));
+
+        let report = &coordinator.reports()[0];
+        assert_eq!(report.modules[0].outcome, StartOutcome::TimedOut(Duration::from_millis(50)));
+        assert_eq!(report.modules[1].outcome, StartOutcome::Skipped { dependency: "echo".to_string() });
+        assert!(coordinator.render_text().contains("skipped: echo didn't start"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_start_stops_the_started_modules() {
+        let coordinator = coordinator();
+        let (mut modules, _, stopped) =
+            modules(&coordinator, &[("echo", Duration::ZERO), ("echo-client", Duration::MAX), ("echo-monitor", Duration::ZERO)]);
+
+        let error = modules[0].start().await.unwrap_err();
+        assert!(error.to_string().contains("module 'echo' stopped: echo-client didn't start"));
+        assert!(modules[1].start().await.unwrap_err().to_string().contains("history migration failed"));
+        assert!(modules[2].start().await.is_err());
+
+        // The monitor (level 1) before the echo module it depends on
+        assert_eq!(*stopped.lock().unwrap(), vec!["echo-monitor".to_string(), "echo".to_string()]);
+        let report = &coordinator.reports()[0];
+        assert_eq!(report.modules[0].outcome, StartOutcome::Stopped { failed: "echo-client".to_string() });
+        assert!(!report.all_started());
+    }
+}
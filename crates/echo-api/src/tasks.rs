@@ -0,0 +1,166 @@
+//! Per-module Background Task Registry (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! `tokio::spawn` returns a handle and forgets the task. If a module loses
+//! the handle (or never aborts it in `stop`), the task runs forever and
+//! nobody notices. Spawning through the registry keeps a record of every
+//! live background task, grouped by module:
+//!
+//! ```text
+//! EchoClientModule::start
+//!     ↓ spawn_tracked("echo-client", "outbox-flusher", ...)
+//! TaskRegistry   ←── admin endpoint: GET /debug/tasks
+//!     ↓ entry removed when the task finishes or is aborted
+//! ```
+//!
+//! # Rust Learning Note
+//!
+//! The entry is removed by a guard's `Drop`, which runs whether the task
+//! completes, panics or is aborted - no cleanup path can be forgotten.
+//!
+//! `tokio::spawn` doesn't carry the spawner's `tracing` span over, so
+//! the task runs in a `task` span that is a child of it: events of a
+//! task spawned in `start()` nest under the module's span.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{debug_span, Instrument};
+use crate::runtimes::{RuntimeAssignments, RuntimeRole};
+
+/// A live background task.
+#[derive(Debug, Clone)]
+pub struct TaskInfo {
+    /// Registry-assigned task ID.
+    pub id: u64,
+    /// Owning module.
+    pub module: String,
+    /// Task name (e.g. `"outbox-flusher"`).
+    pub name: String,
+    /// When the task was spawned.
+    pub spawned_at: Instant,
+}
+
+impl TaskInfo {
+    /// How long the task has been alive.
+    pub fn age(&self) -> Duration {
+        self.spawned_at.elapsed()
+    }
+}
+
+/// Registry of live background tasks.
+#[derive(Default)]
+pub struct TaskRegistry {
+    next_id: AtomicU64,
+    tasks: Mutex<BTreeMap<u64, TaskInfo>>,
+}
+
+impl TaskRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide registry.
+    pub fn global() -> Arc<TaskRegistry> {
+        static GLOBAL: OnceLock<Arc<TaskRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(TaskRegistry::new())).clone()
+    }
+
+    /// Spawns `future` and tracks it until it finishes or is aborted.
+    ///
+    /// Runs on the [`RuntimeRole::Modules`] runtime if one is assigned,
+    /// otherwise on the current runtime, in a `task` span under the
+    /// caller's.
+    pub fn spawn<F>(self: &Arc<Self>, module: &str, name: &str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.tasks.lock().unwrap().insert(id, TaskInfo {
+            id,
+            module: module.to_string(),
+            name: name.to_string(),
+            spawned_at: Instant::now(),
+        });
+
+        let guard = TaskGuard { registry: self.clone(), id };
+        let span = debug_span!("task", module_id = module, task = name);
+        let task = async move {
+            let _guard = guard;
+            future.await
+        }
+        .instrument(span);
+        match RuntimeAssignments::global().handle(RuntimeRole::Modules) {
+            Some(modules) => modules.spawn(task),
+            None => tokio::spawn(task),
+        }
+    }
+
+    /// Returns all live tasks, oldest first.
+    pub fn tasks(&self) -> Vec<TaskInfo> {
+        self.tasks.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Returns the number of live tasks per module.
+    pub fn count_by_module(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for task in self.tasks.lock().unwrap().values() {
+            *counts.entry(task.module.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+/// Removes the task's entry when dropped.
+struct TaskGuard {
+    registry: Arc<TaskRegistry>,
+    id: u64,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        self.registry.tasks.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Spawns `future` on the process-wide [`TaskRegistry`].
+pub fn spawn_tracked<F>(module: &str, name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    TaskRegistry::global().spawn(module, name, future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tracks_until_finished() {
+        let registry = Arc::new(TaskRegistry::new());
+
+        let task = registry.spawn("echo-client", "one-shot", async { 42 });
+        assert_eq!(task.await.unwrap(), 42);
+
+        assert!(registry.tasks().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aborted_task_is_removed() {
+        let registry = Arc::new(TaskRegistry::new());
+
+        let task = registry.spawn("echo-client", "forever", std::future::pending::<()>());
+        assert_eq!(registry.count_by_module().get("echo-client"), Some(&1));
+
+        task.abort();
+        let _ = task.await;
+        assert!(registry.tasks().is_empty());
+    }
+}
@@ -0,0 +1,403 @@
+//! Tower Adapters (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Bridges `EchoService` and `tower::Service`, in both directions, so
+//! standard tower middleware composes with echo gateways and handlers:
+//!
+//! ```text
+//! Arc<dyn EchoService>  (gateway or handler)
+//!     ↓ EchoTowerService::new
+//! tower::Service<EchoRequest>
+//!     ↓ ServiceBuilder: timeout, rate_limit, buffer, concurrency_limit, ...
+//! tower::Service<EchoRequest>
+//!     ↓ TowerEchoService::new
+//! Arc<dyn EchoService>  (hand back to the module / register as handler)
+//! ```
+//!
+//! ```rust,ignore
+//! let service = ServiceBuilder::new()
+//!     .buffer(64)
+//!     .rate_limit(100, Duration::from_secs(1))
+//!     .timeout(Duration::from_millis(200))
+//!     .service(EchoTowerService::new(gateway));
+//! let gateway: Arc<dyn EchoService> = Arc::new(TowerEchoService::new(service));
+//! ```
+//!
+//! Middleware errors come back as echo errors: tower's timeout becomes
+//! [`echo_contract::deadline_exceeded`], other middleware errors become
+//! `Error::Protocol`; an `hsu_common::Error` from the inner service
+//! passes through unchanged.
+//!
+//! ## Rust Learning Note
+//!
+//! `EchoRequest` isn't `Clone` (`EchoFile` carries a stream), so
+//! middleware that replays requests (`tower::retry`) doesn't apply; use
+//! `echo_at_least_once` for retries.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use hsu_common::{Error, Result};
+use echo_contract::{
+    deadline_exceeded, encode_history, stream_pages, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest,
+    HistoryExportFormat, HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo,
+    SessionEcho,
+};
+use tower::{BoxError, Service, ServiceExt};
+
+/// One `EchoService` call as a value.
+pub enum EchoRequest {
+    Echo(Arc<str>),
+    EchoBytes(Bytes),
+    EchoReliable { message: Arc<str>, idempotency_key: String },
+    EchoFile(ByteStream),
+    EchoWithSession { session_id: String, message: Arc<str> },
+    GetInfo,
+    ScheduleEcho { message: Arc<str>, schedule: EchoSchedule },
+    CancelScheduledEcho { job_id: String },
+    /// `stream_history` is sent as one of these per page.
+    GetHistory(HistoryQuery),
+    ImportHistory { format: HistoryExportFormat, data: ByteStream },
+}
+
+impl EchoRequest {
+    /// The `EchoService` method this request calls (`echo`, ...).
+    pub fn method(&self) -> &'static str {
+        match self {
+            EchoRequest::Echo(_) => "echo",
+            EchoRequest::EchoBytes(_) => "echo_bytes",
+            EchoRequest::EchoReliable { .. } => "echo_reliable",
+            EchoRequest::EchoFile(_) => "echo_file",
+            EchoRequest::EchoWithSession { .. } => "echo_with_session",
+            EchoRequest::GetInfo => "get_info",
+            EchoRequest::ScheduleEcho { .. } => "schedule_echo",
+            EchoRequest::CancelScheduledEcho { .. } => "cancel_scheduled_echo",
+            EchoRequest::GetHistory(_) => "get_history",
+            EchoRequest::ImportHistory { .. } => "import_history",
+        }
+    }
+}
+
+impl fmt::Debug for EchoRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EchoRequest::{}", self.method())
+    }
+}
+
+/// Result of an [`EchoRequest`]; the variant matches the request's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EchoResponse {
+    Echo(Arc<str>),
+    EchoBytes(Bytes),
+    EchoReliable(EchoAck),
+    EchoFile(FileDigest),
+    EchoWithSession(SessionEcho),
+    GetInfo(ServerInfo),
+    ScheduleEcho(ScheduledEcho),
+    CancelScheduledEcho(bool),
+    GetHistory(HistoryPage),
+    ImportHistory(HistoryImportReport),
+}
+
+/// `tower::Service` over an `EchoService`.
+///
+/// Always ready; back-pressure comes from the middleware in front.
+#[derive(Clone)]
+pub struct EchoTowerService {
+    inner: Arc<dyn EchoService>,
+}
+
+impl EchoTowerService {
+    /// Wraps `inner` (a gateway, handler or decorator).
+    pub fn new(inner: Arc<dyn EchoService>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Service<EchoRequest> for EchoTowerService {
+    type Response = EchoResponse;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<EchoResponse>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: EchoRequest) -> Self::Future {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            Ok(match request {
+                EchoRequest::Echo(message) => EchoResponse::Echo(inner.echo(message).await?),
+                EchoRequest::EchoBytes(payload) => EchoResponse::EchoBytes(inner.echo_bytes(payload).await?),
+                EchoRequest::EchoReliable { message, idempotency_key } => {
+                    EchoResponse::EchoReliable(inner.echo_reliable(message, idempotency_key).await?)
+                }
+                EchoRequest::EchoFile(chunks) => EchoResponse::EchoFile(inner.echo_file(chunks).await?),
+                EchoRequest::EchoWithSession { session_id, message } => {
+                    EchoResponse::EchoWithSession(inner.echo_with_session(session_id, message).await?)
+                }
+                EchoRequest::GetInfo => EchoResponse::GetInfo(inner.get_info().await?),
+                EchoRequest::ScheduleEcho { message, schedule } => {
+                    EchoResponse::ScheduleEcho(inner.schedule_echo(message, schedule).await?)
+                }
+                EchoRequest::CancelScheduledEcho { job_id } => {
+                    EchoResponse::CancelScheduledEcho(inner.cancel_scheduled_echo(job_id).await?)
+                }
+                EchoRequest::GetHistory(query) => EchoResponse::GetHistory(inner.get_history(query).await?),
+                EchoRequest::ImportHistory { format, data } => {
+                    EchoResponse::ImportHistory(inner.import_history(format, data).await?)
+                }
+            })
+        })
+    }
+}
+
+/// `EchoService` over a `tower::Service` stack.
+///
+/// Each call clones the stack and waits for it to be ready, so use
+/// cheaply clonable middleware (`buffer` makes any stack so). The stack
+/// only has to be `Send`; the mutex is held just for the clone.
+pub struct TowerEchoService<S> {
+    inner: Mutex<S>,
+}
+
+impl<S> TowerEchoService<S> {
+    /// Wraps the tower stack `inner`.
+    pub fn new(inner: S) -> Self {
+        Self { inner: Mutex::new(inner) }
+    }
+}
+
+impl<S> TowerEchoService<S>
+where
+    S: Service<EchoRequest, Response = EchoResponse> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    async fn call(&self, request: EchoRequest) -> Result<EchoResponse> {
+        let service = self.inner.lock().unwrap().clone();
+        call_stack(service, request).await
+    }
+}
+
+/// Sends `request` through (a clone of) the tower stack.
+async fn call_stack<S>(service: S, request: EchoRequest) -> Result<EchoResponse>
+where
+    S: Service<EchoRequest, Response = EchoResponse>,
+    S::Error: Into<BoxError>,
+{
+    let method = request.method();
+    let service = service.ready_oneshot().await.map_err(|e| to_echo_error(e.into(), method))?;
+    service.oneshot(request).await.map_err(|e| to_echo_error(e.into(), method))
+}
+
+#[async_trait]
+impl<S> EchoService for TowerEchoService<S>
+where
+    S: Service<EchoRequest, Response = EchoResponse> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        match self.call(EchoRequest::Echo(message)).await? {
+            EchoResponse::Echo(reply) => Ok(reply),
+            other => Err(mismatch("echo", &other)),
+        }
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        match self.call(EchoRequest::EchoBytes(payload)).await? {
+            EchoResponse::EchoBytes(reply) => Ok(reply),
+            other => Err(mismatch("echo_bytes", &other)),
+        }
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        match self.call(EchoRequest::EchoReliable { message, idempotency_key }).await? {
+            EchoResponse::EchoReliable(ack) => Ok(ack),
+            other => Err(mismatch("echo_reliable", &other)),
+        }
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        match self.call(EchoRequest::EchoFile(chunks)).await? {
+            EchoResponse::EchoFile(digest) => Ok(digest),
+            other => Err(mismatch("echo_file", &other)),
+        }
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        match self.call(EchoRequest::EchoWithSession { session_id, message }).await? {
+            EchoResponse::EchoWithSession(reply) => Ok(reply),
+            other => Err(mismatch("echo_with_session", &other)),
+        }
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        match self.call(EchoRequest::GetInfo).await? {
+            EchoResponse::GetInfo(info) => Ok(info),
+            other => Err(mismatch("get_info", &other)),
+        }
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        match self.call(EchoRequest::ScheduleEcho { message, schedule }).await? {
+            EchoResponse::ScheduleEcho(scheduled) => Ok(scheduled),
+            other => Err(mismatch("schedule_echo", &other)),
+        }
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        match self.call(EchoRequest::CancelScheduledEcho { job_id }).await? {
+            EchoResponse::CancelScheduledEcho(cancelled) => Ok(cancelled),
+            other => Err(mismatch("cancel_scheduled_echo", &other)),
+        }
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        match self.call(EchoRequest::GetHistory(query)).await? {
+            EchoResponse::GetHistory(page) => Ok(page),
+            other => Err(mismatch("get_history", &other)),
+        }
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        // A response can't carry a stream: page through get_history, so
+        // every page passes the middleware
+        let service = self.inner.lock().unwrap().clone();
+        Ok(stream_pages(query, move |query| {
+            let service = service.clone();
+            async move {
+                match call_stack(service, EchoRequest::GetHistory(query)).await? {
+                    EchoResponse::GetHistory(page) => Ok(page),
+                    other => Err(mismatch("get_history", &other)),
+                }
+            }
+        }))
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        // Encoded on this side, from the same pages
+        Ok(encode_history(self.stream_history(query).await?, format))
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        match self.call(EchoRequest::ImportHistory { format, data }).await? {
+            EchoResponse::ImportHistory(report) => Ok(report),
+            other => Err(mismatch("import_history", &other)),
+        }
+    }
+}
+
+/// Recovers the echo error behind a middleware error.
+fn to_echo_error(error: BoxError, method: &str) -> Error {
+    let error = match error.downcast::<Error>() {
+        Ok(error) => return *error,
+        Err(error) => error,
+    };
+    if error.is::<tower::timeout::error::Elapsed>() {
+        return deadline_exceeded(format!("{} timed out in tower middleware", method));
+    }
+    Error::Protocol(format!("{} failed in tower middleware: {}", method, error))
+}
+
+fn mismatch(method: &str, response: &EchoResponse) -> Error {
+    Error::Protocol(format!("tower service answered {} with {:?}", method, response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use echo_contract::is_deadline_exceeded;
+    use tower::ServiceBuilder;
+
+    /// Echoes after `delay`.
+    struct SlowEcho {
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl EchoService for SlowEcho {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            tokio::time::sleep(self.delay).await;
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+
+        async fn echo_file(&self, _chunks: ByteStream) -> Result<FileDigest> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn echo_with_session(&self, _session_id: String, _message: Arc<str>) -> Result<SessionEcho> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn get_info(&self) -> Result<ServerInfo> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn schedule_echo(&self, _message: Arc<str>, _schedule: EchoSchedule) -> Result<ScheduledEcho> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn cancel_scheduled_echo(&self, _job_id: String) -> Result<bool> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn get_history(&self, _query: HistoryQuery) -> Result<HistoryPage> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn stream_history(&self, _query: HistoryQuery) -> Result<HistoryStream> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn export_history(&self, _format: HistoryExportFormat, _query: HistoryQuery) -> Result<ByteStream> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn import_history(&self, _format: HistoryExportFormat, _data: ByteStream) -> Result<HistoryImportReport> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+    }
+
+    fn through_tower(delay: Duration, timeout: Duration) -> Arc<dyn EchoService> {
+        let stack = ServiceBuilder::new()
+            .buffer(8)
+            .timeout(timeout)
+            .service(EchoTowerService::new(Arc::new(SlowEcho { delay })));
+        Arc::new(TowerEchoService::new(stack))
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_through_middleware() {
+        let service = through_tower(Duration::ZERO, Duration::from_secs(5));
+
+        assert_eq!(&*service.echo("hi".into()).await.unwrap(), "hi");
+        let ack = service.echo_reliable("hi".into(), "k1".to_string()).await.unwrap();
+        assert_eq!(ack.idempotency_key, "k1");
+        // The inner service's own error passes through unchanged
+        let error = service.echo_with_session("s".to_string(), "hi".into()).await.unwrap_err();
+        assert_eq!(error.to_string(), Error::Protocol("not supported".to_string()).to_string());
+    }
+
+    #[tokio::test]
+    async fn test_tower_timeout_is_deadline_exceeded() {
+        let service = through_tower(Duration::from_secs(5), Duration::from_millis(10));
+
+        let error = service.echo("hi".into()).await.unwrap_err();
+        assert!(is_deadline_exceeded(&error), "{}", error);
+    }
+}
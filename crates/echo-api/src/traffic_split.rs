@@ -0,0 +1,407 @@
+//! Traffic Splitting Between Two Implementations (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A canary rollout inside one module process: the current implementation
+//! (`v1`) and the new one (`v2`) are both registered, and a configurable
+//! share of the calls goes to the new one. Each variant is measured on
+//! its own, so the rollout can be judged before it is widened:
+//!
+//! ```text
+//! Direct / gRPC handler
+//!     ↓
+//! TrafficSplitEchoService ── TrafficSplit (canary 10%)
+//!     ├─ 90% ──→ stable "v1" ──┐
+//!     └─ 10% ──→ canary "v2" ──┴─→ TrafficSplitMetrics (calls, errors, latency per variant)
+//!                                    ↓ GET /metrics
+//!
+//! POST /maintenance/echo/traffic-split -d 25   ← widen the rollout at runtime
+//! ```
+//!
+//! Only the echo calls are split. Calls with a session ID always reach
+//! the same variant, as do calls of one caller, so a client sees one
+//! variant consistently; anonymous calls are spread evenly. Scheduling,
+//! history and `get_info` go to the stable variant - both variants are
+//! expected to share those stores.
+//!
+//! Replies carry the variant name as the `variant` response metadata.
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! svc := stable
+//! if split.ToCanary(sessionID) {
+//!     svc = canary
+//! }
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::{Error, Result};
+use tracing::info;
+use echo_contract::{
+    attach_response_metadata, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, RequestContext, ScheduledEcho, ServerInfo,
+    SessionEcho,
+};
+use crate::maintenance::MaintenanceRegistry;
+
+/// Response metadata key naming the variant that answered.
+pub const VARIANT_METADATA_KEY: &str = "variant";
+
+/// Operation that sets the canary share (body: a percentage).
+pub const TRAFFIC_SPLIT_OPERATION: &str = "traffic-split";
+
+/// Share of the calls routed to the canary variant.
+pub struct TrafficSplit {
+    canary_percent: AtomicU8,
+    /// Anonymous calls routed so far.
+    routed: AtomicU64,
+}
+
+impl TrafficSplit {
+    /// Creates a split sending `canary_percent` of the calls to the canary.
+    pub fn new(canary_percent: u8) -> Result<Arc<Self>> {
+        Ok(Arc::new(Self {
+            canary_percent: AtomicU8::new(valid_percent(canary_percent)?),
+            routed: AtomicU64::new(0),
+        }))
+    }
+
+    /// Returns the share of calls routed to the canary, in percent.
+    pub fn canary_percent(&self) -> u8 {
+        self.canary_percent.load(Ordering::Relaxed)
+    }
+
+    /// Changes the canary share (0 rolls back, 100 completes the rollout).
+    pub fn set_canary_percent(&self, canary_percent: u8) -> Result<()> {
+        self.canary_percent.store(valid_percent(canary_percent)?, Ordering::Relaxed);
+        info!("[TrafficSplit] Routing {}% of the calls to the canary", canary_percent);
+        Ok(())
+    }
+
+    /// Whether a call goes to the canary.
+    ///
+    /// Calls with a `sticky_key` are assigned by its hash, so they always
+    /// land on the same variant (for a given percentage); the others take
+    /// turns, so every 100 of them include exactly `canary_percent`.
+    pub fn to_canary(&self, sticky_key: Option<&str>) -> bool {
+        let percent = u64::from(self.canary_percent());
+        match sticky_key {
+            Some(key) => {
+                let mut hasher = DefaultHasher::new();
+                key.hash(&mut hasher);
+                hasher.finish() % 100 < percent
+            }
+            None => {
+                let n = self.routed.fetch_add(1, Ordering::Relaxed);
+                (n + 1) * percent / 100 > n * percent / 100
+            }
+        }
+    }
+}
+
+fn valid_percent(percent: u8) -> Result<u8> {
+    if percent > 100 {
+        return Err(Error::Validation { message: format!("Canary share {}% is above 100%", percent) });
+    }
+    Ok(percent)
+}
+
+/// Calls, errors and latency of one variant.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VariantStats {
+    /// Calls routed to the variant.
+    pub calls: u64,
+    /// Calls that returned an error.
+    pub errors: u64,
+    /// Total time spent in the variant.
+    pub latency_seconds: f64,
+}
+
+/// Name, help, type and value of one per-variant metric.
+type VariantMetric = (&'static str, &'static str, &'static str, fn(&VariantStats) -> f64);
+
+/// Per-variant counters.
+#[derive(Debug, Default)]
+pub struct TrafficSplitMetrics {
+    variants: Mutex<BTreeMap<String, VariantStats>>,
+}
+
+impl TrafficSplitMetrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide metrics.
+    pub fn global() -> Arc<TrafficSplitMetrics> {
+        static GLOBAL: OnceLock<Arc<TrafficSplitMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(TrafficSplitMetrics::new())).clone()
+    }
+
+    /// Returns the counters of `variant` (zero if it never got a call).
+    pub fn stats(&self, variant: &str) -> VariantStats {
+        self.variants.lock().unwrap().get(variant).copied().unwrap_or_default()
+    }
+
+    fn record(&self, variant: &str, success: bool, started: Instant) {
+        let mut variants = self.variants.lock().unwrap();
+        let stats = variants.entry(variant.to_string()).or_default();
+        stats.calls += 1;
+        if !success {
+            stats.errors += 1;
+        }
+        stats.latency_seconds += started.elapsed().as_secs_f64();
+    }
+
+    /// Renders the counters in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let variants = self.variants.lock().unwrap();
+        let mut out = String::new();
+        let metrics: [VariantMetric; 3] = [
+            ("echo_variant_calls_total", "Echo calls routed to each variant", "counter", |s| s.calls as f64),
+            ("echo_variant_errors_total", "Echo calls failed, by variant", "counter", |s| s.errors as f64),
+            ("echo_variant_latency_seconds_sum", "Time spent in each variant", "counter", |s| s.latency_seconds),
+        ];
+        for (name, help, kind, value) in metrics {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (variant, stats) in variants.iter() {
+                let _ = writeln!(out, "{}{{variant=\"{}\"}} {}", name, variant, value(stats));
+            }
+        }
+        out
+    }
+}
+
+/// Registers [`TRAFFIC_SPLIT_OPERATION`] for `module`: the argument is the
+/// new canary share in percent (the current one is reported if empty).
+pub fn register_traffic_split_operation(module: &str, split: Arc<TrafficSplit>) {
+    MaintenanceRegistry::global().register_operation(module, TRAFFIC_SPLIT_OPERATION, Arc::new(move |argument: String| {
+        let split = split.clone();
+        Box::pin(async move {
+            if !argument.is_empty() {
+                let percent = argument.trim_end_matches('%').parse().map_err(|_| Error::Validation {
+                    message: format!("Invalid canary share '{}' (expected 0-100)", argument),
+                })?;
+                split.set_canary_percent(percent)?;
+            }
+            Ok(format!("{}% of the calls go to the canary", split.canary_percent()))
+        })
+    }));
+}
+
+/// One implementation taking part in the split.
+struct Variant {
+    name: String,
+    service: Arc<dyn EchoService>,
+}
+
+/// Decorator routing echo calls to a stable or a canary implementation.
+pub struct TrafficSplitEchoService {
+    stable: Variant,
+    canary: Variant,
+    split: Arc<TrafficSplit>,
+    metrics: Arc<TrafficSplitMetrics>,
+}
+
+impl TrafficSplitEchoService {
+    /// Splits between `stable` and `canary` (each with its variant name),
+    /// recording into the global [`TrafficSplitMetrics`].
+    pub fn new(
+        stable: (impl Into<String>, Arc<dyn EchoService>),
+        canary: (impl Into<String>, Arc<dyn EchoService>),
+        split: Arc<TrafficSplit>,
+    ) -> Self {
+        Self::with_metrics(stable, canary, split, TrafficSplitMetrics::global())
+    }
+
+    /// Splits between `stable` and `canary`, recording into `metrics`.
+    pub fn with_metrics(
+        stable: (impl Into<String>, Arc<dyn EchoService>),
+        canary: (impl Into<String>, Arc<dyn EchoService>),
+        split: Arc<TrafficSplit>,
+        metrics: Arc<TrafficSplitMetrics>,
+    ) -> Self {
+        Self {
+            stable: Variant { name: stable.0.into(), service: stable.1 },
+            canary: Variant { name: canary.0.into(), service: canary.1 },
+            split,
+            metrics,
+        }
+    }
+
+    /// Picks the variant of a call; sticky by session, then by caller.
+    fn choose(&self, session_id: Option<&str>) -> &Variant {
+        let caller = RequestContext::current().caller;
+        if self.split.to_canary(session_id.or(caller.as_deref())) {
+            &self.canary
+        } else {
+            &self.stable
+        }
+    }
+
+    /// Records the outcome of a call to `variant` and labels the reply.
+    fn finish<T>(&self, variant: &Variant, started: Instant, result: Result<T>) -> Result<T> {
+        self.metrics.record(&variant.name, result.is_ok(), started);
+        attach_response_metadata(VARIANT_METADATA_KEY, variant.name.as_str());
+        result
+    }
+}
+
+#[async_trait]
+impl EchoService for TrafficSplitEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let (variant, started) = (self.choose(None), Instant::now());
+        self.finish(variant, started, variant.service.echo(message).await)
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let (variant, started) = (self.choose(None), Instant::now());
+        self.finish(variant, started, variant.service.echo_bytes(payload).await)
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let (variant, started) = (self.choose(None), Instant::now());
+        self.finish(variant, started, variant.service.echo_reliable(message, idempotency_key).await)
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let (variant, started) = (self.choose(None), Instant::now());
+        self.finish(variant, started, variant.service.echo_file(chunks).await)
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let (variant, started) = (self.choose(Some(&session_id)), Instant::now());
+        self.finish(variant, started, variant.service.echo_with_session(session_id, message).await)
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.stable.service.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.stable.service.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.stable.service.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.stable.service.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.stable.service.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.stable.service.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.stable.service.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Replies with its own name.
+    struct NamedService(&'static str);
+
+    #[async_trait]
+    impl EchoService for NamedService {
+        async fn echo(&self, _message: Arc<str>) -> Result<Arc<str>> {
+            Ok(self.0.into())
+        }
+
+        async fn echo_bytes(&self, _payload: Bytes) -> Result<Bytes> {
+            Err(Error::Protocol(format!("{} failed", self.0)))
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+
+        async fn echo_with_session(&self, session_id: String, _message: Arc<str>) -> Result<SessionEcho> {
+            Ok(SessionEcho { message: self.0.into(), session_id, count: 1, previous_seen: None })
+        }
+    }
+
+    fn service(percent: u8, metrics: Arc<TrafficSplitMetrics>) -> TrafficSplitEchoService {
+        TrafficSplitEchoService::with_metrics(
+            ("v1", Arc::new(NamedService("v1")) as Arc<dyn EchoService>),
+            ("v2", Arc::new(NamedService("v2")) as Arc<dyn EchoService>),
+            TrafficSplit::new(percent).unwrap(),
+            metrics,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_calls_split_by_percentage() {
+        let metrics = Arc::new(TrafficSplitMetrics::new());
+        let service = service(10, metrics.clone());
+
+        let mut canary = 0;
+        for _ in 0..100 {
+            if &*service.echo("hi".into()).await.unwrap() == "v2" {
+                canary += 1;
+            }
+        }
+        assert_eq!(canary, 10);
+        assert!(service.echo_bytes(Bytes::new()).await.is_err());
+
+        let (v1, v2) = (metrics.stats("v1"), metrics.stats("v2"));
+        assert_eq!(v1.calls + v2.calls, 101);
+        assert_eq!(v1.errors + v2.errors, 1);
+        assert!(metrics.render_prometheus().contains("echo_variant_calls_total{variant=\"v2\"}"));
+    }
+
+    #[tokio::test]
+    async fn test_sessions_and_callers_are_sticky() {
+        let service = service(50, Arc::new(TrafficSplitMetrics::new()));
+
+        for session in ["a", "b", "c", "d"] {
+            let first = service.echo_with_session(session.to_string(), "hi".into()).await.unwrap().message;
+            for _ in 0..5 {
+                let again = service.echo_with_session(session.to_string(), "hi".into()).await.unwrap().message;
+                assert_eq!(again, first);
+            }
+        }
+
+        let caller = RequestContext::default().with_caller("tenant");
+        let first = caller.clone().scope(service.echo("hi".into())).await.unwrap();
+        for _ in 0..5 {
+            assert_eq!(caller.clone().scope(service.echo("hi".into())).await.unwrap(), first);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_operation_moves_the_split() {
+        let split = TrafficSplit::new(0).unwrap();
+        assert!(!split.to_canary(Some("anyone")));
+        register_traffic_split_operation("traffic-split-test", split.clone());
+        let registry = MaintenanceRegistry::global();
+
+        let summary = registry.run_operation("traffic-split-test", TRAFFIC_SPLIT_OPERATION, "100%".into())
+            .await.unwrap().unwrap();
+        assert_eq!(summary, "100% of the calls go to the canary");
+        assert!(split.to_canary(Some("anyone")) && split.to_canary(None));
+
+        assert!(registry.run_operation("traffic-split-test", TRAFFIC_SPLIT_OPERATION, "150".into())
+            .await.unwrap().is_err());
+        assert!(TrafficSplit::new(101).is_err());
+        registry.clear("traffic-split-test");
+    }
+}
@@ -0,0 +1,235 @@
+//! Typed Service Clients (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Client code wants "call `echo` on the echo service", not "ask the
+//! gateways for a service with protocol `Auto`, through a
+//! `ServiceGatewayFactory` built from `GatewayFactoryFuncs`".
+//! [`TypedServiceClient`] keeps that machinery behind one call:
+//!
+//! ```text
+//! client.call(|svc| svc.echo(message))
+//!     ↓ first call (or after UNAVAILABLE): resolve
+//! ServiceSource<dyn EchoService>  (EchoServiceGateways::get_service)
+//!     ↓ ServiceGatewayFactory → Direct / gRPC gateway
+//! Arc<dyn EchoService>  (cached)
+//! ```
+//!
+//! The protocol defaults to `Auto` (Direct when the server module is in
+//! process, gRPC otherwise); [`with_protocol`](TypedServiceClient::with_protocol)
+//! pins one where it matters.
+//!
+//! ## Comparison with Golang
+//!
+//! ```go
+//! client := echoapi.NewEchoClient(gateways)
+//! reply, err := client.Echo1(ctx, "hello")
+//! ```
+//!
+//! ## Rust Learning Note
+//!
+//! `async_trait` methods return `Pin<Box<dyn Future + Send + 'a>>`
+//! borrowing the service, so `call` takes a closure from `&'a C` to a
+//! `BoxFuture<'a, _>` for *every* `'a` (`for<'a>`). That is what lets
+//! `|svc| svc.echo(message)` compile without cloning or `async move`.
+
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use hsu_common::{Protocol, Result};
+use echo_contract::{is_unavailable, EchoEvents, EchoService, EchoServiceGateways};
+use tracing::debug;
+
+/// Resolves a service of type `C` over a protocol.
+#[async_trait]
+pub trait ServiceSource<C: ?Sized>: Send + Sync {
+    async fn resolve(&self, protocol: Protocol) -> Result<Arc<C>>;
+}
+
+/// Echo service and events, resolved through the echo gateways.
+struct EchoGatewaysSource(Arc<dyn EchoServiceGateways>);
+
+#[async_trait]
+impl ServiceSource<dyn EchoService> for EchoGatewaysSource {
+    async fn resolve(&self, protocol: Protocol) -> Result<Arc<dyn EchoService>> {
+        self.0.get_service(protocol).await
+    }
+}
+
+#[async_trait]
+impl ServiceSource<dyn EchoEvents> for EchoGatewaysSource {
+    async fn resolve(&self, protocol: Protocol) -> Result<Arc<dyn EchoEvents>> {
+        self.0.get_events(protocol).await
+    }
+}
+
+/// Client for a service of type `C` (e.g. `dyn EchoService`).
+///
+/// Resolves the service on first use and caches it; an `UNAVAILABLE`
+/// error drops the cache, so the next call resolves again.
+pub struct TypedServiceClient<C: ?Sized> {
+    source: Arc<dyn ServiceSource<C>>,
+    protocol: Protocol,
+    resolved: Mutex<Option<Arc<C>>>,
+}
+
+impl<C: ?Sized + Send + Sync + 'static> TypedServiceClient<C> {
+    /// Creates a client resolving through `source`.
+    pub fn new(source: Arc<dyn ServiceSource<C>>) -> Self {
+        Self {
+            source,
+            protocol: Protocol::Auto,
+            resolved: Mutex::new(None),
+        }
+    }
+
+    /// Uses `protocol` instead of `Auto`.
+    pub fn with_protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Returns the service, resolving it if needed.
+    pub async fn service(&self) -> Result<Arc<C>> {
+        if let Some(service) = self.resolved.lock().unwrap().clone() {
+            return Ok(service);
+        }
+        debug!("[TypedServiceClient] Resolving service with protocol {:?}", self.protocol);
+        let service = self.source.resolve(self.protocol).await?;
+        *self.resolved.lock().unwrap() = Some(service.clone());
+        Ok(service)
+    }
+
+    /// Runs `call` on the service.
+    ///
+    /// ```rust,ignore
+    /// let reply = client.call(|svc| svc.echo(message)).await?;
+    /// ```
+    pub async fn call<T, F>(&self, call: F) -> Result<T>
+    where
+        F: for<'a> FnOnce(&'a C) -> BoxFuture<'a, Result<T>>,
+    {
+        let service = self.service().await?;
+        let result = call(&service).await;
+        if let Err(e) = &result {
+            if is_unavailable(e) {
+                debug!("[TypedServiceClient] Service unavailable, resolving again on the next call");
+                self.resolved.lock().unwrap().take();
+            }
+        }
+        result
+    }
+}
+
+impl TypedServiceClient<dyn EchoService> {
+    /// Client for the echo service behind `gateways`.
+    pub fn echo(gateways: Arc<dyn EchoServiceGateways>) -> Self {
+        Self::new(Arc::new(EchoGatewaysSource(gateways)))
+    }
+}
+
+impl TypedServiceClient<dyn EchoEvents> {
+    /// Client for the echo events behind `gateways`.
+    pub fn events(gateways: Arc<dyn EchoServiceGateways>) -> Self {
+        Self::new(Arc::new(EchoGatewaysSource(gateways)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use bytes::Bytes;
+    use echo_contract::{
+        unavailable, ByteStream, EchoAck, EchoSchedule, FileDigest, HistoryExportFormat, HistoryImportReport,
+        HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+    };
+    use hsu_common::Error;
+
+    /// Fails with UNAVAILABLE when the message is "down".
+    struct FlakyEcho;
+
+    #[async_trait]
+    impl EchoService for FlakyEcho {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            match &*message {
+                "down" => Err(unavailable("connection refused")),
+                _ => Ok(message),
+            }
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+
+        async fn echo_file(&self, _chunks: ByteStream) -> Result<FileDigest> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn echo_with_session(&self, _session_id: String, _message: Arc<str>) -> Result<SessionEcho> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn get_info(&self) -> Result<ServerInfo> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn schedule_echo(&self, _message: Arc<str>, _schedule: EchoSchedule) -> Result<ScheduledEcho> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn cancel_scheduled_echo(&self, _job_id: String) -> Result<bool> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn get_history(&self, _query: HistoryQuery) -> Result<HistoryPage> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn stream_history(&self, _query: HistoryQuery) -> Result<HistoryStream> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn export_history(&self, _format: HistoryExportFormat, _query: HistoryQuery) -> Result<ByteStream> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+
+        async fn import_history(&self, _format: HistoryExportFormat, _data: ByteStream) -> Result<HistoryImportReport> {
+            Err(Error::Protocol("not supported".to_string()))
+        }
+    }
+
+    /// Counts resolutions.
+    #[derive(Default)]
+    struct CountingSource {
+        resolved: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ServiceSource<dyn EchoService> for CountingSource {
+        async fn resolve(&self, _protocol: Protocol) -> Result<Arc<dyn EchoService>> {
+            self.resolved.fetch_add(1, Ordering::SeqCst);
+            Ok(Arc::new(FlakyEcho))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_resolves_once_and_again_after_unavailable() {
+        let source = Arc::new(CountingSource::default());
+        let client = TypedServiceClient::<dyn EchoService>::new(source.clone());
+
+        let message: Arc<str> = "hi".into();
+        assert_eq!(&*client.call(|svc| svc.echo(message)).await.unwrap(), "hi");
+        let ack = client.call(|svc| svc.echo_reliable("hi".into(), "k1".to_string())).await.unwrap();
+        assert_eq!(ack.idempotency_key, "k1");
+        assert_eq!(source.resolved.load(Ordering::SeqCst), 1);
+
+        assert!(client.call(|svc| svc.echo("down".into())).await.is_err());
+        client.call(|svc| svc.echo("hi".into())).await.unwrap();
+        assert_eq!(source.resolved.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,340 @@
+//! Client-Side Response Validation (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! A conformance test or a monitoring probe doesn't just want *a* reply -
+//! it wants the *right* reply. Validators registered with the gateways
+//! check every echoed reply before the caller sees it, and turn a
+//! mismatch into a typed error:
+//!
+//! ```text
+//! caller: echo("ping")
+//!     ↓
+//! ValidatingEchoService ──→ gateway → server
+//!     ↓ reply "pong"
+//! EqualsRequest: "pong" != "ping"
+//!     ↓
+//! Err(INVALID_RESPONSE: validator 'equals_request': ...)   + failure metric
+//! ```
+//!
+//! Validators apply to the text echoes (`echo`, `echo_reliable`,
+//! `echo_with_session`); the first failing one decides the error, which
+//! [`echo_contract::is_invalid_response`] recognizes. Replies of calls
+//! with [transformations](echo_contract::EchoTransform) legitimately
+//! differ from the request, so [`ResponseValidator::EqualsRequest`]
+//! skips them.
+//!
+//! # Rust Learning Note
+//!
+//! [`ResponseValidator::Custom`] holds an `Arc<dyn Fn>`: closures have no
+//! nameable type, so the enum stores them behind a trait object. That
+//! also means no derived `Debug` - the impl below prints the name only.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::{Error, Result};
+use echo_contract::{
+    invalid_response, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, RequestContext, ScheduledEcho, ServerInfo,
+    SessionEcho,
+};
+use regex::Regex;
+use tracing::warn;
+
+/// Custom check: gets the request and the reply, returns why the reply is wrong.
+pub type ReplyCheck = dyn Fn(&str, &str) -> std::result::Result<(), String> + Send + Sync;
+
+/// A check applied to every echoed reply.
+#[derive(Clone)]
+pub enum ResponseValidator {
+    /// The reply must equal the request (skipped for transformed calls).
+    EqualsRequest,
+    /// The reply must match the regular expression.
+    Matches(Regex),
+    /// The reply must be at most this many bytes.
+    MaxBytes(usize),
+    /// Any other check, reported under `name`.
+    Custom { name: Arc<str>, check: Arc<ReplyCheck> },
+}
+
+impl ResponseValidator {
+    /// Validator requiring replies to match `pattern`.
+    pub fn matches(pattern: &str) -> Result<Self> {
+        Regex::new(pattern)
+            .map(ResponseValidator::Matches)
+            .map_err(|e| Error::Validation { message: format!("Invalid reply pattern '{}': {}", pattern, e) })
+    }
+
+    /// Validator running `check` on each request and reply.
+    pub fn custom(
+        name: impl Into<Arc<str>>,
+        check: impl Fn(&str, &str) -> std::result::Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        ResponseValidator::Custom { name: name.into(), check: Arc::new(check) }
+    }
+
+    /// Name used in errors and metrics (`equals_request`, `matches`, ...).
+    pub fn name(&self) -> &str {
+        match self {
+            ResponseValidator::EqualsRequest => "equals_request",
+            ResponseValidator::Matches(_) => "matches",
+            ResponseValidator::MaxBytes(_) => "max_bytes",
+            ResponseValidator::Custom { name, .. } => name,
+        }
+    }
+
+    /// Checks `reply` to `request`, returning why it is wrong.
+    pub fn check(&self, request: &str, reply: &str) -> std::result::Result<(), String> {
+        match self {
+            ResponseValidator::EqualsRequest => {
+                if reply == request || !RequestContext::current().transforms.is_empty() {
+                    Ok(())
+                } else {
+                    Err(format!("expected '{}', got '{}'", request, reply))
+                }
+            }
+            ResponseValidator::Matches(pattern) => {
+                if pattern.is_match(reply) {
+                    Ok(())
+                } else {
+                    Err(format!("'{}' doesn't match /{}/", reply, pattern))
+                }
+            }
+            ResponseValidator::MaxBytes(limit) => {
+                if reply.len() <= *limit {
+                    Ok(())
+                } else {
+                    Err(format!("reply is {} bytes, limit is {}", reply.len(), limit))
+                }
+            }
+            ResponseValidator::Custom { check, .. } => check(request, reply),
+        }
+    }
+}
+
+impl fmt::Debug for ResponseValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResponseValidator::EqualsRequest => f.write_str("EqualsRequest"),
+            ResponseValidator::Matches(pattern) => f.debug_tuple("Matches").field(&pattern.as_str()).finish(),
+            ResponseValidator::MaxBytes(limit) => f.debug_tuple("MaxBytes").field(limit).finish(),
+            ResponseValidator::Custom { name, .. } => f.debug_struct("Custom").field("name", name).finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Validation counters.
+#[derive(Debug, Default)]
+pub struct ValidationMetrics {
+    validated: AtomicU64,
+    /// Failures by validator name.
+    failures: Mutex<BTreeMap<String, u64>>,
+}
+
+impl ValidationMetrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide metrics.
+    pub fn global() -> Arc<ValidationMetrics> {
+        static GLOBAL: OnceLock<Arc<ValidationMetrics>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(ValidationMetrics::new())).clone()
+    }
+
+    /// Returns the number of replies checked.
+    pub fn validated(&self) -> u64 {
+        self.validated.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of replies `validator` rejected.
+    pub fn failures(&self, validator: &str) -> u64 {
+        self.failures.lock().unwrap().get(validator).copied().unwrap_or(0)
+    }
+
+    /// Renders the counters in the Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP echo_response_validations_total Client echo replies checked by response validators");
+        let _ = writeln!(out, "# TYPE echo_response_validations_total counter");
+        let _ = writeln!(out, "echo_response_validations_total {}", self.validated());
+        let _ = writeln!(out, "# HELP echo_response_validation_failures_total Client echo replies rejected, by validator");
+        let _ = writeln!(out, "# TYPE echo_response_validation_failures_total counter");
+        for (validator, value) in self.failures.lock().unwrap().iter() {
+            let _ = writeln!(out, "echo_response_validation_failures_total{{validator=\"{}\"}} {}", validator, value);
+        }
+        out
+    }
+}
+
+/// Decorator checking echoed replies with [`ResponseValidator`]s.
+pub struct ValidatingEchoService {
+    inner: Arc<dyn EchoService>,
+    validators: Arc<[ResponseValidator]>,
+    metrics: Arc<ValidationMetrics>,
+}
+
+impl ValidatingEchoService {
+    /// Wraps `inner`, recording into the global [`ValidationMetrics`].
+    pub fn new(inner: Arc<dyn EchoService>, validators: impl Into<Arc<[ResponseValidator]>>) -> Self {
+        Self::with_metrics(inner, validators, ValidationMetrics::global())
+    }
+
+    /// Wraps `inner`, recording into `metrics`.
+    pub fn with_metrics(
+        inner: Arc<dyn EchoService>,
+        validators: impl Into<Arc<[ResponseValidator]>>,
+        metrics: Arc<ValidationMetrics>,
+    ) -> Self {
+        Self { inner, validators: validators.into(), metrics }
+    }
+
+    fn validate(&self, request: &str, reply: &str) -> Result<()> {
+        self.metrics.validated.fetch_add(1, Ordering::Relaxed);
+        for validator in self.validators.iter() {
+            if let Err(reason) = validator.check(request, reply) {
+                *self.metrics.failures.lock().unwrap().entry(validator.name().to_string()).or_default() += 1;
+                warn!("[ResponseValidation] Validator '{}' rejected a reply: {}", validator.name(), reason);
+                return Err(invalid_response(format_args!("validator '{}': {}", validator.name(), reason)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EchoService for ValidatingEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let reply = self.inner.echo(message.clone()).await?;
+        self.validate(&message, &reply)?;
+        Ok(reply)
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        self.inner.echo_bytes(payload).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let ack = self.inner.echo_reliable(message.clone(), idempotency_key).await?;
+        self.validate(&message, &ack.message)?;
+        Ok(ack)
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.inner.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let echo = self.inner.echo_with_session(session_id, message.clone()).await?;
+        self.validate(&message, &echo.message)?;
+        Ok(echo)
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::{EchoTransform, Priority};
+
+    /// Replies with the message upper-cased if it starts with `shout:`.
+    struct MockService;
+
+    #[async_trait]
+    impl EchoService for MockService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            match message.strip_prefix("shout:") {
+                Some(rest) => Ok(rest.to_uppercase().into()),
+                None => Ok(message),
+            }
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            Ok(EchoAck { message: self.echo(message).await?, idempotency_key, duplicate: false })
+        }
+    }
+
+    fn validating(validators: Vec<ResponseValidator>) -> (ValidatingEchoService, Arc<ValidationMetrics>) {
+        let metrics = Arc::new(ValidationMetrics::new());
+        (ValidatingEchoService::with_metrics(Arc::new(MockService), validators, metrics.clone()), metrics)
+    }
+
+    #[tokio::test]
+    async fn test_mismatch_becomes_invalid_response() {
+        let (service, metrics) = validating(vec![ResponseValidator::EqualsRequest]);
+
+        assert_eq!(&*service.echo("ping".into()).await.unwrap(), "ping");
+        let error = service.echo("shout:ping".into()).await.unwrap_err();
+        assert!(echo_contract::is_invalid_response(&error), "{}", error);
+        assert!(error.to_string().contains("equals_request"));
+        let error = service.echo_reliable("shout:ping".into(), "key".into()).await.unwrap_err();
+        assert!(echo_contract::is_invalid_response(&error));
+
+        assert_eq!((metrics.validated(), metrics.failures("equals_request")), (3, 2));
+        assert!(metrics.render_prometheus()
+            .contains("echo_response_validation_failures_total{validator=\"equals_request\"} 2"));
+    }
+
+    #[tokio::test]
+    async fn test_transformed_calls_skip_equals_request() {
+        let (service, _) = validating(vec![ResponseValidator::EqualsRequest]);
+        let context = RequestContext::new(Priority::Normal).with_transforms(vec![EchoTransform::Nfc]);
+
+        assert!(context.scope(service.echo("shout:ping".into())).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_first_failing_validator_decides() {
+        let (service, metrics) = validating(vec![
+            ResponseValidator::matches("^[a-z]+$").unwrap(),
+            ResponseValidator::MaxBytes(3),
+            ResponseValidator::custom("never", |_, _| Err("unreachable".to_string())),
+        ]);
+
+        let error = service.echo("ping".into()).await.unwrap_err();
+        assert!(error.to_string().contains("max_bytes"), "{}", error);
+        let error = service.echo("PING".into()).await.unwrap_err();
+        assert!(error.to_string().contains("matches"), "{}", error);
+        let error = service.echo("abc".into()).await.unwrap_err();
+        assert!(error.to_string().contains("never"), "{}", error);
+        assert_eq!(metrics.failures("never"), 1);
+
+        assert!(ResponseValidator::matches("(").is_err());
+    }
+}
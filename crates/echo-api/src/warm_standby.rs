@@ -0,0 +1,394 @@
+//! Warm Standby Transport (Layer 3/5 Boundary)
+//!
+//! # Architecture
+//!
+//! Falling back to another protocol after a failure normally pays for a
+//! connection setup (TCP, HTTP/2, TLS) exactly when things are already
+//! going wrong. With a warm standby the gateways open the second
+//! transport next to the one in use and keep it alive with periodic
+//! probes, so a call the primary couldn't deliver is resent at once:
+//!
+//! ```text
+//! caller: echo("ping")
+//!     ↓
+//! FallbackEchoService
+//!     ├─ primary (e.g. Direct) ──→ UNAVAILABLE
+//!     └─ standby (e.g. gRPC, warm) ──→ "ping" ──→ caller   (fallbacks += 1)
+//!
+//! keep_warm task: every probe_interval ──→ standby.get_info()
+//!                     ↓ Warm { probe_latency } / Cold { error }
+//!                 GatewayCapabilities::standby ──→ GET /capabilities
+//! ```
+//!
+//! Only [`UNAVAILABLE`](echo_contract::is_unavailable) failures fall
+//! back; other errors (deadlines, rejected replies) come from the service
+//! itself and are returned as they are. Even UNAVAILABLE doesn't prove
+//! the service never saw the call - a connection can drop after the
+//! request went out - so only calls that are safe to run twice are
+//! resent: the idempotent reads and echoes (`echo`, `echo_bytes`,
+//! `get_info`, `get_history`) and `echo_reliable`, whose idempotency key
+//! lets the server answer a repeat from its dedup window. Sessions and
+//! scheduled jobs change server state and get the primary's error, as do
+//! the streaming methods, which consume their input. A cold standby isn't
+//! tried at all - the caller gets the primary's error without a second
+//! wait.
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! reply, err := primary.Echo(ctx, msg)
+//! if isUnavailable(err) && standby.Warm() { reply, err = standby.Echo(ctx, msg) }
+//! ```
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::{Protocol, Result};
+use echo_contract::{
+    is_unavailable, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+    StandbyState, StandbyStatus,
+};
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+/// Which transport to keep warm, and how often to probe it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmStandbyConfig {
+    /// Standby protocol (`Grpc`, or `Http` for a server with JSON
+    /// transcoding). Calls already using it don't fall back.
+    pub protocol: Protocol,
+    /// Time between probes; also how long one probe may take.
+    pub probe_interval: Duration,
+}
+
+impl WarmStandbyConfig {
+    /// Keeps `protocol` warm, probing every 10s.
+    pub fn new(protocol: Protocol) -> Self {
+        Self { protocol, probe_interval: Duration::from_secs(10) }
+    }
+}
+
+/// A connected second transport and how ready it is.
+pub struct WarmStandby {
+    protocol: Protocol,
+    endpoint: String,
+    service: Arc<dyn EchoService>,
+    state: Mutex<StandbyState>,
+    fallbacks: AtomicU64,
+}
+
+impl WarmStandby {
+    /// Wraps `service`, the standby gateway over `protocol` to `endpoint`
+    /// (not probed yet).
+    pub fn new(protocol: Protocol, endpoint: impl Into<String>, service: Arc<dyn EchoService>) -> Arc<Self> {
+        Arc::new(Self {
+            protocol,
+            endpoint: endpoint.into(),
+            service,
+            state: Mutex::new(StandbyState::Connecting),
+            fallbacks: AtomicU64::new(0),
+        })
+    }
+
+    /// The standby protocol.
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Whether the last probe succeeded.
+    pub fn is_warm(&self) -> bool {
+        matches!(*self.state.lock().unwrap(), StandbyState::Warm { .. })
+    }
+
+    /// State and fallback count, as reported in the capabilities.
+    pub fn status(&self) -> StandbyStatus {
+        StandbyStatus {
+            protocol: self.protocol,
+            endpoint: self.endpoint.clone(),
+            state: self.state.lock().unwrap().clone(),
+            fallbacks: self.fallbacks.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Sends one `get_info` over the standby, giving up after `timeout`.
+    ///
+    /// Logs only when the standby turns warm or cold, not on every probe.
+    pub async fn probe(&self, timeout: Duration) -> bool {
+        let started = Instant::now();
+        let state = match tokio::time::timeout(timeout, self.service.get_info()).await {
+            Ok(Ok(_)) => StandbyState::Warm { probe_latency: started.elapsed() },
+            Ok(Err(e)) => StandbyState::Cold { error: e.to_string() },
+            Err(_) => StandbyState::Cold { error: format!("probe timed out after {:?}", timeout) },
+        };
+        let warm = matches!(state, StandbyState::Warm { .. });
+        let was_warm = std::mem::replace(&mut *self.state.lock().unwrap(), state.clone());
+        match (matches!(was_warm, StandbyState::Warm { .. }), &state) {
+            (false, StandbyState::Warm { probe_latency }) => {
+                info!("[WarmStandby] {:?} standby to {} is warm (probe {:?})", self.protocol, self.endpoint, probe_latency);
+            }
+            (true, StandbyState::Cold { error }) => {
+                warn!("[WarmStandby] {:?} standby to {} went cold: {}", self.protocol, self.endpoint, error);
+            }
+            _ => {}
+        }
+        warm
+    }
+
+    /// Probes every `interval` until the standby is dropped or `closed`
+    /// turns true (the gateways were closed).
+    pub fn keep_warm(self: &Arc<Self>, interval: Duration, mut closed: watch::Receiver<bool>) -> impl Future<Output = ()> {
+        let standby: Weak<Self> = Arc::downgrade(self);
+        async move {
+            loop {
+                let Some(current) = standby.upgrade() else { break };
+                current.probe(interval).await;
+                drop(current);
+                tokio::select! {
+                    () = tokio::time::sleep(interval) => {}
+                    // Also ends if the gateways are gone
+                    _ = closed.wait_for(|closed| *closed) => break,
+                }
+            }
+            debug!("[WarmStandby] Stopped probing");
+        }
+    }
+}
+
+/// Decorator resending calls the primary couldn't deliver over a warm
+/// standby, if they are safe to run twice (see the module docs).
+pub struct FallbackEchoService {
+    primary: Arc<dyn EchoService>,
+    standby: Arc<WarmStandby>,
+}
+
+impl FallbackEchoService {
+    /// Falls back from `primary` to `standby` while it is warm.
+    pub fn new(primary: Arc<dyn EchoService>, standby: Arc<WarmStandby>) -> Self {
+        Self { primary, standby }
+    }
+
+    /// Returns `result`, or the standby's answer to `resend` if the
+    /// primary was unavailable and the standby is warm.
+    async fn fallback<T, F>(
+        &self,
+        method: &'static str,
+        result: Result<T>,
+        resend: impl FnOnce(Arc<dyn EchoService>) -> F,
+    ) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        match result {
+            Err(e) if is_unavailable(&e) && self.standby.is_warm() => {
+                debug!("[WarmStandby] {} failed ({}), resending over {:?}", method, e, self.standby.protocol);
+                let result = resend(self.standby.service.clone()).await;
+                if result.is_ok() {
+                    self.standby.fallbacks.fetch_add(1, Ordering::Relaxed);
+                }
+                result
+            }
+            result => result,
+        }
+    }
+}
+
+#[async_trait]
+impl EchoService for FallbackEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let result = self.primary.echo(message.clone()).await;
+        self.fallback("echo", result, |standby| async move { standby.echo(message).await }).await
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let result = self.primary.echo_bytes(payload.clone()).await;
+        self.fallback("echo_bytes", result, |standby| async move { standby.echo_bytes(payload).await }).await
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let result = self.primary.echo_reliable(message.clone(), idempotency_key.clone()).await;
+        self.fallback("echo_reliable", result, |standby| async move {
+            standby.echo_reliable(message, idempotency_key).await
+        }).await
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        self.primary.echo_file(chunks).await
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        self.primary.echo_with_session(session_id, message).await
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        let result = self.primary.get_info().await;
+        self.fallback("get_info", result, |standby| async move { standby.get_info().await }).await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        self.primary.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.primary.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        let result = self.primary.get_history(query.clone()).await;
+        self.fallback("get_history", result, |standby| async move { standby.get_history(query).await }).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.primary.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.primary.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        self.primary.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use echo_contract::unavailable;
+
+    /// Echoes with a prefix while `up`, fails as UNAVAILABLE otherwise.
+    struct SwitchService {
+        prefix: &'static str,
+        up: AtomicBool,
+    }
+
+    impl SwitchService {
+        fn new(prefix: &'static str, up: bool) -> Arc<Self> {
+            Arc::new(Self { prefix, up: AtomicBool::new(up) })
+        }
+
+        fn check(&self) -> Result<()> {
+            if self.up.load(Ordering::Relaxed) {
+                Ok(())
+            } else {
+                Err(unavailable("connection refused"))
+            }
+        }
+    }
+
+    #[async_trait]
+    impl EchoService for SwitchService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            self.check()?;
+            Ok(format!("{}{}", self.prefix, message).into())
+        }
+
+        async fn get_info(&self) -> Result<ServerInfo> {
+            self.check()?;
+            Ok(ServerInfo {
+                module_id: "echo".to_string(),
+                instance_id: "a1b2".to_string(),
+                version: "0.1.0".to_string(),
+                git_hash: "abc123".to_string(),
+                uptime: Duration::ZERO,
+                features: Vec::new(),
+            })
+        }
+
+        async fn schedule_echo(&self, _message: Arc<str>, _schedule: EchoSchedule) -> Result<ScheduledEcho> {
+            self.check()?;
+            Ok(ScheduledEcho { job_id: "job-1".to_string(), next_run: std::time::SystemTime::now() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_primary_falls_back_once_warm() {
+        let standby_service = SwitchService::new("standby:", true);
+        let standby = WarmStandby::new(Protocol::Grpc, "127.0.0.1:50055", standby_service.clone());
+        let service = FallbackEchoService::new(SwitchService::new("", false), standby.clone());
+
+        // Not probed yet: the primary's error is returned as it is
+        assert!(is_unavailable(&service.echo("hi".into()).await.unwrap_err()));
+        assert_eq!(standby.status().state, StandbyState::Connecting);
+
+        assert!(standby.probe(Duration::from_secs(1)).await);
+        assert_eq!(&*service.echo("hi".into()).await.unwrap(), "standby:hi");
+        assert_eq!(standby.status().fallbacks, 1);
+
+        standby_service.up.store(false, Ordering::Relaxed);
+        assert!(!standby.probe(Duration::from_secs(1)).await);
+        assert!(matches!(standby.status().state, StandbyState::Cold { .. }));
+        assert!(service.echo("hi".into()).await.is_err());
+        assert_eq!(standby.status().fallbacks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_do_not_fall_back() {
+        let standby = WarmStandby::new(Protocol::Grpc, "127.0.0.1:50055", SwitchService::new("standby:", true));
+        assert!(standby.probe(Duration::from_secs(1)).await);
+        let service = FallbackEchoService::new(SwitchService::new("", true), standby.clone());
+
+        assert_eq!(&*service.echo("hi".into()).await.unwrap(), "hi");
+        assert!(service.echo_bytes(Bytes::from_static(b"x")).await.is_err());
+        assert_eq!(standby.status().fallbacks, 0);
+    }
+
+    #[tokio::test]
+    async fn test_state_changing_calls_do_not_fall_back() {
+        let standby_service = SwitchService::new("standby:", true);
+        let standby = WarmStandby::new(Protocol::Grpc, "127.0.0.1:50055", standby_service);
+        assert!(standby.probe(Duration::from_secs(1)).await);
+        let service = FallbackEchoService::new(SwitchService::new("", false), standby.clone());
+
+        // The primary may have created the job before its connection dropped
+        let schedule = EchoSchedule::After(Duration::from_secs(1));
+        assert!(is_unavailable(&service.schedule_echo("hi".into(), schedule).await.unwrap_err()));
+        assert_eq!(standby.status().fallbacks, 0);
+    }
+
+    #[tokio::test]
+    async fn test_keep_warm_stops_when_closed() {
+        let standby = WarmStandby::new(Protocol::Grpc, "127.0.0.1:50055", SwitchService::new("", true));
+        let (closed, watch) = watch::channel(false);
+        let task = tokio::spawn(standby.keep_warm(Duration::from_millis(10), watch));
+
+        while !standby.is_warm() {
+            tokio::task::yield_now().await;
+        }
+        closed.send_replace(true);
+        task.await.unwrap();
+    }
+    #[cfg(feature = "grpc")]
+    #[tokio::test]
+    async fn test_standby_is_probed_before_the_first_call() {
+        use echo_api_grpc::generated::echo_service_server::EchoServiceServer;
+        use echo_api_grpc::EchoGrpcHandler;
+        use crate::gateways::{new_standalone_echo_service_gateways, GatewayOptions};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(EchoServiceServer::new(EchoGrpcHandler::new(SwitchService::new("", true))))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        let gateways = new_standalone_echo_service_gateways(GatewayOptions {
+            grpc_address: Some(address),
+            warm_standby: Some(WarmStandbyConfig::new(Protocol::Grpc)),
+            ..Default::default()
+        });
+
+        // No call made: creating the gateways connected and probed it
+        let warm = async {
+            while !matches!(gateways.capabilities().standby.map(|s| s.state), Some(StandbyState::Warm { .. })) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), warm).await.expect("standby should turn warm");
+        gateways.close().await.unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+//! Wiring Consistency Lint (Layer 5)
+//!
+//! # Architecture
+//!
+//! A module's wiring names its services in several places that the
+//! framework never cross-checks: the dependency declaration, the service
+//! IDs the handlers registrar publishes, and the service IDs its gateways
+//! ask for. When they drift (`service` in one, `service1` in another),
+//! nothing fails until a consumer's gateway can't find the service at
+//! runtime. Each module crate's wiring tests describe the module in a
+//! [`WiringDescriptor`], filled from what its `init_*` function hands the
+//! framework, and lint it instead:
+//!
+//! ```text
+//! WiringDescriptor (echo)
+//!   provides           service, events       ← ModuleDependencies
+//!   handler_services   service, events       ← handlers of create_module
+//!   handler_protocols  grpc, http            ← the descriptor's registrar
+//!   direct_closure     yes                   ← the descriptor's enabler
+//!   gateway_services   service, events       ← gateways' service_ids()
+//!   gateway_protocols  direct, grpc          ← gateways' capabilities
+//!       ↓ lint()
+//!   [] or [GatewayServiceUnhandled("service2"), ...]
+//! ```
+//!
+//! A gateway service must have a handler - published by the registrar,
+//! or handed over by direct closure - and so must every protocol the
+//! gateways claim to carry. Services the module only reaches remotely are
+//! its `consumed_gateways`, checked against what it declares to consume.
+//!
+//! Test-only: built for echo-api's own tests and with the `wiring-lint`
+//! feature, which the module crates turn on in their dev-dependencies.
+
+use std::fmt;
+use hsu_common::{Protocol, ServiceID};
+use crate::dependencies::{ModuleDependencies, ServiceRef};
+
+/// One module's wiring, as the lint sees it.
+#[derive(Debug, Clone, Default)]
+pub struct WiringDescriptor {
+    /// The module ID it registers under.
+    pub module_id: String,
+    /// What it declares to provide and consume.
+    pub dependencies: ModuleDependencies,
+    /// Service IDs its handlers registrar publishes (empty without one).
+    pub handler_services: Vec<String>,
+    /// Protocols its handlers registrar serves (empty without one).
+    pub handler_protocols: Vec<Protocol>,
+    /// Whether it hands its handlers to consumers by direct closure.
+    pub direct_closure: bool,
+    /// Service IDs of the gateways consumers reach it through.
+    pub gateway_services: Vec<String>,
+    /// Protocols those gateways carry calls over.
+    pub gateway_protocols: Vec<Protocol>,
+    /// Services of other modules its own gateways can ask for (remote-only
+    /// from its point of view).
+    pub consumed_gateways: Vec<ServiceRef>,
+}
+
+/// A wiring inconsistency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WiringIssue {
+    /// A gateway asks for a service nothing handles.
+    GatewayServiceUnhandled(String),
+    /// The gateways carry a protocol nothing serves.
+    GatewayProtocolUnserved(Protocol),
+    /// A declared service is neither published nor handed over directly.
+    ProvidedServiceUnserved(String),
+    /// The registrar publishes a service the module doesn't declare.
+    HandlerServiceUndeclared(String),
+    /// A declared dependency no consumer-side gateway asks for.
+    ConsumedServiceUnknown(ServiceRef),
+}
+
+impl fmt::Display for WiringIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WiringIssue::GatewayServiceUnhandled(service) => {
+                write!(f, "gateway service '{}' has no handler", service)
+            }
+            WiringIssue::GatewayProtocolUnserved(protocol) => {
+                write!(f, "gateways carry {:?}, which neither the registrar nor direct closure serves", protocol)
+            }
+            WiringIssue::ProvidedServiceUnserved(service) => {
+                write!(f, "provided service '{}' is neither published nor handed over by direct closure", service)
+            }
+            WiringIssue::HandlerServiceUndeclared(service) => {
+                write!(f, "registrar publishes '{}', which isn't declared as provided", service)
+            }
+            WiringIssue::ConsumedServiceUnknown(service) => {
+                write!(f, "consumes '{}', which no gateway of the module asks for", service)
+            }
+        }
+    }
+}
+
+impl WiringDescriptor {
+    /// Describes `module_id` declaring `dependencies`.
+    pub fn new(module_id: impl Into<String>, dependencies: ModuleDependencies) -> Self {
+        Self { module_id: module_id.into(), dependencies, ..Default::default() }
+    }
+
+    /// Records the service IDs the handlers registrar publishes.
+    pub fn with_handler_services(mut self, services: impl IntoIterator<Item = ServiceID>) -> Self {
+        self.handler_services = services.into_iter().map(|service| service.to_string()).collect();
+        self
+    }
+
+    /// Records the protocols the handlers registrar serves.
+    pub fn with_handler_protocols(mut self, protocols: impl IntoIterator<Item = Protocol>) -> Self {
+        self.handler_protocols = protocols.into_iter().collect();
+        self
+    }
+
+    /// Records whether handlers are handed over by direct closure.
+    pub fn with_direct_closure(mut self, enabled: bool) -> Self {
+        self.direct_closure = enabled;
+        self
+    }
+
+    /// Records the service IDs of the module's own gateways.
+    pub fn with_gateway_services(mut self, services: impl IntoIterator<Item = ServiceID>) -> Self {
+        self.gateway_services = services.into_iter().map(|service| service.to_string()).collect();
+        self
+    }
+
+    /// Records the protocols the module's own gateways carry.
+    pub fn with_gateway_protocols(mut self, protocols: impl IntoIterator<Item = Protocol>) -> Self {
+        self.gateway_protocols = protocols.into_iter().collect();
+        self
+    }
+
+    /// Records the services `module`'s gateways, used by this module, ask for.
+    pub fn with_consumed_gateways(mut self, module: &str, services: impl IntoIterator<Item = ServiceID>) -> Self {
+        self.consumed_gateways
+            .extend(services.into_iter().map(|service| ServiceRef::new(module, service.to_string())));
+        self
+    }
+
+    /// Whether `service` reaches a handler in this module.
+    fn handled(&self, service: &str) -> bool {
+        self.handler_services.iter().any(|s| s == service)
+            || (self.direct_closure && self.dependencies.provides.iter().any(|s| s == service))
+    }
+
+    /// Whether `protocol` reaches a handler in this module.
+    fn served(&self, protocol: Protocol) -> bool {
+        match protocol {
+            Protocol::Direct => self.direct_closure,
+            protocol => self.handler_protocols.contains(&protocol),
+        }
+    }
+
+    /// Every inconsistency, in descriptor order.
+    pub fn lint(&self) -> Vec<WiringIssue> {
+        let mut issues = Vec::new();
+        for service in &self.gateway_services {
+            if !self.handled(service) {
+                issues.push(WiringIssue::GatewayServiceUnhandled(service.clone()));
+            }
+        }
+        for &protocol in &self.gateway_protocols {
+            if !self.served(protocol) {
+                issues.push(WiringIssue::GatewayProtocolUnserved(protocol));
+            }
+        }
+        for service in &self.dependencies.provides {
+            if !self.handled(service) {
+                issues.push(WiringIssue::ProvidedServiceUnserved(service.clone()));
+            }
+        }
+        for service in &self.handler_services {
+            if !self.dependencies.provides.contains(service) {
+                issues.push(WiringIssue::HandlerServiceUndeclared(service.clone()));
+            }
+        }
+        for service in &self.dependencies.consumes {
+            if !self.consumed_gateways.contains(service) {
+                issues.push(WiringIssue::ConsumedServiceUnknown(service.clone()));
+            }
+        }
+        issues
+    }
+
+    /// Panics listing every inconsistency - for the wiring tests.
+    pub fn assert_consistent(&self) {
+        let issues = self.lint();
+        if !issues.is_empty() {
+            let lines: Vec<String> = issues.iter().map(|issue| format!("  - {}", issue)).collect();
+            panic!("wiring of module '{}' is inconsistent:\n{}", self.module_id, lines.join("\n"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_drifted_service_ids() {
+        let descriptor = WiringDescriptor::new("echo", ModuleDependencies::default().provides("service"))
+            .with_handler_services([ServiceID::from("service1")])
+            .with_gateway_services([ServiceID::from("service2")])
+            .with_consumed_gateways("echo", [ServiceID::from("service")]);
+
+        assert_eq!(descriptor.lint(), vec![
+            WiringIssue::GatewayServiceUnhandled("service2".to_string()),
+            WiringIssue::ProvidedServiceUnserved("service".to_string()),
+            WiringIssue::HandlerServiceUndeclared("service1".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_direct_closure_serves_declared_services_and_protocols() {
+        let dependencies = ModuleDependencies::default().provides("monitor").consumes("echo/events");
+        let descriptor = WiringDescriptor::new("echo-monitor", dependencies)
+            .with_direct_closure(true)
+            .with_gateway_services([ServiceID::from("monitor")])
+            .with_consumed_gateways("echo", [ServiceID::from("events")]);
+        descriptor.assert_consistent();
+
+        let descriptor = descriptor.with_direct_closure(false);
+        assert_eq!(descriptor.lint().len(), 2);
+
+        let descriptor = WiringDescriptor::new("echo", ModuleDependencies::default().provides("service"))
+            .with_handler_services([ServiceID::from("service")])
+            .with_handler_protocols([Protocol::Grpc])
+            .with_gateway_services([ServiceID::from("service")])
+            .with_gateway_protocols([Protocol::Direct, Protocol::Grpc, Protocol::Http]);
+        assert_eq!(descriptor.lint(), vec![
+            WiringIssue::GatewayProtocolUnserved(Protocol::Direct),
+            WiringIssue::GatewayProtocolUnserved(Protocol::Http),
+        ]);
+
+        let unknown = WiringDescriptor::new("echo-client", ModuleDependencies::default().consumes("echo/echo-service"))
+            .with_consumed_gateways("echo", [ServiceID::from("service")]);
+        assert_eq!(unknown.lint(), vec![WiringIssue::ConsumedServiceUnknown(ServiceRef::new("echo", "echo-service"))]);
+    }
+}
@@ -0,0 +1,156 @@
+//! Admin HTTP endpoint.
+//!
+//! # Architecture
+//!
+//! A small plain-HTTP server, separate from the protocol servers, for
+//! operating a running process:
+//!
+//! | Route              | Description                                    |
+//! |--------------------|------------------------------------------------|
+//! | `GET /log-level`   | Active log directives                          |
+//! | `PUT /log-level`   | Replace log directives (body: directive string)|
+//! | `GET /metrics`     | Prometheus metrics                             |
+//! | `GET /health`      | Module checks (self-test); 503 if any fails    |
+//! | `GET /info`        | Instance ID, version, git hash, uptime, features|
+//! | `GET /capabilities`| What each protocol of each gateway set supports|
+//! | `GET /maintenance` | Maintenance operations and background task runs|
+//! | `POST /maintenance/{module}/{op}` | Run a maintenance operation (body: its argument) |
+//! | `GET /quotas`      | Byte quota and bytes echoed per caller         |
+//! | `GET /debug/runtime` | tokio runtime metrics per assigned runtime   |
+//! | `GET /debug/tasks` | Live background tasks per module               |
+//! | `GET /debug/startup` | Module start levels, outcomes and times      |
+//! | `GET /debug/memory`| Heap stats (`jemalloc` feature)                |
+//!
+//! ```bash
+//! curl -X PUT --data 'info,echo_server=debug' http://localhost:9090/log-level
+//! curl -X POST http://localhost:9090/maintenance/echo/purge-history
+//! curl -X POST --data 'disk swap' http://localhost:9090/maintenance/echo/enter-maintenance
+//! ```
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use hsu_common::{Error, Result};
+use echo_api::{
+    AdaptiveConcurrencyMetrics, BatchingMetrics, ByteLedger, CapabilitiesRegistry, ChaosMetrics, CoalescingMetrics,
+    HealthRegistry, InfoRegistry, LatencyBudgetMetrics, MaintenanceRegistry, MirrorMetrics, PanicRegistry, PriorityMetrics, SizeMetrics,
+    StartupCoordinator, TrafficSplitMetrics, ValidationMetrics,
+};
+use tracing::{debug, info};
+
+use crate::diagnostics::{memory_report, runtime_report, tasks_report};
+use crate::logging::LogLevelHandle;
+
+/// State shared by all admin requests.
+#[derive(Clone)]
+pub struct AdminState {
+    /// Runtime log level control.
+    pub log_levels: LogLevelHandle,
+}
+
+/// Serves the admin endpoint on `addr` until the process exits.
+pub async fn serve_admin(addr: SocketAddr, state: AdminState) -> Result<()> {
+    let make_service = make_service_fn(move |_conn| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| handle(request, state.clone())))
+        }
+    });
+
+    let server = Server::try_bind(&addr)
+        .map_err(|e| Error::Protocol(format!("Failed to bind admin endpoint {}: {}", addr, e)))?
+        .serve(make_service);
+    info!("[Admin] ✅ Admin endpoint listening on http://{}", server.local_addr());
+
+    server.await.map_err(|e| Error::Protocol(format!("Admin endpoint failed: {}", e)))
+}
+
+/// Spawns [`serve_admin`] in the background.
+pub fn spawn_admin(addr: SocketAddr, state: AdminState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        if let Err(e) = serve_admin(addr, state).await {
+            tracing::error!("[Admin] {}", e);
+        }
+    })
+}
+
+async fn handle(request: Request<Body>, state: AdminState) -> std::result::Result<Response<Body>, Infallible> {
+    debug!("[Admin] {} {}", request.method(), request.uri().path());
+
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/log-level") => text(StatusCode::OK, state.log_levels.current()),
+        (&Method::PUT, "/log-level") => {
+            match hyper::body::to_bytes(request.into_body()).await {
+                Ok(body) => {
+                    let directives = String::from_utf8_lossy(&body);
+                    match state.log_levels.set_directives(directives.trim()) {
+                        Ok(()) => text(StatusCode::OK, state.log_levels.current()),
+                        Err(e) => text(StatusCode::BAD_REQUEST, e.to_string()),
+                    }
+                }
+                Err(e) => text(StatusCode::BAD_REQUEST, e.to_string()),
+            }
+        }
+        (&Method::GET, "/metrics") => {
+            let mut metrics = SizeMetrics::global().render_prometheus();
+            metrics.push_str(&PanicRegistry::global().render_prometheus());
+            metrics.push_str(&PriorityMetrics::global().render_prometheus());
+            metrics.push_str(&AdaptiveConcurrencyMetrics::global().render_prometheus());
+            metrics.push_str(&MaintenanceRegistry::global().render_prometheus());
+            metrics.push_str(&ByteLedger::global().render_prometheus());
+            metrics.push_str(&CoalescingMetrics::global().render_prometheus());
+            metrics.push_str(&BatchingMetrics::global().render_prometheus());
+            metrics.push_str(&ValidationMetrics::global().render_prometheus());
+            metrics.push_str(&TrafficSplitMetrics::global().render_prometheus());
+            metrics.push_str(&MirrorMetrics::global().render_prometheus());
+            metrics.push_str(&ChaosMetrics::global().render_prometheus());
+            metrics.push_str(&LatencyBudgetMetrics::global().render_prometheus());
+            text(StatusCode::OK, metrics)
+        }
+        (&Method::GET, "/health") => {
+            let health = HealthRegistry::global();
+            let status = if health.is_healthy() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+            text(status, health.render_text())
+        }
+        (&Method::GET, "/info") => text(StatusCode::OK, InfoRegistry::global().render_text().await),
+        (&Method::GET, "/capabilities") => text(StatusCode::OK, CapabilitiesRegistry::global().render_text()),
+        (&Method::GET, "/maintenance") => text(StatusCode::OK, MaintenanceRegistry::global().render_text()),
+        (&Method::POST, path) if path.starts_with("/maintenance/") => {
+            let path = path.to_string();
+            match hyper::body::to_bytes(request.into_body()).await {
+                Ok(body) => run_maintenance(&path, String::from_utf8_lossy(&body).trim().to_string()).await,
+                Err(e) => text(StatusCode::BAD_REQUEST, e.to_string()),
+            }
+        }
+        (&Method::GET, "/quotas") => text(StatusCode::OK, ByteLedger::global().render_text()),
+        (&Method::GET, "/debug/runtime") => text(StatusCode::OK, runtime_report()),
+        (&Method::GET, "/debug/tasks") => text(StatusCode::OK, tasks_report()),
+        (&Method::GET, "/debug/startup") => text(StatusCode::OK, StartupCoordinator::global().render_text()),
+        (&Method::GET, "/debug/memory") => text(StatusCode::OK, memory_report()),
+        _ => text(StatusCode::NOT_FOUND, "not found".to_string()),
+    };
+    Ok(response)
+}
+
+/// Runs the operation named by `/maintenance/{module}/{operation}` with
+/// the request body as its argument.
+async fn run_maintenance(path: &str, argument: String) -> Response<Body> {
+    let Some((module, operation)) = path.trim_start_matches("/maintenance/").split_once('/') else {
+        return text(StatusCode::NOT_FOUND, "expected /maintenance/{module}/{operation}".to_string());
+    };
+    match MaintenanceRegistry::global().run_operation(module, operation, argument).await {
+        Some(Ok(summary)) => {
+            info!("[Admin] Maintenance {}/{}: {}", module, operation, summary);
+            text(StatusCode::OK, summary)
+        }
+        Some(Err(e)) => text(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        None => text(StatusCode::NOT_FOUND, format!("no maintenance operation {}/{}", module, operation)),
+    }
+}
+
+fn text(status: StatusCode, body: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response
+}
@@ -0,0 +1,135 @@
+//! Command-line flags shared by all binaries.
+//!
+//! # Rust Learning Note
+//!
+//! `#[derive(clap::Args)]` makes a reusable **group** of flags. Each
+//! binary embeds it with `#[command(flatten)]`, so the flags are declared
+//! once here instead of in every `main.rs`.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use echo_api::{StartupCoordinator, DEFAULT_MODULE_START_TIMEOUT};
+use hsu_common::Result;
+use tracing::info;
+
+use crate::admin::{spawn_admin, AdminState};
+use crate::describe::ConfigDescription;
+use crate::logging::{init_logging, LogFileConfig, LoggingConfig, LogLevelHandle};
+use crate::panic_hook::install_panic_hook;
+use crate::runtimes::RuntimeLayout;
+
+/// Logging and admin flags.
+#[derive(clap::Args, Debug, Clone)]
+pub struct BootstrapArgs {
+    /// Log levels per target, e.g. "info,echo_server=debug,tonic=warn"
+    #[arg(long, default_value = "info")]
+    pub log: String,
+
+    /// Log line format: pretty, json or logfmt
+    #[arg(long, default_value = "pretty")]
+    pub log_format: String,
+
+    /// Write logs to this file instead of stdout
+    #[arg(long)]
+    pub log_file: Option<PathBuf>,
+
+    /// Write logs to stderr instead of stdout
+    #[arg(long, conflicts_with = "log_file")]
+    pub log_stderr: bool,
+
+    /// Log file rotation: never, hourly or daily
+    #[arg(long, default_value = "daily")]
+    pub log_rotation: String,
+
+    /// Also ship log events as JSON lines, tagged with trace and request
+    /// IDs, to udp://HOST:PORT, tcp://HOST:PORT or a file (see echo-logtail)
+    #[arg(long, value_name = "TARGET")]
+    pub log_ship: Option<String>,
+
+    /// Admin endpoint address, e.g. 127.0.0.1:9090 (disabled if omitted)
+    #[arg(long)]
+    pub admin_addr: Option<SocketAddr>,
+
+    /// Worker threads of the protocol (main) runtime (default: one per core)
+    #[arg(long)]
+    pub protocol_workers: Option<usize>,
+
+    /// Run module background tasks on a dedicated runtime with this many
+    /// worker threads (default: share the protocol runtime)
+    #[arg(long)]
+    pub module_workers: Option<usize>,
+
+    /// Also write the effective configuration (secrets redacted) to this file
+    #[arg(long, value_name = "PATH")]
+    pub config_dump: Option<PathBuf>,
+
+    /// Check the configuration, report every problem and exit without starting anything
+    #[arg(long)]
+    pub validate_config: bool,
+
+    /// Fail a module whose start takes longer than this (modules start
+    /// concurrently where their dependencies allow)
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_MODULE_START_TIMEOUT.as_secs())]
+    pub module_start_timeout_secs: u64,
+}
+
+impl BootstrapArgs {
+    /// Builds the runtime layout from the flags.
+    pub fn runtime_layout(&self) -> RuntimeLayout {
+        RuntimeLayout {
+            protocol_workers: self.protocol_workers,
+            module_workers: self.module_workers,
+        }
+    }
+
+    /// Builds the logging config from the flags.
+    pub fn logging_config(&self) -> Result<LoggingConfig> {
+        let mut config = self.log.parse::<LoggingConfig>()?
+            .with_format(self.log_format.parse()?);
+        if let Some(path) = &self.log_file {
+            config = config.with_file(LogFileConfig {
+                path: path.clone(),
+                rotation: self.log_rotation.parse()?,
+            });
+        }
+        if self.log_stderr {
+            config = config.with_stderr();
+        }
+        if let Some(target) = &self.log_ship {
+            config = config.with_ship(target.parse()?);
+        }
+        Ok(config)
+    }
+}
+
+/// Initializes logging, installs the panic hook, sets the module start
+/// timeout and starts the admin endpoint (if configured).
+///
+/// Call right after [`finish_validation`](crate::finish_validation),
+/// inside the tokio runtime (see [`Runtimes`](crate::Runtimes)).
+pub fn bootstrap(args: &BootstrapArgs) -> Result<LogLevelHandle> {
+    let log_levels = init_logging(&args.logging_config()?)?;
+    install_panic_hook();
+    StartupCoordinator::global().set_module_timeout(Duration::from_secs(args.module_start_timeout_secs));
+    if let Some(addr) = args.admin_addr {
+        spawn_admin(addr, AdminState { log_levels: log_levels.clone() });
+    }
+    Ok(log_levels)
+}
+
+/// Logs the startup banner with the effective configuration, and writes
+/// it to `--config-dump` if given.
+///
+/// Call once the configuration is complete, right before the runtime
+/// starts (see [`describe`](crate::describe)).
+pub fn announce_startup(args: &BootstrapArgs, description: &ConfigDescription) -> Result<()> {
+    for line in description.render().lines() {
+        info!("[Startup] {}", line);
+    }
+    if let Some(path) = &args.config_dump {
+        description.write_to(path)?;
+        info!("[Startup] Effective configuration written to {}", path.display());
+    }
+    Ok(())
+}
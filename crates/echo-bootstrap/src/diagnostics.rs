@@ -0,0 +1,176 @@
+//! Runtime, task and memory diagnostics.
+//!
+//! # Architecture
+//!
+//! Plain-text reports served by the admin endpoint:
+//!
+//! | Route             | Source                                           |
+//! |-------------------|--------------------------------------------------|
+//! | `/debug/runtime`  | tokio `RuntimeMetrics` per assigned runtime      |
+//! | `/debug/tasks`    | `echo_api::TaskRegistry` (per-module tasks)      |
+//! | `/debug/memory`   | jemalloc stats (`jemalloc` feature), resident set size (Linux) |
+//!
+//! [`DiagnosticsSample`] reads the numbers back from the reports - of
+//! this process, or fetched from another one's admin endpoint - so a
+//! soak test can watch them grow (`echo-bench soak`).
+
+use std::fmt::Write;
+use echo_api::{RuntimeAssignments, TaskRegistry};
+use tokio::runtime::Handle;
+
+/// Reports metrics of every assigned runtime (see `Runtimes`), or of the
+/// current runtime if the binary didn't assign any.
+pub fn runtime_report() -> String {
+    runtime_report_for(&RuntimeAssignments::global())
+}
+
+fn runtime_report_for(assignments: &RuntimeAssignments) -> String {
+    let assignments = assignments.list();
+    if assignments.is_empty() {
+        return handle_report(&Handle::current());
+    }
+    let mut out = String::new();
+    for assignment in assignments {
+        let _ = writeln!(out, "[{}] {}", assignment.role, assignment.name);
+        out.push_str(&handle_report(&assignment.handle));
+    }
+    out
+}
+
+fn handle_report(handle: &Handle) -> String {
+    let metrics = handle.metrics();
+    let mut out = String::new();
+    let _ = writeln!(out, "workers: {}", metrics.num_workers());
+    let _ = writeln!(out, "alive_tasks: {}", metrics.num_alive_tasks());
+    let _ = writeln!(out, "global_queue_depth: {}", metrics.global_queue_depth());
+    for worker in 0..metrics.num_workers() {
+        let _ = writeln!(out, "worker[{}]: busy={:?} parks={}",
+            worker,
+            metrics.worker_total_busy_duration(worker),
+            metrics.worker_park_count(worker));
+    }
+    out
+}
+
+/// Reports live background tasks, grouped by module.
+pub fn tasks_report() -> String {
+    let registry = TaskRegistry::global();
+    let mut out = String::new();
+    for (module, count) in registry.count_by_module() {
+        let _ = writeln!(out, "{}: {} task(s)", module, count);
+    }
+    for task in registry.tasks() {
+        let _ = writeln!(out, "  #{} {}/{} age={:?}", task.id, task.module, task.name, task.age());
+    }
+    if out.is_empty() {
+        out.push_str("no tracked tasks\n");
+    }
+    out
+}
+
+/// Reports heap statistics.
+#[cfg(feature = "jemalloc")]
+pub fn memory_report() -> String {
+    use tikv_jemalloc_ctl::{epoch, stats};
+
+    // Stats are cached; advancing the epoch refreshes them
+    if let Err(e) = epoch::advance() {
+        return format!("jemalloc stats unavailable: {}\n", e);
+    }
+    let mut out = String::new();
+    let _ = writeln!(out, "allocated: {}", stats::allocated::read().unwrap_or_default());
+    let _ = writeln!(out, "active: {}", stats::active::read().unwrap_or_default());
+    let _ = writeln!(out, "resident: {}", stats::resident::read().unwrap_or_default());
+    let _ = writeln!(out, "retained: {}", stats::retained::read().unwrap_or_default());
+    out
+}
+
+/// Reports heap statistics.
+#[cfg(not(feature = "jemalloc"))]
+pub fn memory_report() -> String {
+    let unavailable = "heap stats unavailable (build with --features jemalloc)\n";
+    match resident_bytes() {
+        Some(resident) => format!("resident: {}\n{}", resident, unavailable),
+        None => unavailable.to_string(),
+    }
+}
+
+/// Resident set size of this process, from `/proc` (Linux only).
+#[cfg(not(feature = "jemalloc"))]
+fn resident_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes = status.lines().find_map(|line| line.strip_prefix("VmRSS:"))?;
+    let kilobytes: u64 = kilobytes.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Task and memory figures read back from the reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiagnosticsSample {
+    /// Tasks alive on all runtimes (`/debug/runtime`).
+    pub alive_tasks: u64,
+    /// Background tasks tracked for the modules (`/debug/tasks`).
+    pub tracked_tasks: u64,
+    /// Heap allocated (jemalloc), else the resident set size; `None` if
+    /// the process reports neither.
+    pub memory_bytes: Option<u64>,
+}
+
+impl DiagnosticsSample {
+    /// Samples this process.
+    pub fn local() -> Self {
+        Self::parse(&runtime_report(), &tasks_report(), &memory_report())
+    }
+
+    /// Reads a sample from the texts of `/debug/runtime`, `/debug/tasks`
+    /// and `/debug/memory`.
+    pub fn parse(runtime: &str, tasks: &str, memory: &str) -> Self {
+        let value = |line: &str, key: &str| line.strip_prefix(key)?.trim().parse::<u64>().ok();
+        let memory_value = |key: &str| memory.lines().find_map(|line| value(line, key));
+        Self {
+            alive_tasks: runtime.lines().filter_map(|line| value(line, "alive_tasks:")).sum(),
+            // Module lines only: `module: N task(s)`; tasks are indented below them
+            tracked_tasks: tasks
+                .lines()
+                .filter(|line| !line.starts_with(' '))
+                .filter_map(|line| line.strip_suffix(" task(s)")?.rsplit_once(": ")?.1.parse::<u64>().ok())
+                .sum(),
+            memory_bytes: memory_value("allocated:").or_else(|| memory_value("resident:")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_api::RuntimeRole;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_runtime_report() {
+        let report = runtime_report_for(&RuntimeAssignments::new());
+        assert!(report.contains("workers: 2"));
+        assert!(report.contains("worker[1]"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_runtime_report_lists_assignments() {
+        let assignments = RuntimeAssignments::new();
+        assignments.assign(RuntimeRole::Modules, "echo-modules", Handle::current());
+
+        let report = runtime_report_for(&assignments);
+        assert!(report.starts_with("[modules] echo-modules\n"));
+        assert!(report.contains("workers: 2"));
+    }
+
+    #[test]
+    fn test_sample_parses_reports() {
+        let runtime = "[protocols] echo-protocols\nworkers: 2\nalive_tasks: 3\n[modules] echo-modules\nalive_tasks: 4\n";
+        let tasks = "echo: 2 task(s)\nmonitor: 1 task(s)\n  #1 echo/session-sweeper age=1s\n";
+        let memory = "allocated: 1000\nactive: 1200\nresident: 4096\n";
+        let sample = DiagnosticsSample::parse(runtime, tasks, memory);
+        assert_eq!(sample, DiagnosticsSample { alive_tasks: 7, tracked_tasks: 3, memory_bytes: Some(1000) });
+
+        let sample = DiagnosticsSample::parse("", "no tracked tasks\n", "heap stats unavailable\n");
+        assert_eq!(sample, DiagnosticsSample::default());
+    }
+}
@@ -0,0 +1,56 @@
+//! Echo Bootstrap - Shared Process Setup for Binaries
+//!
+//! # Architecture
+//!
+//! Everything a `main.rs` needs **before** `run_with_config`:
+//!
+//! 1. ✅ `init_logging` - Per-target, runtime-reloadable log levels
+//! 2. ✅ `spawn_admin` - Admin HTTP endpoint (log levels, metrics)
+//! 3. ✅ `BootstrapArgs` - Shared flags (`--log`, `--log-format`, `--log-file`, ...)
+//! 4. ✅ `install_panic_hook` - Panics logged with backtrace
+//! 5. ✅ `diagnostics` - Runtime/task/memory reports (admin `/debug/*`), `DiagnosticsSample` for soak tests
+//! 6. ✅ `PidFile` - PID file with single-instance locking
+//! 7. ✅ `parse_listen_addresses` - Multiple bind addresses, IPv6
+//! 8. ✅ `Runtimes` - Separate protocol and module runtimes
+//! 9. ✅ `spawn_inmem_registry` - In-memory service registry (`--registry inmem`)
+//! 10. ✅ `ConfigDescription` - Startup banner, effective config (`--config-dump`)
+//! 11. ✅ `ConfigCheck` - Cross-flag validation, all problems at once (`--validate-config`)
+//! 12. ✅ `load_secrets` - Keys from `NAME` / `NAME_FILE` variables, redacted in the banner
+//! 13. ✅ `ShippingLayer` - Log events with trace/request IDs to a collector (`--log-ship`)
+//!
+//! Keeping this out of the binaries means every binary gets the same
+//! flags and behavior, and each `main.rs` stays minimal.
+//!
+//! ## Golang Equivalent
+//!
+//! `pkg/logging/` plus the admin handlers in `cmd/`
+
+pub mod admin;
+pub mod args;
+pub mod describe;
+pub mod diagnostics;
+pub mod listen;
+pub mod log_shipping;
+pub mod logging;
+pub mod panic_hook;
+pub mod pid_file;
+pub mod registry;
+pub mod runtimes;
+pub mod secrets;
+pub mod validation;
+
+pub use admin::{AdminState, serve_admin, spawn_admin};
+pub use args::{BootstrapArgs, announce_startup, bootstrap};
+pub use describe::{ConfigDescription, Describe, parse_described, redact};
+pub use diagnostics::DiagnosticsSample;
+pub use panic_hook::install_panic_hook;
+pub use pid_file::PidFile;
+pub use listen::parse_listen_addresses;
+pub use log_shipping::{LogShipTarget, ShippedEvent, ShippingLayer};
+pub use registry::{serve_registry, spawn_inmem_registry};
+pub use runtimes::{RuntimeLayout, Runtimes};
+pub use secrets::load_secrets;
+pub use validation::{ConfigCheck, Validate, finish_validation};
+pub use logging::{
+    LoggingConfig, LogFormat, LogFileConfig, LogRotation, LogLevelHandle, init_logging,
+};
@@ -0,0 +1,83 @@
+//! Listen address parsing.
+//!
+//! Each `--listen` value becomes one protocol server, so a binary can bind
+//! several interfaces (IPv4 and IPv6) at once:
+//!
+//! ```bash
+//! echo-grpc-srv --listen 127.0.0.1:50051 --listen '[::1]:50051'
+//! echo-grpc-srv --listen '[::]'    # dual-stack, port from --port
+//! ```
+//!
+//! Note: on Linux `[::]` already accepts IPv4 connections (dual-stack),
+//! so binding `0.0.0.0:P` **and** `[::]:P` on the same port conflicts.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use hsu_common::{Error, Result};
+
+/// Parses listen addresses, defaulting to `0.0.0.0:default_port`.
+///
+/// Accepts `host:port`, `[v6]:port`, or a bare IP (uses `default_port`).
+pub fn parse_listen_addresses(addresses: &[String], default_port: u16) -> Result<Vec<SocketAddr>> {
+    if addresses.is_empty() {
+        return Ok(vec![SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), default_port)]);
+    }
+
+    let mut parsed: Vec<SocketAddr> = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        let address = address.trim();
+        let addr = match address.parse::<SocketAddr>() {
+            Ok(addr) => addr,
+            Err(_) => address
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .map(|ip| SocketAddr::new(ip, default_port))
+                .map_err(|_| Error::Validation {
+                    message: format!("Invalid listen address '{}'", address),
+                })?,
+        };
+
+        // Port 0 may repeat (each bind gets its own ephemeral port)
+        if addr.port() != 0 && parsed.contains(&addr) {
+            return Err(Error::Validation {
+                message: format!("Duplicate listen address '{}'", addr),
+            });
+        }
+        parsed.push(addr);
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_default_address() {
+        let addresses = parse_listen_addresses(&[], 50051).unwrap();
+        assert_eq!(addresses, vec!["0.0.0.0:50051".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_ipv4_and_ipv6() {
+        let addresses = parse_listen_addresses(
+            &strings(&["127.0.0.1:1234", "[::1]:1234", "[::]", "::1"]),
+            0,
+        ).unwrap();
+
+        assert_eq!(addresses[1], "[::1]:1234".parse().unwrap());
+        assert_eq!(addresses[2], "[::]:0".parse().unwrap());
+        assert_eq!(addresses[3], "[::1]:0".parse().unwrap());
+        assert_eq!(addresses[2].to_string(), "[::]:0");
+    }
+
+    #[test]
+    fn test_rejects_invalid_and_duplicate() {
+        assert!(parse_listen_addresses(&strings(&["localhost:80"]), 0).is_err());
+        assert!(parse_listen_addresses(&strings(&["[::1]:80", "[::1]:80"]), 0).is_err());
+    }
+}
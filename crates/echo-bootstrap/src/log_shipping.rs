@@ -0,0 +1,375 @@
+//! Log shipping to a collector (`--log-ship`).
+//!
+//! # Architecture
+//!
+//! ```text
+//! echo-grpc-cli ──┐                                   ┌─ RequestContext::current()
+//! echo-grpc-srv ──┤ ShippingLayer::on_event ──────────┤    trace_id, request_id
+//!                 │     ↓ one JSON line per event     └─ event and span fields, level, target
+//!                 │ bounded queue (full → dropped, never blocks the caller)
+//!                 │     ↓ writer thread
+//!                 └→ udp://collector:5140 | tcp://collector:5140 | events.ndjson
+//!                        ↓
+//!                    echo-logtail --request <id>   merged, ordered by timestamp
+//! ```
+//!
+//! The IDs come from the [`RequestContext`] the event is logged in: the
+//! client module traces every call, the `x-echo-trace` header carries the
+//! IDs to the server, and the server runs the call in the same context.
+//! Events outside a call have neither ID.
+//!
+//! Shipping is best effort: the queue drops events when the sink can't
+//! keep up, and a TCP collector that went away is reconnected at most
+//! once per second.
+//!
+//! # Rust Learning Note
+//!
+//! The writer runs on a plain thread, not a tokio task: logging starts
+//! before (and ends after) the runtimes, and the thread must never log
+//! itself - an event about a failed send would be shipped again.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use echo_contract::RequestContext;
+use hsu_common::{Error, Result};
+use serde_json::{json, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Events waiting for the writer; more are dropped.
+const QUEUE_CAPACITY: usize = 4096;
+
+/// Minimum time between two connection attempts to a TCP collector.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where shipped events go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogShipTarget {
+    /// One datagram per event (`udp://host:port`).
+    Udp(String),
+    /// Newline-delimited events on a connection (`tcp://host:port`).
+    Tcp(String),
+    /// Newline-delimited events appended to a file (any other value).
+    File(PathBuf),
+}
+
+impl FromStr for LogShipTarget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let target = match s.split_once("://") {
+            Some(("udp", address)) => LogShipTarget::Udp(address.to_string()),
+            Some(("tcp", address)) => LogShipTarget::Tcp(address.to_string()),
+            Some(("file", path)) => LogShipTarget::File(PathBuf::from(path)),
+            Some((scheme, _)) => {
+                return Err(Error::Validation {
+                    message: format!("Unknown log ship scheme '{}' (expected udp://, tcp:// or a file path)", scheme),
+                })
+            }
+            None => LogShipTarget::File(PathBuf::from(s)),
+        };
+        match &target {
+            LogShipTarget::Udp(address) | LogShipTarget::Tcp(address) if !address.contains(':') => {
+                Err(Error::Validation { message: format!("Log ship address '{}' has no port", address) })
+            }
+            _ => Ok(target),
+        }
+    }
+}
+
+impl fmt::Display for LogShipTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogShipTarget::Udp(address) => write!(f, "udp://{}", address),
+            LogShipTarget::Tcp(address) => write!(f, "tcp://{}", address),
+            LogShipTarget::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// One shipped log event (a JSON line on the wire).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShippedEvent {
+    /// When it was logged, Unix milliseconds.
+    pub timestamp_ms: u64,
+    /// Name of the process (binary) that logged it.
+    pub process: String,
+    /// Its process ID.
+    pub pid: u32,
+    /// `ERROR` ... `TRACE`.
+    pub level: String,
+    /// Module path of the event.
+    pub target: String,
+    /// The formatted message.
+    pub message: String,
+    /// Client operation the event belongs to.
+    pub trace_id: Option<String>,
+    /// Call the event belongs to.
+    pub request_id: Option<String>,
+    /// Other fields of the event.
+    pub fields: BTreeMap<String, String>,
+}
+
+impl ShippedEvent {
+    /// Encodes the event as a JSON object.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "ts_ms": self.timestamp_ms,
+            "process": self.process,
+            "pid": self.pid,
+            "level": self.level,
+            "target": self.target,
+            "message": self.message,
+            "trace_id": self.trace_id,
+            "request_id": self.request_id,
+            "fields": self.fields,
+        })
+    }
+
+    /// Decodes a shipped JSON line (`None` if it isn't one).
+    pub fn from_json(line: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        let text = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+        Some(Self {
+            timestamp_ms: value.get("ts_ms")?.as_u64()?,
+            process: text("process").unwrap_or_default(),
+            pid: value.get("pid").and_then(Value::as_u64).unwrap_or_default() as u32,
+            level: text("level").unwrap_or_default(),
+            target: text("target").unwrap_or_default(),
+            message: text("message").unwrap_or_default(),
+            trace_id: text("trace_id"),
+            request_id: text("request_id"),
+            fields: value
+                .get("fields")
+                .and_then(Value::as_object)
+                .map(|fields| fields.iter().map(|(k, v)| (k.clone(), plain(v))).collect())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// A JSON value as text, strings without quotes.
+fn plain(value: &Value) -> String {
+    value.as_str().map_or_else(|| value.to_string(), str::to_string)
+}
+
+/// Ships every event that passes the log filter to a [`LogShipTarget`].
+pub struct ShippingLayer {
+    process: String,
+    queue: SyncSender<String>,
+}
+
+impl ShippingLayer {
+    /// Starts the writer thread for `target`, naming events after `process`.
+    pub fn new(target: &LogShipTarget, process: impl Into<String>) -> Result<Self> {
+        let sink = Sink::open(target)?;
+        let (queue, events) = mpsc::sync_channel(QUEUE_CAPACITY);
+        std::thread::Builder::new()
+            .name("log-shipper".to_string())
+            .spawn(move || sink.run(events))
+            .map_err(|e| Error::Validation { message: format!("Log shipper thread: {}", e) })?;
+        Ok(Self::with_queue(process, queue))
+    }
+
+    fn with_queue(process: impl Into<String>, queue: SyncSender<String>) -> Self {
+        Self { process: process.into(), queue }
+    }
+}
+
+impl<S> Layer<S> for ShippingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(visitor);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(visitor) = span.extensions_mut().get_mut::<FieldVisitor>() {
+                values.record(visitor);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        // Span fields outermost first, so inner spans and the event win
+        let mut visitor = FieldVisitor::default();
+        for span in ctx.event_scope(event).into_iter().flat_map(|scope| scope.from_root()) {
+            if let Some(span_fields) = span.extensions().get::<FieldVisitor>() {
+                visitor.fields.extend(span_fields.fields.clone());
+            }
+        }
+        event.record(&mut visitor);
+        let mut fields = visitor.fields;
+        let context = RequestContext::current();
+
+        let shipped = ShippedEvent {
+            timestamp_ms: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+            process: self.process.clone(),
+            pid: std::process::id(),
+            level: event.metadata().level().to_string(),
+            target: event.metadata().target().to_string(),
+            message: fields.remove("message").unwrap_or_default(),
+            // An explicit field wins over the call's context
+            trace_id: fields.remove("trace_id").or(context.trace_id),
+            request_id: fields.remove("request_id").or(context.request_id),
+            fields,
+        };
+        // Full queue: drop rather than block the logging task
+        let _ = self.queue.try_send(shipped.to_json().to_string());
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.fields.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+/// The writing end of a [`LogShipTarget`].
+enum Sink {
+    Udp { socket: UdpSocket, address: String },
+    Tcp { address: String, stream: Option<TcpStream>, last_attempt: Option<Instant> },
+    File(File),
+}
+
+impl Sink {
+    fn open(target: &LogShipTarget) -> Result<Self> {
+        let invalid = |e: std::io::Error| Error::Validation { message: format!("Log ship target {}: {}", target, e) };
+        match target {
+            LogShipTarget::Udp(address) => {
+                let remote = address.to_socket_addrs().map_err(invalid)?.next().ok_or_else(|| Error::Validation {
+                    message: format!("Log ship address '{}' doesn't resolve", address),
+                })?;
+                let local = if remote.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(local).map_err(invalid)?;
+                Ok(Sink::Udp { socket, address: address.clone() })
+            }
+            LogShipTarget::Tcp(address) => Ok(Sink::Tcp { address: address.clone(), stream: None, last_attempt: None }),
+            LogShipTarget::File(path) => {
+                let file = OpenOptions::new().create(true).append(true).open(path).map_err(invalid)?;
+                Ok(Sink::File(file))
+            }
+        }
+    }
+
+    /// Writes queued lines until every [`ShippingLayer`] is gone.
+    fn run(mut self, events: Receiver<String>) {
+        for line in events {
+            // Nowhere to report a failure without logging it: the event is lost
+            let _ = self.write(&line);
+        }
+    }
+
+    fn write(&mut self, line: &str) -> std::io::Result<()> {
+        match self {
+            Sink::Udp { socket, address } => socket.send_to(line.as_bytes(), address.as_str()).map(drop),
+            Sink::Tcp { address, stream, last_attempt } => {
+                if stream.is_none() {
+                    if last_attempt.is_some_and(|at| at.elapsed() < RECONNECT_INTERVAL) {
+                        return Ok(());
+                    }
+                    *last_attempt = Some(Instant::now());
+                    *stream = Some(TcpStream::connect(address.as_str())?);
+                }
+                let result = stream.as_mut().map_or(Ok(()), |stream| writeln!(stream, "{}", line));
+                if result.is_err() {
+                    *stream = None;
+                }
+                result
+            }
+            Sink::File(file) => writeln!(file, "{}", line),
+        }
+    }
+}
+
+/// Name of the running binary, for [`ShippedEvent::process`].
+pub fn process_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| path.file_stem().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "echo".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!("udp://collector:5140".parse::<LogShipTarget>().unwrap(), LogShipTarget::Udp("collector:5140".into()));
+        assert_eq!("tcp://[::1]:5140".parse::<LogShipTarget>().unwrap(), LogShipTarget::Tcp("[::1]:5140".into()));
+        assert_eq!("logs/events.ndjson".parse::<LogShipTarget>().unwrap(), LogShipTarget::File("logs/events.ndjson".into()));
+        assert!("udp://collector".parse::<LogShipTarget>().is_err());
+        assert!("kafka://broker:9092".parse::<LogShipTarget>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_events_carry_the_call_ids() {
+        let (queue, events) = mpsc::sync_channel(8);
+        let subscriber = tracing_subscriber::registry().with(ShippingLayer::with_queue("echo-test", queue));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        tracing::info!(bytes = 5, "outside a call");
+        RequestContext::default()
+            .with_trace("4bf92f35", "7d1c")
+            .scope(async { tracing::warn!("[EchoClient] Response: hello") })
+            .await;
+
+        let outside = ShippedEvent::from_json(&events.recv().unwrap()).unwrap();
+        assert_eq!((outside.message.as_str(), outside.trace_id), ("outside a call", None));
+        assert_eq!(outside.fields["bytes"], "5");
+
+        let inside = ShippedEvent::from_json(&events.recv().unwrap()).unwrap();
+        assert_eq!(inside.process, "echo-test");
+        assert_eq!(inside.level, "WARN");
+        assert_eq!(inside.trace_id.as_deref(), Some("4bf92f35"));
+        assert_eq!(inside.request_id.as_deref(), Some("7d1c"));
+        assert_eq!(ShippedEvent::from_json(&inside.to_json().to_string()), Some(inside));
+    }
+
+    #[test]
+    fn test_events_carry_their_span_fields() {
+        let (queue, events) = mpsc::sync_channel(8);
+        let subscriber = tracing_subscriber::registry().with(ShippingLayer::with_queue("echo-test", queue));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let call = tracing::info_span!("call", method = "echo", attempt = 1, peer = tracing::field::Empty);
+        call.record("peer", "10.0.0.1:50051");
+        let _call = call.entered();
+        let _retry = tracing::info_span!("retry", attempt = 2).entered();
+        tracing::info!(bytes = 5, "sent");
+
+        let event = ShippedEvent::from_json(&events.recv().unwrap()).unwrap();
+        assert_eq!(event.fields["method"], "echo");
+        assert_eq!(event.fields["peer"], "10.0.0.1:50051");
+        assert_eq!(event.fields["attempt"], "2");
+        assert_eq!(event.fields["bytes"], "5");
+    }
+}
@@ -0,0 +1,443 @@
+//! Logging facade with per-target levels.
+//!
+//! # Architecture
+//!
+//! Instead of one global level (`tracing_subscriber::fmt::init()`), each
+//! module/crate target gets its own level:
+//!
+//! ```text
+//! --log "info,echo_server=debug,echo_client=info,tonic=warn"
+//!     ↓ parsed into
+//! LoggingConfig { default_level: "info", targets: {echo_server: debug, ...} }
+//!     ↓ installed as
+//! reload::Layer<EnvFilter>  ←── LogLevelHandle::set_directives() (admin endpoint)
+//!     ↓ events formatted as
+//! pretty | json | logfmt   →   stdout or rotating log file
+//!     ↓ and, with --log-ship (see crate::log_shipping)
+//! ShippingLayer            →   JSON lines with trace/request IDs to a collector
+//! ```
+//!
+//! # Rust Learning Note
+//!
+//! `tracing_subscriber::reload` wraps a layer so it can be **swapped** at
+//! runtime through a handle - no restart needed to turn on debug logs.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use hsu_common::{Error, Result};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{fmt as tracing_fmt, reload, EnvFilter, Layer, Registry};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::log_shipping::{process_name, LogShipTarget, ShippingLayer};
+
+/// Log line format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines (the `tracing_subscriber` default).
+    #[default]
+    Pretty,
+    /// One JSON object per line.
+    Json,
+    /// `key=value` pairs per line.
+    Logfmt,
+}
+
+impl FromStr for LogFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            "logfmt" => Ok(LogFormat::Logfmt),
+            other => Err(Error::Validation {
+                message: format!("Unknown log format '{}' (expected pretty, json or logfmt)", other),
+            }),
+        }
+    }
+}
+
+/// How often the log file is rotated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogRotation {
+    Never,
+    Hourly,
+    #[default]
+    Daily,
+}
+
+impl FromStr for LogRotation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "never" => Ok(LogRotation::Never),
+            "hourly" => Ok(LogRotation::Hourly),
+            "daily" => Ok(LogRotation::Daily),
+            other => Err(Error::Validation {
+                message: format!("Unknown log rotation '{}' (expected never, hourly or daily)", other),
+            }),
+        }
+    }
+}
+
+/// Log file output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFileConfig {
+    /// Log file path; rotated files get a date suffix (e.g. `echo.log.2024-01-31`).
+    pub path: PathBuf,
+    /// Rotation period.
+    pub rotation: LogRotation,
+}
+
+impl LogFileConfig {
+    fn appender(&self) -> Result<RollingFileAppender> {
+        let file_name = self.path.file_name().ok_or_else(|| Error::Validation {
+            message: format!("Log file path '{}' has no file name", self.path.display()),
+        })?;
+        let directory = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let rotation = match self.rotation {
+            LogRotation::Never => Rotation::NEVER,
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+        };
+        Ok(RollingFileAppender::new(rotation, directory, file_name))
+    }
+}
+
+/// Log levels and output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggingConfig {
+    /// Level for targets without an explicit entry.
+    pub default_level: String,
+    /// Level per module/crate target (e.g. `echo_server` → `debug`).
+    pub targets: BTreeMap<String, String>,
+    /// Line format.
+    pub format: LogFormat,
+    /// Write to a rotating file instead of stdout.
+    pub file: Option<LogFileConfig>,
+    /// Write to stderr instead of stdout (without a file).
+    pub stderr: bool,
+    /// Also ship every event to a collector.
+    pub ship: Option<LogShipTarget>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            default_level: "info".to_string(),
+            targets: BTreeMap::new(),
+            format: LogFormat::default(),
+            file: None,
+            stderr: false,
+            ship: None,
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Sets the level for one target.
+    pub fn with_target(mut self, target: impl Into<String>, level: impl Into<String>) -> Self {
+        self.targets.insert(target.into(), level.into());
+        self
+    }
+
+    /// Sets the line format.
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Writes logs to a rotating file instead of stdout.
+    pub fn with_file(mut self, file: LogFileConfig) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// Writes logs to stderr instead of stdout, leaving stdout to the
+    /// program (e.g. the pipe transport of a managed unit).
+    pub fn with_stderr(mut self) -> Self {
+        self.stderr = true;
+        self
+    }
+
+    /// Also ships every event to `target` (see [`crate::log_shipping`]).
+    pub fn with_ship(mut self, target: LogShipTarget) -> Self {
+        self.ship = Some(target);
+        self
+    }
+
+    /// Returns the config as an `EnvFilter` directive string.
+    pub fn directives(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Formats the level directives (format, file and shipping are not included).
+impl fmt::Display for LoggingConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.default_level)?;
+        for (target, level) in &self.targets {
+            write!(f, ",{}={}", target, level)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses level directives, e.g. `"info,echo_server=debug,tonic=warn"`.
+///
+/// Format, file output and shipping keep their defaults.
+impl FromStr for LoggingConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut config = LoggingConfig::default();
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    config.targets.insert(target.to_string(), level.to_string());
+                }
+                None => config.default_level = directive.to_string(),
+            }
+        }
+        // Reject what EnvFilter would reject
+        parse_filter(&config.directives())?;
+        Ok(config)
+    }
+}
+
+fn parse_filter(directives: &str) -> Result<EnvFilter> {
+    EnvFilter::try_new(directives).map_err(|e| Error::Validation {
+        message: format!("Invalid log directives '{}': {}", directives, e),
+    })
+}
+
+/// Handle for changing log levels at runtime.
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    handle: reload::Handle<EnvFilter, Registry>,
+}
+
+impl LogLevelHandle {
+    /// Replaces the active filter with `directives`.
+    pub fn set_directives(&self, directives: &str) -> Result<()> {
+        let filter = parse_filter(directives)?;
+        self.handle.reload(filter).map_err(|e| Error::Protocol(
+            format!("Failed to reload log filter: {}", e),
+        ))?;
+        tracing::info!("[Logging] ✅ Log levels set to '{}'", directives);
+        Ok(())
+    }
+
+    /// Returns the active filter as a directive string.
+    pub fn current(&self) -> String {
+        self.handle
+            .with_current(|filter| filter.to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Installs the global subscriber with reloadable per-target levels.
+///
+/// Replaces `tracing_subscriber::fmt::init()` in the binaries.
+pub fn init_logging(config: &LoggingConfig) -> Result<LogLevelHandle> {
+    let (filter, handle) = reload::Layer::new(parse_filter(&config.directives())?);
+
+    let output = match &config.file {
+        // No ANSI colors in files
+        Some(file) => fmt_layer(config.format, file.appender()?, false),
+        None if config.stderr => fmt_layer(config.format, std::io::stderr, true),
+        None => fmt_layer(config.format, std::io::stdout, true),
+    };
+    let shipping = match &config.ship {
+        Some(target) => Some(ShippingLayer::new(target, process_name())?),
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(output)
+        .with(shipping)
+        .try_init()
+        .map_err(|e| Error::Validation {
+            message: format!("Logging already initialized: {}", e),
+        })?;
+
+    Ok(LogLevelHandle { handle })
+}
+
+/// Builds the formatting layer for `format`.
+///
+/// # Rust Learning Note
+///
+/// Each format produces a **different** layer type. `.boxed()` erases
+/// them to one `Box<dyn Layer<S>>`, so a runtime choice is possible.
+fn fmt_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_fmt::layer().with_writer(writer).with_ansi(ansi);
+    match format {
+        LogFormat::Pretty => layer.boxed(),
+        LogFormat::Json => layer.json().boxed(),
+        LogFormat::Logfmt => layer.event_format(LogfmtFormat).boxed(),
+    }
+}
+
+/// Formats events as logfmt: `ts=... level=info target=echo_server msg="..."`,
+/// followed by the fields of the spans the event is in, outermost first.
+struct LogfmtFormat;
+
+impl<S, N> FormatEvent<S, N> for LogfmtFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let metadata = event.metadata();
+        write!(writer, "ts=")?;
+        SystemTime.format_time(&mut writer)?;
+        write!(writer, " level={} target={}",
+            metadata.level().as_str().to_lowercase(), logfmt_value(metadata.target()))?;
+
+        let mut visitor = LogfmtVisitor { writer: &mut writer, result: Ok(()) };
+        event.record(&mut visitor);
+        visitor.result?;
+
+        for span in ctx.event_scope().into_iter().flat_map(|scope| scope.from_root()) {
+            let extensions = span.extensions();
+            if let Some(fields) = extensions.get::<FormattedFields<N>>().filter(|fields| !fields.is_empty()) {
+                write!(writer, " {}", fields)?;
+            }
+        }
+
+        writeln!(writer)
+    }
+}
+
+struct LogfmtVisitor<'a, 'w> {
+    writer: &'a mut Writer<'w>,
+    result: fmt::Result,
+}
+
+impl Visit for LogfmtVisitor<'_, '_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, &format!("{:?}", value));
+    }
+}
+
+impl LogfmtVisitor<'_, '_> {
+    fn record(&mut self, field: &Field, value: &str) {
+        if self.result.is_err() {
+            return;
+        }
+        let key = match field.name() {
+            "message" => "msg",
+            name => name,
+        };
+        self.result = write!(self.writer, " {}={}", key, logfmt_value(value));
+    }
+}
+
+/// Quotes `value` if logfmt requires it.
+fn logfmt_value(value: &str) -> String {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '=' || c == '"') {
+        format!("{:?}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_per_target_levels() {
+        let config: LoggingConfig = "info,echo_server=debug,tonic=warn".parse().unwrap();
+
+        assert_eq!(config.default_level, "info");
+        assert_eq!(config.targets.get("echo_server").map(String::as_str), Some("debug"));
+        assert_eq!(config.targets.get("tonic").map(String::as_str), Some("warn"));
+    }
+
+    #[test]
+    fn test_directives_round_trip() {
+        let config = LoggingConfig::default()
+            .with_target("tonic", "warn")
+            .with_target("echo_server", "debug");
+
+        assert_eq!(config.directives(), "info,echo_server=debug,tonic=warn");
+        assert_eq!(config.directives().parse::<LoggingConfig>().unwrap(), config);
+    }
+
+    #[test]
+    fn test_rejects_invalid_level() {
+        assert!("echo_server=loud".parse::<LoggingConfig>().is_err());
+    }
+
+    #[test]
+    fn test_parse_format_and_rotation() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("logfmt".parse::<LogFormat>().unwrap(), LogFormat::Logfmt);
+        assert!("xml".parse::<LogFormat>().is_err());
+        assert_eq!("hourly".parse::<LogRotation>().unwrap(), LogRotation::Hourly);
+    }
+
+    #[test]
+    fn test_logfmt_quoting() {
+        assert_eq!(logfmt_value("echo_server"), "echo_server");
+        assert_eq!(logfmt_value("Hello world"), "\"Hello world\"");
+        assert_eq!(logfmt_value("a=b"), "\"a=b\"");
+        assert_eq!(logfmt_value(""), "\"\"");
+    }
+
+    /// Collects everything written, for asserting on formatted lines.
+    #[derive(Clone, Default)]
+    struct Captured(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_logfmt_renders_span_fields() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = Registry::default().with(fmt_layer(LogFormat::Logfmt, move || writer.clone(), false));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _call = tracing::info_span!("call", method = "echo").entered();
+            let _attempt = tracing::info_span!("attempt", attempt = 2).entered();
+            tracing::info!("sent");
+        });
+
+        let line = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(line.ends_with(" msg=sent method=\"echo\" attempt=2\n"), "{}", line);
+    }
+}
@@ -0,0 +1,24 @@
+//! Panic hook that reports panics through `tracing`.
+//!
+//! # Rust Learning Note
+//!
+//! The default hook prints to stderr, bypassing the log format and log
+//! file. This hook logs the panic (with a backtrace) as a regular error
+//! event instead. Panics are still caught and converted to errors by
+//! `echo_api::catch_panic` - the hook only **reports** them.
+
+use std::backtrace::Backtrace;
+use tracing::error;
+
+/// Replaces the default panic hook with one that logs via `tracing`.
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let thread = std::thread::current();
+        error!(
+            "[Panic] thread '{}' {}\n{}",
+            thread.name().unwrap_or("<unnamed>"),
+            info,
+            Backtrace::force_capture(),
+        );
+    }));
+}
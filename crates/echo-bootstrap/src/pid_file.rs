@@ -0,0 +1,145 @@
+//! PID file with single-instance locking.
+//!
+//! # Architecture
+//!
+//! ```text
+//! echo-grpc-srv --pid-file /run/echo.pid
+//!     ↓ PidFile::acquire
+//! open (create) → try_lock (exclusive, non-blocking)
+//!     ├─ locked by another process → Err("already running (pid 1234)")
+//!     └─ acquired → write our PID, hold the lock until drop
+//!                      ↓ graceful shutdown
+//!                   remove file, release lock
+//! ```
+//!
+//! # Rust Learning Note
+//!
+//! The lock is **advisory** and held by the open file, so the OS releases
+//! it even if the process crashes. A stale PID file left behind by a crash
+//! therefore doesn't block the next start - only a live process does.
+//!
+//! `File::try_lock` only exists since Rust 1.89, so the lock is taken with
+//! `flock(2)` directly. Other platforms write the file without locking it.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use hsu_common::{Error, Result};
+use tracing::{debug, warn};
+
+/// A locked PID file, removed on drop.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+    /// Never read: holding the descriptor open is what holds the lock.
+    _file: File,
+}
+
+impl PidFile {
+    /// Creates and locks `path`, writing the current process ID.
+    ///
+    /// Fails if another process holds the lock.
+    pub fn acquire(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        // A file we can't create or write is an I/O failure; only another
+        // instance holding the lock is the caller's to resolve
+        let io_error = |e: std::io::Error| Error::Protocol(format!("PID file {}: {}", path.display(), e));
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(io_error)?;
+
+        if !try_lock(&file).map_err(io_error)? {
+            let mut owner = String::new();
+            let _ = file.read_to_string(&mut owner);
+            return Err(Error::Validation {
+                message: format!("Another instance is already running (pid {}, lock held on {})",
+                    owner.trim(), path.display()),
+            });
+        }
+
+        file.set_len(0).map_err(io_error)?;
+        file.rewind().map_err(io_error)?;
+        writeln!(file, "{}", std::process::id()).map_err(io_error)?;
+        file.flush().map_err(io_error)?;
+
+        debug!("[PidFile] Locked {} (pid {})", path.display(), std::process::id());
+        Ok(Self { path, _file: file })
+    }
+
+    /// Returns the PID file path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // Remove while still holding the lock, so no other instance can
+        // lock the file we are about to delete. The lock goes with the
+        // descriptor, when `_file` is dropped after this.
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("[PidFile] Failed to remove {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Takes an exclusive, non-blocking lock on `file`; `false` if another
+/// process holds it.
+#[cfg(unix)]
+fn try_lock(file: &File) -> io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: flock only reads the descriptor, which `file` keeps open
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    let e = io::Error::last_os_error();
+    match e.kind() {
+        io::ErrorKind::WouldBlock => Ok(false),
+        _ => Err(e),
+    }
+}
+
+#[cfg(not(unix))]
+fn try_lock(_file: &File) -> io::Result<bool> {
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_pid_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("echo-bootstrap-{}-{}.pid", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_second_instance_is_refused() {
+        let path = temp_pid_path("second");
+
+        let first = PidFile::acquire(&path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.trim(), std::process::id().to_string());
+
+        let error = PidFile::acquire(&path).unwrap_err();
+        assert!(error.to_string().contains("already running"));
+
+        drop(first);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_stale_file_is_reused() {
+        let path = temp_pid_path("stale");
+        std::fs::write(&path, "999999\n").unwrap();
+
+        let pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(pid_file.path()).unwrap().trim(),
+            std::process::id().to_string());
+    }
+}
@@ -0,0 +1,126 @@
+//! Service-registry server for the in-memory store.
+//!
+//! # Architecture
+//!
+//! Serves [`InMemoryServiceRegistry`] with the framework's registry server
+//! (`hsu_service_registry::RegistryServer`), so the runtime's registry
+//! client (publishing) and the client gateways (discovering) use it like
+//! the external `hsu-registry` - same routes, same payloads:
+//!
+//! ```text
+//! ServiceRegistryClient ──HTTP──→ RegistryServer ──→ InMemoryServiceRegistry
+//!                                 (hsu-service-registry)  (echo-api, ServiceRegistry impl)
+//! ```
+//!
+//! With `--registry inmem` each binary calls [`spawn_inmem_registry`]
+//! with its registry URL. Whichever process starts first hosts the
+//! registry; the others find the port taken and use it:
+//!
+//! ```bash
+//! cargo run --bin echo-grpc-srv -- --registry inmem   # hosts :8080
+//! cargo run --bin echo-grpc-cli -- --registry inmem   # uses it
+//! ```
+//!
+//! The registry lives as long as the hosting process - fine for local
+//! development, not a replacement for a real registry. The `echo-registry`
+//! binary serves it standalone, optionally persisted to a file.
+
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::sync::Arc;
+use hsu_common::{Error, Result};
+use hsu_service_registry::RegistryServer;
+use echo_api::InMemoryServiceRegistry;
+use tracing::info;
+
+/// Serves `registry` on `listener` until the process exits.
+pub async fn serve_registry(listener: TcpListener, registry: Arc<InMemoryServiceRegistry>) -> Result<()> {
+    listener.set_nonblocking(true)
+        .map_err(|e| Error::Protocol(format!("Failed to configure registry listener: {}", e)))?;
+    let listener = tokio::net::TcpListener::from_std(listener)
+        .map_err(|e| Error::Protocol(format!("Failed to start registry: {}", e)))?;
+    let address = listener.local_addr()
+        .map_err(|e| Error::Protocol(format!("Failed to start registry: {}", e)))?;
+    info!("[Registry] ✅ In-memory service registry listening on http://{}", address);
+
+    RegistryServer::new(registry).serve(listener).await
+}
+
+/// Hosts the process-wide in-memory registry at `url` (e.g.
+/// `http://localhost:8080`) in the background.
+///
+/// Returns `None` if the address is already taken - another process
+/// hosts the registry and this one uses it through `url`.
+pub fn spawn_inmem_registry(url: &str) -> Result<Option<tokio::task::JoinHandle<()>>> {
+    let addr = registry_addr(url)?;
+    let listener = match TcpListener::bind(addr) {
+        Ok(listener) => listener,
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+            info!("[Registry] {} is taken, using the registry hosted there", addr);
+            return Ok(None);
+        }
+        Err(e) => return Err(Error::Protocol(format!("Failed to bind registry {}: {}", addr, e))),
+    };
+
+    let registry = InMemoryServiceRegistry::global();
+    Ok(Some(tokio::spawn(async move {
+        if let Err(e) = serve_registry(listener, registry).await {
+            tracing::error!("[Registry] {}", e);
+        }
+    })))
+}
+
+/// Resolves the host and port of a registry URL.
+fn registry_addr(url: &str) -> Result<SocketAddr> {
+    let invalid = || Error::Validation { message: format!("Invalid registry URL '{}'", url) };
+    let authority = url
+        .strip_prefix("http://")
+        .unwrap_or(url)
+        .split('/')
+        .next()
+        .filter(|authority| !authority.is_empty())
+        .ok_or_else(invalid)?;
+    let authority = if authority.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+    authority.to_socket_addrs().map_err(|_| invalid())?.next().ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hsu_common::{ModuleID, Protocol, ServiceID};
+    use hsu_service_registry::{RemoteAPI, ServiceRegistry, ServiceRegistryClient};
+
+    #[test]
+    fn test_registry_addr() {
+        assert_eq!(registry_addr("http://127.0.0.1:8080").unwrap(), "127.0.0.1:8080".parse().unwrap());
+        assert_eq!(registry_addr("http://127.0.0.1:9000/api").unwrap().port(), 9000);
+        assert_eq!(registry_addr("127.0.0.1").unwrap().port(), 80);
+        assert!(registry_addr("http://").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_framework_client_publishes_and_discovers() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let registry = Arc::new(InMemoryServiceRegistry::new());
+        tokio::spawn(serve_registry(listener, registry.clone()));
+
+        let client = ServiceRegistryClient::new(&url).unwrap();
+        let api = RemoteAPI {
+            service_ids: vec![ServiceID::from("service")],
+            protocol: Protocol::Grpc,
+            address: "localhost:50051".to_string(),
+            metadata: Default::default(),
+        };
+        client.publish(&ModuleID::from("echo"), 7, vec![api]).await.unwrap();
+
+        // The store behind the server has it, and the client finds it again
+        assert_eq!(registry.discover("echo").await.unwrap()[0].address, "localhost:50051");
+        let found = client.discover(&ModuleID::from("echo")).await.unwrap();
+        assert_eq!(found[0].address, "localhost:50051");
+        assert!(client.discover(&ModuleID::from("other")).await.is_err());
+    }
+}
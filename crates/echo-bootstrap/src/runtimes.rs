@@ -0,0 +1,111 @@
+//! Runtime layout: protocol vs module runtimes.
+//!
+//! # Architecture
+//!
+//! `#[tokio::main]` gives a binary one runtime for everything. With
+//! `--module-workers` the binary gets a second, dedicated runtime for
+//! module background tasks:
+//!
+//! ```text
+//! main()
+//!     ↓ Runtimes::build(layout)
+//! "echo-protocol" (--protocol-workers)  ──block_on──→ run_with_config, gRPC
+//! "echo-modules"  (--module-workers)    ←── TaskRegistry::spawn
+//! ```
+//!
+//! Both are recorded in `echo_api::RuntimeAssignments`, which the admin
+//! endpoint reports under `/debug/runtime`.
+//!
+//! Only tasks spawned through `TaskRegistry` move: module start/stop and
+//! the framework's own work (gRPC server, registry heartbeats) stay on the
+//! protocol runtime, because `run_with_config` runs where it is awaited.
+//!
+//! # Rust Learning Note
+//!
+//! A `Runtime` must not be dropped from inside async code, so the module
+//! runtime is shut down by [`Runtimes::block_on`] after the protocol
+//! runtime's future completes, back on the plain `main` thread.
+
+use std::future::Future;
+use echo_api::{RuntimeAssignments, RuntimeRole};
+use hsu_common::{Error, Result};
+use tokio::runtime::{Builder, Runtime};
+
+/// Worker counts per runtime (`None` = tokio default / shared runtime).
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeLayout {
+    /// Workers of the protocol (main) runtime; one per core if `None`.
+    pub protocol_workers: Option<usize>,
+    /// Workers of a dedicated module runtime; modules share the protocol
+    /// runtime if `None`.
+    pub module_workers: Option<usize>,
+}
+
+/// The runtimes built from a [`RuntimeLayout`].
+pub struct Runtimes {
+    protocol: Runtime,
+    modules: Option<Runtime>,
+}
+
+impl Runtimes {
+    /// Builds the runtimes and records their assignments.
+    pub fn build(layout: &RuntimeLayout) -> Result<Self> {
+        let protocol = build_runtime("echo-protocol", layout.protocol_workers)?;
+        let assignments = RuntimeAssignments::global();
+        assignments.assign(RuntimeRole::Protocol, "echo-protocol", protocol.handle().clone());
+
+        let modules = layout.module_workers
+            .map(|workers| build_runtime("echo-modules", Some(workers)))
+            .transpose()?;
+        if let Some(modules) = &modules {
+            assignments.assign(RuntimeRole::Modules, "echo-modules", modules.handle().clone());
+        }
+
+        Ok(Self { protocol, modules })
+    }
+
+    /// Runs `future` on the protocol runtime, then shuts everything down.
+    pub fn block_on<F: Future>(self, future: F) -> F::Output {
+        let Runtimes { protocol, modules } = self;
+        let output = protocol.block_on(future);
+        if let Some(modules) = modules {
+            modules.shutdown_background();
+        }
+        output
+    }
+}
+
+fn build_runtime(name: &str, workers: Option<usize>) -> Result<Runtime> {
+    let mut builder = Builder::new_multi_thread();
+    builder.enable_all().thread_name(name);
+    if let Some(workers) = workers {
+        builder.worker_threads(workers.max(1));
+    }
+    builder.build().map_err(|e| Error::Validation {
+        message: format!("Failed to build {} runtime: {}", name, e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedicated_module_runtime() {
+        let runtimes = Runtimes::build(&RuntimeLayout {
+            protocol_workers: Some(2),
+            module_workers: Some(1),
+        }).unwrap();
+
+        let workers = runtimes.block_on(async {
+            let modules = RuntimeAssignments::global().handle(RuntimeRole::Modules).unwrap();
+            let thread = modules
+                .spawn(async { std::thread::current().name().map(str::to_string) })
+                .await
+                .unwrap();
+            assert_eq!(thread.as_deref(), Some("echo-modules"));
+            tokio::runtime::Handle::current().metrics().num_workers()
+        });
+        assert_eq!(workers, 2);
+    }
+}
@@ -0,0 +1,27 @@
+//! Keys loaded from the environment, never from flags.
+//!
+//! # Architecture
+//!
+//! ```text
+//! ECHO_PAYLOAD_KEYS="cli-1:<hex>,cli-2:<hex>"      ─┐ one or the other
+//! ECHO_PAYLOAD_KEYS_FILE=/run/secrets/payload-keys ─┘ (one key per line)
+//!     ↓ load_secrets("ECHO_PAYLOAD_KEYS")
+//! Vec<Secret<String>>  →  PayloadKey::parse(key.expose())
+//!     ↓ ConfigDescription::with_secrets
+//! [secrets]
+//!   ECHO_PAYLOAD_KEYS = cli-1:<redacted>,cli-2:<redacted>
+//! ```
+//!
+//! Flag values show up in `ps`, shell history and the config dump; an
+//! environment variable or a mounted secrets file doesn't.
+
+use echo_contract::Secret;
+use hsu_common::Result;
+
+/// Reads the list of secrets in `variable` (or the file `variable_FILE`
+/// names), separated by commas or whitespace.
+///
+/// Empty if neither variable is set.
+pub fn load_secrets(variable: &str) -> Result<Vec<Secret<String>>> {
+    Ok(Secret::from_env(variable)?.map(|list| list.split_list()).unwrap_or_default())
+}
@@ -0,0 +1,200 @@
+//! Configuration validation (`--validate-config`).
+//!
+//! # Architecture
+//!
+//! ```text
+//! main.rs: build Config from the flags (no side effects yet)
+//!     ↓
+//! ConfigCheck ← check_args(&args)        cross-flag rules of the binary
+//!             ← config.validate(&mut ..) modules, protocol servers, registry URL
+//!     ↓ finish_validation
+//! problems? → Err listing every one of them
+//! --validate-config? → "configuration is valid", exit before anything starts
+//! otherwise → bootstrap (logging, admin), PID file, registry, modules...
+//! ```
+//!
+//! Each problem names the flag (or config field) it is about, and all of
+//! them are reported at once, so a broken command line is fixed in one
+//! round instead of one error per attempt:
+//!
+//! ```text
+//! Invalid configuration (2 problems):
+//!   --slow-start-from: 100 is above --slow-start-to (64)
+//!   servers[1].listen_address: '0.0.0.0:50051' is bound twice
+//! ```
+//!
+//! Configuration only comes from flags here; there is no config file to
+//! report line and column numbers for.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use hsu_common::{Error, Result};
+use hsu_module_api::Config;
+
+use crate::args::BootstrapArgs;
+
+/// Registry URL forms the echo binaries understand (besides none at all).
+const REGISTRY_SCHEMES: &[&str] = &["http://", "consul://", "etcd://"];
+
+/// Problems found in a configuration, collected before reporting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigCheck {
+    problems: Vec<(String, String)>,
+}
+
+impl ConfigCheck {
+    /// Creates a check without problems.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a problem with `field` (a flag like `--port`, or a config field).
+    pub fn problem(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.problems.push((field.into(), message.into()));
+    }
+
+    /// Records `message` about `field` unless `valid`.
+    ///
+    /// The message is only built for invalid values.
+    pub fn require(&mut self, valid: bool, field: &str, message: impl FnOnce() -> String) {
+        if !valid {
+            self.problem(field, message());
+        }
+    }
+
+    /// Records the error of `result`, if any, against `field`.
+    pub fn require_ok<T>(&mut self, field: &str, result: Result<T>) {
+        if let Err(e) = result {
+            self.problem(field, e.to_string());
+        }
+    }
+
+    /// The problems found so far, as `(field, message)`.
+    pub fn problems(&self) -> &[(String, String)] {
+        &self.problems
+    }
+
+    /// `Ok` without problems, else one validation error listing them all.
+    pub fn into_result(self) -> Result<()> {
+        if self.problems.is_empty() {
+            return Ok(());
+        }
+        let count = self.problems.len();
+        let mut message = format!("Invalid configuration ({} problem{}):", count, if count == 1 { "" } else { "s" });
+        for (field, problem) in &self.problems {
+            let _ = write!(message, "\n  {}: {}", field, problem);
+        }
+        Err(Error::Validation { message })
+    }
+}
+
+/// Checks a configuration for problems before anything is started.
+pub trait Validate {
+    /// Adds the problems of `self` to `check`.
+    fn validate(&self, check: &mut ConfigCheck);
+}
+
+impl Validate for Config {
+    fn validate(&self, check: &mut ConfigCheck) {
+        let mut ids = HashSet::new();
+        for (index, module) in self.modules.iter().enumerate() {
+            let id = module.id.to_string();
+            check.require(ids.insert(id.clone()), &format!("modules[{}].id", index), || {
+                format!("module '{}' is configured twice", id)
+            });
+        }
+        check.require(self.modules.iter().any(|module| module.enabled), "modules", || {
+            "no module is enabled".to_string()
+        });
+
+        let mut bound = HashSet::new();
+        for (index, server) in self.runtime.servers.iter().enumerate() {
+            let field = format!("servers[{}].listen_address", index);
+            match server.listen_address.parse::<SocketAddr>() {
+                // Port 0 asks for a free port: never a clash
+                Ok(address) => check.require(address.port() == 0 || bound.insert(address), &field, || {
+                    format!("'{}' is bound twice", address)
+                }),
+                Err(e) => check.problem(field, format!("'{}' is not an address: {}", server.listen_address, e)),
+            }
+        }
+
+        let url = self.runtime.service_registry.url.as_str();
+        let known = url.is_empty() || url == "inmem" || REGISTRY_SCHEMES.iter().any(|scheme| url.starts_with(scheme));
+        check.require(known, "registry.url", || {
+            format!("unsupported registry URL '{}' (expected http://, consul://, etcd:// or inmem)", url)
+        });
+    }
+}
+
+/// Reports the problems of `check`, if any, together with those of the
+/// logging flags.
+///
+/// Call before [`bootstrap`](crate::bootstrap): `--validate-config` must
+/// not open log files, bind the admin endpoint or load anything. Returns
+/// `true` if the process should stop here because only `--validate-config`
+/// was asked for.
+pub fn finish_validation(args: &BootstrapArgs, mut check: ConfigCheck) -> Result<bool> {
+    check.require_ok("--log", args.logging_config());
+    check.into_result()?;
+    if args.validate_config {
+        // Logging isn't set up yet
+        println!("[Config] ✅ Configuration is valid");
+    }
+    Ok(args.validate_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hsu_common::{ModuleID, Protocol};
+    use hsu_module_api::{ModuleConfig, ProtocolServerConfig, RuntimeConfig, ServiceRegistryConfig};
+
+    #[test]
+    fn test_all_problems_are_reported() {
+        let mut check = ConfigCheck::new();
+        check.require(true, "--port", || unreachable!());
+        check.require(false, "--slow-start-from", || "4 is above --slow-start-to (2)".to_string());
+        check.require_ok("--id-strategy", Err::<(), _>(Error::Validation { message: "unknown".to_string() }));
+        assert_eq!(check.problems().len(), 2);
+
+        let Err(Error::Validation { message }) = check.into_result() else {
+            panic!("expected a validation error");
+        };
+        assert!(message.starts_with("Invalid configuration (2 problems):\n  --slow-start-from: 4 is above"));
+        assert!(message.contains("\n  --id-strategy: "));
+        assert!(ConfigCheck::new().into_result().is_ok());
+    }
+
+    #[test]
+    fn test_config_problems() {
+        let server = |address: &str| ProtocolServerConfig { protocol: Protocol::Grpc, listen_address: address.to_string() };
+        let module = |id: &str| ModuleConfig { id: ModuleID::from(id), enabled: false, servers: vec![] };
+        let config = Config {
+            runtime: RuntimeConfig {
+                service_registry: ServiceRegistryConfig { url: "zk://localhost:2181".to_string() },
+                servers: vec![
+                    server("0.0.0.0:50051"),
+                    server("[::]:0"),
+                    server("0.0.0.0:50051"),
+                    server("[::]:0"),
+                    server("localhost"),
+                ],
+            },
+            modules: vec![module("echo"), module("echo")],
+        };
+
+        let mut check = ConfigCheck::new();
+        config.validate(&mut check);
+
+        let fields: Vec<&str> = check.problems().iter().map(|(field, _)| field.as_str()).collect();
+        assert_eq!(fields, [
+            "modules[1].id",
+            "modules",
+            "servers[2].listen_address",
+            "servers[4].listen_address",
+            "registry.url",
+        ]);
+    }
+}
@@ -0,0 +1,20 @@
+//! Generates `$OUT_DIR/echo_client.h` from the `extern "C"` API.
+//!
+//! Nothing is written into the source tree; a test checks that the
+//! checked-in `include/echo_client.h` matches.
+
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("set by cargo");
+    let out_dir = std::env::var("OUT_DIR").expect("set by cargo");
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("cbindgen.toml is valid");
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("the extern \"C\" API is cbindgen-compatible")
+        .write_to_file(format!("{}/echo_client.h", out_dir));
+}
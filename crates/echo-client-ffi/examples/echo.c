@@ -0,0 +1,49 @@
+/*
+ * Calls the echo service from C.
+ *
+ *   cargo build --release -p echo-client-ffi
+ *   cc crates/echo-client-ffi/examples/echo.c -Icrates/echo-client-ffi/include \
+ *      -Ltarget/release -lecho_client_ffi -o echo-c
+ *   cargo run --release --bin echo-grpc-srv -- --port 50051 &
+ *   LD_LIBRARY_PATH=target/release ./echo-c localhost:50051 "Hello from C!"
+ */
+#include <stdio.h>
+#include <unistd.h>
+
+#include "echo_client.h"
+
+static void on_echo(void *user_data, EchoStatus status, const char *message) {
+    int *done = user_data;
+    printf("async: %s %s\n", status == ECHO_STATUS_OK ? "ok" : "failed:", message);
+    __atomic_store_n(done, 1, __ATOMIC_RELEASE);
+}
+
+int main(int argc, char **argv) {
+    const char *address = argc > 1 ? argv[1] : "localhost:50051";
+    const char *message = argc > 2 ? argv[2] : "Hello from C!";
+
+    EchoClient *client = echo_client_init(address, 2000);
+    if (client == NULL) {
+        fprintf(stderr, "init failed: %s\n", echo_last_error());
+        return 1;
+    }
+
+    char *response = NULL;
+    EchoStatus status = echo_client_echo_blocking(client, message, &response);
+    if (status == ECHO_STATUS_OK) {
+        printf("blocking: %s\n", response);
+        echo_string_free(response);
+    } else {
+        fprintf(stderr, "blocking echo failed (%d): %s\n", status, echo_last_error());
+    }
+
+    int done = 0;
+    if (echo_client_echo_async(client, message, on_echo, &done) == ECHO_STATUS_OK) {
+        while (!__atomic_load_n(&done, __ATOMIC_ACQUIRE)) {
+            usleep(1000);
+        }
+    }
+
+    echo_client_shutdown(client);
+    return status == ECHO_STATUS_OK ? 0 : 1;
+}
@@ -0,0 +1,106 @@
+/* Generated by cbindgen from crates/echo-client-ffi - do not edit. */
+
+#ifndef ECHO_CLIENT_H
+#define ECHO_CLIENT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of an FFI call.
+typedef enum EchoStatus {
+  ECHO_STATUS_OK = 0,
+  // Null pointer, invalid UTF-8, bad address, ...
+  ECHO_STATUS_INVALID_ARGUMENT = 1,
+  // The call missed its deadline.
+  ECHO_STATUS_DEADLINE_EXCEEDED = 2,
+  // The server couldn't be reached; worth retrying.
+  ECHO_STATUS_UNAVAILABLE = 3,
+  // The server shed load; back off before retrying.
+  ECHO_STATUS_OVERLOADED = 4,
+  // Any other failure (see `echo_last_error`).
+  ECHO_STATUS_ERROR = 5,
+} EchoStatus;
+
+// An echo client (opaque to C).
+typedef struct EchoClient EchoClient;
+
+// Called with the outcome of `echo_client_echo_async`.
+//
+// `message` is the echoed message on `ECHO_STATUS_OK`, the error text
+// otherwise; it is only valid during the call. Runs on a runtime
+// worker thread. Passing `NULL` is rejected with `ECHO_STATUS_INVALID_ARGUMENT`.
+typedef void (*EchoCallback)(void *user_data, enum EchoStatus status, const char *message);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a client for the echo server at `address` (`host:port`).
+//
+// `deadline_ms` bounds every call (`0`: unbounded). Connects lazily, so
+// an unreachable server shows up on the first call. Returns `NULL` on
+// failure (see `echo_last_error`).
+//
+// # Safety
+//
+// `address` must be a valid NUL-terminated string.
+struct EchoClient *echo_client_init(const char *address, uint64_t deadline_ms);
+
+// Echoes `message`, blocking the calling thread.
+//
+// On `ECHO_STATUS_OK`, `*response` receives the echoed message (free it
+// with `echo_string_free`). Must not be called from an `EchoCallback`.
+//
+// # Safety
+//
+// `client` must come from `echo_client_init` and not be shut down;
+// `message` must be a valid NUL-terminated string; `response` must be
+// a valid pointer.
+enum EchoStatus echo_client_echo_blocking(const struct EchoClient *client,
+                                          const char *message,
+                                          char **response);
+
+// Echoes `message` without blocking; `callback` receives the outcome.
+//
+// Returns `ECHO_STATUS_OK` once the call is started; failures to start
+// (bad arguments) are returned directly and `callback` isn't invoked.
+//
+// # Safety
+//
+// `client` must come from `echo_client_init` and not be shut down;
+// `message` must be a valid NUL-terminated string; `user_data` must be
+// safe to use from another thread.
+enum EchoStatus echo_client_echo_async(const struct EchoClient *client,
+                                       const char *message,
+                                       EchoCallback callback,
+                                       void *user_data);
+
+// Shuts the client down and frees it.
+//
+// Pending `echo_client_echo_async` calls are cancelled; their callbacks
+// are not invoked. `NULL` is ignored.
+//
+// # Safety
+//
+// `client` must come from `echo_client_init` and not be used afterwards.
+void echo_client_shutdown(struct EchoClient *client);
+
+// Frees a string returned by this library. `NULL` is ignored.
+//
+// # Safety
+//
+// `string` must come from this library and not be freed twice.
+void echo_string_free(char *string);
+
+// Text of the last error on the calling thread (`NULL` if none).
+//
+// Valid until the next call into this library on the same thread.
+const char *echo_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ECHO_CLIENT_H */
@@ -0,0 +1,353 @@
+//! C Bindings for the Echo Client (Layer 5, FFI)
+//!
+//! # Architecture
+//!
+//! Lets a non-Rust host application (C, C++, anything with a C FFI) call
+//! the echo service through the same gateways the echo-client module
+//! uses for a configured `grpc_address`:
+//!
+//! ```text
+//! C host
+//!   echo_client_init("localhost:50051", 2000)
+//!       └── EchoClient { tokio runtime, new_standalone_echo_service_gateways(GatewayOptions) }
+//!                                          ↓ get_service(Grpc): pooled, self-healing channels
+//!   echo_client_echo_blocking / echo_client_echo_async ──gRPC──→ echo-grpc-srv
+//!   echo_client_shutdown                   └── gateways.close()
+//! ```
+//!
+//! Each client owns its own tokio runtime, so the host needs no async
+//! runtime and several clients don't interfere. cbindgen generates the
+//! header into `OUT_DIR` on build; `include/echo_client.h` is the checked-in
+//! copy, kept equal to it by a test.
+//!
+//! ## Ownership Rules
+//!
+//! | Value                          | Owned by | Release with             |
+//! |--------------------------------|----------|--------------------------|
+//! | `EchoClient *` from init       | host     | `echo_client_shutdown`   |
+//! | `*response` of echo_blocking   | host     | `echo_string_free`       |
+//! | `message` passed to a callback | library  | (valid during the call)  |
+//! | `echo_last_error()`            | library  | (valid until next call)  |
+//!
+//! ## Rust Learning Note
+//!
+//! Panics must not unwind into C, so every entry point runs inside
+//! `catch_unwind` and reports a panic as `ECHO_STATUS_ERROR`. Raw
+//! pointers aren't `Send`; the callback's `user_data` is wrapped in a
+//! type that asserts it is (the host promised so by passing it).
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Arc;
+use std::time::Duration;
+use echo_api::{new_standalone_echo_service_gateways, GatewayOptions};
+use echo_api_grpc::{ChannelPool, GrpcChannelOptions};
+use echo_contract::{error_kind, EchoErrorKind, EchoService, EchoServiceGateways};
+use hsu_common::{Error, Protocol, Result};
+use tokio::runtime::Runtime;
+use tracing::debug;
+
+/// Result of an FFI call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EchoStatus {
+    Ok = 0,
+    /// Null pointer, invalid UTF-8, bad address, ...
+    InvalidArgument = 1,
+    /// The call missed its deadline.
+    DeadlineExceeded = 2,
+    /// The server couldn't be reached; worth retrying.
+    Unavailable = 3,
+    /// The server shed load; back off before retrying.
+    Overloaded = 4,
+    /// Any other failure (see `echo_last_error`).
+    Error = 5,
+}
+
+impl From<&Error> for EchoStatus {
+    fn from(error: &Error) -> Self {
+        match error_kind(error) {
+            Some(EchoErrorKind::DeadlineExceeded) => EchoStatus::DeadlineExceeded,
+            Some(EchoErrorKind::Unavailable) => EchoStatus::Unavailable,
+            Some(EchoErrorKind::Overloaded { .. }) => EchoStatus::Overloaded,
+            Some(
+                EchoErrorKind::InvalidResponse
+                | EchoErrorKind::IntegrityError
+                | EchoErrorKind::Storage
+                | EchoErrorKind::ProtocolUnsupported,
+            ) => EchoStatus::Error,
+            None if matches!(error, Error::Validation { .. }) => EchoStatus::InvalidArgument,
+            None => EchoStatus::Error,
+        }
+    }
+}
+
+/// Called with the outcome of `echo_client_echo_async`.
+///
+/// `message` is the echoed message on `ECHO_STATUS_OK`, the error text
+/// otherwise; it is only valid during the call. Runs on a runtime
+/// worker thread. Passing `NULL` is rejected with `ECHO_STATUS_INVALID_ARGUMENT`.
+pub type EchoCallback = Option<extern "C" fn(user_data: *mut c_void, status: EchoStatus, message: *const c_char)>;
+
+/// An echo client (opaque to C).
+pub struct EchoClient {
+    runtime: Runtime,
+    gateways: Arc<dyn EchoServiceGateways>,
+    service: Arc<dyn EchoService>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Creates a client for the echo server at `address` (`host:port`).
+///
+/// `deadline_ms` bounds every call (`0`: unbounded). Connects lazily, so
+/// an unreachable server shows up on the first call. Returns `NULL` on
+/// failure (see `echo_last_error`).
+///
+/// # Safety
+///
+/// `address` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn echo_client_init(address: *const c_char, deadline_ms: u64) -> *mut EchoClient {
+    let result = guard(|| {
+        let address = str_arg(address, "address")?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("echo-ffi")
+            .enable_all()
+            .build()
+            .map_err(|e| Error::Protocol(format!("Failed to start runtime: {}", e)))?;
+
+        // Channels are created inside the runtime they run on
+        let (gateways, service) = runtime.block_on(async {
+            let options = GatewayOptions {
+                deadline: (deadline_ms > 0).then(|| Duration::from_millis(deadline_ms)),
+                grpc_address: Some(address.to_string()),
+                channel_pool: Arc::new(ChannelPool::new(GrpcChannelOptions::default())),
+                ..GatewayOptions::default()
+            };
+            let gateways = new_standalone_echo_service_gateways(options);
+            let service = gateways.get_service(Protocol::Grpc).await?;
+            Ok::<_, Error>((gateways, service))
+        })?;
+        debug!("[EchoClientFfi] Client created for {}", address);
+        Ok(Box::into_raw(Box::new(EchoClient { runtime, gateways, service })))
+    });
+    result.unwrap_or(ptr::null_mut())
+}
+
+/// Echoes `message`, blocking the calling thread.
+///
+/// On `ECHO_STATUS_OK`, `*response` receives the echoed message (free it
+/// with `echo_string_free`). Must not be called from an `EchoCallback`.
+///
+/// # Safety
+///
+/// `client` must come from `echo_client_init` and not be shut down;
+/// `message` must be a valid NUL-terminated string; `response` must be
+/// a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn echo_client_echo_blocking(
+    client: *const EchoClient,
+    message: *const c_char,
+    response: *mut *mut c_char,
+) -> EchoStatus {
+    let result = guard(|| {
+        let client = client_arg(client)?;
+        let message = str_arg(message, "message")?;
+        if response.is_null() {
+            return Err(invalid("response is NULL"));
+        }
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(invalid("echo_client_echo_blocking called from a runtime thread (inside a callback?)"));
+        }
+
+        let echoed = client.runtime.block_on(client.service.echo(message.into()))?;
+        *response = c_string(&echoed).into_raw();
+        Ok(())
+    });
+    status(result)
+}
+
+/// Echoes `message` without blocking; `callback` receives the outcome.
+///
+/// Returns `ECHO_STATUS_OK` once the call is started; failures to start
+/// (bad arguments) are returned directly and `callback` isn't invoked.
+///
+/// # Safety
+///
+/// `client` must come from `echo_client_init` and not be shut down;
+/// `message` must be a valid NUL-terminated string; `user_data` must be
+/// safe to use from another thread.
+#[no_mangle]
+pub unsafe extern "C" fn echo_client_echo_async(
+    client: *const EchoClient,
+    message: *const c_char,
+    callback: EchoCallback,
+    user_data: *mut c_void,
+) -> EchoStatus {
+    let result = guard(|| {
+        let client = client_arg(client)?;
+        let message: Arc<str> = str_arg(message, "message")?.into();
+        let callback = callback.ok_or_else(|| invalid("callback is NULL"))?;
+        let user_data = UserData(user_data);
+        let service = client.service.clone();
+
+        client.runtime.spawn(async move {
+            let user_data = user_data;
+            let (status, text) = match service.echo(message).await {
+                Ok(echoed) => (EchoStatus::Ok, c_string(&echoed)),
+                Err(e) => (EchoStatus::from(&e), c_string(&e.to_string())),
+            };
+            // A panicking callback must not take the worker thread down
+            let _ = catch_unwind(AssertUnwindSafe(|| callback(user_data.0, status, text.as_ptr())));
+        });
+        Ok(())
+    });
+    status(result)
+}
+
+/// Shuts the client down and frees it.
+///
+/// Pending `echo_client_echo_async` calls are cancelled; their callbacks
+/// are not invoked. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `client` must come from `echo_client_init` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn echo_client_shutdown(client: *mut EchoClient) {
+    if client.is_null() {
+        return;
+    }
+    let _ = guard(|| {
+        let client = Box::from_raw(client);
+        client.runtime.block_on(client.gateways.close())?;
+        client.runtime.shutdown_timeout(Duration::from_secs(5));
+        debug!("[EchoClientFfi] Client shut down");
+        Ok(())
+    });
+}
+
+/// Frees a string returned by this library. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `string` must come from this library and not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn echo_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Text of the last error on the calling thread (`NULL` if none).
+///
+/// Valid until the next call into this library on the same thread.
+#[no_mangle]
+pub extern "C" fn echo_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |error| error.as_ptr()))
+}
+
+/// `user_data` handed back to the host's callback on another thread.
+struct UserData(*mut c_void);
+
+// The host passes `user_data` for use from the callback thread (see
+// `echo_client_echo_async`); we only hand it back.
+unsafe impl Send for UserData {}
+
+/// Runs an entry point: clears the last error, records a new one, turns
+/// panics into errors.
+fn guard<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    let result = catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err(Error::Protocol("panic in echo-client-ffi".to_string())));
+    if let Err(e) = &result {
+        LAST_ERROR.with(|last| *last.borrow_mut() = Some(c_string(&e.to_string())));
+    }
+    result
+}
+
+fn status(result: Result<()>) -> EchoStatus {
+    match result {
+        Ok(()) => EchoStatus::Ok,
+        Err(e) => EchoStatus::from(&e),
+    }
+}
+
+unsafe fn str_arg<'a>(value: *const c_char, name: &str) -> Result<&'a str> {
+    if value.is_null() {
+        return Err(invalid(&format!("{} is NULL", name)));
+    }
+    CStr::from_ptr(value).to_str().map_err(|_| invalid(&format!("{} is not valid UTF-8", name)))
+}
+
+unsafe fn client_arg<'a>(client: *const EchoClient) -> Result<&'a EchoClient> {
+    client.as_ref().ok_or_else(|| invalid("client is NULL"))
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Validation { message: message.to_string() }
+}
+
+/// A C string of `text`; interior NULs (never in echo messages) are dropped.
+fn c_string(text: &str) -> CString {
+    CString::new(text.replace('\0', "")).expect("NULs were removed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::{deadline_exceeded, overloaded};
+
+    #[test]
+    fn test_status_from_error_kind() {
+        assert_eq!(EchoStatus::from(&deadline_exceeded("slow")), EchoStatus::DeadlineExceeded);
+        assert_eq!(EchoStatus::from(&overloaded(Duration::from_millis(10), "full")), EchoStatus::Overloaded);
+        assert_eq!(EchoStatus::from(&invalid("bad")), EchoStatus::InvalidArgument);
+        assert_eq!(EchoStatus::from(&Error::Protocol("boom".to_string())), EchoStatus::Error);
+    }
+
+    #[test]
+    fn test_null_arguments_are_rejected() {
+        unsafe {
+            assert!(echo_client_init(ptr::null(), 0).is_null());
+            let error = CStr::from_ptr(echo_last_error()).to_str().unwrap();
+            assert!(error.contains("address is NULL"), "{}", error);
+
+            let mut response = ptr::null_mut();
+            let status = echo_client_echo_blocking(ptr::null(), b"hi\0".as_ptr().cast(), &mut response);
+            assert_eq!(status, EchoStatus::InvalidArgument);
+            assert!(response.is_null());
+
+            echo_client_shutdown(ptr::null_mut());
+            echo_string_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_checked_in_header_is_current() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/echo_client.h"));
+        let checked_in = include_str!("../include/echo_client.h");
+        assert!(generated == checked_in, "include/echo_client.h is stale: copy it from $OUT_DIR/echo_client.h");
+    }
+
+    #[test]
+    fn test_unreachable_server_reports_error() {
+        unsafe {
+            let client = echo_client_init(b"127.0.0.1:1\0".as_ptr().cast(), 500);
+            assert!(!client.is_null());
+
+            let mut response = ptr::null_mut();
+            let status = echo_client_echo_blocking(client, b"hi\0".as_ptr().cast(), &mut response);
+            assert_ne!(status, EchoStatus::Ok);
+            assert!(!echo_last_error().is_null());
+
+            echo_client_shutdown(client);
+        }
+    }
+}
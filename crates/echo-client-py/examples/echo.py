@@ -0,0 +1,32 @@
+"""Calls the echo service from Python.
+
+    pip install maturin
+    maturin develop -m crates/echo-client-py/Cargo.toml
+    cargo run --release --bin echo-grpc-srv -- --port 50051 &
+    python crates/echo-client-py/examples/echo.py localhost:50051
+"""
+
+import asyncio
+import sys
+
+import echo_client
+
+
+async def main(address: str) -> None:
+    client = echo_client.EchoClient.connect(address, deadline_ms=2000)
+
+    print("blocking:", client.echo_blocking("Hello from Python!"))
+    print("async:", await client.echo("Hello from asyncio!"))
+
+    async for reply in client.echo_stream(["one", "two", "three"]):
+        print("stream:", reply)
+    client.close()
+
+    try:
+        await echo_client.EchoClient.connect("127.0.0.1:1", deadline_ms=500).echo("nobody home")
+    except echo_client.EchoError as e:
+        print(f"expected failure ({type(e).__name__}): {e}")
+
+
+if __name__ == "__main__":
+    asyncio.run(main(sys.argv[1] if len(sys.argv) > 1 else "localhost:50051"))
@@ -0,0 +1,249 @@
+//! Python Bindings for the Echo Client (Layer 5, PyO3)
+//!
+//! # Architecture
+//!
+//! A Python extension module (`echo_client`) over the same gateway stack
+//! the echo-client module uses for a configured `grpc_address`, so
+//! scripts and notebooks can drive the example service without Rust:
+//!
+//! ```text
+//! Python (asyncio)
+//!   client = echo_client.EchoClient.connect("localhost:50051")
+//!   await client.echo("hi")  ──future_into_py──→ tokio runtime
+//!                                                  └── grpc_echo_service ──gRPC──→ echo-grpc-srv
+//! ```
+//!
+//! Calls run on a tokio runtime owned by `pyo3-async-runtimes`; awaiting
+//! them doesn't block the event loop, and `echo_blocking` releases the
+//! GIL while it waits.
+//!
+//! ```bash
+//! pip install maturin
+//! maturin develop -m crates/echo-client-py/Cargo.toml
+//! python crates/echo-client-py/examples/echo.py localhost:50051
+//! ```
+//!
+//! ## Errors
+//!
+//! | Contract error kind   | Python exception           |
+//! |-----------------------|----------------------------|
+//! | `DeadlineExceeded`    | `DeadlineExceededError`    |
+//! | `Unavailable`         | `UnavailableError`         |
+//! | `Overloaded`          | `OverloadedError`          |
+//! | `InvalidResponse`     | `InvalidResponseError`     |
+//! | `IntegrityError`      | `IntegrityError`           |
+//! | `Storage`             | `StorageError`             |
+//! | `ProtocolUnsupported` | `ProtocolUnsupportedError` |
+//! | anything else         | `EchoError` (base class)   |
+//!
+//! ## Rust Learning Note
+//!
+//! `future_into_py` needs a `Send + 'static` future, so every method
+//! clones the `Arc<dyn EchoService>` into the future instead of
+//! borrowing `self` across the `await`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use echo_api::{grpc_echo_service, GatewayOptions};
+use echo_api_grpc::{ChannelPool, GrpcChannelOptions};
+use echo_contract::{error_kind, EchoErrorKind, EchoService, Priority, RequestContext};
+use hsu_common::Error;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyStopAsyncIteration, PyValueError};
+use pyo3::prelude::*;
+
+create_exception!(echo_client, EchoError, PyException, "An echo call failed.");
+create_exception!(echo_client, DeadlineExceededError, EchoError, "The call missed its deadline.");
+create_exception!(echo_client, UnavailableError, EchoError, "The server couldn't be reached; worth retrying.");
+create_exception!(echo_client, OverloadedError, EchoError, "The server shed load; back off before retrying.");
+create_exception!(echo_client, InvalidResponseError, EchoError, "A response validator rejected the reply.");
+create_exception!(echo_client, IntegrityError, EchoError, "A message arrived corrupted; worth retrying.");
+create_exception!(echo_client, StorageError, EchoError, "The server's storage failed; the request was fine.");
+create_exception!(echo_client, ProtocolUnsupportedError, EchoError, "The protocol can't reach the service; try another.");
+
+/// Client for the echo service.
+#[pyclass(module = "echo_client", frozen)]
+struct EchoClient {
+    service: Arc<dyn EchoService>,
+    channel_pool: Arc<ChannelPool>,
+    priority: Priority,
+}
+
+#[pymethods]
+impl EchoClient {
+    /// Creates a client for the echo server at `address` (`host:port`).
+    ///
+    /// Connects lazily: an unreachable server shows up on the first call.
+    #[staticmethod]
+    #[pyo3(signature = (address, deadline_ms = None, priority = "normal"))]
+    fn connect(address: &str, deadline_ms: Option<u64>, priority: &str) -> PyResult<Self> {
+        let priority = Priority::from_name(priority)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown priority '{}' (expected high, normal or low)", priority)))?;
+
+        // Channels are created inside the runtime they run on
+        let _runtime = pyo3_async_runtimes::tokio::get_runtime().enter();
+        let channel_pool = Arc::new(ChannelPool::new(GrpcChannelOptions::default()));
+        let options = GatewayOptions {
+            deadline: deadline_ms.map(Duration::from_millis),
+            grpc_address: Some(address.to_string()),
+            channel_pool: channel_pool.clone(),
+            ..GatewayOptions::default()
+        };
+        Ok(Self { service: grpc_echo_service(address, &options), channel_pool, priority })
+    }
+
+    /// Closes the client's connections; later calls fail with `EchoError`.
+    fn close(&self) {
+        self.channel_pool.close();
+    }
+
+    /// Echoes `message` (awaitable).
+    fn echo<'py>(&self, py: Python<'py>, message: String) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        let priority = self.priority;
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            echo(service, priority, message).await.map_err(to_py)
+        })
+    }
+
+    /// Echoes `message`, blocking the calling thread (GIL released).
+    fn echo_blocking(&self, py: Python<'_>, message: String) -> PyResult<String> {
+        let service = self.service.clone();
+        let priority = self.priority;
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(echo(service, priority, message))
+        })
+        .map_err(to_py)
+    }
+
+    /// Echoes each of `messages` in turn; an async iterator of the replies.
+    ///
+    /// ```python
+    /// async for reply in client.echo_stream(["a", "b", "c"]):
+    ///     print(reply)
+    /// ```
+    fn echo_stream(&self, messages: Vec<String>) -> EchoStream {
+        EchoStream {
+            service: self.service.clone(),
+            priority: self.priority,
+            pending: Mutex::new(messages.into()),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EchoClient(priority={})", self.priority)
+    }
+}
+
+/// Async iterator returned by `EchoClient.echo_stream`.
+#[pyclass(module = "echo_client", frozen)]
+struct EchoStream {
+    service: Arc<dyn EchoService>,
+    priority: Priority,
+    pending: Mutex<VecDeque<String>>,
+}
+
+#[pymethods]
+impl EchoStream {
+    fn __aiter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let Some(message) = self.pending.lock().unwrap().pop_front() else {
+            return Err(PyStopAsyncIteration::new_err(()));
+        };
+        let service = self.service.clone();
+        let priority = self.priority;
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            echo(service, priority, message).await.map_err(to_py)
+        })
+    }
+}
+
+async fn echo(service: Arc<dyn EchoService>, priority: Priority, message: String) -> hsu_common::Result<String> {
+    let reply = RequestContext::new(priority).scope(service.echo(message.into())).await?;
+    Ok(reply.to_string())
+}
+
+/// Maps an echo error to the matching Python exception.
+fn to_py(error: Error) -> PyErr {
+    let message = error.to_string();
+    match error_kind(&error) {
+        Some(EchoErrorKind::DeadlineExceeded) => DeadlineExceededError::new_err(message),
+        Some(EchoErrorKind::Unavailable) => UnavailableError::new_err(message),
+        Some(EchoErrorKind::Overloaded { .. }) => OverloadedError::new_err(message),
+        Some(EchoErrorKind::InvalidResponse) => InvalidResponseError::new_err(message),
+        Some(EchoErrorKind::IntegrityError) => IntegrityError::new_err(message),
+        Some(EchoErrorKind::Storage) => StorageError::new_err(message),
+        Some(EchoErrorKind::ProtocolUnsupported) => ProtocolUnsupportedError::new_err(message),
+        None => EchoError::new_err(message),
+    }
+}
+
+/// The `echo_client` Python module.
+#[pymodule]
+fn echo_client(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<EchoClient>()?;
+    m.add_class::<EchoStream>()?;
+    m.add("EchoError", m.py().get_type::<EchoError>())?;
+    m.add("DeadlineExceededError", m.py().get_type::<DeadlineExceededError>())?;
+    m.add("UnavailableError", m.py().get_type::<UnavailableError>())?;
+    m.add("OverloadedError", m.py().get_type::<OverloadedError>())?;
+    m.add("InvalidResponseError", m.py().get_type::<InvalidResponseError>())?;
+    m.add("IntegrityError", m.py().get_type::<IntegrityError>())?;
+    m.add("StorageError", m.py().get_type::<StorageError>())?;
+    m.add("ProtocolUnsupportedError", m.py().get_type::<ProtocolUnsupportedError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::{deadline_exceeded, overloaded, unavailable};
+
+    #[test]
+    fn test_error_kinds_map_to_exceptions() {
+        Python::with_gil(|py| {
+            assert!(to_py(deadline_exceeded("slow")).is_instance_of::<DeadlineExceededError>(py));
+            assert!(to_py(unavailable("gone")).is_instance_of::<UnavailableError>(py));
+            assert!(to_py(overloaded(Duration::from_millis(10), "full")).is_instance_of::<OverloadedError>(py));
+
+            // Every kind can be caught as EchoError
+            let other = to_py(Error::Protocol("boom".to_string()));
+            assert!(other.is_instance_of::<EchoError>(py));
+            assert!(!other.is_instance_of::<UnavailableError>(py));
+            assert!(to_py(deadline_exceeded("slow")).is_instance_of::<EchoError>(py));
+        });
+    }
+
+    #[test]
+    fn test_unknown_priority_is_rejected() {
+        Python::with_gil(|py| {
+            let error = EchoClient::connect("localhost:50051", None, "urgent").err().unwrap();
+            assert!(error.is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn test_unreachable_server_raises_echo_error() {
+        Python::with_gil(|py| {
+            let client = EchoClient::connect("127.0.0.1:1", Some(500), "normal").unwrap();
+            let error = client.echo_blocking(py, "hi".to_string()).unwrap_err();
+            assert!(error.is_instance_of::<EchoError>(py));
+            client.close();
+        });
+    }
+
+    #[test]
+    fn test_module_exports_client_and_exceptions() {
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "echo_client").unwrap();
+            echo_client(&module).unwrap();
+            for name in ["EchoClient", "EchoError", "DeadlineExceededError", "ProtocolUnsupportedError"] {
+                assert!(module.hasattr(name).unwrap(), "{} missing", name);
+            }
+        });
+    }
+}
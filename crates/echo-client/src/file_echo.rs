@@ -0,0 +1,51 @@
+//! Streaming File Echo (Layer 3)
+//!
+//! # Architecture
+//!
+//! Streams a file to `EchoService::echo_file` in fixed-size chunks:
+//!
+//! ```text
+//! tokio::fs::File
+//!     ↓ ReaderStream (chunk_size bytes at a time)
+//! ByteStream
+//!     ↓ Direct: passed as-is (zero-copy Bytes)
+//!     ↓ gRPC:   client-streaming EchoFile RPC
+//! FileDigest { byte_count, chunk_count, sha256 }
+//! ```
+//!
+//! Memory use is bounded by the chunk size, not the file size.
+
+use std::path::Path;
+
+use echo_contract::{EchoService, FileDigest};
+use futures::StreamExt;
+use hsu_common::{Error, Result};
+use tokio_util::io::ReaderStream;
+use tracing::debug;
+
+/// Default chunk size for file streaming (64 KiB).
+///
+/// Well below tonic's default 4 MiB message limit.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Streams the file at `path` through the echo service.
+pub async fn echo_file(
+    service: &dyn EchoService,
+    path: &Path,
+    chunk_size: usize,
+) -> Result<FileDigest> {
+    debug!("[EchoFile] Streaming {} in {} byte chunks", path.display(), chunk_size);
+
+    let file = tokio::fs::File::open(path).await.map_err(|e| Error::Validation {
+        message: format!("Failed to open {}: {}", path.display(), e),
+    })?;
+
+    let display = path.display().to_string();
+    let chunks = ReaderStream::with_capacity(file, chunk_size).map(move |chunk| {
+        chunk.map_err(|e| Error::Validation {
+            message: format!("Failed to read {}: {}", display, e),
+        })
+    });
+
+    service.echo_file(Box::pin(chunks)).await
+}
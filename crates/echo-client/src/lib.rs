@@ -9,6 +9,9 @@
 //! - **Layer 3 (Module/Domain)**: `module.rs` - Module behavior
 //! - **Layer 5 (Module Wiring)**: `wiring.rs` - Module self-registration
 //! - **Layer 5 (Service Provider)**: `service_provider.rs` - Service access
+//! - **Outbox**: `outbox.rs` - Bounded buffer for fire-and-forget delivery
+//! - **Reliable delivery**: `reliable.rs` - At-least-once retries with idempotency keys
+//! - **File echo**: `file_echo.rs` - Chunked streaming of large payloads
 //!
 //! ## Why Separate from echo-server?
 //!
@@ -23,11 +26,17 @@
 //! - Domain: `pkg/echoclient/echoclientdomain/module.go`
 //! - Wiring: `pkg/echoclient/echoclientwiring/wiring.go`
 
+pub mod file_echo;
 pub mod module;
+pub mod outbox;
+pub mod reliable;
 pub mod service_provider;
 pub mod wiring;
 
+pub use file_echo::{echo_file, DEFAULT_CHUNK_SIZE};
 pub use module::EchoClientModule;
+pub use outbox::{EchoOutbox, OutboxConfig, OutboxStats, OverflowPolicy};
+pub use reliable::{echo_at_least_once, new_idempotency_key, RetryPolicy};
 pub use service_provider::EchoClientServiceProvider;
 pub use wiring::{init_echo_client_module, EchoClientModuleConfig};
 
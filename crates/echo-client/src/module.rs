@@ -6,13 +6,38 @@
 //!
 //! Wiring (Layer 5) is in `wiring.rs` - kept separate!
 
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use async_trait::async_trait;
+use echo_api::{protocol_name, spawn_tracked, until_cancelled, CancellationToken, TypedServiceClient};
+#[cfg(feature = "grpc")]
+use echo_api_grpc::{ChannelPool, ConnectivityState};
+use echo_contract::{
+    CallInfo, EchoService, EchoServiceGateways, EchoTransform, Priority, RequestContext, ECHO_CLIENT_MODULE_ID,
+};
+use futures::StreamExt;
+use serde_json::json;
 use hsu_common::{ModuleID, Result};
 use hsu_module_api::Module;
-use tracing::info;
+#[cfg(feature = "grpc")]
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{info, info_span, instrument, warn, Instrument};
+use uuid::Uuid;
 
+use crate::file_echo::{echo_file, DEFAULT_CHUNK_SIZE};
+use crate::outbox::{is_buffered, EchoOutbox};
+use crate::reliable::{echo_at_least_once, RetryPolicy};
 use crate::service_provider::EchoClientServiceProvider;
 
+/// Upper bound for delivering buffered messages on `stop()`.
+const FINAL_FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long `stop()` waits for background tasks to end after closing the
+/// gateways.
+const TASK_STOP_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Echo client module implementation.
 ///
 /// This is the Module/Domain layer (Layer 3) - module behavior.
@@ -24,20 +49,234 @@ use crate::service_provider::EchoClientServiceProvider;
 pub struct EchoClientModule {
     id: ModuleID,
     service_provider: EchoClientServiceProvider,
-    message: String,
+    message: Arc<str>,
+    outbox: Option<Arc<EchoOutbox>>,
+    flusher: Option<JoinHandle<()>>,
+    connectivity_logger: Option<JoinHandle<()>>,
+    retry_policy: Option<RetryPolicy>,
+    file: Option<PathBuf>,
+    priority: Priority,
+    transforms: Vec<EchoTransform>,
+    caller: Option<String>,
+    accept_language: Option<String>,
+    trace_id: String,
+    session_id: Option<String>,
+    watch_events: bool,
+    event_watcher: Option<JoinHandle<()>>,
+    json_output: bool,
+    /// Cancelled on Ctrl-C: cuts a waiting `start()` short.
+    cancellation: CancellationToken,
 }
 
 impl EchoClientModule {
     /// Creates a new echo client module.
     ///
     /// Note: This is called by the wiring layer (Layer 5).
-    pub fn new(service_provider: EchoClientServiceProvider, message: String) -> Self {
+    pub fn new(service_provider: EchoClientServiceProvider, message: impl Into<Arc<str>>) -> Self {
         Self {
-            id: ModuleID::from("echo-client"),
+            id: ModuleID::from(ECHO_CLIENT_MODULE_ID),
             service_provider,
-            message,
+            message: message.into(),
+            outbox: None,
+            flusher: None,
+            connectivity_logger: None,
+            retry_policy: None,
+            file: None,
+            priority: Priority::default(),
+            transforms: Vec::new(),
+            caller: None,
+            accept_language: None,
+            trace_id: Uuid::new_v4().simple().to_string(),
+            session_id: None,
+            watch_events: false,
+            event_watcher: None,
+            json_output: false,
+            cancellation: CancellationToken::new(),
         }
     }
+
+    /// Aborts `start()` when `token` is cancelled, instead of waiting for
+    /// the echo service until its timeouts elapse.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Enables fire-and-forget delivery through the given outbox.
+    ///
+    /// When the echo service is unreachable, messages are buffered in the
+    /// outbox instead of failing `start()`, and flushed in the background.
+    pub fn with_outbox(mut self, outbox: Arc<EchoOutbox>) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    /// Enables at-least-once delivery via `echo_reliable` with retries.
+    pub fn with_reliable_delivery(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Additionally streams the given file through `echo_file` on start.
+    pub fn with_file(mut self, path: PathBuf) -> Self {
+        self.file = Some(path);
+        self
+    }
+
+    /// Sends every call with the given priority class.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Asks the server to transform the echoed message (see `echo_contract::transform`).
+    pub fn with_transforms(mut self, transforms: Vec<EchoTransform>) -> Self {
+        self.transforms = transforms;
+        self
+    }
+
+    /// Names this client to the server, which accounts echoed bytes per caller.
+    pub fn with_caller(mut self, caller: impl Into<String>) -> Self {
+        self.caller = Some(caller.into());
+        self
+    }
+
+    /// Asks the server for error messages in these languages (an
+    /// `Accept-Language` value such as `de, en;q=0.5`).
+    pub fn with_accept_language(mut self, accept_language: impl Into<String>) -> Self {
+        self.accept_language = Some(accept_language.into());
+        self
+    }
+
+    /// Request context of every call: priority, transforms, caller and
+    /// languages, traced with this module's trace ID and a fresh request ID.
+    fn context(&self) -> RequestContext {
+        let mut context = RequestContext::new(self.priority)
+            .with_transforms(self.transforms.clone())
+            .with_trace(self.trace_id.as_str(), Uuid::new_v4().to_string());
+        if let Some(caller) = &self.caller {
+            context = context.with_caller(caller.clone());
+        }
+        if let Some(accept_language) = &self.accept_language {
+            context = context.with_accept_language(accept_language.clone());
+        }
+        context
+    }
+
+    /// Sends messages through `echo_with_session` in the given session.
+    ///
+    /// Run the client repeatedly with the same ID to watch the server-side
+    /// counter grow, whichever protocol each run uses.
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Logs the echo server's activity events while the module runs.
+    pub fn with_event_watcher(mut self) -> Self {
+        self.watch_events = true;
+        self
+    }
+
+    /// Prints the reply and how it was delivered as a JSON line on stdout.
+    ///
+    /// Sends a plain `echo`, whatever the session and reliable-delivery
+    /// settings, so the reported `CallInfo` covers exactly one call.
+    pub fn with_json_output(mut self) -> Self {
+        self.json_output = true;
+        self
+    }
+
+    /// Subscribes to the server's echo events and logs them in the background.
+    async fn spawn_event_watcher(&self, gateways: Arc<dyn EchoServiceGateways>) -> Result<JoinHandle<()>> {
+        let mut events = TypedServiceClient::events(gateways).call(|events| events.subscribe()).await?;
+        Ok(spawn_tracked(&self.id.to_string(), "event-watcher", async move {
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(event) => info!("[EchoClient] Server event: {} ({} bytes, success={})",
+                        event.method, event.request_bytes, event.success),
+                    Err(e) => warn!("[EchoClient] Event stream error: {}", e),
+                }
+            }
+            info!("[EchoClient] Event stream ended");
+        }))
+    }
+
+    /// Logs connectivity changes of the gateways' gRPC channels.
+    #[cfg(feature = "grpc")]
+    fn spawn_connectivity_logger(&self, pool: &ChannelPool) -> JoinHandle<()> {
+        let mut events = pool.subscribe();
+        spawn_tracked(&self.id.to_string(), "connectivity-logger", async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => match event.state {
+                        ConnectivityState::TransientFailure | ConnectivityState::Failed => {
+                            warn!("[EchoClient] Connection to {} is {:?} (attempt {})",
+                                event.address, event.state, event.attempt)
+                        }
+                        ConnectivityState::Ready => {
+                            info!("[EchoClient] Connection to {} is ready", event.address)
+                        }
+                        _ => {}
+                    },
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("[EchoClient] Missed {} connectivity events", missed)
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Sends the configured message, honoring the reliable-delivery and
+    /// session settings.
+    ///
+    /// Cloning the `Arc<str>` message is a reference-count bump, so retries
+    /// and buffering never copy the message text. The reply is logged in
+    /// the call's context, so the line carries its trace and request IDs.
+    async fn send(&self, service: &dyn EchoService) -> Result<Arc<str>> {
+        let context = self.context();
+        let span = info_span!("client_call", module_id = %self.id,
+            request_id = context.request_id.as_deref().unwrap_or("-"));
+        context.scope(async {
+            let response = match &self.retry_policy {
+                Some(policy) => {
+                    let ack = echo_at_least_once(service, self.message.clone(), policy).await?;
+                    Ok(ack.message)
+                }
+                None => match &self.session_id {
+                    Some(session_id) => {
+                        let echo = service.echo_with_session(session_id.clone(), self.message.clone()).await?;
+                        info!("Session {}: message #{} (previous at {:?})",
+                            echo.session_id, echo.count, echo.previous_seen);
+                        Ok(echo.message)
+                    }
+                    None => service.echo(self.message.clone()).await,
+                },
+            }?;
+            info!("Response: {}", response);
+            Ok(response)
+        }.instrument(span)).await
+    }
+}
+
+/// One line of `--json` output.
+fn call_info_json(response: &str, call_info: &CallInfo) -> serde_json::Value {
+    json!({
+        "response": response,
+        "protocol": protocol_name(&call_info.protocol_used),
+        "endpoint": &call_info.endpoint,
+        "latency_ms": call_info.latency.as_secs_f64() * 1000.0,
+        "attempts": call_info.attempts,
+        "server": &call_info.server_metadata,
+        "protocol_race": call_info.protocol_race.as_ref().map(|race| json!({
+            "winner": protocol_name(&race.winner),
+            "decided_after_ms": race.decided_after.as_secs_f64() * 1000.0,
+            "failures": race.failures.iter()
+                .map(|(protocol, error)| json!({ "protocol": protocol_name(protocol), "error": error }))
+                .collect::<Vec<_>>(),
+        })),
+    })
 }
 
 #[async_trait]
@@ -46,24 +285,113 @@ impl Module for EchoClientModule {
         &self.id
     }
 
+    #[instrument(name = "module", skip_all, fields(module_id = %self.id))]
     async fn start(&mut self) -> Result<()> {
-        info!("[EchoClient] Starting...");
+        info!("Starting (trace {})...", self.trace_id);
         
         // Get gateways from service provider
         let gateways = self.service_provider.get_gateways();
+        #[cfg(feature = "grpc")]
+        {
+            self.connectivity_logger = Some(self.spawn_connectivity_logger(&self.service_provider.channel_pool()));
+        }
+        
+        // Subscribe before sending, so our own calls show up too
+        if self.watch_events {
+            let watcher = self.spawn_event_watcher(gateways.clone());
+            match until_cancelled(&self.cancellation, "subscribing to server events", watcher).await {
+                Ok(watcher) => self.event_watcher = Some(watcher),
+                Err(e) if self.cancellation.is_cancelled() => return Err(e),
+                Err(e) => warn!("Can't watch server events: {}", e),
+            }
+        }
         
-        // Get service
-        let service = gateways.get_service(hsu_common::Protocol::Auto).await?;
+        let echo = TypedServiceClient::echo(gateways.clone());
         
-        info!("[EchoClient] Calling echo service...");
-        let response = service.echo(self.message.clone()).await?;
-        info!("[EchoClient] Response: {}", response);
+        let Some(outbox) = self.outbox.clone() else {
+            // Get service (may wait on the registry)
+            let service = until_cancelled(&self.cancellation, "waiting for the echo service", echo.service()).await?;
+            
+            info!("Calling echo service...");
+            if self.json_output {
+                let call = self.context().scope(gateways.echo_with_info(hsu_common::Protocol::Auto, self.message.clone()));
+                let (response, call_info) = until_cancelled(&self.cancellation, "calling the echo service", call).await?;
+                println!("{}", call_info_json(&response, &call_info));
+            } else {
+                until_cancelled(&self.cancellation, "calling the echo service", self.send(service.as_ref())).await?;
+            }
+            
+            if let Some(path) = &self.file {
+                info!("Streaming file {}...", path.display());
+                let echo = echo_file(service.as_ref(), path, DEFAULT_CHUNK_SIZE);
+                let digest = until_cancelled(&self.cancellation, "streaming the file", echo).await?;
+                info!("File digest: {} bytes in {} chunks, sha256={}",
+                    digest.byte_count, digest.chunk_count, digest.sha256);
+            }
+            
+            return Ok(());
+        };
+        
+        // Fire-and-forget: buffer the message if the service is unreachable
+        let send = async {
+            let service = echo.service().await?;
+            self.send(service.as_ref()).await
+        };
+        let result = until_cancelled(&self.cancellation, "calling the echo service", send).await;
+        if let Err(e) = result {
+            if !is_buffered(&e) {
+                return Err(e);
+            }
+            // Still fire-and-forget: the message goes to the outbox rather
+            // than vanishing with the start, and a stop() still flushes it
+            if self.cancellation.is_cancelled() {
+                outbox.submit(self.message.clone())?;
+                return Err(e);
+            }
+            warn!("Echo service unreachable ({}), buffering message", e);
+            outbox.submit(self.message.clone())?;
+        }
+        
+        self.flusher = Some(outbox.spawn_flusher(&self.id, gateways));
         
         Ok(())
     }
 
+    #[instrument(name = "module", skip_all, fields(module_id = %self.id))]
     async fn stop(&mut self) -> Result<()> {
-        info!("[EchoClient] Stopping...");
+        info!("Stopping...");
+        
+        if let Some(flusher) = self.flusher.take() {
+            flusher.abort();
+        }
+        let gateways = self.service_provider.get_gateways();
+        if let Some(outbox) = &self.outbox {
+            // One last attempt, so buffered messages aren't lost silently
+            if outbox.depth() > 0 {
+                let flush = async {
+                    let service = TypedServiceClient::echo(gateways.clone()).service().await?;
+                    outbox.flush(service.as_ref()).await
+                };
+                match tokio::time::timeout(FINAL_FLUSH_TIMEOUT, flush).await {
+                    Ok(Ok(sent)) => info!("✅ Flushed {} buffered messages", sent),
+                    Ok(Err(e)) => warn!("Final outbox flush failed: {}", e),
+                    Err(_) => warn!("Final outbox flush timed out after {:?}", FINAL_FLUSH_TIMEOUT),
+                }
+            }
+            let stats = outbox.stats();
+            info!("Outbox stats: depth={}, enqueued={}, delivered={}, dropped={}",
+                stats.depth, stats.enqueued, stats.delivered, stats.dropped);
+        }
+        
+        // Ends the event watch and the connectivity log, closes channels
+        gateways.close().await?;
+        for mut task in [self.event_watcher.take(), self.connectivity_logger.take()].into_iter().flatten() {
+            if tokio::time::timeout(TASK_STOP_TIMEOUT, &mut task).await.is_err() {
+                warn!("Background task didn't end after close, aborting it");
+                task.abort();
+            }
+        }
+        
         Ok(())
     }
 }
@@ -0,0 +1,341 @@
+//! Client-side Outbox for Echo Messages (Layer 3)
+//!
+//! # Architecture
+//!
+//! A bounded, in-memory buffer for **fire-and-forget** messages.
+//!
+//! ```text
+//! EchoClientModule
+//!     ↓ submit("Hello!")        (echo service unreachable)
+//! EchoOutbox [msg1, msg2, ...]  (bounded, overflow policy)
+//!     ↓ flush loop              (every flush_interval)
+//! EchoServiceGateways → EchoService::echo()
+//! ```
+//!
+//! Messages submitted while the echo service is down are kept here and
+//! delivered in order once connectivity returns. The outbox never blocks
+//! the caller - when it is full, the configured [`OverflowPolicy`] decides
+//! what gets dropped.
+//!
+//! Unreachable is worth waiting out; `PROTOCOL_UNSUPPORTED` is not - no
+//! gateway could ever deliver the messages. The protocols a module's
+//! gateways support are fixed once it is wired, so the client module
+//! finds out on its first call and fails instead of buffering (see
+//! [`is_buffered`]); the flush loop only ever meets outages.
+//!
+//! This is a useful template for any fire-and-forget module!
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use echo_api::spawn_tracked;
+use echo_contract::{is_protocol_unsupported, EchoService, EchoServiceGateways};
+use hsu_common::{Error, ModuleID, Protocol, Result};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// What to do when a message is submitted to a full outbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Evict the oldest buffered message to make room for the new one.
+    DropOldest,
+    /// Silently discard the new message.
+    DropNewest,
+    /// Discard the new message and return an error to the caller.
+    Reject,
+}
+
+/// Whether a message whose delivery failed with `error` belongs in the
+/// outbox: not if no gateway could ever deliver it.
+pub fn is_buffered(error: &Error) -> bool {
+    !is_protocol_unsupported(error)
+}
+
+/// Configuration for the client outbox.
+#[derive(Debug, Clone)]
+pub struct OutboxConfig {
+    /// Maximum number of buffered messages.
+    pub capacity: usize,
+    /// Behavior when the outbox is full.
+    pub overflow_policy: OverflowPolicy,
+    /// How often the background flusher retries delivery.
+    pub flush_interval: Duration,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow_policy: OverflowPolicy::DropOldest,
+            flush_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Point-in-time snapshot of outbox metrics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutboxStats {
+    /// Messages currently waiting for delivery.
+    pub depth: usize,
+    /// Messages accepted into the outbox.
+    pub enqueued: u64,
+    /// Messages successfully delivered.
+    pub delivered: u64,
+    /// Messages lost due to overflow.
+    pub dropped: u64,
+}
+
+/// Bounded buffer of messages waiting for the echo service.
+///
+/// # Rust Learning Note
+///
+/// ## Why std::sync::Mutex here?
+///
+/// The lock is only held for short, non-async sections (push/pop), never
+/// across an `.await`. For that case `std::sync::Mutex` is cheaper than
+/// `tokio::sync::Mutex` and perfectly safe in async code.
+pub struct EchoOutbox {
+    config: OutboxConfig,
+    queue: Mutex<VecDeque<Arc<str>>>,
+    enqueued: AtomicU64,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl EchoOutbox {
+    /// Creates an empty outbox.
+    pub fn new(config: OutboxConfig) -> Self {
+        Self {
+            queue: Mutex::new(VecDeque::with_capacity(config.capacity)),
+            config,
+            enqueued: AtomicU64::new(0),
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Buffers a message for later delivery.
+    ///
+    /// Returns an error only when the outbox is full and the policy is
+    /// [`OverflowPolicy::Reject`].
+    pub fn submit(&self, message: Arc<str>) -> Result<()> {
+        let mut queue = self.queue.lock().unwrap();
+
+        if queue.len() >= self.config.capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            match self.config.overflow_policy {
+                OverflowPolicy::DropOldest => {
+                    queue.pop_front();
+                    warn!("[EchoOutbox] Outbox full, dropped oldest message");
+                }
+                OverflowPolicy::DropNewest => {
+                    warn!("[EchoOutbox] Outbox full, dropped new message");
+                    return Ok(());
+                }
+                OverflowPolicy::Reject => {
+                    return Err(Error::Validation {
+                        message: format!("Echo outbox is full ({} messages)", self.config.capacity),
+                    });
+                }
+            }
+        }
+
+        queue.push_back(message);
+        self.enqueued.fetch_add(1, Ordering::Relaxed);
+        debug!("[EchoOutbox] Message buffered, depth={}", queue.len());
+        Ok(())
+    }
+
+    /// Delivers buffered messages in order.
+    ///
+    /// Stops at the first failure, keeping the failed message at the head
+    /// of the queue so ordering is preserved. Returns the number of
+    /// messages delivered.
+    ///
+    /// A message leaves the queue only once delivered: a flush cancelled
+    /// mid-call (aborted flusher, timed-out final flush) keeps it, to be
+    /// sent again - at least once, never lost.
+    pub async fn flush(&self, service: &dyn EchoService) -> Result<usize> {
+        let mut sent = 0;
+
+        loop {
+            // Peek under the lock, release it before awaiting
+            let next = self.queue.lock().unwrap().front().cloned();
+            let Some(message) = next else {
+                return Ok(sent);
+            };
+
+            let response = service.echo(message.clone()).await?;
+            {
+                let mut queue = self.queue.lock().unwrap();
+                // DropOldest may have evicted it (and counted it) meanwhile
+                if queue.front().is_some_and(|front| Arc::ptr_eq(front, &message)) {
+                    queue.pop_front();
+                }
+            }
+            self.delivered.fetch_add(1, Ordering::Relaxed);
+            sent += 1;
+            debug!("[EchoOutbox] Delivered buffered message, response: {}", response);
+        }
+    }
+
+    /// Returns the number of buffered messages.
+    pub fn depth(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    /// Returns a snapshot of the outbox metrics.
+    pub fn stats(&self) -> OutboxStats {
+        OutboxStats {
+            depth: self.depth(),
+            enqueued: self.enqueued.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Spawns the background flusher.
+    ///
+    /// Every `flush_interval` the flusher resolves the echo service and
+    /// drains the outbox. Failures are logged and retried on the next tick.
+    ///
+    /// The task is tracked under `module` in the task registry.
+    pub fn spawn_flusher(
+        self: Arc<Self>,
+        module: &ModuleID,
+        gateways: Arc<dyn EchoServiceGateways>,
+    ) -> JoinHandle<()> {
+        spawn_tracked(&module.to_string(), "outbox-flusher", async move {
+            let mut ticker = tokio::time::interval(self.config.flush_interval);
+            loop {
+                ticker.tick().await;
+
+                if self.depth() == 0 {
+                    continue;
+                }
+
+                let result = match gateways.get_service(Protocol::Auto).await {
+                    Ok(service) => self.flush(service.as_ref()).await,
+                    Err(e) => Err(e),
+                };
+
+                match result {
+                    Ok(sent) => info!("[EchoOutbox] ✅ Flushed {} buffered messages", sent),
+                    Err(e) => debug!("[EchoOutbox] Echo service still unreachable: {}", e),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use echo_contract::EchoAck;
+    use std::sync::atomic::AtomicBool;
+
+    struct MockService {
+        up: AtomicBool,
+        received: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl EchoService for MockService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            if !self.up.load(Ordering::SeqCst) {
+                return Err(Error::Protocol("service down".to_string()));
+            }
+            self.received.lock().unwrap().push(message.to_string());
+            Ok(message)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            let message = self.echo(message).await?;
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+    }
+
+    fn outbox(capacity: usize, overflow_policy: OverflowPolicy) -> EchoOutbox {
+        EchoOutbox::new(OutboxConfig {
+            capacity,
+            overflow_policy,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let outbox = outbox(2, OverflowPolicy::DropOldest);
+        outbox.submit("a".into()).unwrap();
+        outbox.submit("b".into()).unwrap();
+        outbox.submit("c".into()).unwrap();
+
+        let stats = outbox.stats();
+        assert_eq!(stats.depth, 2);
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(&**outbox.queue.lock().unwrap().front().unwrap(), "b");
+    }
+
+    #[test]
+    fn test_reject_when_full() {
+        let outbox = outbox(1, OverflowPolicy::Reject);
+        outbox.submit("a".into()).unwrap();
+        assert!(outbox.submit("b".into()).is_err());
+        assert_eq!(outbox.stats().dropped, 1);
+    }
+
+    #[test]
+    fn test_unsupported_protocols_are_not_buffered() {
+        assert!(is_buffered(&Error::Protocol("service down".to_string())));
+        let unsupported = echo_contract::EchoErrorKind::ProtocolUnsupported.message("Http: not wired");
+        assert!(!is_buffered(&Error::Protocol(unsupported)));
+    }
+
+    #[tokio::test]
+    async fn test_flush_preserves_order_across_outage() {
+        let outbox = outbox(8, OverflowPolicy::DropNewest);
+        let service = MockService {
+            up: AtomicBool::new(false),
+            received: Mutex::new(Vec::new()),
+        };
+
+        outbox.submit("first".into()).unwrap();
+        outbox.submit("second".into()).unwrap();
+
+        assert!(outbox.flush(&service).await.is_err());
+        assert_eq!(outbox.depth(), 2);
+
+        service.up.store(true, Ordering::SeqCst);
+        assert_eq!(outbox.flush(&service).await.unwrap(), 2);
+        assert_eq!(*service.received.lock().unwrap(), vec!["first", "second"]);
+        assert_eq!(outbox.stats().delivered, 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_flush_keeps_the_message() {
+        /// Never answers.
+        struct Hung;
+
+        #[async_trait]
+        impl EchoService for Hung {
+            async fn echo(&self, _message: Arc<str>) -> Result<Arc<str>> {
+                std::future::pending().await
+            }
+        }
+
+        let outbox = outbox(8, OverflowPolicy::DropNewest);
+        outbox.submit("in flight".into()).unwrap();
+        assert!(tokio::time::timeout(Duration::from_millis(10), outbox.flush(&Hung)).await.is_err());
+
+        let stats = outbox.stats();
+        assert_eq!((stats.depth, stats.delivered, stats.dropped), (1, 0, 0));
+    }
+}
@@ -0,0 +1,163 @@
+//! At-least-once Echo Delivery (Layer 3)
+//!
+//! # Architecture
+//!
+//! The client half of `EchoService::echo_reliable`:
+//!
+//! 1. Generate **one** idempotency key per logical message
+//! 2. Retry with exponential backoff until the server acknowledges
+//! 3. Reuse the same key on every attempt
+//!
+//! The server deduplicates by key, so a retry after a lost response is
+//! acknowledged (`duplicate = true`) instead of being processed twice.
+//!
+//! An overloaded server answers with a `retry_after` hint; the next
+//! attempt waits at least that long instead of hammering it.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use echo_contract::{retry_after, EchoAck, EchoService};
+use hsu_common::Result;
+use tracing::{debug, warn};
+
+/// Retry policy for at-least-once delivery.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts (including the first one).
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for the (doubling) retry delay.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Generates a fresh idempotency key.
+pub fn new_idempotency_key() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Sends `message` until the server acknowledges it or attempts run out.
+///
+/// All attempts share one idempotency key.
+pub async fn echo_at_least_once(
+    service: &dyn EchoService,
+    message: Arc<str>,
+    policy: &RetryPolicy,
+) -> Result<EchoAck> {
+    let idempotency_key = new_idempotency_key();
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 1;
+
+    loop {
+        match service.echo_reliable(message.clone(), idempotency_key.clone()).await {
+            Ok(ack) => {
+                debug!("[EchoReliable] Acknowledged key={} after {} attempt(s), duplicate={}",
+                    ack.idempotency_key, attempt, ack.duplicate);
+                return Ok(ack);
+            }
+            Err(e) if attempt < policy.max_attempts => {
+                // Honor the server's back-off hint when it sheds load
+                let delay = retry_after(&e).map_or(backoff, |hint| hint.max(backoff));
+                warn!("[EchoReliable] Attempt {} failed ({}), retrying in {:?}", attempt, e, delay);
+                tokio::time::sleep(delay).await;
+                backoff = (backoff * 2).min(policy.max_backoff);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use hsu_common::Error;
+    use std::sync::Mutex;
+
+    /// Fails the first `failures` calls, recording every key it sees.
+    struct FlakyService {
+        failures: Mutex<u32>,
+        keys: Mutex<Vec<String>>,
+        /// Fail with this back-off hint (overloaded) instead of a reset.
+        retry_after: Option<Duration>,
+    }
+
+    #[async_trait]
+    impl EchoService for FlakyService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            Ok(message)
+        }
+
+        async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+            self.keys.lock().unwrap().push(idempotency_key.clone());
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(match self.retry_after {
+                    Some(hint) => echo_contract::overloaded(hint, "queue full"),
+                    None => Error::Protocol("connection reset".to_string()),
+                });
+            }
+            Ok(EchoAck { message, idempotency_key, duplicate: false })
+        }
+
+        async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+            Ok(payload)
+        }
+    }
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_with_same_key() {
+        let service = FlakyService { failures: Mutex::new(2), keys: Mutex::new(Vec::new()), retry_after: None };
+
+        let ack = echo_at_least_once(&service, "Hello".into(), &fast_policy(5)).await.unwrap();
+
+        let keys = service.keys.lock().unwrap();
+        assert_eq!(keys.len(), 3);
+        assert!(keys.iter().all(|k| *k == ack.idempotency_key));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let service = FlakyService { failures: Mutex::new(10), keys: Mutex::new(Vec::new()), retry_after: None };
+
+        assert!(echo_at_least_once(&service, "Hello".into(), &fast_policy(3)).await.is_err());
+        assert_eq!(service.keys.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_retry_after_hint() {
+        let service = FlakyService {
+            failures: Mutex::new(1),
+            keys: Mutex::new(Vec::new()),
+            retry_after: Some(Duration::from_millis(30)),
+        };
+
+        let started = std::time::Instant::now();
+        echo_at_least_once(&service, "Hello".into(), &fast_policy(2)).await.unwrap();
+
+        // The policy's 1ms backoff is stretched to the server's hint
+        assert!(started.elapsed() >= Duration::from_millis(30));
+    }
+}
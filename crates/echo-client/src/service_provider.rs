@@ -12,11 +12,18 @@
 //! That's because `new_echo_service_gateways()` is **echo-specific** Layer 5 code
 //! that intrinsically knows it's for the "echo" module. The target module ID is
 //! not configuration - it's the **identity** of the echo API layer itself.
+//!
+//! The gateways are kept in a [`ServiceMap`], keyed by contract: a client
+//! that also consumes another domain service adds its gateways with
+//! [`with_gateways`](EchoClientServiceProvider::with_gateways) instead of
+//! growing this struct.
 
 use std::sync::Arc;
 use echo_contract::EchoServiceGateways;
 use hsu_module_api::ServiceConnector;
-use echo_api::new_echo_service_gateways;
+use echo_api::{new_echo_service_gateways_with_options, GatewayOptions, ServiceMap};
+#[cfg(feature = "grpc")]
+use echo_api_grpc::ChannelPool;
 use tracing::debug;
 
 /// Service provider for Echo client module.
@@ -35,7 +42,9 @@ use tracing::debug;
 /// ```
 #[derive(Clone)]
 pub struct EchoClientServiceProvider {
-    gateways: Arc<dyn EchoServiceGateways>,
+    gateways: ServiceMap,
+    #[cfg(feature = "grpc")]
+    channel_pool: Arc<ChannelPool>,
 }
 
 impl EchoClientServiceProvider {
@@ -46,16 +55,52 @@ impl EchoClientServiceProvider {
     pub fn new(
         service_connector: Arc<dyn ServiceConnector>,
     ) -> Self {
-        debug!("[EchoClientServiceProvider] Creating echo service gateways");
+        Self::with_options(service_connector, GatewayOptions::default())
+    }
+    
+    /// Creates a client service provider whose gateways use `options`.
+    pub fn with_options(
+        service_connector: Arc<dyn ServiceConnector>,
+        options: GatewayOptions,
+    ) -> Self {
+        debug!("[EchoClientServiceProvider] Creating echo service gateways: {:?}", options);
         
-        let gateways = new_echo_service_gateways(service_connector);
+        #[cfg(feature = "grpc")]
+        let channel_pool = options.channel_pool.clone();
+        let gateways = ServiceMap::new()
+            .with::<dyn EchoServiceGateways>(new_echo_service_gateways_with_options(service_connector, options));
         
-        Self { gateways }
+        Self {
+            gateways,
+            #[cfg(feature = "grpc")]
+            channel_pool,
+        }
+    }
+    
+    /// Adds the gateways of another contract `T` (e.g. `dyn MonitorServiceGateways`).
+    pub fn with_gateways<T: ?Sized + Send + Sync + 'static>(mut self, gateways: Arc<T>) -> Self {
+        self.gateways.insert(gateways);
+        self
     }
     
-    /// Gets the service gateways.
+    /// Gets the gateways of contract `T`, if provided.
+    pub fn gateways<T: ?Sized + Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.gateways.get::<T>()
+    }
+    
+    /// Gets the echo service gateways.
+    ///
+    /// Shim for `gateways::<dyn EchoServiceGateways>()`, which is always provided.
     pub fn get_gateways(&self) -> Arc<dyn EchoServiceGateways> {
-        self.gateways.clone()
+        self.gateways::<dyn EchoServiceGateways>().expect("echo gateways are provided on creation")
+    }
+    
+    /// Gets the pool behind direct-address gRPC gateways.
+    ///
+    /// Subscribe to it to observe connectivity changes.
+    #[cfg(feature = "grpc")]
+    pub fn channel_pool(&self) -> Arc<ChannelPool> {
+        self.channel_pool.clone()
     }
 }
 
@@ -12,9 +12,20 @@
 //! This is MODULE-specific, not application-specific!
 //! Each module has its own wiring that defines how it integrates with the framework.
 
-use std::sync::{Arc, Once};
+use std::path::PathBuf;
+use std::sync::{Arc, Once, OnceLock};
+use std::time::Duration;
 use std::collections::HashMap;
 use hsu_common::{ModuleID, Result};
+use echo_api::{
+    BatchingConfig, CancellationToken, DependencyRegistry, Discovery, GatewayOptions, HedgingPolicy, LatencyBudgets,
+    ModuleDependencies, PanicGuardModule, PanicPolicy, PayloadKey, ResponseValidator, StartupCoordinator,
+};
+#[cfg(feature = "grpc")]
+use echo_api::{MirrorConfig, WarmStandbyConfig};
+#[cfg(feature = "grpc")]
+use echo_api_grpc::{ChannelPool, GrpcChannelOptions, SigningKey};
+use echo_contract::{EchoServiceId, EchoTransform, Priority, ECHO_CLIENT_MODULE_ID};
 use hsu_module_api::{
     ServiceProviderHandle, ServiceConnector, 
     new_module_descriptor, register_module, Module,
@@ -23,20 +34,125 @@ use tracing::{debug, info};
 
 use crate::service_provider::EchoClientServiceProvider;
 use crate::module::EchoClientModule;
+use crate::outbox::{EchoOutbox, OutboxConfig};
+use crate::reliable::RetryPolicy;
 
 /// Configuration for Echo client module.
 pub struct EchoClientModuleConfig {
     pub module_id: ModuleID,
+    /// Buffer messages while the echo service is unreachable (disabled if `None`).
+    pub outbox: Option<OutboxConfig>,
+    /// Use at-least-once delivery with this retry policy (plain `echo` if `None`).
+    pub reliable_delivery: Option<RetryPolicy>,
+    /// File to stream through `echo_file` on start.
+    pub file: Option<PathBuf>,
+    /// Per-call deadline for echo calls, Direct or gRPC (unbounded if `None`).
+    pub call_deadline: Option<Duration>,
+    /// Dial this gRPC server directly (`host:port`) instead of using the
+    /// framework-created channel.
+    pub grpc_address: Option<String>,
+    /// Keepalive, idle-eviction and reconnect settings for `grpc_address` channels.
+    #[cfg(feature = "grpc")]
+    pub grpc_channel: GrpcChannelOptions,
+    /// How to find the echo server without `grpc_address` (registry or mDNS).
+    pub discovery: Discovery,
+    /// Hedge slow idempotent gRPC calls (disabled if `None`).
+    pub hedging: Option<HedgingPolicy>,
+    /// Let identical concurrent echo calls share one request.
+    pub coalescing: bool,
+    /// Send gRPC echo calls in batches (unary if `None`).
+    pub batching: Option<BatchingConfig>,
+    /// Race gRPC against JSON/HTTP on the first call and keep the winner.
+    pub protocol_racing: bool,
+    /// Checks applied to every reply; a rejected reply fails the call.
+    pub validators: Vec<ResponseValidator>,
+    /// Checksum messages end to end; corruption fails the call.
+    pub integrity: bool,
+    /// Seal messages with this key, shared with the echo server module.
+    pub payload_key: Option<Arc<PayloadKey>>,
+    /// Sign every gRPC request as this key's caller (replacing `caller`).
+    #[cfg(feature = "grpc")]
+    pub signing_key: Option<Arc<SigningKey>>,
+    /// Copy echo calls to a second endpoint and compare its replies (no
+    /// mirroring if `None`).
+    #[cfg(feature = "grpc")]
+    pub mirror: Option<MirrorConfig>,
+    /// How long each echo method may take; slower calls are logged and
+    /// counted (`echo_latency_budget_violations_total`), not failed.
+    pub latency_budgets: LatencyBudgets,
+    /// Keep a second transport warm and resend unavailable calls over it
+    /// (no standby if `None`).
+    #[cfg(feature = "grpc")]
+    pub warm_standby: Option<WarmStandbyConfig>,
+    /// Priority class of this client's calls.
+    pub priority: Priority,
+    /// Transformations the server applies to the echoed message.
+    pub transforms: Vec<EchoTransform>,
+    /// Caller (tenant) name sent with every call, for per-caller byte
+    /// accounting on the server (anonymous if `None`).
+    pub caller: Option<String>,
+    /// Languages to receive error messages in, as an `Accept-Language`
+    /// value (English if `None`).
+    pub accept_language: Option<String>,
+    /// Send through `echo_with_session` in this session (plain `echo` if `None`).
+    pub session_id: Option<String>,
+    /// Log the echo server's activity events while running.
+    pub watch_events: bool,
+    /// Print the reply and its `CallInfo` as a JSON line on stdout (plain
+    /// `echo` only: no session, no reliable delivery).
+    pub json_output: bool,
+    /// What to do when the module panics in `start`/`stop`.
+    pub panic_policy: PanicPolicy,
+    /// Cancelled on Ctrl-C (see [`echo_api::cancel_on_ctrl_c`]): aborts a
+    /// `start()` still waiting for the echo service.
+    pub cancellation: CancellationToken,
 }
 
 impl Default for EchoClientModuleConfig {
     fn default() -> Self {
         Self {
-            module_id: ModuleID::from("echo-client"),
+            module_id: ModuleID::from(ECHO_CLIENT_MODULE_ID),
+            outbox: None,
+            reliable_delivery: None,
+            file: None,
+            call_deadline: None,
+            grpc_address: None,
+            #[cfg(feature = "grpc")]
+            grpc_channel: GrpcChannelOptions::default(),
+            discovery: Discovery::default(),
+            hedging: None,
+            coalescing: false,
+            batching: None,
+            protocol_racing: false,
+            validators: Vec::new(),
+            integrity: false,
+            payload_key: None,
+            #[cfg(feature = "grpc")]
+            signing_key: None,
+            #[cfg(feature = "grpc")]
+            mirror: None,
+            latency_budgets: LatencyBudgets::default(),
+            #[cfg(feature = "grpc")]
+            warm_standby: None,
+            priority: Priority::default(),
+            transforms: Vec::new(),
+            caller: None,
+            accept_language: None,
+            session_id: None,
+            watch_events: false,
+            json_output: false,
+            panic_policy: PanicPolicy::default(),
+            cancellation: CancellationToken::new(),
         }
     }
 }
 
+/// Module configuration captured at init time.
+///
+/// The framework factories are plain function pointers, so they can't
+/// capture the config - they read it from here instead.
+static MODULE_CONFIG: OnceLock<EchoClientModuleConfig> = OnceLock::new();
+
 /// Factory function for creating the service provider.
 ///
 /// This is a **function pointer** (not a closure) to match the framework API.
@@ -51,7 +167,32 @@ fn create_service_provider(
 ) -> ServiceProviderHandle {
     debug!("[EchoClientModule] Creating service provider");
     
-    let service_provider = EchoClientServiceProvider::new(service_connector);
+    let options = match MODULE_CONFIG.get() {
+        Some(config) => GatewayOptions {
+            deadline: config.call_deadline,
+            grpc_address: config.grpc_address.clone(),
+            #[cfg(feature = "grpc")]
+            channel_pool: Arc::new(ChannelPool::new(config.grpc_channel.clone())),
+            hedging: config.hedging.clone(),
+            discovery: config.discovery.clone(),
+            coalescing: config.coalescing,
+            batching: config.batching.clone(),
+            protocol_racing: config.protocol_racing,
+            validators: config.validators.clone(),
+            integrity: config.integrity,
+            #[cfg(feature = "grpc")]
+            signing_key: config.signing_key.clone(),
+            payload_key: config.payload_key.clone(),
+            #[cfg(feature = "grpc")]
+            mirror: config.mirror.clone(),
+            latency_budgets: config.latency_budgets.clone(),
+            #[cfg(feature = "grpc")]
+            warm_standby: config.warm_standby.clone(),
+            ..GatewayOptions::default()
+        },
+        None => GatewayOptions::default(),
+    };
+    let service_provider = EchoClientServiceProvider::with_options(service_connector, options);
     
     // Store the gateways in the map (keyed by target module ID)
     let gateways = service_provider.get_gateways();
@@ -76,14 +217,68 @@ fn create_service_provider(
 fn create_module(service_provider: EchoClientServiceProvider) -> (Box<dyn Module>, ()) {
     debug!("[EchoClientModule] Creating module");
     
-    let module = EchoClientModule::new(
+    let mut module = EchoClientModule::new(
         service_provider,
         "Hello from Rust client!".to_string(),
     );
     
+    if let Some(outbox_config) = MODULE_CONFIG.get().and_then(|c| c.outbox.clone()) {
+        debug!("[EchoClientModule] Outbox enabled: capacity={}, policy={:?}",
+            outbox_config.capacity, outbox_config.overflow_policy);
+        module = module.with_outbox(Arc::new(EchoOutbox::new(outbox_config)));
+    }
+    
+    if let Some(policy) = MODULE_CONFIG.get().and_then(|c| c.reliable_delivery.clone()) {
+        debug!("[EchoClientModule] Reliable delivery enabled: max_attempts={}", policy.max_attempts);
+        module = module.with_reliable_delivery(policy);
+    }
+    
+    if let Some(path) = MODULE_CONFIG.get().and_then(|c| c.file.clone()) {
+        module = module.with_file(path);
+    }
+    
+    if let Some(priority) = MODULE_CONFIG.get().map(|c| c.priority) {
+        module = module.with_priority(priority);
+    }
+    
+    if let Some(transforms) = MODULE_CONFIG.get().map(|c| c.transforms.clone()) {
+        module = module.with_transforms(transforms);
+    }
+    
+    if let Some(caller) = MODULE_CONFIG.get().and_then(|c| c.caller.clone()) {
+        module = module.with_caller(caller);
+    }
+    
+    if let Some(accept_language) = MODULE_CONFIG.get().and_then(|c| c.accept_language.clone()) {
+        module = module.with_accept_language(accept_language);
+    }
+    
+    if let Some(session_id) = MODULE_CONFIG.get().and_then(|c| c.session_id.clone()) {
+        module = module.with_session(session_id);
+    }
+    
+    if MODULE_CONFIG.get().is_some_and(|c| c.watch_events) {
+        module = module.with_event_watcher();
+    }
+    
+    if MODULE_CONFIG.get().is_some_and(|c| c.json_output) {
+        module = module.with_json_output();
+    }
+    
+    // Its own child of the process token, so the coordinator can cancel just this start
+    let cancellation = MODULE_CONFIG.get().map(|c| c.cancellation.child_token()).unwrap_or_default();
+    module = module.with_cancellation(cancellation.clone());
+    
     let handlers = (); // Client doesn't provide handlers
     
-    (Box::new(module), handlers)
+    let panic_policy = MODULE_CONFIG.get().map(|c| c.panic_policy).unwrap_or_default();
+    let module = PanicGuardModule::new(Box::new(module), panic_policy);
+    (Box::new(StartupCoordinator::global().coordinate(Box::new(module), cancellation)), handlers)
+}
+
+/// What the client declares: it calls the echo service, nothing more.
+fn module_dependencies() -> ModuleDependencies {
+    ModuleDependencies::default().consumes_echo(EchoServiceId::Service)
 }
 
 static INIT: Once = Once::new();
@@ -120,6 +315,8 @@ pub fn init_echo_client_module(config: EchoClientModuleConfig) -> Result<()> {
         );
         
         register_module(config.module_id.clone(), descriptor);
+        DependencyRegistry::global().declare(&config.module_id, module_dependencies());
+        let _ = MODULE_CONFIG.set(config);
         
         info!("[EchoClientModule] ✅ Module registered successfully");
     });
@@ -127,3 +324,20 @@ pub fn init_echo_client_module(config: EchoClientModuleConfig) -> Result<()> {
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_api::{echo_gateway_service_ids, WiringDescriptor};
+    use echo_contract::ECHO_MODULE_ID;
+
+    #[test]
+    fn test_wiring_is_consistent() {
+        let config = EchoClientModuleConfig::default();
+
+        // No registrar, no direct closure, no gateways of its own
+        WiringDescriptor::new(config.module_id.to_string(), module_dependencies())
+            .with_consumed_gateways(ECHO_MODULE_ID, echo_gateway_service_ids())
+            .assert_consistent();
+    }
+}
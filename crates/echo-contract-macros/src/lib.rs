@@ -0,0 +1,339 @@
+//! Contract Codegen (Layer 3)
+//!
+//! # Architecture
+//!
+//! Every contract used to come with the same three hand-written pieces:
+//! a handlers struct, a gateways trait and a direct closure enabler.
+//! [`macro@hsu_contract`] generates all three from the service trait:
+//!
+//! ```text
+//! #[hsu_contract(events = EchoEvents, direct_closure = echo_direct_closure_enabler)]
+//! pub trait EchoService { ... }
+//!     ├── pub struct EchoServiceHandlers { service, events }   + new(), with_events()
+//!     ├── pub trait EchoServiceGateways { module_id, service_ids, enable_direct_closure,
+//!     │                                   get_service, get_events, close }
+//!     └── pub fn echo_direct_closure_enabler(DirectClosureEnablerOptions<..>)
+//! ```
+//!
+//! Gateway methods only one contract has (the echo gateways'
+//! `capabilities`, `echo_with_info`) are passed in `gateway_items`.
+//!
+//! Generated code reaches `async_trait`, `tracing` and the `hsu_common`
+//! and `hsu_module_api` types through `echo_contract::__private`, so the
+//! contract crate needs nothing else in scope.
+//!
+//! # Rust Learning Note
+//!
+//! An attribute macro gets the tokens of the item it's on and returns
+//! the tokens that replace it: here the trait itself, unchanged, plus
+//! the generated items. It runs in the compiler, so it can't look at
+//! other items; everything it needs (the events trait, extra gateway
+//! methods) comes in its arguments.
+//!
+//! ## Comparison with Golang
+//!
+//! The Go HSU examples generate these from the proto service with
+//! `protoc` plugins; here the Rust trait is the source of truth.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, Ident, ItemTrait, Path, Token, TraitItem};
+
+/// Generates the handlers struct, gateways trait and direct closure
+/// enabler of a service trait.
+///
+/// ```ignore
+/// #[hsu_contract(
+///     events = EchoEvents,                        // handlers.events, gateways.get_events()
+///     direct_closure = echo_direct_closure_enabler,
+///     gateway_items = {
+///         /// Describes what each protocol can do.
+///         fn capabilities(&self) -> GatewayCapabilities;
+///     },
+/// )]
+/// #[async_trait]
+/// pub trait EchoService: Send + Sync { ... }
+/// ```
+///
+/// Options (all optional): `handlers = Name`, `gateways = Name` and
+/// `direct_closure = name` rename the generated items (default
+/// `<Trait>Handlers`, `<Trait>Gateways`, `<trait>_direct_closure_enabler`),
+/// `events = Trait` adds an events service next to the main one, and
+/// `gateway_items = { ... }` adds trait items to the gateways.
+#[proc_macro_attribute]
+pub fn hsu_contract(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(args as ContractArgs);
+    let service = syn::parse_macro_input!(item as ItemTrait);
+    expand_contract(args, service).into()
+}
+
+/// `#[hsu_contract(...)]` options.
+#[derive(Default)]
+struct ContractArgs {
+    handlers: Option<Ident>,
+    gateways: Option<Ident>,
+    direct_closure: Option<Ident>,
+    events: Option<Path>,
+    gateway_items: Vec<TraitItem>,
+}
+
+impl Parse for ContractArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self::default();
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "handlers" => args.handlers = Some(input.parse()?),
+                "gateways" => args.gateways = Some(input.parse()?),
+                "direct_closure" => args.direct_closure = Some(input.parse()?),
+                "events" => args.events = Some(input.parse()?),
+                "gateway_items" => {
+                    let items;
+                    braced!(items in input);
+                    while !items.is_empty() {
+                        args.gateway_items.push(items.parse()?);
+                    }
+                }
+                other => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!("unknown hsu_contract option `{}` (expected handlers, gateways, direct_closure, events or gateway_items)", other),
+                    ))
+                }
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(args)
+    }
+}
+
+fn expand_contract(args: ContractArgs, service: ItemTrait) -> TokenStream2 {
+    let private = quote!(::echo_contract::__private);
+    let vis = &service.vis;
+    let name = &service.ident;
+    let handlers = args.handlers.unwrap_or_else(|| format_ident!("{}Handlers", name));
+    let gateways = args.gateways.unwrap_or_else(|| format_ident!("{}Gateways", name));
+    let enabler = args.direct_closure
+        .unwrap_or_else(|| format_ident!("{}_direct_closure_enabler", snake_case(&name.to_string())));
+    let gateway_items = &args.gateway_items;
+
+    let handlers_doc = format!(
+        "Service handlers provided by the server module: the [`{name}`] implementation registered with \
+         the protocol servers and handed to the gateways by direct closure.\n\n\
+         # Rust Learning Note\n\n\
+         In Golang:\n\
+         ```go\n\
+         type {handlers} struct {{\n    Service {name}\n}}\n\
+         ```\n\n\
+         In Rust:\n\
+         ```rust,ignore\n\
+         pub struct {handlers} {{\n    pub service: Arc<dyn {name}>,\n}}\n\
+         ```\n\n\
+         Same concept - holder for service implementations!",
+    );
+    let gateways_doc = format!(
+        "Service gateways provided by the wiring layer: [`{name}`] over whichever protocol the caller \
+         asks for.\n\n\
+         # Rust Learning Note\n\n\
+         ## The Gateway Pattern\n\n\
+         ```text\n\
+         Client Module\n    ↓ asks for\n{gateways}\n    ↓ returns\nArc<dyn {name}>\n    ↓ client uses\nthe service\n\
+         ```\n\n\
+         The client doesn't know or care if it's Direct (a local call), gRPC or HTTP \
+         (remote calls): protocol selection is transparent!",
+    );
+    let get_service_doc = format!(
+        "Gets the [`{name}`] using `protocol` (Direct, Grpc, Http or Auto).\n\n\
+         # Rust Learning Note\n\n\
+         This is equivalent to Golang's:\n\
+         ```go\n\
+         func (g *{gateways}) GetService(ctx context.Context, protocol Protocol) ({name}, error)\n\
+         ```\n\n\
+         Both return an interface/trait that the caller can use!",
+    );
+    let enabler_doc = format!(
+        "Enables direct closure for [`{name}`].\n\n\
+         Called by the module registry during initialization. It performs two key operations:\n\n\
+         1. Registers the services with the ServiceConnector\n\
+         2. Stores the handlers in the gateways",
+    );
+
+    let (events_field, events_init, events_with, events_get) = match &args.events {
+        Some(events) => (
+            quote! {
+                /// Activity notifications (not offered if `None`)
+                pub events: ::std::option::Option<#private::Arc<dyn #events>>,
+            },
+            quote!(events: ::std::option::Option::None),
+            quote! {
+                /// Also offers activity notifications from `events`.
+                pub fn with_events(mut self, events: #private::Arc<dyn #events>) -> Self {
+                    self.events = ::std::option::Option::Some(events);
+                    self
+                }
+            },
+            quote! {
+                /// Gets the events using the specified protocol.
+                ///
+                /// Fails if the server doesn't publish events.
+                async fn get_events(&self, protocol: #private::Protocol) -> #private::Result<#private::Arc<dyn #events>>;
+            },
+        ),
+        None => (quote!(), quote!(), quote!(), quote!()),
+    };
+
+    quote! {
+        #service
+
+        #[doc = #handlers_doc]
+        #[derive(Clone)]
+        #vis struct #handlers {
+            /// The service implementation
+            pub service: #private::Arc<dyn #name>,
+            #events_field
+        }
+
+        impl #handlers {
+            /// Creates new service handlers.
+            pub fn new(service: #private::Arc<dyn #name>) -> Self {
+                Self { service, #events_init }
+            }
+
+            #events_with
+        }
+
+        #[doc = #gateways_doc]
+        #[#private::async_trait]
+        #vis trait #gateways: Send + Sync {
+            /// Returns the target module ID.
+            fn module_id(&self) -> #private::ModuleID;
+
+            /// Returns the list of service IDs provided.
+            fn service_ids(&self) -> ::std::vec::Vec<#private::ServiceID>;
+
+            /// Enables direct closure (local calls) by registering handlers.
+            ///
+            /// This is called during module initialization to enable
+            /// in-process calls without going through gRPC/HTTP.
+            fn enable_direct_closure(&self, handlers: #handlers);
+
+            #[doc = #get_service_doc]
+            async fn get_service(&self, protocol: #private::Protocol) -> #private::Result<#private::Arc<dyn #name>>;
+
+            #events_get
+
+            #(#gateway_items)*
+
+            /// Releases what the gateways hold: channels, event subscriptions.
+            ///
+            /// Called from the client module's `stop()`; gateways handed out
+            /// earlier fail afterwards. The default has nothing to release.
+            async fn close(&self) -> #private::Result<()> {
+                ::std::result::Result::Ok(())
+            }
+        }
+
+        #[doc = #enabler_doc]
+        #vis fn #enabler(
+            options: #private::DirectClosureEnablerOptions<#private::Arc<dyn #gateways>, #handlers>,
+        ) {
+            let _span = #private::tracing::debug_span!(
+                "direct_closure",
+                module_id = %options.service_gateways.module_id(),
+            )
+            .entered();
+            #private::tracing::debug!("Enabling direct closure");
+
+            // 1. Register with ServiceConnector
+            options.service_connector.enable_direct_closure(
+                options.service_gateways.module_id(),
+                options.service_gateways.service_ids(),
+            );
+
+            // 2. Store handlers in gateways
+            options.service_gateways.enable_direct_closure(options.service_handlers);
+
+            #private::tracing::debug!("✅ Direct closure enabled");
+        }
+    }
+}
+
+/// `MonitorService` → `monitor_service`.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(args: TokenStream2, service: TokenStream2) -> syn::Result<String> {
+        let args: ContractArgs = syn::parse2(args)?;
+        Ok(expand_contract(args, syn::parse2(service)?).to_string())
+    }
+
+    #[test]
+    fn test_contract_generates_handlers_and_gateways() {
+        let service = quote! {
+            pub trait MonitorService: Send + Sync {
+                async fn stats(&self) -> Result<MonitorStats>;
+            }
+        };
+        let expanded = contract(quote!(), service).unwrap();
+
+        assert!(expanded.contains("pub struct MonitorServiceHandlers"));
+        assert!(expanded.contains("pub trait MonitorServiceGateways"));
+        assert!(expanded.contains("handlers : MonitorServiceHandlers"));
+        assert!(expanded.contains("pub fn monitor_service_direct_closure_enabler"));
+        assert!(!expanded.contains("get_events"));
+    }
+
+    #[test]
+    fn test_options_add_events_and_gateway_items() {
+        let args = quote! {
+            gateways = EchoGateways,
+            events = EchoEvents,
+            gateway_items = {
+                /// Describes what each protocol can do.
+                fn capabilities(&self) -> GatewayCapabilities;
+            },
+        };
+        let expanded = contract(args, quote!(pub trait EchoService: Send + Sync {})).unwrap();
+
+        assert!(expanded.contains("pub trait EchoGateways"));
+        assert!(expanded.contains("pub fn with_events"));
+        assert!(expanded.contains("async fn get_events"));
+        assert!(expanded.contains("fn capabilities"));
+    }
+
+    #[test]
+    fn test_unknown_option_is_rejected() {
+        let error = contract(quote!(stream = EchoEvents), quote!(trait EchoService {})).unwrap_err();
+        assert!(error.to_string().contains("unknown hsu_contract option `stream`"));
+    }
+
+    #[test]
+    fn test_enabler_registers_then_stores_handlers() {
+        let args = quote!(direct_closure = monitor_direct_closure_enabler);
+        let expanded = contract(args, quote!(pub trait MonitorService: Send + Sync {})).unwrap();
+
+        assert!(expanded.contains("pub fn monitor_direct_closure_enabler"));
+        assert!(expanded.contains("dyn MonitorServiceGateways > , MonitorServiceHandlers >"));
+        let register = expanded.find("service_connector . enable_direct_closure").unwrap();
+        let store = expanded.find("service_gateways . enable_direct_closure").unwrap();
+        assert!(register < store);
+    }
+}
@@ -0,0 +1,334 @@
+//! Gateway capabilities: what each protocol can do, asked up front.
+//!
+//! # Architecture
+//!
+//! Without this a caller learns that a protocol is missing the hard way -
+//! `get_service(Protocol::Http)` fails at runtime. The gateways describe
+//! themselves instead, from the factories they wire and what the adapters
+//! behind them support:
+//!
+//! ```text
+//! EchoServiceGateways::capabilities()
+//!     ↓
+//! PROTOCOL  WIRED  STREAMING  COMPRESSION  AUTH  ENCRYPTED  NOTE
+//! direct    yes    yes        -            -     no
+//! grpc      yes    yes        no           yes   no
+//! http      no     no         -            -     no         no HTTP gateway factory
+//! ```
+//!
+//! The CLI's `check` command compares the table with what the registry
+//! publishes; the admin endpoint serves it as `GET /capabilities`.
+//!
+//! Gateways keeping a second transport warm for fallback (see
+//! `echo_api::warm_standby`) add a [`StandbyStatus`] line below the table:
+//!
+//! ```text
+//! standby: grpc to 127.0.0.1:50055 warm (probe 3ms), 2 fallback(s)
+//! ```
+//!
+//! Asking for a protocol that isn't wired fails with a
+//! [`ProtocolUnsupported`] error listing the protocols that are, so a
+//! caller can pick another one instead of parsing the message:
+//!
+//! ```text
+//! PROTOCOL_UNSUPPORTED: service=service protocol=http supported=direct,grpc: no HTTP gateway factory
+//!     ↓ ProtocolUnsupported::from_error
+//! ProtocolUnsupported { service: "service", protocol: Http, supported: [Direct, Grpc], .. }
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+use hsu_common::{Error, ModuleID, Protocol, Result, ServiceID};
+
+use crate::errors::{EchoErrorKind, PROTOCOL_UNSUPPORTED};
+
+/// What one protocol of a gateway set supports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolCapabilities {
+    pub protocol: Protocol,
+    /// `get_service(protocol)` can hand out a gateway.
+    pub wired: bool,
+    /// The streaming methods work (`echo_file`, history streams, events).
+    pub streaming: bool,
+    /// Messages are compressed on the wire (`None` where there is no wire).
+    pub compression: Option<bool>,
+    /// Requests prove who sends them (`None` where there is no wire).
+    pub auth: Option<bool>,
+    /// Payloads are sealed end to end, whatever the transport.
+    pub encrypted: bool,
+    /// Why the protocol isn't wired, or what limits it.
+    pub note: Option<String>,
+}
+
+impl ProtocolCapabilities {
+    /// A protocol the gateways can't hand out, and why.
+    pub fn missing(protocol: Protocol, note: impl Into<String>) -> Self {
+        Self {
+            protocol,
+            wired: false,
+            streaming: false,
+            compression: None,
+            auth: None,
+            encrypted: false,
+            note: Some(note.into()),
+        }
+    }
+
+    /// Adds a note on what limits the protocol.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// What a gateway set can hand out, per protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GatewayCapabilities {
+    /// Module the gateways lead to.
+    pub module_id: ModuleID,
+    /// Service `get_service` hands out.
+    pub service_id: ServiceID,
+    /// One entry per protocol (`Auto` picks among the wired ones).
+    pub protocols: Vec<ProtocolCapabilities>,
+    /// Transport kept warm for fallback (`None` without warm standby).
+    pub standby: Option<StandbyStatus>,
+}
+
+/// How ready a warm standby transport is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StandbyState {
+    /// Not probed yet.
+    Connecting,
+    /// The last probe succeeded, taking this long.
+    Warm { probe_latency: Duration },
+    /// The last probe failed; calls don't fall back until one succeeds.
+    Cold { error: String },
+}
+
+/// A transport the gateways keep connected next to the one in use, so a
+/// failed call can switch to it without a connection setup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StandbyStatus {
+    pub protocol: Protocol,
+    /// Where it goes (`host:port` or `registry:<module>`).
+    pub endpoint: String,
+    pub state: StandbyState,
+    /// Calls it has answered after the primary transport failed.
+    pub fallbacks: u64,
+}
+
+impl fmt::Display for StandbyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} to {} ", name(self.protocol), self.endpoint)?;
+        match &self.state {
+            StandbyState::Connecting => f.write_str("connecting")?,
+            StandbyState::Warm { probe_latency } => write!(f, "warm (probe {:.0?})", probe_latency)?,
+            StandbyState::Cold { error } => write!(f, "cold ({})", error)?,
+        }
+        write!(f, ", {} fallback(s)", self.fallbacks)
+    }
+}
+
+impl GatewayCapabilities {
+    /// Returns the entry of `protocol`, if listed.
+    pub fn get(&self, protocol: Protocol) -> Option<&ProtocolCapabilities> {
+        self.protocols.iter().find(|entry| entry.protocol == protocol)
+    }
+
+    /// Whether `get_service(protocol)` can hand out a gateway.
+    pub fn supports(&self, protocol: Protocol) -> bool {
+        protocol == Protocol::Auto && self.protocols.iter().any(|entry| entry.wired)
+            || self.get(protocol).is_some_and(|entry| entry.wired)
+    }
+
+    /// The wired protocols, in table order.
+    pub fn supported(&self) -> Vec<Protocol> {
+        self.protocols.iter().filter(|entry| entry.wired).map(|entry| entry.protocol).collect()
+    }
+
+    /// Fails with a [`ProtocolUnsupported`] error if `protocol` isn't
+    /// wired, before any call is made.
+    pub fn require(&self, protocol: Protocol) -> Result<()> {
+        if self.supports(protocol) {
+            return Ok(());
+        }
+        let note = self.get(protocol).and_then(|entry| entry.note.as_deref()).unwrap_or("not wired");
+        Err(ProtocolUnsupported {
+            service: self.service_id.clone(),
+            protocol,
+            supported: self.supported(),
+            reason: format!("module '{}': {}", self.module_id, note),
+        }
+        .into())
+    }
+}
+
+/// A service asked for over a protocol its gateways can't use.
+///
+/// # Rust Learning Note
+///
+/// `hsu_common::Error` has no variant for this, so the error travels as
+/// `Error::Protocol("PROTOCOL_UNSUPPORTED: service=... protocol=...
+/// supported=...: reason")`; [`from_error`](Self::from_error) reads the
+/// fields back, even from an error that crossed a process boundary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolUnsupported {
+    pub service: ServiceID,
+    pub protocol: Protocol,
+    /// Protocols that would have worked (empty if none).
+    pub supported: Vec<Protocol>,
+    /// Why the protocol isn't available.
+    pub reason: String,
+}
+
+impl ProtocolUnsupported {
+    /// Reads the fields back out of an error created from this type.
+    pub fn from_error(error: &Error) -> Option<Self> {
+        let Error::Protocol(message) = error else { return None };
+        let rest = message.strip_prefix(PROTOCOL_UNSUPPORTED)?.strip_prefix(": ")?;
+        let (fields, reason) = rest.split_once(": ").unwrap_or((rest, ""));
+        let (mut service, mut protocol, mut supported) = (None, None, Vec::new());
+        for field in fields.split(' ') {
+            match field.split_once('=')? {
+                ("service", value) => service = Some(ServiceID::from(value)),
+                ("protocol", value) => protocol = Some(parse_name(value)?),
+                ("supported", "") => {}
+                ("supported", values) => {
+                    supported = values.split(',').map(parse_name).collect::<Option<_>>()?;
+                }
+                _ => return None,
+            }
+        }
+        Some(Self { service: service?, protocol: protocol?, supported, reason: reason.to_string() })
+    }
+}
+
+impl fmt::Display for ProtocolUnsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let supported: Vec<_> = self.supported.iter().map(|&protocol| name(protocol)).collect();
+        write!(f, "service={} protocol={} supported={}", self.service, name(self.protocol), supported.join(","))?;
+        if !self.reason.is_empty() {
+            write!(f, ": {}", self.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<ProtocolUnsupported> for Error {
+    fn from(unsupported: ProtocolUnsupported) -> Self {
+        Error::Protocol(EchoErrorKind::ProtocolUnsupported.message(unsupported))
+    }
+}
+
+/// `grpc`, `http`, ... (as the registry spells them).
+fn name(protocol: Protocol) -> String {
+    format!("{:?}", protocol).to_lowercase()
+}
+
+/// Reverses [`name`].
+fn parse_name(name: &str) -> Option<Protocol> {
+    match name {
+        "direct" => Some(Protocol::Direct),
+        "grpc" => Some(Protocol::Grpc),
+        "http" => Some(Protocol::Http),
+        "auto" => Some(Protocol::Auto),
+        _ => None,
+    }
+}
+
+impl fmt::Display for GatewayCapabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |value: bool| if value { "yes" } else { "no" };
+        let wire = |value: Option<bool>| value.map_or("-", flag);
+        writeln!(f, "module: {}", self.module_id)?;
+        write!(f, "{:<9} {:<6} {:<10} {:<12} {:<5} {:<10} NOTE",
+            "PROTOCOL", "WIRED", "STREAMING", "COMPRESSION", "AUTH", "ENCRYPTED")?;
+        for entry in &self.protocols {
+            write!(f, "\n{:<9} {:<6} {:<10} {:<12} {:<5} {:<10} {}",
+                name(entry.protocol), flag(entry.wired), flag(entry.streaming), wire(entry.compression),
+                wire(entry.auth), flag(entry.encrypted), entry.note.as_deref().unwrap_or(""))?;
+        }
+        if let Some(standby) = &self.standby {
+            write!(f, "\nstandby: {}", standby)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capabilities() -> GatewayCapabilities {
+        GatewayCapabilities {
+            module_id: ModuleID::from("echo"),
+            service_id: ServiceID::from("service"),
+            protocols: vec![
+                ProtocolCapabilities::missing(Protocol::Direct, "not in this process"),
+                ProtocolCapabilities {
+                    protocol: Protocol::Grpc,
+                    wired: true,
+                    streaming: true,
+                    compression: Some(false),
+                    auth: Some(true),
+                    encrypted: false,
+                    note: None,
+                },
+                ProtocolCapabilities::missing(Protocol::Http, "no HTTP gateway factory"),
+            ],
+            standby: None,
+        }
+    }
+
+    #[test]
+    fn test_supports_wired_protocols_only() {
+        let capabilities = capabilities();
+
+        assert!(capabilities.supports(Protocol::Grpc));
+        assert!(capabilities.supports(Protocol::Auto));
+        assert!(!capabilities.supports(Protocol::Direct));
+
+        assert!(capabilities.require(Protocol::Grpc).is_ok());
+    }
+
+    #[test]
+    fn test_unsupported_protocol_error_round_trip() {
+        let error = capabilities().require(Protocol::Http).unwrap_err();
+
+        assert!(error.to_string().contains(
+            "PROTOCOL_UNSUPPORTED: service=service protocol=http supported=grpc: module 'echo': no HTTP gateway factory"
+        ), "{}", error);
+        let unsupported = ProtocolUnsupported::from_error(&error).unwrap();
+        assert_eq!(unsupported.service, ServiceID::from("service"));
+        assert_eq!(unsupported.protocol, Protocol::Http);
+        assert_eq!(unsupported.supported, vec![Protocol::Grpc]);
+        assert_eq!(unsupported.reason, "module 'echo': no HTTP gateway factory");
+        assert_eq!(ProtocolUnsupported::from_error(&Error::Protocol("UNAVAILABLE: down".to_string())), None);
+    }
+
+    #[test]
+    fn test_renders_a_table() {
+        let text = capabilities().to_string();
+
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines[0], "module: echo");
+        assert!(lines[1].starts_with("PROTOCOL"));
+        assert!(lines[2].starts_with("direct    no     no         -            -     no         not in this process"));
+        assert!(lines[3].starts_with("grpc      yes    yes        no           yes   no"));
+        assert_eq!(lines.len(), 5);
+    }
+
+    #[test]
+    fn test_renders_standby_below_the_table() {
+        let mut capabilities = capabilities();
+        capabilities.standby = Some(StandbyStatus {
+            protocol: Protocol::Http,
+            endpoint: "127.0.0.1:50055".to_string(),
+            state: StandbyState::Warm { probe_latency: Duration::from_millis(3) },
+            fallbacks: 2,
+        });
+
+        let text = capabilities.to_string();
+        assert_eq!(text.lines().last(), Some("standby: http to 127.0.0.1:50055 warm (probe 3ms), 2 fallback(s)"));
+    }
+}
@@ -0,0 +1,349 @@
+//! Request context (call-scoped metadata).
+//!
+//! # Architecture
+//!
+//! Metadata about a call (its priority, the transformations of its reply,
+//! who is calling) travels **beside** the arguments, so `EchoService` signatures don't
+//! change. The caller runs the call in a context scope; protocol adapters
+//! carry it across the wire:
+//!
+//! ```text
+//! Client:  RequestContext::scope(ctx, service.echo(..))
+//!              ↓ Direct: same task, context visible as-is
+//!              ↓ gRPC:   EchoGrpcGateway → `x-echo-priority`, `x-echo-transform`,
+//!                                          `x-echo-caller`, `x-echo-trace`,
+//!                                          `accept-language` headers
+//!                        EchoGrpcHandler → RequestContext::scope(ctx, ..)
+//! Server:  RequestContext::current().priority / .transforms / .caller
+//! ```
+//!
+//! The accepted languages pick the locale of user-facing error messages
+//! (see [`crate::messages`]).
+//!
+//! The trace and request IDs do nothing to the call itself: log sinks
+//! read them from the current context, so the events a call causes in
+//! the client and in the server carry the same IDs.
+//!
+//! ## Comparison with Golang
+//!
+//! Go threads `ctx context.Context` through every call explicitly. Rust
+//! has no ambient context, so this uses a tokio **task-local** instead:
+//!
+//! ```go
+//! ctx = WithPriority(ctx, PriorityHigh)
+//! service.Echo1(ctx, "hello")
+//! ```
+//!
+//! ```rust,ignore
+//! RequestContext::new(Priority::High)
+//!     .scope(service.echo("hello".into()))
+//!     .await
+//! ```
+//!
+//! ## Call Info
+//!
+//! The same task-local trick counts wire attempts: protocol gateways call
+//! [`record_attempt`] per request they send (reconnect retries and hedged
+//! attempts included), and [`count_attempts`] reads the total for a
+//! [`CallInfo`].
+//!
+//! ## Response Metadata
+//!
+//! Metadata flows back the same way: the server side attaches entries
+//! (instance id, version) with [`attach_response_metadata`], the gRPC
+//! handler sends what it [collected](collect_response_metadata) as
+//! `x-echo-meta-*` headers, and the gRPC gateway attaches them again on
+//! the client. On the Direct path the entries never leave the task.
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+use hsu_common::{Error, Protocol};
+
+pub use crate::types::{Priority, PRIORITY_METADATA_KEY, TRACE_METADATA_KEY, TRANSFORM_METADATA_KEY};
+use crate::transform::EchoTransform;
+
+impl FromStr for Priority {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Priority::from_name(s).ok_or_else(|| Error::Validation {
+            message: format!("Unknown priority '{}' (expected high, normal or low)", s.trim().to_ascii_lowercase()),
+        })
+    }
+}
+
+/// Call-scoped metadata.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RequestContext {
+    /// Scheduling class.
+    pub priority: Priority,
+    /// Applied in order to the text reply (see [`crate::transform`]).
+    pub transforms: Vec<EchoTransform>,
+    /// Who is calling, for per-caller accounting (anonymous if `None`).
+    ///
+    /// Self-declared: there is no authentication to vouch for it.
+    pub caller: Option<String>,
+    /// Groups the calls of one client operation in the logs.
+    pub trace_id: Option<String>,
+    /// Identifies this call in the logs of every process it passes.
+    pub request_id: Option<String>,
+    /// Languages the caller reads error messages in (`Accept-Language`
+    /// syntax, e.g. `de-CH, fr;q=0.8`); English if `None`.
+    pub accept_language: Option<String>,
+    /// A probe (the server's start-up self-test): served like any call,
+    /// but left out of the request metrics.
+    pub probe: bool,
+}
+
+tokio::task_local! {
+    static CURRENT: RequestContext;
+    static ATTEMPTS: Cell<u32>;
+    static RESPONSE_METADATA: RefCell<BTreeMap<String, String>>;
+}
+
+/// Prefix of the gRPC metadata keys carrying response metadata
+/// (`x-echo-meta-instance-id`).
+pub const RESPONSE_METADATA_PREFIX: &str = "x-echo-meta-";
+
+impl RequestContext {
+    /// Creates a context with the given priority.
+    pub fn new(priority: Priority) -> Self {
+        Self {
+            priority,
+            transforms: Vec::new(),
+            caller: None,
+            trace_id: None,
+            request_id: None,
+            accept_language: None,
+            probe: false,
+        }
+    }
+
+    /// Has the server transform the reply (see [`crate::transform`]).
+    pub fn with_transforms(mut self, transforms: Vec<EchoTransform>) -> Self {
+        self.transforms = transforms;
+        self
+    }
+
+    /// Names the caller (tenant) the call is accounted to.
+    pub fn with_caller(mut self, caller: impl Into<String>) -> Self {
+        self.caller = Some(caller.into());
+        self
+    }
+
+    /// Asks for error messages in these languages (`Accept-Language` syntax).
+    pub fn with_accept_language(mut self, accept_language: impl Into<String>) -> Self {
+        self.accept_language = Some(accept_language.into());
+        self
+    }
+
+    /// Marks the call as a probe (see [`RequestContext::probe`]).
+    pub fn as_probe(mut self) -> Self {
+        self.probe = true;
+        self
+    }
+
+    /// Tags the call's log events with `trace_id` and `request_id`.
+    pub fn with_trace(mut self, trace_id: impl Into<String>, request_id: impl Into<String>) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// The part of the context that shapes the reply: priority, transforms
+    /// and caller. Calls agreeing on it may share one request (coalescing,
+    /// batching); the trace and request IDs, languages and probe flag only
+    /// describe each call, and are left out.
+    pub fn sharing_key(&self) -> Self {
+        Self {
+            priority: self.priority,
+            transforms: self.transforms.clone(),
+            caller: self.caller.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// The [`TRACE_METADATA_KEY`] value (`TRACE_ID/REQUEST_ID`), if traced.
+    pub fn trace_header(&self) -> Option<String> {
+        let trace_id = self.trace_id.as_deref()?;
+        Some(format!("{}/{}", trace_id, self.request_id.as_deref().unwrap_or_default()))
+    }
+
+    /// Takes the IDs of a [`TRACE_METADATA_KEY`] value (ignored if empty).
+    pub fn with_trace_header(mut self, value: &str) -> Self {
+        let (trace_id, request_id) = value.split_once('/').unwrap_or((value, ""));
+        self.trace_id = (!trace_id.is_empty()).then(|| trace_id.to_string());
+        self.request_id = (!request_id.is_empty()).then(|| request_id.to_string());
+        self
+    }
+
+    /// Returns the context of the running call (default outside a scope).
+    pub fn current() -> RequestContext {
+        CURRENT.try_with(|ctx| ctx.clone()).unwrap_or_default()
+    }
+
+    /// Whether the running call is a probe (without cloning the context).
+    pub fn current_is_probe() -> bool {
+        CURRENT.try_with(|ctx| ctx.probe).unwrap_or(false)
+    }
+
+    /// Runs `future` with this context as [`RequestContext::current`].
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+}
+
+/// How a call was carried out (see `EchoServiceGateways::echo_with_info`).
+#[derive(Debug, Clone)]
+pub struct CallInfo {
+    /// Transport that carried the call (`Auto` resolved to Direct or gRPC).
+    pub protocol_used: Protocol,
+    /// Where the call went: `host:port`, or a description for Direct and
+    /// registry-resolved channels.
+    pub endpoint: String,
+    /// Wall time of the call, retries included.
+    pub latency: Duration,
+    /// Requests sent, including reconnect retries and hedged attempts.
+    pub attempts: u32,
+    /// Metadata the serving instance attached (empty if it attached none).
+    pub server_metadata: BTreeMap<String, String>,
+    /// How `Auto` picked its transport, if it raced them (see [`ProtocolRace`]).
+    pub protocol_race: Option<ProtocolRace>,
+}
+
+/// Outcome of racing transports for the first `Auto` call.
+///
+/// The first `echo` goes out over every transport; the first to answer
+/// wins and carries every later call of the gateway set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolRace {
+    /// Transport that answered first.
+    pub winner: Protocol,
+    /// Time until the winner answered.
+    pub decided_after: Duration,
+    /// Contestants that failed, with their error.
+    pub failures: Vec<(Protocol, String)>,
+}
+
+/// Runs `future`, returning its output and the attempts it recorded.
+///
+/// Nested calls also add their attempts to the enclosing count, so a
+/// decorator may count a call its caller counts as well.
+pub async fn count_attempts<F: Future>(future: F) -> (F::Output, u32) {
+    let (output, attempts) = ATTEMPTS.scope(Cell::new(0), async {
+        let output = future.await;
+        (output, ATTEMPTS.with(Cell::get))
+    }).await;
+    let _ = ATTEMPTS.try_with(|outer| outer.set(outer.get() + attempts));
+    (output, attempts)
+}
+
+/// Records one request sent by a protocol gateway.
+///
+/// No-op outside [`count_attempts`].
+pub fn record_attempt() {
+    let _ = ATTEMPTS.try_with(|attempts| attempts.set(attempts.get() + 1));
+}
+
+/// Runs `future`, returning its output and the response metadata attached meanwhile.
+///
+/// Like [`count_attempts`], nested calls pass their entries on to the
+/// enclosing collection.
+pub async fn collect_response_metadata<F: Future>(future: F) -> (F::Output, BTreeMap<String, String>) {
+    let (output, metadata) = RESPONSE_METADATA.scope(RefCell::new(BTreeMap::new()), async {
+        let output = future.await;
+        (output, RESPONSE_METADATA.with(|metadata| metadata.take()))
+    }).await;
+    let _ = RESPONSE_METADATA.try_with(|outer| outer.borrow_mut().extend(metadata.clone()));
+    (output, metadata)
+}
+
+/// Attaches `key: value` to the response of the running call.
+///
+/// No-op outside [`collect_response_metadata`].
+pub fn attach_response_metadata(key: impl Into<String>, value: impl Into<String>) {
+    let _ = RESPONSE_METADATA.try_with(|metadata| metadata.borrow_mut().insert(key.into(), value.into()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_round_trip() {
+        for priority in Priority::ALL {
+            assert_eq!(priority.as_str().parse::<Priority>().unwrap(), priority);
+            assert_eq!(Priority::ALL[priority.index()], priority);
+        }
+        assert!("urgent".parse::<Priority>().is_err());
+    }
+
+    #[test]
+    fn test_trace_header_round_trip() {
+        let context = RequestContext::default().with_trace("4bf92f35", "7d1c");
+        assert_eq!(context.trace_header().as_deref(), Some("4bf92f35/7d1c"));
+        assert_eq!(RequestContext::default().with_trace_header("4bf92f35/7d1c"), context);
+
+        let trace_only = RequestContext::default().with_trace_header("4bf92f35");
+        assert_eq!((trace_only.trace_id.as_deref(), trace_only.request_id), (Some("4bf92f35"), None));
+        assert_eq!(RequestContext::default().trace_header(), None);
+    }
+
+    #[tokio::test]
+    async fn test_scope_sets_current_context() {
+        assert_eq!(RequestContext::current().priority, Priority::Normal);
+
+        let inner = RequestContext::new(Priority::High)
+            .scope(async { RequestContext::current().priority })
+            .await;
+
+        assert_eq!(inner, Priority::High);
+        assert_eq!(RequestContext::current().priority, Priority::Normal);
+    }
+
+    #[tokio::test]
+    async fn test_count_attempts() {
+        record_attempt();
+
+        let ((), attempts) = count_attempts(async {
+            record_attempt();
+            // Concurrent futures of the same task count too (hedging)
+            futures::join!(async { record_attempt() }, async { record_attempt() });
+        }).await;
+
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_nested_scopes_pass_results_on() {
+        let (((inner_attempts, inner_metadata), outer_metadata), outer_attempts) =
+            count_attempts(collect_response_metadata(async {
+                record_attempt();
+                let ((), attempts) = count_attempts(async { record_attempt() }).await;
+                let ((), metadata) =
+                    collect_response_metadata(async { attach_response_metadata("instance-id", "a1") }).await;
+                (attempts, metadata)
+            })).await;
+
+        assert_eq!((inner_attempts, outer_attempts), (1, 2));
+        assert_eq!(inner_metadata, outer_metadata);
+    }
+
+    #[tokio::test]
+    async fn test_collect_response_metadata() {
+        attach_response_metadata("ignored", "outside any scope");
+
+        let (reply, metadata) = collect_response_metadata(async {
+            attach_response_metadata("instance-id", "a1");
+            attach_response_metadata("server-version", "0.1.0");
+            "hello"
+        }).await;
+
+        assert_eq!(reply, "hello");
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata["instance-id"], "a1");
+    }
+}
@@ -0,0 +1,214 @@
+//! Error kinds of the echo contract (`alloc` only).
+//!
+//! # Architecture
+//!
+//! `hsu_common::Error` has no variants for these failures, so they travel
+//! as `Error::Protocol` messages with a stable prefix - which every
+//! protocol carrying the error text preserves:
+//!
+//! ```text
+//! DEADLINE_EXCEEDED: <detail>
+//! UNAVAILABLE: <detail>
+//! OVERLOADED: retry_after_ms=250: <detail>
+//! INVALID_RESPONSE: <detail>
+//! INTEGRITY_ERROR: <detail>
+//! STORAGE_ERROR: <detail>
+//! PROTOCOL_UNSUPPORTED: service=service protocol=http supported=direct,grpc: <detail>
+//! INVALID_ARGUMENT: field=idempotency_key: <description>    (Error::Validation)
+//! ```
+//!
+//! [`EchoErrorKind`] formats and classifies these messages without
+//! `hsu-common`, so a `no_std` consumer (e.g. a WASM client reading the
+//! error text of a response) interprets them exactly like the `std`
+//! helpers (`is_overloaded`, `retry_after`, ...).
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use core::time::Duration;
+
+/// Prefix of the error returned when a call misses its deadline.
+///
+/// Matches the gRPC status code name, so callers see the same error
+/// whether the call was Direct or remote.
+pub const DEADLINE_EXCEEDED: &str = "DEADLINE_EXCEEDED";
+
+/// Error prefix for calls that failed because the service was unreachable.
+pub const UNAVAILABLE: &str = "UNAVAILABLE";
+
+/// Error prefix for calls rejected because the server is at capacity.
+pub const OVERLOADED: &str = "OVERLOADED";
+
+/// Error prefix for replies rejected by a client-side response validator.
+pub const INVALID_RESPONSE: &str = "INVALID_RESPONSE";
+
+/// Error prefix for messages whose checksum didn't match on arrival.
+pub const INTEGRITY_ERROR: &str = "INTEGRITY_ERROR";
+
+/// Error prefix for failures of the server's own storage (history
+/// database, job store, ...).
+pub const STORAGE_ERROR: &str = "STORAGE_ERROR";
+
+/// Error prefix for calls over a protocol the gateways can't hand out.
+pub const PROTOCOL_UNSUPPORTED: &str = "PROTOCOL_UNSUPPORTED";
+
+/// Prefix of validation errors naming the request field at fault.
+pub const INVALID_ARGUMENT: &str = "INVALID_ARGUMENT";
+
+/// A request field that failed validation.
+///
+/// Travels as an `Error::Validation` message
+/// (`INVALID_ARGUMENT: field=NAME: <description>`) and, over gRPC, as a
+/// `google.rpc.BadRequest` field violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldViolation {
+    /// The request field, e.g. `idempotency_key`.
+    pub field: String,
+    /// What is wrong with it.
+    pub description: String,
+}
+
+impl FieldViolation {
+    /// Creates a violation of `field`.
+    pub fn new(field: impl Into<String>, description: impl Into<String>) -> Self {
+        Self { field: field.into(), description: description.into() }
+    }
+
+    /// Formats the validation error message.
+    pub fn message(&self) -> String {
+        format!("{}: field={}: {}", INVALID_ARGUMENT, self.field, self.description)
+    }
+
+    /// Parses a message formatted by [`message`](Self::message).
+    pub fn parse(message: &str) -> Option<Self> {
+        let rest = message.strip_prefix(INVALID_ARGUMENT)?.strip_prefix(": field=")?;
+        let (field, description) = rest.split_once(": ")?;
+        Some(Self::new(field, description))
+    }
+}
+
+/// Failures the contract defines beyond `hsu_common::Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EchoErrorKind {
+    /// The call missed its deadline.
+    DeadlineExceeded,
+    /// The service couldn't be reached; worth retrying on a new connection.
+    Unavailable,
+    /// The server shed load; back off for `retry_after` if given.
+    Overloaded { retry_after: Option<Duration> },
+    /// The call succeeded, but the client rejected the reply.
+    InvalidResponse,
+    /// A message arrived corrupted (its checksum didn't match).
+    IntegrityError,
+    /// The server's storage failed; the request was fine, retrying may help
+    /// once the storage recovers.
+    Storage,
+    /// The gateways can't use the requested protocol; retrying won't help,
+    /// another protocol might.
+    ProtocolUnsupported,
+}
+
+impl EchoErrorKind {
+    /// Returns the message prefix (`DEADLINE_EXCEEDED`, ...).
+    pub fn code(&self) -> &'static str {
+        match self {
+            EchoErrorKind::DeadlineExceeded => DEADLINE_EXCEEDED,
+            EchoErrorKind::Unavailable => UNAVAILABLE,
+            EchoErrorKind::Overloaded { .. } => OVERLOADED,
+            EchoErrorKind::InvalidResponse => INVALID_RESPONSE,
+            EchoErrorKind::IntegrityError => INTEGRITY_ERROR,
+            EchoErrorKind::Storage => STORAGE_ERROR,
+            EchoErrorKind::ProtocolUnsupported => PROTOCOL_UNSUPPORTED,
+        }
+    }
+
+    /// Formats the error message for `detail`.
+    pub fn message(&self, detail: impl fmt::Display) -> String {
+        match self {
+            EchoErrorKind::Overloaded { retry_after: Some(retry_after) } => {
+                format!("{}: retry_after_ms={}: {}", OVERLOADED, retry_after.as_millis(), detail)
+            }
+            kind => format!("{}: {}", kind.code(), detail),
+        }
+    }
+
+    /// Classifies an error message; `None` for any other error.
+    pub fn classify(message: &str) -> Option<Self> {
+        if message.starts_with(DEADLINE_EXCEEDED) {
+            Some(EchoErrorKind::DeadlineExceeded)
+        } else if message.starts_with(UNAVAILABLE) {
+            Some(EchoErrorKind::Unavailable)
+        } else if let Some(rest) = message.strip_prefix(OVERLOADED) {
+            let retry_after = rest
+                .strip_prefix(": retry_after_ms=")
+                .and_then(|hint| hint.split(':').next()?.parse().ok())
+                .map(Duration::from_millis);
+            Some(EchoErrorKind::Overloaded { retry_after })
+        } else if message.starts_with(INVALID_RESPONSE) {
+            Some(EchoErrorKind::InvalidResponse)
+        } else if message.starts_with(INTEGRITY_ERROR) {
+            Some(EchoErrorKind::IntegrityError)
+        } else if message.starts_with(STORAGE_ERROR) {
+            Some(EchoErrorKind::Storage)
+        } else if message.starts_with(PROTOCOL_UNSUPPORTED) {
+            Some(EchoErrorKind::ProtocolUnsupported)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the detail of a message formatted by [`EchoErrorKind::message`],
+    /// without the prefix and hint; `None` for any other message.
+    pub fn detail(message: &str) -> Option<&str> {
+        let kind = Self::classify(message)?;
+        let rest = message.strip_prefix(kind.code())?.strip_prefix(": ")?;
+        match kind {
+            EchoErrorKind::Overloaded { retry_after: Some(_) } => Some(rest.split_once(": ")?.1),
+            _ => Some(rest),
+        }
+    }
+}
+
+impl fmt::Display for EchoErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_round_trip() {
+        let kinds = [
+            EchoErrorKind::DeadlineExceeded,
+            EchoErrorKind::Unavailable,
+            EchoErrorKind::Overloaded { retry_after: Some(Duration::from_millis(250)) },
+            EchoErrorKind::Overloaded { retry_after: None },
+            EchoErrorKind::InvalidResponse,
+            EchoErrorKind::IntegrityError,
+            EchoErrorKind::Storage,
+            EchoErrorKind::ProtocolUnsupported,
+        ];
+        for kind in kinds {
+            assert_eq!(EchoErrorKind::classify(&kind.message("detail")), Some(kind));
+        }
+        assert_eq!(EchoErrorKind::classify("Validation failed"), None);
+    }
+
+    #[test]
+    fn test_detail_strips_prefix_and_hint() {
+        let overloaded = EchoErrorKind::Overloaded { retry_after: Some(Duration::from_millis(250)) };
+        assert_eq!(EchoErrorKind::detail(&overloaded.message("quota: 9 of 8 bytes")), Some("quota: 9 of 8 bytes"));
+        assert_eq!(EchoErrorKind::detail(&EchoErrorKind::Unavailable.message("draining")), Some("draining"));
+        assert_eq!(EchoErrorKind::detail("Validation failed"), None);
+    }
+
+    #[test]
+    fn test_field_violation_round_trip() {
+        let violation = FieldViolation::new("idempotency_key", "must not be empty: got \"\"");
+        assert_eq!(FieldViolation::parse(&violation.message()), Some(violation));
+        assert_eq!(FieldViolation::parse("line 3: unknown method"), None);
+    }
+}
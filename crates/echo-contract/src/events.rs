@@ -0,0 +1,79 @@
+//! Echo activity notifications (pub/sub).
+//!
+//! # Architecture
+//!
+//! The server publishes one [`EchoEvent`] per processed call; any module
+//! can [`subscribe`](EchoEvents::subscribe) to them:
+//!
+//! ```text
+//! EchoServiceImpl ─→ event bus ─┬─→ Direct subscriber (in-process broadcast)
+//!                               └─→ gRPC server stream ─→ remote subscriber
+//! ```
+//!
+//! Delivery is best-effort: a subscriber that falls too far behind skips
+//! the events it missed instead of slowing the server down.
+
+use std::pin::Pin;
+use std::str::FromStr;
+use std::time::SystemTime;
+use async_trait::async_trait;
+use futures::Stream;
+use hsu_common::{Error, Result};
+
+pub use crate::types::EchoMethod;
+
+impl FromStr for EchoMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        EchoMethod::from_name(s).ok_or_else(|| Error::Validation {
+            message: format!("Unknown echo method '{}'", s),
+        })
+    }
+}
+
+/// One processed echo call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EchoEvent {
+    /// The method that was called.
+    pub method: EchoMethod,
+    /// Size of the request payload in bytes.
+    pub request_bytes: u64,
+    /// `false` if the service returned an error.
+    pub success: bool,
+    /// When the call finished.
+    pub at: SystemTime,
+}
+
+/// A stream of echo events.
+///
+/// Ends when the publisher goes away (server shutdown, connection lost).
+pub type EchoEventStream = Pin<Box<dyn Stream<Item = Result<EchoEvent>> + Send>>;
+
+/// Echo events contract (protocol-agnostic).
+///
+/// ## Comparison with Golang
+///
+/// ```go
+/// type EchoEvents interface {
+///     Subscribe(ctx context.Context) (<-chan EchoEvent, error)
+/// }
+/// ```
+#[async_trait]
+pub trait EchoEvents: Send + Sync {
+    /// Subscribes to events published from now on.
+    async fn subscribe(&self) -> Result<EchoEventStream>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_round_trip() {
+        for method in EchoMethod::ALL {
+            assert_eq!(method.as_str().parse::<EchoMethod>().unwrap(), method);
+        }
+        assert!("bogus".parse::<EchoMethod>().is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Protocol fallback for any contract's gateways, generated by a macro.
+//!
+//! # Architecture
+//!
+//! Every contract has its own gateways trait (`EchoServiceGateways`,
+//! `MonitorServiceGateways`, ...), each with
+//! `get_service(protocol) -> Result<Arc<dyn Service>>`. Trying protocols in
+//! turn used to be a hand-written match per caller, listing the protocols
+//! the caller knew about; [`impl_service_gateway_ext!`] generates it once
+//! per contract instead:
+//!
+//! ```text
+//! impl_service_gateway_ext!(pub trait EchoServiceGatewaysExt for EchoServiceGateways => EchoService);
+//!     ↓
+//! gateways.get_first_service(&[Protocol::Direct, Protocol::Grpc])
+//!     Direct → PROTOCOL_UNSUPPORTED (not in this process)  → next
+//!     Grpc   → Ok(service)
+//! ```
+//!
+//! Only [`ProtocolUnsupported`](crate::ProtocolUnsupported) errors move on
+//! to the next protocol; any other failure (unreachable, overloaded) is
+//! the answer, since another protocol would reach the same server.
+//!
+//! # Rust Learning Note
+//!
+//! The gateways traits share no supertrait, so a generic function can't
+//! call `get_service` on all of them; a `macro_rules!` macro can, because
+//! it expands per trait. The macro reaches its dependencies through
+//! `$crate::__private`, so callers don't need `async_trait` in scope.
+
+/// Generates an extension trait with protocol fallback for a gateways
+/// trait, implemented for every type (and `dyn`) implementing it.
+///
+/// ```ignore
+/// echo_contract::impl_service_gateway_ext!(
+///     /// Protocol fallback for the monitor gateways.
+///     pub trait MonitorServiceGatewaysExt for MonitorServiceGateways => MonitorService
+/// );
+///
+/// let service = gateways.get_any_service().await?;
+/// ```
+#[macro_export]
+macro_rules! impl_service_gateway_ext {
+    ($(#[$meta:meta])* $vis:vis trait $ext:ident for $gateways:path => $service:path) => {
+        $(#[$meta])*
+        #[$crate::__private::async_trait]
+        $vis trait $ext {
+            /// Returns the service over the first of `protocols` the
+            /// gateways support; fails with the last `PROTOCOL_UNSUPPORTED`
+            /// error if none is, or with the first other error.
+            async fn get_first_service(
+                &self,
+                protocols: &[$crate::__private::Protocol],
+            ) -> $crate::__private::Result<$crate::__private::Arc<dyn $service>>;
+
+            /// Like `get_first_service`, trying every protocol in
+            /// [`FALLBACK_PROTOCOLS`]($crate::FALLBACK_PROTOCOLS) order.
+            async fn get_any_service(&self) -> $crate::__private::Result<$crate::__private::Arc<dyn $service>> {
+                self.get_first_service(&$crate::FALLBACK_PROTOCOLS).await
+            }
+        }
+
+        #[$crate::__private::async_trait]
+        impl<T: $gateways + ?Sized> $ext for T {
+            async fn get_first_service(
+                &self,
+                protocols: &[$crate::__private::Protocol],
+            ) -> $crate::__private::Result<$crate::__private::Arc<dyn $service>> {
+                let mut unsupported = None;
+                for &protocol in protocols {
+                    match self.get_service(protocol).await {
+                        Ok(service) => return Ok(service),
+                        Err(e) if $crate::is_protocol_unsupported(&e) => unsupported = Some(e),
+                        Err(e) => return Err(e),
+                    }
+                }
+                Err(unsupported.unwrap_or_else(|| $crate::__private::Error::Validation {
+                    message: "No protocol to get the service with".to_string(),
+                }))
+            }
+        }
+    };
+}
+
+/// Protocols `get_any_service` tries, cheapest first.
+///
+/// `Auto` isn't listed: it is the gateways' own choice among these.
+pub const FALLBACK_PROTOCOLS: [hsu_common::Protocol; 3] =
+    [hsu_common::Protocol::Direct, hsu_common::Protocol::Grpc, hsu_common::Protocol::Http];
+
+#[doc(hidden)]
+pub mod __private {
+    pub use async_trait::async_trait;
+    pub use hsu_common::{Error, ModuleID, Protocol, Result, ServiceID};
+    pub use hsu_module_api::DirectClosureEnablerOptions;
+    pub use std::sync::Arc;
+    pub use tracing;
+}
+
+crate::impl_service_gateway_ext!(
+    /// Protocol fallback for [`EchoServiceGateways`](crate::EchoServiceGateways).
+    pub trait EchoServiceGatewaysExt for crate::EchoServiceGateways => crate::EchoService
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use async_trait::async_trait;
+    use hsu_common::{ModuleID, Protocol, Result, ServiceID};
+    use crate::{
+        unavailable, CallInfo, EchoEvents, EchoService, EchoServiceGateways, EchoServiceHandlers, GatewayCapabilities,
+        ProtocolUnsupported,
+    };
+
+    /// Supports nothing; records which protocols were asked for.
+    struct MockGateways {
+        asked: Mutex<Vec<Protocol>>,
+        /// Fails `Grpc` as unreachable instead of unsupported.
+        grpc_down: bool,
+    }
+
+    #[async_trait]
+    impl EchoServiceGateways for MockGateways {
+        fn module_id(&self) -> ModuleID {
+            ModuleID::from("echo")
+        }
+
+        fn service_ids(&self) -> Vec<ServiceID> {
+            vec![ServiceID::from("service")]
+        }
+
+        fn enable_direct_closure(&self, _handlers: EchoServiceHandlers) {}
+
+        async fn get_service(&self, protocol: Protocol) -> Result<Arc<dyn EchoService>> {
+            self.asked.lock().unwrap().push(protocol);
+            if protocol == Protocol::Grpc && self.grpc_down {
+                return Err(unavailable("connection refused"));
+            }
+            Err(ProtocolUnsupported {
+                service: ServiceID::from("service"),
+                protocol,
+                supported: vec![],
+                reason: "mock".to_string(),
+            }
+            .into())
+        }
+
+        fn capabilities(&self) -> GatewayCapabilities {
+            GatewayCapabilities {
+                module_id: ModuleID::from("echo"),
+                service_id: ServiceID::from("service"),
+                protocols: vec![],
+                standby: None,
+            }
+        }
+
+        async fn get_events(&self, _protocol: Protocol) -> Result<Arc<dyn EchoEvents>> {
+            Err(hsu_common::Error::Protocol("not supported by mock".to_string()))
+        }
+
+        async fn echo_with_info(&self, _protocol: Protocol, _message: Arc<str>) -> Result<(Arc<str>, CallInfo)> {
+            Err(hsu_common::Error::Protocol("not supported by mock".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_on_unsupported_only() {
+        let gateways: Arc<dyn EchoServiceGateways> = Arc::new(MockGateways { asked: Mutex::new(vec![]), grpc_down: false });
+        let error = gateways.get_any_service().await.err().unwrap();
+        assert_eq!(ProtocolUnsupported::from_error(&error).unwrap().protocol, Protocol::Http);
+
+        let gateways = MockGateways { asked: Mutex::new(vec![]), grpc_down: true };
+        let error = gateways.get_any_service().await.err().unwrap();
+        assert!(crate::is_unavailable(&error), "{}", error);
+        // Http would reach the same server
+        assert_eq!(*gateways.asked.lock().unwrap(), vec![Protocol::Direct, Protocol::Grpc]);
+    }
+}
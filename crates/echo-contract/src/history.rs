@@ -0,0 +1,429 @@
+//! Echo call history queries.
+//!
+//! # Architecture
+//!
+//! A server that keeps a history answers
+//! [`get_history`](crate::EchoService::get_history) one page at a time,
+//! newest first. Each page carries a cursor for the next one:
+//!
+//! ```text
+//! get_history(limit 2)                 → [#9, #8]  next_cursor "8"
+//! get_history(limit 2, cursor "8")     → [#7, #6]  next_cursor "6"
+//! get_history(limit 2, cursor "6")     → [#5]      next_cursor None (done)
+//! ```
+//!
+//! A cursor points between entries, not at an offset, so entries
+//! recorded while a client is paging don't shift the pages.
+//! [`stream_history`](crate::EchoService::stream_history) walks all pages
+//! server-side and streams the entries, for result sets too large for
+//! one response ([`stream_pages`] does the walking).
+//!
+//! Filters combine: a time range (`since` inclusive, `until` exclusive)
+//! and a substring of the echoed message.
+//!
+//! [`export_history`](crate::EchoService::export_history) streams the
+//! same entries serialized as a file, in one of the
+//! [`HistoryExportFormat`]s:
+//!
+//! ```text
+//! ndjson: {"id":9,"method":"echo","message":"hi","request_bytes":2,"success":true,"at_unix_ms":1700000000000,"record_id":"r9"}
+//! csv:    id,method,message,request_bytes,success,at_unix_ms,record_id
+//!         9,echo,hi,2,true,1700000000000,r9
+//! ```
+//!
+//! The encoding lives here, without serde, so every implementation -
+//! server, gateway, tower adapter - writes byte-identical files.
+//!
+//! [`import_history`](crate::EchoService::import_history) reads such a
+//! file back, e.g. into a fresh instance. It is all or nothing: one bad
+//! record rejects the file. Entries the history already has (same
+//! record ID, method, message, size, outcome and millisecond) are skipped
+//! and counted in the [`HistoryImportReport`], so importing a file twice
+//! changes nothing.
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use bytes::Bytes;
+use futures::{stream, Stream, StreamExt};
+use hsu_common::{Error, Result};
+
+use crate::types::EchoMethod;
+use crate::ByteStream;
+
+/// Entries per page when the query doesn't say.
+pub const DEFAULT_HISTORY_PAGE: usize = 100;
+
+/// Most entries a single page returns.
+pub const MAX_HISTORY_PAGE: usize = 1000;
+
+/// One processed echo call, as recorded in the history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Assigned by the store, increasing in recording order; pages are
+    /// cut at these.
+    pub id: u64,
+    /// Made up by the server's ID generator when the call is recorded
+    /// (like request and session IDs), and kept by export and import.
+    /// Empty for entries recorded without one.
+    pub record_id: String,
+    /// The method that was called.
+    pub method: EchoMethod,
+    /// The echoed text (empty for `echo_bytes` and `echo_file`).
+    pub message: String,
+    /// Size of the request payload in bytes.
+    pub request_bytes: u64,
+    /// `false` if the service returned an error.
+    pub success: bool,
+    /// When the call finished.
+    pub at: SystemTime,
+}
+
+/// Which history entries to return.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryQuery {
+    /// Continue after this cursor (a previous page's `next_cursor`).
+    pub cursor: Option<String>,
+    /// Entries per page (0: [`DEFAULT_HISTORY_PAGE`]); for
+    /// `stream_history`, the total to stream (0: all).
+    pub limit: u32,
+    /// Only entries recorded at or after this time.
+    pub since: Option<SystemTime>,
+    /// Only entries recorded before this time.
+    pub until: Option<SystemTime>,
+    /// Only entries whose message contains this text (case-sensitive).
+    pub contains: Option<String>,
+}
+
+impl HistoryQuery {
+    /// Queries the newest entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continues after `cursor`.
+    pub fn with_cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Sets the page size (or the stream's total).
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Keeps entries recorded in `since..until`.
+    pub fn with_time_range(mut self, since: Option<SystemTime>, until: Option<SystemTime>) -> Self {
+        self.since = since;
+        self.until = until;
+        self
+    }
+
+    /// Keeps entries whose message contains `text`.
+    pub fn with_contains(mut self, text: impl Into<String>) -> Self {
+        self.contains = Some(text.into());
+        self
+    }
+
+    /// The page size to use, within `1..=`[`MAX_HISTORY_PAGE`].
+    pub fn page_size(&self) -> usize {
+        match self.limit as usize {
+            0 => DEFAULT_HISTORY_PAGE,
+            limit => limit.min(MAX_HISTORY_PAGE),
+        }
+    }
+
+    /// The entry ID the cursor points past (entries below it come next).
+    pub fn cursor_id(&self) -> Result<Option<u64>> {
+        self.cursor
+            .as_deref()
+            .map(|cursor| cursor.parse().map_err(|_| Error::Validation {
+                message: format!("Invalid history cursor '{}'", cursor),
+            }))
+            .transpose()
+    }
+
+    /// Whether `entry` passes the filters (the cursor isn't checked).
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.since.map_or(true, |since| entry.at >= since)
+            && self.until.map_or(true, |until| entry.at < until)
+            && self.contains.as_deref().map_or(true, |text| entry.message.contains(text))
+    }
+}
+
+/// The cursor continuing after the entry `id`.
+pub fn history_cursor(id: u64) -> String {
+    id.to_string()
+}
+
+/// Response of [`EchoService::get_history`](crate::EchoService::get_history).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryPage {
+    /// Matching entries, newest first.
+    pub entries: Vec<HistoryEntry>,
+    /// Cursor of the next page (`None`: this was the last).
+    pub next_cursor: Option<String>,
+}
+
+/// A stream of history entries, newest first.
+pub type HistoryStream = Pin<Box<dyn Stream<Item = Result<HistoryEntry>> + Send>>;
+
+/// Streams every entry matching `query`, fetching one page after another.
+///
+/// Builds `stream_history` on top of any page source (a store, a
+/// `get_history` call); `query.limit` caps the total (0: all). A failed
+/// fetch ends the stream after its error.
+pub fn stream_pages<F, Fut>(query: HistoryQuery, fetch: F) -> HistoryStream
+where
+    F: Fn(HistoryQuery) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<HistoryPage>> + Send + 'static,
+{
+    let total = match query.limit {
+        0 => usize::MAX,
+        limit => limit as usize,
+    };
+    let first = HistoryQuery { limit: total.min(MAX_HISTORY_PAGE) as u32, ..query };
+    let pages = stream::unfold((Some(first), fetch), |(query, fetch)| async move {
+        let query = query?;
+        match fetch(query.clone()).await {
+            Ok(page) => {
+                let next = page.next_cursor.map(|cursor| HistoryQuery { cursor: Some(cursor), ..query });
+                Some((stream::iter(page.entries.into_iter().map(Ok)).left_stream(), (next, fetch)))
+            }
+            Err(e) => Some((stream::iter([Err(e)]).right_stream(), (None, fetch))),
+        }
+    });
+    Box::pin(pages.flatten().take(total))
+}
+
+/// Entries encoded per exported chunk (at most; fewer if the rest
+/// isn't fetched yet).
+const EXPORT_BATCH: usize = 256;
+
+/// File format of [`EchoService::export_history`](crate::EchoService::export_history).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HistoryExportFormat {
+    /// One JSON object per line.
+    #[default]
+    Ndjson,
+    /// A header line, then one record per entry (RFC 4180 quoting).
+    Csv,
+}
+
+impl HistoryExportFormat {
+    /// The name used on the wire and the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryExportFormat::Ndjson => "ndjson",
+            HistoryExportFormat::Csv => "csv",
+        }
+    }
+
+    /// MIME type, for HTTP downloads.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            HistoryExportFormat::Ndjson => "application/x-ndjson",
+            HistoryExportFormat::Csv => "text/csv",
+        }
+    }
+
+    /// The line before the first entry, if the format has one.
+    pub fn header(&self) -> Option<&'static str> {
+        match self {
+            HistoryExportFormat::Ndjson => None,
+            HistoryExportFormat::Csv => Some("id,method,message,request_bytes,success,at_unix_ms,record_id\n"),
+        }
+    }
+
+    /// Serializes `entry` as one line, `\n` included.
+    pub fn encode(&self, entry: &HistoryEntry) -> String {
+        let at_unix_ms = entry.at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+        match self {
+            HistoryExportFormat::Ndjson => format!(
+                "{{\"id\":{},\"method\":\"{}\",\"message\":{},\"request_bytes\":{},\"success\":{},\"at_unix_ms\":{},\"record_id\":{}}}\n",
+                entry.id, entry.method, json_string(&entry.message), entry.request_bytes, entry.success, at_unix_ms,
+                json_string(&entry.record_id),
+            ),
+            HistoryExportFormat::Csv => format!(
+                "{},{},{},{},{},{},{}\n",
+                entry.id, entry.method, csv_field(&entry.message), entry.request_bytes, entry.success, at_unix_ms,
+                csv_field(&entry.record_id),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for HistoryExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HistoryExportFormat {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ndjson" | "jsonl" => Ok(HistoryExportFormat::Ndjson),
+            "csv" => Ok(HistoryExportFormat::Csv),
+            _ => Err(Error::Validation {
+                message: format!("Unknown history export format '{}' (expected ndjson or csv)", name),
+            }),
+        }
+    }
+}
+
+/// Response of [`EchoService::import_history`](crate::EchoService::import_history).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryImportReport {
+    /// Entries added to the history.
+    pub imported: u64,
+    /// Entries skipped because the history already had them.
+    pub duplicates: u64,
+}
+
+/// Serializes a stream of entries as `format`, a batch of lines per chunk.
+///
+/// An entry error is passed on after the lines before it.
+pub fn encode_history(entries: HistoryStream, format: HistoryExportFormat) -> ByteStream {
+    let header = stream::iter(format.header().map(|header| Ok(Bytes::from_static(header.as_bytes()))));
+    let lines = entries.ready_chunks(EXPORT_BATCH).flat_map(move |batch| {
+        let mut chunks = Vec::new();
+        let mut lines = String::new();
+        for entry in batch {
+            match entry {
+                Ok(entry) => lines.push_str(&format.encode(&entry)),
+                Err(e) => {
+                    if !lines.is_empty() {
+                        chunks.push(Ok(Bytes::from(std::mem::take(&mut lines))));
+                    }
+                    chunks.push(Err(e));
+                }
+            }
+        }
+        if !lines.is_empty() {
+            chunks.push(Ok(Bytes::from(lines)));
+        }
+        stream::iter(chunks)
+    });
+    Box::pin(header.chain(lines))
+}
+
+/// `text` as a JSON string literal.
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `text` as a CSV field, quoted only if it has to be.
+fn csv_field(text: &str) -> Cow<'_, str> {
+    if text.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn entry(id: u64, message: &str, at_secs: u64) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            record_id: format!("r{}", id),
+            method: EchoMethod::Echo,
+            message: message.to_string(),
+            request_bytes: message.len() as u64,
+            success: true,
+            at: UNIX_EPOCH + Duration::from_secs(at_secs),
+        }
+    }
+
+    #[test]
+    fn test_filters_combine() {
+        let query = HistoryQuery::new()
+            .with_time_range(Some(UNIX_EPOCH + Duration::from_secs(10)), Some(UNIX_EPOCH + Duration::from_secs(20)))
+            .with_contains("ell");
+
+        assert!(query.matches(&entry(1, "hello", 10)));
+        assert!(!query.matches(&entry(2, "hello", 20)));
+        assert!(!query.matches(&entry(3, "hello", 9)));
+        assert!(!query.matches(&entry(4, "bye", 15)));
+        assert!(HistoryQuery::new().matches(&entry(5, "", 0)));
+    }
+
+    #[tokio::test]
+    async fn test_stream_pages_follows_cursors() {
+        // Ten entries, #10 newest, served three per page
+        let fetch = |query: HistoryQuery| async move {
+            let before = query.cursor_id()?.unwrap_or(11);
+            let entries: Vec<HistoryEntry> = (1..before).rev().take(3).map(|id| entry(id, "", 0)).collect();
+            let next_cursor = entries.last().filter(|last| last.id > 1).map(|last| history_cursor(last.id));
+            Ok(HistoryPage { entries, next_cursor })
+        };
+
+        let all: Vec<u64> = stream_pages(HistoryQuery::new(), fetch).map(|e| e.unwrap().id).collect().await;
+        assert_eq!(all, (1..=10).rev().collect::<Vec<_>>());
+        let capped: Vec<u64> = stream_pages(HistoryQuery::new().with_limit(4), fetch).map(|e| e.unwrap().id).collect().await;
+        assert_eq!(capped, vec![10, 9, 8, 7]);
+    }
+
+    #[test]
+    fn test_export_formats_escape_messages() {
+        let tricky = entry(7, "say \"hi\",\nbye\\", 1_700_000_000);
+        assert_eq!(
+            HistoryExportFormat::Ndjson.encode(&tricky),
+            "{\"id\":7,\"method\":\"echo\",\"message\":\"say \\\"hi\\\",\\nbye\\\\\",\"request_bytes\":14,\"success\":true,\"at_unix_ms\":1700000000000,\"record_id\":\"r7\"}\n",
+        );
+        assert_eq!(
+            HistoryExportFormat::Csv.encode(&tricky),
+            "7,echo,\"say \"\"hi\"\",\nbye\\\",14,true,1700000000000,r7\n",
+        );
+        assert_eq!(HistoryExportFormat::Csv.encode(&entry(8, "plain", 0)), "8,echo,plain,5,true,0,r8\n");
+        assert_eq!("CSV".parse::<HistoryExportFormat>().unwrap(), HistoryExportFormat::Csv);
+        assert!("xml".parse::<HistoryExportFormat>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_encode_history_writes_header_then_lines() {
+        let entries: HistoryStream = Box::pin(stream::iter([Ok(entry(2, "b", 0)), Ok(entry(1, "a", 0))]));
+        let chunks: Vec<Bytes> = encode_history(entries, HistoryExportFormat::Csv).map(|c| c.unwrap()).collect().await;
+        let file: Vec<u8> = chunks.concat();
+        assert_eq!(
+            String::from_utf8(file).unwrap(),
+            "id,method,message,request_bytes,success,at_unix_ms,record_id\n2,echo,b,1,true,0,r2\n1,echo,a,1,true,0,r1\n",
+        );
+    }
+
+    #[test]
+    fn test_page_size_and_cursor() {
+        assert_eq!(HistoryQuery::new().page_size(), DEFAULT_HISTORY_PAGE);
+        assert_eq!(HistoryQuery::new().with_limit(5).page_size(), 5);
+        assert_eq!(HistoryQuery::new().with_limit(u32::MAX).page_size(), MAX_HISTORY_PAGE);
+
+        assert_eq!(HistoryQuery::new().cursor_id().unwrap(), None);
+        assert_eq!(HistoryQuery::new().with_cursor(history_cursor(42)).cursor_id().unwrap(), Some(42));
+        assert!(HistoryQuery::new().with_cursor("bogus").cursor_id().is_err());
+    }
+}
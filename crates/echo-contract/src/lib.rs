@@ -33,7 +33,7 @@
 //! ```rust,ignore
 //! #[async_trait]
 //! pub trait EchoService: Send + Sync {
-//!     async fn echo(&self, message: String) -> Result<String>;
+//!     async fn echo(&self, message: Arc<str>) -> Result<Arc<str>>;
 //! }
 //!
 //! pub struct EchoServiceHandlers {
@@ -45,129 +45,99 @@
 //!     async fn get_service(&self, protocol: Protocol) -> Result<Arc<dyn EchoService>>;
 //! }
 //! ```
+//!
+//! Only the service trait is written by hand: `#[hsu_contract]` (from
+//! `echo-contract-macros`) generates the handlers struct, the gateways
+//! trait and the direct closure enabler.
+//!
+//! ## Cargo Features
+//!
+//! | Feature | Default | Provides                                                        |
+//! |---------|---------|-----------------------------------------------------------------|
+//! | `std`   | ✅      | `EchoService`, `EchoEvents`, gateways, `RequestContext`, `Message` |
+//! | `alloc` |         | Domain types (`EchoAck`, `Priority`, ...), `EchoErrorKind`, `Secret` |
+//!
+//! With `default-features = false, features = ["alloc"]` the crate is
+//! `#![no_std]` and depends on nothing: embedded or WASM consumers share
+//! the domain types even where the async traits can't run.
 
-use std::sync::Arc;
-use async_trait::async_trait;
-use hsu_common::{Result, ModuleID, ServiceID, Protocol};
+#![cfg_attr(not(feature = "std"), no_std)]
 
-/// Echo service contract (protocol-agnostic).
-///
-/// This trait defines the business interface without any protocol knowledge.
-/// It can be implemented by:
-/// - Domain layer (EchoServiceImpl)
-/// - Protocol adapters (EchoGrpcGateway)
-/// - Test mocks
-///
-/// # Rust Learning Note
-///
-/// ## Why Arc<dyn Trait>?
-///
-/// We use `Arc<dyn EchoService>` because:
-/// 1. **Arc**: Thread-safe shared ownership (Send + Sync)
-/// 2. **dyn**: Dynamic dispatch (trait object)
-/// 3. **EchoService**: The contract interface
-///
-/// This allows us to pass different implementations at runtime!
-#[async_trait]
-pub trait EchoService: Send + Sync {
-    /// Echoes the input message.
-    ///
-    /// This is pure business logic - no protocol knowledge!
-    async fn echo(&self, message: String) -> Result<String>;
-}
+#[cfg(feature = "alloc")]
+extern crate alloc;
+// Code generated by `#[hsu_contract]` names this crate, here too
+#[cfg(feature = "std")]
+extern crate self as echo_contract;
 
-/// Service handlers provided by server module.
-///
-/// This struct holds the actual service implementations that will be
-/// registered with protocol servers (gRPC, HTTP, etc.).
-///
-/// # Rust Learning Note
-///
-/// In Golang:
-/// ```go
-/// type EchoServiceHandlers struct {
-///     Service1 Service1
-/// }
-/// ```
-///
-/// In Rust:
-/// ```rust,ignore
-/// pub struct EchoServiceHandlers {
-///     pub service: Arc<dyn EchoService>,
-/// }
-/// ```
-///
-/// Same concept - holder for service implementations!
-#[derive(Clone)]
-pub struct EchoServiceHandlers {
-    /// The echo service implementation
-    pub service: Arc<dyn EchoService>,
-}
+#[cfg(feature = "alloc")]
+pub mod errors;
+#[cfg(feature = "alloc")]
+pub mod secret;
+#[cfg(feature = "alloc")]
+pub mod types;
 
-impl EchoServiceHandlers {
-    /// Creates new service handlers.
-    pub fn new(service: Arc<dyn EchoService>) -> Self {
-        Self { service }
-    }
-}
+#[cfg(feature = "std")]
+pub mod capabilities;
+#[cfg(feature = "std")]
+pub mod context;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
+pub mod gateway_ext;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "std")]
+pub mod messages;
+#[cfg(feature = "std")]
+pub mod schedule;
+#[cfg(feature = "std")]
+mod service;
+#[cfg(feature = "std")]
+pub mod spans;
+#[cfg(feature = "std")]
+pub mod transform;
 
-/// Service gateways provided by wiring layer.
-///
-/// This trait defines how to get service instances with different protocols.
-/// It's implemented by the wiring layer and used by client modules.
-///
-/// # Rust Learning Note
-///
-/// ## The Gateway Pattern
-///
-/// ```text
-/// Client Module
-///     ↓ asks for
-/// EchoServiceGateways
-///     ↓ returns
-/// Arc<dyn EchoService>
-///     ↓ client uses
-/// .echo("Hello!")
-/// ```
-///
-/// The client doesn't know or care if it's:
-/// - Direct (local call)
-/// - gRPC (remote call)
-/// - HTTP (future)
-///
-/// Protocol selection is transparent!
-#[async_trait]
-pub trait EchoServiceGateways: Send + Sync {
-    /// Returns the target module ID (e.g., "echo").
-    fn module_id(&self) -> ModuleID;
-    
-    /// Returns the list of service IDs provided.
-    fn service_ids(&self) -> Vec<ServiceID>;
-    
-    /// Enables direct closure (local calls) by registering handlers.
-    ///
-    /// This is called during module initialization to enable
-    /// in-process calls without going through gRPC/HTTP.
-    fn enable_direct_closure(&self, handlers: EchoServiceHandlers);
-    
-    /// Gets the echo service using the specified protocol.
-    ///
-    /// # Arguments
-    ///
-    /// * `protocol` - Protocol to use (Direct, Grpc, Http, Auto)
-    ///
-    /// # Returns
-    ///
-    /// A trait object that implements `EchoService`.
-    ///
-    /// # Rust Learning Note
-    ///
-    /// This is equivalent to Golang's:
-    /// ```go
-    /// func (g *EchoServiceGateways) GetService1(ctx context.Context, protocol Protocol) (Service1, error)
-    /// ```
-    ///
-    /// Both return an interface/trait that the caller can use!
-    async fn get_service(&self, protocol: Protocol) -> Result<Arc<dyn EchoService>>;
-}
+#[cfg(feature = "alloc")]
+pub use errors::{
+    EchoErrorKind, FieldViolation, DEADLINE_EXCEEDED, INTEGRITY_ERROR, INVALID_ARGUMENT, INVALID_RESPONSE, OVERLOADED,
+    PROTOCOL_UNSUPPORTED, STORAGE_ERROR, UNAVAILABLE,
+};
+#[cfg(feature = "alloc")]
+pub use secret::Secret;
+#[cfg(feature = "alloc")]
+pub use types::{
+    EchoAck, EchoMethod, EchoServiceId, FileDigest, Priority, CALLER_METADATA_KEY, CHECKSUM_METADATA_KEY,
+    ACCEPT_LANGUAGE_METADATA_KEY, ECHO_CLIENT_MODULE_ID, ECHO_MODULE_ID, ECHO_MONITOR_MODULE_ID, NONCE_METADATA_KEY, PRIORITY_METADATA_KEY, PROBE_METADATA_KEY, SIGNATURE_METADATA_KEY, TIMESTAMP_METADATA_KEY, TRACE_METADATA_KEY, TRANSFORM_METADATA_KEY,
+};
 
+#[cfg(feature = "std")]
+pub use capabilities::{GatewayCapabilities, ProtocolCapabilities, ProtocolUnsupported, StandbyState, StandbyStatus};
+#[cfg(feature = "std")]
+pub use context::{
+    attach_response_metadata, collect_response_metadata, count_attempts, record_attempt, CallInfo, ProtocolRace,
+    RequestContext, RESPONSE_METADATA_PREFIX,
+};
+#[cfg(feature = "std")]
+pub use events::{EchoEvent, EchoEventStream, EchoEvents};
+#[cfg(feature = "std")]
+pub use echo_contract_macros::hsu_contract;
+#[cfg(feature = "std")]
+pub use gateway_ext::{EchoServiceGatewaysExt, FALLBACK_PROTOCOLS};
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub use gateway_ext::__private;
+#[cfg(feature = "std")]
+pub use history::{
+    encode_history, history_cursor, stream_pages, HistoryEntry, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, DEFAULT_HISTORY_PAGE, MAX_HISTORY_PAGE,
+};
+#[cfg(feature = "std")]
+pub use schedule::{format_duration, parse_duration, EchoSchedule, ScheduledEcho};
+#[cfg(feature = "std")]
+pub use service::*;
+#[cfg(feature = "std")]
+pub use messages::{Locale, Message, CONTENT_LANGUAGE_METADATA_KEY};
+#[cfg(feature = "std")]
+pub use spans::{current_request_id, protocol_field};
+#[cfg(feature = "std")]
+pub use transform::{format_transforms, parse_transforms, CaseLocale, EchoTransform, MAX_TRANSFORMS};
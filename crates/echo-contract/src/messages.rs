@@ -0,0 +1,199 @@
+//! Message catalog (user-facing error texts).
+//!
+//! # Architecture
+//!
+//! Errors a user can act on - a request field that failed validation, a
+//! quota that ran out - are rendered from this catalog in the caller's
+//! language. The language comes from the call's context, not from an
+//! argument, so no `EchoService` signature changes:
+//!
+//! ```text
+//! echo-grpc-cli --accept-language de
+//!     ↓ accept-language: de                 (RequestContext::accept_language)
+//! EchoServiceImpl / quota decorator
+//!     ↓ Message::EmptyField.localize()      → "darf nicht leer sein"
+//!     ↓ content-language: de                (response metadata)
+//! EchoGrpcHandler
+//!     ↓ INVALID_ARGUMENT "... darf nicht leer sein"
+//!       + google.rpc.LocalizedMessage { locale: "de", message: "darf nicht leer sein" }
+//! ```
+//!
+//! Without `accept-language` the texts are the English ones, unchanged.
+//! Everything outside the catalog (internal failures, logs) stays English.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::context::{attach_response_metadata, RequestContext};
+
+/// Response metadata key naming the language of a localized error.
+pub const CONTENT_LANGUAGE_METADATA_KEY: &str = "content-language";
+
+/// A language of the catalog.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Fr,
+    Es,
+}
+
+impl Locale {
+    /// All locales, English (the fallback) first.
+    pub const ALL: [Locale; 4] = [Locale::En, Locale::De, Locale::Fr, Locale::Es];
+
+    /// The BCP 47 tag, e.g. `de`.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Fr => "fr",
+            Locale::Es => "es",
+        }
+    }
+
+    /// The locale of a language tag; only the primary subtag counts
+    /// (`de-CH` is `de`).
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let primary = tag.trim().split(['-', '_']).next()?;
+        Locale::ALL.into_iter().find(|locale| locale.tag().eq_ignore_ascii_case(primary))
+    }
+
+    /// Picks the catalog locale an `Accept-Language` value prefers most
+    /// (`de-CH, fr;q=0.8, *;q=0.1`); English if it names none of them.
+    pub fn negotiate(accept_language: &str) -> Locale {
+        let mut ranges: Vec<(&str, f32)> = accept_language
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse().ok())?;
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        // Stable: equally preferred ranges keep their order
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranges
+            .into_iter()
+            .find_map(|(tag, _)| if tag == "*" { Some(Locale::En) } else { Locale::from_tag(tag) })
+            .unwrap_or_default()
+    }
+
+    /// The locale the running call asked for; `None` if it didn't.
+    pub fn current() -> Option<Locale> {
+        RequestContext::current().accept_language.as_deref().map(Locale::negotiate)
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.tag())
+    }
+}
+
+/// A user-facing error text of the catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message<'a> {
+    /// A required request field was empty.
+    EmptyField,
+    /// A streamed file grew past the server's limit.
+    FileTooLarge { limit: u64 },
+    /// A caller's byte quota ran out.
+    QuotaExceeded { caller: &'a str, used: u64, limit: u64, window: Duration },
+    /// A request is larger than the whole byte quota.
+    RequestOverQuota { bytes: u64, limit: u64 },
+}
+
+impl Message<'_> {
+    /// Renders the text in `locale`.
+    pub fn render(&self, locale: Locale) -> String {
+        match (self, locale) {
+            (Message::EmptyField, Locale::En) => "must not be empty".to_string(),
+            (Message::EmptyField, Locale::De) => "darf nicht leer sein".to_string(),
+            (Message::EmptyField, Locale::Fr) => "ne doit pas être vide".to_string(),
+            (Message::EmptyField, Locale::Es) => "no debe estar vacío".to_string(),
+
+            (Message::FileTooLarge { limit }, Locale::En) => format!("File exceeds limit of {} bytes", limit),
+            (Message::FileTooLarge { limit }, Locale::De) => format!("Datei überschreitet die Grenze von {} Bytes", limit),
+            (Message::FileTooLarge { limit }, Locale::Fr) => format!("Le fichier dépasse la limite de {} octets", limit),
+            (Message::FileTooLarge { limit }, Locale::Es) => format!("El archivo supera el límite de {} bytes", limit),
+
+            (Message::QuotaExceeded { caller, used, limit, window }, Locale::En) => format!(
+                "byte quota of {} exceeded: {} of {} bytes used in the last {:?}", caller, used, limit, window),
+            (Message::QuotaExceeded { caller, used, limit, window }, Locale::De) => format!(
+                "Byte-Kontingent von {} überschritten: {} von {} Bytes in den letzten {:?} verbraucht", caller, used, limit, window),
+            (Message::QuotaExceeded { caller, used, limit, window }, Locale::Fr) => format!(
+                "quota d'octets de {} dépassé : {} sur {} octets utilisés au cours des dernières {:?}", caller, used, limit, window),
+            (Message::QuotaExceeded { caller, used, limit, window }, Locale::Es) => format!(
+                "cuota de bytes de {} superada: {} de {} bytes usados en los últimos {:?}", caller, used, limit, window),
+
+            (Message::RequestOverQuota { bytes, limit }, Locale::En) => format!(
+                "request of {} bytes exceeds the byte quota of {} bytes", bytes, limit),
+            (Message::RequestOverQuota { bytes, limit }, Locale::De) => format!(
+                "Anfrage mit {} Bytes überschreitet das Byte-Kontingent von {} Bytes", bytes, limit),
+            (Message::RequestOverQuota { bytes, limit }, Locale::Fr) => format!(
+                "la requête de {} octets dépasse le quota de {} octets", bytes, limit),
+            (Message::RequestOverQuota { bytes, limit }, Locale::Es) => format!(
+                "la solicitud de {} bytes supera la cuota de {} bytes", bytes, limit),
+        }
+    }
+
+    /// Renders the text in the running call's locale (see
+    /// [`Locale::current`]) and, if the call asked for one, names it in
+    /// the response metadata ([`CONTENT_LANGUAGE_METADATA_KEY`]).
+    pub fn localize(&self) -> String {
+        match Locale::current() {
+            Some(locale) => {
+                attach_response_metadata(CONTENT_LANGUAGE_METADATA_KEY, locale.tag());
+                self.render(locale)
+            }
+            None => self.render(Locale::En),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::collect_response_metadata;
+    use crate::types::Priority;
+
+    #[test]
+    fn test_negotiate_follows_quality() {
+        assert_eq!(Locale::negotiate("de-CH"), Locale::De);
+        assert_eq!(Locale::negotiate("ja, fr;q=0.5, de;q=0.8"), Locale::De);
+        assert_eq!(Locale::negotiate("es;q=0, fr;q=0.1"), Locale::Fr);
+        assert_eq!(Locale::negotiate("ja, *;q=0.5, es;q=0.2"), Locale::En);
+        assert_eq!(Locale::negotiate("ja"), Locale::En);
+        assert_eq!(Locale::negotiate(""), Locale::En);
+    }
+
+    #[test]
+    fn test_every_message_in_every_locale() {
+        let messages = [
+            Message::EmptyField,
+            Message::FileTooLarge { limit: 10 },
+            Message::QuotaExceeded { caller: "cli", used: 90, limit: 100, window: Duration::from_secs(60) },
+            Message::RequestOverQuota { bytes: 200, limit: 100 },
+        ];
+        for message in &messages {
+            let english = message.render(Locale::En);
+            for locale in &Locale::ALL[1..] {
+                assert_ne!(message.render(*locale), english, "{:?} in {}", message, locale);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_localize_uses_the_call_context() {
+        assert_eq!(Message::EmptyField.localize(), "must not be empty");
+
+        let context = RequestContext::new(Priority::Normal).with_accept_language("fr-CA, en;q=0.5");
+        let (text, metadata) = collect_response_metadata(context.scope(async { Message::EmptyField.localize() })).await;
+        assert_eq!(text, "ne doit pas être vide");
+        assert_eq!(metadata.get(CONTENT_LANGUAGE_METADATA_KEY).map(String::as_str), Some("fr"));
+    }
+}
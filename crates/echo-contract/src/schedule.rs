@@ -0,0 +1,156 @@
+//! Server-side scheduled echoes.
+//!
+//! # Architecture
+//!
+//! [`EchoService::schedule_echo`](crate::EchoService::schedule_echo) hands
+//! a message to the server's scheduler, which echoes it later - once, or
+//! on a recurring schedule - and publishes each run like any other call:
+//!
+//! ```text
+//! schedule_echo("tick", "every 5m") ─→ job store ─→ scheduler loop
+//!                                                       ↓ when due
+//!                                            echo("tick") ─→ EchoEvents
+//! ```
+//!
+//! A schedule travels as text, so every protocol carries it as a plain
+//! string:
+//!
+//! | Text                | Schedule                                 |
+//! |---------------------|------------------------------------------|
+//! | `30s`, `in 30s`     | once, after 30 seconds                   |
+//! | `every 5m`          | every 5 minutes, first run in 5 minutes  |
+//! | `*/15 9-17 * * 1-5` | five-field cron expression (UTC)         |
+//!
+//! Durations take the units `ms`, `s`, `m`, `h` and `d`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use hsu_common::{Error, Result};
+
+/// When a scheduled echo runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EchoSchedule {
+    /// Once, after the delay.
+    After(Duration),
+    /// Repeatedly at this interval, starting one interval from now.
+    Every(Duration),
+    /// Whenever the five-field cron expression matches (minute granularity, UTC).
+    ///
+    /// Only the field count is checked here; the server validates the
+    /// fields when the job is scheduled.
+    Cron(String),
+}
+
+impl FromStr for EchoSchedule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(interval) = s.strip_prefix("every ") {
+            let interval = parse_duration(interval)?;
+            if interval.is_zero() {
+                return Err(invalid(s, "the interval must be positive"));
+            }
+            return Ok(EchoSchedule::Every(interval));
+        }
+        if let Some(delay) = s.strip_prefix("in ") {
+            return Ok(EchoSchedule::After(parse_duration(delay)?));
+        }
+        if let Ok(delay) = parse_duration(s) {
+            return Ok(EchoSchedule::After(delay));
+        }
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() == 5 {
+            return Ok(EchoSchedule::Cron(fields.join(" ")));
+        }
+        Err(invalid(s, "expected a delay (30s), 'every <interval>' or a five-field cron expression"))
+    }
+}
+
+impl fmt::Display for EchoSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EchoSchedule::After(delay) => write!(f, "in {}", format_duration(*delay)),
+            EchoSchedule::Every(interval) => write!(f, "every {}", format_duration(*interval)),
+            EchoSchedule::Cron(expression) => f.write_str(expression),
+        }
+    }
+}
+
+/// Response of [`EchoService::schedule_echo`](crate::EchoService::schedule_echo).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledEcho {
+    /// ID to cancel the job with.
+    pub job_id: String,
+    /// When the job runs first.
+    pub next_run: SystemTime,
+}
+
+fn invalid(schedule: &str, reason: &str) -> Error {
+    Error::Validation {
+        message: format!("Invalid schedule '{}': {}", schedule, reason),
+    }
+}
+
+/// Parses `<number><unit>` with the units `ms`, `s`, `m`, `h` and `d`.
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let invalid = |reason: &str| Error::Validation {
+        message: format!("Invalid duration '{}': {}", s, reason),
+    };
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number.parse().map_err(|_| invalid("expected a duration like 30s"))?;
+    let millis = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        _ => return Err(invalid("unknown unit (expected ms, s, m, h or d)")),
+    };
+    number
+        .checked_mul(millis)
+        .map(Duration::from_millis)
+        .ok_or_else(|| invalid("duration too large"))
+}
+
+/// Formats `duration` in the largest unit that represents it exactly.
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    for (unit, size) in [("d", 86_400_000), ("h", 3_600_000), ("m", 60_000), ("s", 1_000)] {
+        if millis > 0 && millis % size == 0 {
+            return format!("{}{}", millis / size, unit);
+        }
+    }
+    format!("{}ms", millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_round_trip() {
+        let cases = [
+            ("30s", EchoSchedule::After(Duration::from_secs(30))),
+            ("in 1500ms", EchoSchedule::After(Duration::from_millis(1500))),
+            ("every 5m", EchoSchedule::Every(Duration::from_secs(300))),
+            ("*/15  9-17 * * 1-5", EchoSchedule::Cron("*/15 9-17 * * 1-5".to_string())),
+        ];
+        for (text, expected) in cases {
+            let schedule: EchoSchedule = text.parse().unwrap();
+            assert_eq!(schedule, expected);
+            assert_eq!(schedule.to_string().parse::<EchoSchedule>().unwrap(), expected);
+        }
+        assert_eq!(EchoSchedule::After(Duration::from_secs(7200)).to_string(), "in 2h");
+    }
+
+    #[test]
+    fn test_invalid_schedules() {
+        for text in ["", "soon", "every 0s", "every 5x", "* * *"] {
+            assert!(text.parse::<EchoSchedule>().is_err(), "{:?} parsed", text);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Secret values (keys, tokens) that never show up in logs.
+//!
+//! # Architecture
+//!
+//! ```text
+//! ECHO_PAYLOAD_KEY=cli-1:<hex>             ─┐
+//! ECHO_PAYLOAD_KEY_FILE=/run/secrets/key   ─┴→ Secret::from_env("ECHO_PAYLOAD_KEY")
+//!                                                 ↓ Secret<String>
+//!                                              PayloadKey::parse(secret.expose())
+//!                                                 ↓ key material kept as Secret<..>
+//! {:?} anywhere (configs, options, errors) → Secret(<redacted>)
+//! drop                                     → value overwritten with zeros
+//! ```
+//!
+//! Secrets are read from the environment, or from a file the environment
+//! names (`NAME_FILE`, the Docker/Kubernetes secrets convention) - never
+//! from flags, which end up in `ps` output, shell history and the
+//! startup config dump.
+//!
+//! # Rust Learning Note
+//!
+//! `Secret<T>` implements `Debug` without looking at `T`, so a struct
+//! holding one can `#[derive(Debug)]` safely. There is deliberately no
+//! `Display`: getting at the value takes an explicit
+//! [`expose`](Secret::expose), which is easy to find in review.
+//!
+//! Its `Drop` zeroizes the value (`T: Zeroize`), so a freed key doesn't
+//! linger in the allocator's free lists or a core dump. The compiler may
+//! still have copied it elsewhere (a moved array, a reallocated `Vec`);
+//! zeroizing shrinks the exposure, it doesn't rule it out.
+
+use core::fmt;
+use core::str::FromStr;
+use zeroize::Zeroize;
+
+/// What a secret formats as.
+pub const REDACTED: &str = "<redacted>";
+
+/// Suffix of the variable naming a file that holds a secret.
+pub const FILE_SUFFIX: &str = "_FILE";
+
+/// A value that `Debug` prints as `Secret(<redacted>)` and that is
+/// zeroized when dropped.
+#[derive(Clone, Default)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    /// Wraps `value`.
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns the secret value.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns the secret value for filling it in place.
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret({})", REDACTED)
+    }
+}
+
+impl FromStr for Secret<alloc::string::String> {
+    type Err = core::convert::Infallible;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(Self(value.into()))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Secret<String> {
+    /// Reads secret `name` from the environment: the variable itself, or
+    /// the file named by `NAME_FILE` (trailing newlines trimmed).
+    ///
+    /// `None` if neither is set; an error if both are, or the file can't
+    /// be read.
+    pub fn from_env(name: &str) -> hsu_common::Result<Option<Self>> {
+        Self::from_variables(name, |variable| std::env::var_os(variable))
+    }
+
+    /// [`from_env`](Self::from_env) with the variables looked up by `var`.
+    fn from_variables(
+        name: &str,
+        var: impl Fn(&str) -> Option<std::ffi::OsString>,
+    ) -> hsu_common::Result<Option<Self>> {
+        let file_variable = format!("{}{}", name, FILE_SUFFIX);
+        match (var(name).and_then(|value| value.into_string().ok()), var(&file_variable)) {
+            (Some(_), Some(_)) => Err(hsu_common::Error::Validation {
+                message: format!("Set either {} or {}, not both", name, file_variable),
+            }),
+            (Some(value), None) => Ok(Some(Self(value))),
+            (None, Some(path)) => {
+                let path = std::path::PathBuf::from(path);
+                let value = std::fs::read_to_string(&path).map(Self).map_err(|e| hsu_common::Error::Validation {
+                    message: format!("{} ({}): {}", file_variable, path.display(), e),
+                })?;
+                Ok(Some(Self(value.0.trim_end_matches(['\r', '\n']).to_string())))
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Splits a list of secrets (separated by commas or whitespace, e.g.
+    /// one per line of a secrets file) into its entries.
+    pub fn split_list(&self) -> Vec<Secret<String>> {
+        self.0
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| Secret(entry.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_is_redacted() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Options {
+            caller: &'static str,
+            key: Secret<String>,
+        }
+
+        let options = Options { caller: "cli-1", key: Secret::new("00ff00ff".into()) };
+        assert_eq!(format!("{:?}", options), "Options { caller: \"cli-1\", key: Secret(<redacted>) }");
+        assert_eq!(options.key.expose(), "00ff00ff");
+    }
+
+    #[test]
+    fn test_drop_zeroizes() {
+        let mut key = Secret::new([0xffu8; 4]);
+        key.expose_mut()[0] = 0x00;
+        assert_eq!(*key.expose(), [0x00, 0xff, 0xff, 0xff]);
+        // What `Drop` runs
+        key.expose_mut().zeroize();
+        assert_eq!(*key.expose(), [0; 4]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_env_and_file() {
+        let dir = std::env::temp_dir().join(format!("echo-secret-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("keys");
+        std::fs::write(&path, "cli-1:00ff\ncli-2:ff00\n").unwrap();
+
+        // A private environment: tests run on parallel threads, and
+        // `set_var` races with every other thread reading the environment
+        let mut environment = std::collections::HashMap::new();
+        environment.insert("SECRET", std::ffi::OsString::from("s3cr3t"));
+        environment.insert("SECRET_LIST_FILE", path.clone().into_os_string());
+        let from = |environment: &std::collections::HashMap<&str, std::ffi::OsString>, name| {
+            Secret::from_variables(name, |variable| environment.get(variable).cloned())
+        };
+
+        let direct = from(&environment, "SECRET").unwrap().unwrap();
+        let list = from(&environment, "SECRET_LIST").unwrap().unwrap();
+        assert_eq!(direct.expose(), "s3cr3t");
+        let entries: Vec<String> = list.split_list().iter().map(|entry| entry.expose().clone()).collect();
+        assert_eq!(entries, ["cli-1:00ff", "cli-2:ff00"]);
+        assert!(from(&environment, "SECRET_UNSET").unwrap().is_none());
+
+        environment.insert("SECRET_FILE", path.into_os_string());
+        assert!(from(&environment, "SECRET").is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,435 @@
+//! The async service contract (`std` feature).
+//!
+//! Service, gateway and handler types built on `async-trait`, `futures`
+//! and `hsu_common`, plus the `hsu_common::Error` helpers for the
+//! contract's [`EchoErrorKind`]s.
+
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use async_trait::async_trait;
+use echo_contract_macros::hsu_contract;
+use bytes::Bytes;
+use futures::Stream;
+use hsu_common::{Error, Result, ModuleID, ServiceID, Protocol};
+
+use crate::capabilities::GatewayCapabilities;
+use crate::context::CallInfo;
+use crate::errors::{EchoErrorKind, FieldViolation, UNAVAILABLE};
+use crate::events::EchoEvents;
+use crate::history::{HistoryExportFormat, HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream};
+use crate::schedule::{EchoSchedule, ScheduledEcho};
+use crate::types::{EchoAck, EchoServiceId, FileDigest, ECHO_MODULE_ID};
+
+/// A stream of binary chunks (used for large payloads).
+///
+/// # Rust Learning Note
+///
+/// `Bytes` is a reference-counted byte buffer - cloning or passing it
+/// around never copies the data. On the Direct path the chunks produced
+/// by the caller reach the service **without a single copy**.
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>;
+
+/// Echo service contract (protocol-agnostic).
+///
+/// This trait defines the business interface without any protocol knowledge.
+/// It can be implemented by:
+/// - Domain layer (EchoServiceImpl)
+/// - Protocol adapters (EchoGrpcGateway)
+/// - Test mocks
+///
+/// Only [`echo`](Self::echo) is required; every other method fails with
+/// [`not_supported`] unless implemented. Decorators must still forward
+/// each method, or the calls they wrap stop at them.
+///
+/// # Rust Learning Note
+///
+/// ## Why Arc<dyn Trait>?
+///
+/// We use `Arc<dyn EchoService>` because:
+/// 1. **Arc**: Thread-safe shared ownership (Send + Sync)
+/// 2. **dyn**: Dynamic dispatch (trait object)
+/// 3. **EchoService**: The contract interface
+///
+/// This allows us to pass different implementations at runtime!
+///
+/// ## Generated companions
+///
+/// [`hsu_contract`] generates [`EchoServiceHandlers`] (what the server
+/// module registers), [`EchoServiceGateways`] (how clients get an
+/// `Arc<dyn EchoService>` over Direct, gRPC or HTTP) and
+/// [`echo_direct_closure_enabler`] from this trait, the way the Go
+/// examples' `protoc` plugins do:
+///
+/// ```text
+/// Client Module ──asks──→ EchoServiceGateways ──returns──→ Arc<dyn EchoService> ──→ .echo("Hello!")
+/// ```
+///
+/// The client doesn't know or care which protocol answers.
+///
+/// ## Why Arc<str> for messages?
+///
+/// Messages are passed as `Arc<str>` rather than `String`. Cloning an
+/// `Arc<str>` only bumps a reference count, so paths that keep or resend
+/// a message (retries, outbox, dedup store) never copy its contents, and
+/// the Direct protocol hands the same allocation from client to server.
+/// Only the gRPC adapters convert to `String`, at the serialization
+/// boundary where a copy happens anyway.
+#[hsu_contract(
+    events = EchoEvents,
+    direct_closure = echo_direct_closure_enabler,
+    gateway_items = {
+        /// Describes what each protocol can do, so callers can check for a
+        /// missing protocol before asking [`get_service`](Self::get_service).
+        fn capabilities(&self) -> GatewayCapabilities;
+
+        /// Echoes `message` and reports how the call was carried out.
+        ///
+        /// Lets callers, tests and the CLI's JSON output check which transport
+        /// `Auto` picked, without parsing logs.
+        async fn echo_with_info(&self, protocol: Protocol, message: Arc<str>) -> Result<(Arc<str>, CallInfo)>;
+    },
+)]
+#[async_trait]
+pub trait EchoService: Send + Sync {
+    /// Echoes the input message.
+    ///
+    /// This is pure business logic - no protocol knowledge!
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>>;
+
+    /// Echoes an arbitrary binary payload.
+    ///
+    /// Unlike [`echo`](Self::echo), the payload doesn't have to be valid
+    /// UTF-8. It maps to a proto `bytes` field on gRPC; JSON-based adapters
+    /// (HTTP) carry it base64-encoded.
+    async fn echo_bytes(&self, _payload: Bytes) -> Result<Bytes> {
+        Err(not_supported("echo_bytes"))
+    }
+
+    /// Echoes the input message with at-least-once semantics.
+    ///
+    /// The caller generates one `idempotency_key` per logical message and
+    /// reuses it on every retry. The server remembers recently processed
+    /// keys, so a retried request is acknowledged again without being
+    /// processed twice.
+    async fn echo_reliable(&self, _message: Arc<str>, _idempotency_key: String) -> Result<EchoAck> {
+        Err(not_supported("echo_reliable"))
+    }
+
+    /// Consumes a stream of file chunks and returns their digest.
+    ///
+    /// Demonstrates large-payload handling: the payload is never buffered
+    /// as a whole, only hashed chunk by chunk.
+    async fn echo_file(&self, _chunks: ByteStream) -> Result<FileDigest> {
+        Err(not_supported("echo_file"))
+    }
+
+    /// Echoes the input message within a session.
+    ///
+    /// The server keeps per-session state keyed by `session_id` (message
+    /// count, last seen), so repeated calls with the same ID - over any
+    /// protocol, from any connection - see the counter grow. Idle sessions
+    /// expire on the server. An empty `session_id` starts a new session;
+    /// the reply carries the ID the server gave it.
+    async fn echo_with_session(&self, _session_id: String, _message: Arc<str>) -> Result<SessionEcho> {
+        Err(not_supported("echo_with_session"))
+    }
+
+    /// Describes the serving instance: module, instance ID, build, uptime.
+    ///
+    /// Tells instances apart behind a load balancer and names the build a
+    /// bug report was made against. Wrappers pass it through unchanged -
+    /// it is a query, not an echo, so it isn't limited, recorded or
+    /// published as an event.
+    async fn get_info(&self) -> Result<ServerInfo> {
+        Err(not_supported("get_info"))
+    }
+
+    /// Has the server echo `message` later, once or recurring (see
+    /// [`crate::schedule`]).
+    ///
+    /// Each run is an ordinary `echo` on the server, published on
+    /// [`EchoEvents`]. Jobs survive a server restart if the server keeps
+    /// them in a persistent job store. Servers without a scheduler fail
+    /// the call.
+    async fn schedule_echo(&self, _message: Arc<str>, _schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        Err(not_supported("schedule_echo"))
+    }
+
+    /// Cancels a job created by [`schedule_echo`](Self::schedule_echo).
+    ///
+    /// Returns `false` if no such job is pending (unknown ID, or a
+    /// one-off job that already ran).
+    async fn cancel_scheduled_echo(&self, _job_id: String) -> Result<bool> {
+        Err(not_supported("cancel_scheduled_echo"))
+    }
+
+    /// Returns one page of the server's call history, newest first (see
+    /// [`crate::history`]).
+    ///
+    /// Like [`get_info`](Self::get_info), a query: wrappers pass it
+    /// through, and it isn't recorded itself. Servers without a history
+    /// fail the call.
+    async fn get_history(&self, _query: HistoryQuery) -> Result<HistoryPage> {
+        Err(not_supported("get_history"))
+    }
+
+    /// Streams every entry matching `query`, newest first.
+    ///
+    /// For result sets too large for one page; `query.limit` caps the
+    /// total instead of the page size.
+    async fn stream_history(&self, _query: HistoryQuery) -> Result<HistoryStream> {
+        Err(not_supported("stream_history"))
+    }
+
+    /// Streams the entries matching `query` as a file in `format` (see
+    /// [`crate::history`]), for download or archiving.
+    ///
+    /// `query` works as for [`stream_history`](Self::stream_history).
+    /// The chunks carry whole lines; concatenated they are the file.
+    async fn export_history(&self, _format: HistoryExportFormat, _query: HistoryQuery) -> Result<ByteStream> {
+        Err(not_supported("export_history"))
+    }
+
+    /// Adds the entries of an exported file, streamed in `data`, to the
+    /// history (see [`crate::history`]).
+    ///
+    /// Chunks may split records anywhere. A malformed record fails the
+    /// whole import with `Error::Validation`; nothing is added. Imported
+    /// entries get new IDs and publish no events - they were published
+    /// where they were recorded.
+    async fn import_history(&self, _format: HistoryExportFormat, _data: ByteStream) -> Result<HistoryImportReport> {
+        Err(not_supported("import_history"))
+    }
+}
+
+/// The error of an [`EchoService`] method the implementation doesn't provide.
+pub fn not_supported(method: &str) -> Error {
+    Error::Protocol(format!("{} is not supported by this service", method))
+}
+
+/// Response of [`EchoService::get_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerInfo {
+    /// Module serving the call (e.g. `echo`).
+    pub module_id: String,
+    /// Random ID of the serving process, fixed for its lifetime.
+    pub instance_id: String,
+    /// Crate version of the server.
+    pub version: String,
+    /// Git commit the server was built from (`unknown` outside a checkout).
+    pub git_hash: String,
+    /// Time since the service was created.
+    pub uptime: Duration,
+    /// Cargo features and optional subsystems enabled in the server.
+    pub features: Vec<String>,
+}
+
+/// Response of [`EchoService::echo_with_session`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionEcho {
+    /// The echoed message.
+    pub message: Arc<str>,
+    /// The session the message was counted in.
+    pub session_id: String,
+    /// Number of messages received in this session, including this one.
+    pub count: u64,
+    /// When the session's previous message was received (`None` for the first).
+    pub previous_seen: Option<SystemTime>,
+}
+
+impl From<EchoServiceId> for ServiceID {
+    fn from(service: EchoServiceId) -> Self {
+        ServiceID::from(service.as_str())
+    }
+}
+
+/// Returns the echo module's ID ([`ECHO_MODULE_ID`]).
+pub fn echo_module_id() -> ModuleID {
+    ModuleID::from(ECHO_MODULE_ID)
+}
+
+/// Classifies `error` into the contract's [`EchoErrorKind`]s.
+pub fn error_kind(error: &Error) -> Option<EchoErrorKind> {
+    match error {
+        Error::Protocol(message) => EchoErrorKind::classify(message),
+        _ => None,
+    }
+}
+
+/// Creates the error returned when a call misses its deadline.
+///
+/// # Rust Learning Note
+///
+/// `hsu_common::Error` has no dedicated variant for this, so the error
+/// is a `Protocol` error with a stable prefix. Use
+/// [`is_deadline_exceeded`] rather than matching on the message.
+pub fn deadline_exceeded(detail: impl fmt::Display) -> Error {
+    Error::Protocol(EchoErrorKind::DeadlineExceeded.message(detail))
+}
+
+/// Returns `true` if `error` was created by [`deadline_exceeded`].
+pub fn is_deadline_exceeded(error: &Error) -> bool {
+    error_kind(error) == Some(EchoErrorKind::DeadlineExceeded)
+}
+
+/// Creates the error returned when the service can't be reached.
+///
+/// Unlike other failures this one is worth retrying on a new connection.
+pub fn unavailable(detail: impl fmt::Display) -> Error {
+    Error::Protocol(EchoErrorKind::Unavailable.message(detail))
+}
+
+/// Returns `true` if `error` was created by [`unavailable`].
+pub fn is_unavailable(error: &Error) -> bool {
+    error_kind(error) == Some(EchoErrorKind::Unavailable)
+}
+
+/// Detail prefix of [`in_maintenance`] errors.
+const MAINTENANCE_DETAIL: &str = "maintenance: ";
+
+/// Creates the error returned while the server is in maintenance mode.
+///
+/// It is an [`unavailable`] error (`UNAVAILABLE: maintenance: <reason>`),
+/// so clients move on to another instance; [`maintenance_reason`] reads
+/// the operator's reason back.
+pub fn in_maintenance(reason: impl fmt::Display) -> Error {
+    unavailable(format!("{}{}", MAINTENANCE_DETAIL, reason))
+}
+
+/// Returns the reason of an [`in_maintenance`] error.
+pub fn maintenance_reason(error: &Error) -> Option<&str> {
+    match error {
+        Error::Protocol(message) => message.strip_prefix(UNAVAILABLE)?.strip_prefix(": ")?.strip_prefix(MAINTENANCE_DETAIL),
+        _ => None,
+    }
+}
+
+/// Creates the error returned when the server sheds load.
+///
+/// `retry_after` tells well-behaved clients how long to back off. It is
+/// encoded in the message (`OVERLOADED: retry_after_ms=250: ...`), so it
+/// survives any protocol that carries the error text; read it back with
+/// [`retry_after`].
+pub fn overloaded(retry_after: Duration, detail: impl fmt::Display) -> Error {
+    Error::Protocol(EchoErrorKind::Overloaded { retry_after: Some(retry_after) }.message(detail))
+}
+
+/// Creates the error returned when a fixed limit is reached.
+///
+/// It is an [`overloaded`] error without a back-off hint: retrying won't
+/// help until something is freed (e.g. a scheduled echo is cancelled).
+pub fn limit_reached(detail: impl fmt::Display) -> Error {
+    Error::Protocol(EchoErrorKind::Overloaded { retry_after: None }.message(detail))
+}
+
+/// Returns `true` if `error` was created by [`overloaded`] or [`limit_reached`].
+pub fn is_overloaded(error: &Error) -> bool {
+    matches!(error_kind(error), Some(EchoErrorKind::Overloaded { .. }))
+}
+
+/// Returns the back-off hint of an [`overloaded`] error.
+pub fn retry_after(error: &Error) -> Option<Duration> {
+    match error_kind(error)? {
+        EchoErrorKind::Overloaded { retry_after } => retry_after,
+        _ => None,
+    }
+}
+
+/// Creates the error returned when a client-side validator rejects a reply.
+///
+/// The server did answer, so retrying the same call rarely helps.
+pub fn invalid_response(detail: impl fmt::Display) -> Error {
+    Error::Protocol(EchoErrorKind::InvalidResponse.message(detail))
+}
+
+/// Returns `true` if `error` was created by [`invalid_response`].
+pub fn is_invalid_response(error: &Error) -> bool {
+    error_kind(error) == Some(EchoErrorKind::InvalidResponse)
+}
+
+/// Creates the error returned when a message fails its integrity check.
+///
+/// Something between client and server corrupted the message; the call
+/// may be retried.
+pub fn integrity_error(detail: impl fmt::Display) -> Error {
+    Error::Protocol(EchoErrorKind::IntegrityError.message(detail))
+}
+
+/// Returns `true` if `error` was created by [`integrity_error`].
+pub fn is_integrity_error(error: &Error) -> bool {
+    error_kind(error) == Some(EchoErrorKind::IntegrityError)
+}
+
+/// Creates the error for a failure of the server's own storage (a
+/// database, file or blocking store call), as opposed to a bad request.
+pub fn storage_error(detail: impl fmt::Display) -> Error {
+    Error::Protocol(EchoErrorKind::Storage.message(detail))
+}
+
+/// Returns `true` if `error` was created by [`storage_error`].
+pub fn is_storage_error(error: &Error) -> bool {
+    error_kind(error) == Some(EchoErrorKind::Storage)
+}
+
+/// Creates the validation error for request field `field`.
+///
+/// An `Error::Validation` naming the field
+/// (`INVALID_ARGUMENT: field=idempotency_key: must not be empty`); the
+/// gRPC adapter sends it as a `BadRequest` field violation and the
+/// gateway turns that back into the same error.
+pub fn invalid_field(field: &str, description: impl fmt::Display) -> Error {
+    Error::Validation { message: FieldViolation::new(field, description.to_string()).message() }
+}
+
+/// Returns the field violation of an [`invalid_field`] error.
+pub fn field_violation(error: &Error) -> Option<FieldViolation> {
+    match error {
+        Error::Validation { message } => FieldViolation::parse(message),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the gateways couldn't use the requested protocol.
+///
+/// Another protocol may work; [`ProtocolUnsupported::from_error`](crate::ProtocolUnsupported::from_error)
+/// says which.
+pub fn is_protocol_unsupported(error: &Error) -> bool {
+    error_kind(error) == Some(EchoErrorKind::ProtocolUnsupported)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overloaded_carries_retry_after() {
+        let error = overloaded(Duration::from_millis(250), "queue full");
+
+        assert!(is_overloaded(&error));
+        assert_eq!(retry_after(&error), Some(Duration::from_millis(250)));
+        assert!(error.to_string().contains("queue full"));
+
+        let other = unavailable("connection refused");
+        assert!(!is_overloaded(&other));
+        assert_eq!(retry_after(&other), None);
+    }
+
+    #[test]
+    fn test_maintenance_is_unavailable_with_reason() {
+        let error = in_maintenance("upgrading disks");
+        assert!(is_unavailable(&error));
+        assert_eq!(maintenance_reason(&error), Some("upgrading disks"));
+        assert_eq!(maintenance_reason(&unavailable("connection refused")), None);
+    }
+
+    #[test]
+    fn test_invalid_field_is_a_validation_error() {
+        let error = invalid_field("idempotency_key", "must not be empty");
+        assert!(matches!(error, Error::Validation { .. }));
+        assert_eq!(field_violation(&error), Some(FieldViolation::new("idempotency_key", "must not be empty")));
+        assert_eq!(field_violation(&Error::Validation { message: "bad".to_string() }), None);
+    }
+}
@@ -0,0 +1,83 @@
+//! Span field conventions shared by every layer.
+//!
+//! # Architecture
+//!
+//! Gateways, handlers, services and modules each open a `tracing` span
+//! (`#[instrument]`) per operation. The spans name the same things the
+//! same way, so a log line deep in a call can be attributed without a
+//! `[Component]` prefix in its message:
+//!
+//! ```text
+//! bin  name=echo-grpc-cli
+//!   └─ module  module_id=echo-client                    (start / stop)
+//!        └─ client_call  module_id=echo-client request_id=7d1c
+//!             └─ grpc_gateway  method=echo protocol=grpc request_id=7d1c
+//! ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ x-echo-trace: TRACE_ID/7d1c ─ ─ ─ ─ ─ ─ ─ ─ ─ ─
+//! grpc_handler  method=echo protocol=grpc request_id=7d1c
+//!   └─ echo_service  module_id=echo method=echo
+//! ```
+//!
+//! The fields, by name:
+//!
+//! | Field        | Value                                        | Recorded by                    |
+//! |--------------|----------------------------------------------|--------------------------------|
+//! | `module_id`  | module ID (`echo`, `echo-client`)            | modules, gateways, services    |
+//! | `service_id` | [`EchoServiceId`](crate::EchoServiceId) (`service`, `events`) | echo gateways |
+//! | `protocol`   | [`protocol_field`] (`direct`, `grpc`)        | gateways, handlers             |
+//! | `request_id` | [`current_request_id`] (`-` untraced)        | client calls, gateways, handlers |
+//!
+//! Spans nested in those (services, tasks) inherit `request_id` from
+//! their ancestors rather than repeating it. A span crossing a process boundary doesn't
+//! nest: the server's spans are roots, joined to the client's by
+//! `request_id` (carried in the `x-echo-trace` header, see
+//! [`RequestContext`]).
+//!
+//! Spans declare `request_id = tracing::field::Empty` and record it once
+//! the call runs: a call's future - and with it the span - is created
+//! before [`RequestContext::scope`] enters the call's context.
+//!
+//! # Rust Learning Note
+//!
+//! `#[instrument(fields(protocol = "grpc"))]` needs the field name as an
+//! identifier, so the names below can't be spliced in as constants; they
+//! are what tests and log processors look the fields up by.
+
+use hsu_common::Protocol;
+use crate::context::RequestContext;
+
+/// Field naming the module a span works for.
+pub const MODULE_ID_FIELD: &str = "module_id";
+
+/// Field naming the echo service a gateway span is for.
+pub const SERVICE_ID_FIELD: &str = "service_id";
+
+/// Field naming the protocol of a gateway or handler span.
+pub const PROTOCOL_FIELD: &str = "protocol";
+
+/// Field carrying the request ID of the call a span belongs to.
+pub const REQUEST_ID_FIELD: &str = "request_id";
+
+/// Value of [`REQUEST_ID_FIELD`] for the running call: its request ID, or
+/// `-` outside a traced call.
+pub fn current_request_id() -> String {
+    RequestContext::current().request_id.unwrap_or_else(|| "-".to_string())
+}
+
+/// Value of [`PROTOCOL_FIELD`]: `direct`, `grpc`, `http` or `auto`.
+pub fn protocol_field(protocol: Protocol) -> String {
+    format!("{:?}", protocol).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Priority;
+
+    #[tokio::test]
+    async fn test_request_id_of_running_call() {
+        assert_eq!(current_request_id(), "-");
+        let context = RequestContext::new(Priority::Normal).with_trace("4bf92f35", "7d1c");
+        assert_eq!(context.scope(async { current_request_id() }).await, "7d1c");
+        assert_eq!(protocol_field(Protocol::Grpc), "grpc");
+    }
+}
@@ -0,0 +1,151 @@
+//! Text transformations of echo replies.
+//!
+//! # Architecture
+//!
+//! A caller picks transformations per request. Like the priority, they
+//! travel in the [`RequestContext`](crate::RequestContext) beside the
+//! message, and the server applies them in order to the text replies
+//! (`echo`, `echo_reliable`, `echo_with_session`):
+//!
+//! ```text
+//! RequestContext::default().with_transforms(parse_transforms("nfkc,lower:tr,truncate:20")?)
+//!     ↓ Direct: same task
+//!     ↓ gRPC:   `x-echo-transform: nfkc,lower:tr,truncate:20`
+//! EchoServiceImpl::echo ── nfkc → lower:tr → truncate:20 ──→ reply
+//! ```
+//!
+//! | Text                   | Transformation                                             |
+//! |------------------------|------------------------------------------------------------|
+//! | `upper`, `lower`       | Unicode case mapping (`ß` → `SS`, word-final `Σ` → `ς`)    |
+//! | `upper:tr`, `lower:tr` | Turkish/Azeri casing of dotted and dotless i (`i` ↔ `İ`)   |
+//! | `nfc`, `nfkc`          | Unicode normalization (`nfkc`: `ﬁ` → `fi`, `①` → `1`)      |
+//! | `truncate:N`           | First `N` user-perceived characters (grapheme clusters)    |
+//!
+//! The contract only names the transformations; the server implements
+//! them, so the Unicode tables stay out of every client.
+
+use std::fmt;
+use std::str::FromStr;
+use hsu_common::{Error, Result};
+
+/// Most transformations a request may chain.
+pub const MAX_TRANSFORMS: usize = 8;
+
+/// Language-specific case mapping rules.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CaseLocale {
+    /// Unicode's default mappings.
+    #[default]
+    Root,
+    /// Turkish and Azeri: `i` ↔ `İ` and `ı` ↔ `I` (`tr`, `az`).
+    Turkic,
+}
+
+/// One transformation of a reply (see the module docs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EchoTransform {
+    /// Uppercase.
+    Upper(CaseLocale),
+    /// Lowercase.
+    Lower(CaseLocale),
+    /// Canonical composition (NFC).
+    Nfc,
+    /// Compatibility composition (NFKC).
+    Nfkc,
+    /// Keep the first `N` grapheme clusters, never splitting one.
+    Truncate(usize),
+}
+
+impl FromStr for EchoTransform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_ascii_lowercase();
+        let (kind, argument) = match name.split_once(':') {
+            Some((kind, argument)) => (kind, Some(argument)),
+            None => (name.as_str(), None),
+        };
+        let locale = |argument: Option<&str>| match argument {
+            None => Ok(CaseLocale::Root),
+            Some("tr") | Some("az") => Ok(CaseLocale::Turkic),
+            Some(other) => Err(invalid(s, &format!("unsupported locale '{}' (expected tr or az)", other))),
+        };
+        match (kind, argument) {
+            ("upper", argument) => Ok(EchoTransform::Upper(locale(argument)?)),
+            ("lower", argument) => Ok(EchoTransform::Lower(locale(argument)?)),
+            ("nfc", None) => Ok(EchoTransform::Nfc),
+            ("nfkc", None) => Ok(EchoTransform::Nfkc),
+            ("truncate", Some(length)) => length
+                .parse()
+                .map(EchoTransform::Truncate)
+                .map_err(|_| invalid(s, "expected truncate:<characters>")),
+            _ => Err(invalid(s, "expected upper, lower, nfc, nfkc or truncate:<characters>")),
+        }
+    }
+}
+
+impl fmt::Display for EchoTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EchoTransform::Upper(CaseLocale::Root) => f.write_str("upper"),
+            EchoTransform::Upper(CaseLocale::Turkic) => f.write_str("upper:tr"),
+            EchoTransform::Lower(CaseLocale::Root) => f.write_str("lower"),
+            EchoTransform::Lower(CaseLocale::Turkic) => f.write_str("lower:tr"),
+            EchoTransform::Nfc => f.write_str("nfc"),
+            EchoTransform::Nfkc => f.write_str("nfkc"),
+            EchoTransform::Truncate(length) => write!(f, "truncate:{}", length),
+        }
+    }
+}
+
+/// Parses a comma-separated chain (`nfkc,lower,truncate:20`); empty text
+/// is the empty chain.
+pub fn parse_transforms(chain: &str) -> Result<Vec<EchoTransform>> {
+    if chain.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let transforms = chain.split(',').map(str::parse).collect::<Result<Vec<EchoTransform>>>()?;
+    if transforms.len() > MAX_TRANSFORMS {
+        return Err(invalid(chain, &format!("at most {} transformations", MAX_TRANSFORMS)));
+    }
+    Ok(transforms)
+}
+
+/// Formats a chain the way [`parse_transforms`] reads it.
+pub fn format_transforms(transforms: &[EchoTransform]) -> String {
+    transforms.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
+
+fn invalid(transform: &str, reason: &str) -> Error {
+    Error::Validation {
+        message: format!("Invalid transform '{}': {}", transform.trim(), reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_round_trip() {
+        let chain = parse_transforms(" NFKC, lower:az ,truncate:20,upper,nfc ").unwrap();
+        assert_eq!(chain, vec![
+            EchoTransform::Nfkc,
+            EchoTransform::Lower(CaseLocale::Turkic),
+            EchoTransform::Truncate(20),
+            EchoTransform::Upper(CaseLocale::Root),
+            EchoTransform::Nfc,
+        ]);
+        assert_eq!(format_transforms(&chain), "nfkc,lower:tr,truncate:20,upper,nfc");
+        assert_eq!(parse_transforms(&format_transforms(&chain)).unwrap(), chain);
+        assert!(parse_transforms("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_transforms() {
+        for chain in ["shout", "upper:de", "nfc:tr", "truncate", "truncate:-1", "lower,,nfc"] {
+            assert!(parse_transforms(chain).is_err(), "{}", chain);
+        }
+        assert!(parse_transforms(&["nfc"; MAX_TRANSFORMS + 1].join(",")).is_err());
+    }
+}
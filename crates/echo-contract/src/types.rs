@@ -0,0 +1,226 @@
+//! Domain types shared by every consumer (`alloc` only).
+//!
+//! Requests and responses of [`EchoService`](crate::EchoService) that
+//! need neither an async runtime nor `std`, so embedded or WASM code can
+//! build and read them without the service traits:
+//!
+//! ```toml
+//! echo-contract = { path = "...", default-features = false, features = ["alloc"] }
+//! ```
+//!
+//! `SessionEcho` and `EchoEvent` carry a `SystemTime` and stay with the
+//! `std` contract.
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::fmt;
+
+/// Digest returned by [`EchoService::echo_file`](crate::EchoService::echo_file).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDigest {
+    /// Total number of bytes received.
+    pub byte_count: u64,
+    /// Number of chunks received.
+    pub chunk_count: u64,
+    /// Lowercase hex SHA-256 of the received bytes.
+    pub sha256: String,
+}
+
+/// Acknowledgement returned by [`EchoService::echo_reliable`](crate::EchoService::echo_reliable).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EchoAck {
+    /// The echoed message.
+    pub message: Arc<str>,
+    /// The idempotency key this acknowledgement belongs to.
+    pub idempotency_key: String,
+    /// `true` if the key was already processed and the stored response was returned.
+    pub duplicate: bool,
+}
+
+/// The `EchoService` method an event reports on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EchoMethod {
+    Echo,
+    EchoBytes,
+    EchoReliable,
+    EchoFile,
+    EchoWithSession,
+}
+
+impl EchoMethod {
+    /// All methods, in declaration order.
+    pub const ALL: [EchoMethod; 5] = [
+        EchoMethod::Echo,
+        EchoMethod::EchoBytes,
+        EchoMethod::EchoReliable,
+        EchoMethod::EchoFile,
+        EchoMethod::EchoWithSession,
+    ];
+
+    /// Returns the method name (`echo`, `echo_bytes`, ...).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EchoMethod::Echo => "echo",
+            EchoMethod::EchoBytes => "echo_bytes",
+            EchoMethod::EchoReliable => "echo_reliable",
+            EchoMethod::EchoFile => "echo_file",
+            EchoMethod::EchoWithSession => "echo_with_session",
+        }
+    }
+
+    /// Looks a method up by [name](Self::as_str).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|method| method.as_str() == name)
+    }
+}
+
+impl fmt::Display for EchoMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// ID of the echo module - the server's module and the registry name
+/// clients discover (not `echo-server`: the ID names the API, not the binary).
+pub const ECHO_MODULE_ID: &str = "echo";
+
+/// ID of the echo monitor module.
+pub const ECHO_MONITOR_MODULE_ID: &str = "echo-monitor";
+
+/// ID of the echo client module.
+pub const ECHO_CLIENT_MODULE_ID: &str = "echo-client";
+
+/// A service the echo module publishes.
+///
+/// Typed so that a gateway asking for `"service"` and a registrar
+/// publishing `"service1"` can't drift apart unnoticed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EchoServiceId {
+    /// [`EchoService`](crate::EchoService), the echo methods.
+    Service,
+    /// [`EchoEvents`](crate::EchoEvents), the event subscription.
+    Events,
+}
+
+impl EchoServiceId {
+    /// All services, in the order they are published.
+    pub const ALL: [EchoServiceId; 2] = [EchoServiceId::Service, EchoServiceId::Events];
+
+    /// Returns the service ID (`service`, `events`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EchoServiceId::Service => "service",
+            EchoServiceId::Events => "events",
+        }
+    }
+
+    /// Looks a service up by [ID](Self::as_str).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|service| service.as_str() == name)
+    }
+}
+
+impl fmt::Display for EchoServiceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Metadata key carrying the priority on protocols with headers.
+pub const PRIORITY_METADATA_KEY: &str = "x-echo-priority";
+
+/// Metadata key carrying the reply transformations (`nfkc,lower,truncate:20`)
+/// on protocols with headers.
+pub const TRANSFORM_METADATA_KEY: &str = "x-echo-transform";
+
+/// Metadata key carrying the caller (tenant) name on protocols with headers.
+pub const CALLER_METADATA_KEY: &str = "x-echo-caller";
+
+/// Metadata key carrying the hex SHA-256 of a request's message, and of
+/// the reply on its response, when end-to-end integrity checks are on.
+pub const CHECKSUM_METADATA_KEY: &str = "x-echo-checksum";
+
+/// Metadata key carrying when a signed request was signed (Unix millis).
+pub const TIMESTAMP_METADATA_KEY: &str = "x-echo-timestamp";
+
+/// Metadata key carrying a signed request's one-time nonce.
+pub const NONCE_METADATA_KEY: &str = "x-echo-nonce";
+
+/// Metadata key carrying the hex HMAC-SHA256 of a signed request.
+pub const SIGNATURE_METADATA_KEY: &str = "x-echo-signature";
+
+/// Metadata key carrying the caller's preferred languages for error
+/// messages (HTTP `Accept-Language` syntax, see [`crate::messages`]).
+pub const ACCEPT_LANGUAGE_METADATA_KEY: &str = "accept-language";
+
+/// Metadata key marking a probe (the server's start-up self-test) on
+/// protocols with headers; its value is ignored.
+pub const PROBE_METADATA_KEY: &str = "x-echo-probe";
+
+/// Metadata key carrying the caller's trace and request IDs
+/// (`TRACE_ID/REQUEST_ID`), so client and server logs can be correlated.
+pub const TRACE_METADATA_KEY: &str = "x-echo-trace";
+
+/// Scheduling class of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Priority {
+    /// Interactive callers, served first.
+    High,
+    /// Default class.
+    #[default]
+    Normal,
+    /// Bulk work (e.g. load generation), served last.
+    Low,
+}
+
+impl Priority {
+    /// All classes, highest first.
+    pub const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    /// Returns the lowercase name (`high`, `normal`, `low`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::High => "high",
+            Priority::Normal => "normal",
+            Priority::Low => "low",
+        }
+    }
+
+    /// Returns the position in [`Priority::ALL`].
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Looks a class up by name, ignoring case and surrounding whitespace.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL.into_iter().find(|priority| priority.as_str().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name_round_trip() {
+        for method in EchoMethod::ALL {
+            assert_eq!(EchoMethod::from_name(method.as_str()), Some(method));
+        }
+        assert_eq!(EchoMethod::from_name("bogus"), None);
+
+        for service in EchoServiceId::ALL {
+            assert_eq!(EchoServiceId::from_name(service.as_str()), Some(service));
+        }
+        assert_eq!(EchoServiceId::from_name("service1"), None);
+
+        assert_eq!(Priority::from_name(" HIGH "), Some(Priority::High));
+        assert_eq!(Priority::from_name("urgent"), None);
+    }
+}
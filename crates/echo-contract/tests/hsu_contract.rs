@@ -0,0 +1,85 @@
+//! `#[hsu_contract]` on a contract outside this crate.
+//!
+//! ```text
+//! cargo test -p echo-contract --test hsu_contract
+//! ```
+//!
+//! Compiling this file is most of the test: the generated handlers,
+//! gateways and direct closure enabler must build with nothing but the
+//! service trait's own imports in scope.
+
+use std::sync::{Arc, RwLock};
+use async_trait::async_trait;
+use echo_contract::hsu_contract;
+use hsu_common::{Error, ModuleID, Protocol, Result, ServiceID};
+use hsu_module_api::DirectClosureEnablerOptions;
+
+/// A contract with one method and default names.
+#[hsu_contract]
+#[async_trait]
+pub trait GreeterService: Send + Sync {
+    async fn greet(&self, name: String) -> Result<String>;
+}
+
+struct Greeter;
+
+#[async_trait]
+impl GreeterService for Greeter {
+    async fn greet(&self, name: String) -> Result<String> {
+        Ok(format!("Hello, {}!", name))
+    }
+}
+
+/// Direct-only gateways, like the monitor's.
+#[derive(Default)]
+struct Gateways {
+    handlers: RwLock<Option<GreeterServiceHandlers>>,
+}
+
+#[async_trait]
+impl GreeterServiceGateways for Gateways {
+    fn module_id(&self) -> ModuleID {
+        ModuleID::from("greeter")
+    }
+
+    fn service_ids(&self) -> Vec<ServiceID> {
+        vec![ServiceID::from("greeter")]
+    }
+
+    fn enable_direct_closure(&self, handlers: GreeterServiceHandlers) {
+        *self.handlers.write().unwrap() = Some(handlers);
+    }
+
+    async fn get_service(&self, protocol: Protocol) -> Result<Arc<dyn GreeterService>> {
+        match (protocol, self.handlers.read().unwrap().as_ref()) {
+            (Protocol::Direct | Protocol::Auto, Some(handlers)) => Ok(handlers.service.clone()),
+            _ => Err(Error::Protocol(format!("{:?} is not available", protocol))),
+        }
+    }
+}
+
+/// Options renaming the generated items.
+#[hsu_contract(handlers = Farewells, gateways = FarewellGateways, direct_closure = farewell_enabler)]
+#[async_trait]
+pub trait FarewellService: Send + Sync {
+    async fn bye(&self) -> Result<()>;
+}
+
+#[tokio::test]
+async fn test_generated_items_work_together() {
+    let gateways = Gateways::default();
+    assert!(gateways.get_service(Protocol::Direct).await.is_err());
+
+    gateways.enable_direct_closure(GreeterServiceHandlers::new(Arc::new(Greeter)));
+    let service = gateways.get_service(Protocol::Auto).await.unwrap();
+    assert_eq!(service.greet("Rust".to_string()).await.unwrap(), "Hello, Rust!");
+    // `close` has a default
+    gateways.close().await.unwrap();
+}
+
+#[test]
+fn test_enablers_fit_the_module_descriptor() {
+    let _: fn(DirectClosureEnablerOptions<Arc<dyn GreeterServiceGateways>, GreeterServiceHandlers>) =
+        greeter_service_direct_closure_enabler;
+    let _: fn(DirectClosureEnablerOptions<Arc<dyn FarewellGateways>, Farewells>) = farewell_enabler;
+}
@@ -0,0 +1,137 @@
+//! Event aggregation (the monitor's business logic).
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use async_trait::async_trait;
+use echo_api::LatencyBudgetMetrics;
+use echo_contract::EchoEvent;
+use hsu_common::Result;
+
+use crate::contract::{MonitorService, MonitorStats, SloObjectives, SloReport};
+use crate::slo::{SloCalculator, SloCounts};
+
+/// Counts echo events; serves the counts as a [`MonitorService`].
+pub struct EventAggregator {
+    stats: Mutex<MonitorStats>,
+    slo: SloCalculator,
+    /// Latency-budget counters of the clients in this process.
+    budgets: Arc<LatencyBudgetMetrics>,
+}
+
+impl Default for EventAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventAggregator {
+    /// Creates an aggregator with zero counts and the default objectives.
+    pub fn new() -> Self {
+        Self::with_objectives(SloObjectives::default())
+    }
+
+    /// Creates an aggregator reporting SLO attainment against `objectives`.
+    pub fn with_objectives(objectives: SloObjectives) -> Self {
+        Self::with_metrics(objectives, LatencyBudgetMetrics::global())
+    }
+
+    /// Like [`with_objectives`](Self::with_objectives), reading latency
+    /// budget counters from `budgets`.
+    pub fn with_metrics(objectives: SloObjectives, budgets: Arc<LatencyBudgetMetrics>) -> Self {
+        Self {
+            stats: Mutex::new(MonitorStats::default()),
+            slo: SloCalculator::new(objectives, Instant::now()),
+            budgets,
+        }
+    }
+
+    /// Adds `event` to the counts.
+    pub fn record(&self, event: &EchoEvent) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.total += 1;
+        if !event.success {
+            stats.failures += 1;
+        }
+        stats.request_bytes += event.request_bytes;
+        *stats.by_method.entry(event.method.as_str()).or_insert(0) += 1;
+    }
+
+    /// Returns a copy of the current counts.
+    pub fn snapshot(&self) -> MonitorStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Returns the objectives SLO attainment is reported against.
+    pub fn objectives(&self) -> &SloObjectives {
+        self.slo.objectives()
+    }
+
+    /// Samples the counts for the SLO windows (every
+    /// [`SloObjectives::sample_every`]).
+    pub fn sample_slo(&self) {
+        self.slo.sample(Instant::now(), self.counts());
+    }
+
+    /// Returns SLO attainment over the windows ending now.
+    pub fn slo_report(&self) -> SloReport {
+        self.slo.report(Instant::now(), self.counts())
+    }
+
+    fn counts(&self) -> SloCounts {
+        SloCounts::read(&self.snapshot(), &self.budgets)
+    }
+}
+
+#[async_trait]
+impl MonitorService for EventAggregator {
+    async fn stats(&self) -> Result<MonitorStats> {
+        Ok(self.snapshot())
+    }
+
+    async fn slo(&self) -> Result<SloReport> {
+        Ok(self.slo_report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+    use echo_contract::EchoMethod;
+
+    fn event(method: EchoMethod, request_bytes: u64, success: bool) -> EchoEvent {
+        EchoEvent { method, request_bytes, success, at: SystemTime::now() }
+    }
+
+    #[tokio::test]
+    async fn test_aggregates_events() {
+        let aggregator = EventAggregator::new();
+        aggregator.record(&event(EchoMethod::Echo, 5, true));
+        aggregator.record(&event(EchoMethod::Echo, 3, false));
+        aggregator.record(&event(EchoMethod::EchoBytes, 2, true));
+
+        let stats = aggregator.stats().await.unwrap();
+        assert_eq!(stats.total, 3);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.request_bytes, 10);
+        assert_eq!(stats.by_method.get("echo"), Some(&2));
+        assert_eq!(stats.by_method.get("echo_bytes"), Some(&1));
+    }
+
+    #[tokio::test]
+    async fn test_slo_from_events() {
+        let budgets = Arc::new(LatencyBudgetMetrics::new());
+        let aggregator = EventAggregator::with_metrics(SloObjectives::default(), budgets);
+        aggregator.record(&event(EchoMethod::Echo, 5, true));
+        aggregator.sample_slo();
+        aggregator.record(&event(EchoMethod::Echo, 3, false));
+
+        let report = aggregator.slo().await.unwrap();
+        assert_eq!(report.windows.len(), 3);
+        for window in &report.windows {
+            assert_eq!((window.calls, window.failures), (2, 1));
+            assert_eq!(window.availability(), Some(50.0));
+            assert_eq!(window.latency_attainment(), None);
+        }
+    }
+}
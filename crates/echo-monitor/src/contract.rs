@@ -0,0 +1,134 @@
+//! Monitor Service Contract (Layer 3)
+//!
+//! Same shape as the echo contract: a service trait, a handlers holder
+//! the module provides, a gateways trait consumers ask for a service, and
+//! the direct closure enabler (all three generated by `#[hsu_contract]`).
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+use async_trait::async_trait;
+use echo_contract::hsu_contract;
+use hsu_common::{Error, Result};
+
+/// Aggregated echo activity.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MonitorStats {
+    /// Events received.
+    pub total: u64,
+    /// Events reporting a failed call.
+    pub failures: u64,
+    /// Request bytes over all events.
+    pub request_bytes: u64,
+    /// Events per `EchoService` method name.
+    pub by_method: BTreeMap<&'static str, u64>,
+}
+
+/// Service level objectives the monitor reports attainment of.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloObjectives {
+    /// Share of echo calls that must succeed, in percent.
+    pub availability: f64,
+    /// Share of budgeted client calls that must stay within their latency
+    /// budget, in percent.
+    pub latency: f64,
+    /// Sliding windows attainment is computed over.
+    pub windows: Vec<Duration>,
+    /// How often the counts are sampled (the windows' resolution).
+    pub sample_every: Duration,
+}
+
+impl Default for SloObjectives {
+    fn default() -> Self {
+        Self {
+            availability: 99.9,
+            latency: 99.0,
+            windows: vec![Duration::from_secs(60), Duration::from_secs(300), Duration::from_secs(3600)],
+            sample_every: Duration::from_secs(10),
+        }
+    }
+}
+
+impl SloObjectives {
+    /// Checks the objectives are percentages and the windows and sampling
+    /// interval are positive.
+    pub fn validate(&self) -> Result<()> {
+        for (name, objective) in [("availability", self.availability), ("latency", self.latency)] {
+            if !(objective > 0.0 && objective <= 100.0) {
+                return Err(Error::Validation {
+                    message: format!("SLO {} objective {}% is not between 0% and 100%", name, objective),
+                });
+            }
+        }
+        if self.windows.is_empty() || self.windows.iter().any(Duration::is_zero) || self.sample_every.is_zero() {
+            return Err(Error::Validation {
+                message: "SLO windows and sampling interval must be positive".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Counts of one sliding window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SloWindow {
+    /// Window length.
+    pub window: Duration,
+    /// How much of it the counts cover (less until the monitor ran as long).
+    pub covered: Duration,
+    /// Echo calls in the window.
+    pub calls: u64,
+    /// Echo calls that failed.
+    pub failures: u64,
+    /// Client calls timed against a latency budget.
+    pub budgeted: u64,
+    /// Client calls slower than their budget.
+    pub over_budget: u64,
+}
+
+impl SloWindow {
+    /// Share of the calls that succeeded, in percent (`None` without calls).
+    pub fn availability(&self) -> Option<f64> {
+        share(self.calls - self.failures, self.calls)
+    }
+
+    /// Share of the budgeted calls within budget, in percent (`None`
+    /// without budgeted calls).
+    pub fn latency_attainment(&self) -> Option<f64> {
+        share(self.budgeted - self.over_budget, self.budgeted)
+    }
+}
+
+fn share(part: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| part as f64 * 100.0 / total as f64)
+}
+
+/// SLO attainment over each window of the objectives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloReport {
+    pub objectives: SloObjectives,
+    /// One entry per objective window, shortest first.
+    pub windows: Vec<SloWindow>,
+}
+
+/// Monitor service contract (protocol-agnostic).
+///
+/// [`MonitorServiceHandlers`], [`MonitorServiceGateways`] (for modules
+/// that consume the monitor) and [`monitor_direct_closure_enabler`] are
+/// generated from it.
+#[hsu_contract(direct_closure = monitor_direct_closure_enabler)]
+#[async_trait]
+pub trait MonitorService: Send + Sync {
+    /// Returns the counts aggregated so far.
+    async fn stats(&self) -> Result<MonitorStats>;
+
+    /// Returns SLO attainment over the sliding windows.
+    async fn slo(&self) -> Result<SloReport>;
+}
+
+/// ID of the monitor's one service.
+pub const MONITOR_SERVICE_ID: &str = "monitor";
+
+echo_contract::impl_service_gateway_ext!(
+    /// Protocol fallback for [`MonitorServiceGateways`] (only Direct works).
+    pub trait MonitorServiceGatewaysExt for MonitorServiceGateways => MonitorService
+);
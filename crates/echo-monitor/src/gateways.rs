@@ -0,0 +1,99 @@
+//! Monitor Service Gateways (Layer 3/5 Boundary)
+//!
+//! The monitor has no protocol adapters: it is reachable in-process only,
+//! through direct closure. Asking for any other protocol fails as
+//! `PROTOCOL_UNSUPPORTED` (see [`ProtocolUnsupported`]).
+
+use std::sync::{Arc, RwLock};
+use async_trait::async_trait;
+use hsu_common::{Error, ModuleID, Protocol, Result, ServiceID};
+use hsu_module_api::ServiceConnector;
+use echo_contract::{ProtocolUnsupported, ECHO_MONITOR_MODULE_ID};
+use tracing::debug;
+
+use crate::contract::{MonitorService, MonitorServiceGateways, MonitorServiceHandlers, MONITOR_SERVICE_ID};
+
+/// The protocols the monitor gateways carry calls over.
+pub const MONITOR_PROTOCOLS: [Protocol; 1] = [Protocol::Direct];
+
+/// Direct-only implementation of [`MonitorServiceGateways`].
+pub struct MonitorServiceGatewaysImpl {
+    module_id: ModuleID,
+    service_handlers: RwLock<Option<MonitorServiceHandlers>>,
+}
+
+impl MonitorServiceGatewaysImpl {
+    /// Creates gateways to the monitor module `module_id`.
+    pub fn new(module_id: ModuleID) -> Self {
+        Self {
+            module_id,
+            service_handlers: RwLock::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl MonitorServiceGateways for MonitorServiceGatewaysImpl {
+    fn module_id(&self) -> ModuleID {
+        self.module_id.clone()
+    }
+
+    fn service_ids(&self) -> Vec<ServiceID> {
+        vec![ServiceID::from(MONITOR_SERVICE_ID)]
+    }
+
+    fn enable_direct_closure(&self, handlers: MonitorServiceHandlers) {
+        debug!("[MonitorServiceGateways] Enabling direct closure for module {}", self.module_id);
+        *self.service_handlers.write().unwrap() = Some(handlers);
+    }
+
+    async fn get_service(&self, protocol: Protocol) -> Result<Arc<dyn MonitorService>> {
+        match protocol {
+            Protocol::Direct | Protocol::Auto => self.service_handlers
+                .read()
+                .unwrap()
+                .as_ref()
+                .map(|handlers| handlers.service.clone())
+                .ok_or_else(|| Error::Protocol(format!(
+                    "Module {} is not running in this process", self.module_id
+                ))),
+            other => Err(ProtocolUnsupported {
+                service: ServiceID::from(MONITOR_SERVICE_ID),
+                protocol: other,
+                supported: MONITOR_PROTOCOLS.to_vec(),
+                reason: format!("module '{}' is only reachable in-process", self.module_id),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Creates gateways to the "echo-monitor" module.
+///
+/// Like the echo gateways, the target module ID is identity, not config.
+pub fn new_monitor_service_gateways(
+    _service_connector: Arc<dyn ServiceConnector>,
+) -> Arc<dyn MonitorServiceGateways> {
+    Arc::new(MonitorServiceGatewaysImpl::new(ModuleID::from(ECHO_MONITOR_MODULE_ID)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::EventAggregator;
+    use crate::contract::MonitorServiceGatewaysExt;
+
+    #[tokio::test]
+    async fn test_direct_only() {
+        let gateways = MonitorServiceGatewaysImpl::new(ModuleID::from("echo-monitor"));
+        assert!(gateways.get_service(Protocol::Auto).await.is_err());
+
+        gateways.enable_direct_closure(MonitorServiceHandlers::new(Arc::new(EventAggregator::new())));
+        let service = gateways.get_service(Protocol::Auto).await.unwrap();
+        assert_eq!(service.stats().await.unwrap().total, 0);
+        let error = gateways.get_service(Protocol::Grpc).await.err().unwrap();
+        let unsupported = ProtocolUnsupported::from_error(&error).unwrap();
+        assert_eq!((unsupported.protocol, unsupported.supported), (Protocol::Grpc, vec![Protocol::Direct]));
+        assert!(gateways.get_any_service().await.is_ok());
+    }
+}
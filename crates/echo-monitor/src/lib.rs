@@ -0,0 +1,53 @@
+//! Echo Monitor Module (Layers 3 + 5)
+//!
+//! # Architecture
+//!
+//! A **third** module that sits between the other two: it consumes the
+//! echo server's events and offers the aggregated counts through its own
+//! contract.
+//!
+//! ```text
+//! echo-client ──EchoService──→ echo (server)
+//!                                 │ EchoEvents
+//!                                 ↓
+//!                           echo-monitor ──MonitorService──→ any module
+//! ```
+//!
+//! ## Layer Separation
+//!
+//! - **Layer 3 (Contract)**: `contract.rs` - `MonitorService` and its handlers/gateways traits
+//! - **Layer 3 (Module/Domain)**: `module.rs` + `aggregator.rs` - Event aggregation
+//! - **Layer 3 (Module/Domain)**: `slo.rs` - SLO attainment derived from the counts
+//! - **Layer 3/5 (API)**: `gateways.rs` - Direct-only gateways + direct closure enabler
+//! - **Layer 5 (Service Provider)**: `service_provider.rs` - Echo gateways (transitive use)
+//! - **Layer 5 (Module Wiring)**: `wiring.rs` - Module self-registration
+//!
+//! ## Why Both Handlers and Gateways?
+//!
+//! - echo-server: Provides handlers, no gateways
+//! - echo-client: Provides gateways, no handlers
+//! - echo-monitor: **Uses** echo gateways, **provides** monitor handlers
+//!
+//! ## Golang Equivalent
+//!
+//! - Domain: `pkg/echomonitor/echomonitordomain/module.go`
+//! - Wiring: `pkg/echomonitor/echomonitorwiring/wiring.go`
+
+pub mod aggregator;
+pub mod contract;
+pub mod gateways;
+pub mod module;
+pub mod service_provider;
+pub mod slo;
+pub mod wiring;
+
+pub use aggregator::EventAggregator;
+pub use contract::{
+    monitor_direct_closure_enabler, MonitorService, MonitorServiceGateways, MonitorServiceGatewaysExt,
+    MonitorServiceHandlers, MonitorStats, SloObjectives, SloReport, SloWindow, MONITOR_SERVICE_ID,
+};
+pub use gateways::{new_monitor_service_gateways, MonitorServiceGatewaysImpl};
+pub use module::EchoMonitorModule;
+pub use service_provider::EchoMonitorServiceProvider;
+pub use slo::{SloCalculator, SloCounts};
+pub use wiring::{init_echo_monitor_module, EchoMonitorModuleConfig};
@@ -0,0 +1,136 @@
+//! Echo Monitor Module (Layer 3)
+//!
+//! # Architecture
+//!
+//! On start, subscribes to the echo server's events (Direct when the
+//! server runs in this process, gRPC otherwise) and feeds them to the
+//! aggregator in a tracked background task. A second task samples the
+//! counts for the SLO windows (see [`crate::slo`]); the attainment is
+//! logged on stop.
+
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use echo_api::{spawn_tracked, until_cancelled, CancellationToken};
+use echo_contract::ECHO_MONITOR_MODULE_ID;
+use futures::StreamExt;
+use hsu_common::{ModuleID, Protocol, Result};
+use hsu_module_api::Module;
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
+
+use crate::aggregator::EventAggregator;
+use crate::service_provider::EchoMonitorServiceProvider;
+
+/// Echo monitor module implementation.
+pub struct EchoMonitorModule {
+    id: ModuleID,
+    service_provider: EchoMonitorServiceProvider,
+    aggregator: Arc<EventAggregator>,
+    report_interval: Option<Duration>,
+    tasks: Vec<JoinHandle<()>>,
+    /// Cancelled on Ctrl-C: cuts a waiting `start()` short.
+    cancellation: CancellationToken,
+}
+
+impl EchoMonitorModule {
+    /// Creates a new monitor module feeding `aggregator`.
+    ///
+    /// Note: This is called by the wiring layer (Layer 5).
+    pub fn new(service_provider: EchoMonitorServiceProvider, aggregator: Arc<EventAggregator>) -> Self {
+        Self {
+            id: ModuleID::from(ECHO_MONITOR_MODULE_ID),
+            service_provider,
+            aggregator,
+            report_interval: None,
+            tasks: Vec::new(),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Logs the counts every `interval`.
+    pub fn with_report_interval(mut self, interval: Duration) -> Self {
+        self.report_interval = Some(interval);
+        self
+    }
+
+    /// Aborts `start()` when `token` is cancelled, instead of waiting to
+    /// subscribe until the connection times out.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    fn log_stats(aggregator: &EventAggregator) {
+        let stats = aggregator.snapshot();
+        info!("[EchoMonitor] {} echo call(s), {} failed, {} request bytes, by method: {:?}",
+            stats.total, stats.failures, stats.request_bytes, stats.by_method);
+    }
+}
+
+#[async_trait]
+impl Module for EchoMonitorModule {
+    fn id(&self) -> &ModuleID {
+        &self.id
+    }
+
+    #[instrument(name = "module", skip_all, fields(module_id = %self.id))]
+    async fn start(&mut self) -> Result<()> {
+        info!("Starting...");
+
+        // Transitive gateway usage: the monitor reaches echo through its gateways
+        let gateways = self.service_provider.echo_gateways();
+        let subscribe = async { gateways.get_events(Protocol::Auto).await?.subscribe().await };
+        let mut events = until_cancelled(&self.cancellation, "subscribing to echo events", subscribe).await?;
+
+        let module = self.id.to_string();
+        let aggregator = self.aggregator.clone();
+        self.tasks.push(spawn_tracked(&module, "event-aggregator", async move {
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(event) => aggregator.record(&event),
+                    Err(e) => warn!("Event stream error: {}", e),
+                }
+            }
+            warn!("Echo event stream ended");
+        }));
+
+        let aggregator = self.aggregator.clone();
+        self.tasks.push(spawn_tracked(&module, "slo-sampler", async move {
+            let mut ticks = tokio::time::interval(aggregator.objectives().sample_every);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                aggregator.sample_slo();
+            }
+        }));
+
+        if let Some(interval) = self.report_interval {
+            let aggregator = self.aggregator.clone();
+            self.tasks.push(spawn_tracked(&module, "stats-reporter", async move {
+                let mut ticks = tokio::time::interval(interval);
+                ticks.tick().await;
+                loop {
+                    ticks.tick().await;
+                    Self::log_stats(&aggregator);
+                }
+            }));
+        }
+
+        info!("✅ Subscribed to echo events");
+        Ok(())
+    }
+
+    #[instrument(name = "module", skip_all, fields(module_id = %self.id))]
+    async fn stop(&mut self) -> Result<()> {
+        info!("Stopping...");
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        Self::log_stats(&self.aggregator);
+        for line in self.aggregator.slo_report().to_string().lines() {
+            info!("SLO {}", line);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+//! Service Provider for Echo Monitor Module
+//!
+//! # Architecture
+//!
+//! The monitor is a **consumer** of the echo module, like echo-client:
+//! - Provides: EchoServiceGateways (to subscribe to echo events)
+//! - Its own MonitorService is offered through handlers, not from here
+//!
+//! Gateways are kept in a [`ServiceMap`] keyed by contract, like the
+//! client's provider.
+
+use std::sync::Arc;
+use echo_api::{new_echo_service_gateways_with_options, GatewayOptions, ServiceMap};
+use echo_contract::EchoServiceGateways;
+use hsu_module_api::ServiceConnector;
+use tracing::debug;
+
+/// Service provider for Echo monitor module.
+#[derive(Clone)]
+pub struct EchoMonitorServiceProvider {
+    gateways: ServiceMap,
+}
+
+impl EchoMonitorServiceProvider {
+    /// Creates a monitor service provider whose echo gateways use `options`.
+    pub fn with_options(
+        service_connector: Arc<dyn ServiceConnector>,
+        options: GatewayOptions,
+    ) -> Self {
+        debug!("[EchoMonitorServiceProvider] Creating echo service gateways");
+        Self {
+            gateways: ServiceMap::new()
+                .with::<dyn EchoServiceGateways>(new_echo_service_gateways_with_options(service_connector, options)),
+        }
+    }
+
+    /// Adds the gateways of another contract `T`.
+    pub fn with_gateways<T: ?Sized + Send + Sync + 'static>(mut self, gateways: Arc<T>) -> Self {
+        self.gateways.insert(gateways);
+        self
+    }
+
+    /// Gets the gateways of contract `T`, if provided.
+    pub fn gateways<T: ?Sized + Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.gateways.get::<T>()
+    }
+
+    /// Gets the echo service gateways.
+    ///
+    /// Shim for `gateways::<dyn EchoServiceGateways>()`, which is always provided.
+    pub fn echo_gateways(&self) -> Arc<dyn EchoServiceGateways> {
+        self.gateways::<dyn EchoServiceGateways>().expect("echo gateways are provided on creation")
+    }
+}
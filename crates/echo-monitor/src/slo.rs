@@ -0,0 +1,226 @@
+//! SLO attainment over sliding windows (a derived-metric module).
+//!
+//! # Architecture
+//!
+//! Nothing new is measured here: the calculator samples counters that
+//! already exist - the aggregated event counts and the client's
+//! latency-budget counters - and derives the SLO numbers from the
+//! difference between now and a sample one window ago:
+//!
+//! ```text
+//! EventAggregator (calls, failures) ──┐
+//! LatencyBudgetMetrics (budgeted,     ├─→ SloCalculator: sample every 10s
+//!                       over budget) ─┘       ↓
+//!                                  now - sample at (now - 5m)
+//!                                             ↓
+//!     5m: availability 99.95% (objective 99.9%, met) over 2000 calls
+//!         latency 98.70% within budget (objective 99%, MISSED) over 2000 calls
+//! ```
+//!
+//! Latency counts come from the process-wide [`LatencyBudgetMetrics`], so
+//! they are only there when the monitor runs beside a client with latency
+//! budgets (`echo-grpc-cli --monitor --latency-budget ...`). Until the
+//! monitor has run for a whole window, the window covers what it has.
+//!
+//! # Rust Learning Note
+//!
+//! Samples are taken against [`Instant`], the monotonic clock: wall-clock
+//! time can jump (NTP, DST), which would stretch or shrink a window.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Sub;
+use std::sync::Mutex;
+use std::time::Instant;
+use echo_api::LatencyBudgetMetrics;
+use echo_contract::{format_duration, EchoMethod};
+
+use crate::contract::{MonitorStats, SloObjectives, SloReport, SloWindow};
+
+/// Counter values the windows are derived from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SloCounts {
+    pub calls: u64,
+    pub failures: u64,
+    pub budgeted: u64,
+    pub over_budget: u64,
+}
+
+impl SloCounts {
+    /// Reads the counters out of `stats` and `budgets`.
+    pub fn read(stats: &MonitorStats, budgets: &LatencyBudgetMetrics) -> Self {
+        Self {
+            calls: stats.total,
+            failures: stats.failures,
+            budgeted: EchoMethod::ALL.into_iter().map(|method| budgets.calls(method)).sum(),
+            over_budget: EchoMethod::ALL.into_iter().map(|method| budgets.violations(method)).sum(),
+        }
+    }
+}
+
+impl Sub for SloCounts {
+    type Output = SloCounts;
+
+    fn sub(self, earlier: SloCounts) -> SloCounts {
+        SloCounts {
+            calls: self.calls.saturating_sub(earlier.calls),
+            failures: self.failures.saturating_sub(earlier.failures),
+            budgeted: self.budgeted.saturating_sub(earlier.budgeted),
+            over_budget: self.over_budget.saturating_sub(earlier.over_budget),
+        }
+    }
+}
+
+/// Keeps counter samples long enough to cover the longest window.
+pub struct SloCalculator {
+    objectives: SloObjectives,
+    samples: Mutex<VecDeque<(Instant, SloCounts)>>,
+}
+
+impl SloCalculator {
+    /// Starts the windows at `started`, with every counter at zero.
+    pub fn new(objectives: SloObjectives, started: Instant) -> Self {
+        Self { objectives, samples: Mutex::new(VecDeque::from([(started, SloCounts::default())])) }
+    }
+
+    /// Returns the objectives reported against.
+    pub fn objectives(&self) -> &SloObjectives {
+        &self.objectives
+    }
+
+    /// Adds a sample, dropping those no window reaches back to anymore.
+    pub fn sample(&self, at: Instant, counts: SloCounts) {
+        let longest = self.objectives.windows.iter().max().copied().unwrap_or_default();
+        let mut samples = self.samples.lock().unwrap();
+        samples.push_back((at, counts));
+        // Keep the newest sample at or before the longest window's start
+        if let Some(horizon) = at.checked_sub(longest) {
+            while samples.get(1).is_some_and(|(next, _)| *next <= horizon) {
+                samples.pop_front();
+            }
+        }
+    }
+
+    /// Computes every window, ending at `now` with `current` counts.
+    pub fn report(&self, now: Instant, current: SloCounts) -> SloReport {
+        let samples = self.samples.lock().unwrap();
+        let mut windows: Vec<_> = self.objectives.windows
+            .iter()
+            .map(|&window| {
+                let start = now.checked_sub(window);
+                // The newest sample at or before the window's start, else the oldest
+                let (at, baseline) = samples
+                    .iter()
+                    .rev()
+                    .find(|(at, _)| start.is_some_and(|start| *at <= start))
+                    .or(samples.front())
+                    .copied()
+                    .unwrap_or((now, current));
+                let counts = current - baseline;
+                SloWindow {
+                    window,
+                    covered: now.saturating_duration_since(at).min(window),
+                    calls: counts.calls,
+                    failures: counts.failures,
+                    budgeted: counts.budgeted,
+                    over_budget: counts.over_budget,
+                }
+            })
+            .collect();
+        windows.sort_by_key(|window| window.window);
+        SloReport { objectives: self.objectives.clone(), windows }
+    }
+}
+
+/// `met` or `MISSED`, so a missed objective stands out in the log.
+fn verdict(attained: f64, objective: f64) -> &'static str {
+    if attained >= objective { "met" } else { "MISSED" }
+}
+
+impl fmt::Display for SloReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, window) in self.windows.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", format_duration(window.window))?;
+            if window.covered < window.window {
+                write!(f, " (first {})", format_duration(window.covered))?;
+            }
+            match window.availability() {
+                Some(availability) => write!(f, ": availability {:.2}% (objective {}%, {}) over {} calls",
+                    availability, self.objectives.availability,
+                    verdict(availability, self.objectives.availability), window.calls)?,
+                None => write!(f, ": no calls")?,
+            }
+            if let Some(attainment) = window.latency_attainment() {
+                write!(f, "; latency {:.2}% within budget (objective {}%, {}) over {} calls",
+                    attainment, self.objectives.latency,
+                    verdict(attainment, self.objectives.latency), window.budgeted)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn counts(calls: u64, failures: u64, budgeted: u64, over_budget: u64) -> SloCounts {
+        SloCounts { calls, failures, budgeted, over_budget }
+    }
+
+    fn objectives(windows: &[u64]) -> SloObjectives {
+        SloObjectives { windows: windows.iter().map(|&secs| Duration::from_secs(secs)).collect(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_windows_slide() {
+        let started = Instant::now();
+        let at = |secs| started + Duration::from_secs(secs);
+        let calculator = SloCalculator::new(objectives(&[300, 60]), started);
+        calculator.sample(at(60), counts(1000, 0, 100, 0));
+        calculator.sample(at(120), counts(2000, 10, 200, 1));
+
+        let report = calculator.report(at(180), counts(3000, 11, 300, 3));
+
+        let [minute, five] = report.windows.as_slice() else { panic!("two windows expected") };
+        assert_eq!((minute.window, minute.covered), (Duration::from_secs(60), Duration::from_secs(60)));
+        assert_eq!((minute.calls, minute.failures, minute.budgeted, minute.over_budget), (1000, 1, 100, 2));
+        assert_eq!(minute.availability(), Some(99.9));
+        assert_eq!(minute.latency_attainment(), Some(98.0));
+        // The monitor hasn't run for five minutes yet
+        assert_eq!((five.covered, five.calls, five.failures), (Duration::from_secs(180), 3000, 11));
+
+        let text = report.to_string();
+        assert!(text.contains("1m: availability 99.90% (objective 99.9%, met) over 1000 calls"), "{}", text);
+        assert!(text.contains("latency 98.00% within budget (objective 99%, MISSED)"), "{}", text);
+        assert!(text.contains("5m (first 3m)"), "{}", text);
+    }
+
+    #[test]
+    fn test_drops_samples_outside_every_window() {
+        let started = Instant::now();
+        let calculator = SloCalculator::new(objectives(&[60]), started);
+        for secs in (10..=300).step_by(10) {
+            calculator.sample(started + Duration::from_secs(secs), counts(secs, 0, 0, 0));
+        }
+
+        let samples = calculator.samples.lock().unwrap();
+        assert_eq!(samples.front().unwrap().0, started + Duration::from_secs(240));
+        assert_eq!(samples.len(), 7);
+    }
+
+    #[test]
+    fn test_quiet_window() {
+        let started = Instant::now();
+        let calculator = SloCalculator::new(objectives(&[60]), started);
+
+        let report = calculator.report(started + Duration::from_secs(90), SloCounts::default());
+
+        assert_eq!(report.windows[0].availability(), None);
+        assert_eq!(report.to_string(), "1m: no calls");
+    }
+}
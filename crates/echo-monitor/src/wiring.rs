@@ -0,0 +1,210 @@
+//! Echo Monitor Module Wiring (Layer 5)
+//!
+//! # Architecture
+//!
+//! The monitor is wired as **both** kinds of module at once:
+//!
+//! ```text
+//! create_service_provider → echo gateways  (consumer of "echo")
+//! create_module           → monitor handlers (provider of "echo-monitor")
+//! direct_closure_enabler  → hands the handlers to consumers' gateways
+//! ```
+//!
+//! The framework links them from the same config as the other modules;
+//! no module knows about the others' wiring.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Once, OnceLock};
+use std::time::Duration;
+use echo_api::{
+    CancellationToken, DependencyRegistry, Discovery, DirectClosureEnablerFn, GatewayOptions, ModuleDependencies,
+    PanicGuardModule, PanicPolicy, StartupCoordinator,
+};
+use echo_contract::{EchoServiceId, ECHO_MONITOR_MODULE_ID};
+use hsu_common::{ModuleID, Result};
+use hsu_module_api::{
+    DirectClosureEnablerOptions, Module, ServiceConnector, ServiceProviderHandle,
+    new_module_descriptor, register_module,
+};
+use tracing::{debug, info};
+
+use crate::aggregator::EventAggregator;
+use crate::contract::{MonitorServiceGateways, MonitorServiceHandlers, SloObjectives, MONITOR_SERVICE_ID};
+use crate::contract::monitor_direct_closure_enabler;
+use crate::module::EchoMonitorModule;
+use crate::service_provider::EchoMonitorServiceProvider;
+
+/// Configuration for Echo monitor module.
+pub struct EchoMonitorModuleConfig {
+    pub module_id: ModuleID,
+    /// Subscribe to this gRPC server directly (`host:port`) instead of
+    /// using the framework-created channel.
+    pub grpc_address: Option<String>,
+    /// How to find the echo server without `grpc_address` (registry or mDNS).
+    pub discovery: Discovery,
+    /// Log the counts this often (only on stop if `None`).
+    pub report_interval: Option<Duration>,
+    /// Objectives SLO attainment is computed against (served by
+    /// `MonitorService::slo`, logged on stop).
+    pub slo: SloObjectives,
+    /// What to do when the module panics in `start`/`stop`.
+    pub panic_policy: PanicPolicy,
+    /// Cancelled on Ctrl-C (see [`echo_api::cancel_on_ctrl_c`]): aborts a
+    /// `start()` still subscribing to the echo events.
+    pub cancellation: CancellationToken,
+}
+
+impl Default for EchoMonitorModuleConfig {
+    fn default() -> Self {
+        Self {
+            module_id: ModuleID::from(ECHO_MONITOR_MODULE_ID),
+            grpc_address: None,
+            discovery: Discovery::default(),
+            report_interval: None,
+            slo: SloObjectives::default(),
+            panic_policy: PanicPolicy::default(),
+            cancellation: CancellationToken::new(),
+        }
+    }
+}
+
+/// Module configuration captured at init time.
+///
+/// The framework factories are plain function pointers, so they can't
+/// capture the config - they read it from here instead.
+static MODULE_CONFIG: OnceLock<EchoMonitorModuleConfig> = OnceLock::new();
+
+/// Factory function for creating the service provider.
+///
+/// Registers the echo gateways under the "echo" module ID, so the echo
+/// module's direct closure reaches the monitor when both run in-process.
+fn create_service_provider(
+    service_connector: Arc<dyn ServiceConnector>,
+) -> ServiceProviderHandle {
+    debug!("[EchoMonitorModule] Creating service provider");
+
+    let options = GatewayOptions {
+        grpc_address: MODULE_CONFIG.get().and_then(|c| c.grpc_address.clone()),
+        discovery: MODULE_CONFIG.get().map(|c| c.discovery.clone()).unwrap_or_default(),
+        ..Default::default()
+    };
+    let service_provider = EchoMonitorServiceProvider::with_options(service_connector, options);
+
+    let gateways = service_provider.echo_gateways();
+    let mut service_gateways_map = HashMap::new();
+    service_gateways_map.insert(
+        gateways.module_id(),
+        Box::new(gateways) as Box<dyn std::any::Any + Send + Sync>,
+    );
+
+    ServiceProviderHandle {
+        service_provider: Box::new(service_provider),
+        service_gateways_map,
+    }
+}
+
+/// Factory function for creating module.
+///
+/// The module and the handlers share one aggregator: the module fills
+/// it, the handlers serve it.
+fn create_module(service_provider: EchoMonitorServiceProvider) -> (Box<dyn Module>, MonitorServiceHandlers) {
+    debug!("[EchoMonitorModule] Creating module");
+
+    let objectives = MODULE_CONFIG.get().map(|c| c.slo.clone()).unwrap_or_default();
+    let aggregator = Arc::new(EventAggregator::with_objectives(objectives));
+    let mut module = EchoMonitorModule::new(service_provider, aggregator.clone());
+    if let Some(interval) = MODULE_CONFIG.get().and_then(|c| c.report_interval) {
+        module = module.with_report_interval(interval);
+    }
+    // Its own child of the process token, so the coordinator can cancel just this start
+    let cancellation = MODULE_CONFIG.get().map(|c| c.cancellation.child_token()).unwrap_or_default();
+    module = module.with_cancellation(cancellation.clone());
+
+    let panic_policy = MODULE_CONFIG.get().map(|c| c.panic_policy).unwrap_or_default();
+    let handlers = MonitorServiceHandlers::new(aggregator);
+
+    let module = PanicGuardModule::new(Box::new(module), panic_policy);
+    (Box::new(StartupCoordinator::global().coordinate(Box::new(module), cancellation)), handlers)
+}
+
+/// Function for enabling direct closure.
+fn direct_closure_enabler(
+    options: DirectClosureEnablerOptions<Arc<dyn MonitorServiceGateways>, MonitorServiceHandlers>,
+) {
+    monitor_direct_closure_enabler(options);
+}
+
+/// What the monitor declares: it watches the echo events and serves
+/// the aggregate.
+fn module_dependencies() -> ModuleDependencies {
+    ModuleDependencies::default()
+        .consumes_echo(EchoServiceId::Events)
+        .provides(MONITOR_SERVICE_ID)
+}
+
+/// The descriptor's direct closure enabler (the wiring test reads it too).
+const DIRECT_CLOSURE_ENABLER: Option<DirectClosureEnablerFn<Arc<dyn MonitorServiceGateways>, MonitorServiceHandlers>> =
+    Some(direct_closure_enabler);
+
+static INIT: Once = Once::new();
+
+/// Initializes the Echo monitor module.
+///
+/// ## Comparison with Golang
+///
+/// **Go version:**
+/// ```go
+/// func init() {
+///     modulewiring.RegisterModule("echo-monitor", modulewiring.ModuleDescriptor[...]{
+///         ServiceProviderFactoryFunc: NewEchoMonitorServiceProvider,
+///         ModuleFactoryFunc:          echomonitordomain.NewEchoMonitorModule,
+///         DirectClosureEnableFunc:    echomonitorapi.MonitorDirectClosureEnable,
+///     })
+/// }
+/// ```
+pub fn init_echo_monitor_module(config: EchoMonitorModuleConfig) -> Result<()> {
+    INIT.call_once(|| {
+        info!("[EchoMonitorModule] Initializing with config: module_id={}", config.module_id);
+
+        let descriptor = new_module_descriptor::<
+            EchoMonitorServiceProvider,
+            Arc<dyn MonitorServiceGateways>,  // Gateway type for modules accessing the monitor
+            MonitorServiceHandlers,           // Handler type the monitor provides
+        >(
+            create_service_provider,
+            create_module,
+            None,                    // Direct only: no protocol handlers
+            DIRECT_CLOSURE_ENABLER,
+        );
+
+        register_module(config.module_id.clone(), descriptor);
+        DependencyRegistry::global().declare(&config.module_id, module_dependencies());
+        let _ = MODULE_CONFIG.set(config);
+
+        info!("[EchoMonitorModule] ✅ Module registered successfully");
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_api::{echo_gateway_service_ids, WiringDescriptor};
+    use echo_contract::ECHO_MODULE_ID;
+    use crate::gateways::{MonitorServiceGatewaysImpl, MONITOR_PROTOCOLS};
+
+    #[test]
+    fn test_wiring_is_consistent() {
+        let config = EchoMonitorModuleConfig::default();
+        let gateways = MonitorServiceGatewaysImpl::new(config.module_id.clone());
+
+        // No registrar: only what the descriptor's enabler hands over
+        WiringDescriptor::new(config.module_id.to_string(), module_dependencies())
+            .with_direct_closure(DIRECT_CLOSURE_ENABLER.is_some())
+            .with_gateway_services(gateways.service_ids())
+            .with_gateway_protocols(MONITOR_PROTOCOLS)
+            .with_consumed_gateways(ECHO_MODULE_ID, echo_gateway_service_ids())
+            .assert_consistent();
+    }
+}
@@ -0,0 +1,198 @@
+//! The Plugin ABI
+//!
+//! Everything the host and a plugin exchange, in types whose layout is
+//! fixed (`#[repr(C)]`) and functions with the C calling convention:
+//!
+//! ```text
+//! host                                         plugin
+//!   EchoPluginHost { abi_version, context, register_handlers }
+//!       ──→ echo_plugin_register(&host) ──→ checks abi_version
+//!       ←── register_handlers(context, EchoPluginHandlers { instance, call, free_buffer, release })
+//!   call(instance, Echo, "hi", &mut out) ──→ Ok, out = "hi" (plugin-allocated)
+//!   free_buffer(out)                     ──→ freed by the allocator that made it
+//!   release(instance)                    ──→ plugin runtime shut down
+//! ```
+//!
+//! Memory is always freed by the side that allocated it: the host copies
+//! an output buffer and hands it back to `free_buffer`. Error text
+//! travels in the output buffer with [`EchoPluginStatus::Error`], so
+//! `UNAVAILABLE: ...` and the other error prefixes survive the boundary.
+//!
+//! Any change to these types, the methods or their encodings bumps
+//! [`ECHO_PLUGIN_ABI_VERSION`]; a plugin built for another version
+//! refuses to register.
+
+use std::ffi::{c_char, c_void};
+use std::time::Duration;
+use echo_contract::ServerInfo;
+use hsu_common::{Error, Result};
+
+/// Version of this ABI; host and plugin must agree on it.
+pub const ECHO_PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Symbol of the [`EchoPluginRegisterFn`] every plugin exports.
+pub const ECHO_PLUGIN_REGISTER_SYMBOL: &[u8] = b"echo_plugin_register\0";
+
+/// Outcome of a call across the boundary.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EchoPluginStatus {
+    Ok = 0,
+    /// The call failed; the output buffer holds the error text.
+    Error = 1,
+    /// The plugin was built for another [`ECHO_PLUGIN_ABI_VERSION`].
+    AbiMismatch = 2,
+}
+
+/// Service methods carried by the ABI, with their encodings.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EchoPluginMethod {
+    /// UTF-8 message in, UTF-8 reply out.
+    Echo = 0,
+    /// Payload in, payload out.
+    EchoBytes = 1,
+    /// Nothing in, JSON [`ServerInfo`] out (see [`encode_info`]).
+    GetInfo = 2,
+}
+
+/// Bytes allocated by the plugin, freed by its `free_buffer`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EchoPluginBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl EchoPluginBuffer {
+    /// A buffer holding nothing (nothing to free).
+    pub const fn empty() -> Self {
+        Self { ptr: std::ptr::null_mut(), len: 0, capacity: 0 }
+    }
+
+    /// Hands `bytes` over without copying.
+    pub fn from_vec(bytes: Vec<u8>) -> Self {
+        let mut bytes = std::mem::ManuallyDrop::new(bytes);
+        Self { ptr: bytes.as_mut_ptr(), len: bytes.len(), capacity: bytes.capacity() }
+    }
+
+    /// The bytes, valid until the buffer is freed.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be empty or come from [`from_vec`](Self::from_vec)
+    /// and not be freed yet.
+    pub unsafe fn as_slice(&self) -> &[u8] {
+        if self.ptr.is_null() {
+            return &[];
+        }
+        std::slice::from_raw_parts(self.ptr, self.len)
+    }
+
+    /// Takes the bytes back, to be dropped by the allocator that made them.
+    ///
+    /// # Safety
+    ///
+    /// Only in the library whose [`from_vec`](Self::from_vec) created the
+    /// buffer, once.
+    pub unsafe fn into_vec(self) -> Vec<u8> {
+        if self.ptr.is_null() {
+            return Vec::new();
+        }
+        Vec::from_raw_parts(self.ptr, self.len, self.capacity)
+    }
+}
+
+/// A plugin's service behind C function pointers.
+///
+/// `call` may be invoked from several host threads at once.
+#[repr(C)]
+pub struct EchoPluginHandlers {
+    /// Plugin state passed back to every function.
+    pub instance: *mut c_void,
+    /// Module the handlers serve (NUL-terminated, valid until `release`).
+    pub module_id: *const c_char,
+    /// Runs `method` on `input`, blocking until it's done; the reply (or
+    /// the error text) is stored in `*output`.
+    pub call: unsafe extern "C" fn(
+        instance: *mut c_void,
+        method: EchoPluginMethod,
+        input: *const u8,
+        input_len: usize,
+        output: *mut EchoPluginBuffer,
+    ) -> EchoPluginStatus,
+    /// Frees a buffer `call` stored.
+    pub free_buffer: unsafe extern "C" fn(buffer: EchoPluginBuffer),
+    /// Releases `instance`; no call may follow.
+    pub release: unsafe extern "C" fn(instance: *mut c_void),
+}
+
+/// What the host passes to the plugin's register function.
+#[repr(C)]
+pub struct EchoPluginHost {
+    /// The host's [`ECHO_PLUGIN_ABI_VERSION`].
+    pub abi_version: u32,
+    /// Host state passed back to `register_handlers`.
+    pub context: *mut c_void,
+    /// Takes ownership of one module's handlers (called during
+    /// registration only).
+    pub register_handlers: unsafe extern "C" fn(context: *mut c_void, handlers: EchoPluginHandlers),
+}
+
+/// `echo_plugin_register`: registers the plugin's handlers with `host`.
+pub type EchoPluginRegisterFn = unsafe extern "C" fn(host: *const EchoPluginHost) -> EchoPluginStatus;
+
+/// Encodes a [`ServerInfo`] for [`EchoPluginMethod::GetInfo`].
+pub fn encode_info(info: &ServerInfo) -> Vec<u8> {
+    serde_json::json!({
+        "module_id": info.module_id,
+        "instance_id": info.instance_id,
+        "version": info.version,
+        "git_hash": info.git_hash,
+        "uptime_ms": info.uptime.as_millis() as u64,
+        "features": info.features,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// Reverses [`encode_info`].
+pub fn decode_info(bytes: &[u8]) -> Result<ServerInfo> {
+    let invalid = |detail: String| Error::Protocol(format!("Invalid plugin get_info reply: {}", detail));
+    let value: serde_json::Value = serde_json::from_slice(bytes).map_err(|e| invalid(e.to_string()))?;
+    let text = |field: &str| {
+        value[field].as_str().map(str::to_string).ok_or_else(|| invalid(format!("missing '{}'", field)))
+    };
+    Ok(ServerInfo {
+        module_id: text("module_id")?,
+        instance_id: text("instance_id")?,
+        version: text("version")?,
+        git_hash: text("git_hash")?,
+        uptime: Duration::from_millis(value["uptime_ms"].as_u64().ok_or_else(|| invalid("missing 'uptime_ms'".into()))?),
+        features: value["features"]
+            .as_array()
+            .map(|features| features.iter().filter_map(|f| f.as_str().map(str::to_string)).collect())
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_round_trip() {
+        let info = ServerInfo {
+            module_id: "echo".to_string(),
+            instance_id: "a1b2".to_string(),
+            version: "0.1.0".to_string(),
+            git_hash: "abc123".to_string(),
+            uptime: Duration::from_millis(61_500),
+            features: vec!["grpc".to_string()],
+        };
+
+        assert_eq!(decode_info(&encode_info(&info)).unwrap(), info);
+        assert!(decode_info(b"{}").unwrap_err().to_string().contains("missing 'module_id'"));
+    }
+}
@@ -0,0 +1,154 @@
+//! The Plugin Side of the ABI
+//!
+//! A plugin crate (`crate-type = ["cdylib"]`) builds its service and
+//! exports it with one macro:
+//!
+//! ```ignore
+//! echo_plugin::export_echo_plugin!(ECHO_MODULE_ID, || Ok(Arc::new(EchoServiceImpl::new()) as Arc<dyn EchoService>));
+//! ```
+//!
+//! Calls arrive on host threads and are run to completion on a runtime
+//! the plugin owns (`echo-plugin` worker threads), so the plugin's async
+//! code never touches the host's runtime. Panics are caught before they
+//! reach the boundary and returned as errors.
+
+use std::ffi::{c_void, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use bytes::Bytes;
+use echo_contract::EchoService;
+use hsu_common::{Error, Result};
+use tokio::runtime::Runtime;
+
+use crate::abi::{
+    encode_info, EchoPluginBuffer, EchoPluginHandlers, EchoPluginHost, EchoPluginMethod, EchoPluginStatus,
+    ECHO_PLUGIN_ABI_VERSION,
+};
+
+/// Exports `echo_plugin_register`, registering the service `build`
+/// returns as the handlers of `module_id` (a `&str`).
+#[macro_export]
+macro_rules! export_echo_plugin {
+    ($module_id:expr, $build:expr) => {
+        /// Registers this plugin's handlers with the host (see `echo_plugin::abi`).
+        ///
+        /// # Safety
+        ///
+        /// `host` must point to a valid `EchoPluginHost`.
+        #[no_mangle]
+        pub unsafe extern "C" fn echo_plugin_register(
+            host: *const $crate::abi::EchoPluginHost,
+        ) -> $crate::abi::EchoPluginStatus {
+            $crate::export::register(host, $module_id, $build)
+        }
+    };
+}
+
+/// What `instance` points to.
+struct PluginInstance {
+    runtime: Runtime,
+    service: Arc<dyn EchoService>,
+    /// What `EchoPluginHandlers::module_id` points to.
+    _module_id: CString,
+}
+
+/// Checks the host's ABI version, builds the service and registers it -
+/// the body of the exported `echo_plugin_register`.
+///
+/// # Safety
+///
+/// `host` must point to a valid [`EchoPluginHost`].
+pub unsafe fn register(
+    host: *const EchoPluginHost,
+    module_id: &str,
+    build: impl FnOnce() -> Result<Arc<dyn EchoService>>,
+) -> EchoPluginStatus {
+    let Some(host) = host.as_ref() else {
+        return EchoPluginStatus::Error;
+    };
+    if host.abi_version != ECHO_PLUGIN_ABI_VERSION {
+        return EchoPluginStatus::AbiMismatch;
+    }
+    let handlers = catch_unwind(AssertUnwindSafe(|| plugin_handlers(module_id, build()?)));
+    match handlers {
+        Ok(Ok(handlers)) => {
+            (host.register_handlers)(host.context, handlers);
+            EchoPluginStatus::Ok
+        }
+        _ => EchoPluginStatus::Error,
+    }
+}
+
+/// Puts `service` behind the ABI, on a runtime of its own.
+///
+/// Also what a host uses to run a service in-process through the same
+/// ABI (see `EchoPlugin::from_handlers`).
+pub fn plugin_handlers(module_id: &str, service: Arc<dyn EchoService>) -> Result<EchoPluginHandlers> {
+    let module_id = CString::new(module_id)
+        .map_err(|_| Error::Validation { message: format!("Module ID {:?} contains a NUL byte", module_id) })?;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("echo-plugin")
+        .enable_all()
+        .build()
+        .map_err(|e| Error::Protocol(format!("Failed to start plugin runtime: {}", e)))?;
+    // The string's buffer stays put when the CString moves into the box
+    let module_id_ptr = module_id.as_ptr();
+    let instance = Box::new(PluginInstance { runtime, service, _module_id: module_id });
+    Ok(EchoPluginHandlers {
+        instance: Box::into_raw(instance) as *mut c_void,
+        module_id: module_id_ptr,
+        call,
+        free_buffer,
+        release,
+    })
+}
+
+unsafe extern "C" fn call(
+    instance: *mut c_void,
+    method: EchoPluginMethod,
+    input: *const u8,
+    input_len: usize,
+    output: *mut EchoPluginBuffer,
+) -> EchoPluginStatus {
+    let Some(instance) = (instance as *const PluginInstance).as_ref() else {
+        return EchoPluginStatus::Error;
+    };
+    let input = if input.is_null() { &[][..] } else { std::slice::from_raw_parts(input, input_len) };
+    let result = catch_unwind(AssertUnwindSafe(|| instance.runtime.block_on(dispatch(&instance.service, method, input))))
+        .unwrap_or_else(|_| Err(Error::Protocol("panic in echo plugin".to_string())));
+    let (status, bytes) = match result {
+        Ok(reply) => (EchoPluginStatus::Ok, reply),
+        Err(e) => (EchoPluginStatus::Error, e.to_string().into_bytes()),
+    };
+    if !output.is_null() {
+        *output = EchoPluginBuffer::from_vec(bytes);
+    }
+    status
+}
+
+/// Decodes `input` for `method`, calls the service and encodes the reply.
+async fn dispatch(service: &Arc<dyn EchoService>, method: EchoPluginMethod, input: &[u8]) -> Result<Vec<u8>> {
+    match method {
+        EchoPluginMethod::Echo => {
+            let message = std::str::from_utf8(input)
+                .map_err(|_| Error::Validation { message: "message is not valid UTF-8".to_string() })?;
+            Ok(service.echo(message.into()).await?.as_bytes().to_vec())
+        }
+        EchoPluginMethod::EchoBytes => Ok(service.echo_bytes(Bytes::copy_from_slice(input)).await?.to_vec()),
+        EchoPluginMethod::GetInfo => Ok(encode_info(&service.get_info().await?)),
+    }
+}
+
+unsafe extern "C" fn free_buffer(buffer: EchoPluginBuffer) {
+    drop(buffer.into_vec());
+}
+
+unsafe extern "C" fn release(instance: *mut c_void) {
+    if instance.is_null() {
+        return;
+    }
+    let instance = *Box::from_raw(instance as *mut PluginInstance);
+    // May run on a host runtime thread, where a blocking shutdown panics
+    instance.runtime.shutdown_background();
+}
@@ -0,0 +1,276 @@
+//! The Host Side of the ABI
+//!
+//! [`EchoPlugin::load`] opens the library, asks it to register its
+//! handlers and wraps each module's handlers in a [`PluginEchoService`],
+//! an ordinary `EchoService` the host can decorate and hand out by direct
+//! closure. Plugin calls block (see [`crate::abi`]), so they run on the
+//! host's blocking pool, never on a runtime worker.
+//!
+//! # Rust Learning Note
+//!
+//! A plugin library is never unloaded: [`EchoPlugin::load`] leaks it
+//! (`Box::leak` gives a `&'static Library`, so `dlclose` never runs).
+//! A handle's `release` (in `Drop`) only starts shutting the plugin's
+//! runtime down - its threads wind down in the background, still running
+//! plugin code that must stay mapped. Loading is a once-per-process
+//! affair, so the leak is one library, not one per call.
+
+use std::ffi::{c_void, CStr};
+use std::path::Path;
+use std::sync::Arc;
+use async_trait::async_trait;
+use bytes::Bytes;
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+use hsu_common::{Error, Result};
+use libloading::Library;
+use tracing::{debug, info};
+
+use crate::abi::{
+    decode_info, EchoPluginBuffer, EchoPluginHandlers, EchoPluginHost, EchoPluginMethod, EchoPluginRegisterFn,
+    EchoPluginStatus, ECHO_PLUGIN_ABI_VERSION, ECHO_PLUGIN_REGISTER_SYMBOL,
+};
+
+/// One module's handlers, registered by a plugin.
+struct PluginHandle {
+    handlers: EchoPluginHandlers,
+    module_id: String,
+}
+
+// The ABI requires `call` to be safe from any thread (the plugin runs
+// calls on its own runtime); `instance` is only passed back to it.
+unsafe impl Send for PluginHandle {}
+unsafe impl Sync for PluginHandle {}
+
+impl PluginHandle {
+    fn new(handlers: EchoPluginHandlers) -> Self {
+        let module_id = if handlers.module_id.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(handlers.module_id) }.to_string_lossy().into_owned()
+        };
+        Self { handlers, module_id }
+    }
+
+    /// Runs `method` on `input` in the plugin, blocking the thread.
+    fn call(&self, method: EchoPluginMethod, input: &[u8]) -> Result<Vec<u8>> {
+        let mut output = EchoPluginBuffer::empty();
+        let (status, bytes) = unsafe {
+            let status = (self.handlers.call)(self.handlers.instance, method, input.as_ptr(), input.len(), &mut output);
+            let bytes = output.as_slice().to_vec();
+            (self.handlers.free_buffer)(output);
+            (status, bytes)
+        };
+        match status {
+            EchoPluginStatus::Ok => Ok(bytes),
+            // Error prefixes (UNAVAILABLE, ...) come through as they were
+            _ => Err(Error::Protocol(String::from_utf8_lossy(&bytes).into_owned())),
+        }
+    }
+}
+
+impl Drop for PluginHandle {
+    fn drop(&mut self) {
+        unsafe { (self.handlers.release)(self.handlers.instance) };
+    }
+}
+
+/// A loaded plugin: the handlers it registered, by module.
+pub struct EchoPlugin {
+    handles: Vec<Arc<PluginHandle>>,
+}
+
+impl EchoPlugin {
+    /// Loads the plugin library at `path` and lets it register its
+    /// handlers.
+    ///
+    /// Fails if the library has no `echo_plugin_register`, was built for
+    /// another ABI version or registers nothing.
+    pub fn load(path: &Path) -> Result<Self> {
+        let failed = |detail: String| Error::Protocol(format!("Failed to load plugin {}: {}", path.display(), detail));
+        // Running its initializers is the point of loading it; never
+        // unloaded (see the module docs)
+        let library = unsafe { Library::new(path) }.map_err(|e| failed(e.to_string()))?;
+        let library: &'static Library = Box::leak(Box::new(library));
+        let register = unsafe { library.get::<EchoPluginRegisterFn>(ECHO_PLUGIN_REGISTER_SYMBOL) }
+            .map_err(|e| failed(e.to_string()))?;
+
+        let mut registered: Vec<EchoPluginHandlers> = Vec::new();
+        let host = EchoPluginHost {
+            abi_version: ECHO_PLUGIN_ABI_VERSION,
+            context: &mut registered as *mut Vec<EchoPluginHandlers> as *mut c_void,
+            register_handlers,
+        };
+        let status = unsafe { register(&host) };
+        let handles: Vec<_> = registered
+            .into_iter()
+            .map(|handlers| Arc::new(PluginHandle::new(handlers)))
+            .collect();
+        let rejected = match status {
+            EchoPluginStatus::Ok if !handles.is_empty() => None,
+            EchoPluginStatus::Ok => Some("it registered no handlers".to_string()),
+            EchoPluginStatus::AbiMismatch => Some(format!("it wasn't built for plugin ABI v{}", ECHO_PLUGIN_ABI_VERSION)),
+            EchoPluginStatus::Error => Some("its registration failed".to_string()),
+        };
+        if let Some(detail) = rejected {
+            // Handed over before the failure: released, their runtimes
+            // winding down in the still loaded library
+            drop(handles);
+            return Err(failed(detail));
+        }
+        let plugin = Self { handles };
+        info!("✅ Loaded plugin {} (modules: {})", path.display(), plugin.module_ids().join(", "));
+        Ok(plugin)
+    }
+
+    /// Uses handlers created in this process (see
+    /// [`plugin_handlers`](crate::plugin_handlers)), e.g. to test a
+    /// plugin's service through the ABI without building a library.
+    pub fn from_handlers(handlers: EchoPluginHandlers) -> Self {
+        Self { handles: vec![Arc::new(PluginHandle::new(handlers))] }
+    }
+
+    /// Modules the plugin registered handlers for.
+    pub fn module_ids(&self) -> Vec<String> {
+        self.handles.iter().map(|handle| handle.module_id.clone()).collect()
+    }
+
+    /// The service of `module_id`, if the plugin registered it.
+    pub fn service(&self, module_id: &str) -> Option<Arc<dyn EchoService>> {
+        self.handles
+            .iter()
+            .find(|handle| handle.module_id == module_id)
+            .map(|handle| Arc::new(PluginEchoService { handle: handle.clone() }) as Arc<dyn EchoService>)
+    }
+}
+
+/// Collects handlers during [`EchoPlugin::load`].
+unsafe extern "C" fn register_handlers(context: *mut c_void, handlers: EchoPluginHandlers) {
+    if let Some(registered) = (context as *mut Vec<EchoPluginHandlers>).as_mut() {
+        registered.push(handlers);
+    }
+}
+
+/// A plugin module's service, called through the ABI.
+pub struct PluginEchoService {
+    handle: Arc<PluginHandle>,
+}
+
+impl PluginEchoService {
+    /// Runs `method` on the blocking pool.
+    async fn call(&self, method: EchoPluginMethod, input: Vec<u8>) -> Result<Vec<u8>> {
+        let handle = self.handle.clone();
+        tokio::task::spawn_blocking(move || handle.call(method, &input))
+            .await
+            .map_err(|e| Error::Protocol(format!("Plugin call was aborted: {}", e)))?
+    }
+
+    fn not_carried(&self, method: &str) -> Error {
+        debug!("{} isn't carried to plugin module {}", method, self.handle.module_id);
+        Error::Protocol(format!("{} isn't carried by the echo plugin ABI (v{})", method, ECHO_PLUGIN_ABI_VERSION))
+    }
+}
+
+#[async_trait]
+impl EchoService for PluginEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let reply = self.call(EchoPluginMethod::Echo, message.as_bytes().to_vec()).await?;
+        let reply = String::from_utf8(reply).map_err(|_| Error::Protocol("Plugin echo reply is not UTF-8".to_string()))?;
+        Ok(reply.into())
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        Ok(self.call(EchoPluginMethod::EchoBytes, payload.to_vec()).await?.into())
+    }
+
+    async fn echo_reliable(&self, _message: Arc<str>, _idempotency_key: String) -> Result<EchoAck> {
+        Err(self.not_carried("echo_reliable"))
+    }
+
+    async fn echo_file(&self, _chunks: ByteStream) -> Result<FileDigest> {
+        Err(self.not_carried("echo_file"))
+    }
+
+    async fn echo_with_session(&self, _session_id: String, _message: Arc<str>) -> Result<SessionEcho> {
+        Err(self.not_carried("echo_with_session"))
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        decode_info(&self.call(EchoPluginMethod::GetInfo, Vec::new()).await?)
+    }
+
+    async fn schedule_echo(&self, _message: Arc<str>, _schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        Err(self.not_carried("schedule_echo"))
+    }
+
+    async fn cancel_scheduled_echo(&self, _job_id: String) -> Result<bool> {
+        Err(self.not_carried("cancel_scheduled_echo"))
+    }
+
+    async fn get_history(&self, _query: HistoryQuery) -> Result<HistoryPage> {
+        Err(self.not_carried("get_history"))
+    }
+
+    async fn stream_history(&self, _query: HistoryQuery) -> Result<HistoryStream> {
+        Err(self.not_carried("stream_history"))
+    }
+
+    async fn export_history(&self, _format: HistoryExportFormat, _query: HistoryQuery) -> Result<ByteStream> {
+        Err(self.not_carried("export_history"))
+    }
+
+    async fn import_history(&self, _format: HistoryExportFormat, _data: ByteStream) -> Result<HistoryImportReport> {
+        Err(self.not_carried("import_history"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::unavailable;
+    use crate::plugin_handlers;
+
+    /// Echoes messages; `echo_bytes` fails as UNAVAILABLE, `get_info` panics.
+    struct MockService;
+
+    #[async_trait]
+    impl EchoService for MockService {
+        async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+            Ok(message)
+        }
+
+        async fn echo_bytes(&self, _payload: Bytes) -> Result<Bytes> {
+            Err(unavailable("plugin backend down"))
+        }
+
+        async fn get_info(&self) -> Result<ServerInfo> {
+            panic!("get_info failed");
+        }
+    }
+
+    fn plugin() -> EchoPlugin {
+        EchoPlugin::from_handlers(plugin_handlers(echo_contract::ECHO_MODULE_ID, Arc::new(MockService)).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_calls_cross_the_abi() {
+        let plugin = plugin();
+        assert_eq!(plugin.module_ids(), vec!["echo".to_string()]);
+        let service = plugin.service("echo").unwrap();
+
+        assert_eq!(&*service.echo("héllo".into()).await.unwrap(), "héllo");
+        let error = service.echo_bytes(Bytes::from_static(b"x")).await.unwrap_err();
+        assert!(echo_contract::is_unavailable(&error), "{}", error);
+        assert!(service.get_info().await.unwrap_err().to_string().contains("panic in echo plugin"));
+        assert!(service.get_history(HistoryQuery::default()).await.unwrap_err().to_string().contains("isn't carried"));
+        assert!(plugin.service("other").is_none());
+    }
+
+    #[test]
+    fn test_missing_library_fails_to_load() {
+        let error = EchoPlugin::load(Path::new("/nonexistent/libecho_plugin.so")).err().unwrap();
+        assert!(error.to_string().contains("Failed to load plugin"), "{}", error);
+    }
+}
@@ -0,0 +1,72 @@
+//! Echo Plugins - Modules Loaded at Runtime (Layer 3/5 Boundary, experimental)
+//!
+//! # Architecture
+//!
+//! Direct closure normally needs the serving module compiled into the
+//! binary. Here the echo server is built as a `cdylib` instead
+//! (`echo-server-plugin`) and loaded by `echo-host` at runtime. A host
+//! module stands in for it: it registers under the plugin's module ID and
+//! hands the plugin's handlers to the client by direct closure, so the
+//! client can't tell the module wasn't linked in:
+//!
+//! ```text
+//! echo-host                                  libecho_server_plugin.so
+//!   EchoPlugin::load(path)
+//!     ├── dlopen, look up echo_plugin_register
+//!     └── echo_plugin_register(&EchoPluginHost) ──→ builds EchoServiceImpl
+//!             ←── register_handlers(EchoPluginHandlers) ── on its own runtime
+//!   init_echo_plugin_module → module "echo"
+//!     └── handlers: PluginEchoService ──call(method, bytes)──→ plugin
+//!           ↓ direct closure
+//!   echo-client gateways (Protocol::Direct)
+//! ```
+//!
+//! Rust has no stable ABI - trait objects, `Arc` and `async fn` can't
+//! cross the library boundary, and the plugin may be built by another
+//! compiler. Everything crossing it is in [`abi`]: `#[repr(C)]` structs,
+//! `extern "C"` function pointers and plain bytes, guarded by
+//! [`ECHO_PLUGIN_ABI_VERSION`]. A plugin exports itself with
+//! [`export_echo_plugin!`].
+//!
+//! ## Limits
+//!
+//! - Only `echo`, `echo_bytes` and `get_info` are carried; other methods
+//!   fail on the host without reaching the plugin.
+//! - The plugin runs its own tokio runtime and `tracing` dispatcher (its
+//!   copies of those crates don't share state with the host's), so its
+//!   logs don't reach the host's subscriber.
+//! - The request context (priority, transforms, caller) isn't carried.
+//! - A plugin is loaded once and never unloaded while the host runs.
+//!
+//! ## Cargo Features
+//!
+//! | Feature | Default | Enables                                     | Pulls in                        |
+//! |---------|---------|---------------------------------------------|---------------------------------|
+//! | `host`  | ✅      | `EchoPlugin`, `init_echo_plugin_module`     | libloading, echo-api, hsu-module-api |
+//!
+//! Plugins depend on this crate with `default-features = false`.
+
+pub mod abi;
+pub mod export;
+#[cfg(feature = "host")]
+pub mod host;
+#[cfg(feature = "host")]
+pub mod module;
+#[cfg(feature = "host")]
+pub mod service_provider;
+#[cfg(feature = "host")]
+pub mod wiring;
+
+pub use abi::{
+    EchoPluginBuffer, EchoPluginHandlers, EchoPluginHost, EchoPluginMethod, EchoPluginRegisterFn, EchoPluginStatus,
+    ECHO_PLUGIN_ABI_VERSION, ECHO_PLUGIN_REGISTER_SYMBOL,
+};
+pub use export::plugin_handlers;
+#[cfg(feature = "host")]
+pub use host::{EchoPlugin, PluginEchoService};
+#[cfg(feature = "host")]
+pub use module::EchoPluginModule;
+#[cfg(feature = "host")]
+pub use service_provider::EchoPluginServiceProvider;
+#[cfg(feature = "host")]
+pub use wiring::{init_echo_plugin_module, EchoPluginModuleConfig};
@@ -0,0 +1,56 @@
+//! Echo Plugin Module (Layer 3)
+//!
+//! # Architecture
+//!
+//! Stands in for the module the plugin serves: the plugin was loaded and
+//! its service built at init, so there's nothing to start here but a
+//! check that the plugin still answers.
+
+use std::path::PathBuf;
+use async_trait::async_trait;
+use hsu_common::{ModuleID, Result};
+use hsu_module_api::Module;
+use tracing::{info, instrument};
+
+use crate::service_provider::EchoPluginServiceProvider;
+
+/// Echo plugin module implementation.
+pub struct EchoPluginModule {
+    id: ModuleID,
+    service_provider: EchoPluginServiceProvider,
+    /// Library the plugin was loaded from (for the logs).
+    path: PathBuf,
+}
+
+impl EchoPluginModule {
+    /// Creates the module serving `id` from the plugin at `path`.
+    ///
+    /// Note: This is called by the wiring layer (Layer 5).
+    pub fn new(id: ModuleID, service_provider: EchoPluginServiceProvider, path: PathBuf) -> Self {
+        Self { id, service_provider, path }
+    }
+}
+
+#[async_trait]
+impl Module for EchoPluginModule {
+    fn id(&self) -> &ModuleID {
+        &self.id
+    }
+
+    #[instrument(name = "module", skip_all, fields(module_id = %self.id))]
+    async fn start(&mut self) -> Result<()> {
+        info!("Starting (plugin {})...", self.path.display());
+        if let Some(service) = self.service_provider.service(&self.id.to_string()) {
+            let info = service.get_info().await?;
+            info!("✅ Plugin serves {} v{} (instance {})", info.module_id, info.version, info.instance_id);
+        }
+        Ok(())
+    }
+
+    #[instrument(name = "module", skip_all, fields(module_id = %self.id))]
+    async fn stop(&mut self) -> Result<()> {
+        // The plugin stays loaded: gateways may still hold its handlers
+        info!("Stopping...");
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+//! Service Provider for Echo Plugin Module
+//!
+//! # Architecture
+//!
+//! Like the server's provider, it provides no gateways - only what the
+//! module's handlers are made of: the loaded plugin.
+
+use std::sync::Arc;
+use echo_contract::EchoService;
+
+use crate::host::EchoPlugin;
+
+/// Service provider for Echo plugin module.
+#[derive(Clone)]
+pub struct EchoPluginServiceProvider {
+    plugin: Arc<EchoPlugin>,
+}
+
+impl EchoPluginServiceProvider {
+    /// Creates a provider handing out the services of `plugin`.
+    pub fn new(plugin: Arc<EchoPlugin>) -> Self {
+        Self { plugin }
+    }
+
+    /// The plugin's service for `module_id`, if it registered one.
+    pub fn service(&self, module_id: &str) -> Option<Arc<dyn EchoService>> {
+        self.plugin.service(module_id)
+    }
+}
@@ -0,0 +1,183 @@
+//! Echo Plugin Module Wiring (Layer 5)
+//!
+//! # Architecture
+//!
+//! Registers a module under the plugin's module ID, wired like the
+//! monitor - Direct only, no protocol handlers:
+//!
+//! ```text
+//! init_echo_plugin_module → EchoPlugin::load(path), kept for the factories
+//! create_module           → PluginEchoService, panic guard, events
+//! direct_closure_enabler  → hands the handlers to the echo gateways
+//! ```
+//!
+//! The plugin is loaded at init, so a missing or incompatible library
+//! fails before any module starts.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Once, OnceLock};
+use echo_api::{
+    echo_direct_closure_enabler, CancellationToken, DependencyRegistry, DirectClosureEnablerFn, EchoEventBus,
+    EventEmittingEchoService, ModuleDependencies, PanicGuardEchoService, PanicGuardModule, PanicPolicy,
+    StartupCoordinator,
+};
+use echo_contract::{echo_module_id, EchoService, EchoServiceGateways, EchoServiceHandlers, EchoServiceId};
+use hsu_common::{Error, ModuleID, Result};
+use hsu_module_api::{
+    DirectClosureEnablerOptions, Module, ServiceConnector, ServiceProviderHandle,
+    new_module_descriptor, register_module,
+};
+use tracing::{debug, info};
+
+use crate::host::EchoPlugin;
+use crate::module::EchoPluginModule;
+use crate::service_provider::EchoPluginServiceProvider;
+
+/// Configuration for Echo plugin module.
+pub struct EchoPluginModuleConfig {
+    /// Module the plugin serves (it must register handlers for it).
+    pub module_id: ModuleID,
+    /// Plugin library (`libecho_server_plugin.so`, `.dylib` or `.dll`).
+    pub path: PathBuf,
+    /// What to do when the plugin's service or the module panics.
+    pub panic_policy: PanicPolicy,
+}
+
+impl Default for EchoPluginModuleConfig {
+    fn default() -> Self {
+        Self {
+            module_id: echo_module_id(),
+            path: PathBuf::new(),
+            panic_policy: PanicPolicy::default(),
+        }
+    }
+}
+
+/// Module configuration captured at init time, with the loaded plugin.
+///
+/// The framework factories are plain function pointers, so they can't
+/// capture the config - they read it from here instead.
+static MODULE_CONFIG: OnceLock<(EchoPluginModuleConfig, Arc<EchoPlugin>)> = OnceLock::new();
+
+/// Factory function for creating the service provider.
+///
+/// Provides no gateways: the module serves the plugin's handlers.
+fn create_service_provider(
+    _service_connector: Arc<dyn ServiceConnector>,
+) -> ServiceProviderHandle {
+    debug!("[EchoPluginModule] Creating service provider");
+
+    let (_, plugin) = MODULE_CONFIG.get().expect("plugin loaded at init");
+    ServiceProviderHandle {
+        service_provider: Box::new(EchoPluginServiceProvider::new(plugin.clone())),
+        service_gateways_map: HashMap::new(),
+    }
+}
+
+/// Factory function for creating module.
+fn create_module(service_provider: EchoPluginServiceProvider) -> (Box<dyn Module>, EchoServiceHandlers) {
+    debug!("[EchoPluginModule] Creating module");
+
+    let (config, _) = MODULE_CONFIG.get().expect("plugin loaded at init");
+    let module_id = config.module_id.to_string();
+    let service = service_provider.service(&module_id).expect("plugin module checked at init");
+
+    // Panics in the plugin are caught there; this guards the host side
+    let service: Arc<dyn EchoService> = Arc::new(PanicGuardEchoService::new(service, module_id, config.panic_policy));
+    let events = Arc::new(EchoEventBus::default());
+    let service: Arc<dyn EchoService> = Arc::new(EventEmittingEchoService::new(service, events.clone()));
+    let handlers = EchoServiceHandlers::new(service).with_events(events);
+
+    let module = EchoPluginModule::new(config.module_id.clone(), service_provider, config.path.clone());
+    let module = PanicGuardModule::new(Box::new(module), config.panic_policy);
+    // start() only asks the loaded plugin for its info: nothing to cancel
+    (Box::new(StartupCoordinator::global().coordinate(Box::new(module), CancellationToken::new())), handlers)
+}
+
+/// Function for enabling direct closure.
+fn direct_closure_enabler(
+    options: DirectClosureEnablerOptions<Arc<dyn EchoServiceGateways>, EchoServiceHandlers>,
+) {
+    echo_direct_closure_enabler(options);
+}
+
+/// What the module declares: every echo service, like the server it
+/// stands in for.
+fn module_dependencies() -> ModuleDependencies {
+    EchoServiceId::ALL
+        .into_iter()
+        .fold(ModuleDependencies::default(), |dependencies, service| dependencies.provides(service.as_str()))
+}
+
+/// The descriptor's direct closure enabler (the wiring test reads it too).
+const DIRECT_CLOSURE_ENABLER: Option<DirectClosureEnablerFn<Arc<dyn EchoServiceGateways>, EchoServiceHandlers>> =
+    Some(direct_closure_enabler);
+
+static INIT: Once = Once::new();
+
+/// Loads the plugin at `config.path` and registers the module serving it.
+///
+/// Fails if the plugin can't be loaded or registers no handlers for
+/// `config.module_id`.
+pub fn init_echo_plugin_module(config: EchoPluginModuleConfig) -> Result<()> {
+    if MODULE_CONFIG.get().is_some() {
+        return Ok(());
+    }
+    let plugin = EchoPlugin::load(&config.path)?;
+    if plugin.service(&config.module_id.to_string()).is_none() {
+        return Err(Error::Protocol(format!(
+            "Plugin {} serves {:?}, not {}",
+            config.path.display(),
+            plugin.module_ids(),
+            config.module_id
+        )));
+    }
+
+    INIT.call_once(|| {
+        info!("[EchoPluginModule] Initializing with config: module_id={}, path={}",
+            config.module_id, config.path.display());
+
+        let descriptor = new_module_descriptor::<
+            EchoPluginServiceProvider,
+            Arc<dyn EchoServiceGateways>,  // Gateway type for clients accessing the plugin
+            EchoServiceHandlers,            // Handler type the plugin provides
+        >(
+            create_service_provider,
+            create_module,
+            None,                    // Direct only: no protocol handlers
+            DIRECT_CLOSURE_ENABLER,
+        );
+
+        register_module(config.module_id.clone(), descriptor);
+        DependencyRegistry::global().declare(&config.module_id, module_dependencies());
+        let _ = MODULE_CONFIG.set((config, Arc::new(plugin)));
+
+        info!("[EchoPluginModule] ✅ Module registered successfully");
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_api::{echo_gateway_service_ids, WiringDescriptor};
+    use hsu_common::Protocol;
+
+    #[test]
+    fn test_wiring_is_consistent() {
+        let config = EchoPluginModuleConfig::default();
+
+        // No registrar: the plugin's handlers (loaded at init, so not
+        // built here) only reach consumers through the descriptor's
+        // enabler. Its gateways are the echo gateways, whose remote
+        // protocols go to whatever else serves "echo" - only Direct
+        // reaches the plugin
+        WiringDescriptor::new(config.module_id.to_string(), module_dependencies())
+            .with_direct_closure(DIRECT_CLOSURE_ENABLER.is_some())
+            .with_gateway_services(echo_gateway_service_ids())
+            .with_gateway_protocols([Protocol::Direct])
+            .assert_consistent();
+    }
+}
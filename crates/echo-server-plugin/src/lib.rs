@@ -0,0 +1,21 @@
+//! Echo Server Plugin (experimental)
+//!
+//! The echo server's domain service as a `cdylib`, loaded at runtime by
+//! `echo-host` (see `echo_plugin` for the ABI):
+//!
+//! ```bash
+//! cargo build --release -p echo-server-plugin
+//! cargo run --release --bin echo-host -- --plugin target/release/libecho_server_plugin.so
+//! ```
+//!
+//! Only the service crosses the boundary; the module around it (limits,
+//! history, protocol servers) isn't part of the plugin.
+
+use std::sync::Arc;
+use echo_contract::{EchoService, ECHO_MODULE_ID};
+use echo_server::EchoServiceImpl;
+
+echo_plugin::export_echo_plugin!(ECHO_MODULE_ID, || {
+    let service = EchoServiceImpl::new().with_info(ECHO_MODULE_ID, vec!["plugin".to_string()]);
+    Ok(Arc::new(service) as Arc<dyn EchoService>)
+});
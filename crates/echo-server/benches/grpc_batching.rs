@@ -0,0 +1,80 @@
+//! Throughput benchmark: batched vs unary echo over gRPC.
+//!
+//! Starts an echo server on a local port, then sends the same number of
+//! small echoes from many concurrent callers - once as unary `Echo`
+//! calls, then through the client-side batcher (`EchoBatch`) with a few
+//! batch sizes. Loopback hides network latency, so real deployments gain
+//! more than this shows; the difference here is per-request overhead.
+//!
+//! Run with:
+//! ```bash
+//! cargo bench -p echo-server --bench grpc_batching
+//! ```
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use echo_api::{grpc_echo_service, BatchingConfig, GatewayOptions};
+use echo_api_grpc::generated::echo_service_server::EchoServiceServer;
+use echo_api_grpc::EchoGrpcHandler;
+use echo_contract::EchoService;
+use echo_server::EchoServiceImpl;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+
+const CALLERS: usize = 256;
+const CALLS_PER_CALLER: usize = 200;
+
+async fn start_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let handler = EchoGrpcHandler::new(Arc::new(EchoServiceImpl::new()));
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(EchoServiceServer::new(handler))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    address.to_string()
+}
+
+/// Sends every call through `service`, returning calls per second.
+async fn run(service: Arc<dyn EchoService>) -> f64 {
+    // Warm up the channel
+    service.echo("warm-up".into()).await.unwrap();
+
+    let started = Instant::now();
+    let callers: Vec<_> = (0..CALLERS).map(|caller| {
+        let service = service.clone();
+        tokio::spawn(async move {
+            for call in 0..CALLS_PER_CALLER {
+                let message: Arc<str> = format!("{}-{}", caller, call).into();
+                assert_eq!(service.echo(message.clone()).await.unwrap(), message);
+            }
+        })
+    }).collect();
+    for caller in callers {
+        caller.await.unwrap();
+    }
+    (CALLERS * CALLS_PER_CALLER) as f64 / started.elapsed().as_secs_f64()
+}
+
+fn main() {
+    let runtime = tokio::runtime::Runtime::new().expect("failed to build runtime");
+    runtime.block_on(async {
+        let address = start_server().await;
+
+        println!("gRPC echo - {} callers x {} calls", CALLERS, CALLS_PER_CALLER);
+        let unary = run(grpc_echo_service(&address, &GatewayOptions::default())).await;
+        println!("  {:<24} {:>9.0} calls/s", "unary", unary);
+
+        for max_messages in [16, 64, 256] {
+            let options = GatewayOptions {
+                batching: Some(BatchingConfig { max_messages, linger: Duration::from_millis(2) }),
+                ..GatewayOptions::default()
+            };
+            let batched = run(grpc_echo_service(&address, &options)).await;
+            println!("  {:<24} {:>9.0} calls/s ({:.1}x)",
+                format!("batched (max {})", max_messages), batched, batched / unary);
+        }
+    });
+}
@@ -0,0 +1,60 @@
+//! Allocation benchmark for message passing on the Direct path.
+//!
+//! Counts heap bytes allocated per `EchoService::echo` call for growing
+//! message sizes. With `Arc<str>` messages the cost is constant (the boxed
+//! async-trait future), independent of message size - the message itself
+//! is never copied.
+//!
+//! Run with:
+//! ```bash
+//! cargo bench -p echo-server --bench message_passing
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use echo_contract::EchoService;
+use echo_server::EchoServiceImpl;
+
+/// Global allocator wrapper that counts allocated bytes.
+struct CountingAllocator;
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: usize = 1_000;
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build runtime");
+    let service: Arc<dyn EchoService> = Arc::new(EchoServiceImpl::new());
+
+    println!("Direct echo - heap bytes allocated per call");
+    for size in [16, 1024, 64 * 1024, 1024 * 1024] {
+        let message: Arc<str> = "x".repeat(size).into();
+
+        let before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        for _ in 0..ITERATIONS {
+            let response = runtime.block_on(service.echo(message.clone())).unwrap();
+            assert_eq!(response.len(), size);
+        }
+        let after = ALLOCATED_BYTES.load(Ordering::Relaxed);
+
+        println!("  {:>9} byte message: {:>6} bytes/call", size, (after - before) / ITERATIONS);
+    }
+}
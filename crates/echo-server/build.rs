@@ -0,0 +1,19 @@
+//! Embeds the git commit in the binary (`ECHO_GIT_HASH`, see `info.rs`).
+//!
+//! Set `ECHO_GIT_HASH` yourself when building outside a checkout
+//! (source tarball, Docker context without `.git`).
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=ECHO_GIT_HASH");
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/index");
+
+    let hash = std::env::var("ECHO_GIT_HASH").ok().or_else(|| {
+        let output = Command::new("git").args(["rev-parse", "--short=12", "HEAD"]).output().ok()?;
+        let hash = String::from_utf8(output.stdout).ok()?;
+        (output.status.success() && !hash.trim().is_empty()).then(|| hash.trim().to_string())
+    });
+    println!("cargo:rustc-env=ECHO_GIT_HASH={}", hash.as_deref().unwrap_or("unknown"));
+}
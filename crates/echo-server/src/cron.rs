@@ -0,0 +1,209 @@
+//! Five-field cron expressions for scheduled echoes.
+//!
+//! `minute hour day-of-month month day-of-week`, evaluated in UTC with
+//! minute granularity. Each field is `*` or a comma-separated list of
+//! values, ranges (`9-17`) and steps (`*/15`, `0-30/10`); day-of-week
+//! counts from Sunday (`0` or `7`). As in classic cron, a job whose
+//! day-of-month and day-of-week are both restricted runs when *either*
+//! matches.
+//!
+//! ## Rust Learning Note
+//!
+//! Each field is a `u64` bitmask (bit `n` set = value `n` matches), so
+//! matching is a shift and an AND. Calendar fields come from the Unix
+//! day number with Howard Hinnant's `civil_from_days` - no date crate
+//! needed for UTC.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use hsu_common::{Error, Result};
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+/// Give up looking for a match this far ahead (e.g. `0 0 30 2 *`).
+const SEARCH_LIMIT: Duration = Duration::from_secs(5 * 366 * DAY);
+
+/// A parsed cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether day-of-month / day-of-week were `*`.
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronExpr {
+    /// Parses a five-field expression.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(invalid(expression, "expected five fields"));
+        };
+        // Sunday is both 0 and 7
+        let mut dow = parse_field(expression, days_of_week, 0, 7)?;
+        if dow & (1 << 7) != 0 {
+            dow |= 1;
+        }
+        Ok(Self {
+            minutes: parse_field(expression, minutes, 0, 59)?,
+            hours: parse_field(expression, hours, 0, 23)?,
+            days_of_month: parse_field(expression, days_of_month, 1, 31)?,
+            months: parse_field(expression, months, 1, 12)?,
+            days_of_week: dow,
+            any_day_of_month: days_of_month == "*",
+            any_day_of_week: days_of_week == "*",
+        })
+    }
+
+    /// The first matching minute strictly after `after`, if any within five years.
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let start = after.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let limit = start + SEARCH_LIMIT.as_secs();
+        let mut t = (start / MINUTE + 1) * MINUTE;
+        while t <= limit {
+            let days = t / DAY;
+            let (_, month, day) = civil_from_days(days as i64);
+            // 1970-01-01 was a Thursday
+            let weekday = (days + 4) % 7;
+            if !self.day_matches(month, day, weekday) {
+                t = (days + 1) * DAY;
+                continue;
+            }
+            let hour = t % DAY / HOUR;
+            if !bit(self.hours, hour) {
+                t = (t / HOUR + 1) * HOUR;
+                continue;
+            }
+            let minute = t % HOUR / MINUTE;
+            if !bit(self.minutes, minute) {
+                t += MINUTE;
+                continue;
+            }
+            return Some(UNIX_EPOCH + Duration::from_secs(t));
+        }
+        None
+    }
+
+    fn day_matches(&self, month: u64, day: u64, weekday: u64) -> bool {
+        if !bit(self.months, month) {
+            return false;
+        }
+        let dom = bit(self.days_of_month, day);
+        let dow = bit(self.days_of_week, weekday);
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => dom || dow,
+            _ => dom && dow,
+        }
+    }
+}
+
+fn bit(mask: u64, value: u64) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn invalid(expression: &str, reason: &str) -> Error {
+    Error::Validation {
+        message: format!("Invalid cron expression '{}': {}", expression, reason),
+    }
+}
+
+/// Parses one field into a bitmask of the values in `min..=max`.
+fn parse_field(expression: &str, field: &str, min: u64, max: u64) -> Result<u64> {
+    let number = |s: &str| -> Result<u64> {
+        match s.parse::<u64>() {
+            Ok(n) if (min..=max).contains(&n) => Ok(n),
+            _ => Err(invalid(expression, &format!("'{}' is not in {}-{}", s, min, max))),
+        }
+    };
+    let mut mask = 0;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => match step.parse::<u64>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(invalid(expression, &format!("bad step in '{}'", item))),
+            },
+            None => (item, 1),
+        };
+        let (first, last) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((first, last)) => (number(first)?, number(last)?),
+                // `5/15` means from 5 to the end
+                None if step > 1 => (number(range)?, max),
+                None => {
+                    let value = number(range)?;
+                    (value, value)
+                }
+            },
+        };
+        if first > last {
+            return Err(invalid(expression, &format!("empty range '{}'", range)));
+        }
+        for value in (first..=last).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+/// Converts days since 1970-01-01 into `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, u64, u64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u64;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u64;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-02-28 23:58:30 UTC, a Wednesday.
+    fn at(offset_secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_709_164_710 + offset_secs)
+    }
+
+    fn next(expression: &str) -> u64 {
+        let next = CronExpr::parse(expression).unwrap().next_after(at(0)).unwrap();
+        next.duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_next_after() {
+        // Next minute
+        assert_eq!(next("* * * * *"), 1_709_164_740);
+        // Leap day midnight
+        assert_eq!(next("0 0 29 2 *"), 1_709_164_800);
+        // Every 15 minutes from 9 to 17: 2024-02-29 09:00
+        assert_eq!(next("*/15 9-17 * * *"), 1_709_164_800 + 9 * HOUR);
+        // Next Saturday (2024-03-02) at 12:30
+        assert_eq!(next("30 12 * * 6"), 1_709_164_800 + 2 * DAY + 12 * HOUR + 30 * MINUTE);
+        // Day-of-month OR day-of-week: the 1st (Friday) comes before a Monday
+        assert_eq!(next("0 0 1 * 1"), 1_709_164_800 + DAY);
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for expression in ["* * * *", "60 * * * *", "* * 0 * *", "*/0 * * * *", "5-1 * * * *", "x * * * *"] {
+            assert!(CronExpr::parse(expression).is_err(), "{:?} parsed", expression);
+        }
+        assert_eq!(CronExpr::parse("0 0 30 2 *").unwrap().next_after(at(0)), None);
+    }
+}
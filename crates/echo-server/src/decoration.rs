@@ -0,0 +1,243 @@
+//! Response Decoration (Layer 3)
+//!
+//! # Architecture
+//!
+//! Tags every response with where it came from, so clients behind a load
+//! balancer (and demos) can tell instances apart:
+//!
+//! ```text
+//! EchoGrpcHandler / Direct gateway
+//!     ↓
+//! DecoratedEchoService   ← "[a1b2] " + reply + " (v0.1.0)"
+//!     ↓                     attach_response_metadata("instance-id", ..)
+//!     ↓                     attach_response_metadata("request-id", ids.next_id())
+//! EchoServiceImpl
+//! ```
+//!
+//! - **Prefix/suffix** wrap the text replies (`echo`, `echo_reliable`,
+//!   `echo_with_session`). Binary payloads and file digests are left
+//!   alone - a banner would corrupt them.
+//! - **Metadata** is attached to every response: gRPC sends it as
+//!   `x-echo-meta-*` headers (JSON transcoding too), and
+//!   `echo_with_info` returns it in `CallInfo::server_metadata`.
+//! - **Request IDs**, if enabled, are fresh per response, from the
+//!   module's [`IdGenerator`] (see [`crate::ids`]).
+//!
+//! Direct callers get the metadata because the decorator runs in their
+//! task - unless Direct isolation moves the service to its own pool.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::Result;
+use echo_contract::{
+    attach_response_metadata, ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat,
+    HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho, ServerInfo, SessionEcho,
+};
+
+use crate::ids::{IdGenerator, UuidV4Ids};
+use crate::info::{instance_id, VERSION};
+
+/// Response metadata key of the per-response ID.
+pub const REQUEST_ID_METADATA_KEY: &str = "request-id";
+
+/// What to attach to every response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseDecoration {
+    /// Prepended to text replies.
+    pub prefix: String,
+    /// Appended to text replies.
+    pub suffix: String,
+    /// Sent with every response (gRPC metadata, `CallInfo::server_metadata`).
+    pub metadata: BTreeMap<String, String>,
+    /// Also send a new [`REQUEST_ID_METADATA_KEY`] with every response.
+    pub request_ids: bool,
+}
+
+impl ResponseDecoration {
+    /// Metadata naming this instance: `instance-id` and `server-version`.
+    pub fn instance() -> Self {
+        Self::default()
+            .with_metadata("instance-id", instance_id())
+            .with_metadata("server-version", VERSION)
+    }
+
+    /// Prepends `prefix` to text replies.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Appends `suffix` to text replies.
+    pub fn with_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// Attaches `key: value` to every response.
+    ///
+    /// Keys become gRPC header names, so keep them lowercase ASCII.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Attaches a new `request-id` to every response.
+    pub fn with_request_ids(mut self) -> Self {
+        self.request_ids = true;
+        self
+    }
+
+    fn decorate(&self, message: Arc<str>) -> Arc<str> {
+        if self.prefix.is_empty() && self.suffix.is_empty() {
+            return message;
+        }
+        format!("{}{}{}", self.prefix, message, self.suffix).into()
+    }
+
+    fn attach(&self) {
+        for (key, value) in &self.metadata {
+            attach_response_metadata(key.as_str(), value.as_str());
+        }
+    }
+}
+
+/// Decorates the responses of the wrapped service (see the module docs).
+pub struct DecoratedEchoService {
+    inner: Arc<dyn EchoService>,
+    decoration: ResponseDecoration,
+    ids: Arc<dyn IdGenerator>,
+}
+
+impl DecoratedEchoService {
+    /// Wraps `inner`.
+    pub fn new(inner: Arc<dyn EchoService>, decoration: ResponseDecoration) -> Self {
+        Self { inner, decoration, ids: Arc::new(UuidV4Ids) }
+    }
+
+    /// Generates request IDs with `ids` instead of random UUIDs.
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    fn attach(&self) {
+        self.decoration.attach();
+        if self.decoration.request_ids {
+            attach_response_metadata(REQUEST_ID_METADATA_KEY, self.ids.next_id());
+        }
+    }
+}
+
+#[async_trait]
+impl EchoService for DecoratedEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let reply = self.inner.echo(message).await?;
+        self.attach();
+        Ok(self.decoration.decorate(reply))
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let payload = self.inner.echo_bytes(payload).await?;
+        self.attach();
+        Ok(payload)
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let mut ack = self.inner.echo_reliable(message, idempotency_key).await?;
+        self.attach();
+        ack.message = self.decoration.decorate(ack.message);
+        Ok(ack)
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let digest = self.inner.echo_file(chunks).await?;
+        self.attach();
+        Ok(digest)
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let mut echo = self.inner.echo_with_session(session_id, message).await?;
+        self.attach();
+        echo.message = self.decoration.decorate(echo.message);
+        Ok(echo)
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        let info = self.inner.get_info().await?;
+        self.attach();
+        Ok(info)
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        let scheduled = self.inner.schedule_echo(message, schedule).await?;
+        self.attach();
+        Ok(scheduled)
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        let cancelled = self.inner.cancel_scheduled_echo(job_id).await?;
+        self.attach();
+        Ok(cancelled)
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        let page = self.inner.get_history(query).await?;
+        self.attach();
+        Ok(page)
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        let entries = self.inner.stream_history(query).await?;
+        self.attach();
+        Ok(entries)
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        let chunks = self.inner.export_history(format, query).await?;
+        self.attach();
+        Ok(chunks)
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        let report = self.inner.import_history(format, data).await?;
+        self.attach();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::collect_response_metadata;
+    use crate::ids::SequentialIds;
+    use crate::service::EchoServiceImpl;
+
+    #[tokio::test]
+    async fn test_decorates_text_replies_and_attaches_metadata() {
+        let decoration = ResponseDecoration::instance().with_prefix("[a] ").with_suffix("!");
+        let service = DecoratedEchoService::new(Arc::new(EchoServiceImpl::new()), decoration);
+
+        let (reply, metadata) = collect_response_metadata(service.echo("hello".into())).await;
+        assert_eq!(&*reply.unwrap(), "[a] hello!");
+        assert_eq!(metadata["instance-id"], instance_id());
+        assert_eq!(metadata["server-version"], VERSION);
+
+        // Binary payloads pass through untouched
+        let payload = service.echo_bytes(Bytes::from_static(b"\x00\x01")).await.unwrap();
+        assert_eq!(&payload[..], b"\x00\x01");
+    }
+
+    #[tokio::test]
+    async fn test_request_ids() {
+        let decoration = ResponseDecoration::default().with_request_ids();
+        let service = DecoratedEchoService::new(Arc::new(EchoServiceImpl::new()), decoration)
+            .with_id_generator(Arc::new(SequentialIds::new("req")));
+
+        for expected in ["req-1", "req-2"] {
+            let (_, metadata) = collect_response_metadata(service.echo("hello".into())).await;
+            assert_eq!(metadata[REQUEST_ID_METADATA_KEY], expected);
+        }
+    }
+}
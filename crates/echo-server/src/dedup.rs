@@ -0,0 +1,191 @@
+//! Idempotency-key deduplication window.
+//!
+//! # Rust Learning Note
+//!
+//! At-least-once delivery means the client may send the **same** request
+//! several times (e.g. the response was lost, so it retried). The server
+//! must recognize those retries and answer them without re-processing.
+//!
+//! ```text
+//! Client                      Server
+//!   ├─ echo_reliable(k1) ───→  process, remember k1 → response
+//!   │        ✗ response lost
+//!   ├─ echo_reliable(k1) ───→  k1 seen! return stored response
+//!   ←──────── ack (duplicate) ─┘
+//! ```
+//!
+//! Keys are only remembered for a bounded **window** (by count and age),
+//! so memory stays constant no matter how long the server runs.
+//!
+//! A retry can arrive while the first attempt is still running. Both must
+//! not run the echo, so a key is claimed **before** processing (see
+//! [`DedupWindow::claim`]); the retry waits on the claim's slot instead.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// Configuration for the deduplication window.
+#[derive(Debug, Clone)]
+pub struct DedupConfig {
+    /// Maximum number of remembered keys.
+    pub capacity: usize,
+    /// How long a key is remembered.
+    pub ttl: Duration,
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            ttl: Duration::from_secs(300),
+        }
+    }
+}
+
+/// The response of one idempotency key, filled in by whichever call
+/// processes it first; the others wait for it.
+pub type DedupSlot = Arc<OnceCell<Arc<str>>>;
+
+/// Bounded store of recently processed idempotency keys and their responses.
+pub struct DedupWindow {
+    config: DedupConfig,
+    entries: HashMap<String, DedupSlot>,
+    // Insertion order, used for both age- and size-based eviction
+    order: VecDeque<(Instant, String)>,
+}
+
+impl DedupWindow {
+    /// Creates an empty window.
+    pub fn new(config: DedupConfig) -> Self {
+        Self {
+            config,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Returns the stored response for `key`, if it is still in the window.
+    ///
+    /// A key still being processed has no response yet.
+    pub fn get(&mut self, key: &str) -> Option<Arc<str>> {
+        self.evict_expired(Instant::now());
+        self.entries.get(key).and_then(|slot| slot.get().cloned())
+    }
+
+    /// Remembers the response for `key`.
+    ///
+    /// The first response recorded for a key wins.
+    pub fn insert(&mut self, key: String, response: Arc<str>) {
+        let _ = self.claim(key).set(response);
+    }
+
+    /// Returns the slot of `key`, adding an empty one if the key is new.
+    ///
+    /// Check and insert happen in one call, so under one lock: concurrent
+    /// calls with the same key get the same slot, and only the first to
+    /// fill it (`OnceCell::get_or_try_init`) runs the echo.
+    pub fn claim(&mut self, key: String) -> DedupSlot {
+        let now = Instant::now();
+        self.evict_expired(now);
+
+        if let Some(slot) = self.entries.get(&key) {
+            return slot.clone();
+        }
+
+        while self.entries.len() >= self.config.capacity {
+            match self.order.pop_front() {
+                Some((_, oldest)) => {
+                    self.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+
+        let slot = DedupSlot::default();
+        self.order.push_back((now, key.clone()));
+        self.entries.insert(key, slot.clone());
+        slot
+    }
+
+    /// Returns the number of remembered keys.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no keys are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn evict_expired(&mut self, now: Instant) {
+        while let Some((inserted, _)) = self.order.front() {
+            if now.duration_since(*inserted) < self.config.ttl {
+                break;
+            }
+            if let Some((_, key)) = self.order.pop_front() {
+                self.entries.remove(&key);
+            }
+        }
+    }
+}
+
+impl Default for DedupWindow {
+    fn default() -> Self {
+        Self::new(DedupConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remembers_first_response() {
+        let mut window = DedupWindow::default();
+        window.insert("k1".to_string(), "first".into());
+        window.insert("k1".to_string(), "second".into());
+
+        assert_eq!(window.get("k1"), Some("first".into()));
+        assert_eq!(window.len(), 1);
+    }
+
+    #[test]
+    fn test_evicts_oldest_when_full() {
+        let mut window = DedupWindow::new(DedupConfig {
+            capacity: 2,
+            ttl: Duration::from_secs(60),
+        });
+        window.insert("k1".to_string(), "a".into());
+        window.insert("k2".to_string(), "b".into());
+        window.insert("k3".to_string(), "c".into());
+
+        assert_eq!(window.get("k1"), None);
+        assert_eq!(window.get("k3"), Some("c".into()));
+    }
+
+    #[test]
+    fn test_evicts_expired_keys() {
+        let mut window = DedupWindow::new(DedupConfig {
+            capacity: 16,
+            ttl: Duration::ZERO,
+        });
+        window.insert("k1".to_string(), "a".into());
+
+        assert_eq!(window.get("k1"), None);
+        assert!(window.is_empty());
+    }
+
+    #[test]
+    fn test_claim_shares_the_slot() {
+        let mut window = DedupWindow::default();
+        let first = window.claim("k1".to_string());
+        let second = window.claim("k1".to_string());
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(window.get("k1"), None);
+        first.set("a".into()).unwrap();
+        assert_eq!(window.get("k1"), Some("a".into()));
+    }
+}
@@ -0,0 +1,548 @@
+//! Echo History with a Transactional Outbox (Layer 3)
+//!
+//! # Architecture
+//!
+//! Every processed call is written to the history. Its event for
+//! `EchoEvents` subscribers is not published directly: it is committed to
+//! the store's outbox **in the same write** as the history entry, and the
+//! [`OutboxRelay`](crate::outbox::OutboxRelay) publishes it from there:
+//!
+//! ```text
+//! gRPC handler / Direct caller
+//!     ↓ echo()
+//! HistoryEchoService ──append(entry, event)──→ HistoryStore
+//!     ↓                                          ├── history  [entry 41, entry 42]
+//! EchoServiceImpl                                └── outbox   [event 42]
+//!                                                        ↓ pending_events()
+//!                                       OutboxRelay ──publish──→ EchoEventBus
+//!                                                   ──mark_published(42)
+//! ```
+//!
+//! Publishing straight from the call (`EventEmittingEchoService`) can
+//! announce a call the history never recorded (crash after publishing)
+//! or record one nobody hears about (crash before). With the outbox,
+//! every recorded call is published at least once - after a crash, the
+//! relay picks up whatever was still pending.
+//!
+//! Stores are pluggable through [`HistoryStore`]; the default is the
+//! in-process [`InMemoryHistoryStore`], and the `sqlite` feature adds a
+//! persistent `SqliteHistoryStore`. Old entries are pruned by the
+//! retention task (see [`crate::retention`]); exported files are read
+//! back by [`crate::history_import`].
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! type HistoryStore interface {
+//!     Migrate() error
+//!     Append(entry HistoryEntry, event EchoEvent) (uint64, error)
+//!     Query(query HistoryQuery) (HistoryPage, error)
+//!     PendingEvents(limit int) ([]OutboxEvent, error)
+//!     MarkPublished(ids []uint64) error
+//!     Prune(before *time.Time, keepNewest *int) (uint64, error)
+//!     Compact() error
+//!     Import(entries []HistoryEntry) (HistoryImportReport, error)
+//! }
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hsu_common::Result;
+use echo_contract::{
+    encode_history, history_cursor, storage_error, stream_pages, ByteStream, EchoAck, EchoEvent, EchoMethod, EchoSchedule, EchoService,
+    FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage, HistoryQuery, HistoryStream, ScheduledEcho,
+    ServerInfo, SessionEcho,
+};
+use tracing::warn;
+
+use crate::ids::{IdGenerator, UuidV4Ids};
+use crate::outbox::{OutboxEvent, OutboxRelay};
+
+pub use echo_contract::HistoryEntry;
+
+/// Storage for the history and its event outbox.
+///
+/// # Rust Learning Note
+///
+/// `append` takes the entry *and* its event so an implementation can
+/// commit both in one transaction - a single lock here, one SQL
+/// transaction in a database. That is the whole point of the pattern.
+pub trait HistoryStore: Send + Sync {
+    /// Creates or upgrades the storage schema; called on module start.
+    fn migrate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Appends `entry` and queues `event` in the outbox, atomically.
+    ///
+    /// `entry.id` is ignored; returns the ID the store assigned.
+    fn append(&self, entry: HistoryEntry, event: EchoEvent) -> Result<u64>;
+
+    /// One page of entries matching `query`, newest first.
+    fn query(&self, query: &HistoryQuery) -> Result<HistoryPage>;
+
+    /// Up to `limit` unpublished events, oldest first.
+    fn pending_events(&self, limit: usize) -> Result<Vec<OutboxEvent>>;
+
+    /// Removes published events from the outbox.
+    fn mark_published(&self, ids: &[u64]) -> Result<()>;
+
+    /// Deletes the entries recorded before `before` and all but the
+    /// newest `keep_newest`; returns how many were deleted.
+    ///
+    /// The outbox is left alone: unpublished events outlive their entry.
+    fn prune(&self, before: Option<SystemTime>, keep_newest: Option<usize>) -> Result<u64>;
+
+    /// Gives the space freed by [`prune`](Self::prune) back; nothing to
+    /// do by default.
+    fn compact(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Adds the imported `entries` (oldest first) the store doesn't have
+    /// yet, without queuing events; `entry.id` is ignored.
+    ///
+    /// An entry is a duplicate if `entries` repeats it (same ID, record
+    /// ID, method, message, size, outcome and millisecond), or if the store
+    /// already holds as many entries with its record ID, method, message,
+    /// size, outcome and millisecond as `entries` has up to it. Stored
+    /// entries were renumbered on import, so only IDs within `entries` are
+    /// compared; record IDs are kept, so they are compared throughout.
+    fn import(&self, entries: Vec<HistoryEntry>) -> Result<HistoryImportReport>;
+}
+
+#[derive(Default)]
+struct Tables {
+    entries: VecDeque<HistoryEntry>,
+    outbox: BTreeMap<u64, EchoEvent>,
+    next_id: u64,
+}
+
+/// In-process history, keeping the latest `max_entries` entries.
+pub struct InMemoryHistoryStore {
+    tables: Mutex<Tables>,
+    max_entries: usize,
+}
+
+impl InMemoryHistoryStore {
+    /// Creates a store keeping the latest 10 000 entries.
+    pub fn new() -> Self {
+        Self::with_max_entries(10_000)
+    }
+
+    /// Creates a store keeping the latest `max_entries` entries.
+    ///
+    /// Unpublished events stay in the outbox even when their entry is dropped.
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        Self {
+            tables: Mutex::new(Tables { next_id: 1, ..Default::default() }),
+            max_entries: max_entries.max(1),
+        }
+    }
+}
+
+impl Default for InMemoryHistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HistoryStore for InMemoryHistoryStore {
+    fn append(&self, mut entry: HistoryEntry, event: EchoEvent) -> Result<u64> {
+        // One lock for both tables: no reader sees one without the other
+        let mut tables = self.tables.lock().unwrap();
+        let id = tables.next_id;
+        tables.next_id += 1;
+        entry.id = id;
+        tables.entries.push_back(entry);
+        tables.outbox.insert(id, event);
+        while tables.entries.len() > self.max_entries {
+            tables.entries.pop_front();
+        }
+        Ok(id)
+    }
+
+    fn query(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+        let before = query.cursor_id()?.unwrap_or(u64::MAX);
+        let tables = self.tables.lock().unwrap();
+        let matching = tables.entries.iter()
+            .rev()
+            .filter(|entry| entry.id < before && query.matches(entry));
+        Ok(page(matching.cloned(), query.page_size()))
+    }
+
+    fn pending_events(&self, limit: usize) -> Result<Vec<OutboxEvent>> {
+        let tables = self.tables.lock().unwrap();
+        Ok(tables.outbox.iter()
+            .take(limit)
+            .map(|(id, event)| OutboxEvent { id: *id, event: event.clone() })
+            .collect())
+    }
+
+    fn mark_published(&self, ids: &[u64]) -> Result<()> {
+        let mut tables = self.tables.lock().unwrap();
+        for id in ids {
+            tables.outbox.remove(id);
+        }
+        Ok(())
+    }
+
+    fn prune(&self, before: Option<SystemTime>, keep_newest: Option<usize>) -> Result<u64> {
+        let mut tables = self.tables.lock().unwrap();
+        let count = tables.entries.len();
+        if let Some(before) = before {
+            tables.entries.retain(|entry| entry.at >= before);
+        }
+        if let Some(keep_newest) = keep_newest {
+            let excess = tables.entries.len().saturating_sub(keep_newest);
+            tables.entries.drain(..excess);
+        }
+        Ok((count - tables.entries.len()) as u64)
+    }
+
+    fn import(&self, entries: Vec<HistoryEntry>) -> Result<HistoryImportReport> {
+        let mut tables = self.tables.lock().unwrap();
+        let mut stored: HashMap<DuplicateKey, usize> = HashMap::new();
+        for entry in &tables.entries {
+            *stored.entry(duplicate_key(entry)).or_default() += 1;
+        }
+        let mut seen = HashSet::new();
+        let mut report = HistoryImportReport::default();
+        for mut entry in entries {
+            let key = duplicate_key(&entry);
+            if !seen.insert((entry.id, key.clone())) {
+                report.duplicates += 1;
+                continue;
+            }
+            if let Some(count) = stored.get_mut(&key).filter(|count| **count > 0) {
+                *count -= 1;
+                report.duplicates += 1;
+                continue;
+            }
+            entry.id = tables.next_id;
+            tables.next_id += 1;
+            tables.entries.push_back(entry);
+            report.imported += 1;
+        }
+        while tables.entries.len() > self.max_entries {
+            tables.entries.pop_front();
+        }
+        Ok(report)
+    }
+}
+
+/// What makes two entries the same call on import, besides their ID.
+/// Files keep milliseconds, so that is the precision compared.
+pub(crate) type DuplicateKey = (String, EchoMethod, String, u64, bool, u128);
+
+pub(crate) fn duplicate_key(entry: &HistoryEntry) -> DuplicateKey {
+    let at_ms = entry.at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+    (entry.record_id.clone(), entry.method, entry.message.clone(), entry.request_bytes, entry.success, at_ms)
+}
+
+/// Takes a page of `page_size` from `entries` (newest first), with a
+/// cursor if more follow.
+pub(crate) fn page(entries: impl Iterator<Item = HistoryEntry>, page_size: usize) -> HistoryPage {
+    // One extra tells whether there is a next page
+    let mut entries: Vec<HistoryEntry> = entries.take(page_size + 1).collect();
+    let next_cursor = (entries.len() > page_size).then(|| {
+        entries.truncate(page_size);
+        history_cursor(entries[page_size - 1].id)
+    });
+    HistoryPage { entries, next_cursor }
+}
+
+/// Runs `call` on `store` on the blocking pool.
+///
+/// Store calls are synchronous and may wait on a lock or the disk (see
+/// `SqliteHistoryStore`); async callers must not run them on a worker.
+pub(crate) async fn run_blocking<T, F>(store: &Arc<dyn HistoryStore>, call: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn HistoryStore) -> Result<T> + Send + 'static,
+{
+    let store = store.clone();
+    tokio::task::spawn_blocking(move || call(&*store))
+        .await
+        .unwrap_or_else(|e| Err(storage_error(format!("History store call failed: {}", e))))
+}
+
+/// Streams every entry of `store` matching `query`, a page at a time.
+///
+/// `query.limit` caps the total (0: all).
+pub fn stream_history(store: Arc<dyn HistoryStore>, query: HistoryQuery) -> HistoryStream {
+    stream_pages(query, move |query| {
+        let store = store.clone();
+        async move { run_blocking(&store, move |store| store.query(&query)).await }
+    })
+}
+
+/// Streams every entry of `store` matching `query` as a file in `format`.
+pub fn export_history(store: Arc<dyn HistoryStore>, format: HistoryExportFormat, query: HistoryQuery) -> ByteStream {
+    encode_history(stream_history(store, query), format)
+}
+
+/// Decorator that writes every call to a [`HistoryStore`].
+///
+/// Takes the place of `EventEmittingEchoService`: events reach the bus
+/// through the store's outbox and the relay.
+pub struct HistoryEchoService {
+    inner: Arc<dyn EchoService>,
+    store: Arc<dyn HistoryStore>,
+    relay: Arc<OutboxRelay>,
+    /// Names the recorded entries
+    ids: Arc<dyn IdGenerator>,
+}
+
+impl HistoryEchoService {
+    /// Wraps `inner`, recording to `store` and waking `relay` on each write.
+    pub fn new(inner: Arc<dyn EchoService>, store: Arc<dyn HistoryStore>, relay: Arc<OutboxRelay>) -> Self {
+        Self { inner, store, relay, ids: Arc::new(UuidV4Ids) }
+    }
+
+    /// Names recorded entries with `ids` instead of random UUIDs.
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    async fn record(&self, method: EchoMethod, message: &str, request_bytes: usize, success: bool) {
+        let at = SystemTime::now();
+        let entry = HistoryEntry {
+            id: 0,
+            record_id: self.ids.next_id(),
+            method,
+            message: message.to_string(),
+            request_bytes: request_bytes as u64,
+            success,
+            at,
+        };
+        let event = EchoEvent {
+            method,
+            request_bytes: request_bytes as u64,
+            success,
+            at,
+        };
+        // Neither entry nor event was stored; the call itself still counts
+        match run_blocking(&self.store, move |store| store.append(entry, event)).await {
+            Ok(_) => self.relay.notify(),
+            Err(e) => warn!("[EchoHistory] Failed to record {} call: {}", method, e),
+        }
+    }
+}
+
+#[async_trait]
+impl EchoService for HistoryEchoService {
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
+        let result = self.inner.echo(message.clone()).await;
+        self.record(EchoMethod::Echo, &message, message.len(), result.is_ok()).await;
+        result
+    }
+
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        let size = payload.len();
+        let result = self.inner.echo_bytes(payload).await;
+        self.record(EchoMethod::EchoBytes, "", size, result.is_ok()).await;
+        result
+    }
+
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        let result = self.inner.echo_reliable(message.clone(), idempotency_key).await;
+        self.record(EchoMethod::EchoReliable, &message, message.len(), result.is_ok()).await;
+        result
+    }
+
+    async fn echo_file(&self, chunks: ByteStream) -> Result<FileDigest> {
+        let result = self.inner.echo_file(chunks).await;
+        let size = result.as_ref().map_or(0, |digest| digest.byte_count as usize);
+        self.record(EchoMethod::EchoFile, "", size, result.is_ok()).await;
+        result
+    }
+
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        let result = self.inner.echo_with_session(session_id, message.clone()).await;
+        self.record(EchoMethod::EchoWithSession, &message, message.len(), result.is_ok()).await;
+        result
+    }
+
+    async fn get_info(&self) -> Result<ServerInfo> {
+        self.inner.get_info().await
+    }
+
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        // Not an echo yet: each run is recorded when it happens
+        self.inner.schedule_echo(message, schedule).await
+    }
+
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        self.inner.cancel_scheduled_echo(job_id).await
+    }
+
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.inner.get_history(query).await
+    }
+
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        self.inner.stream_history(query).await
+    }
+
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        self.inner.export_history(format, query).await
+    }
+
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        // Not recorded: the import is no echo call
+        self.inner.import_history(format, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use echo_api::EchoEventBus;
+    use echo_contract::EchoEvents;
+    use futures::StreamExt;
+    use crate::ids::SequentialIds;
+    use crate::service::EchoServiceImpl;
+
+    #[test]
+    fn test_append_commits_entry_and_event() {
+        let store = InMemoryHistoryStore::with_max_entries(2);
+        let event = |bytes| EchoEvent { method: EchoMethod::Echo, request_bytes: bytes, success: true, at: SystemTime::now() };
+        let entry = |message: &str| HistoryEntry {
+            id: 0,
+            record_id: String::new(),
+            method: EchoMethod::Echo,
+            message: message.to_string(),
+            request_bytes: message.len() as u64,
+            success: true,
+            at: SystemTime::now(),
+        };
+        for message in ["a", "bb", "ccc"] {
+            store.append(entry(message), event(message.len() as u64)).unwrap();
+        }
+
+        // Oldest entry dropped, its event still pending
+        let recent = store.query(&HistoryQuery::new()).unwrap().entries;
+        assert_eq!(recent.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 2]);
+        let pending = store.pending_events(10).unwrap();
+        assert_eq!(pending.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        store.mark_published(&[1, 2]).unwrap();
+        assert_eq!(store.pending_events(10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_prune_by_age_and_count() {
+        let store = InMemoryHistoryStore::new();
+        let start = SystemTime::now();
+        for i in 0..5 {
+            let at = start + Duration::from_secs(i);
+            let entry = HistoryEntry {
+                id: 0,
+                record_id: String::new(),
+                method: EchoMethod::Echo,
+                message: String::new(),
+                request_bytes: 0,
+                success: true,
+                at,
+            };
+            store.append(entry, EchoEvent { method: EchoMethod::Echo, request_bytes: 0, success: true, at }).unwrap();
+        }
+
+        assert_eq!(store.prune(Some(start + Duration::from_secs(1)), None).unwrap(), 1);
+        assert_eq!(store.prune(None, Some(2)).unwrap(), 2);
+        let ids: Vec<u64> = store.query(&HistoryQuery::new()).unwrap().entries.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![5, 4]);
+        assert_eq!(store.prune(None, Some(2)).unwrap(), 0);
+        // Unpublished events are kept
+        assert_eq!(store.pending_events(10).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_import_keeps_distinct_calls_of_the_same_millisecond() {
+        let store = InMemoryHistoryStore::new();
+        let at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        let entry = |id| HistoryEntry {
+            id,
+            record_id: String::new(),
+            method: EchoMethod::Echo,
+            message: "tick".to_string(),
+            request_bytes: 4,
+            success: true,
+            at,
+        };
+        store.append(entry(0), EchoEvent { method: EchoMethod::Echo, request_bytes: 4, success: true, at }).unwrap();
+
+        // One of the three is stored already; ID 2 is repeated
+        let report = store.import(vec![entry(1), entry(2), entry(2), entry(3)]).unwrap();
+        assert_eq!(report, HistoryImportReport { imported: 2, duplicates: 2 });
+        let report = store.import(vec![entry(1), entry(2), entry(3)]).unwrap();
+        assert_eq!(report, HistoryImportReport { imported: 0, duplicates: 3 });
+    }
+
+    #[tokio::test]
+    async fn test_pages_follow_the_cursor() {
+        let store = Arc::new(InMemoryHistoryStore::new());
+        for message in ["a1", "b2", "a3", "b4", "a5"] {
+            let entry = HistoryEntry {
+                id: 0,
+                record_id: String::new(),
+                method: EchoMethod::Echo,
+                message: message.to_string(),
+                request_bytes: 2,
+                success: true,
+                at: SystemTime::now(),
+            };
+            let event = EchoEvent { method: EchoMethod::Echo, request_bytes: 2, success: true, at: entry.at };
+            store.append(entry, event).unwrap();
+        }
+
+        let query = HistoryQuery::new().with_contains("a").with_limit(2);
+        let first = store.query(&query).unwrap();
+        assert_eq!(first.entries.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(), vec!["a5", "a3"]);
+        let cursor = first.next_cursor.unwrap();
+        let second = store.query(&query.clone().with_cursor(cursor)).unwrap();
+        assert_eq!(second.entries.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(), vec!["a1"]);
+        assert_eq!(second.next_cursor, None);
+
+        let streamed: Vec<_> = stream_history(store.clone(), HistoryQuery::new()).collect().await;
+        assert_eq!(streamed.len(), 5);
+        let capped: Vec<_> = stream_history(store.clone(), HistoryQuery::new().with_limit(3)).collect().await;
+        assert_eq!(capped.into_iter().map(|e| e.unwrap().id).collect::<Vec<_>>(), vec![5, 4, 3]);
+
+        let file: Vec<Bytes> = export_history(store, HistoryExportFormat::Ndjson, HistoryQuery::new().with_contains("b"))
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        let lines: Vec<String> = String::from_utf8(file.concat()).unwrap().lines().map(String::from).collect();
+        assert_eq!(lines.len(), 2);
+        let newest: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!((newest["id"].as_u64(), newest["message"].as_str()), (Some(4), Some("b4")));
+    }
+
+    #[tokio::test]
+    async fn test_recorded_calls_reach_subscribers_through_the_relay() {
+        let store = Arc::new(InMemoryHistoryStore::new());
+        let bus = Arc::new(EchoEventBus::default());
+        let relay = Arc::new(OutboxRelay::new(store.clone(), bus.clone()));
+        let service = HistoryEchoService::new(Arc::new(EchoServiceImpl::new()), store.clone(), relay.clone())
+            .with_id_generator(Arc::new(SequentialIds::new("record")));
+        let mut events = bus.subscribe().await.unwrap();
+
+        service.echo("hello".into()).await.unwrap();
+        let entry = store.query(&HistoryQuery::new()).unwrap().entries.remove(0);
+        assert_eq!((entry.message.as_str(), entry.record_id.as_str()), ("hello", "record-1"));
+        assert_eq!(store.pending_events(10).unwrap().len(), 1);
+
+        assert_eq!(relay.relay_pending().unwrap(), 1);
+        let event = tokio::time::timeout(Duration::from_secs(1), events.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(event.method, EchoMethod::Echo);
+        assert_eq!(event.request_bytes, 5);
+        assert!(store.pending_events(10).unwrap().is_empty());
+    }
+}
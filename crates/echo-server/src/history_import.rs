@@ -0,0 +1,370 @@
+//! History Import (Layer 3)
+//!
+//! # Architecture
+//!
+//! Reads a file written by `export_history` back into a [`HistoryStore`],
+//! e.g. to move the history to a fresh instance:
+//!
+//! ```text
+//! old instance ── export_history(csv) ──→ history.csv ── import_history(csv) ──→ new instance
+//!
+//! ByteStream (chunks split records anywhere)
+//!     ↓ HistoryDecoder::push     whole records (a quoted CSV field may span lines)
+//!     ↓ decode_record(format)    HistoryEntry, or "line 12: ..." (Error::Validation)
+//! Vec<HistoryEntry>              sorted oldest first
+//!     ↓ HistoryStore::import     one transaction, skipping entries the store has
+//! HistoryImportReport { imported, duplicates }
+//! ```
+//!
+//! Every record is validated before anything is stored, so a bad file
+//! leaves the history as it was. That means holding the file's entries
+//! in memory; [`MAX_IMPORT_ENTRIES`], [`MAX_IMPORT_BYTES`] and
+//! [`MAX_IMPORT_RECORD_BYTES`] bound them.
+//!
+//! Imported entries get new IDs, oldest first, after the entries the
+//! store already has. Their events are not published again.
+//!
+//! ## Comparison with Golang
+//!
+//! | Golang                                 | Rust                                 |
+//! |----------------------------------------|--------------------------------------|
+//! | `bufio.Scanner` over the request body  | `HistoryDecoder` over the chunks     |
+//! | `encoding/csv.Reader`                  | `csv_fields` (RFC 4180 quoting)      |
+//! | `json.Unmarshal` into a struct         | `serde_json::Value` + field checks   |
+
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use echo_contract::{storage_error, ByteStream, EchoMethod, HistoryEntry, HistoryExportFormat, HistoryImportReport};
+use futures::StreamExt;
+use hsu_common::{Error, Result};
+use serde_json::Value;
+use tracing::info;
+
+use crate::history::HistoryStore;
+
+/// Most entries a single import may carry.
+pub const MAX_IMPORT_ENTRIES: usize = 1_000_000;
+
+/// Largest file a single import may carry: room for
+/// [`MAX_IMPORT_ENTRIES`] records of a typical size.
+pub const MAX_IMPORT_BYTES: usize = 256 * 1024 * 1024;
+
+/// Longest record (one entry) a file may hold.
+pub const MAX_IMPORT_RECORD_BYTES: usize = 1024 * 1024;
+
+/// CSV header of files exported before entries had record IDs; their
+/// records have no `record_id` column.
+const CSV_HEADER_WITHOUT_RECORD_ID: &str = "id,method,message,request_bytes,success,at_unix_ms";
+
+/// Adds the entries of the `format` file streamed in `data` to `store`.
+///
+/// Fails with `Error::Validation` naming the line of the first bad
+/// record; nothing is stored then.
+pub async fn import_history(
+    store: Arc<dyn HistoryStore>,
+    format: HistoryExportFormat,
+    mut data: ByteStream,
+) -> Result<HistoryImportReport> {
+    let mut decoder = HistoryDecoder::new(format);
+    while let Some(chunk) = data.next().await {
+        decoder.push(&chunk?)?;
+    }
+    let mut entries = decoder.finish()?;
+    // Exports are newest first; the new IDs follow the recording order
+    entries.sort_by_key(|entry| (entry.at, entry.id));
+
+    // The store call is synchronous and one transaction for the whole file
+    let report = tokio::task::spawn_blocking(move || store.import(entries))
+        .await
+        .unwrap_or_else(|e| Err(storage_error(format!("History import failed: {}", e))))?;
+    info!("[HistoryImport] ✅ Imported {} {} entries ({} duplicates skipped)",
+        report.imported, format, report.duplicates);
+    Ok(report)
+}
+
+/// Cuts the chunks of an exported file into records and decodes them.
+struct HistoryDecoder {
+    format: HistoryExportFormat,
+    /// Bytes of the record not complete yet
+    buffer: Vec<u8>,
+    /// How much of `buffer` was already searched for the record's end
+    scanned: usize,
+    in_quotes: bool,
+    /// Line the buffered record starts on
+    line: usize,
+    /// Bytes received so far
+    received: usize,
+    header_seen: bool,
+    /// A CSV file without the `record_id` column
+    without_record_id: bool,
+    entries: Vec<HistoryEntry>,
+}
+
+impl HistoryDecoder {
+    fn new(format: HistoryExportFormat) -> Self {
+        Self {
+            format,
+            buffer: Vec::new(),
+            scanned: 0,
+            in_quotes: false,
+            line: 1,
+            received: 0,
+            header_seen: false,
+            without_record_id: false,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Decodes the records `chunk` completes.
+    fn push(&mut self, chunk: &[u8]) -> Result<()> {
+        self.received += chunk.len();
+        if self.received > MAX_IMPORT_BYTES {
+            return Err(invalid(self.line, format!("file larger than {} bytes", MAX_IMPORT_BYTES)));
+        }
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.extend_from_slice(chunk);
+        let mut start = 0;
+        for end in self.scanned..buffer.len() {
+            match buffer[end] {
+                // A doubled quote toggles twice, so escapes need no special case
+                b'"' if self.format == HistoryExportFormat::Csv => self.in_quotes = !self.in_quotes,
+                b'\n' if !self.in_quotes => {
+                    self.record(&buffer[start..end])?;
+                    start = end + 1;
+                }
+                _ => {}
+            }
+        }
+        buffer.drain(..start);
+        // Checked as it grows: a record without an end never completes
+        if buffer.len() > MAX_IMPORT_RECORD_BYTES {
+            return Err(invalid(self.line, format!("record longer than {} bytes", MAX_IMPORT_RECORD_BYTES)));
+        }
+        self.scanned = buffer.len();
+        self.buffer = buffer;
+        Ok(())
+    }
+
+    /// Decodes a last record without a line break; returns all entries.
+    fn finish(mut self) -> Result<Vec<HistoryEntry>> {
+        if self.in_quotes {
+            return Err(invalid(self.line, "unterminated quoted field"));
+        }
+        let rest = std::mem::take(&mut self.buffer);
+        self.record(&rest)?;
+        Ok(self.entries)
+    }
+
+    fn record(&mut self, bytes: &[u8]) -> Result<()> {
+        let line = self.line;
+        if bytes.len() > MAX_IMPORT_RECORD_BYTES {
+            return Err(invalid(line, format!("record longer than {} bytes", MAX_IMPORT_RECORD_BYTES)));
+        }
+        self.line += 1 + bytes.iter().filter(|b| **b == b'\n').count();
+
+        let text = std::str::from_utf8(bytes).map_err(|_| invalid(line, "not valid UTF-8"))?;
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+        if let Some(header) = self.format.header().filter(|_| !self.header_seen) {
+            self.header_seen = true;
+            let header = header.trim_end();
+            self.without_record_id = text == CSV_HEADER_WITHOUT_RECORD_ID;
+            if text != header && !self.without_record_id {
+                return Err(invalid(line, format!("expected the header '{}'", header)));
+            }
+            return Ok(());
+        }
+        if self.entries.len() == MAX_IMPORT_ENTRIES {
+            return Err(invalid(line, format!("more than {} entries", MAX_IMPORT_ENTRIES)));
+        }
+        let entry = decode_record(self.format, text, self.without_record_id).map_err(|reason| invalid(line, reason))?;
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+/// Parses one record (without its line break) written by
+/// [`HistoryExportFormat::encode`]; older files may lack the record ID.
+fn decode_record(
+    format: HistoryExportFormat,
+    record: &str,
+    without_record_id: bool,
+) -> std::result::Result<HistoryEntry, String> {
+    match format {
+        HistoryExportFormat::Ndjson => {
+            let value: Value = serde_json::from_str(record).map_err(|e| format!("invalid JSON: {}", e))?;
+            let field = |name: &str| value.get(name).ok_or_else(|| format!("missing '{}'", name));
+            let number = |name: &str| field(name)?.as_u64()
+                .ok_or_else(|| format!("'{}' is not a non-negative integer", name));
+            let text = |name: &str| field(name)?.as_str().ok_or_else(|| format!("'{}' is not a string", name));
+            Ok(HistoryEntry {
+                id: number("id")?,
+                record_id: match value.get("record_id") {
+                    Some(_) => text("record_id")?.to_string(),
+                    None => String::new(),
+                },
+                method: method(text("method")?)?,
+                message: text("message")?.to_string(),
+                request_bytes: number("request_bytes")?,
+                success: field("success")?.as_bool().ok_or("'success' is not a boolean")?,
+                at: UNIX_EPOCH + Duration::from_millis(number("at_unix_ms")?),
+            })
+        }
+        HistoryExportFormat::Csv => {
+            let expected = if without_record_id { 6 } else { 7 };
+            let mut fields = csv_fields(record)?;
+            if fields.len() != expected {
+                return Err(format!("expected {} fields, found {}", expected, fields.len()));
+            }
+            fields.resize(7, String::new());
+            let [id, method_name, message, request_bytes, success, at_unix_ms, record_id] = <[String; 7]>::try_from(fields)
+                .map_err(|fields| format!("expected 7 fields, found {}", fields.len()))?;
+            let number = |name: &str, value: &str| value.parse::<u64>()
+                .map_err(|_| format!("'{}' is not a non-negative integer: '{}'", name, value));
+            Ok(HistoryEntry {
+                id: number("id", &id)?,
+                record_id,
+                method: method(&method_name)?,
+                message,
+                request_bytes: number("request_bytes", &request_bytes)?,
+                success: success.parse().map_err(|_| format!("'success' is not true or false: '{}'", success))?,
+                at: UNIX_EPOCH + Duration::from_millis(number("at_unix_ms", &at_unix_ms)?),
+            })
+        }
+    }
+}
+
+fn method(name: &str) -> std::result::Result<EchoMethod, String> {
+    EchoMethod::from_name(name).ok_or_else(|| format!("unknown method '{}'", name))
+}
+
+/// Splits a CSV record into its fields, undoing RFC 4180 quoting.
+fn csv_fields(record: &str) -> std::result::Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => fields.push(std::mem::take(&mut field)),
+            (_, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+fn invalid(line: usize, reason: impl std::fmt::Display) -> Error {
+    Error::Validation {
+        message: format!("Invalid history file, line {}: {}", line, reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use echo_contract::HistoryQuery;
+    use futures::stream;
+    use crate::history::{export_history, InMemoryHistoryStore};
+
+    fn entry(message: &str, at_ms: u64) -> HistoryEntry {
+        HistoryEntry {
+            id: 0,
+            record_id: format!("r{}", at_ms),
+            method: EchoMethod::Echo,
+            message: message.to_string(),
+            request_bytes: message.len() as u64,
+            success: true,
+            at: UNIX_EPOCH + Duration::from_millis(at_ms),
+        }
+    }
+
+    /// `file` as a stream of `size`-byte chunks.
+    fn chunked(file: &[u8], size: usize) -> ByteStream {
+        let chunks: Vec<Result<Bytes>> = file.chunks(size).map(|c| Ok(Bytes::copy_from_slice(c))).collect();
+        Box::pin(stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import_round_trips() {
+        for format in [HistoryExportFormat::Ndjson, HistoryExportFormat::Csv] {
+            let source = Arc::new(InMemoryHistoryStore::new());
+            source.import(vec![entry("first", 1_000), entry("say \"hi\",\nbye", 2_000), entry("", 3_000)]).unwrap();
+            let file: Vec<Bytes> = export_history(source.clone(), format, HistoryQuery::new())
+                .map(|chunk| chunk.unwrap())
+                .collect()
+                .await;
+
+            // Three-byte chunks split records, quotes and UTF-8 alike
+            let target = Arc::new(InMemoryHistoryStore::new());
+            let report = import_history(target.clone(), format, chunked(&file.concat(), 3)).await.unwrap();
+            assert_eq!(report, HistoryImportReport { imported: 3, duplicates: 0 });
+            let expected = source.query(&HistoryQuery::new()).unwrap();
+            assert_eq!(target.query(&HistoryQuery::new()).unwrap(), expected);
+            assert!(target.pending_events(10).unwrap().is_empty());
+
+            // Importing again changes nothing
+            let report = import_history(target.clone(), format, chunked(&file.concat(), 64)).await.unwrap();
+            assert_eq!(report, HistoryImportReport { imported: 0, duplicates: 3 });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_files_without_record_ids_import() {
+        let store = Arc::new(InMemoryHistoryStore::new());
+        let csv = "id,method,message,request_bytes,success,at_unix_ms\n1,echo,old,3,true,5\n";
+        let ndjson = "{\"id\":2,\"method\":\"echo\",\"message\":\"older\",\"request_bytes\":5,\"success\":true,\"at_unix_ms\":6}\n";
+        import_history(store.clone(), HistoryExportFormat::Csv, chunked(csv.as_bytes(), 64)).await.unwrap();
+        import_history(store.clone(), HistoryExportFormat::Ndjson, chunked(ndjson.as_bytes(), 64)).await.unwrap();
+
+        let entries = store.query(&HistoryQuery::new()).unwrap().entries;
+        let imported: Vec<_> = entries.iter().map(|e| (e.message.as_str(), e.record_id.as_str())).collect();
+        assert_eq!(imported, vec![("older", ""), ("old", "")]);
+    }
+
+    #[tokio::test]
+    async fn test_bad_record_rejects_the_whole_file() {
+        let store = Arc::new(InMemoryHistoryStore::new());
+        let file = "{\"id\":1,\"method\":\"echo\",\"message\":\"ok\",\"request_bytes\":2,\"success\":true,\"at_unix_ms\":5}\n\
+                    \n\
+                    {\"id\":2,\"method\":\"shout\",\"message\":\"no\",\"request_bytes\":2,\"success\":true,\"at_unix_ms\":6}\n";
+        let error = import_history(store.clone(), HistoryExportFormat::Ndjson, chunked(file.as_bytes(), 16))
+            .await
+            .unwrap_err();
+        assert!(matches!(&error, Error::Validation { message } if message.contains("line 3: unknown method 'shout'")));
+        assert!(store.query(&HistoryQuery::new()).unwrap().entries.is_empty());
+
+        let headerless = "1,echo,ok,2,true,5\n";
+        let error = import_history(store, HistoryExportFormat::Csv, chunked(headerless.as_bytes(), 64))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("line 1: expected the header"));
+    }
+
+    #[test]
+    fn test_record_without_an_end_is_bounded() {
+        let mut decoder = HistoryDecoder::new(HistoryExportFormat::Ndjson);
+        decoder.push(&vec![b'x'; MAX_IMPORT_RECORD_BYTES]).unwrap();
+        let error = decoder.push(b"x").unwrap_err();
+        assert!(error.to_string().contains("line 1: record longer than"));
+    }
+
+    #[test]
+    fn test_csv_fields() {
+        assert_eq!(csv_fields("1,echo,plain").unwrap(), vec!["1", "echo", "plain"]);
+        assert_eq!(csv_fields("\"a,\"\"b\"\"\",,x").unwrap(), vec!["a,\"b\"", "", "x"]);
+        assert!(csv_fields("\"open").is_err());
+    }
+}
@@ -0,0 +1,256 @@
+//! ID generation strategies.
+//!
+//! # Architecture
+//!
+//! Every ID the server makes up comes from one [`IdGenerator`], handed out
+//! by the service provider:
+//!
+//! ```text
+//! EchoServerModuleConfig::id_generator (UUIDv4 if None)
+//!     ↓
+//! EchoServerServiceProvider::ids()
+//!     ├─→ DecoratedEchoService   request-id response metadata
+//!     ├─→ EchoServiceImpl        session ID for echo_with_session("")
+//!     ├─→ EchoScheduler          scheduled job IDs
+//!     └─→ HistoryEchoService     history record IDs
+//! ```
+//!
+//! | Strategy       | Example                                  | Sorts by creation time |
+//! |----------------|------------------------------------------|------------------------|
+//! | `uuid-v4`      | `0b6e5c1a-6f5e-4f0e-9d53-1c2a3b4c5d6e`   | no                     |
+//! | `uuid-v7`      | `01890a5d-ac96-774b-bcce-b302099a8057`   | yes                    |
+//! | `snowflake:N`  | `7055468497211392001`                    | yes (per worker `N`)   |
+//!
+//! A history entry carries two IDs: its generated record ID, kept when
+//! the history moves to another instance, and the number the store
+//! assigns in order, which doubles as the pagination cursor.
+//!
+//! Tests pass a [`SequentialIds`] to get the same IDs on every run.
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! type IDGenerator interface {
+//!     NewID() string
+//! }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use hsu_common::{Error, Result};
+
+/// Makes up unique IDs.
+pub trait IdGenerator: Send + Sync {
+    /// Returns a new ID, different from every one returned before.
+    fn next_id(&self) -> String;
+}
+
+/// Random UUIDs (version 4), the default.
+#[derive(Debug, Default)]
+pub struct UuidV4Ids;
+
+impl IdGenerator for UuidV4Ids {
+    fn next_id(&self) -> String {
+        uuid::Uuid::new_v4().to_string()
+    }
+}
+
+/// Time-ordered UUIDs (version 7): a millisecond timestamp, then random bits.
+#[derive(Debug, Default)]
+pub struct UuidV7Ids;
+
+impl IdGenerator for UuidV7Ids {
+    fn next_id(&self) -> String {
+        uuid::Uuid::now_v7().to_string()
+    }
+}
+
+/// Milliseconds from the Unix epoch to 2020-01-01, where snowflake time starts.
+const SNOWFLAKE_EPOCH_MS: u64 = 1_577_836_800_000;
+const WORKER_BITS: u32 = 10;
+const SEQUENCE_BITS: u32 = 12;
+
+/// Snowflake-style 64-bit IDs: milliseconds since 2020, the worker, and a
+/// sequence within the millisecond.
+///
+/// ```text
+/// | 41 bits: ms since 2020 | 10 bits: worker | 12 bits: sequence |
+/// ```
+///
+/// Unique across instances as long as each has its own worker number.
+/// IDs keep increasing if the clock steps back: the generator stays on
+/// its last millisecond until the clock catches up.
+#[derive(Debug)]
+pub struct SnowflakeIds {
+    worker: u64,
+    /// Millisecond and sequence of the last ID.
+    last: Mutex<(u64, u64)>,
+}
+
+impl SnowflakeIds {
+    /// Highest worker number.
+    pub const MAX_WORKER: u16 = (1 << WORKER_BITS) - 1;
+
+    /// Creates a generator for `worker` (0 to [`MAX_WORKER`](Self::MAX_WORKER)).
+    pub fn new(worker: u16) -> Result<Self> {
+        if worker > Self::MAX_WORKER {
+            return Err(Error::Validation {
+                message: format!("Snowflake worker {} out of range (0-{})", worker, Self::MAX_WORKER),
+            });
+        }
+        Ok(Self { worker: worker as u64, last: Mutex::new((0, 0)) })
+    }
+
+    /// Returns a new ID as a number.
+    pub fn next_u64(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64)
+            .saturating_sub(SNOWFLAKE_EPOCH_MS);
+        let mut last = self.last.lock().unwrap();
+        let (ms, sequence) = match *last {
+            (last_ms, sequence) if now <= last_ms => {
+                // Sequence exhausted: borrow the next millisecond
+                if sequence + 1 == 1 << SEQUENCE_BITS {
+                    (last_ms + 1, 0)
+                } else {
+                    (last_ms, sequence + 1)
+                }
+            }
+            _ => (now, 0),
+        };
+        *last = (ms, sequence);
+        (ms << (WORKER_BITS + SEQUENCE_BITS)) | (self.worker << SEQUENCE_BITS) | sequence
+    }
+}
+
+impl IdGenerator for SnowflakeIds {
+    fn next_id(&self) -> String {
+        self.next_u64().to_string()
+    }
+}
+
+/// `prefix-1`, `prefix-2`, ... - deterministic IDs for tests.
+#[derive(Debug)]
+pub struct SequentialIds {
+    prefix: String,
+    next: AtomicU64,
+}
+
+impl SequentialIds {
+    /// Starts at `prefix-1`.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self { prefix: prefix.into(), next: AtomicU64::new(1) }
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self) -> String {
+        format!("{}-{}", self.prefix, self.next.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A configurable [`IdGenerator`]: `uuid-v4`, `uuid-v7` or `snowflake:WORKER`.
+///
+/// Snowflake has no default worker: two instances sharing one would hand
+/// out the same IDs, so each must be given its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// [`UuidV4Ids`]
+    #[default]
+    UuidV4,
+    /// [`UuidV7Ids`]
+    UuidV7,
+    /// [`SnowflakeIds`] for `worker`
+    Snowflake { worker: u16 },
+}
+
+impl IdStrategy {
+    /// Creates the generator.
+    pub fn generator(&self) -> Result<Arc<dyn IdGenerator>> {
+        Ok(match *self {
+            IdStrategy::UuidV4 => Arc::new(UuidV4Ids),
+            IdStrategy::UuidV7 => Arc::new(UuidV7Ids),
+            IdStrategy::Snowflake { worker } => Arc::new(SnowflakeIds::new(worker)?),
+        })
+    }
+}
+
+impl FromStr for IdStrategy {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        let invalid = || Error::Validation {
+            message: format!("Unknown ID strategy '{}' (expected uuid-v4, uuid-v7 or snowflake:WORKER)", name),
+        };
+        match name.split_once(':') {
+            None if name == "uuid-v4" => Ok(IdStrategy::UuidV4),
+            None if name == "uuid-v7" => Ok(IdStrategy::UuidV7),
+            None if name == "snowflake" => Err(Error::Validation {
+                message: format!(
+                    "ID strategy 'snowflake' needs this instance's worker number, e.g. snowflake:3 (0-{})",
+                    SnowflakeIds::MAX_WORKER
+                ),
+            }),
+            Some(("snowflake", worker)) => {
+                let worker = worker.parse().ok().filter(|w| *w <= SnowflakeIds::MAX_WORKER).ok_or_else(invalid)?;
+                Ok(IdStrategy::Snowflake { worker })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for IdStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdStrategy::UuidV4 => write!(f, "uuid-v4"),
+            IdStrategy::UuidV7 => write!(f, "uuid-v7"),
+            IdStrategy::Snowflake { worker } => write!(f, "snowflake:{}", worker),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snowflake_ids_increase_and_carry_the_worker() {
+        let ids = SnowflakeIds::new(7).unwrap();
+        let mut previous = ids.next_u64();
+        // More than one millisecond's worth of sequence
+        for _ in 0..5000 {
+            let id = ids.next_u64();
+            assert!(id > previous);
+            assert_eq!((id >> SEQUENCE_BITS) & SnowflakeIds::MAX_WORKER as u64, 7);
+            previous = id;
+        }
+        assert!(SnowflakeIds::new(1024).is_err());
+    }
+
+    #[test]
+    fn test_uuid_v7_ids_sort_by_time() {
+        let first = UuidV7Ids.next_id();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        assert!(UuidV7Ids.next_id() > first);
+        assert_ne!(UuidV4Ids.next_id(), UuidV4Ids.next_id());
+    }
+
+    #[test]
+    fn test_strategies_parse() {
+        for name in ["uuid-v4", "uuid-v7", "snowflake:12"] {
+            assert_eq!(name.parse::<IdStrategy>().unwrap().to_string(), name);
+        }
+        assert_eq!("snowflake:0".parse::<IdStrategy>().unwrap(), IdStrategy::Snowflake { worker: 0 });
+        for name in ["uuid", "snowflake", "snowflake:1024", "snowflake:x", "uuid-v4:1"] {
+            assert!(name.parse::<IdStrategy>().is_err(), "{}", name);
+        }
+
+        let ids = SequentialIds::new("job");
+        assert_eq!((ids.next_id(), ids.next_id()), ("job-1".to_string(), "job-2".to_string()));
+    }
+}
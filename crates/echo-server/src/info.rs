@@ -0,0 +1,31 @@
+//! Instance Identity and Build Info (Layer 3)
+//!
+//! # Architecture
+//!
+//! What `EchoService::get_info` reports about this process:
+//!
+//! | Field         | Source                                             |
+//! |---------------|----------------------------------------------------|
+//! | `module_id`   | `EchoServerModuleConfig::module_id`                |
+//! | `instance_id` | Random UUID, created on first use                  |
+//! | `version`     | `CARGO_PKG_VERSION` of echo-server                 |
+//! | `git_hash`    | `build.rs` (`git rev-parse`, or `ECHO_GIT_HASH`)   |
+//! | `uptime`      | Since the service was created                      |
+//! | `features`    | echo-api Cargo features + enabled server subsystems|
+//!
+//! The instance ID is the same one response decoration attaches, so a
+//! tagged response can be matched with `echo-grpc-cli info`.
+
+use std::sync::OnceLock;
+
+/// Crate version of the server.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Git commit the server was built from (`unknown` outside a checkout).
+pub const GIT_HASH: &str = env!("ECHO_GIT_HASH");
+
+/// Returns this process's instance ID (a random UUID, fixed for its lifetime).
+pub fn instance_id() -> &'static str {
+    static INSTANCE_ID: OnceLock<String> = OnceLock::new();
+    INSTANCE_ID.get_or_init(|| uuid::Uuid::new_v4().to_string())
+}
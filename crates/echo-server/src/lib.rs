@@ -23,13 +23,41 @@
 //! - Domain: `pkg/echoserver/echoserverdomain/module.go`
 //! - Wiring: `pkg/echoserver/echoserverwiring/wiring.go`
 
+pub mod cron;
+pub mod decoration;
+pub mod dedup;
+pub mod history;
+pub mod history_import;
+pub mod ids;
+pub mod info;
 pub mod module;
+pub mod outbox;
+pub mod retention;
+pub mod scheduler;
+pub mod self_test;
 pub mod service_provider;
 pub mod service;
+pub mod session;
+pub mod transform;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_history;
 pub mod wiring;
 
 pub use module::EchoServerModule;
+pub use history::{HistoryEchoService, HistoryEntry, HistoryStore, InMemoryHistoryStore};
+pub use ids::{IdGenerator, IdStrategy, SequentialIds, SnowflakeIds, UuidV4Ids, UuidV7Ids};
+pub use outbox::{OutboxEvent, OutboxRelay, spawn_outbox_relay};
+pub use retention::{HistoryRetention, RetentionConfig, spawn_history_retention};
+pub use scheduler::{EchoScheduler, FileJobStore, InMemoryJobStore, JobStore, ScheduledJob, SchedulerConfig, spawn_scheduler};
+pub use self_test::{SelfTestConfig, SelfTestTargets, run_self_test};
 pub use service_provider::EchoServerServiceProvider;
-pub use service::EchoServiceImpl;
-pub use wiring::{init_echo_server_module, EchoServerModuleConfig};
+pub use service::{EchoServiceConfig, EchoServiceImpl};
+pub use decoration::{DecoratedEchoService, ResponseDecoration, REQUEST_ID_METADATA_KEY};
+pub use info::{instance_id, GIT_HASH, VERSION};
+pub use dedup::{DedupConfig, DedupSlot, DedupWindow};
+#[cfg(feature = "sqlite")]
+pub use sqlite_history::SqliteHistoryStore;
+pub use session::{InMemorySessionStore, SessionConfig, SessionState, SessionStore, spawn_session_sweeper};
+pub use transform::apply_transforms;
+pub use wiring::{init_echo_server_module, bound_endpoints, CanaryConfig, EchoServerModuleConfig, STABLE_VARIANT};
 
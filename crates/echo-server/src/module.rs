@@ -6,12 +6,28 @@
 //!
 //! Wiring (Layer 5) is in `wiring.rs` - kept separate!
 
+use std::sync::Arc;
+use std::time::Duration;
 use async_trait::async_trait;
-use hsu_common::{ModuleID, Result};
+use hsu_common::{ModuleID, Protocol, Result};
 use hsu_module_api::Module;
-use tracing::info;
+use echo_contract::{echo_module_id, EchoService, EchoServiceId};
+use echo_api::{
+    BoundEndpoint, BoundEndpoints, CancellationToken, Chaos, EchoEventBus, HealthRegistry, HealthStatus, MaintenanceMode, MaintenanceRegistry,
+    HEARTBEAT_INTERVAL, MdnsAdvertisement, RegisteredApi, RegistryBackend, SlowStart, TrafficSplit, grpc_api,
+    register_chaos_operation, register_maintenance_operations, register_traffic_split_operation, spawn_tracked,
+    serve_pipe, until_cancelled,
+};
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
 
+use crate::history::{run_blocking, HistoryStore};
+use crate::outbox::{spawn_outbox_relay, OutboxRelay};
+use crate::retention::{register_purge_operation, spawn_history_retention, HistoryRetention};
+use crate::scheduler::{spawn_scheduler, EchoScheduler};
+use crate::self_test::{run_self_test, SelfTestConfig, SelfTestTargets};
 use crate::service_provider::EchoServerServiceProvider;
+use crate::session::{spawn_session_sweeper, SessionConfig, SessionStore};
 
 /// Echo server module implementation.
 ///
@@ -24,18 +40,259 @@ use crate::service_provider::EchoServerServiceProvider;
 pub struct EchoServerModule {
     id: ModuleID,
     _service_provider: EchoServerServiceProvider,
+    endpoints: Arc<BoundEndpoints>,
+    sessions: Option<(Arc<dyn SessionStore>, SessionConfig)>,
+    sweeper: Option<JoinHandle<()>>,
+    mdns: bool,
+    advertisement: Option<MdnsAdvertisement>,
+    /// Registry to publish to ourselves, with the host clients should dial.
+    registry: Option<(Arc<dyn RegistryBackend>, String)>,
+    registry_heartbeat: Option<JoinHandle<()>>,
+    self_test: Option<(SelfTestTargets, SelfTestConfig)>,
+    scheduler: Option<Arc<EchoScheduler>>,
+    scheduler_loop: Option<JoinHandle<()>>,
+    history: Option<(Arc<dyn HistoryStore>, Arc<OutboxRelay>)>,
+    outbox_loop: Option<JoinHandle<()>>,
+    /// Closed on stop, so subscriptions end with the module.
+    events: Option<Arc<EchoEventBus>>,
+    retention: Option<Arc<HistoryRetention>>,
+    retention_loop: Option<JoinHandle<()>>,
+    slow_start: Option<Arc<SlowStart>>,
+    /// Maintenance switch, with how long `enter-maintenance` waits for a drain.
+    maintenance: Option<(Arc<MaintenanceMode>, Duration)>,
+    maintenance_watch: Option<JoinHandle<()>>,
+    traffic_split: Option<Arc<TrafficSplit>>,
+    chaos: Option<Arc<Chaos>>,
+    chaos_loop: Option<JoinHandle<()>>,
+    /// Served to the supervising parent over stdin/stdout.
+    stdio_pipe: Option<Arc<dyn EchoService>>,
+    stdio_pipe_loop: Option<JoinHandle<()>>,
+    /// Cancelled on Ctrl-C: cuts a waiting `start()` short.
+    cancellation: CancellationToken,
 }
 
+/// Health check failing while the module is in maintenance mode.
+const MAINTENANCE_CHECK: &str = "maintenance";
+
+/// How long a cancelled start waits to withdraw a publish it cut off.
+const CANCELLED_UNPUBLISH_TIMEOUT: Duration = Duration::from_secs(2);
+
 impl EchoServerModule {
     /// Creates a new echo server module.
     ///
     /// Note: This is called by the wiring layer (Layer 5).
     pub fn new(service_provider: EchoServerServiceProvider) -> Self {
         Self {
-            id: ModuleID::from("echo"),  // Note: This is "echo", not "echo-server"!
+            id: echo_module_id(),  // Note: This is "echo", not "echo-server"!
             _service_provider: service_provider,
+            endpoints: Arc::new(BoundEndpoints::new()),
+            sessions: None,
+            sweeper: None,
+            mdns: false,
+            advertisement: None,
+            registry: None,
+            registry_heartbeat: None,
+            self_test: None,
+            scheduler: None,
+            scheduler_loop: None,
+            history: None,
+            outbox_loop: None,
+            events: None,
+            retention: None,
+            retention_loop: None,
+            slow_start: None,
+            maintenance: None,
+            maintenance_watch: None,
+            traffic_split: None,
+            chaos: None,
+            chaos_loop: None,
+            stdio_pipe: None,
+            stdio_pipe_loop: None,
+            cancellation: CancellationToken::new(),
         }
     }
+    
+    /// Aborts `start()` when `token` is cancelled: the history migration,
+    /// self-test and registry publishing don't outlast a Ctrl-C.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+    
+    /// Expires idle sessions of `store` while the module runs.
+    pub fn with_sessions(mut self, store: Arc<dyn SessionStore>, config: SessionConfig) -> Self {
+        self.sessions = Some((store, config));
+        self
+    }
+    
+    /// Advertises the bound gRPC endpoints over mDNS while running.
+    pub fn with_mdns_advertisement(mut self) -> Self {
+        self.mdns = true;
+        self
+    }
+    
+    /// Publishes the bound gRPC endpoints to `backend` while running,
+    /// as reachable at `advertise_host`.
+    ///
+    /// For registries the framework doesn't publish to (Consul, etcd).
+    pub fn with_registry_backend(mut self, backend: Arc<dyn RegistryBackend>, advertise_host: String) -> Self {
+        self.registry = Some((backend, advertise_host));
+        self
+    }
+    
+    /// Echoes through `targets` (and the bound gRPC ports) before
+    /// reporting Ready; see [`crate::self_test`].
+    pub fn with_self_test(mut self, targets: SelfTestTargets, config: SelfTestConfig) -> Self {
+        self.self_test = Some((targets, config));
+        self
+    }
+    
+    /// Restores `scheduler`'s saved jobs and runs them while running.
+    pub fn with_scheduler(mut self, scheduler: Arc<EchoScheduler>) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+    
+    /// Migrates `store` on start and publishes its outbox through `relay`
+    /// while running.
+    pub fn with_history(mut self, store: Arc<dyn HistoryStore>, relay: Arc<OutboxRelay>) -> Self {
+        self.history = Some((store, relay));
+        self
+    }
+    
+    /// Ends the subscriptions to `events` when stopped.
+    pub fn with_events(mut self, events: Arc<EchoEventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+    
+    /// Prunes the history with `retention` while running and offers a
+    /// manual purge on the admin endpoint.
+    pub fn with_history_retention(mut self, retention: Arc<HistoryRetention>) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+    
+    /// Restarts `slow_start`'s ramp on every start.
+    pub fn with_slow_start(mut self, slow_start: Arc<SlowStart>) -> Self {
+        self.slow_start = Some(slow_start);
+        self
+    }
+    
+    /// Offers `enter-maintenance`/`leave-maintenance` on the admin
+    /// endpoint while running, and reports `mode` in health and the
+    /// registry entry.
+    pub fn with_maintenance_mode(mut self, mode: Arc<MaintenanceMode>, drain_timeout: Duration) -> Self {
+        self.maintenance = Some((mode, drain_timeout));
+        self
+    }
+    
+    /// Lets the admin endpoint move `split` while running.
+    pub fn with_traffic_split(mut self, split: Arc<TrafficSplit>) -> Self {
+        self.traffic_split = Some(split);
+        self
+    }
+    
+    /// Drops `chaos`'s connections while running and lets the admin
+    /// endpoint change its faults.
+    pub fn with_chaos(mut self, chaos: Arc<Chaos>) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+    
+    /// Serves `service` over stdin/stdout while running, to the parent
+    /// supervising this process (see [`echo_api::pipe`]).
+    pub fn with_stdio_pipe(mut self, service: Arc<dyn EchoService>) -> Self {
+        self.stdio_pipe = Some(service);
+        self
+    }
+    
+    /// Shares `endpoints` with the handlers registrar that fills it.
+    pub fn with_endpoints(mut self, endpoints: Arc<BoundEndpoints>) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+    
+    /// Returns the endpoints the protocol servers actually bound.
+    ///
+    /// With port 0 this is the only way to learn the real port.
+    pub fn bound_endpoints(&self) -> Vec<BoundEndpoint> {
+        self.endpoints.list()
+    }
+
+    /// The gRPC APIs published to the registry backend, as reachable at `host`.
+    fn registry_apis(&self, host: &str) -> Vec<RegisteredApi> {
+        let maintenance = self.maintenance.as_ref().and_then(|(mode, _)| mode.reason());
+        self.bound_endpoints()
+            .iter()
+            .filter(|endpoint| endpoint.protocol == Protocol::Grpc)
+            .map(|endpoint| RegisteredApi {
+                maintenance: maintenance.clone(),
+                ..grpc_api(host, endpoint.port, &EchoServiceId::ALL.map(|service| service.as_str()))
+            })
+            .collect()
+    }
+
+    /// Renews the registry entry every [`HEARTBEAT_INTERVAL`] until aborted,
+    /// publishing it again if it expired meanwhile.
+    fn spawn_registry_heartbeat(&self, backend: Arc<dyn RegistryBackend>, host: &str) -> JoinHandle<()> {
+        let module = self.id.to_string();
+        let apis = self.registry_apis(host);
+        let maintenance = self.maintenance.as_ref().map(|(mode, _)| mode.clone());
+        spawn_tracked(&self.id.to_string(), "registry-heartbeat", async move {
+            let mut ticks = tokio::time::interval(HEARTBEAT_INTERVAL);
+            // The first tick is immediate, and start() just published
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let Err(e) = backend.heartbeat(&module, std::process::id()).await else {
+                    continue;
+                };
+                warn!("[EchoServer] {} registry heartbeat failed, publishing again: {}", backend.name(), e);
+                let reason = maintenance.as_ref().and_then(|mode| mode.reason());
+                let apis = apis.iter()
+                    .map(|api| RegisteredApi { maintenance: reason.clone(), ..api.clone() })
+                    .collect();
+                if let Err(e) = backend.publish(&module, std::process::id(), apis).await {
+                    warn!("[EchoServer] Failed to republish to the {} registry: {}", backend.name(), e);
+                }
+            }
+        })
+    }
+
+    /// Follows the maintenance mode until aborted: a failing health check
+    /// and a republished registry entry while it is active.
+    fn spawn_maintenance_watch(&self, mode: &MaintenanceMode) -> JoinHandle<()> {
+        let module = self.id.to_string();
+        let mut changes = mode.subscribe();
+        let registry = self.registry.as_ref().map(|(backend, host)| (backend.clone(), self.registry_apis(host)));
+        record_maintenance_health(&module, changes.borrow_and_update().as_deref());
+        spawn_tracked(&self.id.to_string(), "maintenance-mode", async move {
+            // The entry published by start() is up to date, only changes are republished
+            while changes.changed().await.is_ok() {
+                let reason = changes.borrow_and_update().clone();
+                record_maintenance_health(&module, reason.as_deref());
+                if let Some((backend, apis)) = &registry {
+                    let apis = apis.iter()
+                        .map(|api| RegisteredApi { maintenance: reason.clone(), ..api.clone() })
+                        .collect();
+                    if let Err(e) = backend.publish(&module, std::process::id(), apis).await {
+                        warn!("[EchoServer] Failed to republish to the {} registry: {}", backend.name(), e);
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Records the maintenance check: failing (GET /health 503) while in
+/// maintenance mode.
+fn record_maintenance_health(module: &str, reason: Option<&str>) {
+    let status = match reason {
+        Some(reason) => HealthStatus::Failing(format!("in maintenance mode: {}", reason)),
+        None => HealthStatus::Passing,
+    };
+    HealthRegistry::global().record(module, MAINTENANCE_CHECK, status, Duration::ZERO);
 }
 
 #[async_trait]
@@ -44,16 +301,207 @@ impl Module for EchoServerModule {
         &self.id
     }
 
+    #[instrument(name = "module", skip_all, fields(module_id = %self.id))]
     async fn start(&mut self) -> Result<()> {
-        info!("[EchoServer] Starting...");
+        info!("Starting...");
         // Server just needs to be ready - handlers are already registered
+        for endpoint in self.bound_endpoints() {
+            info!("✅ Serving {:?} on port {}", endpoint.protocol, endpoint.port);
+        }
+        if let Some(events) = &self.events {
+            events.open();
+        }
+        if let Some((store, config)) = &self.sessions {
+            self.sweeper = Some(spawn_session_sweeper(&self.id.to_string(), store.clone(), config));
+        }
+        if let Some(scheduler) = &self.scheduler {
+            self.scheduler_loop = Some(spawn_scheduler(&self.id.to_string(), scheduler.clone()));
+        }
+        if let Some((store, relay)) = &self.history {
+            // On the blocking pool: a cancelled start stops waiting, the
+            // migration finishes on its own thread
+            let migrate = run_blocking(store, |store| store.migrate());
+            until_cancelled(&self.cancellation, "migrating the history store", migrate).await?;
+            self.outbox_loop = Some(spawn_outbox_relay(&self.id.to_string(), relay.clone()));
+        }
+        if let Some(retention) = &self.retention {
+            self.retention_loop = Some(spawn_history_retention(&self.id.to_string(), retention.clone()));
+            register_purge_operation(&self.id.to_string(), retention.clone());
+        }
+        if let Some(split) = &self.traffic_split {
+            register_traffic_split_operation(&self.id.to_string(), split.clone());
+            info!("Canary receives {}% of the echo calls", split.canary_percent());
+        }
+        if let Some(chaos) = &self.chaos {
+            register_chaos_operation(&self.id.to_string(), chaos.clone());
+            let drops = chaos.clone();
+            self.chaos_loop = Some(spawn_tracked(&self.id.to_string(), "chaos-drops", async move {
+                drops.run_drops().await
+            }));
+            warn!("⚠️ Chaos enabled: {}", chaos.config());
+        }
+        // Before publishing, so a misregistered server is never discovered
+        if let Some((targets, config)) = &self.self_test {
+            let grpc_ports: Vec<u16> = self.bound_endpoints()
+                .iter()
+                .filter(|endpoint| endpoint.protocol == Protocol::Grpc)
+                .map(|endpoint| endpoint.port)
+                .collect();
+            let self_test = run_self_test(&self.id.to_string(), targets, &grpc_ports, config);
+            if let Err(e) = until_cancelled(&self.cancellation, "running the self-test", self_test).await {
+                if config.fail_start || self.cancellation.is_cancelled() {
+                    return Err(e);
+                }
+                warn!("{} (starting anyway)", e);
+            }
+        }
+        // Private to the parent, so not held back by publishing
+        if let Some(service) = &self.stdio_pipe {
+            let service = service.clone();
+            self.stdio_pipe_loop = Some(spawn_tracked(&self.id.to_string(), "stdio-pipe", async move {
+                if let Err(e) = serve_pipe(service, tokio::io::stdin(), tokio::io::stdout()).await {
+                    warn!("Stdio pipe failed: {}", e);
+                }
+            }));
+            info!("✅ Serving the parent over the stdio pipe");
+        }
+        // Published below: the reconnect burst starts here, so does the ramp
+        if let Some(slow_start) = &self.slow_start {
+            slow_start.begin();
+            info!("Slow start: admitting {} concurrent calls, ramping up", slow_start.limit());
+        }
+        if let Some((backend, host)) = &self.registry {
+            let publish = backend.publish(&self.id.to_string(), std::process::id(), self.registry_apis(host));
+            if let Err(e) = until_cancelled(&self.cancellation, "publishing to the registry", publish).await {
+                // The cut-off request may have reached the registry: take it back
+                if self.cancellation.is_cancelled() {
+                    let unpublish = backend.unpublish(&self.id.to_string(), std::process::id());
+                    match tokio::time::timeout(CANCELLED_UNPUBLISH_TIMEOUT, unpublish).await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => warn!("Failed to unpublish from the {} registry: {}", backend.name(), e),
+                        Err(_) => warn!("Unpublishing from the {} registry timed out", backend.name()),
+                    }
+                }
+                return Err(e);
+            }
+            info!("✅ Published to the {} registry", backend.name());
+            self.registry_heartbeat = Some(self.spawn_registry_heartbeat(backend.clone(), host));
+        }
+        // After publishing: the watcher republishes on every toggle
+        if let Some((mode, drain_timeout)) = &self.maintenance {
+            register_maintenance_operations(&self.id.to_string(), mode.clone(), *drain_timeout);
+            self.maintenance_watch = Some(self.spawn_maintenance_watch(mode));
+        }
+        // A LAN convenience: serving works without it, so don't fail the start
+        if self.mdns {
+            match MdnsAdvertisement::advertise(&self.id.to_string(), &self.bound_endpoints()) {
+                Ok(advertisement) => self.advertisement = Some(advertisement),
+                Err(e) => warn!("mDNS advertisement disabled: {}", e),
+            }
+        }
         Ok(())
     }
 
+    #[instrument(name = "module", skip_all, fields(module_id = %self.id))]
     async fn stop(&mut self) -> Result<()> {
-        info!("[EchoServer] Stopping...");
+        info!("Stopping...");
+        if let Some(sweeper) = self.sweeper.take() {
+            sweeper.abort();
+        }
+        // Pending jobs stay in the job store for the next start
+        if let Some(scheduler_loop) = self.scheduler_loop.take() {
+            scheduler_loop.abort();
+        }
+        if let Some(retention_loop) = self.retention_loop.take() {
+            retention_loop.abort();
+        }
+        if let Some(maintenance_watch) = self.maintenance_watch.take() {
+            maintenance_watch.abort();
+        }
+        if let Some(chaos_loop) = self.chaos_loop.take() {
+            chaos_loop.abort();
+        }
+        if let Some(stdio_pipe_loop) = self.stdio_pipe_loop.take() {
+            stdio_pipe_loop.abort();
+        }
+        MaintenanceRegistry::global().clear(&self.id.to_string());
+        // One last round, so events of the final calls aren't left behind
+        if let Some(outbox_loop) = self.outbox_loop.take() {
+            outbox_loop.abort();
+        }
+        if let Some((_, relay)) = &self.history {
+            if let Err(e) = relay.relay_pending() {
+                warn!("Failed to flush the event outbox: {}", e);
+            }
+        }
+        // After the last events went out: subscribers see their streams end
+        if let Some(events) = &self.events {
+            events.close();
+        }
+        // Withdrawn on drop
+        self.advertisement.take();
+        if let Some(registry_heartbeat) = self.registry_heartbeat.take() {
+            registry_heartbeat.abort();
+        }
+        if let Some((backend, _)) = &self.registry {
+            if let Err(e) = backend.unpublish(&self.id.to_string(), std::process::id()).await {
+                warn!("Failed to unpublish from the {} registry: {}", backend.name(), e);
+            }
+        }
         Ok(())
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
 
+    /// A registry whose publish never answers, remembering unpublishes.
+    #[derive(Default)]
+    struct StuckRegistry {
+        unpublished: AtomicBool,
+    }
+
+    #[async_trait]
+    impl RegistryBackend for StuckRegistry {
+        fn name(&self) -> &'static str {
+            "stuck"
+        }
+
+        async fn publish(&self, _module_id: &str, _process_id: u32, _apis: Vec<RegisteredApi>) -> Result<()> {
+            std::future::pending().await
+        }
+
+        async fn unpublish(&self, _module_id: &str, _process_id: u32) -> Result<()> {
+            self.unpublished.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn discover(&self, _module_id: &str) -> Result<Vec<RegisteredApi>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_start_withdraws_its_publish() {
+        let registry = Arc::new(StuckRegistry::default());
+        let token = CancellationToken::new();
+        let mut module = EchoServerModule::new(EchoServerServiceProvider::default())
+            .with_registry_backend(registry.clone(), "127.0.0.1".to_string())
+            .with_cancellation(token.clone());
+
+        let cancel = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        });
+        let error = tokio::time::timeout(Duration::from_secs(5), module.start())
+            .await
+            .expect("cancellation should end the start")
+            .unwrap_err();
+
+        assert!(error.to_string().contains("start cancelled while publishing to the registry"));
+        assert!(registry.unpublished.load(Ordering::SeqCst));
+    }
+}
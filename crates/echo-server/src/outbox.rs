@@ -0,0 +1,104 @@
+//! Outbox Relay (Layer 3)
+//!
+//! # Architecture
+//!
+//! Publishes the events committed to a [`HistoryStore`]'s outbox (see
+//! [`crate::history`]) and removes them once published:
+//!
+//! ```text
+//! HistoryEchoService ──append──→ outbox ──notify──┐
+//!                                                 ↓
+//! OutboxRelay loop:  pending_events(batch) → EchoEventBus::publish → mark_published
+//!                    (woken per append, polls every second as a fallback)
+//! ```
+//!
+//! Delivery is **at least once**: a crash between publishing and
+//! `mark_published` publishes those events again after the restart.
+//! Subscribers that must not count twice can key on the event's time and
+//! method - "exactly-once-ish", the honest promise of any outbox.
+//!
+//! This is a useful template for any module that must not lose events!
+
+use std::sync::Arc;
+use std::time::Duration;
+use echo_api::{spawn_tracked, EchoEventBus};
+use echo_contract::{storage_error, EchoEvent};
+use hsu_common::Result;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::history::HistoryStore;
+
+/// Events published per store round trip.
+const BATCH_SIZE: usize = 256;
+
+/// How often the relay looks for pending events without being woken.
+///
+/// Catches events left over from before a restart.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An event waiting in the outbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutboxEvent {
+    /// Outbox ID (the ID of the history entry it was committed with).
+    pub id: u64,
+    /// The event to publish.
+    pub event: EchoEvent,
+}
+
+/// Moves events from a store's outbox to the event bus.
+pub struct OutboxRelay {
+    store: Arc<dyn HistoryStore>,
+    bus: Arc<EchoEventBus>,
+    wake: Notify,
+}
+
+impl OutboxRelay {
+    /// Relays from `store`'s outbox to `bus`.
+    pub fn new(store: Arc<dyn HistoryStore>, bus: Arc<EchoEventBus>) -> Self {
+        Self { store, bus, wake: Notify::new() }
+    }
+
+    /// Wakes the relay loop (called after each append).
+    pub fn notify(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Publishes every pending event; returns how many were published.
+    pub fn relay_pending(&self) -> Result<usize> {
+        let mut published = 0;
+        loop {
+            let pending = self.store.pending_events(BATCH_SIZE)?;
+            if pending.is_empty() {
+                return Ok(published);
+            }
+            let ids: Vec<u64> = pending.iter().map(|pending| pending.id).collect();
+            for pending in pending {
+                self.bus.publish(pending.event);
+            }
+            // Published but not marked if this fails: sent again next round
+            self.store.mark_published(&ids)?;
+            published += ids.len();
+        }
+    }
+}
+
+/// Runs `relay` until aborted.
+pub fn spawn_outbox_relay(module: &str, relay: Arc<OutboxRelay>) -> JoinHandle<()> {
+    spawn_tracked(module, "outbox-relay", async move {
+        loop {
+            // The store calls block: keep them off the async workers
+            let relaying = relay.clone();
+            let relayed = tokio::task::spawn_blocking(move || relaying.relay_pending())
+                .await
+                .unwrap_or_else(|e| Err(storage_error(format!("Outbox relay failed: {}", e))));
+            match relayed {
+                Ok(0) => {}
+                Ok(published) => debug!("[OutboxRelay] Published {} event(s)", published),
+                Err(e) => warn!("[OutboxRelay] Failed to relay events: {}", e),
+            }
+            let _ = tokio::time::timeout(POLL_INTERVAL, relay.wake.notified()).await;
+        }
+    })
+}
@@ -0,0 +1,199 @@
+//! History Retention (Layer 3)
+//!
+//! # Architecture
+//!
+//! Keeps a [`HistoryStore`] from growing forever. A background task
+//! prunes entries beyond a maximum age and/or count, then compacts the
+//! store; operators can run the same purge by hand from the admin
+//! endpoint:
+//!
+//! ```text
+//! every interval (and once on start)
+//!     ↓
+//! HistoryRetention::purge() ── prune(now - max_age, max_entries) ──→ HistoryStore
+//!     ↓ pruned > 0               └── compact()  (SQLite: VACUUM, WAL checkpoint)
+//! MaintenanceRegistry::record_run("echo", "history-retention", Ok(pruned))
+//!     ↓ GET /metrics
+//! echo_maintenance_items_total{module="echo",task="history-retention"}
+//!
+//! POST /maintenance/echo/purge-history  →  same purge(), right now
+//! ```
+//!
+//! Pruning only deletes history entries. Events still waiting in the
+//! outbox are published regardless, so retention never loses an event.
+//!
+//! The store calls are synchronous and a large delete or `VACUUM` can
+//! take a while, so each run goes to tokio's blocking pool instead of
+//! stalling the module's runtime.
+//!
+//! ## Comparison with Golang
+//!
+//! | Golang                                  | Rust                                   |
+//! |-----------------------------------------|----------------------------------------|
+//! | `time.NewTicker` + goroutine            | `tokio::time::interval` in a task      |
+//! | `cancel()` on stop                      | `JoinHandle::abort()` on stop          |
+//! | run the store call inline               | `spawn_blocking` (sync store)          |
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use echo_api::{spawn_tracked, MaintenanceRegistry};
+use echo_contract::storage_error;
+use hsu_common::Result;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::history::HistoryStore;
+
+/// Task name in the maintenance metrics.
+pub const RETENTION_TASK: &str = "history-retention";
+
+/// Admin operation running a purge right away.
+pub const PURGE_OPERATION: &str = "purge-history";
+
+/// How much history to keep.
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    /// Prune entries older than this (no age limit if `None`).
+    pub max_age: Option<Duration>,
+    /// Keep at most this many entries (no count limit if `None`).
+    pub max_entries: Option<usize>,
+    /// How often the task prunes.
+    pub interval: Duration,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_age: None,
+            max_entries: None,
+            interval: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Applies a [`RetentionConfig`] to a store.
+pub struct HistoryRetention {
+    store: Arc<dyn HistoryStore>,
+    config: RetentionConfig,
+}
+
+impl HistoryRetention {
+    /// Prunes `store` according to `config`.
+    pub fn new(store: Arc<dyn HistoryStore>, config: RetentionConfig) -> Self {
+        Self { store, config }
+    }
+
+    /// The configuration in effect.
+    pub fn config(&self) -> &RetentionConfig {
+        &self.config
+    }
+
+    /// Prunes now and compacts if anything was pruned; returns how many
+    /// entries were deleted.
+    ///
+    /// Blocks on the store - see [`run`](Self::run) from async code.
+    pub fn purge(&self) -> Result<u64> {
+        let before = self.config.max_age.and_then(|age| SystemTime::now().checked_sub(age));
+        let pruned = self.store.prune(before, self.config.max_entries)?;
+        if pruned > 0 {
+            self.store.compact()?;
+        }
+        Ok(pruned)
+    }
+
+    /// Runs [`purge`](Self::purge) on the blocking pool and records the
+    /// run for `module`'s maintenance metrics.
+    pub async fn run(self: &Arc<Self>, module: &str) -> Result<u64> {
+        let retention = self.clone();
+        let result = tokio::task::spawn_blocking(move || retention.purge())
+            .await
+            .unwrap_or_else(|e| Err(storage_error(format!("History purge failed: {}", e))));
+        MaintenanceRegistry::global().record_run(module, RETENTION_TASK, &result);
+        result
+    }
+}
+
+/// Runs `retention` every interval until aborted, the first time right away.
+pub fn spawn_history_retention(module: &str, retention: Arc<HistoryRetention>) -> JoinHandle<()> {
+    let owner = module.to_string();
+    spawn_tracked(module, RETENTION_TASK, async move {
+        let mut ticks = tokio::time::interval(retention.config.interval);
+        loop {
+            ticks.tick().await;
+            match retention.run(&owner).await {
+                Ok(0) => {}
+                Ok(pruned) => info!("[HistoryRetention] Pruned {} history entries", pruned),
+                Err(e) => warn!("[HistoryRetention] Failed to prune the history: {}", e),
+            }
+        }
+    })
+}
+
+/// Registers [`PURGE_OPERATION`] for `module` on the admin endpoint.
+pub fn register_purge_operation(module: &str, retention: Arc<HistoryRetention>) {
+    let owner = module.to_string();
+    MaintenanceRegistry::global().register_operation(module, PURGE_OPERATION, Arc::new(move |_| {
+        let retention = retention.clone();
+        let owner = owner.clone();
+        Box::pin(async move {
+            let pruned = retention.run(&owner).await?;
+            Ok(format!("pruned {} history entries", pruned))
+        })
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::{EchoEvent, EchoMethod, HistoryQuery};
+    use crate::history::{HistoryEntry, InMemoryHistoryStore};
+
+    fn append(store: &InMemoryHistoryStore, at: SystemTime) {
+        let entry = HistoryEntry {
+            id: 0,
+            record_id: String::new(),
+            method: EchoMethod::Echo,
+            message: String::new(),
+            request_bytes: 0,
+            success: true,
+            at,
+        };
+        store.append(entry, EchoEvent { method: EchoMethod::Echo, request_bytes: 0, success: true, at }).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_prunes_and_records() {
+        let store = Arc::new(InMemoryHistoryStore::new());
+        append(&store, SystemTime::now() - Duration::from_secs(3600));
+        for _ in 0..4 {
+            append(&store, SystemTime::now());
+        }
+        let retention = Arc::new(HistoryRetention::new(store.clone(), RetentionConfig {
+            max_age: Some(Duration::from_secs(60)),
+            max_entries: Some(3),
+            ..Default::default()
+        }));
+
+        // One too old, one beyond the count
+        assert_eq!(retention.run("retention-test").await.unwrap(), 2);
+        assert_eq!(store.query(&HistoryQuery::new()).unwrap().entries.len(), 3);
+        assert_eq!(retention.run("retention-test").await.unwrap(), 0);
+
+        let stats = MaintenanceRegistry::global().stats("retention-test", RETENTION_TASK).unwrap();
+        assert_eq!((stats.runs, stats.items), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_purge_operation() {
+        let store = Arc::new(InMemoryHistoryStore::new());
+        append(&store, SystemTime::now());
+        append(&store, SystemTime::now());
+        let config = RetentionConfig { max_entries: Some(0), ..Default::default() };
+        register_purge_operation("purge-test", Arc::new(HistoryRetention::new(store.clone(), config)));
+
+        let summary = MaintenanceRegistry::global().run_operation("purge-test", PURGE_OPERATION, String::new()).await;
+        assert_eq!(summary.unwrap().unwrap(), "pruned 2 history entries");
+        assert!(store.query(&HistoryQuery::new()).unwrap().entries.is_empty());
+        MaintenanceRegistry::global().clear("purge-test");
+    }
+}
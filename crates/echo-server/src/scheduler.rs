@@ -0,0 +1,486 @@
+//! Scheduled Echoes (Layer 3)
+//!
+//! # Architecture
+//!
+//! `schedule_echo` stores a job; a background loop echoes each job's
+//! message when it is due, through the same service stack as a caller's
+//! `echo` - so every run is published on `EchoEvents`:
+//!
+//! ```text
+//! schedule_echo("tick", every 5m) ─→ EchoScheduler ─save─→ JobStore (file)
+//!                                        ↑ wake                ↑ load on start
+//!                                  scheduler loop ── sleeps until the next due job
+//!                                        ↓ due
+//!                           EventEmittingEchoService::echo("tick") ─→ EchoEvents
+//! ```
+//!
+//! - **One-off** jobs (`30s`) are removed when they run.
+//! - **Recurring** jobs (`every 5m`, cron) are rescheduled from the time
+//!   they ran; runs missed while the server was down are not caught up,
+//!   the job runs once and continues on schedule.
+//! - **Persistence**: every change is saved to the [`JobStore`], off the
+//!   async workers and outside the job lock. With a [`FileJobStore`] the
+//!   jobs survive a restart and overdue ones run right after the start.
+//! - **At least once**: a due job is removed or advanced only after its
+//!   echo ran, so a crash mid-run repeats the run after the restart
+//!   instead of losing it.
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! type JobStore interface {
+//!     Load() ([]ScheduledJob, error)
+//!     Save(jobs []ScheduledJob) error
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use echo_api::spawn_tracked;
+use echo_contract::{limit_reached, storage_error, EchoSchedule, EchoService, ScheduledEcho};
+use hsu_common::{Error, Result};
+use serde_json::{json, Value};
+use tokio::sync::{Mutex as AsyncMutex, Notify};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::cron::CronExpr;
+use crate::ids::{IdGenerator, UuidV4Ids};
+
+/// Scheduler settings.
+#[derive(Debug, Clone)]
+pub struct SchedulerConfig {
+    /// Pending jobs accepted at most; `schedule_echo` fails with
+    /// `RESOURCE_EXHAUSTED` beyond that.
+    pub max_jobs: usize,
+    /// File keeping the jobs across restarts (in memory only if `None`).
+    pub store_path: Option<PathBuf>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_jobs: 1000,
+            store_path: None,
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// Creates the store selected by `store_path`.
+    pub fn job_store(&self) -> Arc<dyn JobStore> {
+        match &self.store_path {
+            Some(path) => Arc::new(FileJobStore::new(path.clone())),
+            None => Arc::new(InMemoryJobStore::default()),
+        }
+    }
+}
+
+/// A pending scheduled echo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledJob {
+    /// ID returned by `schedule_echo`.
+    pub id: String,
+    /// Message echoed on each run.
+    pub message: Arc<str>,
+    /// When the job runs.
+    pub schedule: EchoSchedule,
+    /// When the job runs next.
+    pub next_run: SystemTime,
+    /// Number of completed runs.
+    pub runs: u64,
+}
+
+/// Storage for pending jobs.
+///
+/// # Rust Learning Note
+///
+/// The scheduler saves the whole job list on every change: job lists are
+/// small, and a full snapshot can't be left half-applied by a crash.
+pub trait JobStore: Send + Sync {
+    /// Returns the jobs saved last (none if nothing was saved yet).
+    fn load(&self) -> Result<Vec<ScheduledJob>>;
+
+    /// Replaces the saved jobs with `jobs`.
+    fn save(&self, jobs: &[ScheduledJob]) -> Result<()>;
+}
+
+/// Keeps the jobs for the lifetime of the process only.
+#[derive(Default)]
+pub struct InMemoryJobStore {
+    jobs: Mutex<Vec<ScheduledJob>>,
+}
+
+impl JobStore for InMemoryJobStore {
+    fn load(&self) -> Result<Vec<ScheduledJob>> {
+        Ok(self.jobs.lock().unwrap().clone())
+    }
+
+    fn save(&self, jobs: &[ScheduledJob]) -> Result<()> {
+        *self.jobs.lock().unwrap() = jobs.to_vec();
+        Ok(())
+    }
+}
+
+/// Keeps the jobs in a JSON file.
+///
+/// Saves write and sync a temporary file, then rename it over the old
+/// one, so a crash mid-save leaves the previous job list intact.
+pub struct FileJobStore {
+    path: PathBuf,
+}
+
+impl FileJobStore {
+    /// Uses the file at `path` (created on the first save).
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn error(&self, detail: impl std::fmt::Display) -> Error {
+        storage_error(format!("Job store {}: {}", self.path.display(), detail))
+    }
+}
+
+impl JobStore for FileJobStore {
+    fn load(&self) -> Result<Vec<ScheduledJob>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(self.error(e)),
+        };
+        let value: Value = serde_json::from_str(&text).map_err(|e| self.error(e))?;
+        let entries = value.as_array().ok_or_else(|| self.error("expected a JSON array"))?;
+        entries.iter().map(|entry| job_from_json(entry).map_err(|e| self.error(e))).collect()
+    }
+
+    fn save(&self, jobs: &[ScheduledJob]) -> Result<()> {
+        let value = Value::Array(jobs.iter().map(job_to_json).collect());
+        let temporary = self.path.with_extension("tmp");
+        let mut file = File::create(&temporary).map_err(|e| self.error(e))?;
+        file.write_all(value.to_string().as_bytes()).map_err(|e| self.error(e))?;
+        file.sync_all().map_err(|e| self.error(e))?;
+        fs::rename(&temporary, &self.path).map_err(|e| self.error(e))?;
+        // The rename itself is durable once the directory is synced
+        #[cfg(unix)]
+        if let Some(directory) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            File::open(directory).and_then(|directory| directory.sync_all()).map_err(|e| self.error(e))?;
+        }
+        Ok(())
+    }
+}
+
+fn job_to_json(job: &ScheduledJob) -> Value {
+    json!({
+        "id": job.id,
+        "message": &*job.message,
+        "schedule": job.schedule.to_string(),
+        "next_run_unix_ms": job.next_run.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64),
+        "runs": job.runs,
+    })
+}
+
+fn job_from_json(value: &Value) -> Result<ScheduledJob> {
+    let string = |name: &str| {
+        value[name].as_str().ok_or_else(|| Error::Validation {
+            message: format!("job without '{}'", name),
+        })
+    };
+    Ok(ScheduledJob {
+        id: string("id")?.to_string(),
+        message: string("message")?.into(),
+        schedule: string("schedule")?.parse()?,
+        next_run: UNIX_EPOCH + Duration::from_millis(value["next_run_unix_ms"].as_u64().unwrap_or_default()),
+        runs: value["runs"].as_u64().unwrap_or_default(),
+    })
+}
+
+/// When `schedule` runs next after `now` (`None`: never again).
+fn next_run(schedule: &EchoSchedule, now: SystemTime) -> Result<Option<SystemTime>> {
+    Ok(match schedule {
+        EchoSchedule::After(delay) => Some(now + *delay),
+        EchoSchedule::Every(interval) => Some(now + *interval),
+        EchoSchedule::Cron(expression) => CronExpr::parse(expression)?.next_after(now),
+    })
+}
+
+/// Server-side scheduler behind `EchoService::schedule_echo`.
+pub struct EchoScheduler {
+    jobs: Mutex<BTreeMap<String, ScheduledJob>>,
+    store: Arc<dyn JobStore>,
+    /// Serializes saves, so an older snapshot never overwrites a newer one
+    saving: AsyncMutex<()>,
+    max_jobs: usize,
+    /// Wakes the loop when a job is added that may be due earlier
+    wake: Notify,
+    /// What runs the echoes; set once the service stack is wired
+    target: OnceLock<Arc<dyn EchoService>>,
+    /// Names new jobs
+    ids: Arc<dyn IdGenerator>,
+}
+
+impl EchoScheduler {
+    /// Creates a scheduler saving its jobs to `store`.
+    pub fn new(store: Arc<dyn JobStore>, max_jobs: usize) -> Self {
+        Self {
+            jobs: Mutex::new(BTreeMap::new()),
+            store,
+            saving: AsyncMutex::new(()),
+            max_jobs,
+            wake: Notify::new(),
+            target: OnceLock::new(),
+            ids: Arc::new(UuidV4Ids),
+        }
+    }
+
+    /// Creates a scheduler as configured.
+    pub fn with_config(config: &SchedulerConfig) -> Self {
+        Self::new(config.job_store(), config.max_jobs)
+    }
+
+    /// Names new jobs with `ids` instead of random UUIDs.
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Runs due echoes through `target` (first call wins).
+    ///
+    /// Pass the stack that publishes events, so runs show up on `EchoEvents`.
+    pub fn set_target(&self, target: Arc<dyn EchoService>) {
+        let _ = self.target.set(target);
+    }
+
+    /// Restores the jobs saved in the store; returns how many were restored.
+    ///
+    /// Jobs scheduled since the start are kept.
+    pub async fn load(&self) -> Result<usize> {
+        let store = self.store.clone();
+        let saved = tokio::task::spawn_blocking(move || store.load())
+            .await
+            .map_err(|e| storage_error(format!("Job store load panicked: {}", e)))??;
+        let restored = saved.len();
+        let mut jobs = self.jobs.lock().unwrap();
+        for job in saved {
+            jobs.entry(job.id.clone()).or_insert(job);
+        }
+        drop(jobs);
+        self.wake.notify_one();
+        Ok(restored)
+    }
+
+    /// Adds a job echoing `message` on `schedule`.
+    pub async fn schedule(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        let next_run = next_run(&schedule, SystemTime::now())?.ok_or_else(|| Error::Validation {
+            message: format!("Schedule '{}' never runs", schedule),
+        })?;
+        let job = ScheduledJob {
+            id: self.ids.next_id(),
+            message,
+            schedule,
+            next_run,
+            runs: 0,
+        };
+
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            if jobs.len() >= self.max_jobs {
+                return Err(limit_reached(format!("Too many scheduled echoes (limit {})", self.max_jobs)));
+            }
+            jobs.insert(job.id.clone(), job.clone());
+        }
+        self.persist().await;
+
+        debug!("[EchoScheduler] Scheduled job {} ({}), first run at {:?}", job.id, job.schedule, job.next_run);
+        self.wake.notify_one();
+        Ok(ScheduledEcho { job_id: job.id, next_run })
+    }
+
+    /// Removes the job `job_id`; returns `false` if it wasn't pending.
+    pub async fn cancel(&self, job_id: &str) -> bool {
+        let cancelled = self.jobs.lock().unwrap().remove(job_id).is_some();
+        if cancelled {
+            self.persist().await;
+            debug!("[EchoScheduler] Cancelled job {}", job_id);
+        }
+        cancelled
+    }
+
+    /// Pending jobs, ordered by ID.
+    pub fn jobs(&self) -> Vec<ScheduledJob> {
+        self.jobs.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Runs the jobs due at `now`; returns how many ran.
+    ///
+    /// Failed echoes are logged; a recurring job stays scheduled either way.
+    /// Jobs are removed or advanced (and saved) only after they ran.
+    pub async fn run_due(&self, now: SystemTime) -> usize {
+        let due: Vec<ScheduledJob> = self.jobs.lock().unwrap().values().filter(|job| job.next_run <= now).cloned().collect();
+        if due.is_empty() {
+            return 0;
+        }
+        let ran = match self.target.get() {
+            Some(target) => {
+                for job in &due {
+                    match target.echo(job.message.clone()).await {
+                        Ok(_) => debug!("[EchoScheduler] Ran job {} (run {})", job.id, job.runs + 1),
+                        Err(e) => warn!("[EchoScheduler] Job {} failed: {}", job.id, e),
+                    }
+                }
+                due.len()
+            }
+            None => {
+                warn!("[EchoScheduler] {} job(s) due before the service was wired, skipped", due.len());
+                0
+            }
+        };
+
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            for job in &due {
+                let next = match &job.schedule {
+                    EchoSchedule::After(_) => None,
+                    schedule => next_run(schedule, now).ok().flatten(),
+                };
+                match next {
+                    Some(next) => {
+                        // Cancelled while it ran: stays cancelled
+                        if let Some(entry) = jobs.get_mut(&job.id) {
+                            entry.next_run = next;
+                            entry.runs += 1;
+                        }
+                    }
+                    None => {
+                        jobs.remove(&job.id);
+                    }
+                }
+            }
+        }
+        self.persist().await;
+        ran
+    }
+
+    /// When the earliest pending job is due.
+    pub fn next_due(&self) -> Option<SystemTime> {
+        self.jobs.lock().unwrap().values().map(|job| job.next_run).min()
+    }
+
+    /// Saves the current jobs on a blocking thread.
+    async fn persist(&self) {
+        let _saving = self.saving.lock().await;
+        // Taken under the save lock, so the last save holds the latest jobs
+        let jobs: Vec<ScheduledJob> = self.jobs.lock().unwrap().values().cloned().collect();
+        let store = self.store.clone();
+        let saved = tokio::task::spawn_blocking(move || store.save(&jobs))
+            .await
+            .map_err(|e| storage_error(format!("Job store save panicked: {}", e)))
+            .and_then(|saved| saved);
+        // The jobs stay scheduled in memory; only a restart would lose them
+        if let Err(e) = saved {
+            warn!("[EchoScheduler] Failed to save jobs: {}", e);
+        }
+    }
+}
+
+/// Restores the saved jobs and runs them when due until aborted.
+///
+/// Sleeps until the earliest job is due, or until a new job is scheduled.
+pub fn spawn_scheduler(module: &str, scheduler: Arc<EchoScheduler>) -> JoinHandle<()> {
+    spawn_tracked(module, "echo-scheduler", async move {
+        match scheduler.load().await {
+            Ok(0) => {}
+            Ok(restored) => info!("[EchoScheduler] ✅ Restored {} scheduled echo(es)", restored),
+            Err(e) => warn!("[EchoScheduler] Failed to restore jobs: {}", e),
+        }
+        loop {
+            let sleep = match scheduler.next_due() {
+                Some(due) => due.duration_since(SystemTime::now()).unwrap_or_default(),
+                // Nothing pending: wait for a job
+                None => Duration::from_secs(3600),
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(sleep) => {
+                    scheduler.run_due(SystemTime::now()).await;
+                }
+                _ = scheduler.wake.notified() => {}
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::SequentialIds;
+    use crate::service::EchoServiceImpl;
+
+    fn scheduler(store: Arc<dyn JobStore>) -> EchoScheduler {
+        let scheduler = EchoScheduler::new(store, 10);
+        scheduler.set_target(Arc::new(EchoServiceImpl::new()));
+        scheduler
+    }
+
+    #[tokio::test]
+    async fn test_one_off_and_recurring_jobs() {
+        let scheduler = scheduler(Arc::new(InMemoryJobStore::default()))
+            .with_id_generator(Arc::new(SequentialIds::new("job")));
+        let once = scheduler.schedule("once".into(), "1s".parse().unwrap()).await.unwrap();
+        let every = scheduler.schedule("tick".into(), "every 1m".parse().unwrap()).await.unwrap();
+        assert_eq!((once.job_id.as_str(), every.job_id.as_str()), ("job-1", "job-2"));
+        assert_eq!(scheduler.run_due(SystemTime::now()).await, 0);
+
+        let later = SystemTime::now() + Duration::from_secs(61);
+        assert_eq!(scheduler.run_due(later).await, 2);
+        let jobs = scheduler.jobs();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].id, every.job_id);
+        assert_eq!(jobs[0].runs, 1);
+        assert_eq!(jobs[0].next_run, later + Duration::from_secs(60));
+
+        assert!(!scheduler.cancel(&once.job_id).await);
+        assert!(scheduler.cancel(&every.job_id).await);
+        assert_eq!(scheduler.next_due(), None);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_survive_restart() {
+        let path = std::env::temp_dir().join(format!("echo-jobs-{}.json", uuid::Uuid::new_v4()));
+        let first = scheduler(Arc::new(FileJobStore::new(&path)));
+        let job = first.schedule("hello \"there\"".into(), "*/5 * * * *".parse().unwrap()).await.unwrap();
+        drop(first);
+
+        let second = scheduler(Arc::new(FileJobStore::new(&path)));
+        assert_eq!(second.load().await.unwrap(), 1);
+        let jobs = second.jobs();
+        assert_eq!(jobs[0].id, job.job_id);
+        assert_eq!(&*jobs[0].message, "hello \"there\"");
+        assert_eq!(jobs[0].schedule, EchoSchedule::Cron("*/5 * * * *".to_string()));
+        let _ = fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_and_excess_jobs() {
+        let scheduler = EchoScheduler::new(Arc::new(InMemoryJobStore::default()), 1);
+        assert!(scheduler.schedule("x".into(), EchoSchedule::Cron("61 * * * *".to_string())).await.is_err());
+        assert!(scheduler.schedule("x".into(), EchoSchedule::Cron("0 0 30 2 *".to_string())).await.is_err());
+        scheduler.schedule("x".into(), "5s".parse().unwrap()).await.unwrap();
+        let error = scheduler.schedule("y".into(), "5s".parse().unwrap()).await.unwrap_err();
+        assert!(echo_contract::is_overloaded(&error));
+        assert_eq!(echo_contract::retry_after(&error), None);
+    }
+
+    #[tokio::test]
+    async fn test_due_job_is_saved_after_it_ran() {
+        let store = Arc::new(InMemoryJobStore::default());
+        let scheduler = scheduler(store.clone());
+        scheduler.schedule("once".into(), "1s".parse().unwrap()).await.unwrap();
+        assert_eq!(store.load().unwrap().len(), 1);
+
+        assert_eq!(scheduler.run_due(SystemTime::now() + Duration::from_secs(2)).await, 1);
+        assert!(store.load().unwrap().is_empty());
+    }
+}
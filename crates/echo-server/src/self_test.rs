@@ -0,0 +1,206 @@
+//! Start-up Self-Test (Layer 3)
+//!
+//! # Architecture
+//!
+//! Before the module reports Ready (and before it is published to a
+//! registry or advertised over mDNS), it echoes a probe message through
+//! its own handlers, the way a caller would reach them:
+//!
+//! ```text
+//! EchoServerModule::start()
+//!     ↓
+//! self-test/direct      → Direct-path service (limits, isolation)
+//! self-test/grpc:<port> → <listen IP>:<port> per bound gRPC endpoint
+//!     ↓ each outcome
+//! HealthRegistry ("echo", check) + log → GET /health
+//! ```
+//!
+//! A misregistered handler (wrong service wired, gRPC server serving
+//! another module) fails here instead of on the first real call. With
+//! `fail_start` the failure aborts the start.
+//!
+//! Each port is dialed on the IP its server listens on (see
+//! [`SelfTestConfig::listen`]; wildcards over loopback). The probes are
+//! marked as such ([`RequestContext::probe`]), so they don't show up in
+//! the request metrics of either side.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use hsu_common::{Error, Result};
+use echo_api::{GatewayOptions, HealthRegistry, HealthStatus, grpc_echo_service};
+use echo_api_grpc::SigningKey;
+use echo_contract::{EchoService, RequestContext};
+use tracing::{info, warn};
+
+/// Message echoed by the self-test.
+const PROBE_MESSAGE: &str = "echo-server self-test";
+
+/// Self-test settings.
+#[derive(Debug, Clone)]
+pub struct SelfTestConfig {
+    /// Also call each bound gRPC endpoint over loopback.
+    pub grpc: bool,
+    /// Give up on a check after this long.
+    pub timeout: Duration,
+    /// Fail the module start if a check fails (otherwise only report it).
+    pub fail_start: bool,
+    /// Where the gRPC servers listen: a bound port is dialed on the IP of
+    /// the address with that port, or of the first dynamic (port 0) one
+    /// (127.0.0.1 if empty).
+    pub listen: Vec<SocketAddr>,
+}
+
+impl Default for SelfTestConfig {
+    fn default() -> Self {
+        Self {
+            grpc: true,
+            timeout: Duration::from_secs(2),
+            fail_start: true,
+            listen: Vec::new(),
+        }
+    }
+}
+
+/// What the self-test calls.
+#[derive(Clone)]
+pub struct SelfTestTargets {
+    /// The handler registered with the protocol servers.
+    pub handler: Arc<dyn EchoService>,
+    /// The Direct-path service, once the direct closure was enabled.
+    ///
+    /// Falls back to `handler` if it never was.
+    pub direct: Arc<OnceLock<Arc<dyn EchoService>>>,
+    /// Signs the gRPC probes, for servers that only accept signed requests.
+    pub signing_key: Option<Arc<SigningKey>>,
+}
+
+/// Runs the checks, records them for `module` and returns `Err` if any failed.
+pub async fn run_self_test(
+    module: &str,
+    targets: &SelfTestTargets,
+    grpc_ports: &[u16],
+    config: &SelfTestConfig,
+) -> Result<()> {
+    let mut failed = Vec::new();
+
+    let direct = targets.direct.get().cloned().unwrap_or_else(|| targets.handler.clone());
+    if !check(module, "self-test/direct", direct.as_ref(), config.timeout).await {
+        failed.push("direct".to_string());
+    }
+
+    if config.grpc {
+        for port in grpc_ports {
+            let options = GatewayOptions {
+                deadline: Some(config.timeout),
+                signing_key: targets.signing_key.clone(),
+                ..Default::default()
+            };
+            let service = grpc_echo_service(&probe_address(*port, &config.listen).to_string(), &options);
+            let name = format!("self-test/grpc:{}", port);
+            if !check(module, &name, service.as_ref(), config.timeout).await {
+                failed.push(format!("grpc:{}", port));
+            }
+            options.channel_pool.close();
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Protocol(format!("Self-test of module '{}' failed: {}", module, failed.join(", "))))
+    }
+}
+
+/// Where to dial the gRPC server bound to `port`.
+fn probe_address(port: u16, listen: &[SocketAddr]) -> SocketAddr {
+    let ip = listen
+        .iter()
+        .find(|address| address.port() == port)
+        .or_else(|| listen.iter().find(|address| address.port() == 0))
+        .map_or(IpAddr::V4(Ipv4Addr::LOCALHOST), |address| address.ip());
+    // A wildcard bind is reached over loopback
+    let ip = match ip {
+        IpAddr::V4(ip) if ip.is_unspecified() => Ipv4Addr::LOCALHOST.into(),
+        IpAddr::V6(ip) if ip.is_unspecified() => Ipv6Addr::LOCALHOST.into(),
+        ip => ip,
+    };
+    SocketAddr::new(ip, port)
+}
+
+/// Echoes the probe through `service`; records and logs the outcome.
+async fn check(module: &str, name: &str, service: &dyn EchoService, timeout: Duration) -> bool {
+    let started = Instant::now();
+    let probe = RequestContext::default().as_probe().scope(service.echo(Arc::from(PROBE_MESSAGE)));
+    let status = match tokio::time::timeout(timeout, probe).await {
+        // Response decoration may wrap the reply in a prefix/suffix
+        Ok(Ok(reply)) if reply.contains(PROBE_MESSAGE) => HealthStatus::Passing,
+        Ok(Ok(reply)) => HealthStatus::Failing(format!("unexpected reply {:?}", reply)),
+        Ok(Err(e)) => HealthStatus::Failing(e.to_string()),
+        Err(_) => HealthStatus::Failing(format!("no reply within {:?}", timeout)),
+    };
+    let latency = started.elapsed();
+    match &status {
+        HealthStatus::Passing => info!("[EchoServer] ✅ {} passed in {:?}", name, latency),
+        HealthStatus::Failing(reason) => warn!("[EchoServer] {} failed: {}", name, reason),
+    }
+    let passing = status == HealthStatus::Passing;
+    HealthRegistry::global().record(module, name, status, latency);
+    passing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::EchoServiceImpl;
+
+    #[tokio::test]
+    async fn test_direct_self_test_passes() {
+        let targets = SelfTestTargets {
+            handler: Arc::new(EchoServiceImpl::new()),
+            direct: Arc::new(OnceLock::new()),
+            signing_key: None,
+        };
+        let config = SelfTestConfig { grpc: false, ..Default::default() };
+
+        run_self_test("self-test-ok", &targets, &[], &config).await.unwrap();
+        let checks = HealthRegistry::global().checks();
+        assert!(checks.iter().any(|c| c.module == "self-test-ok" && c.name == "self-test/direct" && c.is_passing()));
+    }
+
+    #[test]
+    fn test_probe_address_follows_the_listen_address() {
+        let listen: Vec<SocketAddr> = vec!["10.0.0.5:50051".parse().unwrap(), "[::]:0".parse().unwrap()];
+        assert_eq!(probe_address(50051, &listen), "10.0.0.5:50051".parse().unwrap());
+        // A dynamic port, bound on the wildcard
+        assert_eq!(probe_address(41234, &listen), "[::1]:41234".parse().unwrap());
+        assert_eq!(probe_address(50051, &[]), "127.0.0.1:50051".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_self_test_is_not_counted() {
+        use echo_api::{SizeLabels, SizeMetrics, SizeMetricsEchoService};
+
+        let labels = SizeLabels { side: "server", protocol: "direct", service: "self-test-metrics" };
+        let handler = Arc::new(SizeMetricsEchoService::new(Arc::new(EchoServiceImpl::new()), labels, SizeMetrics::global()));
+        let targets = SelfTestTargets { handler, direct: Arc::new(OnceLock::new()), signing_key: None };
+        let config = SelfTestConfig { grpc: false, ..Default::default() };
+
+        run_self_test("self-test-metrics", &targets, &[], &config).await.unwrap();
+        assert_eq!(SizeMetrics::global().get(labels).requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_grpc_port_fails() {
+        let targets = SelfTestTargets {
+            handler: Arc::new(EchoServiceImpl::new()),
+            direct: Arc::new(OnceLock::new()),
+            signing_key: None,
+        };
+        let config = SelfTestConfig { timeout: Duration::from_millis(500), ..Default::default() };
+
+        // Nothing listens on port 1
+        let error = run_self_test("self-test-grpc", &targets, &[1], &config).await.unwrap_err();
+        assert!(error.to_string().contains("grpc:1"), "{}", error);
+    }
+}
@@ -11,10 +11,49 @@
 //! 3. **Implements trait**: Type-safe interface
 //! 4. **Testable**: Easy to unit test
 
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
 use async_trait::async_trait;
-use hsu_common::Result;
-use echo_contract::EchoService;
-use tracing::debug;
+use bytes::Bytes;
+use futures::StreamExt;
+use hsu_common::{Error, Result};
+use echo_contract::{
+    ByteStream, EchoAck, EchoSchedule, EchoService, FileDigest, HistoryExportFormat, HistoryImportReport, HistoryPage,
+    HistoryQuery, HistoryStream, RequestContext, ScheduledEcho, ServerInfo, SessionEcho, ECHO_MODULE_ID,
+    format_transforms, invalid_field, Message,
+};
+use sha2::{Digest, Sha256};
+use tracing::{debug, instrument};
+
+use crate::dedup::{DedupConfig, DedupWindow};
+use crate::history::{export_history, run_blocking, stream_history, HistoryStore};
+use crate::history_import::import_history;
+use crate::ids::{IdGenerator, UuidV4Ids};
+use crate::info::{instance_id, GIT_HASH, VERSION};
+use crate::scheduler::EchoScheduler;
+use crate::session::{InMemorySessionStore, SessionConfig, SessionStore};
+use crate::transform::apply_transforms;
+
+/// Configuration for the echo service implementation.
+#[derive(Debug, Clone)]
+pub struct EchoServiceConfig {
+    /// Idempotency-key window for `echo_reliable`.
+    pub dedup: DedupConfig,
+    /// Maximum total size accepted by `echo_file`.
+    pub max_file_bytes: u64,
+    /// Session expiry for `echo_with_session`.
+    pub sessions: SessionConfig,
+}
+
+impl Default for EchoServiceConfig {
+    fn default() -> Self {
+        Self {
+            dedup: DedupConfig::default(),
+            max_file_bytes: 64 * 1024 * 1024,
+            sessions: SessionConfig::default(),
+        }
+    }
+}
 
 /// Echo service implementation.
 ///
@@ -27,8 +66,8 @@ use tracing::debug;
 /// #[tokio::main]
 /// async fn main() {
 ///     let service = EchoServiceImpl::new();
-///     let result = service.echo("Hello!".to_string()).await.unwrap();
-///     assert_eq!(result, "Hello!");
+///     let result = service.echo("Hello!".into()).await.unwrap();
+///     assert_eq!(&*result, "Hello!");
 /// }
 /// ```
 pub struct EchoServiceImpl {
@@ -37,12 +76,98 @@ pub struct EchoServiceImpl {
     // - Cache clients
     // - Configuration
     // - Metrics
+    
+    /// Recently processed idempotency keys (for `echo_reliable`)
+    dedup: Mutex<DedupWindow>,
+    
+    /// Upper bound for `echo_file` payloads
+    max_file_bytes: u64,
+    
+    /// Per-session state (for `echo_with_session`)
+    sessions: Arc<dyn SessionStore>,
+    
+    /// Reported by `get_info`
+    module_id: String,
+    features: Vec<String>,
+    started: Instant,
+    
+    /// Runs `schedule_echo` jobs (scheduling fails without one)
+    scheduler: Option<Arc<EchoScheduler>>,
+    
+    /// Answers `get_history` (history queries fail without one)
+    history: Option<Arc<dyn HistoryStore>>,
+    
+    /// Names the sessions `echo_with_session` starts
+    ids: Arc<dyn IdGenerator>,
 }
 
 impl EchoServiceImpl {
     /// Creates a new echo service.
     pub fn new() -> Self {
-        Self {}
+        Self::with_config(EchoServiceConfig::default())
+    }
+
+    /// Creates a new echo service with custom configuration.
+    pub fn with_config(config: EchoServiceConfig) -> Self {
+        Self {
+            dedup: Mutex::new(DedupWindow::new(config.dedup)),
+            max_file_bytes: config.max_file_bytes,
+            sessions: Arc::new(InMemorySessionStore::new()),
+            module_id: ECHO_MODULE_ID.to_string(),
+            features: Vec::new(),
+            started: Instant::now(),
+            scheduler: None,
+            history: None,
+            ids: Arc::new(UuidV4Ids),
+        }
+    }
+    
+    /// Keeps session state in `store` instead of in memory.
+    pub fn with_session_store(mut self, store: Arc<dyn SessionStore>) -> Self {
+        self.sessions = store;
+        self
+    }
+    
+    /// Reports `module_id` and `features` from `get_info`.
+    pub fn with_info(mut self, module_id: impl Into<String>, features: Vec<String>) -> Self {
+        self.module_id = module_id.into();
+        self.features = features;
+        self
+    }
+    
+    /// Accepts `schedule_echo` jobs and hands them to `scheduler`.
+    pub fn with_scheduler(mut self, scheduler: Arc<EchoScheduler>) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+    
+    /// Answers `get_history` from `store`.
+    pub fn with_history(mut self, store: Arc<dyn HistoryStore>) -> Self {
+        self.history = Some(store);
+        self
+    }
+    
+    /// Names new sessions with `ids` instead of random UUIDs.
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+    
+    /// Returns the session store (e.g. to run the expiry sweeper on it).
+    pub fn session_store(&self) -> Arc<dyn SessionStore> {
+        self.sessions.clone()
+    }
+    
+    fn scheduler(&self) -> Result<&EchoScheduler> {
+        self.scheduler.as_deref().ok_or_else(|| Error::Protocol(
+            "Scheduled echoes are not enabled on this server".to_string(),
+        ))
+    }
+    
+    fn history(&self) -> Result<&Arc<dyn HistoryStore>> {
+        self.history.as_ref().ok_or_else(|| Error::Protocol(
+            "History is not enabled on this server".to_string(),
+        ))
     }
 }
 
@@ -63,7 +188,7 @@ impl EchoService for EchoServiceImpl {
     /// ```rust,ignore
     /// #[async_trait]
     /// impl EchoService for EchoServiceImpl {
-    ///     async fn echo(&self, message: String) -> Result<String> {
+    ///     async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
     ///         // Pure business logic!
     ///         Ok(message)
     ///     }
@@ -77,7 +202,10 @@ impl EchoService for EchoServiceImpl {
     /// - gRPC (cross-process)
     /// - HTTP (future)
     /// - Any other protocol!
-    async fn echo(&self, message: String) -> Result<String> {
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "echo",
+    ))]
+    async fn echo(&self, message: Arc<str>) -> Result<Arc<str>> {
         debug!("EchoService::echo called with: {}", message);
         
         // Business logic goes here
@@ -86,37 +214,399 @@ impl EchoService for EchoServiceImpl {
         // - Database access
         // - External API calls
         // - Complex computations
+
+        // Caller-selected transformations (upper, nfc, truncate:N, ...)
+        let transforms = RequestContext::current().transforms;
+        if transforms.is_empty() {
+            return Ok(message);
+        }
+        Ok(apply_transforms(&message, &transforms).into())
+    }
+
+    /// Echoes a binary payload.
+    ///
+    /// `Bytes` is reference-counted, so returning it doesn't copy the data.
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "echo_bytes",
+    ))]
+    async fn echo_bytes(&self, payload: Bytes) -> Result<Bytes> {
+        debug!("EchoService::echo_bytes called with {} bytes", payload.len());
+        Ok(payload)
+    }
+
+    /// Echoes the input message, deduplicating retries by idempotency key.
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "echo_reliable",
+    ))]
+    async fn echo_reliable(&self, message: Arc<str>, idempotency_key: String) -> Result<EchoAck> {
+        debug!("EchoService::echo_reliable called with key: {}", idempotency_key);
+        
+        if idempotency_key.is_empty() {
+            // Rendered in the caller's language (RequestContext::accept_language)
+            return Err(invalid_field("idempotency_key", Message::EmptyField.localize()));
+        }
+        
+        // The transforms shape the response, so a key reused with other
+        // transforms is another call
+        let transforms = RequestContext::current().transforms;
+        let dedup_key = if transforms.is_empty() {
+            idempotency_key.clone()
+        } else {
+            format!("{}\0{}", idempotency_key, format_transforms(&transforms))
+        };
+
+        // Claimed under one lock: a retry racing the first attempt waits for
+        // its response instead of echoing again. A failed attempt leaves the
+        // slot empty, so the next retry runs.
+        let slot = self.dedup.lock().unwrap().claim(dedup_key);
+        let mut processed = false;
+        let response = slot
+            .get_or_try_init(|| {
+                processed = true;
+                self.echo(message)
+            })
+            .await?
+            .clone();
+        if !processed {
+            debug!("Duplicate request for key {}, returning stored response", idempotency_key);
+        }
+        
+        Ok(EchoAck { message: response, idempotency_key, duplicate: !processed })
+    }
+
+    /// Hashes a chunked payload without buffering it.
+    ///
+    /// Rejects payloads larger than `max_file_bytes` as soon as the limit
+    /// is crossed, so a huge upload can't exhaust server memory or CPU.
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "echo_file",
+    ))]
+    async fn echo_file(&self, mut chunks: ByteStream) -> Result<FileDigest> {
+        let mut hasher = Sha256::new();
+        let mut byte_count = 0u64;
+        let mut chunk_count = 0u64;
+        
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            byte_count += chunk.len() as u64;
+            if byte_count > self.max_file_bytes {
+                return Err(Error::Validation {
+                    message: Message::FileTooLarge { limit: self.max_file_bytes }.localize(),
+                });
+            }
+            hasher.update(&chunk);
+            chunk_count += 1;
+        }
+        
+        debug!("EchoService::echo_file received {} bytes in {} chunks", byte_count, chunk_count);
+        
+        Ok(FileDigest {
+            byte_count,
+            chunk_count,
+            sha256: to_hex(&hasher.finalize()),
+        })
+    }
+
+    /// Echoes the input message and counts it in its session.
+    ///
+    /// An empty `session_id` starts a new session under a generated ID.
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "echo_with_session",
+    ))]
+    async fn echo_with_session(&self, session_id: String, message: Arc<str>) -> Result<SessionEcho> {
+        debug!("EchoService::echo_with_session called with session: {}", session_id);
+        
+        let session_id = if session_id.is_empty() { self.ids.next_id() } else { session_id };
+        
+        let message = self.echo(message).await?;
+        let state = self.sessions.touch(&session_id, SystemTime::now());
         
-        Ok(message)
+        Ok(SessionEcho {
+            message,
+            session_id,
+            count: state.count,
+            previous_seen: state.previous_seen,
+        })
+    }
+
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "get_info",
+    ))]
+    async fn get_info(&self) -> Result<ServerInfo> {
+        Ok(ServerInfo {
+            module_id: self.module_id.clone(),
+            instance_id: instance_id().to_string(),
+            version: VERSION.to_string(),
+            git_hash: GIT_HASH.to_string(),
+            uptime: self.started.elapsed(),
+            features: self.features.clone(),
+        })
+    }
+
+    /// Stores the job; the scheduler echoes the message when it is due.
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "schedule_echo",
+    ))]
+    async fn schedule_echo(&self, message: Arc<str>, schedule: EchoSchedule) -> Result<ScheduledEcho> {
+        debug!("EchoService::schedule_echo called with schedule: {}", schedule);
+        self.scheduler()?.schedule(message, schedule).await
+    }
+
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "cancel_scheduled_echo",
+    ))]
+    async fn cancel_scheduled_echo(&self, job_id: String) -> Result<bool> {
+        debug!("EchoService::cancel_scheduled_echo called with job: {}", job_id);
+        Ok(self.scheduler()?.cancel(&job_id).await)
+    }
+
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "get_history",
+    ))]
+    async fn get_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        debug!("EchoService::get_history called with query: {:?}", query);
+        run_blocking(self.history()?, move |store| store.query(&query)).await
+    }
+
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "stream_history",
+    ))]
+    async fn stream_history(&self, query: HistoryQuery) -> Result<HistoryStream> {
+        debug!("EchoService::stream_history called with query: {:?}", query);
+        // Fails here for a bad cursor, not on the first item
+        query.cursor_id()?;
+        Ok(stream_history(self.history()?.clone(), query))
+    }
+
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "export_history",
+    ))]
+    async fn export_history(&self, format: HistoryExportFormat, query: HistoryQuery) -> Result<ByteStream> {
+        debug!("EchoService::export_history called with format: {}, query: {:?}", format, query);
+        query.cursor_id()?;
+        Ok(export_history(self.history()?.clone(), format, query))
     }
+
+    #[instrument(name = "echo_service", level = "debug", skip_all, fields(
+        module_id = %self.module_id, method = "import_history",
+    ))]
+    async fn import_history(&self, format: HistoryExportFormat, data: ByteStream) -> Result<HistoryImportReport> {
+        debug!("EchoService::import_history called with format: {}", format);
+        import_history(self.history()?.clone(), format, data).await
+    }
+}
+
+
+/// Formats bytes as lowercase hex.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::history::InMemoryHistoryStore;
+    use crate::ids::SequentialIds;
 
     #[tokio::test]
     async fn test_echo_service() {
         let service = EchoServiceImpl::new();
         
-        let result = service.echo("Hello, Rust!".to_string()).await.unwrap();
-        assert_eq!(result, "Hello, Rust!");
+        let result = service.echo("Hello, Rust!".into()).await.unwrap();
+        assert_eq!(&*result, "Hello, Rust!");
     }
 
     #[tokio::test]
     async fn test_echo_empty() {
         let service = EchoServiceImpl::new();
         
-        let result = service.echo("".to_string()).await.unwrap();
-        assert_eq!(result, "");
+        let result = service.echo("".into()).await.unwrap();
+        assert_eq!(&*result, "");
+    }
+
+    #[tokio::test]
+    async fn test_echo_applies_request_transforms() {
+        let service = EchoServiceImpl::new();
+        let context = RequestContext::default().with_transforms(echo_contract::parse_transforms("nfkc,upper").unwrap());
+
+        let result = context.clone().scope(service.echo("ﬁle".into())).await.unwrap();
+        assert_eq!(&*result, "FILE");
+        let ack = context.scope(service.echo_reliable("ﬁle".into(), "k1".into())).await.unwrap();
+        assert_eq!(&*ack.message, "FILE");
     }
 
     #[tokio::test]
     async fn test_echo_unicode() {
         let service = EchoServiceImpl::new();
         
-        let result = service.echo("🦀 Rust! 🚀".to_string()).await.unwrap();
-        assert_eq!(result, "🦀 Rust! 🚀");
+        let result = service.echo("🦀 Rust! 🚀".into()).await.unwrap();
+        assert_eq!(&*result, "🦀 Rust! 🚀");
+    }
+
+    #[tokio::test]
+    async fn test_get_info() {
+        let service = EchoServiceImpl::new().with_info("echo-a", vec!["grpc".to_string()]);
+        
+        let info = service.get_info().await.unwrap();
+        assert_eq!(info.module_id, "echo-a");
+        assert_eq!(info.instance_id, instance_id());
+        assert_eq!(info.version, VERSION);
+        assert!(!info.git_hash.is_empty());
+        assert_eq!(info.features, vec!["grpc".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_echo_needs_scheduler() {
+        let service = EchoServiceImpl::new();
+        assert!(service.schedule_echo("x".into(), "5s".parse().unwrap()).await.is_err());
+        
+        let scheduler = Arc::new(EchoScheduler::with_config(&Default::default()));
+        let service = EchoServiceImpl::new().with_scheduler(scheduler.clone());
+        let job = service.schedule_echo("x".into(), "5s".parse().unwrap()).await.unwrap();
+        assert_eq!(scheduler.jobs().len(), 1);
+        assert!(service.cancel_scheduled_echo(job.job_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_get_history_needs_history() {
+        let service = EchoServiceImpl::new();
+        assert!(service.get_history(HistoryQuery::new()).await.is_err());
+        
+        let service = EchoServiceImpl::new().with_history(Arc::new(InMemoryHistoryStore::new()));
+        assert_eq!(service.get_history(HistoryQuery::new()).await.unwrap(), HistoryPage::default());
+        assert!(service.stream_history(HistoryQuery::new().with_cursor("x")).await.is_err());
+        assert!(service.export_history(HistoryExportFormat::Csv, HistoryQuery::new().with_cursor("x")).await.is_err());
+
+        let empty: ByteStream = Box::pin(futures::stream::empty());
+        let report = service.import_history(HistoryExportFormat::Ndjson, empty).await.unwrap();
+        assert_eq!(report, HistoryImportReport::default());
+    }
+
+    #[tokio::test]
+    async fn test_echo_shares_allocation() {
+        let service = EchoServiceImpl::new();
+        let message: Arc<str> = "x".repeat(1024).into();
+        
+        let result = service.echo(message.clone()).await.unwrap();
+        assert!(Arc::ptr_eq(&message, &result));
+    }
+
+    #[tokio::test]
+    async fn test_echo_bytes_non_utf8() {
+        let service = EchoServiceImpl::new();
+        let payload = Bytes::from_static(&[0xff, 0x00, 0xfe]);
+        
+        let result = service.echo_bytes(payload.clone()).await.unwrap();
+        assert_eq!(result, payload);
+    }
+
+    #[tokio::test]
+    async fn test_echo_reliable_deduplicates_retries() {
+        let service = EchoServiceImpl::new();
+        
+        let first = service.echo_reliable("Hello".into(), "k1".to_string()).await.unwrap();
+        assert!(!first.duplicate);
+        
+        let retry = service.echo_reliable("Hello".into(), "k1".to_string()).await.unwrap();
+        assert!(retry.duplicate);
+        assert_eq!(retry.message, first.message);
+    }
+
+    #[tokio::test]
+    async fn test_echo_reliable_key_is_per_transform_chain() {
+        let service = EchoServiceImpl::new();
+        let upper = RequestContext::default().with_transforms(echo_contract::parse_transforms("upper").unwrap());
+
+        let plain = service.echo_reliable("Hello".into(), "k1".to_string()).await.unwrap();
+        let shouted = upper.clone().scope(service.echo_reliable("Hello".into(), "k1".to_string())).await.unwrap();
+        assert_eq!((&*plain.message, plain.duplicate), ("Hello", false));
+        assert_eq!((&*shouted.message, shouted.duplicate), ("HELLO", false));
+
+        let retry = upper.scope(service.echo_reliable("Hello".into(), "k1".to_string())).await.unwrap();
+        assert_eq!((&*retry.message, retry.duplicate), ("HELLO", true));
+    }
+
+    #[tokio::test]
+    async fn test_echo_reliable_concurrent_retries_echo_once() {
+        let service = EchoServiceImpl::new();
+        
+        let (a, b) = tokio::join!(
+            service.echo_reliable("Hello".into(), "k1".to_string()),
+            service.echo_reliable("Hello".into(), "k1".to_string()),
+        );
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert_eq!([a.duplicate, b.duplicate].iter().filter(|duplicate| !**duplicate).count(), 1);
+        assert_eq!(a.message, b.message);
+    }
+
+    #[tokio::test]
+    async fn test_echo_file_digest() {
+        let service = EchoServiceImpl::new();
+        let chunks: Vec<Result<bytes::Bytes>> = vec![
+            Ok(bytes::Bytes::from_static(b"hello ")),
+            Ok(bytes::Bytes::from_static(b"world")),
+        ];
+        
+        let digest = service.echo_file(Box::pin(futures::stream::iter(chunks))).await.unwrap();
+        assert_eq!(digest.byte_count, 11);
+        assert_eq!(digest.chunk_count, 2);
+        assert_eq!(digest.sha256, "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+    }
+
+    #[tokio::test]
+    async fn test_echo_file_enforces_limit() {
+        let service = EchoServiceImpl::with_config(EchoServiceConfig {
+            max_file_bytes: 4,
+            ..Default::default()
+        });
+        let chunks: Vec<Result<bytes::Bytes>> = vec![Ok(bytes::Bytes::from_static(b"too large"))];
+        
+        assert!(service.echo_file(Box::pin(futures::stream::iter(chunks))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_echo_with_session_counts_per_session() {
+        let service = EchoServiceImpl::new();
+        
+        let first = service.echo_with_session("s1".to_string(), "Hello".into()).await.unwrap();
+        assert_eq!(first.count, 1);
+        assert_eq!(first.previous_seen, None);
+        
+        let second = service.echo_with_session("s1".to_string(), "Again".into()).await.unwrap();
+        assert_eq!(&*second.message, "Again");
+        assert_eq!(second.count, 2);
+        assert!(second.previous_seen.is_some());
+        
+        let other = service.echo_with_session("s2".to_string(), "Hi".into()).await.unwrap();
+        assert_eq!(other.count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_echo_with_session_names_new_sessions() {
+        let service = EchoServiceImpl::new().with_id_generator(Arc::new(SequentialIds::new("session")));
+        
+        let first = service.echo_with_session(String::new(), "Hi".into()).await.unwrap();
+        assert_eq!((first.session_id.as_str(), first.count), ("session-1", 1));
+        let again = service.echo_with_session(first.session_id, "Hi".into()).await.unwrap();
+        assert_eq!((again.session_id.as_str(), again.count), ("session-1", 2));
+        assert_eq!(service.echo_with_session(String::new(), "Hi".into()).await.unwrap().session_id, "session-2");
+    }
+
+    #[tokio::test]
+    async fn test_echo_reliable_requires_key() {
+        let service = EchoServiceImpl::new();
+        
+        assert!(service.echo_reliable("Hello".into(), String::new()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validation_speaks_the_callers_language() {
+        let service = EchoServiceImpl::new();
+        let context = RequestContext::new(Default::default()).with_accept_language("es");
+
+        let error = context.scope(service.echo_reliable("Hola".into(), String::new())).await.unwrap_err();
+        let violation = echo_contract::field_violation(&error).unwrap();
+        assert_eq!(violation.description, "no debe estar vacío");
     }
 }
 
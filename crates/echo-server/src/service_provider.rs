@@ -4,9 +4,34 @@
 //!
 //! This is the **server-specific** service provider!
 //! - Provides: EchoServiceHandlers (for registration)
+//! - Provides: the ID generator the module's services share
 //! - Does NOT provide: EchoServiceGateways (server doesn't need them!)
 
+use std::sync::Arc;
+
+use crate::ids::{IdGenerator, UuidV4Ids};
+
 /// Service provider for Echo server module.
 ///
 #[derive(Clone)]
-pub struct EchoServerServiceProvider {}
+pub struct EchoServerServiceProvider {
+    ids: Arc<dyn IdGenerator>,
+}
+
+impl EchoServerServiceProvider {
+    /// Creates a provider handing out `ids`.
+    pub fn new(ids: Arc<dyn IdGenerator>) -> Self {
+        Self { ids }
+    }
+
+    /// Generates request, session and job IDs.
+    pub fn ids(&self) -> Arc<dyn IdGenerator> {
+        self.ids.clone()
+    }
+}
+
+impl Default for EchoServerServiceProvider {
+    fn default() -> Self {
+        Self::new(Arc::new(UuidV4Ids))
+    }
+}
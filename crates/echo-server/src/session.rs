@@ -0,0 +1,215 @@
+//! Per-session state for `echo_with_session`.
+//!
+//! # Architecture
+//!
+//! The session is identified by a caller-chosen ID, not by the connection,
+//! so the same session survives reconnects and works over every protocol:
+//!
+//! ```text
+//! Client (Direct)  ─┐
+//!                   ├─ echo_with_session("s1", ...) ─→ SessionStore["s1"] { count, last_seen }
+//! Client (gRPC)    ─┘                                        ↑
+//!                                          SessionSweeper ───┘ drops idle sessions
+//! ```
+//!
+//! Stores are pluggable through [`SessionStore`]; the default is the
+//! in-process [`InMemorySessionStore`]. A store shared between server
+//! replicas (Redis, a database) would implement the same trait.
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! type SessionStore interface {
+//!     Touch(sessionID string, now time.Time) SessionState
+//!     RemoveIdle(before time.Time) int
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use echo_api::spawn_tracked;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// Session expiry settings.
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    /// Sessions without a message for this long are removed.
+    pub idle_timeout: Duration,
+    /// How often the sweeper looks for idle sessions.
+    pub sweep_interval: Duration,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(300),
+            sweep_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State kept per session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionState {
+    /// Number of messages received.
+    pub count: u64,
+    /// When the first message was received.
+    pub created_at: SystemTime,
+    /// When the latest message was received.
+    pub last_seen: SystemTime,
+    /// When the message before the latest one was received.
+    pub previous_seen: Option<SystemTime>,
+}
+
+/// Storage for session state.
+///
+/// # Rust Learning Note
+///
+/// Methods are synchronous: the update must be atomic per session, and
+/// an in-memory store only needs a short lock. A remote store would do
+/// the same with an atomic server-side operation (e.g. a Lua script).
+pub trait SessionStore: Send + Sync {
+    /// Records a message for `session_id` at `now` and returns the updated
+    /// state, creating the session if needed.
+    fn touch(&self, session_id: &str, now: SystemTime) -> SessionState;
+
+    /// Returns the state of `session_id`, if it exists.
+    fn get(&self, session_id: &str) -> Option<SessionState>;
+
+    /// Removes sessions last seen before `idle_before`; returns how many.
+    fn remove_idle(&self, idle_before: SystemTime) -> usize;
+
+    /// Returns the number of live sessions.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no sessions.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Session store backed by a `HashMap` in this process.
+#[derive(Default)]
+pub struct InMemorySessionStore {
+    sessions: Mutex<HashMap<String, SessionState>>,
+}
+
+impl InMemorySessionStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for InMemorySessionStore {
+    fn touch(&self, session_id: &str, now: SystemTime) -> SessionState {
+        let mut sessions = self.sessions.lock().unwrap();
+        let state = sessions
+            .entry(session_id.to_string())
+            .and_modify(|state| {
+                state.count += 1;
+                state.previous_seen = Some(state.last_seen);
+                state.last_seen = now;
+            })
+            .or_insert(SessionState {
+                count: 1,
+                created_at: now,
+                last_seen: now,
+                previous_seen: None,
+            });
+        state.clone()
+    }
+
+    fn get(&self, session_id: &str) -> Option<SessionState> {
+        self.sessions.lock().unwrap().get(session_id).cloned()
+    }
+
+    fn remove_idle(&self, idle_before: SystemTime) -> usize {
+        let mut sessions = self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|_, state| state.last_seen >= idle_before);
+        before - sessions.len()
+    }
+
+    fn len(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+}
+
+/// Periodically removes idle sessions from `store`.
+///
+/// The task is tracked under `module` (see `echo_api::TaskRegistry`);
+/// abort the returned handle to stop it.
+pub fn spawn_session_sweeper(
+    module: &str,
+    store: Arc<dyn SessionStore>,
+    config: &SessionConfig,
+) -> JoinHandle<()> {
+    let config = config.clone();
+    spawn_tracked(module, "session-sweeper", async move {
+        let mut ticks = tokio::time::interval(config.sweep_interval);
+        loop {
+            ticks.tick().await;
+            let Some(idle_before) = SystemTime::now().checked_sub(config.idle_timeout) else {
+                continue;
+            };
+            let removed = store.remove_idle(idle_before);
+            if removed > 0 {
+                debug!("[SessionSweeper] Expired {} idle session(s), {} left", removed, store.len());
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_touch_counts_and_tracks_previous() {
+        let store = InMemorySessionStore::new();
+        let t0 = SystemTime::now();
+        let t1 = t0 + Duration::from_secs(1);
+
+        let first = store.touch("s1", t0);
+        assert_eq!(first.count, 1);
+        assert_eq!(first.previous_seen, None);
+
+        let second = store.touch("s1", t1);
+        assert_eq!(second.count, 2);
+        assert_eq!(second.previous_seen, Some(t0));
+        assert_eq!(second.created_at, t0);
+
+        assert_eq!(store.touch("s2", t1).count, 1);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_remove_idle() {
+        let store = InMemorySessionStore::new();
+        let t0 = SystemTime::now();
+        store.touch("old", t0);
+        store.touch("new", t0 + Duration::from_secs(60));
+
+        assert_eq!(store.remove_idle(t0 + Duration::from_secs(30)), 1);
+        assert!(store.get("old").is_none());
+        assert!(store.get("new").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sweeper_expires_sessions() {
+        let store = Arc::new(InMemorySessionStore::new());
+        store.touch("s1", SystemTime::now() - Duration::from_secs(10));
+
+        let sweeper = spawn_session_sweeper("echo", store.clone(), &SessionConfig {
+            idle_timeout: Duration::from_secs(5),
+            sweep_interval: Duration::from_millis(10),
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        sweeper.abort();
+
+        assert!(store.is_empty());
+    }
+}
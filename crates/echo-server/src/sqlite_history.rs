@@ -0,0 +1,332 @@
+//! SQLite History Store (Layer 3, `sqlite` feature)
+//!
+//! # Architecture
+//!
+//! A [`HistoryStore`] in a single SQLite file, so the history and the
+//! unpublished events survive restarts:
+//!
+//! ```text
+//! append(entry, event) ── BEGIN ─→ INSERT INTO history ─→ INSERT INTO outbox ─→ COMMIT
+//! query(q)             ── SELECT ... FROM history WHERE id < cursor ... ORDER BY id DESC
+//! pending_events(n)    ── SELECT ... FROM outbox ORDER BY id LIMIT n
+//! mark_published(ids)  ── DELETE FROM outbox WHERE id = ?
+//! prune(before, keep)  ── DELETE FROM history WHERE at_unix_ms < ? / id <= (newest beyond keep)
+//! compact()            ── VACUUM (once a quarter of the file is free) + WAL checkpoint, on its own connection
+//! import(entries)      ── BEGIN ─→ per entry: SELECT COUNT(*) (same call) / INSERT INTO history ─→ COMMIT
+//! ```
+//!
+//! `AUTOINCREMENT` never hands out a pruned ID again, so cursors stay
+//! valid across pruning.
+//!
+//! The schema is versioned with `PRAGMA user_version`; [`migrate`]
+//! (run on module start) applies the missing [`MIGRATIONS`] in order, each
+//! in its own transaction.
+//!
+//! [`migrate`]: HistoryStore::migrate
+//!
+//! ## Comparison with Golang
+//!
+//! | Golang                           | Rust                           |
+//! |----------------------------------|--------------------------------|
+//! | `database/sql` + `mattn/go-sqlite3` | `rusqlite` (bundled SQLite) |
+//! | `*sql.DB` (pooled, goroutine-safe) | `Mutex<Connection>`          |
+//! | `tx, _ := db.Begin()`            | `conn.transaction()?`          |
+//!
+//! # Rust Learning Note
+//!
+//! `rusqlite::Connection` is `Send` but not `Sync`: one connection can't
+//! be used from two threads at once. A `Mutex` makes the store `Sync`,
+//! and SQLite serializes writers anyway. Every call blocks on that lock
+//! and on the disk, so async callers go through `spawn_blocking` (see
+//! `history::run_blocking`) rather than calling the store on a worker.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use hsu_common::{Error, Result};
+use echo_contract::{storage_error, EchoEvent, EchoMethod, HistoryImportReport, HistoryPage, HistoryQuery};
+use rusqlite::{params, Connection, Row};
+use tracing::info;
+
+use crate::history::{duplicate_key, page, DuplicateKey, HistoryEntry, HistoryStore};
+use crate::outbox::OutboxEvent;
+
+/// Schema migrations; migration `i` upgrades `user_version` `i` to `i + 1`.
+///
+/// Append only: released migrations never change.
+pub const MIGRATIONS: &[&str] = &[
+    // 1: history and its outbox (outbox rows share their entry's ID)
+    "CREATE TABLE history (
+        id            INTEGER PRIMARY KEY AUTOINCREMENT,
+        method        TEXT    NOT NULL,
+        message       TEXT    NOT NULL,
+        request_bytes INTEGER NOT NULL,
+        success       INTEGER NOT NULL,
+        at_unix_ms    INTEGER NOT NULL
+    );
+    CREATE TABLE outbox (
+        id            INTEGER PRIMARY KEY,
+        method        TEXT    NOT NULL,
+        request_bytes INTEGER NOT NULL,
+        success       INTEGER NOT NULL,
+        at_unix_ms    INTEGER NOT NULL
+    );",
+    // 2: time-range queries
+    "CREATE INDEX history_at ON history (at_unix_ms);",
+    // 3: generated record IDs (empty for the entries recorded before)
+    "ALTER TABLE history ADD COLUMN record_id TEXT NOT NULL DEFAULT '';",
+];
+
+/// History kept in an SQLite database file.
+pub struct SqliteHistoryStore {
+    path: PathBuf,
+    conn: Mutex<Connection>,
+}
+
+impl SqliteHistoryStore {
+    /// Opens (or creates) the database at `path`.
+    ///
+    /// The schema is created by [`migrate`](HistoryStore::migrate).
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let conn = connect(&path)?;
+        Ok(Self { path, conn: Mutex::new(conn) })
+    }
+
+    /// A new connection to the same database, for maintenance.
+    fn connect(&self) -> Result<Connection> {
+        connect(&self.path)
+    }
+
+    /// The schema version of the database.
+    pub fn schema_version(&self) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| self.error(e))?;
+        Ok(version as usize)
+    }
+
+    fn error(&self, detail: impl std::fmt::Display) -> Error {
+        database_error(&self.path, detail)
+    }
+}
+
+impl HistoryStore for SqliteHistoryStore {
+    fn migrate(&self) -> Result<()> {
+        let from = self.schema_version()?;
+        if from > MIGRATIONS.len() {
+            return Err(self.error(format!(
+                "schema version {} is newer than this server ({})", from, MIGRATIONS.len()
+            )));
+        }
+        let mut conn = self.conn.lock().unwrap();
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(from) {
+            let tx = conn.transaction().map_err(|e| self.error(e))?;
+            tx.execute_batch(migration).map_err(|e| self.error(e))?;
+            tx.pragma_update(None, "user_version", (version + 1) as i64).map_err(|e| self.error(e))?;
+            tx.commit().map_err(|e| self.error(e))?;
+        }
+        if from < MIGRATIONS.len() {
+            info!("[SqliteHistoryStore] ✅ Migrated {} from schema version {} to {}",
+                self.path.display(), from, MIGRATIONS.len());
+        }
+        Ok(())
+    }
+
+    fn append(&self, entry: HistoryEntry, event: EchoEvent) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+        // Both rows or neither
+        let tx = conn.transaction().map_err(|e| self.error(e))?;
+        tx.execute(
+            "INSERT INTO history (method, message, request_bytes, success, at_unix_ms, record_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                entry.method.as_str(), entry.message, entry.request_bytes as i64, entry.success, unix_ms(entry.at),
+                entry.record_id,
+            ],
+        ).map_err(|e| self.error(e))?;
+        let id = tx.last_insert_rowid();
+        tx.execute(
+            "INSERT INTO outbox (id, method, request_bytes, success, at_unix_ms) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, event.method.as_str(), event.request_bytes as i64, event.success, unix_ms(event.at)],
+        ).map_err(|e| self.error(e))?;
+        tx.commit().map_err(|e| self.error(e))?;
+        Ok(id as u64)
+    }
+
+    fn query(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+        let page_size = query.page_size();
+        let conn = self.conn.lock().unwrap();
+        // A NULL parameter switches its filter off
+        let mut statement = conn.prepare(
+            "SELECT id, method, message, request_bytes, success, at_unix_ms, record_id FROM history
+             WHERE (?1 IS NULL OR id < ?1)
+               AND (?2 IS NULL OR at_unix_ms >= ?2)
+               AND (?3 IS NULL OR at_unix_ms < ?3)
+               AND (?4 IS NULL OR instr(message, ?4) > 0)
+             ORDER BY id DESC LIMIT ?5",
+        ).map_err(|e| self.error(e))?;
+        let rows = statement.query_map(
+            params![
+                query.cursor_id()?.map(|id| id as i64),
+                query.since.map(unix_ms),
+                query.until.map(unix_ms),
+                query.contains,
+                (page_size + 1) as i64,
+            ],
+            entry_from_row,
+        ).map_err(|e| self.error(e))?;
+        let entries: Vec<HistoryEntry> = rows.collect::<rusqlite::Result<_>>().map_err(|e| self.error(e))?;
+        Ok(page(entries.into_iter(), page_size))
+    }
+
+    fn pending_events(&self, limit: usize) -> Result<Vec<OutboxEvent>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT id, method, request_bytes, success, at_unix_ms FROM outbox ORDER BY id LIMIT ?1",
+        ).map_err(|e| self.error(e))?;
+        let rows = statement.query_map([limit as i64], |row| {
+            Ok(OutboxEvent {
+                id: row.get::<_, i64>(0)? as u64,
+                event: EchoEvent {
+                    method: method_column(row, 1)?,
+                    request_bytes: row.get::<_, i64>(2)? as u64,
+                    success: row.get(3)?,
+                    at: from_unix_ms(row.get(4)?),
+                },
+            })
+        }).map_err(|e| self.error(e))?;
+        rows.collect::<rusqlite::Result<_>>().map_err(|e| self.error(e))
+    }
+
+    fn mark_published(&self, ids: &[u64]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| self.error(e))?;
+        {
+            let mut statement = tx.prepare("DELETE FROM outbox WHERE id = ?1").map_err(|e| self.error(e))?;
+            for id in ids {
+                statement.execute([*id as i64]).map_err(|e| self.error(e))?;
+            }
+        }
+        tx.commit().map_err(|e| self.error(e))
+    }
+
+    fn prune(&self, before: Option<SystemTime>, keep_newest: Option<usize>) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| self.error(e))?;
+        let mut pruned = 0;
+        if let Some(before) = before {
+            pruned += tx.execute("DELETE FROM history WHERE at_unix_ms < ?1", [unix_ms(before)])
+                .map_err(|e| self.error(e))?;
+        }
+        if let Some(keep_newest) = keep_newest {
+            // NULL (fewer rows than kept) matches nothing
+            pruned += tx.execute(
+                "DELETE FROM history WHERE id <= (SELECT id FROM history ORDER BY id DESC LIMIT 1 OFFSET ?1)",
+                [keep_newest as i64],
+            ).map_err(|e| self.error(e))?;
+        }
+        tx.commit().map_err(|e| self.error(e))?;
+        Ok(pruned as u64)
+    }
+
+    fn compact(&self) -> Result<()> {
+        // A connection of its own: VACUUM can take a while, and appends
+        // must not queue behind it on `self.conn`. They wait in SQLite
+        // instead (up to the busy timeout), and only while it rewrites.
+        let conn = self.connect()?;
+        let pages = |pragma: &str| conn.query_row(pragma, [], |row| row.get::<_, i64>(0)).map_err(|e| self.error(e));
+        let (free, total) = (pages("PRAGMA freelist_count")?, pages("PRAGMA page_count")?);
+        // VACUUM rewrites the whole file: only worth it for a lot of free space
+        if free > 0 && free * 4 >= total {
+            conn.execute_batch("VACUUM").map_err(|e| self.error(e))?;
+            info!("[SqliteHistoryStore] Compacted {}: {} of {} pages were free", self.path.display(), free, total);
+        }
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(())).map_err(|e| self.error(e))
+    }
+
+    fn import(&self, entries: Vec<HistoryEntry>) -> Result<HistoryImportReport> {
+        let mut conn = self.conn.lock().unwrap();
+        // All or nothing, like the validation before it
+        let tx = conn.transaction().map_err(|e| self.error(e))?;
+        let mut report = HistoryImportReport::default();
+        {
+            // at_unix_ms first: the history_at index finds the candidates
+            let mut matching = tx.prepare(
+                "SELECT COUNT(*) FROM history
+                 WHERE at_unix_ms = ?1 AND method = ?2 AND message = ?3 AND request_bytes = ?4 AND success = ?5
+                   AND record_id = ?6",
+            ).map_err(|e| self.error(e))?;
+            let mut insert = tx.prepare(
+                "INSERT INTO history (at_unix_ms, method, message, request_bytes, success, record_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            ).map_err(|e| self.error(e))?;
+            let mut seen = HashSet::new();
+            // How many entries of `entries` so far have each key
+            let mut occurrences: HashMap<DuplicateKey, i64> = HashMap::new();
+            for entry in entries {
+                let key = duplicate_key(&entry);
+                if !seen.insert((entry.id, key.clone())) {
+                    report.duplicates += 1;
+                    continue;
+                }
+                let occurrence = occurrences.entry(key).or_default();
+                *occurrence += 1;
+                let row = params![
+                    unix_ms(entry.at), entry.method.as_str(), entry.message, entry.request_bytes as i64, entry.success,
+                    entry.record_id,
+                ];
+                // Counts the rows this import added too: the n-th occurrence
+                // is new only if fewer than n rows match
+                if matching.query_row(row, |row| row.get::<_, i64>(0)).map_err(|e| self.error(e))? >= *occurrence {
+                    report.duplicates += 1;
+                } else {
+                    insert.execute(row).map_err(|e| self.error(e))?;
+                    report.imported += 1;
+                }
+            }
+        }
+        tx.commit().map_err(|e| self.error(e))?;
+        Ok(report)
+    }
+}
+
+fn connect(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path).map_err(|e| database_error(path, e))?;
+    // Readers (the relay) don't block behind a long write
+    conn.pragma_update(None, "journal_mode", "WAL").map_err(|e| database_error(path, e))?;
+    conn.busy_timeout(Duration::from_secs(5)).map_err(|e| database_error(path, e))?;
+    Ok(conn)
+}
+
+fn database_error(path: &Path, detail: impl std::fmt::Display) -> Error {
+    storage_error(format!("History database {}: {}", path.display(), detail))
+}
+
+fn entry_from_row(row: &Row<'_>) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: row.get::<_, i64>(0)? as u64,
+        method: method_column(row, 1)?,
+        message: row.get(2)?,
+        request_bytes: row.get::<_, i64>(3)? as u64,
+        success: row.get(4)?,
+        at: from_unix_ms(row.get(5)?),
+        record_id: row.get(6)?,
+    })
+}
+
+fn method_column(row: &Row<'_>, index: usize) -> rusqlite::Result<EchoMethod> {
+    let name: String = row.get(index)?;
+    EchoMethod::from_name(&name).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, format!("unknown method '{}'", name).into())
+    })
+}
+
+fn unix_ms(at: SystemTime) -> i64 {
+    at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64)
+}
+
+fn from_unix_ms(ms: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(ms.max(0) as u64)
+}
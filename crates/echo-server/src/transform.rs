@@ -0,0 +1,132 @@
+//! Echo transformations (see [`echo_contract::transform`]).
+//!
+//! # Architecture
+//!
+//! The contract names the transformations a caller may request; this is
+//! where the server carries them out, on every text reply:
+//!
+//! ```text
+//! EchoServiceImpl::echo(message)
+//!     ↓ RequestContext::current().transforms = [Nfkc, Lower(Turkic), Truncate(20)]
+//! apply_transforms(message) ── "ＩSTANBUL ﬁles" → "ıstanbul files" ──→ reply
+//! ```
+//!
+//! - **Casing** uses the full Unicode mappings of `str::to_uppercase` /
+//!   `to_lowercase` (one character may become several, word-final sigma
+//!   lowercases to `ς`). Turkic locales first map the dotted and dotless
+//!   i's, which the default rules get wrong for Turkish text.
+//! - **Normalization** comes from `unicode-normalization`.
+//! - **Truncation** counts extended grapheme clusters
+//!   (`unicode-segmentation`), so an emoji with its modifiers or a letter
+//!   with its combining accents is kept or dropped as a whole.
+//!
+//! ## Golang Equivalent
+//!
+//! ```go
+//! strings.ToUpperSpecial(unicode.TurkishCase, s)
+//! norm.NFKC.String(s)
+//! uniseg.NewGraphemes(s) // first n clusters
+//! ```
+
+use echo_contract::{CaseLocale, EchoTransform};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Applies `transforms` to `message` in order.
+pub fn apply_transforms(message: &str, transforms: &[EchoTransform]) -> String {
+    transforms.iter().fold(message.to_string(), |text, transform| apply(&text, *transform))
+}
+
+/// Applies one transformation.
+pub fn apply(text: &str, transform: EchoTransform) -> String {
+    match transform {
+        EchoTransform::Upper(CaseLocale::Root) => text.to_uppercase(),
+        EchoTransform::Upper(CaseLocale::Turkic) => text.replace('i', "İ").to_uppercase(),
+        EchoTransform::Lower(CaseLocale::Root) => text.to_lowercase(),
+        EchoTransform::Lower(CaseLocale::Turkic) => {
+            // "I" + combining dot above is a decomposed "İ"
+            text.replace("I\u{307}", "i").replace('İ', "i").replace('I', "ı").to_lowercase()
+        }
+        EchoTransform::Nfc => text.nfc().collect(),
+        EchoTransform::Nfkc => text.nfkc().collect(),
+        EchoTransform::Truncate(length) => text.graphemes(true).take(length).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_contract::parse_transforms;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_transformations() {
+        let cases = [
+            ("upper", "straße", "STRASSE"),
+            ("lower", "ΟΔΟΣ", "οδος"),
+            ("upper:tr", "istanbul ılık", "İSTANBUL ILIK"),
+            ("lower:tr", "İSTANBUL ILIK", "istanbul ılık"),
+            ("lower", "İ", "i\u{307}"),
+            ("nfc", "e\u{301}", "é"),
+            ("nfkc", "ﬁle ①", "file 1"),
+            ("truncate:3", "e\u{301}👍🏽ab", "e\u{301}👍🏽a"),
+            ("truncate:9", "short", "short"),
+            ("nfkc,lower:tr,truncate:8", "ＩSTANBUL ﬁles", "ıstanbul"),
+        ];
+        for (chain, input, expected) in cases {
+            assert_eq!(apply_transforms(input, &parse_transforms(chain).unwrap()), expected, "{}", chain);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_normalization_is_idempotent(text in "\\PC*") {
+            let nfc = apply(&text, EchoTransform::Nfc);
+            let nfkc = apply(&text, EchoTransform::Nfkc);
+            prop_assert_eq!(apply(&nfc, EchoTransform::Nfc), nfc.clone());
+            prop_assert_eq!(apply(&nfkc, EchoTransform::Nfkc), nfkc.clone());
+            // NFKC text is already NFC, and NFKC of NFC is NFKC
+            prop_assert_eq!(apply(&nfkc, EchoTransform::Nfc), nfkc.clone());
+            prop_assert_eq!(apply(&nfc, EchoTransform::Nfkc), nfkc);
+        }
+
+        #[test]
+        fn prop_truncation_keeps_whole_graphemes(text in "\\PC*", length in 0usize..12) {
+            let truncated = apply(&text, EchoTransform::Truncate(length));
+            prop_assert!(text.starts_with(&truncated));
+            let kept = truncated.graphemes(true).count();
+            prop_assert_eq!(kept, length.min(text.graphemes(true).count()));
+            // The rest starts on a cluster boundary: nothing was split
+            let rest = &text[truncated.len()..];
+            let clusters: Vec<&str> = truncated.graphemes(true).chain(rest.graphemes(true)).collect();
+            prop_assert_eq!(clusters.len(), text.graphemes(true).count());
+        }
+
+        #[test]
+        fn prop_casing_round_trips_ascii(text in "[ -~]*") {
+            for locale in [CaseLocale::Root, CaseLocale::Turkic] {
+                let upper = apply(&text, EchoTransform::Upper(locale));
+                let lower = apply(&upper, EchoTransform::Lower(locale));
+                prop_assert_eq!(apply(&lower, EchoTransform::Upper(locale)), upper);
+            }
+            prop_assert_eq!(apply(&text, EchoTransform::Lower(CaseLocale::Root)), text.to_ascii_lowercase());
+        }
+
+        #[test]
+        fn prop_chains_round_trip_as_text(chain in prop::collection::vec(transform(), 0..8)) {
+            let text = echo_contract::format_transforms(&chain);
+            prop_assert_eq!(parse_transforms(&text).unwrap(), chain);
+        }
+    }
+
+    fn transform() -> impl Strategy<Value = EchoTransform> {
+        let locale = prop_oneof![Just(CaseLocale::Root), Just(CaseLocale::Turkic)];
+        prop_oneof![
+            locale.clone().prop_map(EchoTransform::Upper),
+            locale.prop_map(EchoTransform::Lower),
+            Just(EchoTransform::Nfc),
+            Just(EchoTransform::Nfkc),
+            (0usize..1000).prop_map(EchoTransform::Truncate),
+        ]
+    }
+}
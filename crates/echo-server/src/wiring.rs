@@ -11,52 +11,288 @@
 //!
 //! This is MODULE-specific, not application-specific!
 
-use std::sync::{Arc, Once};
+use std::sync::{Arc, Once, OnceLock};
 use std::collections::HashMap;
+use std::time::Duration;
 use hsu_common::{ModuleID, Result};
 use hsu_module_api::{
     ServiceProviderHandle, ServiceConnector, 
-    ProtocolToServicesMap, HandlersRegistrarOptions,
+    ProtocolToServicesMap, HandlersRegistrarOptions, DirectClosureEnablerOptions,
     new_module_descriptor, register_module, Module, 
 };
-use echo_contract::{EchoServiceHandlers, EchoServiceGateways};
-use crate::service::EchoServiceImpl;
+use echo_contract::{echo_module_id, EchoService, EchoServiceHandlers, EchoServiceGateways, EchoServiceId};
+use crate::service::{EchoServiceConfig, EchoServiceImpl};
+use crate::session::{InMemorySessionStore, SessionStore};
+use crate::history::{HistoryEchoService, HistoryStore};
+use crate::outbox::OutboxRelay;
+use crate::retention::{HistoryRetention, RetentionConfig};
+use crate::decoration::{DecoratedEchoService, ResponseDecoration};
+use crate::ids::IdGenerator;
 use crate::module::EchoServerModule;
-use echo_api::{new_echo_handlers_registrar, echo_direct_closure_enabler};
-use tracing::{debug, info};
+use crate::scheduler::{EchoScheduler, SchedulerConfig};
+use crate::self_test::{SelfTestConfig, SelfTestTargets};
+use echo_api::{
+    new_echo_handlers_registrar, echo_direct_closure_enabler, DirectClosureEnablerFn, HandlersRegistrarFn,
+    limit_direct_handlers, DirectConcurrencyLimits, CancellationToken,
+    isolate_direct_handlers, DirectIsolationConfig,
+    PanicGuardEchoService, PanicGuardModule, PanicPolicy, StartupCoordinator,
+    PriorityEchoService, PriorityLanesConfig,
+    ByteQuotaEchoService, ByteQuotaConfig,
+    SlowStart, SlowStartConfig, SlowStartEchoService,
+    MaintenanceMode, MaintenanceModeEchoService,
+    TrafficSplit, TrafficSplitEchoService,
+    Chaos, ChaosConfig, ChaosEchoService,
+    AdaptiveConcurrencyEchoService, AdaptiveConcurrencyConfig,
+    EchoEventBus, EventEmittingEchoService,
+    BoundEndpoint, BoundEndpoints,
+    DependencyRegistry, ModuleDependencies, JsonTranscoding,
+    AuditSink, RecordingEchoService, RegistryBackend,
+    InfoRegistry, enabled_features,
+    DecryptingEchoService, PayloadKeyring,
+};
+use echo_api_grpc::{SignatureVerifier, SigningKey};
+use tracing::{debug, info, warn};
 
 use crate::service_provider::EchoServerServiceProvider;
 
+/// Variant name of the regular implementation when a canary takes part.
+pub const STABLE_VARIANT: &str = "v1";
+
+/// A second implementation taking a share of the echo calls (canary
+/// rollout; see [`echo_api::traffic_split`]).
+#[derive(Debug, Clone)]
+pub struct CanaryConfig {
+    /// Variant name in metrics and the `variant` response metadata.
+    pub name: String,
+    /// Share of the echo calls routed to it, in percent (changeable at
+    /// runtime through the `traffic-split` admin operation).
+    pub percent: u8,
+    /// Its domain settings.
+    pub service: EchoServiceConfig,
+    /// Tags its replies, so the variants are easy to tell apart.
+    pub decoration: Option<ResponseDecoration>,
+}
+
+impl Default for CanaryConfig {
+    fn default() -> Self {
+        Self {
+            name: "v2".to_string(),
+            percent: 10,
+            service: EchoServiceConfig::default(),
+            decoration: None,
+        }
+    }
+}
+
 /// Configuration for Echo server module.
 pub struct EchoServerModuleConfig {
     pub module_id: ModuleID,
     pub grpc_port: u16,
+    /// Domain service settings (dedup window, payload limits).
+    pub service: EchoServiceConfig,
+    /// Concurrency limits for in-process (Direct) callers.
+    pub direct_limits: DirectConcurrencyLimits,
+    /// Run CPU-bound services on a dedicated pool for in-process callers.
+    pub direct_isolation: DirectIsolationConfig,
+    /// What to do when the service or module panics.
+    pub panic_policy: PanicPolicy,
+    /// Admit calls through weighted priority lanes (disabled if `None`).
+    ///
+    /// Applies to Direct and gRPC callers alike.
+    pub priority_lanes: Option<PriorityLanesConfig>,
+    /// Count bytes echoed per caller, and enforce a byte budget if it has
+    /// a limit (no accounting if `None`).
+    pub byte_quota: Option<ByteQuotaConfig>,
+    /// Admit few concurrent calls after each start, ramping up to the
+    /// maximum (disabled if `None`).
+    pub slow_start: Option<SlowStartConfig>,
+    /// Reject calls beyond a latency-driven concurrency limit (disabled if `None`).
+    pub adaptive_concurrency: Option<AdaptiveConcurrencyConfig>,
+    /// Where `echo_with_session` keeps session state (in memory if `None`).
+    pub session_store: Option<Arc<dyn SessionStore>>,
+    /// Which gRPC servers also accept JSON-encoded requests.
+    pub json_transcoding: JsonTranscoding,
+    /// Browser origins allowed to call the JSON endpoints (`*`: any; no
+    /// CORS headers if empty).
+    pub cors_origins: Vec<String>,
+    /// Record every call with its response here (recording off if `None`).
+    pub audit_sink: Option<Arc<dyn AuditSink>>,
+    /// Advertise the gRPC endpoints over mDNS (LAN discovery without a registry).
+    pub mdns_advertise: bool,
+    /// Publish to this registry ourselves (Consul, etcd - see
+    /// [`echo_api::module_registry_backend`]); `None` leaves it to the framework.
+    pub registry_backend: Option<Arc<dyn RegistryBackend>>,
+    /// Host published with the bound ports (what clients dial).
+    pub advertise_host: String,
+    /// Echo through the Direct path and the bound gRPC ports before
+    /// reporting Ready (self-test off if `None`).
+    pub self_test: Option<SelfTestConfig>,
+    /// Prefix/suffix and metadata attached to every response (off if `None`).
+    pub response_decoration: Option<ResponseDecoration>,
+    /// Accept `schedule_echo` jobs (scheduling fails if `None`).
+    pub scheduler: Option<SchedulerConfig>,
+    /// Record every call here; events are then published through the
+    /// store's outbox (history off if `None`).
+    pub history_store: Option<Arc<dyn HistoryStore>>,
+    /// Prune the history by age and/or size in the background (kept
+    /// until the store's own limit if `None`).
+    pub history_retention: Option<RetentionConfig>,
+    /// Generates request, session and job IDs (random UUIDs if `None`;
+    /// see [`crate::ids::IdStrategy`]).
+    pub id_generator: Option<Arc<dyn IdGenerator>>,
+    /// Open messages sealed with one of these keys (one per client
+    /// module) and seal their replies; plain messages pass through
+    /// (see [`echo_api::encryption`]).
+    pub payload_keys: Option<Arc<PayloadKeyring>>,
+    /// Accept only requests signed with a caller's key, each once (see
+    /// [`echo_api_grpc::signing`]; unsigned requests accepted if `None`).
+    pub request_signing: Option<SignatureVerifier>,
+    /// How long the `enter-maintenance` admin operation waits for calls
+    /// in flight to finish before answering.
+    pub maintenance_drain_timeout: Duration,
+    /// Route a share of the echo calls to a second implementation (all
+    /// calls go to one implementation if `None`).
+    pub canary: Option<CanaryConfig>,
+    /// Inject delays, errors and connection drops (changeable at runtime
+    /// through the `chaos` admin operation; no faults if `None`).
+    pub chaos: Option<ChaosConfig>,
+    /// Serve Direct-equivalent calls over stdin/stdout, for a parent
+    /// supervising this process with a pipe (see [`echo_api::pipe`]);
+    /// logs must then go to stderr.
+    pub stdio_pipe: bool,
+    /// Cancelled on Ctrl-C (see [`echo_api::cancel_on_ctrl_c`]): aborts a
+    /// `start()` still migrating, self-testing or publishing.
+    pub cancellation: CancellationToken,
 }
 
 impl Default for EchoServerModuleConfig {
     fn default() -> Self {
         Self {
-            module_id: ModuleID::from("echo"),  // Match Golang: "echo" not "echo-server"!
+            module_id: echo_module_id(),  // Match Golang: "echo" not "echo-server"!
             grpc_port: 0,
+            service: EchoServiceConfig::default(),
+            direct_limits: DirectConcurrencyLimits::default(),
+            direct_isolation: DirectIsolationConfig::default(),
+            panic_policy: PanicPolicy::default(),
+            priority_lanes: None,
+            byte_quota: None,
+            slow_start: None,
+            adaptive_concurrency: None,
+            session_store: None,
+            json_transcoding: JsonTranscoding::default(),
+            cors_origins: Vec::new(),
+            audit_sink: None,
+            mdns_advertise: false,
+            registry_backend: None,
+            advertise_host: "localhost".to_string(),
+            self_test: None,
+            response_decoration: None,
+            scheduler: None,
+            history_store: None,
+            history_retention: None,
+            id_generator: None,
+            payload_keys: None,
+            request_signing: None,
+            maintenance_drain_timeout: Duration::from_secs(30),
+            canary: None,
+            chaos: None,
+            stdio_pipe: false,
+            cancellation: CancellationToken::new(),
         }
     }
 }
 
+/// Module configuration captured at init time.
+///
+/// The framework factories are plain function pointers, so they can't
+/// capture the config - they read it from here instead.
+static MODULE_CONFIG: OnceLock<EchoServerModuleConfig> = OnceLock::new();
+
+/// Endpoints bound by this module's protocol servers.
+///
+/// Filled by the handlers registrar, read by the module - both are plain
+/// function pointers, so they share it through a static too.
+static BOUND_ENDPOINTS: OnceLock<Arc<BoundEndpoints>> = OnceLock::new();
+
+fn module_endpoints() -> Arc<BoundEndpoints> {
+    BOUND_ENDPOINTS.get_or_init(|| Arc::new(BoundEndpoints::new())).clone()
+}
+
+/// Returns the endpoints the echo server module actually bound.
+///
+/// Use with port 0: tests start the server on an ephemeral port and read
+/// it here (or `wait_for` it on the returned registry).
+pub fn bound_endpoints() -> Arc<BoundEndpoints> {
+    module_endpoints()
+}
+
+/// The Direct-path service, as wrapped by the direct closure enabler.
+///
+/// Set by the enabler, called by the module's self-test.
+static DIRECT_SERVICE: OnceLock<Arc<OnceLock<Arc<dyn EchoService>>>> = OnceLock::new();
+
+/// Key the self-test signs its gRPC probes with, when signing is required.
+///
+/// Generated at init and added to the module's verifier, so no
+/// configured caller's secret is needed (or used) by the server itself.
+static SELF_TEST_SIGNING_KEY: OnceLock<Arc<SigningKey>> = OnceLock::new();
+
+/// Caller name of [`SELF_TEST_SIGNING_KEY`].
+const SELF_TEST_CALLER: &str = "_self-test";
+
+fn direct_service() -> Arc<OnceLock<Arc<dyn EchoService>>> {
+    DIRECT_SERVICE.get_or_init(|| Arc::new(OnceLock::new())).clone()
+}
+
+/// Features reported by `get_info`: echo-api's Cargo features plus the
+/// optional subsystems enabled in the module config.
+fn server_features() -> Vec<String> {
+    let mut features: Vec<String> = enabled_features().into_iter().map(String::from).collect();
+    if let Some(config) = MODULE_CONFIG.get() {
+        let subsystems = [
+            ("priority-lanes", config.priority_lanes.is_some()),
+            ("byte-quota", config.byte_quota.as_ref().is_some_and(|quota| quota.limit_bytes.is_some())),
+            ("slow-start", config.slow_start.is_some()),
+            ("adaptive-concurrency", config.adaptive_concurrency.is_some()),
+            ("json-transcoding", config.json_transcoding != JsonTranscoding::Disabled),
+            ("recording", config.audit_sink.is_some()),
+            ("mdns-advertise", config.mdns_advertise),
+            ("self-test", config.self_test.is_some()),
+            ("response-decoration", config.response_decoration.is_some()),
+            ("scheduler", config.scheduler.is_some()),
+            ("history", config.history_store.is_some()),
+            ("history-retention", config.history_store.is_some() && config.history_retention.is_some()),
+            ("payload-encryption", config.payload_keys.is_some()),
+            ("request-signing", config.request_signing.is_some()),
+            ("canary", config.canary.is_some()),
+            ("chaos", config.chaos.is_some()),
+        ];
+        features.extend(subsystems.iter().filter(|(_, enabled)| *enabled).map(|(name, _)| name.to_string()));
+    }
+    features
+}
+
 /// Factory function for creating the service provider.
 ///
 /// This is a **function pointer** (not a closure) to match the framework API.
 ///
 /// Note: Server modules receive protocol_servers from the framework at module creation time,
-/// not at service provider creation time. So we create an empty service provider here.
+/// not at service provider creation time. So the service provider only
+/// carries what the module's services share: the ID generator.
 fn create_service_provider(
     _service_connector: Arc<dyn ServiceConnector>,
 ) -> ServiceProviderHandle {
     debug!("[EchoServerModule] Creating service provider");
     
+    let service_provider = match MODULE_CONFIG.get().and_then(|c| c.id_generator.clone()) {
+        Some(ids) => EchoServerServiceProvider::new(ids),
+        None => EchoServerServiceProvider::default(),
+    };
+    
     // For a server module, we don't provide service gateways
     // (servers provide handlers, not gateways)
     ServiceProviderHandle {
-        service_provider: Box::new(EchoServerServiceProvider {}),
+        service_provider: Box::new(service_provider),
         service_gateways_map: HashMap::new(),  // No gateways provided
     }
 }
@@ -68,13 +304,196 @@ fn create_service_provider(
 fn create_module(service_provider: EchoServerServiceProvider) -> (Box<dyn Module>, EchoServiceHandlers) {
     debug!("[EchoServerModule] Creating module");
     
-    // Create module
-    let module = EchoServerModule::new(service_provider);
-
-    // Create service handlers (implementations)
-    let handlers = EchoServiceHandlers {
-        service: Arc::new(EchoServiceImpl::new()),
+    let panic_policy = MODULE_CONFIG.get()
+        .map(|c| c.panic_policy)
+        .unwrap_or_default();
+    
+    let service_config = MODULE_CONFIG.get()
+        .map(|c| c.service.clone())
+        .unwrap_or_default();
+    let session_store = MODULE_CONFIG.get()
+        .and_then(|c| c.session_store.clone())
+        .unwrap_or_else(|| Arc::new(InMemorySessionStore::new()));
+    
+    let ids = service_provider.ids();
+    
+    // Create module (start/stop panics are caught); it sweeps idle sessions
+    let mut module = EchoServerModule::new(service_provider)
+        .with_endpoints(module_endpoints())
+        .with_sessions(session_store.clone(), service_config.sessions.clone());
+    // Its own child of the process token, so the coordinator can cancel just this start
+    let cancellation = MODULE_CONFIG.get().map(|c| c.cancellation.child_token()).unwrap_or_default();
+    module = module.with_cancellation(cancellation.clone());
+    if MODULE_CONFIG.get().is_some_and(|c| c.mdns_advertise) {
+        module = module.with_mdns_advertisement();
+    }
+    if let Some(config) = MODULE_CONFIG.get() {
+        if let Some(backend) = &config.registry_backend {
+            module = module.with_registry_backend(backend.clone(), config.advertise_host.clone());
+        }
+    }
+    let module_id = module.id().to_string();
+    
+    let scheduler = MODULE_CONFIG.get()
+        .and_then(|c| c.scheduler.as_ref())
+        .map(|config| Arc::new(EchoScheduler::with_config(config).with_id_generator(ids.clone())));
+    if let Some(scheduler) = &scheduler {
+        module = module.with_scheduler(scheduler.clone());
+    }
+    
+    // Create service handlers (implementations); a canary shares the
+    // session store, scheduler and history with the stable one
+    let implementation = |config: EchoServiceConfig| {
+        let mut service = EchoServiceImpl::with_config(config)
+            .with_session_store(session_store.clone())
+            .with_info(module_id.clone(), server_features())
+            .with_id_generator(ids.clone());
+        if let Some(scheduler) = &scheduler {
+            service = service.with_scheduler(scheduler.clone());
+        }
+        if let Some(store) = MODULE_CONFIG.get().and_then(|c| c.history_store.clone()) {
+            service = service.with_history(store);
+        }
+        Arc::new(service)
+    };
+    let stable = implementation(service_config);
+    // Answers the admin endpoint's GET /info, bypassing limits and lanes
+    InfoRegistry::global().register(&module_id, stable.clone());
+    let service: Arc<dyn EchoService> = match MODULE_CONFIG.get().and_then(|c| c.canary.clone()) {
+        Some(canary) => {
+            debug!("[EchoServerModule] Canary '{}' enabled: {}% of the echo calls", canary.name, canary.percent);
+            let mut candidate: Arc<dyn EchoService> = implementation(canary.service);
+            if let Some(decoration) = canary.decoration {
+                candidate = Arc::new(DecoratedEchoService::new(candidate, decoration));
+            }
+            let split = TrafficSplit::new(canary.percent).expect("canary share validated at init");
+            module = module.with_traffic_split(split.clone());
+            let stable: Arc<dyn EchoService> = stable;
+            Arc::new(TrafficSplitEchoService::new((STABLE_VARIANT, stable), (canary.name, candidate), split))
+        }
+        None => stable,
+    };
+    // The panic guard is innermost, so it covers both Direct and gRPC calls
+    let service: Arc<dyn EchoService> = Arc::new(PanicGuardEchoService::new(service, module_id, panic_policy));
+    
+    // Publish every processed call (calls shed by the limiters outside aren't),
+    // through the history outbox when there is a history
+    let events = Arc::new(EchoEventBus::default());
+    module = module.with_events(events.clone());
+    let service: Arc<dyn EchoService> = match MODULE_CONFIG.get().and_then(|c| c.history_store.clone()) {
+        Some(store) => {
+            let relay = Arc::new(OutboxRelay::new(store.clone(), events.clone()));
+            module = module.with_history(store.clone(), relay.clone());
+            if let Some(config) = MODULE_CONFIG.get().and_then(|c| c.history_retention.clone()) {
+                module = module.with_history_retention(Arc::new(HistoryRetention::new(store.clone(), config)));
+            }
+            Arc::new(HistoryEchoService::new(service, store, relay).with_id_generator(ids.clone()))
+        }
+        None => Arc::new(EventEmittingEchoService::new(service, events.clone())),
+    };
+    // Scheduled runs are published too, but bypass the limiters and lanes below
+    if let Some(scheduler) = &scheduler {
+        scheduler.set_target(service.clone());
+    }
+    
+    // The adaptive limit measures the service itself, not time spent queued
+    let service = match MODULE_CONFIG.get().and_then(|c| c.adaptive_concurrency.as_ref()) {
+        Some(config) => {
+            debug!("[EchoServerModule] Adaptive concurrency enabled: {:?}", config.controller);
+            Arc::new(AdaptiveConcurrencyEchoService::new(service, config)) as Arc<dyn EchoService>
+        }
+        None => service,
     };
+    
+    // Priority lanes wrap everything, so queued calls don't hold resources
+    let service = match MODULE_CONFIG.get().and_then(|c| c.priority_lanes.clone()) {
+        Some(lanes) => {
+            debug!("[EchoServerModule] Priority lanes enabled: max_concurrent={}, weights={:?}",
+                lanes.max_concurrent, lanes.weights);
+            Arc::new(PriorityEchoService::new(service, lanes)) as Arc<dyn EchoService>
+        }
+        None => service,
+    };
+    
+    // Over-quota calls are rejected before they queue in a lane
+    let service = match MODULE_CONFIG.get().and_then(|c| c.byte_quota.clone()) {
+        Some(quota) => {
+            debug!("[EchoServerModule] Byte accounting enabled: limit={:?} per {:?}",
+                quota.limit_bytes, quota.window);
+            Arc::new(ByteQuotaEchoService::new(service, quota)) as Arc<dyn EchoService>
+        }
+        None => service,
+    };
+    
+    // The reconnect burst after a start is shed before it queues in a lane
+    let service = match MODULE_CONFIG.get().and_then(|c| c.slow_start.clone()) {
+        Some(config) => {
+            debug!("[EchoServerModule] Slow start enabled: {} to {} concurrent calls over {:?}",
+                config.initial, config.max_concurrent, config.ramp);
+            let slow_start = SlowStart::new(config);
+            module = module.with_slow_start(slow_start.clone());
+            Arc::new(SlowStartEchoService::new(service, slow_start)) as Arc<dyn EchoService>
+        }
+        None => service,
+    };
+    
+    // In maintenance mode calls are refused before any limiter sees them
+    let maintenance = MaintenanceMode::new();
+    let drain_timeout = MODULE_CONFIG.get()
+        .map(|c| c.maintenance_drain_timeout)
+        .unwrap_or(Duration::from_secs(30));
+    module = module.with_maintenance_mode(maintenance.clone(), drain_timeout);
+    let service: Arc<dyn EchoService> = Arc::new(MaintenanceModeEchoService::new(service, maintenance));
+    
+    // Injected faults hit every call that got past maintenance, like a flaky network would
+    let service = match MODULE_CONFIG.get().and_then(|c| c.chaos) {
+        Some(config) => {
+            let chaos = Chaos::new(config).expect("chaos config validated at init");
+            module = module.with_chaos(chaos.clone());
+            Arc::new(ChaosEchoService::new(service, chaos)) as Arc<dyn EchoService>
+        }
+        None => service,
+    };
+    
+    // Shed calls carry no banner; the capture shows the decorated reply
+    let service = match MODULE_CONFIG.get().and_then(|c| c.response_decoration.clone()) {
+        Some(decoration) => {
+            Arc::new(DecoratedEchoService::new(service, decoration).with_id_generator(ids)) as Arc<dyn EchoService>
+        }
+        None => service,
+    };
+    
+    // Recording is outermost: the capture shows what callers got, shed calls included
+    let service = match MODULE_CONFIG.get().and_then(|c| c.audit_sink.clone()) {
+        Some(sink) => Arc::new(RecordingEchoService::new(service, sink)) as Arc<dyn EchoService>,
+        None => service,
+    };
+    
+    // Sealed messages are opened before anything else sees them, so the
+    // capture, history and limits all work on plain text
+    let service = match MODULE_CONFIG.get().and_then(|c| c.payload_keys.clone()) {
+        Some(keys) => {
+            debug!("[EchoServerModule] Payload encryption enabled: {} keys", keys.len());
+            Arc::new(DecryptingEchoService::new(service, keys)) as Arc<dyn EchoService>
+        }
+        None => service,
+    };
+    let handlers = EchoServiceHandlers::new(service).with_events(events);
+    if MODULE_CONFIG.get().is_some_and(|c| c.stdio_pipe) {
+        module = module.with_stdio_pipe(handlers.service.clone());
+    }
+
+    if let Some(config) = MODULE_CONFIG.get().and_then(|c| c.self_test.clone()) {
+        let targets = SelfTestTargets {
+            handler: handlers.service.clone(),
+            direct: direct_service(),
+            signing_key: SELF_TEST_SIGNING_KEY.get().cloned(),
+        };
+        module = module.with_self_test(targets, config);
+    }
+    let module = PanicGuardModule::new(Box::new(module), panic_policy);
+    // Started together with the other modules, dependencies first
+    let module = StartupCoordinator::global().coordinate(Box::new(module), cancellation);
 
     (Box::new(module), handlers)
 }
@@ -86,10 +505,69 @@ fn echo_handlers_registrar(
     options: HandlersRegistrarOptions<EchoServiceHandlers>,
 ) -> Result<ProtocolToServicesMap> {
     debug!("[EchoServerModule] Creating handlers registrar with {} servers", options.protocol_servers.len());
-    let registrar = new_echo_handlers_registrar(options.protocol_servers)?;
-    registrar.register_handlers(options.service_handlers)
+    let json_transcoding = MODULE_CONFIG.get()
+        .map(|c| c.json_transcoding.clone())
+        .unwrap_or_default();
+    let cors_origins = MODULE_CONFIG.get()
+        .map(|c| c.cors_origins.clone())
+        .unwrap_or_default();
+    let signature_verifier = MODULE_CONFIG.get().and_then(|c| c.request_signing.clone());
+    if let Some(verifier) = &signature_verifier {
+        debug!("[EchoServerModule] Request signing required: {} callers", verifier.len());
+    }
+    let registrar = new_echo_handlers_registrar(options.protocol_servers)?
+        .with_endpoints(module_endpoints())
+        .with_json_transcoding(json_transcoding)
+        .with_cors_origins(cors_origins)
+        .with_signature_verifier(signature_verifier);
+    let services = registrar.register_handlers(options.service_handlers)?;
+    
+    // Mirror into the process-wide registry (admin endpoint, other modules)
+    for endpoint in module_endpoints().list() {
+        BoundEndpoints::global().record(endpoint);
+    }
+    Ok(services)
+}
+
+/// Function for enabling direct closure.
+///
+/// Applies the configured Direct-path thread-pool isolation and
+/// concurrency limits before handing the handlers to the generic echo
+/// enabler. The gRPC path registers the unwrapped handlers, so it's
+/// unaffected.
+fn direct_closure_enabler(
+    mut options: DirectClosureEnablerOptions<Arc<dyn EchoServiceGateways>, EchoServiceHandlers>,
+) {
+    if let Some(config) = MODULE_CONFIG.get() {
+        let handlers = options.service_handlers;
+        let handlers = match isolate_direct_handlers(handlers.clone(), &config.direct_isolation) {
+            Ok(isolated) => isolated,
+            Err(e) => {
+                warn!("[EchoServerModule] Direct isolation disabled: {}", e);
+                handlers
+            }
+        };
+        // Limit outermost, so calls queued for the pool count against the limit
+        options.service_handlers = limit_direct_handlers(handlers, &config.direct_limits);
+    }
+    let _ = direct_service().set(options.service_handlers.service.clone());
+    echo_direct_closure_enabler(options);
 }
 
+/// What the server declares: every echo service, consuming none.
+fn module_dependencies() -> ModuleDependencies {
+    EchoServiceId::ALL
+        .into_iter()
+        .fold(ModuleDependencies::default(), |dependencies, service| dependencies.provides(service.as_str()))
+}
+
+/// The descriptor's handlers registrar (the wiring test reads it too).
+const HANDLERS_REGISTRAR: Option<HandlersRegistrarFn<EchoServiceHandlers>> = Some(echo_handlers_registrar);
+
+/// The descriptor's direct closure enabler (with limits).
+const DIRECT_CLOSURE_ENABLER: Option<DirectClosureEnablerFn<Arc<dyn EchoServiceGateways>, EchoServiceHandlers>> =
+    Some(direct_closure_enabler);
+
 static INIT: Once = Once::new();
 
 /// Initializes the Echo server module.
@@ -112,8 +590,25 @@ static INIT: Once = Once::new();
 ///     })
 /// }
 /// ```
-pub fn init_echo_server_module(config: EchoServerModuleConfig) -> Result<()> {
+pub fn init_echo_server_module(mut config: EchoServerModuleConfig) -> Result<()> {
+    if let Some(canary) = &config.canary {
+        TrafficSplit::new(canary.percent)?;
+    }
+    if let Some(chaos) = &config.chaos {
+        chaos.validate()?;
+    }
+    config.direct_limits.validate()?;
     INIT.call_once(|| {
+        // The self-test's gRPC probes must pass the signature check too
+        if config.self_test.is_some() {
+            if let Some(verifier) = config.request_signing.take() {
+                let secret = [uuid::Uuid::new_v4().into_bytes(), uuid::Uuid::new_v4().into_bytes()].concat();
+                let key = SigningKey::new(SELF_TEST_CALLER, secret).expect("self-test key is valid");
+                config.request_signing = Some(verifier.with_key(key.clone()));
+                let _ = SELF_TEST_SIGNING_KEY.set(Arc::new(key));
+            }
+        }
+        
         info!("[EchoServerModule] Initializing with config: module_id={}, grpc_port={}", 
             config.module_id, config.grpc_port);
         
@@ -126,11 +621,13 @@ pub fn init_echo_server_module(config: EchoServerModuleConfig) -> Result<()> {
         >(
             create_service_provider,
             create_module,
-            Some(echo_handlers_registrar),  // Server provides handlers!
-            Some(echo_direct_closure_enabler), // Enable direct closure!
+            HANDLERS_REGISTRAR,      // Server provides handlers!
+            DIRECT_CLOSURE_ENABLER,  // Enable direct closure (with limits)!
         );
         
         register_module(config.module_id.clone(), descriptor);
+        DependencyRegistry::global().declare(&config.module_id, module_dependencies());
+        let _ = MODULE_CONFIG.set(config);
         
         info!("[EchoServerModule] ✅ Module registered successfully");
     });
@@ -138,3 +635,36 @@ pub fn init_echo_server_module(config: EchoServerModuleConfig) -> Result<()> {
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echo_api::{
+        echo_gateway_service_ids, echo_handler_service_ids, gateway_capabilities, GatewayOptions, WiringDescriptor,
+        ECHO_HANDLER_PROTOCOLS,
+    };
+
+    #[tokio::test]
+    async fn test_wiring_is_consistent() {
+        let config = EchoServerModuleConfig::default();
+        // What init registers: the handlers of create_module, handed to
+        // the descriptor's registrar and direct closure enabler
+        let (_module, handlers) = create_module(EchoServerServiceProvider::default());
+        let mut descriptor = WiringDescriptor::new(config.module_id.to_string(), module_dependencies())
+            .with_direct_closure(DIRECT_CLOSURE_ENABLER.is_some());
+        if HANDLERS_REGISTRAR.is_some() {
+            descriptor = descriptor
+                .with_handler_services(echo_handler_service_ids(&handlers))
+                .with_handler_protocols(ECHO_HANDLER_PROTOCOLS);
+        }
+
+        // What consumers reach it through: the echo gateways, with the
+        // handlers handed over
+        let capabilities =
+            gateway_capabilities(echo_module_id(), &GatewayOptions::default(), Some(handlers.service), handlers.events);
+        descriptor
+            .with_gateway_services(echo_gateway_service_ids())
+            .with_gateway_protocols(capabilities.supported())
+            .assert_consistent();
+    }
+}
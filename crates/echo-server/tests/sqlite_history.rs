@@ -0,0 +1,198 @@
+//! SQLite history store against a real database file.
+//!
+//! ```text
+//! cargo test -p echo-server --features sqlite --test sqlite_history
+//! ```
+
+#![cfg(feature = "sqlite")]
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use echo_contract::{is_storage_error, EchoEvent, EchoMethod, HistoryQuery};
+use echo_server::sqlite_history::MIGRATIONS;
+use echo_server::{HistoryEntry, HistoryStore, SqliteHistoryStore};
+
+/// A database path unique to this test run, removed on drop.
+struct TempDatabase(PathBuf);
+
+impl TempDatabase {
+    fn new() -> Self {
+        Self(std::env::temp_dir().join(format!("echo-history-{}.db", uuid::Uuid::new_v4())))
+    }
+}
+
+impl Drop for TempDatabase {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.0.display(), suffix));
+        }
+    }
+}
+
+fn call(method: EchoMethod, message: &str, success: bool) -> (HistoryEntry, EchoEvent) {
+    // Whole milliseconds: what the database keeps
+    let at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+    let entry = HistoryEntry {
+        id: 0,
+        record_id: String::new(),
+        method,
+        message: message.to_string(),
+        request_bytes: message.len() as u64,
+        success,
+        at,
+    };
+    let event = EchoEvent { method, request_bytes: message.len() as u64, success, at };
+    (entry, event)
+}
+
+#[test]
+fn test_history_and_outbox_survive_reopening() {
+    let database = TempDatabase::new();
+
+    let store = SqliteHistoryStore::open(&database.0).unwrap();
+    assert_eq!(store.schema_version().unwrap(), 0);
+    store.migrate().unwrap();
+    assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+
+    let (entry, event) = call(EchoMethod::Echo, "hello", true);
+    assert_eq!(store.append(entry, event).unwrap(), 1);
+    let (entry, event) = call(EchoMethod::EchoReliable, "again", false);
+    assert_eq!(store.append(entry, event).unwrap(), 2);
+    store.mark_published(&[1]).unwrap();
+    drop(store);
+
+    // Migrating an up-to-date database changes nothing
+    let store = SqliteHistoryStore::open(&database.0).unwrap();
+    store.migrate().unwrap();
+
+    let recent = store.query(&HistoryQuery::new()).unwrap().entries;
+    assert_eq!(recent.len(), 2);
+    assert_eq!(recent[0].id, 2);
+    assert_eq!(recent[0].method, EchoMethod::EchoReliable);
+    assert!(!recent[0].success);
+    assert_eq!(recent[1].message, "hello");
+    assert_eq!(recent[1].at, call(EchoMethod::Echo, "", true).0.at);
+
+    let pending = store.pending_events(10).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, 2);
+    assert_eq!(pending[0].event, call(EchoMethod::EchoReliable, "again", false).1);
+    assert!(pending[0].event.at < SystemTime::now());
+}
+
+#[test]
+fn test_query_filters_and_pages() {
+    let database = TempDatabase::new();
+    let store = SqliteHistoryStore::open(&database.0).unwrap();
+    store.migrate().unwrap();
+    let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    for (i, message) in ["alpha", "beta", "alphabet", "gamma", "Alpha"].iter().enumerate() {
+        let (mut entry, event) = call(EchoMethod::Echo, message, true);
+        entry.at = start + Duration::from_secs(i as u64);
+        store.append(entry, event).unwrap();
+    }
+
+    // Case-sensitive substring, one per page
+    let query = HistoryQuery::new().with_contains("alpha").with_limit(1);
+    let first = store.query(&query).unwrap();
+    assert_eq!(first.entries[0].message, "alphabet");
+    let second = store.query(&query.clone().with_cursor(first.next_cursor.unwrap())).unwrap();
+    assert_eq!(second.entries[0].message, "alpha");
+    assert_eq!(second.next_cursor, None);
+
+    // since inclusive, until exclusive
+    let query = HistoryQuery::new()
+        .with_time_range(Some(start + Duration::from_secs(1)), Some(start + Duration::from_secs(3)));
+    let page = store.query(&query).unwrap();
+    assert_eq!(page.entries.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(), vec!["alphabet", "beta"]);
+
+    assert!(store.query(&HistoryQuery::new().with_cursor("nope")).is_err());
+}
+
+#[test]
+fn test_newer_schema_is_refused() {
+    let database = TempDatabase::new();
+    let store = SqliteHistoryStore::open(&database.0).unwrap();
+    store.migrate().unwrap();
+    drop(store);
+
+    let conn = rusqlite::Connection::open(&database.0).unwrap();
+    conn.pragma_update(None, "user_version", (MIGRATIONS.len() + 1) as i64).unwrap();
+    drop(conn);
+
+    let store = SqliteHistoryStore::open(&database.0).unwrap();
+    let error = store.migrate().unwrap_err();
+    assert!(is_storage_error(&error), "{}", error);
+    assert!(error.to_string().contains("newer than this server"), "{}", error);
+}
+
+#[test]
+fn test_prune_and_compact() {
+    let database = TempDatabase::new();
+    let store = SqliteHistoryStore::open(&database.0).unwrap();
+    store.migrate().unwrap();
+    let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    let message = "x".repeat(1000);
+    for i in 0..200 {
+        let (mut entry, event) = call(EchoMethod::Echo, &message, true);
+        entry.at = start + Duration::from_secs(i);
+        store.append(entry, event).unwrap();
+    }
+
+    assert_eq!(store.prune(Some(start + Duration::from_secs(50)), None).unwrap(), 50);
+    assert_eq!(store.prune(None, Some(10)).unwrap(), 140);
+    assert_eq!(store.prune(None, Some(10)).unwrap(), 0);
+    store.compact().unwrap();
+
+    let page = store.query(&HistoryQuery::new().with_limit(100)).unwrap();
+    assert_eq!(page.entries.iter().map(|e| e.id).collect::<Vec<_>>(), (191..=200).rev().collect::<Vec<_>>());
+    // The outbox keeps every unpublished event
+    assert_eq!(store.pending_events(1000).unwrap().len(), 200);
+
+    // Pruned IDs are never reused, so old cursors stay meaningful
+    store.prune(None, Some(0)).unwrap();
+    let (entry, event) = call(EchoMethod::Echo, "after", true);
+    assert_eq!(store.append(entry, event).unwrap(), 201);
+}
+
+#[test]
+fn test_import_skips_duplicates() {
+    let database = TempDatabase::new();
+    let store = SqliteHistoryStore::open(&database.0).unwrap();
+    store.migrate().unwrap();
+    let (entry, event) = call(EchoMethod::Echo, "hello", true);
+    store.append(entry, event).unwrap();
+
+    // "hello" is already there, "bye" comes twice
+    let imported = vec![
+        call(EchoMethod::Echo, "hello", true).0,
+        call(EchoMethod::Echo, "bye", true).0,
+        call(EchoMethod::Echo, "bye", true).0,
+        call(EchoMethod::Echo, "bye", false).0,
+    ];
+    let report = store.import(imported).unwrap();
+    assert_eq!((report.imported, report.duplicates), (2, 2));
+
+    let entries = store.query(&HistoryQuery::new()).unwrap().entries;
+    assert_eq!(entries.iter().map(|e| (e.id, e.success)).collect::<Vec<_>>(), vec![(3, false), (2, true), (1, true)]);
+    // Imports queue no events
+    assert_eq!(store.pending_events(10).unwrap().len(), 1);
+}
+
+#[test]
+fn test_import_keeps_distinct_calls_of_the_same_millisecond() {
+    let database = TempDatabase::new();
+    let store = SqliteHistoryStore::open(&database.0).unwrap();
+    store.migrate().unwrap();
+    let (entry, event) = call(EchoMethod::Echo, "tick", true);
+    store.append(entry, event).unwrap();
+
+    // Three identical calls in one millisecond; the store has one of them
+    let imported: Vec<HistoryEntry> = (1..=3)
+        .map(|id| HistoryEntry { id, ..call(EchoMethod::Echo, "tick", true).0 })
+        .collect();
+    let report = store.import(imported.clone()).unwrap();
+    assert_eq!((report.imported, report.duplicates), (2, 1));
+    let report = store.import(imported).unwrap();
+    assert_eq!((report.imported, report.duplicates), (0, 3));
+}
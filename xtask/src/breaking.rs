@@ -0,0 +1,228 @@
+//! Breaking-change detection between two versions of the proto.
+//!
+//! Covers buf's `WIRE_JSON` category: anything that breaks an old client
+//! talking to a new server (or the reverse), on the binary wire or via
+//! JSON field names, plus removed/retyped rpcs.
+//!
+//! ```text
+//! removed message / enum / service / rpc
+//! removed field or enum value whose number isn't reserved
+//! field renamed, retyped or relabeled (repeated ↔ singular)
+//! rpc request/response type or streaming changed
+//! ```
+//!
+//! Adding things is never breaking.
+
+use std::collections::BTreeMap;
+use prost_types::{DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto};
+
+/// Returns every breaking change from `old` to `new`.
+pub fn breaking_changes(old: &FileDescriptorProto, new: &FileDescriptorProto) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if old.package() != new.package() {
+        problems.push(format!("package changed from '{}' to '{}'", old.package(), new.package()));
+    }
+
+    let new_messages = messages(&new.message_type, "");
+    for (name, old_message) in messages(&old.message_type, "") {
+        match new_messages.get(&name) {
+            Some(new_message) => compare_fields(&name, old_message, new_message, &mut problems),
+            None => problems.push(format!("message {} removed", name)),
+        }
+    }
+
+    let new_enums = enums(&new.enum_type, &new.message_type);
+    for (name, old_enum) in enums(&old.enum_type, &old.message_type) {
+        match new_enums.get(&name) {
+            Some(new_enum) => compare_values(&name, old_enum, new_enum, &mut problems),
+            None => problems.push(format!("enum {} removed", name)),
+        }
+    }
+
+    for old_service in &old.service {
+        let Some(new_service) = new.service.iter().find(|s| s.name == old_service.name) else {
+            problems.push(format!("service {} removed", old_service.name()));
+            continue;
+        };
+        for old_method in &old_service.method {
+            let rpc = format!("{}.{}", old_service.name(), old_method.name());
+            let Some(new_method) = new_service.method.iter().find(|m| m.name == old_method.name) else {
+                problems.push(format!("rpc {} removed", rpc));
+                continue;
+            };
+            if old_method.input_type() != new_method.input_type() {
+                problems.push(format!("rpc {} request changed from {} to {}",
+                    rpc, old_method.input_type(), new_method.input_type()));
+            }
+            if old_method.output_type() != new_method.output_type() {
+                problems.push(format!("rpc {} response changed from {} to {}",
+                    rpc, old_method.output_type(), new_method.output_type()));
+            }
+            if old_method.client_streaming() != new_method.client_streaming() {
+                problems.push(format!("rpc {} client streaming changed", rpc));
+            }
+            if old_method.server_streaming() != new_method.server_streaming() {
+                problems.push(format!("rpc {} server streaming changed", rpc));
+            }
+        }
+    }
+
+    problems
+}
+
+/// All messages, nested ones included, by dotted name.
+fn messages<'a>(types: &'a [DescriptorProto], parent: &str) -> BTreeMap<String, &'a DescriptorProto> {
+    let mut all = BTreeMap::new();
+    for message in types {
+        let name = format!("{}{}", parent, message.name());
+        all.extend(messages(&message.nested_type, &format!("{}.", name)));
+        all.insert(name, message);
+    }
+    all
+}
+
+/// All enums, top-level and nested in messages, by dotted name.
+fn enums<'a>(
+    top_level: &'a [EnumDescriptorProto],
+    types: &'a [DescriptorProto],
+) -> BTreeMap<String, &'a EnumDescriptorProto> {
+    let mut all: BTreeMap<_, _> = top_level.iter().map(|e| (e.name().to_string(), e)).collect();
+    for (name, message) in messages(types, "") {
+        for enumeration in &message.enum_type {
+            all.insert(format!("{}.{}", name, enumeration.name()), enumeration);
+        }
+    }
+    all
+}
+
+fn compare_fields(message: &str, old: &DescriptorProto, new: &DescriptorProto, problems: &mut Vec<String>) {
+    for old_field in &old.field {
+        let number = old_field.number();
+        let Some(new_field) = new.field.iter().find(|f| f.number() == number) else {
+            let reserved = new.reserved_range
+                .iter()
+                .any(|range| range.start() <= number && number < range.end());
+            if !reserved {
+                problems.push(format!("field {}.{} (= {}) removed without reserving its number",
+                    message, old_field.name(), number));
+            }
+            continue;
+        };
+        if old_field.name() != new_field.name() {
+            problems.push(format!("field {} (= {}) renamed from {} to {} (breaks JSON)",
+                message, number, old_field.name(), new_field.name()));
+        }
+        if type_of(old_field) != type_of(new_field) {
+            problems.push(format!("field {}.{} (= {}) changed type from {} to {}",
+                message, old_field.name(), number, type_of(old_field), type_of(new_field)));
+        }
+        if old_field.label() != new_field.label() {
+            problems.push(format!("field {}.{} (= {}) changed label from {} to {}",
+                message, old_field.name(), number,
+                old_field.label().as_str_name(), new_field.label().as_str_name()));
+        }
+    }
+}
+
+fn compare_values(enumeration: &str, old: &EnumDescriptorProto, new: &EnumDescriptorProto, problems: &mut Vec<String>) {
+    for old_value in &old.value {
+        let number = old_value.number();
+        match new.value.iter().find(|v| v.number() == number) {
+            Some(new_value) if new_value.name() != old_value.name() => {
+                problems.push(format!("enum value {} (= {}) renamed from {} to {} (breaks JSON)",
+                    enumeration, number, old_value.name(), new_value.name()));
+            }
+            Some(_) => {}
+            None => {
+                // Enum reserved ranges are inclusive, unlike message ones
+                let reserved = new.reserved_range
+                    .iter()
+                    .any(|range| range.start() <= number && number <= range.end());
+                if !reserved {
+                    problems.push(format!("enum value {}.{} (= {}) removed without reserving its number",
+                        enumeration, old_value.name(), number));
+                }
+            }
+        }
+    }
+}
+
+/// Scalar type name, or the (unresolved) message/enum name.
+fn type_of(field: &FieldDescriptorProto) -> String {
+    match &field.type_name {
+        Some(name) => name.clone(),
+        None => field.r#type().as_str_name().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto;
+
+    const OLD: &str = r#"
+        syntax = "proto3";
+        package proto;
+        service EchoService {
+          rpc Echo(EchoRequest) returns (EchoResponse) {}
+          rpc EchoFile(stream EchoRequest) returns (EchoResponse) {}
+        }
+        message EchoRequest { string message = 1; string key = 2; }
+        message EchoResponse { string message = 1; }
+    "#;
+
+    fn changes(new: &str) -> Vec<String> {
+        breaking_changes(&proto::parse("old.proto", OLD).unwrap(), &proto::parse("new.proto", new).unwrap())
+    }
+
+    #[test]
+    fn test_additions_are_not_breaking() {
+        assert_eq!(changes(r#"
+            syntax = "proto3";
+            package proto;
+            service EchoService {
+              rpc Echo(EchoRequest) returns (EchoResponse) {}
+              rpc EchoFile(stream EchoRequest) returns (EchoResponse) {}
+              rpc EchoBytes(EchoRequest) returns (EchoResponse) {}
+            }
+            message EchoRequest { string message = 1; string key = 2; bytes payload = 3; }
+            message EchoResponse { string message = 1; }
+            message Extra {}
+        "#), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_reserved_removal_is_not_breaking() {
+        assert_eq!(changes(r#"
+            syntax = "proto3";
+            package proto;
+            service EchoService {
+              rpc Echo(EchoRequest) returns (EchoResponse) {}
+              rpc EchoFile(stream EchoRequest) returns (EchoResponse) {}
+            }
+            message EchoRequest { reserved 2; string message = 1; }
+            message EchoResponse { string message = 1; }
+        "#), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_reports_breaking_changes() {
+        let problems = changes(r#"
+            syntax = "proto3";
+            package proto;
+            service EchoService {
+              rpc EchoFile(EchoRequest) returns (EchoResponse) {}
+            }
+            message EchoRequest { bytes message = 1; }
+            message EchoResponse { string text = 1; }
+        "#);
+        assert_eq!(problems, vec![
+            "field EchoRequest.message (= 1) changed type from TYPE_STRING to TYPE_BYTES",
+            "field EchoRequest.key (= 2) removed without reserving its number",
+            "field EchoResponse (= 1) renamed from message to text (breaks JSON)",
+            "rpc EchoService.Echo removed",
+            "rpc EchoService.EchoFile client streaming changed",
+        ]);
+    }
+}
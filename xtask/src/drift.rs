@@ -0,0 +1,133 @@
+//! Drift between the proto services and the Rust contract traits.
+//!
+//! The compiler already catches drift between the proto and the gRPC
+//! adapters (they implement the generated traits). What it can't catch is
+//! an rpc with no counterpart on the protocol-agnostic trait - or a trait
+//! method that only Direct callers can reach. This matches them by name:
+//!
+//! ```text
+//! rpc EchoService.EchoWithSession  ↔  EchoService::echo_with_session
+//! rpc EchoEvents.Subscribe         ↔  EchoEvents::subscribe
+//! ```
+
+use std::path::Path;
+use prost_types::FileDescriptorProto;
+
+use crate::Result;
+
+/// Proto services and the trait (and its source file) each one maps to.
+const CONTRACTS: &[(&str, &str)] = &[
+    ("EchoService", "crates/echo-contract/src/lib.rs"),
+    ("EchoEvents", "crates/echo-contract/src/events.rs"),
+];
+
+/// Returns every mismatch between the proto services and the traits.
+pub fn drift(root: &Path, file: &FileDescriptorProto) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
+
+    for (service_name, source_path) in CONTRACTS {
+        let Some(service) = file.service.iter().find(|s| s.name() == *service_name) else {
+            problems.push(format!("proto has no service {}", service_name));
+            continue;
+        };
+        let source = std::fs::read_to_string(root.join(source_path))?;
+        let Some(methods) = trait_methods(&source, service_name) else {
+            problems.push(format!("{} has no trait {}", source_path, service_name));
+            continue;
+        };
+
+        let rpcs: Vec<String> = service.method.iter().map(|m| to_snake_case(m.name())).collect();
+        for rpc in service.method.iter().filter(|m| !methods.contains(&to_snake_case(m.name()))) {
+            problems.push(format!("rpc {}.{} has no method {}::{}",
+                service_name, rpc.name(), service_name, to_snake_case(rpc.name())));
+        }
+        for method in methods.iter().filter(|m| !rpcs.contains(m)) {
+            problems.push(format!("trait method {}::{} has no rpc in service {}",
+                service_name, method, service_name));
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Method names declared in `pub trait <name>` in `source`.
+fn trait_methods(source: &str, name: &str) -> Option<Vec<String>> {
+    let start = source.find(&format!("pub trait {}", name))?;
+    let body = &source[start..];
+    let open = body.find('{')?;
+
+    // Cut the trait body at its matching brace (default method bodies nest)
+    let mut depth = 0;
+    let mut end = body.len();
+    for (i, c) in body[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = open + i;
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let methods = body[open..end]
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("//"))
+        .filter_map(|line| {
+            let rest = line.strip_prefix("async fn ").or_else(|| line.strip_prefix("fn "))?;
+            Some(rest.split(|c: char| c == '(' || c == '<').next()?.to_string())
+        })
+        .collect();
+    Some(methods)
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto;
+
+    #[test]
+    fn test_repo_contract_has_no_drift() {
+        let root = proto::workspace_root();
+        let file = proto::load_working_tree(&root).unwrap();
+        assert_eq!(drift(&root, &file).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_trait_methods() {
+        let source = r#"
+            /// Docs mentioning fn not_a_method()
+            pub trait EchoService: Send + Sync {
+                /// Echoes.
+                async fn echo(&self, message: Arc<str>) -> Result<Arc<str>>;
+                fn name(&self) -> &str { "echo" }
+                async fn echo_with_session(&self) -> Result<()>;
+            }
+            fn outside() {}
+        "#;
+        assert_eq!(
+            trait_methods(source, "EchoService").unwrap(),
+            vec!["echo", "name", "echo_with_session"]
+        );
+        assert_eq!(to_snake_case("EchoWithSession"), "echo_with_session");
+    }
+}
@@ -0,0 +1,116 @@
+//! Proto lint: the naming subset of buf's `DEFAULT` rules.
+//!
+//! Deliberately leaves out the request/response naming rules - the
+//! streaming rpcs (`EchoFile`, `Subscribe`) take chunk/event messages
+//! that are named for what they carry.
+
+use prost_types::{DescriptorProto, EnumDescriptorProto, FileDescriptorProto};
+
+/// Returns every lint problem in `file`.
+pub fn lint(file: &FileDescriptorProto) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if file.package.as_deref().unwrap_or_default().is_empty() {
+        problems.push("file has no package".to_string());
+    }
+    if file.syntax.as_deref() != Some("proto3") {
+        problems.push("file must use syntax = \"proto3\"".to_string());
+    }
+
+    for service in &file.service {
+        let service_name = service.name();
+        if !is_pascal_case(service_name) {
+            problems.push(format!("service {} should be PascalCase", service_name));
+        }
+        for method in &service.method {
+            if !is_pascal_case(method.name()) {
+                problems.push(format!("rpc {}.{} should be PascalCase", service_name, method.name()));
+            }
+        }
+    }
+
+    for message in &file.message_type {
+        lint_message(message, "", &mut problems);
+    }
+    for enumeration in &file.enum_type {
+        lint_enum(enumeration, "", &mut problems);
+    }
+
+    problems
+}
+
+fn lint_message(message: &DescriptorProto, parent: &str, problems: &mut Vec<String>) {
+    let name = format!("{}{}", parent, message.name());
+    if !is_pascal_case(message.name()) {
+        problems.push(format!("message {} should be PascalCase", name));
+    }
+    for field in &message.field {
+        if !is_snake_case(field.name(), false) {
+            problems.push(format!("field {}.{} should be lower_snake_case", name, field.name()));
+        }
+    }
+    for nested in &message.nested_type {
+        lint_message(nested, &format!("{}.", name), problems);
+    }
+    for enumeration in &message.enum_type {
+        lint_enum(enumeration, &format!("{}.", name), problems);
+    }
+}
+
+fn lint_enum(enumeration: &EnumDescriptorProto, parent: &str, problems: &mut Vec<String>) {
+    let name = format!("{}{}", parent, enumeration.name());
+    if !is_pascal_case(enumeration.name()) {
+        problems.push(format!("enum {} should be PascalCase", name));
+    }
+    if enumeration.value.first().is_some_and(|value| value.number() != 0) {
+        problems.push(format!("enum {} must start with a zero value", name));
+    }
+    for value in &enumeration.value {
+        if !is_snake_case(value.name(), true) {
+            problems.push(format!("enum value {}.{} should be UPPER_SNAKE_CASE", name, value.name()));
+        }
+    }
+}
+
+fn is_pascal_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_snake_case(name: &str, upper: bool) -> bool {
+    let letter_ok = |c: char| if upper { c.is_ascii_uppercase() } else { c.is_ascii_lowercase() };
+    name.starts_with(letter_ok)
+        && !name.ends_with('_')
+        && !name.contains("__")
+        && name.chars().all(|c| letter_ok(c) || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto;
+
+    #[test]
+    fn test_repo_proto_is_clean() {
+        let file = proto::load_working_tree(&proto::workspace_root()).unwrap();
+        assert_eq!(lint(&file), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_reports_naming_problems() {
+        let file = proto::parse("bad.proto", r#"
+            syntax = "proto3";
+            package p;
+            service echo_service { rpc do_echo(Req) returns (Req) {} }
+            message Req { string Message = 1; }
+            enum Kind { kind_a = 1; }
+        "#).unwrap();
+
+        let problems = lint(&file);
+        assert!(problems.contains(&"service echo_service should be PascalCase".to_string()));
+        assert!(problems.contains(&"rpc echo_service.do_echo should be PascalCase".to_string()));
+        assert!(problems.contains(&"field Req.Message should be lower_snake_case".to_string()));
+        assert!(problems.contains(&"enum Kind must start with a zero value".to_string()));
+        assert!(problems.contains(&"enum value Kind.kind_a should be UPPER_SNAKE_CASE".to_string()));
+    }
+}
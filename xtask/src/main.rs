@@ -0,0 +1,142 @@
+//! Workspace Tasks (`cargo xtask ...`)
+//!
+//! # Architecture
+//!
+//! Checks that keep the proto contract and the Rust contract in step,
+//! runnable locally and in CI with nothing but cargo and git:
+//!
+//! ```text
+//! cargo xtask proto-lint                    naming rules on api/proto/echoservice.proto
+//! cargo xtask proto-breaking [--against R]  wire/API breaks vs. the proto at revision R (default HEAD~1)
+//! cargo xtask proto-drift                   every rpc ↔ a method on the matching Rust trait
+//! cargo xtask proto-gen                     regenerate the tonic code and compile the adapters
+//! cargo xtask proto-check [--against R]     all of the above
+//! ```
+//!
+//! The proto is parsed with `protox-parse` (pure Rust), so only
+//! `proto-gen` needs `protoc` - same as a normal build.
+//!
+//! # Rust Learning Note
+//!
+//! The "xtask" pattern: workspace automation is a plain binary crate,
+//! wired up by an alias in `.cargo/config.toml`. No make/just/shell
+//! scripts, and it runs wherever the workspace builds.
+//!
+//! ## Comparison with Golang
+//!
+//! The Go version would use `buf lint` / `buf breaking` plus a
+//! `//go:generate protoc ...` line; here both live in the workspace.
+
+mod breaking;
+mod drift;
+mod lint;
+mod proto;
+
+use std::path::Path;
+use std::process::{Command, ExitCode};
+use clap::{Parser, Subcommand};
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(about = "Workspace maintenance tasks")]
+struct Args {
+    #[command(subcommand)]
+    task: Task,
+}
+
+#[derive(Subcommand, Debug)]
+enum Task {
+    /// Lint the proto contract
+    ProtoLint,
+    /// Report breaking changes against the proto at an earlier revision
+    ProtoBreaking {
+        /// Git revision to compare against
+        #[arg(long, default_value = "HEAD~1")]
+        against: String,
+    },
+    /// Check that every rpc has a matching Rust trait method and vice versa
+    ProtoDrift,
+    /// Regenerate the tonic code and compile the gRPC adapters against it
+    ProtoGen,
+    /// Run lint, breaking, drift and gen
+    ProtoCheck {
+        /// Git revision to compare against
+        #[arg(long, default_value = "HEAD~1")]
+        against: String,
+    },
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let root = proto::workspace_root();
+
+    let result = match args.task {
+        Task::ProtoLint => proto_lint(&root),
+        Task::ProtoBreaking { against } => proto_breaking(&root, &against),
+        Task::ProtoDrift => proto_drift(&root),
+        Task::ProtoGen => proto_gen(&root),
+        Task::ProtoCheck { against } => proto_lint(&root)
+            .and_then(|()| proto_breaking(&root, &against))
+            .and_then(|()| proto_drift(&root))
+            .and_then(|()| proto_gen(&root)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Turns a list of problems into an error, printing each one.
+fn report(task: &str, problems: Vec<String>) -> Result<()> {
+    if problems.is_empty() {
+        println!("✅ {}: ok", task);
+        return Ok(());
+    }
+    for problem in &problems {
+        eprintln!("  - {}", problem);
+    }
+    Err(format!("{}: {} problem(s)", task, problems.len()).into())
+}
+
+fn proto_lint(root: &Path) -> Result<()> {
+    let file = proto::load_working_tree(root)?;
+    report("proto-lint", lint::lint(&file))
+}
+
+fn proto_breaking(root: &Path, against: &str) -> Result<()> {
+    let Some(old) = proto::load_at_revision(root, against)? else {
+        println!("✅ proto-breaking: {} not present at {}, nothing to compare", proto::PROTO_PATH, against);
+        return Ok(());
+    };
+    let new = proto::load_working_tree(root)?;
+    report(&format!("proto-breaking (against {})", against), breaking::breaking_changes(&old, &new))
+}
+
+fn proto_drift(root: &Path) -> Result<()> {
+    let file = proto::load_working_tree(root)?;
+    report("proto-drift", drift::drift(root, &file)?)
+}
+
+/// Regenerates via the adapter crate's build script.
+///
+/// `tonic-build` emits `rerun-if-changed` for the proto, so a check of
+/// echo-api-grpc regenerates whenever the proto changed, and then compiles
+/// handler.rs/gateway.rs against the new generated traits.
+fn proto_gen(root: &Path) -> Result<()> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .current_dir(root)
+        .args(["check", "--package", "echo-api-grpc", "--all-targets"])
+        .status()?;
+    if !status.success() {
+        return Err("proto-gen: echo-api-grpc doesn't compile against the regenerated code".into());
+    }
+    println!("✅ proto-gen: ok");
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! Loading the proto contract from the working tree or a git revision.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use prost_types::FileDescriptorProto;
+
+use crate::Result;
+
+/// The proto contract, relative to the workspace root.
+pub const PROTO_PATH: &str = "api/proto/echoservice.proto";
+
+/// The workspace root (the parent of this crate).
+pub fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives inside the workspace")
+        .to_path_buf()
+}
+
+/// Parses proto source into a descriptor.
+///
+/// Type names are left unresolved (`EchoRequest`, not `.proto.EchoRequest`),
+/// which is fine for comparing two versions of the same single file.
+pub fn parse(name: &str, source: &str) -> Result<FileDescriptorProto> {
+    protox_parse::parse(name, source).map_err(|e| format!("{}: {}", name, e).into())
+}
+
+/// Loads the proto as it is on disk.
+pub fn load_working_tree(root: &Path) -> Result<FileDescriptorProto> {
+    let source = std::fs::read_to_string(root.join(PROTO_PATH))?;
+    parse(PROTO_PATH, &source)
+}
+
+/// Loads the proto as committed at `revision`, `None` if it didn't exist there.
+pub fn load_at_revision(root: &Path, revision: &str) -> Result<Option<FileDescriptorProto>> {
+    git(root, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", revision)])
+        .map_err(|_| format!("unknown git revision '{}'", revision))?;
+
+    let object = format!("{}:{}", revision, PROTO_PATH);
+    if git(root, &["cat-file", "-e", &object]).is_err() {
+        return Ok(None);
+    }
+    let source = git(root, &["show", &object])?;
+    parse(&object, &source).map(Some)
+}
+
+fn git(root: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").current_dir(root).args(args).output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ).into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}